@@ -1,4 +1,9 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use furnace::colors::TrueColorPalette;
+use furnace::terminal::ansi_parser::AnsiParser;
+use furnace::terminal::coalesce::{coalesce, MAX_BATCH_BYTES};
+use std::collections::VecDeque;
+use std::io::Read;
 
 /// Benchmark terminal output processing throughput
 fn bench_output_processing(c: &mut Criterion) {
@@ -59,10 +64,79 @@ fn bench_memory_allocation(c: &mut Criterion) {
     group.finish();
 }
 
+/// PTY stand-in producing bulk output, as from `cat bigfile`
+struct MockPty {
+    remaining: usize,
+}
+
+impl Read for MockPty {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.remaining);
+        buf[..n].fill(b'x');
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// Benchmark reading and batching bulk shell output with different read buffer sizes
+fn bench_bulk_read(c: &mut Criterion) {
+    const TOTAL: usize = 4 * 1024 * 1024;
+    let mut group = c.benchmark_group("bulk_read");
+    group.throughput(Throughput::Bytes(TOTAL as u64));
+
+    for kb in &[4, 8, 64] {
+        group.bench_with_input(BenchmarkId::from_parameter(kb), kb, |b, &kb| {
+            let mut buf = vec![0u8; kb * 1024];
+            b.iter(|| {
+                let mut pty = MockPty { remaining: TOTAL };
+                let mut chunks = VecDeque::new();
+                loop {
+                    let n = pty.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    chunks.push_back(buf[..n].to_vec());
+                }
+
+                let mut processed = 0;
+                while let Some(first) = chunks.pop_front() {
+                    let batch = coalesce(first, || chunks.pop_front(), MAX_BATCH_BYTES);
+                    processed += black_box(String::from_utf8_lossy(&batch)).len();
+                }
+                processed
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark parsing 1MB of colored output into owned vs borrowed lines
+fn bench_ansi_parse(c: &mut Criterion) {
+    let line = "\x1b[1;32muser@host\x1b[0m:\x1b[34m~/src\x1b[0m$ ls -la \x1b[33mtarget\x1b[0m\r\n";
+    let text = line.repeat(1024 * 1024 / line.len());
+    let palette = TrueColorPalette::default_dark();
+
+    let mut group = c.benchmark_group("ansi_parse_1mb");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("owned", |b| {
+        b.iter(|| AnsiParser::parse_with_hyperlinks(black_box(&text), &palette));
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| AnsiParser::parse_borrowed(black_box(&text), &palette));
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_output_processing,
     bench_scrollback_management,
-    bench_memory_allocation
+    bench_memory_allocation,
+    bench_bulk_read,
+    bench_ansi_parse
 );
 criterion_main!(benches);
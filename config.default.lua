@@ -11,6 +11,15 @@ config = {
         default_shell = nil,
         -- Starting directory (nil = home)
         working_dir = nil,
+        -- Regex matched against the last output line to detect the prompt
+        -- (nil = built-in detection, e.g. lines ending in $, #, %, > or ❯)
+        prompt_regex = nil,
+        -- Close a tab when its shell exits (false keeps it with "[process exited]")
+        close_on_exit = true,
+        -- Quit when the last tab's shell exits (false keeps it open)
+        quit_on_last_exit = true,
+        -- Size in KiB of the buffer shell output is read into (1-1024)
+        read_buffer_kb = 8,
         -- Extra environment variables
         env = {
             -- MY_VAR = "value",
@@ -22,9 +31,18 @@ config = {
         enable_tabs = false,
         enable_split_pane = false,
         font_size = 12,
+        font_family = "JetBrains Mono", -- GPU renderer font; falls back to a system monospace font if missing
+        fallback_fonts = {}, -- families or font file paths for glyphs the primary font lacks, e.g. { "Noto Sans Symbols 2" }
         cursor_style = "block", -- "block" | "underline" | "bar"
         scrollback_lines = 10000,
+        scrollback_to_disk = false, -- keep older scrollback in a temp file instead of dropping it
         hardware_acceleration = true, -- uses GPU if built with `--features gpu`, else CPU fallback
+        max_fps = 170, -- frame rate cap; drops lower on its own while output floods in
+        line_wrap = "wrap", -- "wrap" (indented continuation rows) | "truncate" (cut with …)
+        confirm_multiline_paste = true, -- preview pastes containing newlines before sending them
+        bracketed_paste = true, -- mark pastes for programs that ask for it (ESC[?2004h)
+        sanitize_output = true, -- drop unsupported OSC, DCS/APC and title-report sequences from output
+        allow_osc52 = false, -- let programs such as tmux and vim set the clipboard (OSC 52)
     },
 
     theme = {
@@ -76,6 +94,28 @@ config = {
         paste = "Ctrl+Shift+V",
         search = "Ctrl+F",
         clear = "Ctrl+L",
+        save_session = "Ctrl+S",
+        load_session = "Ctrl+Shift+L",
+        next_theme = "Ctrl+]",
+        prev_theme = "Ctrl+[",
+        toggle_resource_monitor = "Ctrl+R",
+        toggle_autocomplete = "Alt+Tab",
+        show_keybindings = "Ctrl+Shift+K",
+        command_palette = "Ctrl+Shift+P",
+        interrupt = "Ctrl+Shift+X",
+        restart_shell = "Ctrl+Shift+R",
+        prev_command = "Ctrl+Shift+Up",
+        next_command = "Ctrl+Shift+Down",
+        copy_rich = "Ctrl+Alt+C",
+        grow_pane = "Ctrl+Shift+Right",
+        shrink_pane = "Ctrl+Shift+Left",
+        scroll_to_top = "Shift+Home",
+        scroll_to_bottom = "Shift+End", -- also resumes following new output
+        toggle_stats_overlay = "Ctrl+Shift+S", -- frame rate, render time and buffer sizes
+        -- Bind any other action by name, e.g. select_all = "Ctrl+Alt+A"
+        custom = {},
+        -- Bindings may be chords typed in sequence, e.g. "Ctrl+B c"
+        chord_timeout_ms = 1000,
     },
 
     hooks = {
@@ -87,8 +127,47 @@ config = {
         on_output = nil,
         on_bell = nil,
         on_title_change = nil,
+        on_resize = nil,
         custom_keybindings = {},
         output_filters = {},
         custom_widgets = {},
+        -- Abort any hook, filter or widget running longer than this (0 = no limit)
+        timeout_ms = 1000,
+    },
+
+    translator = {
+        -- User-defined command mappings, e.g. bat = "type"
+        -- An empty target ("") disables a built-in mapping
+        custom = {},
+    },
+
+    progress_bar = {
+        -- Spinner animation: "line" (ASCII), "dots", "braille", "arc" or "bounce"
+        spinner_style = "line",
+    },
+
+    session = {
+        -- Checkpoint the current session every N seconds for crash recovery (0 = off)
+        -- Requires features.session_manager = true
+        autosave_interval_secs = 0,
+    },
+
+    plugins = {
+        -- Load every .so/.dll/.dylib in this directory as a plugin, e.g. "~/.furnace/plugins"
+        directory = nil,
+    },
+
+    ui = {
+        -- Seconds each notification stays on screen; several at once stack, newest on top
+        notification_duration_secs = 2,
+    },
+
+    logging = {
+        -- Also write the log to this file, e.g. "~/.furnace/furnace.log" (--log-file overrides)
+        file = nil,
+        -- Rotate the file once it grows past this many megabytes
+        max_size_mb = 10,
+        -- Rotated files kept (furnace.log.1, furnace.log.2, ...)
+        max_files = 3,
     },
 }
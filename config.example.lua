@@ -41,6 +41,13 @@ config = {
         -- Cursor style: "block", "underline", or "bar"
         cursor_style = "block",
         
+        -- Bell: "none", "visual" (flash the screen), "audible" (beep), or "both"
+        bell_style = "none",
+        
+        -- Tab labels: "title" (set by programs), "cwd" (shell's directory) or "process"
+        tab_title_source = "title",
+        tab_title_max_width = 24,
+        
         -- Number of lines to keep in scrollback buffer
         -- Higher values use more memory but allow more history
         scrollback_lines = 10000,
@@ -88,6 +95,7 @@ config = {
         --     opacity = 0.3,              -- 0.0 (transparent) to 1.0 (opaque)
         --     mode = "fill",              -- "fill", "fit", "stretch", "tile", "center"
         --     blur = 5.0,                 -- Blur effect strength (0.0 = no blur)
+        --     animate = false,            -- Play animated GIF/APNG images
         --     color = "#1E1E1E"           -- Fallback solid color
         -- },
 
@@ -99,7 +107,8 @@ config = {
         --     color = "#00FF0080",        -- Trail color with alpha (#RRGGBBAA format)
         --     fade_mode = "exponential",  -- "linear", "exponential", "smooth"
         --     width = 1.0,                -- Trail width multiplier
-        --     animation_speed = 16        -- Milliseconds per frame (~60 FPS)
+        --     animation_speed = 16,       -- Milliseconds per frame (~60 FPS)
+        --     fade_ms = 250               -- Milliseconds for a cell to fade back to the background
         -- }
     },
 
@@ -156,6 +165,13 @@ config = {
         -- Hook for title changes
         -- on_title_change = "~/.furnace/scripts/log_title.lua",
         
+        -- Hook for terminal resizes (receives the new columns and rows)
+        -- on_resize = "~/.furnace/scripts/relayout.lua",
+        
+        -- Abort a hook that runs longer than this many milliseconds, so a
+        -- script stuck in a loop can't freeze the terminal (0 = no limit)
+        timeout_ms = 1000,
+        
         -- Custom keybindings with Lua functions
         -- Bind keys to arbitrary Lua code for ultimate flexibility
         custom_keybindings = {
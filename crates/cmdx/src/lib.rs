@@ -8,6 +8,7 @@
 //! - Comprehensive argument and flag translation
 //! - Zero-copy design with static maps
 //! - Configurable enable/disable
+//! - BSD-flavored flags on macOS (`sed`, `stat`, `ls`, `date`, `tail`)
 //!
 //! # Supported Commands
 //!
@@ -91,6 +92,7 @@
 //! }
 //! ```
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
@@ -100,6 +102,12 @@ use std::sync::LazyLock;
 pub struct CommandTranslator {
     enabled: bool,
     current_os: OsType,
+    /// User-defined mappings, consulted before the static maps
+    custom_mappings: HashMap<String, CustomMapping>,
+    /// Expand environment variable references before translation
+    expand_env: bool,
+    /// Collect flag explanations in translation results
+    explain: bool,
     // Use references to static maps instead of cloning
     _phantom: std::marker::PhantomData<()>,
 }
@@ -119,6 +127,32 @@ pub struct CommandMapping {
     pub arg_translator: fn(&str) -> String,
 }
 
+/// Argument translator that also records how each flag was mapped
+type ExplainedArgTranslator = fn(&str, &mut Vec<String>) -> String;
+
+/// Reports arguments that a translator could not map faithfully
+type ArgValidator = fn(&str) -> Vec<TranslationError>;
+
+/// A user-defined command mapping, typically loaded from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomMapping {
+    /// Target command; an empty target disables translation of the source command
+    pub target: String,
+    /// Append the original arguments unchanged after the target command
+    pub passthrough_args: bool,
+}
+
+impl CustomMapping {
+    /// Create a mapping to `target` that passes arguments through
+    #[must_use]
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            passthrough_args: true,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TranslationResult {
     pub translated: bool,
@@ -129,6 +163,9 @@ pub struct TranslationResult {
     pub errors: Vec<TranslationError>,
     /// Whether the command contains pipelines
     pub has_pipeline: bool,
+    /// Human-readable flag mappings, e.g. `-r → /S (recursive)`.
+    /// Only populated when explanations are enabled on the translator.
+    pub explanations: Vec<String>,
 }
 
 /// Errors that can occur during command translation
@@ -176,6 +213,17 @@ pub enum PipelineOperator {
     Semicolon,
 }
 
+impl OsType {
+    /// Target OS for the reverse translation direction
+    fn opposite(self) -> Self {
+        match self {
+            Self::Windows => Self::Linux,
+            Self::Linux | Self::MacOs => Self::Windows,
+            Self::Unknown => Self::Unknown,
+        }
+    }
+}
+
 impl PipelineOperator {
     /// Parse a pipeline operator from a string
     fn from_str(s: &str) -> Option<Self> {
@@ -229,6 +277,186 @@ impl Default for CommandMapping {
     }
 }
 
+// ============================================================================
+// Pipeline Tokenizer
+// ============================================================================
+
+/// Quote-aware pipeline tokenizer shared by operator detection and parsing.
+/// Operators inside single or double quotes are kept as part of the command.
+fn split_pipeline(command: &str) -> Vec<PipelineSegment> {
+    let mut segments = Vec::new();
+    let mut current_segment = String::new();
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if !in_double_quote => {
+                in_single_quote = !in_single_quote;
+                current_segment.push(c);
+            }
+            '"' if !in_single_quote => {
+                in_double_quote = !in_double_quote;
+                current_segment.push(c);
+            }
+            '|' if !in_single_quote && !in_double_quote => {
+                // Check for || vs |
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                    segments.push(PipelineSegment {
+                        command: current_segment.trim().to_string(),
+                        operator: Some(PipelineOperator::Or),
+                    });
+                } else {
+                    segments.push(PipelineSegment {
+                        command: current_segment.trim().to_string(),
+                        operator: Some(PipelineOperator::Pipe),
+                    });
+                }
+                current_segment = String::new();
+            }
+            '&' if !in_single_quote && !in_double_quote => {
+                // Check for &&
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    segments.push(PipelineSegment {
+                        command: current_segment.trim().to_string(),
+                        operator: Some(PipelineOperator::And),
+                    });
+                    current_segment = String::new();
+                } else {
+                    // Single & (background) - just pass through
+                    current_segment.push(c);
+                }
+            }
+            '>' if !in_single_quote && !in_double_quote => {
+                // Check for >> vs >
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    segments.push(PipelineSegment {
+                        command: current_segment.trim().to_string(),
+                        operator: Some(PipelineOperator::RedirectAppend),
+                    });
+                } else {
+                    segments.push(PipelineSegment {
+                        command: current_segment.trim().to_string(),
+                        operator: Some(PipelineOperator::RedirectOut),
+                    });
+                }
+                current_segment = String::new();
+            }
+            '<' if !in_single_quote && !in_double_quote => {
+                segments.push(PipelineSegment {
+                    command: current_segment.trim().to_string(),
+                    operator: Some(PipelineOperator::RedirectIn),
+                });
+                current_segment = String::new();
+            }
+            ';' if !in_single_quote && !in_double_quote => {
+                segments.push(PipelineSegment {
+                    command: current_segment.trim().to_string(),
+                    operator: Some(PipelineOperator::Semicolon),
+                });
+                current_segment = String::new();
+            }
+            _ => {
+                current_segment.push(c);
+            }
+        }
+    }
+
+    // Add the last segment
+    if !current_segment.trim().is_empty() {
+        segments.push(PipelineSegment {
+            command: current_segment.trim().to_string(),
+            operator: None,
+        });
+    }
+
+    segments
+}
+
+// ============================================================================
+// Environment Expansion
+// ============================================================================
+
+/// Expands `$VAR` and `${VAR}` references (Linux-origin commands).
+/// Unknown variables and references inside single quotes are left verbatim.
+fn expand_unix_env(command: &str) -> String {
+    let mut result = String::with_capacity(command.len());
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+    let mut rest = command;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+            '"' if !in_single_quote => in_double_quote = !in_double_quote,
+            '$' if !in_single_quote => {
+                let (name, consumed) = if let Some(braced) = rest.strip_prefix('{') {
+                    match braced.find('}') {
+                        Some(end) => (&braced[..end], end + 2),
+                        None => ("", 0),
+                    }
+                } else {
+                    let end = rest
+                        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], end)
+                };
+
+                if !name.is_empty() {
+                    if let Ok(value) = std::env::var(name) {
+                        result.push_str(&value);
+                        rest = &rest[consumed..];
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+/// Expands `%VAR%` references (Windows-origin commands).
+/// Unknown variables are left verbatim.
+fn expand_windows_env(command: &str) -> String {
+    let mut result = String::with_capacity(command.len());
+    let mut rest = command;
+
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        match after.find('%') {
+            Some(end) if end > 0 && !after[..end].contains(char::is_whitespace) => {
+                let name = &after[..end];
+                match std::env::var(name) {
+                    Ok(value) => result.push_str(&value),
+                    Err(_) => {
+                        result.push('%');
+                        result.push_str(name);
+                        result.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
 // ============================================================================
 // Argument Translators for Linux -> Windows
 // ============================================================================
@@ -239,10 +467,40 @@ fn identity_args(args: &str) -> String {
     args.to_string()
 }
 
+/// Splits arguments on whitespace, keeping single- and double-quoted spans
+/// (including their quotes) together as one token
+fn tokenize_args(args: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+
+    for (i, c) in args.char_indices() {
+        match c {
+            '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+            '"' if !in_single_quote => in_double_quote = !in_double_quote,
+            c if c.is_whitespace() && !in_single_quote && !in_double_quote => {
+                if let Some(s) = start.take() {
+                    tokens.push(&args[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+
+    if let Some(s) = start {
+        tokens.push(&args[s..]);
+    }
+
+    tokens
+}
+
 /// Helper function to check if a flag is present (handles combined flags like -rf)
 #[inline]
 fn has_flag(args: &str, short: char, long: Option<&str>) -> bool {
-    for part in args.split_whitespace() {
+    for part in tokenize_args(args) {
         if part.starts_with("--") {
             if let Some(l) = long {
                 if part.strip_prefix("--") == Some(l) {
@@ -263,7 +521,8 @@ fn has_flag(args: &str, short: char, long: Option<&str>) -> bool {
 /// Note: Paths can start with / on Linux (absolute paths)
 #[inline]
 fn extract_paths(args: &str) -> Vec<&str> {
-    args.split_whitespace()
+    tokenize_args(args)
+        .into_iter()
         .filter(|part| {
             // Filter out flags (start with -)
             // But allow paths starting with / (Linux absolute paths)
@@ -275,7 +534,8 @@ fn extract_paths(args: &str) -> Vec<&str> {
 /// Helper function to extract non-flag arguments for Windows commands
 #[inline]
 fn extract_paths_windows(args: &str) -> Vec<&str> {
-    args.split_whitespace()
+    tokenize_args(args)
+        .into_iter()
         .filter(|part| {
             // Keep paths that start with drive letters (C:\) or relative paths
             // Filter out Windows flags that start with /
@@ -291,7 +551,7 @@ fn extract_paths_windows(args: &str) -> Vec<&str> {
 
 /// Helper to get flag value like -n 10 returns Some("10")
 fn get_flag_value<'a>(args: &'a str, short: char, long: Option<&str>) -> Option<&'a str> {
-    let parts: Vec<&str> = args.split_whitespace().collect();
+    let parts: Vec<&str> = tokenize_args(args);
     for (i, part) in parts.iter().enumerate() {
         if part.starts_with("--") {
             if let Some(l) = long {
@@ -333,6 +593,11 @@ fn get_flag_value<'a>(args: &'a str, short: char, long: Option<&str>) -> Option<
 /// - `-1` -> `/B` (one entry per line, bare format)
 #[inline]
 fn ls_to_dir_args(args: &str) -> String {
+    ls_to_dir_args_explained(args, &mut Vec::new())
+}
+
+/// `ls_to_dir_args` that records each flag mapping in `explanations`
+fn ls_to_dir_args_explained(args: &str, explanations: &mut Vec<String>) -> String {
     let args = args.trim();
 
     if args.is_empty() {
@@ -345,16 +610,19 @@ fn ls_to_dir_args(args: &str) -> String {
     // -a, --all: show hidden files
     if has_flag(args, 'a', Some("all")) {
         result.push_str(" /A");
+        explanations.push("-a → /A (show hidden files)".to_string());
     }
 
     // -R, --recursive: recursive listing
     if has_flag(args, 'R', Some("recursive")) {
         result.push_str(" /S");
+        explanations.push("-R → /S (recursive)".to_string());
     }
 
     // -1: one entry per line (bare format)
     if has_flag(args, '1', None) {
         result.push_str(" /B");
+        explanations.push("-1 → /B (bare format)".to_string());
     }
 
     // -S: sort by size
@@ -386,10 +654,12 @@ fn ls_to_dir_args(args: &str) -> String {
 
     // Apply sort options
     if !sort_options.is_empty() {
+        let start = result.len();
         result.push_str(" /O");
         for opt in sort_options {
             result.push_str(opt);
         }
+        explanations.push(format!("-S/-t/-X →{} (sort order)", &result[start..]));
     }
 
     // Extract paths
@@ -469,6 +739,11 @@ fn dir_to_ls_args(args: &str) -> String {
 /// - `-i`, `--interactive` -> `/P` (prompt before delete)
 /// - `-v`, `--verbose` -> (no equivalent, ignored)
 fn rm_to_del_args(args: &str) -> String {
+    rm_to_del_args_explained(args, &mut Vec::new())
+}
+
+/// `rm_to_del_args` that records each flag mapping in `explanations`
+fn rm_to_del_args_explained(args: &str, explanations: &mut Vec<String>) -> String {
     let args = args.trim();
 
     if args.is_empty() {
@@ -484,12 +759,15 @@ fn rm_to_del_args(args: &str) -> String {
 
     if has_recursive {
         result.push_str(" /S");
+        explanations.push("-r → /S (recursive)".to_string());
     }
     if has_force {
         result.push_str(" /F /Q");
+        explanations.push("-f → /F /Q (force, quiet)".to_string());
     }
     if has_interactive {
         result.push_str(" /P");
+        explanations.push("-i → /P (prompt before delete)".to_string());
     }
 
     // Extract paths
@@ -508,6 +786,11 @@ fn rm_to_del_args(args: &str) -> String {
 /// - `/Q` -> `-f` (quiet, treated as force)
 /// - `/P` -> `-i` (prompt/interactive)
 fn del_to_rm_args(args: &str) -> String {
+    del_to_rm_args_explained(args, &mut Vec::new())
+}
+
+/// `del_to_rm_args` that records each flag mapping in `explanations`
+fn del_to_rm_args_explained(args: &str, explanations: &mut Vec<String>) -> String {
     let args = args.trim();
 
     if args.is_empty() {
@@ -519,12 +802,15 @@ fn del_to_rm_args(args: &str) -> String {
 
     if args_upper.contains("/S") {
         result.push_str(" -r");
+        explanations.push("/S → -r (recursive)".to_string());
     }
     if args_upper.contains("/F") || args_upper.contains("/Q") {
         result.push_str(" -f");
+        explanations.push("/F /Q → -f (force, quiet)".to_string());
     }
     if args_upper.contains("/P") {
         result.push_str(" -i");
+        explanations.push("/P → -i (prompt before delete)".to_string());
     }
 
     // Extract paths
@@ -545,6 +831,11 @@ fn del_to_rm_args(args: &str) -> String {
 /// - `-p`, `--preserve` -> /K (preserve attributes)
 /// - `-n`, `--no-clobber` -> /-Y (don't overwrite)
 fn cp_to_copy_args(args: &str) -> String {
+    cp_to_copy_args_explained(args, &mut Vec::new())
+}
+
+/// `cp_to_copy_args` that records each flag mapping in `explanations`
+fn cp_to_copy_args_explained(args: &str, explanations: &mut Vec<String>) -> String {
     let args = args.trim();
 
     if args.is_empty() {
@@ -557,15 +848,19 @@ fn cp_to_copy_args(args: &str) -> String {
     // For recursive copy, we'd use xcopy, but keep it simple for now
     if has_recursive {
         result.push_str(" /E");
+        explanations.push("-r → /E (recursive, including empty directories)".to_string());
     }
     if has_flag(args, 'f', Some("force")) {
         result.push_str(" /Y");
+        explanations.push("-f → /Y (overwrite without prompting)".to_string());
     }
     if has_flag(args, 'i', Some("interactive")) || has_flag(args, 'n', Some("no-clobber")) {
         result.push_str(" /-Y");
+        explanations.push("-i/-n → /-Y (prompt before overwrite)".to_string());
     }
     if has_flag(args, 'p', Some("preserve")) {
         result.push_str(" /K");
+        explanations.push("-p → /K (preserve attributes)".to_string());
     }
 
     // Extract paths (source and destination)
@@ -743,7 +1038,7 @@ fn grep_to_findstr_args(args: &str) -> String {
 
     // Extract pattern and files
     // grep pattern file1 file2 -> findstr "pattern" file1 file2
-    let parts: Vec<&str> = args.split_whitespace().collect();
+    let parts: Vec<&str> = tokenize_args(args);
     let mut pattern_found = false;
 
     for (i, part) in parts.iter().enumerate() {
@@ -812,7 +1107,7 @@ fn findstr_to_grep_args(args: &str) -> String {
     }
 
     // Extract pattern and files
-    for part in args.split_whitespace() {
+    for part in tokenize_args(args) {
         let part_upper = part.to_uppercase();
         if part_upper.starts_with("/C:") {
             // Literal string search
@@ -1044,7 +1339,7 @@ fn ping_windows_to_linux_args(args: &str) -> String {
     }
 
     let mut result = String::with_capacity(args.len() + 10);
-    let parts: Vec<&str> = args.split_whitespace().collect();
+    let parts: Vec<&str> = tokenize_args(args);
 
     for (i, part) in parts.iter().enumerate() {
         let part_lower = part.to_lowercase();
@@ -1109,7 +1404,7 @@ fn kill_to_taskkill_args(args: &str) -> String {
     }
 
     // Extract PIDs
-    for part in args.split_whitespace() {
+    for part in tokenize_args(args) {
         if !part.starts_with('-') {
             // It's a PID
             result.push_str(" /PID ");
@@ -1137,7 +1432,7 @@ fn taskkill_to_kill_args(args: &str) -> String {
     }
 
     // Extract PID from /PID value
-    let parts: Vec<&str> = args.split_whitespace().collect();
+    let parts: Vec<&str> = tokenize_args(args);
     for (i, part) in parts.iter().enumerate() {
         if part.to_uppercase() == "/PID" {
             if let Some(pid) = parts.get(i + 1) {
@@ -1167,7 +1462,7 @@ fn echo_linux_to_windows_args(args: &str) -> String {
     // For simplicity, just extract the message
     let mut result = String::with_capacity(args.len());
 
-    for part in args.split_whitespace() {
+    for part in tokenize_args(args) {
         if !part.starts_with('-') {
             if !result.is_empty() {
                 result.push(' ');
@@ -1230,268 +1525,908 @@ fn sort_windows_to_linux_args(args: &str) -> String {
     result
 }
 
+/// Returns the explaining variant of an argument translator, if one exists
+fn explained_translator(target_os: OsType, cmd: &str) -> Option<ExplainedArgTranslator> {
+    match (target_os, cmd) {
+        (OsType::Windows, "ls") => Some(ls_to_dir_args_explained),
+        (OsType::Windows, "rm") => Some(rm_to_del_args_explained),
+        (OsType::Windows, "cp") => Some(cp_to_copy_args_explained),
+        (OsType::Linux | OsType::MacOs, "del" | "erase") => Some(del_to_rm_args_explained),
+        _ => None,
+    }
+}
+
 // ============================================================================
-// Static Command Mappings
+// Permission Argument Translators
 // ============================================================================
 
-static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
-    LazyLock::new(|| {
-        let mut m = HashMap::new();
+/// Maps a set of rwx bits to icacls rights
+fn icacls_rights(read: bool, write: bool, execute: bool) -> Option<&'static str> {
+    match (read, write, execute) {
+        (true, true, true) => Some("M"),
+        (true, true, false) => Some("R,W"),
+        (true, false, true) => Some("RX"),
+        (true, false, false) => Some("R"),
+        (false, true, true) => Some("W,X"),
+        (false, true, false) => Some("W"),
+        (false, false, true) => Some("X"),
+        (false, false, false) => None,
+    }
+}
 
-        // ========== File System Commands ==========
+/// Windows principal used for a chmod class (`u`, `g`, `o`)
+fn icacls_principal(class: char) -> &'static str {
+    match class {
+        'u' => "%USERNAME%",
+        'g' => "Users",
+        _ => "Everyone",
+    }
+}
 
-        m.insert(
-            "ls",
-            CommandMapping {
-                target_cmd: "dir",
-                description: "List directory contents",
-                arg_translator: ls_to_dir_args,
-            },
-        );
+/// Builds icacls clauses for an octal mode like `644` or `0755`
+fn octal_mode_clauses(mode: &str, errors: &mut Vec<TranslationError>) -> Option<Vec<String>> {
+    if !(3..=4).contains(&mode.len()) || !mode.chars().all(|c| ('0'..='7').contains(&c)) {
+        return None;
+    }
 
-        m.insert(
-            "pwd",
-            CommandMapping {
-                target_cmd: "cd",
-                description: "Print working directory",
-                arg_translator: identity_args,
-            },
-        );
+    let digits: Vec<u32> = mode.chars().filter_map(|c| c.to_digit(8)).collect();
+    let (special, perms) = digits.split_at(digits.len() - 3);
+    if special.first().is_some_and(|&d| d != 0) {
+        errors.push(TranslationError::PartialTranslation(format!(
+            "chmod {mode}: setuid/setgid/sticky bits have no icacls equivalent"
+        )));
+    }
 
-        m.insert(
-            "cat",
-            CommandMapping {
-                target_cmd: "type",
-                description: "Display file contents",
-                arg_translator: cat_to_type_args,
-            },
-        );
+    let mut clauses = Vec::new();
+    for (class, &bits) in ['u', 'g', 'o'].into_iter().zip(perms) {
+        let principal = icacls_principal(class);
+        if let Some(rights) = icacls_rights(bits & 4 != 0, bits & 2 != 0, bits & 1 != 0) {
+            clauses.push(format!("/grant {principal}:({rights})"));
+        }
+        // A read-only owner is enforced with an explicit write deny
+        if class == 'u' && bits & 2 == 0 {
+            clauses.push(format!("/deny {principal}:(W)"));
+        }
+    }
 
-        m.insert(
-            "rm",
-            CommandMapping {
-                target_cmd: "del",
-                description: "Remove files",
-                arg_translator: rm_to_del_args,
-            },
-        );
+    Some(clauses)
+}
 
-        m.insert(
-            "cp",
-            CommandMapping {
-                target_cmd: "copy",
-                description: "Copy files",
-                arg_translator: cp_to_copy_args,
-            },
-        );
+/// Builds icacls clauses for a symbolic mode like `u+x` or `go-w,a+r`
+fn symbolic_mode_clauses(mode: &str, errors: &mut Vec<TranslationError>) -> Option<Vec<String>> {
+    let mut clauses = Vec::new();
 
-        m.insert(
-            "mv",
-            CommandMapping {
-                target_cmd: "move",
-                description: "Move/rename files",
-                arg_translator: mv_to_move_args,
-            },
-        );
+    for part in mode.split(',') {
+        let op_pos = part.find(['+', '-', '='])?;
+        let (who, rest) = part.split_at(op_pos);
+        if !who.chars().all(|c| "ugoa".contains(c)) {
+            return None;
+        }
+        let mut rest = rest.chars();
+        let op = rest.next()?;
+        let perms: String = rest.collect();
 
-        m.insert(
-            "mkdir",
-            CommandMapping {
-                target_cmd: "md",
-                description: "Create directory",
-                arg_translator: mkdir_to_md_args,
-            },
-        );
+        if perms.chars().any(|c| !"rwx".contains(c)) {
+            if perms.chars().any(|c| !"rwxXst".contains(c)) {
+                return None;
+            }
+            errors.push(TranslationError::PartialTranslation(format!(
+                "chmod {part}: only r, w and x map to icacls rights"
+            )));
+        }
 
-        m.insert(
-            "rmdir",
-            CommandMapping {
-                target_cmd: "rd",
-                description: "Remove directory",
-                arg_translator: rmdir_to_rd_args,
-            },
-        );
+        let Some(rights) = icacls_rights(
+            perms.contains('r'),
+            perms.contains('w'),
+            perms.contains('x'),
+        ) else {
+            continue;
+        };
 
-        m.insert(
-            "touch",
-            CommandMapping {
-                target_cmd: "type nul >",
-                description: "Create empty file",
-                arg_translator: |args| args.trim().to_string(),
-            },
-        );
+        let action = match op {
+            '+' => "/grant",
+            '-' => "/deny",
+            _ => "/grant:r",
+        };
 
-        m.insert(
-            "ln",
-            CommandMapping {
-                target_cmd: "mklink",
-                description: "Create symbolic link",
-                arg_translator: |args| {
-                    // ln -s target link -> mklink link target (reversed order)
-                    let parts: Vec<&str> = args
-                        .split_whitespace()
-                        .filter(|p| !p.starts_with('-'))
-                        .collect();
-                    if parts.len() >= 2 {
-                        format!(" {} {}", parts[1], parts[0])
-                    } else {
-                        args.to_string()
-                    }
-                },
-            },
-        );
+        // Without an explicit class, apply to the owner (Windows has no umask)
+        let classes: Vec<char> = if who.is_empty() {
+            vec!['u']
+        } else if who.contains('a') {
+            vec!['o']
+        } else {
+            who.chars().collect()
+        };
 
-        // ========== Text Processing Commands ==========
+        for class in classes {
+            clauses.push(format!("{action} {}:({rights})", icacls_principal(class)));
+        }
+    }
 
-        m.insert(
-            "grep",
-            CommandMapping {
-                target_cmd: "findstr",
-                description: "Search text patterns",
-                arg_translator: grep_to_findstr_args,
-            },
-        );
+    Some(clauses)
+}
 
-        m.insert(
-            "head",
-            CommandMapping {
-                target_cmd: "powershell Get-Content",
-                description: "Display first lines of file",
-                arg_translator: head_to_ps_args,
-            },
-        );
+/// Translates chmod arguments into icacls clauses, collecting any
+/// modes that could not be fully mapped as `PartialTranslation` errors
+fn chmod_to_icacls(args: &str) -> (String, Vec<TranslationError>) {
+    let mut errors = Vec::new();
+    let mut recursive = false;
+    let mut mode = None;
+    let mut files = Vec::new();
+
+    for part in tokenize_args(args) {
+        match part {
+            "-R" | "--recursive" => recursive = true,
+            "-v" | "-c" | "-f" | "--verbose" | "--changes" | "--silent" | "--quiet" => {}
+            _ if mode.is_none() => mode = Some(part),
+            _ => files.push(part),
+        }
+    }
 
-        m.insert(
-            "tail",
-            CommandMapping {
-                target_cmd: "powershell Get-Content",
-                description: "Display last lines of file",
-                arg_translator: tail_to_ps_args,
-            },
-        );
+    let Some(mode) = mode else {
+        return (String::new(), errors);
+    };
+
+    let clauses = if mode.starts_with("--reference") {
+        None
+    } else if mode.starts_with(|c: char| c.is_ascii_digit()) {
+        octal_mode_clauses(mode, &mut errors)
+    } else {
+        symbolic_mode_clauses(mode, &mut errors)
+    };
+
+    let Some(clauses) = clauses.filter(|c| !c.is_empty()) else {
+        errors.push(TranslationError::PartialTranslation(format!(
+            "chmod {mode}: mode cannot be mapped to icacls"
+        )));
+        // Showing the current ACL is harmless; granting guessed rights is not
+        let mut result = String::new();
+        for file in &files {
+            result.push(' ');
+            result.push_str(file);
+        }
+        return (result, errors);
+    };
+
+    let mut invocations = Vec::with_capacity(files.len());
+    for file in &files {
+        let mut invocation = format!("{file} {}", clauses.join(" "));
+        if recursive {
+            invocation.push_str(" /T");
+        }
+        invocations.push(invocation);
+    }
 
-        m.insert(
-            "wc",
-            CommandMapping {
-                target_cmd: "find /c /v \"\"",
-                description: "Count lines/words/characters",
-                arg_translator: |args| {
-                    // wc -l file -> find /c /v "" file
-                    let mut result = String::new();
-                    for path in extract_paths(args) {
-                        result.push(' ');
-                        result.push_str(path);
+    (format!(" {}", invocations.join(" && icacls ")), errors)
+}
+
+/// Translates chmod modes to icacls grant/deny clauses
+/// Supported modes:
+/// - Octal (`644`, `755`) -> `/grant` per owner/group/other, `/deny (W)` for a read-only owner
+/// - Symbolic (`u+x`, `go-w`, `a=r`) -> `/grant`, `/deny` or `/grant:r`
+/// - `-R`, `--recursive` -> `/T`
+fn chmod_to_icacls_args(args: &str) -> String {
+    chmod_to_icacls(args).0
+}
+
+/// Reports chmod modes that could not be fully translated
+fn chmod_to_icacls_errors(args: &str) -> Vec<TranslationError> {
+    chmod_to_icacls(args).1
+}
+
+/// find predicates that take a value but have no `dir` equivalent
+const FIND_VALUE_PREDICATES: &[&str] = &[
+    "-mtime",
+    "-mmin",
+    "-atime",
+    "-amin",
+    "-ctime",
+    "-cmin",
+    "-size",
+    "-newer",
+    "-user",
+    "-group",
+    "-perm",
+    "-path",
+    "-ipath",
+    "-regex",
+    "-maxdepth",
+    "-mindepth",
+];
+
+/// Reports find predicates that `dir /S /B` cannot express
+/// Supported predicates: `-name`, `-iname`, `-type f`, `-type d`
+fn find_errors(args: &str) -> Vec<TranslationError> {
+    let parts: Vec<&str> = tokenize_args(args);
+    let mut errors = Vec::new();
+    let mut i = 0;
+
+    while i < parts.len() {
+        let part = parts[i];
+        match part {
+            "-name" | "-iname" => i += 1,
+            "-type" => {
+                let kind = parts.get(i + 1).copied().unwrap_or("");
+                if kind != "f" && kind != "d" {
+                    errors.push(TranslationError::PartialTranslation(format!(
+                        "find -type {kind}: only f and d are supported, predicate dropped"
+                    )));
+                }
+                i += 1;
+            }
+            _ if part.starts_with('-') => {
+                errors.push(TranslationError::PartialTranslation(format!(
+                    "find {part}: predicate not supported by dir, dropped"
+                )));
+                if FIND_VALUE_PREDICATES.contains(&part) {
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    errors
+}
+
+/// Returns the validator for a command's arguments, if one exists
+fn arg_validator(target_os: OsType, cmd: &str) -> Option<ArgValidator> {
+    match (target_os, cmd) {
+        (OsType::Windows, "chmod") => Some(chmod_to_icacls_errors),
+        (OsType::Windows, "find") => Some(find_errors),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Archive Argument Translators
+// ============================================================================
+
+/// Parsed tar invocation, shared by both translation directions
+#[derive(Debug, Default, PartialEq)]
+struct TarArgs<'a> {
+    /// Operation mode: `c` (create), `x` (extract) or `t` (list)
+    mode: Option<char>,
+    /// Compression filter: `z` (gzip), `j` (bzip2) or `J` (xz)
+    compression: Option<char>,
+    verbose: bool,
+    file: Option<&'a str>,
+    directory: Option<&'a str>,
+    /// Unrecognized flags, passed through unchanged; unknown letters from a
+    /// bundle such as `-cvpzf` are gathered into one flag (`-p`)
+    other_flags: Vec<Cow<'a, str>>,
+    paths: Vec<&'a str>,
+}
+
+/// Parses tar flags, including bundled (`-czvf`) and old-style (`czvf`) forms
+fn parse_tar_args(args: &str) -> TarArgs<'_> {
+    let parts: Vec<&str> = tokenize_args(args);
+    let mut tar = TarArgs::default();
+    let mut i = 0;
+
+    while i < parts.len() {
+        let part = parts[i];
+        let bundled = if let Some(flags) = part.strip_prefix('-').filter(|f| !f.starts_with('-')) {
+            Some(flags)
+        } else if i == 0 && part.chars().all(|c| "cxtzjJvf".contains(c)) {
+            // Old-style first argument without a leading dash: tar czf out.tgz dir
+            Some(part)
+        } else {
+            None
+        };
+
+        if let Some(flags) = bundled {
+            let mut unknown = String::new();
+            for c in flags.chars() {
+                match c {
+                    'c' | 'x' | 't' => tar.mode = Some(c),
+                    'z' | 'j' | 'J' => tar.compression = Some(c),
+                    'v' => tar.verbose = true,
+                    'f' => {
+                        tar.file = parts.get(i + 1).copied();
+                        i += 1;
                     }
-                    result
-                },
-            },
-        );
+                    'C' => {
+                        tar.directory = parts.get(i + 1).copied();
+                        i += 1;
+                    }
+                    _ => unknown.push(c),
+                }
+            }
+            if !unknown.is_empty() {
+                tar.other_flags.push(Cow::Owned(format!("-{unknown}")));
+            }
+        } else if let Some(long) = part.strip_prefix("--") {
+            let (key, value) = match long.split_once('=') {
+                Some((k, v)) => (k, Some(v)),
+                None => (long, None),
+            };
+            match key {
+                "create" => tar.mode = Some('c'),
+                "extract" | "get" => tar.mode = Some('x'),
+                "list" => tar.mode = Some('t'),
+                "gzip" | "gunzip" => tar.compression = Some('z'),
+                "bzip2" => tar.compression = Some('j'),
+                "xz" => tar.compression = Some('J'),
+                "verbose" => tar.verbose = true,
+                "file" | "directory" => {
+                    let value = value.or_else(|| {
+                        i += 1;
+                        parts.get(i).copied()
+                    });
+                    if key == "file" {
+                        tar.file = value;
+                    } else {
+                        tar.directory = value;
+                    }
+                }
+                _ => tar.other_flags.push(Cow::Borrowed(part)),
+            }
+        } else {
+            tar.paths.push(part);
+        }
+        i += 1;
+    }
+
+    tar
+}
+
+/// Emits a normalized tar argument string: ` -czvf FILE [-C DIR] [flags] paths`
+fn format_tar_args(tar: &TarArgs<'_>) -> String {
+    let mut result = String::with_capacity(32);
+    let mut flags = String::new();
+
+    flags.extend(tar.mode);
+    flags.extend(tar.compression);
+    if tar.verbose {
+        flags.push('v');
+    }
+    if tar.file.is_some() {
+        flags.push('f');
+    }
+
+    if !flags.is_empty() {
+        result.push_str(" -");
+        result.push_str(&flags);
+    }
+    if let Some(file) = tar.file {
+        result.push(' ');
+        result.push_str(file);
+    }
+    if let Some(dir) = tar.directory {
+        result.push_str(" -C ");
+        result.push_str(dir);
+    }
+    let other_flags = tar.other_flags.iter().map(AsRef::as_ref);
+    for part in other_flags.chain(tar.paths.iter().copied()) {
+        result.push(' ');
+        result.push_str(part);
+    }
+
+    result
+}
+
+/// Translates tar flags into a normalized tar invocation
+/// Supported flags:
+/// - `-c`/`--create`, `-x`/`--extract`, `-t`/`--list` (mode)
+/// - `-z`/`--gzip`, `-j`/`--bzip2`, `-J`/`--xz` (compression)
+/// - `-f FILE`/`--file=FILE`, `-C DIR`/`--directory=DIR`
+/// - `-v`/`--verbose`
+fn tar_args(args: &str) -> String {
+    format_tar_args(&parse_tar_args(args))
+}
+
+/// Translates tar to a Windows command line.
+/// Windows 10+ ships bsdtar, which accepts the normalized flags. Zip archives
+/// (which that tar can't gzip into) fall back to `Compress-Archive` /
+/// `Expand-Archive`. The mapping has an empty target so this returns the full command.
+fn tar_to_windows_args(args: &str) -> String {
+    let tar = parse_tar_args(args);
+    let is_zip = tar
+        .file
+        .is_some_and(|f| f.to_ascii_lowercase().ends_with(".zip"));
+
+    match (tar.mode, tar.file) {
+        (Some('c'), Some(file)) if is_zip && !tar.paths.is_empty() => format!(
+            "powershell Compress-Archive -Path {} -DestinationPath {}",
+            tar.paths.join(","),
+            file
+        ),
+        (Some('x'), Some(file)) if is_zip => format!(
+            "powershell Expand-Archive -Path {} -DestinationPath {}",
+            file,
+            tar.directory.unwrap_or(".")
+        ),
+        _ => format!("tar{}", format_tar_args(&tar)),
+    }
+}
+
+// ============================================================================
+// Argument Translators for GNU -> BSD (macOS)
+// ============================================================================
+
+/// Translates GNU ls flags to BSD ls equivalents
+/// Supported flags:
+/// - `--color`, `--color=WHEN` -> `-G` (colorized output)
+/// - `--all` -> `-a`, `--almost-all` -> `-A`
+/// - `--recursive` -> `-R`, `--reverse` -> `-r`
+/// - `--human-readable` -> `-h`
+/// - `-X`, `--group-directories-first` -> (no BSD equivalent, dropped)
+fn ls_gnu_to_bsd_args(args: &str) -> String {
+    let mut result = String::with_capacity(args.len() + 4);
+
+    for part in tokenize_args(args) {
+        let translated = match part {
+            "--color" | "--color=auto" | "--color=always" => "-G",
+            "--color=never" | "--group-directories-first" => continue,
+            "--all" => "-a",
+            "--almost-all" => "-A",
+            "--recursive" => "-R",
+            "--reverse" => "-r",
+            "--human-readable" => "-h",
+            _ if part.starts_with('-') && !part.starts_with("--") && part.contains('X') => {
+                // BSD ls has no sort-by-extension; strip it from combined flags
+                let stripped: String = part.chars().filter(|&c| c != 'X').collect();
+                if stripped == "-" {
+                    continue;
+                }
+                result.push(' ');
+                result.push_str(&stripped);
+                continue;
+            }
+            _ => part,
+        };
+        result.push(' ');
+        result.push_str(translated);
+    }
+
+    result
+}
+
+/// Translates Windows dir flags to BSD ls equivalents
+/// Same as `dir_to_ls_args`, with GNU-only flags adjusted for BSD ls
+fn dir_to_bsd_ls_args(args: &str) -> String {
+    ls_gnu_to_bsd_args(&dir_to_ls_args(args))
+}
+
+/// Translates GNU sed flags to BSD sed equivalents
+/// Supported flags:
+/// - `-i`, `--in-place` -> `-i ''` (BSD requires an explicit backup suffix)
+/// - `--in-place=SUFFIX` -> `-i SUFFIX`
+/// - `-r`, `--regexp-extended` -> `-E`
+/// - `--quiet`, `--silent` -> `-n`
+/// - `--expression=SCRIPT` -> `-e SCRIPT`
+fn sed_gnu_to_bsd_args(args: &str) -> String {
+    let mut result = String::with_capacity(args.len() + 4);
+
+    for part in tokenize_args(args) {
+        result.push(' ');
+        match part {
+            "-i" | "--in-place" => result.push_str("-i ''"),
+            "-r" | "--regexp-extended" => result.push_str("-E"),
+            "--quiet" | "--silent" => result.push_str("-n"),
+            _ => {
+                if let Some(suffix) = part.strip_prefix("--in-place=") {
+                    result.push_str("-i ");
+                    result.push_str(suffix);
+                } else if let Some(script) = part.strip_prefix("--expression=") {
+                    result.push_str("-e ");
+                    result.push_str(script);
+                } else {
+                    result.push_str(part);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Translates a GNU stat format string to BSD stat format specifiers
+/// Supported specifiers: `%s`, `%n`, `%U`, `%G`, `%a`, `%A`, `%Y`, `%X`, `%h`, `%F`
+fn stat_gnu_to_bsd_format(format: &str) -> String {
+    let mut result = String::with_capacity(format.len() + 8);
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push_str("%z"),
+            Some('n') => result.push_str("%N"),
+            Some('U') => result.push_str("%Su"),
+            Some('G') => result.push_str("%Sg"),
+            Some('a') => result.push_str("%Lp"),
+            Some('A') => result.push_str("%Sp"),
+            Some('Y') => result.push_str("%m"),
+            Some('X') => result.push_str("%a"),
+            Some('h') => result.push_str("%l"),
+            Some('F') => result.push_str("%HT"),
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+
+    result
+}
+
+/// Translates GNU stat flags to BSD stat equivalents
+/// Supported flags:
+/// - `-c FORMAT`, `--format=FORMAT`, `--printf=FORMAT` -> `-f FORMAT`
+/// - `-L`, `--dereference` -> `-L`
+fn stat_gnu_to_bsd_args(args: &str) -> String {
+    let parts: Vec<&str> = tokenize_args(args);
+    let mut result = String::with_capacity(args.len() + 8);
+    let mut i = 0;
+
+    while i < parts.len() {
+        let part = parts[i];
+        result.push(' ');
+        if part == "-c" {
+            result.push_str("-f");
+            if let Some(format) = parts.get(i + 1) {
+                result.push(' ');
+                result.push_str(&stat_gnu_to_bsd_format(format));
+                i += 1;
+            }
+        } else if let Some(format) = part
+            .strip_prefix("--format=")
+            .or_else(|| part.strip_prefix("--printf="))
+        {
+            result.push_str("-f ");
+            result.push_str(&stat_gnu_to_bsd_format(format));
+        } else if part == "--dereference" {
+            result.push_str("-L");
+        } else {
+            result.push_str(part);
+        }
+        i += 1;
+    }
+
+    result
+}
+
+/// Translates GNU date flags to BSD date equivalents
+/// Supported flags:
+/// - `-d @EPOCH`, `--date=@EPOCH` -> `-r EPOCH`
+/// - `-I`, `--iso-8601` -> `+%Y-%m-%d`
+/// - `-R`, `--rfc-2822`, `--rfc-email` -> `"+%a, %d %b %Y %T %z"`
+/// - `--utc`, `--universal` -> `-u`
+/// - `/T` -> (Windows display-only flag, dropped)
+fn date_gnu_to_bsd_args(args: &str) -> String {
+    let parts: Vec<&str> = tokenize_args(args)
+        .into_iter()
+        .filter(|part| !part.eq_ignore_ascii_case("/t"))
+        .collect();
+    let mut result = String::with_capacity(args.len() + 16);
+    let mut i = 0;
+
+    while i < parts.len() {
+        let part = parts[i];
+        result.push(' ');
+        match part {
+            "-d" | "--date" if parts.get(i + 1).is_some_and(|v| v.starts_with('@')) => {
+                result.push_str("-r ");
+                result.push_str(&parts[i + 1][1..]);
+                i += 1;
+            }
+            "-I" | "--iso-8601" | "--iso-8601=date" => result.push_str("+%Y-%m-%d"),
+            "-R" | "--rfc-2822" | "--rfc-email" => result.push_str("\"+%a, %d %b %Y %T %z\""),
+            "--utc" | "--universal" => result.push_str("-u"),
+            _ => {
+                if let Some(epoch) = part.strip_prefix("--date=@") {
+                    result.push_str("-r ");
+                    result.push_str(epoch);
+                } else {
+                    result.push_str(part);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    result
+}
+
+/// Translates GNU tail flags to BSD tail equivalents
+/// Supported flags:
+/// - `--follow`, `--follow=descriptor` -> `-f`
+/// - `--follow=name`, `-F` -> `-F`
+/// - `--lines=N` -> `-n N`
+/// - `--retry`, `--pid=PID` -> (no BSD equivalent, dropped)
+fn tail_gnu_to_bsd_args(args: &str) -> String {
+    let mut result = String::with_capacity(args.len() + 4);
+
+    for part in tokenize_args(args) {
+        let translated = match part {
+            "--follow" | "--follow=descriptor" => "-f",
+            "--follow=name" => "-F",
+            "--retry" => continue,
+            _ if part.starts_with("--pid=") => continue,
+            _ => {
+                if let Some(lines) = part.strip_prefix("--lines=") {
+                    result.push_str(" -n ");
+                    result.push_str(lines);
+                    continue;
+                }
+                part
+            }
+        };
+        result.push(' ');
+        result.push_str(translated);
+    }
+
+    result
+}
+
+// ============================================================================
+// Static Command Mappings
+// ============================================================================
+
+static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
+    LazyLock::new(|| {
+        let mut m = HashMap::new();
+
+        // ========== File System Commands ==========
 
         m.insert(
-            "sort",
+            "ls",
             CommandMapping {
-                target_cmd: "sort",
-                description: "Sort lines",
-                arg_translator: sort_linux_to_windows_args,
+                target_cmd: "dir",
+                description: "List directory contents",
+                arg_translator: ls_to_dir_args,
             },
         );
 
         m.insert(
-            "uniq",
+            "pwd",
             CommandMapping {
-                target_cmd: "powershell Get-Unique",
-                description: "Filter duplicate lines",
+                target_cmd: "cd",
+                description: "Print working directory",
                 arg_translator: identity_args,
             },
         );
 
         m.insert(
-            "cut",
+            "cat",
             CommandMapping {
-                target_cmd: "powershell ForEach-Object",
-                description: "Extract columns (limited translation)",
-                arg_translator: |args| {
-                    // Basic cut -d',' -f1 translation - extracts first comma-delimited field
-                    // Complex cut commands may need manual adjustment
-                    let paths = extract_paths(args);
-                    if paths.is_empty() {
-                        " { $_.Split(',')[0] }".to_string()
-                    } else {
-                        format!(" {{ $_.Split(',')[0] }} {}", paths.join(" "))
-                    }
-                },
+                target_cmd: "type",
+                description: "Display file contents",
+                arg_translator: cat_to_type_args,
             },
         );
 
         m.insert(
-            "tr",
+            "rm",
             CommandMapping {
-                target_cmd: "powershell -replace",
-                description: "Translate characters",
-                arg_translator: identity_args,
+                target_cmd: "del",
+                description: "Remove files",
+                arg_translator: rm_to_del_args,
             },
         );
 
         m.insert(
-            "diff",
+            "cp",
             CommandMapping {
-                target_cmd: "fc",
-                description: "Compare files",
-                arg_translator: identity_args,
+                target_cmd: "copy",
+                description: "Copy files",
+                arg_translator: cp_to_copy_args,
             },
         );
 
         m.insert(
-            "tee",
+            "mv",
             CommandMapping {
-                target_cmd: "powershell Tee-Object",
-                description: "Read from stdin and write to stdout and files",
-                arg_translator: identity_args,
+                target_cmd: "move",
+                description: "Move/rename files",
+                arg_translator: mv_to_move_args,
             },
         );
 
-        // ========== Viewing Commands ==========
-
         m.insert(
-            "less",
+            "mkdir",
             CommandMapping {
-                target_cmd: "more",
-                description: "Page through file",
-                arg_translator: identity_args,
+                target_cmd: "md",
+                description: "Create directory",
+                arg_translator: mkdir_to_md_args,
             },
         );
 
         m.insert(
-            "more",
+            "rmdir",
             CommandMapping {
-                target_cmd: "more",
-                description: "Page through file",
-                arg_translator: identity_args,
+                target_cmd: "rd",
+                description: "Remove directory",
+                arg_translator: rmdir_to_rd_args,
             },
         );
 
-        // ========== System Commands ==========
-
         m.insert(
-            "clear",
+            "touch",
             CommandMapping {
-                target_cmd: "cls",
-                description: "Clear screen",
-                arg_translator: identity_args,
+                target_cmd: "type nul >",
+                description: "Create empty file",
+                arg_translator: |args| args.trim().to_string(),
             },
         );
 
         m.insert(
-            "which",
+            "ln",
             CommandMapping {
-                target_cmd: "where",
-                description: "Locate command",
-                arg_translator: identity_args,
+                target_cmd: "mklink",
+                description: "Create symbolic link",
+                arg_translator: |args| {
+                    // ln -s target link -> mklink link target (reversed order)
+                    let parts: Vec<&str> = tokenize_args(args)
+                        .into_iter()
+                        .filter(|p| !p.starts_with('-'))
+                        .collect();
+                    if parts.len() >= 2 {
+                        format!(" {} {}", parts[1], parts[0])
+                    } else {
+                        args.to_string()
+                    }
+                },
             },
         );
 
+        // ========== Text Processing Commands ==========
+
         m.insert(
-            "whoami",
+            "grep",
+            CommandMapping {
+                target_cmd: "findstr",
+                description: "Search text patterns",
+                arg_translator: grep_to_findstr_args,
+            },
+        );
+
+        m.insert(
+            "head",
+            CommandMapping {
+                target_cmd: "powershell Get-Content",
+                description: "Display first lines of file",
+                arg_translator: head_to_ps_args,
+            },
+        );
+
+        m.insert(
+            "tail",
+            CommandMapping {
+                target_cmd: "powershell Get-Content",
+                description: "Display last lines of file",
+                arg_translator: tail_to_ps_args,
+            },
+        );
+
+        m.insert(
+            "wc",
+            CommandMapping {
+                target_cmd: "find /c /v \"\"",
+                description: "Count lines/words/characters",
+                arg_translator: |args| {
+                    // wc -l file -> find /c /v "" file
+                    let mut result = String::new();
+                    for path in extract_paths(args) {
+                        result.push(' ');
+                        result.push_str(path);
+                    }
+                    result
+                },
+            },
+        );
+
+        m.insert(
+            "sort",
+            CommandMapping {
+                target_cmd: "sort",
+                description: "Sort lines",
+                arg_translator: sort_linux_to_windows_args,
+            },
+        );
+
+        m.insert(
+            "uniq",
+            CommandMapping {
+                target_cmd: "powershell Get-Unique",
+                description: "Filter duplicate lines",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "cut",
+            CommandMapping {
+                target_cmd: "powershell ForEach-Object",
+                description: "Extract columns (limited translation)",
+                arg_translator: |args| {
+                    // Basic cut -d',' -f1 translation - extracts first comma-delimited field
+                    // Complex cut commands may need manual adjustment
+                    let paths = extract_paths(args);
+                    if paths.is_empty() {
+                        " { $_.Split(',')[0] }".to_string()
+                    } else {
+                        format!(" {{ $_.Split(',')[0] }} {}", paths.join(" "))
+                    }
+                },
+            },
+        );
+
+        m.insert(
+            "tr",
+            CommandMapping {
+                target_cmd: "powershell -replace",
+                description: "Translate characters",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "diff",
+            CommandMapping {
+                target_cmd: "fc",
+                description: "Compare files",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "tee",
+            CommandMapping {
+                target_cmd: "powershell Tee-Object",
+                description: "Read from stdin and write to stdout and files",
+                arg_translator: identity_args,
+            },
+        );
+
+        // ========== Viewing Commands ==========
+
+        m.insert(
+            "less",
+            CommandMapping {
+                target_cmd: "more",
+                description: "Page through file",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "more",
+            CommandMapping {
+                target_cmd: "more",
+                description: "Page through file",
+                arg_translator: identity_args,
+            },
+        );
+
+        // ========== System Commands ==========
+
+        m.insert(
+            "clear",
+            CommandMapping {
+                target_cmd: "cls",
+                description: "Clear screen",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "which",
+            CommandMapping {
+                target_cmd: "where",
+                description: "Locate command",
+                arg_translator: identity_args,
+            },
+        );
+
+        m.insert(
+            "whoami",
             CommandMapping {
                 target_cmd: "whoami",
                 description: "Display current user",
@@ -1631,7 +2566,10 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 arg_translator: |args| {
                     // killall firefox -> taskkill /IM firefox.exe /F
                     let mut result = String::new();
-                    for name in args.split_whitespace().filter(|p| !p.starts_with('-')) {
+                    for name in tokenize_args(args)
+                        .into_iter()
+                        .filter(|p| !p.starts_with('-'))
+                    {
                         if !result.is_empty() {
                             result.push_str(" & taskkill /IM");
                         }
@@ -1722,7 +2660,7 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 arg_translator: |args| {
                     // curl -o file url -> Invoke-WebRequest -Uri url -OutFile file
                     let mut result = String::new();
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let parts: Vec<&str> = tokenize_args(args);
 
                     let mut output_file = None;
                     let mut url = None;
@@ -1763,7 +2701,7 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 arg_translator: |args| {
                     // wget url -O file -> Invoke-WebRequest -Uri url -OutFile file
                     let mut result = String::new();
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let parts: Vec<&str> = tokenize_args(args);
 
                     let mut output_file = None;
                     let mut url = None;
@@ -1899,9 +2837,10 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
         m.insert(
             "tar",
             CommandMapping {
-                target_cmd: "tar",
+                // tar_to_windows_args emits the full command (tar or a PowerShell fallback)
+                target_cmd: "",
                 description: "Archive files",
-                arg_translator: identity_args,
+                arg_translator: tar_to_windows_args,
             },
         );
 
@@ -1912,8 +2851,8 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 description: "Create zip archive",
                 arg_translator: |args| {
                     // zip archive.zip file1 file2 -> Compress-Archive -Path file1,file2 -DestinationPath archive.zip
-                    let parts: Vec<&str> = args
-                        .split_whitespace()
+                    let parts: Vec<&str> = tokenize_args(args)
+                        .into_iter()
                         .filter(|p| !p.starts_with('-'))
                         .collect();
                     if parts.len() >= 2 {
@@ -1934,7 +2873,7 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 description: "Extract zip archive",
                 arg_translator: |args| {
                     // unzip archive.zip -d dir -> Expand-Archive -Path archive.zip -DestinationPath dir
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let parts: Vec<&str> = tokenize_args(args);
                     let mut archive = "";
                     let mut dest_dir = ".";
 
@@ -1963,8 +2902,8 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 target_cmd: "powershell Compress-Archive",
                 description: "Gzip compression",
                 arg_translator: |args| {
-                    let file = args
-                        .split_whitespace()
+                    let file = tokenize_args(args)
+                        .into_iter()
                         .find(|p| !p.starts_with('-'))
                         .unwrap_or("");
                     format!(" -Path {} -DestinationPath {}.gz", file, file)
@@ -1988,7 +2927,7 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
             CommandMapping {
                 target_cmd: "icacls",
                 description: "Change file permissions",
-                arg_translator: identity_args,
+                arg_translator: chmod_to_icacls_args,
             },
         );
 
@@ -2009,10 +2948,11 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 target_cmd: "dir /S /B",
                 description: "Find files",
                 arg_translator: |args| {
-                    // find /path -name "pattern" -> dir /S /B path\*pattern*
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    // find /path -type f -name "pattern" -> dir /S /B /A-D path\pattern
+                    let parts: Vec<&str> = tokenize_args(args);
                     let mut path = ".";
                     let mut pattern = "*";
+                    let mut attributes = "";
 
                     let mut i = 0;
                     while i < parts.len() {
@@ -2022,13 +2962,24 @@ static LINUX_TO_WINDOWS_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                                 pattern = p.trim_matches('"').trim_matches('\'');
                                 i += 1;
                             }
+                        } else if part == "-type" {
+                            // -type f -> files only, -type d -> directories only
+                            match parts.get(i + 1).copied() {
+                                Some("f") => attributes = " /A-D",
+                                Some("d") => attributes = " /AD",
+                                _ => {}
+                            }
+                            i += 1;
+                        } else if FIND_VALUE_PREDICATES.contains(&part) {
+                            // Unsupported predicate: skip its value (reported by find_errors)
+                            i += 1;
                         } else if !part.starts_with('-') {
                             path = part;
                         }
                         i += 1;
                     }
 
-                    format!(" {}\\{}", path, pattern)
+                    format!("{} {}\\{}", attributes, path, pattern)
                 },
             },
         );
@@ -2309,8 +3260,8 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 description: "Create symbolic link",
                 arg_translator: |args| {
                     // mklink link target -> ln -s target link (reversed order)
-                    let parts: Vec<&str> = args
-                        .split_whitespace()
+                    let parts: Vec<&str> = tokenize_args(args)
+                        .into_iter()
                         .filter(|p| !p.starts_with('/'))
                         .collect();
                     if parts.len() >= 2 {
@@ -2366,7 +3317,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                     }
 
                     // Extract search string and file
-                    for part in args.split_whitespace() {
+                    for part in tokenize_args(args) {
                         if !part.starts_with('/') {
                             result.push(' ');
                             result.push_str(part);
@@ -2583,7 +3534,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 arg_translator: |args| {
                     // Filter out Windows-specific flags
                     let mut result = String::new();
-                    for part in args.split_whitespace() {
+                    for part in tokenize_args(args) {
                         if !part.starts_with('/') {
                             if !result.is_empty() {
                                 result.push(' ');
@@ -2781,7 +3732,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
             CommandMapping {
                 target_cmd: "tar",
                 description: "Archive files",
-                arg_translator: identity_args,
+                arg_translator: tar_args,
             },
         );
 
@@ -2863,7 +3814,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                     }
                     if args_upper.contains("/T") {
                         // Extract time value
-                        let parts: Vec<&str> = args.split_whitespace().collect();
+                        let parts: Vec<&str> = tokenize_args(args);
                         for (i, part) in parts.iter().enumerate() {
                             if part.to_uppercase() == "/T" {
                                 if let Some(time) = parts.get(i + 1) {
@@ -2968,7 +3919,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 target_cmd: "systemctl",
                 description: "Service control",
                 arg_translator: |args| {
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let parts: Vec<&str> = tokenize_args(args);
                     if parts.is_empty() {
                         return String::new();
                     }
@@ -2993,7 +3944,7 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
                 target_cmd: "systemctl",
                 description: "Network/service commands",
                 arg_translator: |args| {
-                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let parts: Vec<&str> = tokenize_args(args);
                     if parts.is_empty() {
                         return String::new();
                     }
@@ -3022,15 +3973,125 @@ static WINDOWS_TO_LINUX_MAP: LazyLock<HashMap<&'static str, CommandMapping>> =
         m
     });
 
+/// macOS adjustments, consulted before `WINDOWS_TO_LINUX_MAP` on macOS.
+/// Windows commands whose Linux translation relies on GNU-only tools or flags
+/// are mapped to BSD equivalents, and GNU-style invocations of BSD tools are
+/// rewritten with BSD flags.
+static MACOS_ADJUST_MAP: LazyLock<HashMap<&'static str, CommandMapping>> = LazyLock::new(|| {
+    let mut m = HashMap::new();
+
+    // ========== Windows -> BSD Commands ==========
+
+    m.insert(
+        "dir",
+        CommandMapping {
+            target_cmd: "ls",
+            description: "List directory contents",
+            arg_translator: dir_to_bsd_ls_args,
+        },
+    );
+
+    m.insert(
+        "start",
+        CommandMapping {
+            target_cmd: "open",
+            description: "Open file or application",
+            arg_translator: identity_args,
+        },
+    );
+
+    m.insert(
+        "ver",
+        CommandMapping {
+            target_cmd: "sw_vers",
+            description: "Display OS version",
+            arg_translator: |_| String::new(),
+        },
+    );
+
+    m.insert(
+        "systeminfo",
+        CommandMapping {
+            target_cmd: "system_profiler SPSoftwareDataType SPHardwareDataType",
+            description: "Display system information",
+            arg_translator: |_| String::new(),
+        },
+    );
+
+    // ========== GNU -> BSD Flag Adjustments ==========
+
+    m.insert(
+        "ls",
+        CommandMapping {
+            target_cmd: "ls",
+            description: "List directory contents (BSD flags)",
+            arg_translator: ls_gnu_to_bsd_args,
+        },
+    );
+
+    m.insert(
+        "sed",
+        CommandMapping {
+            target_cmd: "sed",
+            description: "Stream editor (BSD flags)",
+            arg_translator: sed_gnu_to_bsd_args,
+        },
+    );
+
+    m.insert(
+        "stat",
+        CommandMapping {
+            target_cmd: "stat",
+            description: "Display file status (BSD format)",
+            arg_translator: stat_gnu_to_bsd_args,
+        },
+    );
+
+    m.insert(
+        "date",
+        CommandMapping {
+            target_cmd: "date",
+            description: "Display date (BSD flags)",
+            arg_translator: date_gnu_to_bsd_args,
+        },
+    );
+
+    m.insert(
+        "tail",
+        CommandMapping {
+            target_cmd: "tail",
+            description: "Display last lines (BSD flags)",
+            arg_translator: tail_gnu_to_bsd_args,
+        },
+    );
+
+    m
+});
+
 impl CommandTranslator {
     /// Create a new command translator
     #[must_use]
     pub fn new(enabled: bool) -> Self {
+        Self::with_custom_mappings(enabled, HashMap::new())
+    }
+
+    /// Create a new command translator with user-defined mappings
+    ///
+    /// Custom mappings take precedence over the built-in maps. A mapping with
+    /// an empty target disables the built-in translation for that command.
+    #[must_use]
+    pub fn with_custom_mappings(
+        enabled: bool,
+        custom_mappings: HashMap<String, CustomMapping>,
+    ) -> Self {
         let current_os = Self::detect_os();
 
         Self {
             enabled,
             current_os,
+            custom_mappings,
+            expand_env: false,
+            explain: false,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -3052,6 +4113,81 @@ impl CommandTranslator {
     /// Supports pipelining with |, >, >>, <, &&, ||, ;
     #[must_use]
     pub fn translate(&self, command: &str) -> TranslationResult {
+        self.translate_for(command, self.current_os)
+    }
+
+    /// Translate a multi-line script, one result per line
+    ///
+    /// Comment lines (starting with `#`) are skipped, blank lines produce
+    /// untranslated no-op results, and leading indentation is preserved in
+    /// `final_command`.
+    #[must_use]
+    pub fn translate_script(&self, input: &str) -> Vec<TranslationResult> {
+        let mut results = Vec::new();
+
+        for line in input.lines() {
+            let content = line.trim_start();
+            if content.starts_with('#') {
+                continue;
+            }
+
+            let mut result = self.translate(content);
+            if !content.trim().is_empty() {
+                let indent = &line[..line.len() - content.len()];
+                result.final_command.insert_str(0, indent);
+            }
+            result.original_command = line.to_string();
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// Translate a command in the opposite direction of the current OS
+    ///
+    /// On Windows this translates Windows commands to Linux; on Linux/macOS it
+    /// translates Linux commands to Windows. User-defined mappings are not applied.
+    #[must_use]
+    pub fn translate_reverse(&self, command: &str) -> TranslationResult {
+        self.translate_for(command, self.current_os.opposite())
+    }
+
+    /// Translate a command in reverse and then forward again
+    ///
+    /// Useful for checking that a mapping is stable, e.g. on Linux
+    /// `ls -la` -> `dir /A` -> `ls -a`.
+    #[must_use]
+    pub fn round_trip(&self, command: &str) -> TranslationResult {
+        let reversed = self.translate_reverse(command);
+        let forward = self.translate(&reversed.final_command);
+
+        let mut errors = reversed.errors;
+        errors.extend(forward.errors);
+        let mut explanations = reversed.explanations;
+        explanations.extend(forward.explanations);
+
+        let description = match (
+            reversed.description.is_empty(),
+            forward.description.is_empty(),
+        ) {
+            (false, false) => format!("{} -> {}", reversed.description, forward.description),
+            (false, true) => reversed.description,
+            _ => forward.description,
+        };
+
+        TranslationResult {
+            translated: reversed.translated && forward.translated,
+            original_command: reversed.original_command,
+            final_command: forward.final_command,
+            description,
+            errors,
+            has_pipeline: reversed.has_pipeline || forward.has_pipeline,
+            explanations,
+        }
+    }
+
+    /// Translate a command with an explicit target OS
+    fn translate_for(&self, command: &str, target_os: OsType) -> TranslationResult {
         let command = command.trim();
         let mut errors: Vec<TranslationError> = Vec::new();
 
@@ -3064,157 +4200,55 @@ impl CommandTranslator {
                 description: String::new(),
                 errors,
                 has_pipeline: false,
+                explanations: Vec::new(),
             };
         }
 
+        // Expand environment references using the source OS syntax
+        let expanded;
+        let command = if self.expand_env {
+            expanded = match target_os {
+                OsType::Windows => expand_unix_env(command),
+                OsType::Linux | OsType::MacOs => expand_windows_env(command),
+                OsType::Unknown => command.to_string(),
+            };
+            expanded.as_str()
+        } else {
+            command
+        };
+
         // Check if command contains pipeline operators
         let has_pipeline = self.contains_pipeline_operators(command);
 
         if has_pipeline {
             // Handle pipelined commands
-            return self.translate_pipeline(command);
+            return self.translate_pipeline(command, target_os);
         }
 
         // Single command translation
-        self.translate_single_command(command, &mut errors)
+        self.translate_single_command(command, target_os, &mut errors)
     }
 
-    /// Check if a command contains any pipeline operators
+    /// Check whether a command contains pipeline operators outside of quotes
     fn contains_pipeline_operators(&self, command: &str) -> bool {
-        // Check for operators while avoiding false positives in strings
-        // This is a simplified check - full parsing would require proper tokenization
-        let operators = ["||", "&&", "|", ">>", ">", "<", ";"];
-
-        for op in operators {
-            if command.contains(op) {
-                // Make sure it's not inside quotes
-                let mut in_single_quote = false;
-                let mut in_double_quote = false;
-                let mut pos = 0;
-
-                for c in command.chars() {
-                    match c {
-                        '\'' if !in_double_quote => in_single_quote = !in_single_quote,
-                        '"' if !in_single_quote => in_double_quote = !in_double_quote,
-                        _ => {}
-                    }
-
-                    if !in_single_quote && !in_double_quote {
-                        // Check if we're at an operator
-                        let remaining = &command[pos..];
-                        if remaining.starts_with(op) {
-                            return true;
-                        }
-                    }
-                    pos += c.len_utf8();
-                }
-            }
-        }
-        false
+        split_pipeline(command)
+            .iter()
+            .any(|segment| segment.operator.is_some())
     }
 
     /// Parse a command line into pipeline segments
     fn parse_pipeline(&self, command: &str) -> Vec<PipelineSegment> {
-        let mut segments = Vec::new();
-        let mut current_segment = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut chars = command.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            match c {
-                '\'' if !in_double_quote => {
-                    in_single_quote = !in_single_quote;
-                    current_segment.push(c);
-                }
-                '"' if !in_single_quote => {
-                    in_double_quote = !in_double_quote;
-                    current_segment.push(c);
-                }
-                '|' if !in_single_quote && !in_double_quote => {
-                    // Check for || vs |
-                    if chars.peek() == Some(&'|') {
-                        chars.next();
-                        segments.push(PipelineSegment {
-                            command: current_segment.trim().to_string(),
-                            operator: Some(PipelineOperator::Or),
-                        });
-                    } else {
-                        segments.push(PipelineSegment {
-                            command: current_segment.trim().to_string(),
-                            operator: Some(PipelineOperator::Pipe),
-                        });
-                    }
-                    current_segment = String::new();
-                }
-                '&' if !in_single_quote && !in_double_quote => {
-                    // Check for &&
-                    if chars.peek() == Some(&'&') {
-                        chars.next();
-                        segments.push(PipelineSegment {
-                            command: current_segment.trim().to_string(),
-                            operator: Some(PipelineOperator::And),
-                        });
-                        current_segment = String::new();
-                    } else {
-                        // Single & (background) - just pass through
-                        current_segment.push(c);
-                    }
-                }
-                '>' if !in_single_quote && !in_double_quote => {
-                    // Check for >> vs >
-                    if chars.peek() == Some(&'>') {
-                        chars.next();
-                        segments.push(PipelineSegment {
-                            command: current_segment.trim().to_string(),
-                            operator: Some(PipelineOperator::RedirectAppend),
-                        });
-                    } else {
-                        segments.push(PipelineSegment {
-                            command: current_segment.trim().to_string(),
-                            operator: Some(PipelineOperator::RedirectOut),
-                        });
-                    }
-                    current_segment = String::new();
-                }
-                '<' if !in_single_quote && !in_double_quote => {
-                    segments.push(PipelineSegment {
-                        command: current_segment.trim().to_string(),
-                        operator: Some(PipelineOperator::RedirectIn),
-                    });
-                    current_segment = String::new();
-                }
-                ';' if !in_single_quote && !in_double_quote => {
-                    segments.push(PipelineSegment {
-                        command: current_segment.trim().to_string(),
-                        operator: Some(PipelineOperator::Semicolon),
-                    });
-                    current_segment = String::new();
-                }
-                _ => {
-                    current_segment.push(c);
-                }
-            }
-        }
-
-        // Add the last segment
-        if !current_segment.trim().is_empty() {
-            segments.push(PipelineSegment {
-                command: current_segment.trim().to_string(),
-                operator: None,
-            });
-        }
-
-        segments
+        split_pipeline(command)
     }
 
     /// Translate a pipeline command (command with operators like |, >, &&, etc.)
-    fn translate_pipeline(&self, command: &str) -> TranslationResult {
+    fn translate_pipeline(&self, command: &str, target_os: OsType) -> TranslationResult {
         let segments = self.parse_pipeline(command);
         let mut errors: Vec<TranslationError> = Vec::new();
         let mut translated_parts: Vec<String> = Vec::new();
         let mut any_translated = false;
         let mut descriptions: Vec<String> = Vec::new();
+        let mut explanations: Vec<String> = Vec::new();
 
         for segment in &segments {
             if segment.command.is_empty() {
@@ -3226,10 +4260,11 @@ impl CommandTranslator {
             }
 
             // Translate the command part
-            let result = self.translate_single_command(&segment.command, &mut errors);
+            let result = self.translate_single_command(&segment.command, target_os, &mut errors);
 
             if result.translated {
                 any_translated = true;
+                explanations.extend(result.explanations);
                 translated_parts.push(result.final_command);
                 if !result.description.is_empty() {
                     descriptions.push(result.description);
@@ -3260,6 +4295,7 @@ impl CommandTranslator {
             description,
             errors,
             has_pipeline: true,
+            explanations,
         }
     }
 
@@ -3267,6 +4303,7 @@ impl CommandTranslator {
     fn translate_single_command(
         &self,
         command: &str,
+        target_os: OsType,
         errors: &mut Vec<TranslationError>,
     ) -> TranslationResult {
         let command = command.trim();
@@ -3279,6 +4316,7 @@ impl CommandTranslator {
                 description: String::new(),
                 errors: Vec::new(),
                 has_pipeline: false,
+                explanations: Vec::new(),
             };
         }
 
@@ -3295,22 +4333,69 @@ impl CommandTranslator {
                     description: String::new(),
                     errors: Vec::new(),
                     has_pipeline: false,
+                    explanations: Vec::new(),
                 };
             }
         };
 
         let args = command.strip_prefix(cmd).unwrap_or("").trim();
 
+        // User-defined mappings win over built-ins (forward direction only)
+        let custom = if target_os == self.current_os {
+            self.custom_mappings.get(cmd)
+        } else {
+            None
+        };
+        if let Some(custom) = custom {
+            if custom.target.is_empty() {
+                return TranslationResult {
+                    translated: false,
+                    original_command: command.to_string(),
+                    final_command: command.to_string(),
+                    description: String::new(),
+                    errors: Vec::new(),
+                    has_pipeline: false,
+                    explanations: Vec::new(),
+                };
+            }
+
+            let mut final_cmd = custom.target.clone();
+            if custom.passthrough_args && !args.is_empty() {
+                final_cmd.push(' ');
+                final_cmd.push_str(args);
+            }
+
+            return TranslationResult {
+                translated: true,
+                original_command: command.to_string(),
+                final_command: final_cmd,
+                description: format!("Custom mapping: {} -> {}", cmd, custom.target),
+                errors: Vec::new(),
+                has_pipeline: false,
+                explanations: Vec::new(),
+            };
+        }
+
         // Determine which direction to translate
-        let (mapping, should_translate) = match self.current_os {
+        let bsd_adjusted = target_os == OsType::MacOs && MACOS_ADJUST_MAP.contains_key(cmd);
+        let (mapping, should_translate) = match target_os {
             OsType::Windows => {
                 // On Windows, translate Linux commands to Windows
                 (LINUX_TO_WINDOWS_MAP.get(cmd), true)
             }
-            OsType::Linux | OsType::MacOs => {
-                // On Linux/Mac, translate Windows commands to Linux
+            OsType::Linux => {
+                // On Linux, translate Windows commands to Linux
                 (WINDOWS_TO_LINUX_MAP.get(cmd), true)
             }
+            OsType::MacOs => {
+                // On Mac, prefer BSD-flavored mappings over the GNU ones
+                (
+                    MACOS_ADJUST_MAP
+                        .get(cmd)
+                        .or_else(|| WINDOWS_TO_LINUX_MAP.get(cmd)),
+                    true,
+                )
+            }
             OsType::Unknown => (None, false),
         };
 
@@ -3322,6 +4407,7 @@ impl CommandTranslator {
                 description: String::new(),
                 errors: Vec::new(),
                 has_pipeline: false,
+                explanations: Vec::new(),
             };
         }
 
@@ -3334,11 +4420,12 @@ impl CommandTranslator {
                 description: String::new(),
                 errors: Vec::new(),
                 has_pipeline: false,
+                explanations: Vec::new(),
             };
         }
 
         // Special case: translate bare "cd" to "pwd" on Windows (shows current directory)
-        if cmd == "cd" && self.current_os == OsType::Windows && args.is_empty() {
+        if cmd == "cd" && target_os == OsType::Windows && args.is_empty() {
             // On Windows, bare "cd" shows current directory like pwd
             // Let it through for translation
         } else if cmd == "cd" && args.is_empty() {
@@ -3350,24 +4437,54 @@ impl CommandTranslator {
                 description: String::new(),
                 errors: Vec::new(),
                 has_pipeline: false,
+                explanations: Vec::new(),
             };
         }
 
         if let Some(mapping) = mapping {
-            let translated_args = (mapping.arg_translator)(args);
+            let mut explanations = Vec::new();
+            let explained = if self.explain && !bsd_adjusted {
+                explained_translator(target_os, cmd)
+            } else {
+                None
+            };
+            let translated_args = match explained {
+                Some(translator) => translator(args, &mut explanations),
+                None => (mapping.arg_translator)(args),
+            };
+            let arg_errors = match arg_validator(target_os, cmd) {
+                Some(validator) => validator(args),
+                None => Vec::new(),
+            };
+            errors.extend(arg_errors.iter().cloned());
             // Use String::with_capacity for more efficient concatenation
             let mut final_cmd =
                 String::with_capacity(mapping.target_cmd.len() + translated_args.len());
             final_cmd.push_str(mapping.target_cmd);
             final_cmd.push_str(&translated_args);
+            let final_cmd = final_cmd.trim();
 
-            TranslationResult {
-                translated: true,
-                original_command: command.to_string(),
-                final_command: final_cmd.trim().to_string(),
-                description: mapping.description.to_string(),
-                errors: Vec::new(),
+            // BSD adjustments that changed nothing are not translations
+            if bsd_adjusted && final_cmd == command {
+                return TranslationResult {
+                    translated: false,
+                    original_command: command.to_string(),
+                    final_command: command.to_string(),
+                    description: String::new(),
+                    errors: Vec::new(),
+                    has_pipeline: false,
+                    explanations: Vec::new(),
+                };
+            }
+
+            TranslationResult {
+                translated: true,
+                original_command: command.to_string(),
+                final_command: final_cmd.to_string(),
+                description: mapping.description.to_string(),
+                errors: arg_errors,
                 has_pipeline: false,
+                explanations,
             }
         } else {
             // Command not found in translation map - add error for context
@@ -3379,6 +4496,7 @@ impl CommandTranslator {
                 description: String::new(),
                 errors: Vec::new(),
                 has_pipeline: false,
+                explanations: Vec::new(),
             }
         }
     }
@@ -3394,11 +4512,71 @@ impl CommandTranslator {
         self.enabled
     }
 
+    /// Builder-style toggle for collecting flag explanations in results
+    #[must_use]
+    pub fn with_explanations(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Check if flag explanations are collected
+    #[must_use]
+    pub fn explains(&self) -> bool {
+        self.explain
+    }
+
+    /// Enable or disable environment variable expansion before translation
+    pub fn set_expand_env(&mut self, expand_env: bool) {
+        self.expand_env = expand_env;
+    }
+
+    /// Check if environment variable expansion is enabled
+    #[must_use]
+    pub fn expands_env(&self) -> bool {
+        self.expand_env
+    }
+
     /// Get current OS type
     #[must_use]
     pub fn current_os(&self) -> OsType {
         self.current_os
     }
+
+    /// List every command the translator knows for the active direction
+    ///
+    /// Returns `(source command, description)` pairs sorted alphabetically.
+    #[must_use]
+    pub fn known_commands(&self) -> Vec<(&'static str, &'static str)> {
+        Self::known_commands_for(self.current_os)
+    }
+
+    /// Known commands when translating for `target_os`
+    fn known_commands_for(target_os: OsType) -> Vec<(&'static str, &'static str)> {
+        let mut commands: HashMap<&'static str, &'static str> = HashMap::new();
+        let maps: &[&HashMap<&'static str, CommandMapping>] = match target_os {
+            OsType::Windows => &[&LINUX_TO_WINDOWS_MAP],
+            OsType::Linux => &[&WINDOWS_TO_LINUX_MAP],
+            // macOS adjustments override the generic descriptions
+            OsType::MacOs => &[&WINDOWS_TO_LINUX_MAP, &MACOS_ADJUST_MAP],
+            OsType::Unknown => &[],
+        };
+
+        for map in maps {
+            for (&cmd, mapping) in map.iter() {
+                commands.insert(cmd, mapping.description);
+            }
+        }
+
+        let mut commands: Vec<_> = commands.into_iter().collect();
+        commands.sort_unstable_by_key(|&(cmd, _)| cmd);
+        commands
+    }
+
+    /// Get the user-defined mappings
+    #[must_use]
+    pub fn custom_mappings(&self) -> &HashMap<String, CustomMapping> {
+        &self.custom_mappings
+    }
 }
 
 #[cfg(test)]
@@ -3469,806 +4647,1676 @@ mod tests {
     // ========== Windows->Linux Basic Tests ==========
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_basic() {
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_basic() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.translate("dir");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "ls");
+
+        let result = translator.translate("cls");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "clear");
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_file_ops() {
+        let translator = CommandTranslator::new(true);
+
+        // type -> cat
+        let result = translator.translate("type file.txt");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "cat file.txt");
+
+        // copy -> cp
+        let result = translator.translate("copy src.txt dst.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("cp"));
+        assert!(result.final_command.contains("src.txt"));
+        assert!(result.final_command.contains("dst.txt"));
+
+        // move -> mv
+        let result = translator.translate("move old.txt new.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("mv"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_del_flags() {
+        let translator = CommandTranslator::new(true);
+
+        // del /S -> rm -r
+        let result = translator.translate("del /S folder");
+        assert!(result.translated);
+        assert!(result.final_command.contains("rm"));
+        assert!(result.final_command.contains("-r"));
+        assert!(result.final_command.contains("folder"));
+
+        // del /F /Q -> rm -f
+        let result = translator.translate("del /F /Q file.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("rm"));
+        assert!(result.final_command.contains("-f"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_dir_flags() {
+        let translator = CommandTranslator::new(true);
+
+        // dir /A -> ls -a
+        let result = translator.translate("dir /A");
+        assert!(result.translated);
+        assert!(result.final_command.contains("ls"));
+        assert!(result.final_command.contains("-a"));
+
+        // dir /S -> ls -R
+        let result = translator.translate("dir /S");
+        assert!(result.translated);
+        assert!(result.final_command.contains("-R"));
+
+        // dir /B -> ls -1
+        let result = translator.translate("dir /B");
+        assert!(result.translated);
+        assert!(result.final_command.contains("-1"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_network() {
+        let translator = CommandTranslator::new(true);
+
+        // tracert -> traceroute
+        let result = translator.translate("tracert google.com");
+        assert!(result.translated);
+        assert!(result.final_command.contains("traceroute"));
+
+        // ipconfig -> ip addr
+        let result = translator.translate("ipconfig");
+        assert!(result.translated);
+        assert!(result.final_command.contains("ip addr"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_findstr() {
+        let translator = CommandTranslator::new(true);
+
+        // findstr /I -> grep -i
+        let result = translator.translate("findstr /I \"pattern\" file.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("grep"));
+        assert!(result.final_command.contains("-i"));
+
+        // findstr /N -> grep -n
+        let result = translator.translate("findstr /N \"search\" log.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("-n"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_taskkill() {
+        let translator = CommandTranslator::new(true);
+
+        // taskkill /F /PID 1234 -> kill -9 1234
+        let result = translator.translate("taskkill /F /PID 1234");
+        assert!(result.translated);
+        assert!(result.final_command.contains("kill"));
+        assert!(result.final_command.contains("-9"));
+        assert!(result.final_command.contains("1234"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_ping() {
+        let translator = CommandTranslator::new(true);
+
+        // ping -n 4 host -> ping -c 4 host
+        let result = translator.translate("ping -n 4 google.com");
+        assert!(result.translated);
+        assert!(result.final_command.contains("ping"));
+        assert!(result.final_command.contains("-c 4"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_mkdir() {
+        let translator = CommandTranslator::new(true);
+
+        // md -> mkdir -p
+        let result = translator.translate("md newfolder");
+        assert!(result.translated);
+        assert!(result.final_command.contains("mkdir"));
+        assert!(result.final_command.contains("-p"));
+        assert!(result.final_command.contains("newfolder"));
+    }
+
+    // ========== Linux->Windows Basic Tests ==========
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_basic() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.translate("ls");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "dir");
+
+        let result = translator.translate("pwd");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "cd");
+
+        let result = translator.translate("clear");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "cls");
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_ls_flags() {
+        let translator = CommandTranslator::new(true);
+
+        // ls -a -> dir /A
+        let result = translator.translate("ls -a");
+        assert!(result.translated);
+        assert!(result.final_command.contains("dir"));
+        assert!(result.final_command.contains("/A"));
+
+        // ls -R -> dir /S
+        let result = translator.translate("ls -R");
+        assert!(result.translated);
+        assert!(result.final_command.contains("/S"));
+
+        // ls -1 -> dir /B
+        let result = translator.translate("ls -1");
+        assert!(result.translated);
+        assert!(result.final_command.contains("/B"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_cat() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.translate("cat file.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("type"));
+        assert!(result.final_command.contains("file.txt"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_rm_flags() {
+        let translator = CommandTranslator::new(true);
+
+        // rm -r -> del /S
+        let result = translator.translate("rm -r folder");
+        assert!(result.translated);
+        assert!(result.final_command.contains("del"));
+        assert!(result.final_command.contains("/S"));
+
+        // rm -f -> del /F /Q
+        let result = translator.translate("rm -f file.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("/F"));
+        assert!(result.final_command.contains("/Q"));
+
+        // rm -rf (combined flags)
+        let result = translator.translate("rm -rf folder");
+        assert!(result.translated);
+        assert!(result.final_command.contains("/S"));
+        assert!(result.final_command.contains("/F"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_grep() {
+        let translator = CommandTranslator::new(true);
+
+        // grep -i -> findstr /I
+        let result = translator.translate("grep -i \"pattern\" file.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("findstr"));
+        assert!(result.final_command.contains("/I"));
+
+        // grep -r -> findstr /S
+        let result = translator.translate("grep -r \"search\" .");
+        assert!(result.translated);
+        assert!(result.final_command.contains("/S"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_ping() {
+        let translator = CommandTranslator::new(true);
+
+        // ping -c 4 host -> ping -n 4 host
+        let result = translator.translate("ping -c 4 google.com");
+        assert!(result.translated);
+        assert!(result.final_command.contains("ping"));
+        assert!(result.final_command.contains("-n 4"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_kill() {
+        let translator = CommandTranslator::new(true);
+
+        // kill -9 1234 -> taskkill /F /PID 1234
+        let result = translator.translate("kill -9 1234");
+        assert!(result.translated);
+        assert!(result.final_command.contains("taskkill"));
+        assert!(result.final_command.contains("/F"));
+        assert!(result.final_command.contains("/PID"));
+        assert!(result.final_command.contains("1234"));
+    }
+
+    // ========== Helper Function Tests ==========
+
+    #[test]
+    fn test_has_flag() {
+        assert!(has_flag("-a", 'a', None));
+        assert!(has_flag("-la", 'a', None));
+        assert!(has_flag("-la", 'l', None));
+        assert!(has_flag("--all", 'a', Some("all")));
+        assert!(has_flag("-rf folder", 'r', None));
+        assert!(has_flag("-rf folder", 'f', None));
+        assert!(!has_flag("-la", 'x', None));
+        assert!(!has_flag("--verbose", 'v', Some("all")));
+    }
+
+    #[test]
+    fn test_extract_paths() {
+        let paths = extract_paths("-a /home/user");
+        assert_eq!(paths, vec!["/home/user"]);
+
+        let paths = extract_paths("-rf folder1 folder2");
+        assert_eq!(paths, vec!["folder1", "folder2"]);
+
+        let paths = extract_paths("file.txt");
+        assert_eq!(paths, vec!["file.txt"]);
+    }
+
+    #[test]
+    fn test_get_flag_value() {
+        assert_eq!(get_flag_value("-n 10 file.txt", 'n', None), Some("10"));
+        assert_eq!(get_flag_value("--lines=5", 'n', Some("lines")), Some("5"));
+        assert_eq!(get_flag_value("-c 4 host", 'c', None), Some("4"));
+        assert_eq!(get_flag_value("-a file.txt", 'n', None), None);
+    }
+
+    #[test]
+    fn test_pipeline_operator_translate_and_from_str() {
+        let pipe = PipelineOperator::from_str("|").unwrap();
+        assert_eq!(pipe.translate(OsType::Linux), "|");
+        let redirect = PipelineOperator::from_str(">>").unwrap();
+        assert_eq!(redirect.translate(OsType::Windows), ">>");
+    }
+
+    #[test]
+    fn test_translation_error_display() {
+        let err = TranslationError::UnknownCommand("foo".to_string());
+        assert!(format!("{err}").contains("Unknown command"));
+        let err = TranslationError::InvalidSyntax("bad".to_string());
+        assert!(format!("{err}").contains("Invalid syntax"));
+    }
+
+    // ========== Argument Translator Tests ==========
+
+    #[test]
+    fn test_ls_to_dir_args() {
+        // -a flag
+        let result = ls_to_dir_args("-a");
+        assert!(result.contains("/A"));
+
+        // -R flag
+        let result = ls_to_dir_args("-R");
+        assert!(result.contains("/S"));
+
+        // -1 flag
+        let result = ls_to_dir_args("-1");
+        assert!(result.contains("/B"));
+
+        // Combined flags
+        let result = ls_to_dir_args("-laR");
+        assert!(result.contains("/A"));
+        assert!(result.contains("/S"));
+
+        // With path
+        let result = ls_to_dir_args("-la /home");
+        assert!(result.contains("/A"));
+        assert!(result.contains("/home"));
+    }
+
+    #[test]
+    fn test_dir_to_ls_args() {
+        // /A flag
+        let result = dir_to_ls_args("/A");
+        assert!(result.contains("-a"));
+
+        // /S flag
+        let result = dir_to_ls_args("/S");
+        assert!(result.contains("-R"));
+
+        // /B flag
+        let result = dir_to_ls_args("/B");
+        assert!(result.contains("-1"));
+
+        // Case insensitive
+        let result = dir_to_ls_args("/a");
+        assert!(result.contains("-a"));
+    }
+
+    #[test]
+    fn test_rm_to_del_args() {
+        // -r flag
+        let result = rm_to_del_args("-r folder");
+        assert!(result.contains("/S"));
+        assert!(result.contains("folder"));
+
+        // -f flag
+        let result = rm_to_del_args("-f file.txt");
+        assert!(result.contains("/F"));
+        assert!(result.contains("/Q"));
+
+        // Combined -rf
+        let result = rm_to_del_args("-rf folder");
+        assert!(result.contains("/S"));
+        assert!(result.contains("/F"));
+    }
+
+    #[test]
+    fn test_del_to_rm_args() {
+        // /S flag
+        let result = del_to_rm_args("/S folder");
+        assert!(result.contains("-r"));
+
+        // /F flag
+        let result = del_to_rm_args("/F file.txt");
+        assert!(result.contains("-f"));
+
+        // /P flag
+        let result = del_to_rm_args("/P file.txt");
+        assert!(result.contains("-i"));
+    }
+
+    #[test]
+    fn test_grep_to_findstr_args() {
+        // -i flag
+        let result = grep_to_findstr_args("-i \"pattern\" file.txt");
+        assert!(result.contains("/I"));
+
+        // -n flag
+        let result = grep_to_findstr_args("-n \"pattern\" file.txt");
+        assert!(result.contains("/N"));
+
+        // -r flag
+        let result = grep_to_findstr_args("-r \"pattern\" .");
+        assert!(result.contains("/S"));
+    }
+
+    #[test]
+    fn test_findstr_to_grep_args() {
+        // /I flag
+        let result = findstr_to_grep_args("/I \"pattern\" file.txt");
+        assert!(result.contains("-i"));
+
+        // /N flag
+        let result = findstr_to_grep_args("/N \"pattern\" file.txt");
+        assert!(result.contains("-n"));
+
+        // /S flag
+        let result = findstr_to_grep_args("/S \"pattern\"");
+        assert!(result.contains("-r"));
+    }
+
+    #[test]
+    fn test_ping_args_translation() {
+        // Linux -c to Windows -n
+        let result = ping_linux_to_windows_args("-c 4 google.com");
+        assert!(result.contains("-n 4"));
+        assert!(result.contains("google.com"));
+
+        // Windows -n to Linux -c
+        let result = ping_windows_to_linux_args("-n 4 google.com");
+        assert!(result.contains("-c 4"));
+    }
+
+    #[test]
+    fn test_kill_to_taskkill_args() {
+        // -9 flag
+        let result = kill_to_taskkill_args("-9 1234");
+        assert!(result.contains("/F"));
+        assert!(result.contains("/PID"));
+        assert!(result.contains("1234"));
+
+        // Without -9
+        let result = kill_to_taskkill_args("5678");
+        assert!(result.contains("/PID"));
+        assert!(result.contains("5678"));
+        assert!(!result.contains("/F"));
+    }
+
+    #[test]
+    fn test_taskkill_to_kill_args() {
+        // /F /PID flags
+        let result = taskkill_to_kill_args("/F /PID 1234");
+        assert!(result.contains("-9"));
+        assert!(result.contains("1234"));
+
+        // Without /F
+        let result = taskkill_to_kill_args("/PID 5678");
+        assert!(result.contains("5678"));
+        assert!(!result.contains("-9"));
+    }
+
+    #[test]
+    fn test_sort_args_translation() {
+        // Linux -r to Windows /R
+        let result = sort_linux_to_windows_args("-r file.txt");
+        assert!(result.contains("/R"));
+        assert!(result.contains("file.txt"));
+
+        // Windows /R to Linux -r
+        let result = sort_windows_to_linux_args("/R file.txt");
+        assert!(result.contains("-r"));
+    }
+
+    #[test]
+    fn test_head_to_ps_args() {
+        // Default (no -n flag)
+        let result = head_to_ps_args("file.txt");
+        assert!(result.contains("-Head 10"));
+        assert!(result.contains("file.txt"));
+
+        // With -n flag
+        let result = head_to_ps_args("-n 20 file.txt");
+        assert!(result.contains("-Head 20"));
+    }
+
+    #[test]
+    fn test_tail_to_ps_args() {
+        // Default (no -n flag)
+        let result = tail_to_ps_args("file.txt");
+        assert!(result.contains("-Tail 10"));
+
+        // With -f flag
+        let result = tail_to_ps_args("-f file.txt");
+        assert!(result.contains("-Wait"));
+    }
+
+    // ========== Command Mapping Coverage Tests ==========
+
+    #[test]
+    fn test_linux_to_windows_map_coverage() {
+        // Verify essential commands are in the map
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ls"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("cat"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("rm"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("cp"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("mv"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("mkdir"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("rmdir"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("grep"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("head"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("tail"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ping"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("curl"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("wget"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ps"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("kill"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("killall"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("df"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("du"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("tar"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("zip"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("unzip"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("find"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("sort"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("netstat"));
+        assert!(LINUX_TO_WINDOWS_MAP.contains_key("traceroute"));
+    }
+
+    #[test]
+    fn test_windows_to_linux_map_coverage() {
+        // Verify essential commands are in the map
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("dir"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("type"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("del"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("copy"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("move"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("md"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("rd"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("findstr"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("find"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("ping"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("ipconfig"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("tasklist"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("taskkill"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("xcopy"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("sort"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("netstat"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("tracert"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("cls"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("where"));
+        assert!(WINDOWS_TO_LINUX_MAP.contains_key("hostname"));
+    }
+
+    #[test]
+    fn test_identity_args() {
+        assert_eq!(identity_args("test"), "test");
+        assert_eq!(identity_args(""), "");
+        assert_eq!(identity_args("multiple args here"), "multiple args here");
+    }
+
+    // ========== Pipeline Tests ==========
+
+    #[test]
+    fn test_pipeline_detection() {
+        let translator = CommandTranslator::new(true);
+
+        // Commands with pipes
+        assert!(translator.contains_pipeline_operators("ls | grep foo"));
+        assert!(translator.contains_pipeline_operators("cat file.txt | sort | uniq"));
+
+        // Commands with redirects
+        assert!(translator.contains_pipeline_operators("echo hello > file.txt"));
+        assert!(translator.contains_pipeline_operators("cat < input.txt"));
+        assert!(translator.contains_pipeline_operators("echo hello >> file.txt"));
+
+        // Commands with chaining
+        assert!(translator.contains_pipeline_operators("ls && echo done"));
+        assert!(translator.contains_pipeline_operators("ls || echo failed"));
+        assert!(translator.contains_pipeline_operators("ls; pwd"));
+
+        // Commands without pipelines
+        assert!(!translator.contains_pipeline_operators("ls -la"));
+        assert!(!translator.contains_pipeline_operators("cat file.txt"));
+    }
+
+    #[test]
+    fn test_pipeline_parsing() {
+        let translator = CommandTranslator::new(true);
+
+        // Single pipe
+        let segments = translator.parse_pipeline("ls | grep foo");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "ls");
+        assert_eq!(segments[0].operator, Some(PipelineOperator::Pipe));
+        assert_eq!(segments[1].command, "grep foo");
+        assert!(segments[1].operator.is_none());
+
+        // Multiple pipes
+        let segments = translator.parse_pipeline("cat file.txt | sort | uniq");
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].command, "cat file.txt");
+        assert_eq!(segments[1].command, "sort");
+        assert_eq!(segments[2].command, "uniq");
+
+        // Output redirect
+        let segments = translator.parse_pipeline("echo hello > output.txt");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "echo hello");
+        assert_eq!(segments[0].operator, Some(PipelineOperator::RedirectOut));
+        assert_eq!(segments[1].command, "output.txt");
+
+        // Command chaining with &&
+        let segments = translator.parse_pipeline("ls && echo done");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "ls");
+        assert_eq!(segments[0].operator, Some(PipelineOperator::And));
+        assert_eq!(segments[1].command, "echo done");
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_pipeline_translation_linux() {
+        let translator = CommandTranslator::new(true);
+
+        // dir | findstr -> ls | grep
+        let result = translator.translate("dir | findstr foo");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("ls"));
+        assert!(result.final_command.contains("|"));
+        assert!(result.final_command.contains("grep"));
+
+        // type file.txt | sort -> cat file.txt | sort
+        let result = translator.translate("type file.txt | sort");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("cat"));
+
+        // Command chaining: cls && dir -> clear && ls
+        let result = translator.translate("cls && dir");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("clear"));
+        assert!(result.final_command.contains("&&"));
+        assert!(result.final_command.contains("ls"));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_pipeline_translation_windows() {
+        let translator = CommandTranslator::new(true);
+
+        // ls | grep -> dir | findstr
+        let result = translator.translate("ls | grep foo");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("dir"));
+        assert!(result.final_command.contains("|"));
+        assert!(result.final_command.contains("findstr"));
+
+        // cat file.txt | sort -> type file.txt | sort
+        let result = translator.translate("cat file.txt | sort");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("type"));
+
+        // Command chaining: clear && ls -> cls && dir
+        let result = translator.translate("clear && ls");
+        assert!(result.translated);
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains("cls"));
+        assert!(result.final_command.contains("&&"));
+        assert!(result.final_command.contains("dir"));
+    }
+
+    #[test]
+    fn test_pipeline_with_redirect() {
+        let translator = CommandTranslator::new(true);
+
+        // Output redirect should be preserved
+        let result = translator.translate("echo hello > output.txt");
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains(">"));
+        assert!(result.final_command.contains("output.txt"));
+
+        // Append redirect
+        let result = translator.translate("echo hello >> output.txt");
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains(">>"));
+    }
+
+    // ========== Error Handling Tests ==========
+
+    #[test]
+    fn test_translation_error_display_all_variants() {
+        let err = TranslationError::UnknownCommand("foo".to_string());
+        assert!(format!("{}", err).contains("Unknown command"));
+        assert!(format!("{}", err).contains("foo"));
+
+        let err = TranslationError::InvalidSyntax("bad syntax".to_string());
+        assert!(format!("{}", err).contains("Invalid syntax"));
+
+        let err = TranslationError::UnsupportedOperator(">>>".to_string());
+        assert!(format!("{}", err).contains("Unsupported operator"));
+
+        let err = TranslationError::PartialTranslation("some parts failed".to_string());
+        assert!(format!("{}", err).contains("Partial translation"));
+    }
+
+    #[test]
+    fn test_translation_result_errors() {
+        let translator = CommandTranslator::new(true);
+
+        // Unknown command should have no errors (just not translated)
+        let result = translator.translate("unknowncommand");
+        assert!(!result.translated);
+        // The command is passed through, not an error
+
+        // Valid translation should have no errors
+        #[cfg(not(target_os = "windows"))]
+        {
+            let result = translator.translate("dir");
+            assert!(result.translated);
+            assert!(result.errors.is_empty());
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let result = translator.translate("ls");
+            assert!(result.translated);
+            assert!(result.errors.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_pipeline_operator_from_str() {
+        assert_eq!(
+            PipelineOperator::from_str("|"),
+            Some(PipelineOperator::Pipe)
+        );
+        assert_eq!(
+            PipelineOperator::from_str(">"),
+            Some(PipelineOperator::RedirectOut)
+        );
+        assert_eq!(
+            PipelineOperator::from_str(">>"),
+            Some(PipelineOperator::RedirectAppend)
+        );
+        assert_eq!(
+            PipelineOperator::from_str("<"),
+            Some(PipelineOperator::RedirectIn)
+        );
+        assert_eq!(
+            PipelineOperator::from_str("&&"),
+            Some(PipelineOperator::And)
+        );
+        assert_eq!(PipelineOperator::from_str("||"), Some(PipelineOperator::Or));
+        assert_eq!(
+            PipelineOperator::from_str(";"),
+            Some(PipelineOperator::Semicolon)
+        );
+        assert_eq!(PipelineOperator::from_str("invalid"), None);
+    }
+
+    #[test]
+    fn test_pipeline_operator_as_str() {
+        assert_eq!(PipelineOperator::Pipe.as_str(), "|");
+        assert_eq!(PipelineOperator::RedirectOut.as_str(), ">");
+        assert_eq!(PipelineOperator::RedirectAppend.as_str(), ">>");
+        assert_eq!(PipelineOperator::RedirectIn.as_str(), "<");
+        assert_eq!(PipelineOperator::And.as_str(), "&&");
+        assert_eq!(PipelineOperator::Or.as_str(), "||");
+        assert_eq!(PipelineOperator::Semicolon.as_str(), ";");
+    }
+
+    #[test]
+    fn test_complex_pipeline() {
         let translator = CommandTranslator::new(true);
 
-        let result = translator.translate("dir");
-        assert!(result.translated);
-        assert_eq!(result.final_command, "ls");
-
-        let result = translator.translate("cls");
-        assert!(result.translated);
-        assert_eq!(result.final_command, "clear");
+        // Complex pipeline with multiple operators
+        let result = translator.translate("echo start; ls | grep test && echo done || echo failed");
+        assert!(result.has_pipeline);
+        // The command should be processed
+        assert!(!result.final_command.is_empty());
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_file_ops() {
+    fn test_pipeline_with_quotes() {
         let translator = CommandTranslator::new(true);
 
-        // type -> cat
-        let result = translator.translate("type file.txt");
-        assert!(result.translated);
-        assert_eq!(result.final_command, "cat file.txt");
-
-        // copy -> cp
-        let result = translator.translate("copy src.txt dst.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("cp"));
-        assert!(result.final_command.contains("src.txt"));
-        assert!(result.final_command.contains("dst.txt"));
-
-        // move -> mv
-        let result = translator.translate("move old.txt new.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("mv"));
+        // Operators inside quotes should not be treated as pipeline operators
+        // Note: This is a simplified test - full quote handling is complex
+        let segments = translator.parse_pipeline("echo 'hello world'");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].command, "echo 'hello world'");
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_del_flags() {
+    fn test_pipeline_detection_ignores_quoted_operators() {
         let translator = CommandTranslator::new(true);
 
-        // del /S -> rm -r
-        let result = translator.translate("del /S folder");
-        assert!(result.translated);
-        assert!(result.final_command.contains("rm"));
-        assert!(result.final_command.contains("-r"));
-        assert!(result.final_command.contains("folder"));
+        // Pipes, redirects and && inside double quotes
+        assert!(!translator.contains_pipeline_operators("echo \"a | b\""));
+        assert!(!translator.contains_pipeline_operators("echo \"a > b.txt\""));
+        assert!(!translator.contains_pipeline_operators("echo \"a >> b.txt\""));
+        assert!(!translator.contains_pipeline_operators("echo \"a && b\""));
 
-        // del /F /Q -> rm -f
-        let result = translator.translate("del /F /Q file.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("rm"));
-        assert!(result.final_command.contains("-f"));
+        // Pipes, redirects and && inside single quotes
+        assert!(!translator.contains_pipeline_operators("echo 'a | b'"));
+        assert!(!translator.contains_pipeline_operators("echo 'a > b.txt'"));
+        assert!(!translator.contains_pipeline_operators("echo 'a && b'"));
+
+        // Quote characters inside the other quote style don't toggle state
+        assert!(!translator.contains_pipeline_operators("echo \"it's | fine\""));
+
+        // Operators outside quotes are still detected
+        assert!(translator.contains_pipeline_operators("echo \"a | b\" | grep a"));
+        assert!(translator.contains_pipeline_operators("echo 'a > b' > out.txt"));
+        assert!(translator.contains_pipeline_operators("echo 'a && b' && ls"));
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_dir_flags() {
+    fn test_quoted_redirect_is_single_command() {
         let translator = CommandTranslator::new(true);
 
-        // dir /A -> ls -a
-        let result = translator.translate("dir /A");
-        assert!(result.translated);
-        assert!(result.final_command.contains("ls"));
-        assert!(result.final_command.contains("-a"));
+        let result = translator.translate("echo \"a > b.txt\"");
+        assert!(!result.has_pipeline);
 
-        // dir /S -> ls -R
-        let result = translator.translate("dir /S");
-        assert!(result.translated);
-        assert!(result.final_command.contains("-R"));
+        let segments = translator.parse_pipeline("echo \"a > b.txt\"");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].command, "echo \"a > b.txt\"");
+        assert!(segments[0].operator.is_none());
 
-        // dir /B -> ls -1
-        let result = translator.translate("dir /B");
-        assert!(result.translated);
-        assert!(result.final_command.contains("-1"));
+        let segments = translator.parse_pipeline("echo 'x | y' && echo \"z > w\"");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "echo 'x | y'");
+        assert!(matches!(segments[0].operator, Some(PipelineOperator::And)));
+        assert_eq!(segments[1].command, "echo \"z > w\"");
     }
 
-    #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_network() {
-        let translator = CommandTranslator::new(true);
-
-        // tracert -> traceroute
-        let result = translator.translate("tracert google.com");
-        assert!(result.translated);
-        assert!(result.final_command.contains("traceroute"));
+    // ========== macOS Adjustment Tests ==========
 
-        // ipconfig -> ip addr
-        let result = translator.translate("ipconfig");
-        assert!(result.translated);
-        assert!(result.final_command.contains("ip addr"));
+    #[test]
+    fn test_ls_gnu_to_bsd_args() {
+        assert_eq!(ls_gnu_to_bsd_args("--color=auto -la"), " -G -la");
+        assert_eq!(ls_gnu_to_bsd_args("--all --human-readable"), " -a -h");
+        assert_eq!(ls_gnu_to_bsd_args("-lX /tmp"), " -l /tmp");
+        assert_eq!(ls_gnu_to_bsd_args("-X --group-directories-first"), "");
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_findstr() {
-        let translator = CommandTranslator::new(true);
-
-        // findstr /I -> grep -i
-        let result = translator.translate("findstr /I \"pattern\" file.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("grep"));
-        assert!(result.final_command.contains("-i"));
-
-        // findstr /N -> grep -n
-        let result = translator.translate("findstr /N \"search\" log.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("-n"));
+    fn test_sed_gnu_to_bsd_args() {
+        assert_eq!(
+            sed_gnu_to_bsd_args("-i s/foo/bar/ file.txt"),
+            " -i '' s/foo/bar/ file.txt"
+        );
+        assert_eq!(sed_gnu_to_bsd_args("-r -n p"), " -E -n p");
+        assert_eq!(
+            sed_gnu_to_bsd_args("--in-place=.bak s/a/b/ f"),
+            " -i .bak s/a/b/ f"
+        );
+        assert_eq!(sed_gnu_to_bsd_args("-i.bak s/a/b/ f"), " -i.bak s/a/b/ f");
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_taskkill() {
-        let translator = CommandTranslator::new(true);
+    fn test_stat_gnu_to_bsd_args() {
+        assert_eq!(stat_gnu_to_bsd_args("-c %s file"), " -f %z file");
+        assert_eq!(
+            stat_gnu_to_bsd_args("--format=%U:%G file"),
+            " -f %Su:%Sg file"
+        );
+        assert_eq!(stat_gnu_to_bsd_format("%n %a %%"), "%N %Lp %%");
+        assert_eq!(stat_gnu_to_bsd_args("file"), " file");
+    }
 
-        // taskkill /F /PID 1234 -> kill -9 1234
-        let result = translator.translate("taskkill /F /PID 1234");
-        assert!(result.translated);
-        assert!(result.final_command.contains("kill"));
-        assert!(result.final_command.contains("-9"));
-        assert!(result.final_command.contains("1234"));
+    #[test]
+    fn test_date_gnu_to_bsd_args() {
+        assert_eq!(date_gnu_to_bsd_args("-d @1700000000"), " -r 1700000000");
+        assert_eq!(date_gnu_to_bsd_args("--date=@0"), " -r 0");
+        assert_eq!(date_gnu_to_bsd_args("-I"), " +%Y-%m-%d");
+        assert_eq!(date_gnu_to_bsd_args("--utc +%H"), " -u +%H");
+        assert_eq!(date_gnu_to_bsd_args("/T"), "");
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_ping() {
-        let translator = CommandTranslator::new(true);
+    fn test_tail_gnu_to_bsd_args() {
+        assert_eq!(tail_gnu_to_bsd_args("--follow=name log.txt"), " -F log.txt");
+        assert_eq!(tail_gnu_to_bsd_args("-f --retry log.txt"), " -f log.txt");
+        assert_eq!(tail_gnu_to_bsd_args("--lines=20 --follow f"), " -n 20 -f f");
+    }
 
-        // ping -n 4 host -> ping -c 4 host
-        let result = translator.translate("ping -n 4 google.com");
-        assert!(result.translated);
-        assert!(result.final_command.contains("ping"));
-        assert!(result.final_command.contains("-c 4"));
+    #[test]
+    fn test_macos_adjust_map_coverage() {
+        for cmd in ["dir", "ls", "sed", "stat", "date", "tail"] {
+            assert!(MACOS_ADJUST_MAP.contains_key(cmd), "missing {cmd}");
+        }
     }
 
     #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_windows_to_linux_mkdir() {
+    #[cfg(target_os = "macos")]
+    fn test_macos_bsd_translations() {
         let translator = CommandTranslator::new(true);
 
-        // md -> mkdir -p
-        let result = translator.translate("md newfolder");
+        let result = translator.translate("sed -i s/a/b/ file.txt");
         assert!(result.translated);
-        assert!(result.final_command.contains("mkdir"));
-        assert!(result.final_command.contains("-p"));
-        assert!(result.final_command.contains("newfolder"));
-    }
-
-    // ========== Linux->Windows Basic Tests ==========
+        assert_eq!(result.final_command, "sed -i '' s/a/b/ file.txt");
 
-    #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_basic() {
-        let translator = CommandTranslator::new(true);
+        let result = translator.translate("stat -c %s file.txt");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "stat -f %z file.txt");
 
-        let result = translator.translate("ls");
+        let result = translator.translate("ls --color=auto");
         assert!(result.translated);
-        assert_eq!(result.final_command, "dir");
+        assert_eq!(result.final_command, "ls -G");
 
-        let result = translator.translate("pwd");
+        let result = translator.translate("date -d @0");
         assert!(result.translated);
-        assert_eq!(result.final_command, "cd");
+        assert_eq!(result.final_command, "date -r 0");
 
-        let result = translator.translate("clear");
+        let result = translator.translate("tail --follow=name app.log");
         assert!(result.translated);
-        assert_eq!(result.final_command, "cls");
+        assert_eq!(result.final_command, "tail -F app.log");
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_ls_flags() {
+    #[cfg(target_os = "macos")]
+    fn test_macos_windows_commands() {
         let translator = CommandTranslator::new(true);
 
-        // ls -a -> dir /A
-        let result = translator.translate("ls -a");
+        // dir /O:E -> GNU-only -X is dropped for BSD ls
+        let result = translator.translate("dir /O:E");
         assert!(result.translated);
-        assert!(result.final_command.contains("dir"));
-        assert!(result.final_command.contains("/A"));
+        assert_eq!(result.final_command, "ls");
 
-        // ls -R -> dir /S
-        let result = translator.translate("ls -R");
+        let result = translator.translate("date /t");
         assert!(result.translated);
-        assert!(result.final_command.contains("/S"));
+        assert_eq!(result.final_command, "date");
 
-        // ls -1 -> dir /B
-        let result = translator.translate("ls -1");
+        let result = translator.translate("ver");
         assert!(result.translated);
-        assert!(result.final_command.contains("/B"));
+        assert_eq!(result.final_command, "sw_vers");
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_cat() {
+    #[cfg(target_os = "macos")]
+    fn test_macos_native_commands_untouched() {
         let translator = CommandTranslator::new(true);
 
-        let result = translator.translate("cat file.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("type"));
-        assert!(result.final_command.contains("file.txt"));
+        // Already BSD-compatible invocations are not reported as translated
+        let result = translator.translate("tail -f app.log");
+        assert!(!result.translated);
+        assert_eq!(result.final_command, "tail -f app.log");
     }
 
-    #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_rm_flags() {
-        let translator = CommandTranslator::new(true);
-
-        // rm -r -> del /S
-        let result = translator.translate("rm -r folder");
-        assert!(result.translated);
-        assert!(result.final_command.contains("del"));
-        assert!(result.final_command.contains("/S"));
+    // ========== Custom Mapping Tests ==========
 
-        // rm -f -> del /F /Q
-        let result = translator.translate("rm -f file.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("/F"));
-        assert!(result.final_command.contains("/Q"));
+    #[test]
+    fn test_custom_mapping_translation() {
+        let mut custom = HashMap::new();
+        custom.insert("bat".to_string(), CustomMapping::new("type"));
+        let translator = CommandTranslator::with_custom_mappings(true, custom);
 
-        // rm -rf (combined flags)
-        let result = translator.translate("rm -rf folder");
+        let result = translator.translate("bat file.txt");
         assert!(result.translated);
-        assert!(result.final_command.contains("/S"));
-        assert!(result.final_command.contains("/F"));
+        assert_eq!(result.final_command, "type file.txt");
+        assert!(result.description.contains("bat"));
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_grep() {
-        let translator = CommandTranslator::new(true);
-
-        // grep -i -> findstr /I
-        let result = translator.translate("grep -i \"pattern\" file.txt");
-        assert!(result.translated);
-        assert!(result.final_command.contains("findstr"));
-        assert!(result.final_command.contains("/I"));
+    fn test_custom_mapping_without_passthrough() {
+        let mut custom = HashMap::new();
+        custom.insert(
+            "ll".to_string(),
+            CustomMapping {
+                target: "ls -la".to_string(),
+                passthrough_args: false,
+            },
+        );
+        let translator = CommandTranslator::with_custom_mappings(true, custom);
 
-        // grep -r -> findstr /S
-        let result = translator.translate("grep -r \"search\" .");
+        let result = translator.translate("ll ignored");
         assert!(result.translated);
-        assert!(result.final_command.contains("/S"));
+        assert_eq!(result.final_command, "ls -la");
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_ping() {
-        let translator = CommandTranslator::new(true);
+    fn test_custom_mapping_overrides_builtin() {
+        let mut custom = HashMap::new();
+        custom.insert("dir".to_string(), CustomMapping::new("exa"));
+        custom.insert("ls".to_string(), CustomMapping::new("lsd"));
+        let translator = CommandTranslator::with_custom_mappings(true, custom);
 
-        // ping -c 4 host -> ping -n 4 host
-        let result = translator.translate("ping -c 4 google.com");
-        assert!(result.translated);
-        assert!(result.final_command.contains("ping"));
-        assert!(result.final_command.contains("-n 4"));
+        assert_eq!(translator.translate("dir /tmp").final_command, "exa /tmp");
+        assert_eq!(translator.translate("ls /tmp").final_command, "lsd /tmp");
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_linux_to_windows_kill() {
-        let translator = CommandTranslator::new(true);
-
-        // kill -9 1234 -> taskkill /F /PID 1234
-        let result = translator.translate("kill -9 1234");
-        assert!(result.translated);
-        assert!(result.final_command.contains("taskkill"));
-        assert!(result.final_command.contains("/F"));
-        assert!(result.final_command.contains("/PID"));
-        assert!(result.final_command.contains("1234"));
+    fn test_custom_mapping_empty_target_disables_builtin() {
+        let mut custom = HashMap::new();
+        custom.insert("dir".to_string(), CustomMapping::new(""));
+        custom.insert("ls".to_string(), CustomMapping::new(""));
+        let translator = CommandTranslator::with_custom_mappings(true, custom);
+
+        for cmd in ["dir /A", "ls -la"] {
+            let result = translator.translate(cmd);
+            assert!(!result.translated);
+            assert_eq!(result.final_command, cmd);
+        }
     }
 
-    // ========== Helper Function Tests ==========
-
     #[test]
-    fn test_has_flag() {
-        assert!(has_flag("-a", 'a', None));
-        assert!(has_flag("-la", 'a', None));
-        assert!(has_flag("-la", 'l', None));
-        assert!(has_flag("--all", 'a', Some("all")));
-        assert!(has_flag("-rf folder", 'r', None));
-        assert!(has_flag("-rf folder", 'f', None));
-        assert!(!has_flag("-la", 'x', None));
-        assert!(!has_flag("--verbose", 'v', Some("all")));
+    fn test_custom_mapping_in_pipeline() {
+        let mut custom = HashMap::new();
+        custom.insert("bat".to_string(), CustomMapping::new("type"));
+        let translator = CommandTranslator::with_custom_mappings(true, custom);
+
+        let result = translator.translate("bat a.txt | more");
+        assert!(result.has_pipeline);
+        assert!(result.final_command.starts_with("type a.txt |"));
     }
 
+    // ========== Reverse/Round-Trip Tests ==========
+
     #[test]
-    fn test_extract_paths() {
-        let paths = extract_paths("-a /home/user");
-        assert_eq!(paths, vec!["/home/user"]);
+    #[cfg(not(target_os = "windows"))]
+    fn test_translate_reverse_linux_to_windows() {
+        let translator = CommandTranslator::new(true);
 
-        let paths = extract_paths("-rf folder1 folder2");
-        assert_eq!(paths, vec!["folder1", "folder2"]);
+        let result = translator.translate_reverse("ls -la");
+        assert!(result.translated);
+        assert!(result.final_command.starts_with("dir"));
+        assert!(result.final_command.contains("/A"));
 
-        let paths = extract_paths("file.txt");
-        assert_eq!(paths, vec!["file.txt"]);
+        // Windows commands are not translated in the reverse direction
+        let result = translator.translate_reverse("dir /A");
+        assert!(!result.translated);
     }
 
     #[test]
-    fn test_get_flag_value() {
-        assert_eq!(get_flag_value("-n 10 file.txt", 'n', None), Some("10"));
-        assert_eq!(get_flag_value("--lines=5", 'n', Some("lines")), Some("5"));
-        assert_eq!(get_flag_value("-c 4 host", 'c', None), Some("4"));
-        assert_eq!(get_flag_value("-a file.txt", 'n', None), None);
+    #[cfg(not(target_os = "windows"))]
+    fn test_round_trip_ls() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.round_trip("ls -la");
+        assert!(result.translated);
+        assert_eq!(result.original_command, "ls -la");
+        assert!(result.final_command.starts_with("ls"));
+        assert!(result.final_command.contains("-a"));
+        assert!(!result.has_pipeline);
     }
 
     #[test]
-    fn test_pipeline_operator_translate_and_from_str() {
-        let pipe = PipelineOperator::from_str("|").unwrap();
-        assert_eq!(pipe.translate(OsType::Linux), "|");
-        let redirect = PipelineOperator::from_str(">>").unwrap();
-        assert_eq!(redirect.translate(OsType::Windows), ">>");
+    #[cfg(not(target_os = "windows"))]
+    fn test_round_trip_preserves_pipeline() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.translate_reverse("ls -la | grep foo");
+        assert!(result.has_pipeline);
+        assert!(result.final_command.contains(" | "));
+
+        let result = translator.round_trip("ls -la | grep foo");
+        assert!(result.has_pipeline);
+        assert!(result.final_command.starts_with("ls"));
+        assert!(result.final_command.contains("grep"));
     }
 
     #[test]
-    fn test_translation_error_display() {
-        let err = TranslationError::UnknownCommand("foo".to_string());
-        assert!(format!("{err}").contains("Unknown command"));
-        let err = TranslationError::InvalidSyntax("bad".to_string());
-        assert!(format!("{err}").contains("Invalid syntax"));
-    }
+    #[cfg(target_os = "windows")]
+    fn test_round_trip_dir() {
+        let translator = CommandTranslator::new(true);
 
-    // ========== Argument Translator Tests ==========
+        let result = translator.translate_reverse("dir /A");
+        assert!(result.translated);
+        assert!(result.final_command.starts_with("ls"));
+
+        let result = translator.round_trip("dir /A");
+        assert!(result.translated);
+        assert!(result.final_command.starts_with("dir"));
+    }
 
     #[test]
-    fn test_ls_to_dir_args() {
-        // -a flag
-        let result = ls_to_dir_args("-a");
-        assert!(result.contains("/A"));
+    fn test_round_trip_untranslatable() {
+        let translator = CommandTranslator::new(true);
 
-        // -R flag
-        let result = ls_to_dir_args("-R");
-        assert!(result.contains("/S"));
+        let result = translator.round_trip("notarealcommand --flag");
+        assert!(!result.translated);
+        assert_eq!(result.final_command, "notarealcommand --flag");
+    }
 
-        // -1 flag
-        let result = ls_to_dir_args("-1");
-        assert!(result.contains("/B"));
+    // ========== Environment Expansion Tests ==========
 
-        // Combined flags
-        let result = ls_to_dir_args("-laR");
-        assert!(result.contains("/A"));
-        assert!(result.contains("/S"));
+    #[test]
+    fn test_expand_unix_env() {
+        std::env::set_var("CMDX_TEST_UNIX_HOME", "/home/tester");
 
-        // With path
-        let result = ls_to_dir_args("-la /home");
-        assert!(result.contains("/A"));
-        assert!(result.contains("/home"));
+        assert_eq!(
+            expand_unix_env("cat $CMDX_TEST_UNIX_HOME/.bashrc"),
+            "cat /home/tester/.bashrc"
+        );
+        assert_eq!(
+            expand_unix_env("cat ${CMDX_TEST_UNIX_HOME}/x"),
+            "cat /home/tester/x"
+        );
+        assert_eq!(
+            expand_unix_env("echo \"$CMDX_TEST_UNIX_HOME\""),
+            "echo \"/home/tester\""
+        );
+        // Single-quoted references are not expanded
+        assert_eq!(
+            expand_unix_env("echo '$CMDX_TEST_UNIX_HOME'"),
+            "echo '$CMDX_TEST_UNIX_HOME'"
+        );
+        // Unknown variables are left verbatim
+        assert_eq!(
+            expand_unix_env("export A=$CMDX_TEST_UNSET_VAR ${CMDX_TEST_UNSET_VAR}"),
+            "export A=$CMDX_TEST_UNSET_VAR ${CMDX_TEST_UNSET_VAR}"
+        );
+        assert_eq!(expand_unix_env("echo $ 5$"), "echo $ 5$");
     }
 
     #[test]
-    fn test_dir_to_ls_args() {
-        // /A flag
-        let result = dir_to_ls_args("/A");
-        assert!(result.contains("-a"));
+    fn test_expand_windows_env() {
+        std::env::set_var("CMDX_TEST_WIN_PROFILE", "C:\\Users\\tester");
 
-        // /S flag
-        let result = dir_to_ls_args("/S");
-        assert!(result.contains("-R"));
+        assert_eq!(
+            expand_windows_env("echo %CMDX_TEST_WIN_PROFILE%"),
+            "echo C:\\Users\\tester"
+        );
+        assert_eq!(
+            expand_windows_env("type %CMDX_TEST_UNSET_VAR%\\a.txt"),
+            "type %CMDX_TEST_UNSET_VAR%\\a.txt"
+        );
+        assert_eq!(expand_windows_env("echo 50% off"), "echo 50% off");
+    }
 
-        // /B flag
-        let result = dir_to_ls_args("/B");
-        assert!(result.contains("-1"));
+    #[test]
+    fn test_expand_env_disabled_by_default() {
+        std::env::set_var("CMDX_TEST_DEFAULT_OFF", "value");
+        let translator = CommandTranslator::new(true);
+        assert!(!translator.expands_env());
 
-        // Case insensitive
-        let result = dir_to_ls_args("/a");
-        assert!(result.contains("-a"));
+        let result = translator.translate("echo %CMDX_TEST_DEFAULT_OFF% $CMDX_TEST_DEFAULT_OFF");
+        assert!(!result.final_command.contains("value"));
     }
 
     #[test]
-    fn test_rm_to_del_args() {
-        // -r flag
-        let result = rm_to_del_args("-r folder");
-        assert!(result.contains("/S"));
-        assert!(result.contains("folder"));
+    #[cfg(not(target_os = "windows"))]
+    fn test_expand_env_windows_origin() {
+        std::env::set_var("CMDX_TEST_WIN_DIR", "/tmp/cmdx");
+        let mut translator = CommandTranslator::new(true);
+        translator.set_expand_env(true);
 
-        // -f flag
-        let result = rm_to_del_args("-f file.txt");
-        assert!(result.contains("/F"));
-        assert!(result.contains("/Q"));
+        let result = translator.translate("dir %CMDX_TEST_WIN_DIR%");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "ls /tmp/cmdx");
 
-        // Combined -rf
-        let result = rm_to_del_args("-rf folder");
-        assert!(result.contains("/S"));
-        assert!(result.contains("/F"));
+        // Linux-style references are not expanded for Windows-origin commands
+        let result = translator.translate("dir $CMDX_TEST_WIN_DIR");
+        assert_eq!(result.final_command, "ls $CMDX_TEST_WIN_DIR");
     }
 
     #[test]
-    fn test_del_to_rm_args() {
-        // /S flag
-        let result = del_to_rm_args("/S folder");
-        assert!(result.contains("-r"));
-
-        // /F flag
-        let result = del_to_rm_args("/F file.txt");
-        assert!(result.contains("-f"));
+    #[cfg(target_os = "windows")]
+    fn test_expand_env_linux_origin() {
+        std::env::set_var("CMDX_TEST_LINUX_DIR", "C:\\cmdx");
+        let mut translator = CommandTranslator::new(true);
+        translator.set_expand_env(true);
 
-        // /P flag
-        let result = del_to_rm_args("/P file.txt");
-        assert!(result.contains("-i"));
+        let result = translator.translate("cat $CMDX_TEST_LINUX_DIR\\a.txt");
+        assert!(result.translated);
+        assert!(result.final_command.contains("C:\\cmdx\\a.txt"));
     }
 
-    #[test]
-    fn test_grep_to_findstr_args() {
-        // -i flag
-        let result = grep_to_findstr_args("-i \"pattern\" file.txt");
-        assert!(result.contains("/I"));
+    // ========== Archive Tests ==========
 
-        // -n flag
-        let result = grep_to_findstr_args("-n \"pattern\" file.txt");
-        assert!(result.contains("/N"));
+    #[test]
+    fn test_parse_tar_args() {
+        let tar = parse_tar_args("-czvf out.tar.gz src docs");
+        assert_eq!(tar.mode, Some('c'));
+        assert_eq!(tar.compression, Some('z'));
+        assert!(tar.verbose);
+        assert_eq!(tar.file, Some("out.tar.gz"));
+        assert_eq!(tar.paths, vec!["src", "docs"]);
+
+        // Old-style bundled flags and long options parse the same way
+        assert_eq!(parse_tar_args("czvf out.tar.gz src docs"), tar);
+        assert_eq!(
+            parse_tar_args("--create --gzip --verbose --file=out.tar.gz src docs"),
+            tar
+        );
+    }
 
-        // -r flag
-        let result = grep_to_findstr_args("-r \"pattern\" .");
-        assert!(result.contains("/S"));
+    #[test]
+    fn test_tar_args_create() {
+        assert_eq!(tar_args("-c -z -f out.tar.gz dir"), " -czf out.tar.gz dir");
+        assert_eq!(tar_args("cjf out.tar.bz2 dir"), " -cjf out.tar.bz2 dir");
+        assert_eq!(
+            tar_to_windows_args("-czf out.tar.gz dir"),
+            "tar -czf out.tar.gz dir"
+        );
     }
 
     #[test]
-    fn test_findstr_to_grep_args() {
-        // /I flag
-        let result = findstr_to_grep_args("/I \"pattern\" file.txt");
-        assert!(result.contains("-i"));
+    fn test_tar_args_extract() {
+        assert_eq!(
+            tar_args("-xzvf archive.tar.gz -C /tmp/out"),
+            " -xzvf archive.tar.gz -C /tmp/out"
+        );
+        assert_eq!(
+            tar_args("--extract --file archive.tar --directory=out"),
+            " -xf archive.tar -C out"
+        );
+    }
 
-        // /N flag
-        let result = findstr_to_grep_args("/N \"pattern\" file.txt");
-        assert!(result.contains("-n"));
+    #[test]
+    fn test_tar_args_list() {
+        assert_eq!(tar_args("-tf archive.tar"), " -tf archive.tar");
+        assert_eq!(tar_args("--list --verbose -f a.tgz"), " -tvf a.tgz");
+    }
 
-        // /S flag
-        let result = findstr_to_grep_args("/S \"pattern\"");
-        assert!(result.contains("-r"));
+    #[test]
+    fn test_tar_args_preserves_unknown_flags() {
+        assert_eq!(
+            tar_args("-cf out.tar --exclude=*.o src"),
+            " -cf out.tar --exclude=*.o src"
+        );
     }
 
     #[test]
-    fn test_ping_args_translation() {
-        // Linux -c to Windows -n
-        let result = ping_linux_to_windows_args("-c 4 google.com");
-        assert!(result.contains("-n 4"));
-        assert!(result.contains("google.com"));
+    fn test_tar_args_keeps_only_unknown_bundled_letters() {
+        // `p` isn't translated; the letters that are aren't repeated
+        assert_eq!(tar_args("-cvpzf backup.tgz /"), " -czvf backup.tgz -p /");
+        let tar = parse_tar_args("-cvpzf backup.tgz /");
+        assert_eq!(tar.other_flags, vec![Cow::Borrowed("-p")]);
+        assert_eq!(tar.paths, vec!["/"]);
+    }
 
-        // Windows -n to Linux -c
-        let result = ping_windows_to_linux_args("-n 4 google.com");
-        assert!(result.contains("-c 4"));
+    #[test]
+    fn test_tar_to_windows_zip_fallback() {
+        assert_eq!(
+            tar_to_windows_args("-czf out.zip a b"),
+            "powershell Compress-Archive -Path a,b -DestinationPath out.zip"
+        );
+        assert_eq!(
+            tar_to_windows_args("-xf out.zip -C dest"),
+            "powershell Expand-Archive -Path out.zip -DestinationPath dest"
+        );
     }
 
     #[test]
-    fn test_kill_to_taskkill_args() {
-        // -9 flag
-        let result = kill_to_taskkill_args("-9 1234");
-        assert!(result.contains("/F"));
-        assert!(result.contains("/PID"));
-        assert!(result.contains("1234"));
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_tar() {
+        let translator = CommandTranslator::new(true);
 
-        // Without -9
-        let result = kill_to_taskkill_args("5678");
-        assert!(result.contains("/PID"));
-        assert!(result.contains("5678"));
-        assert!(!result.contains("/F"));
+        let result = translator.translate("tar czf out.tar.gz dir");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "tar -czf out.tar.gz dir");
     }
 
     #[test]
-    fn test_taskkill_to_kill_args() {
-        // /F /PID flags
-        let result = taskkill_to_kill_args("/F /PID 1234");
-        assert!(result.contains("-9"));
-        assert!(result.contains("1234"));
+    #[cfg(not(target_os = "windows"))]
+    fn test_windows_to_linux_tar() {
+        let translator = CommandTranslator::new(true);
 
-        // Without /F
-        let result = taskkill_to_kill_args("/PID 5678");
-        assert!(result.contains("5678"));
-        assert!(!result.contains("-9"));
+        let result = translator.translate("tar -x -f archive.tar");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "tar -xf archive.tar");
     }
 
-    #[test]
-    fn test_sort_args_translation() {
-        // Linux -r to Windows /R
-        let result = sort_linux_to_windows_args("-r file.txt");
-        assert!(result.contains("/R"));
-        assert!(result.contains("file.txt"));
+    // ========== Explanation Tests ==========
 
-        // Windows /R to Linux -r
-        let result = sort_windows_to_linux_args("/R file.txt");
-        assert!(result.contains("-r"));
+    #[test]
+    fn test_rm_to_del_args_explained() {
+        let mut explanations = Vec::new();
+        let result = rm_to_del_args_explained("-rf folder", &mut explanations);
+        assert_eq!(result, " /S /F /Q folder");
+        assert_eq!(
+            explanations,
+            vec!["-r → /S (recursive)", "-f → /F /Q (force, quiet)"]
+        );
     }
 
     #[test]
-    fn test_head_to_ps_args() {
-        // Default (no -n flag)
-        let result = head_to_ps_args("file.txt");
-        assert!(result.contains("-Head 10"));
-        assert!(result.contains("file.txt"));
+    fn test_ls_to_dir_args_explained() {
+        let mut explanations = Vec::new();
+        let result = ls_to_dir_args_explained("-aS", &mut explanations);
+        assert_eq!(result, ls_to_dir_args("-aS"));
+        assert_eq!(
+            explanations,
+            vec![
+                "-a → /A (show hidden files)",
+                "-S/-t/-X → /O-S (sort order)"
+            ]
+        );
+    }
 
-        // With -n flag
-        let result = head_to_ps_args("-n 20 file.txt");
-        assert!(result.contains("-Head 20"));
+    #[test]
+    fn test_explanations_disabled_by_default() {
+        let translator = CommandTranslator::new(true);
+        assert!(!translator.explains());
+        assert!(translator
+            .translate("rm -rf folder")
+            .explanations
+            .is_empty());
+        assert!(translator
+            .translate("del /S folder")
+            .explanations
+            .is_empty());
     }
 
     #[test]
-    fn test_tail_to_ps_args() {
-        // Default (no -n flag)
-        let result = tail_to_ps_args("file.txt");
-        assert!(result.contains("-Tail 10"));
+    #[cfg(target_os = "windows")]
+    fn test_translate_with_explanations() {
+        let translator = CommandTranslator::new(true).with_explanations(true);
 
-        // With -f flag
-        let result = tail_to_ps_args("-f file.txt");
-        assert!(result.contains("-Wait"));
+        let result = translator.translate("rm -rf folder");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "del /S /F /Q folder");
+        assert_eq!(
+            result.explanations,
+            vec!["-r → /S (recursive)", "-f → /F /Q (force, quiet)"]
+        );
     }
 
-    // ========== Command Mapping Coverage Tests ==========
-
     #[test]
-    fn test_linux_to_windows_map_coverage() {
-        // Verify essential commands are in the map
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ls"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("cat"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("rm"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("cp"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("mv"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("mkdir"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("rmdir"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("grep"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("head"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("tail"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ping"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("curl"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("wget"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("ps"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("kill"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("killall"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("df"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("du"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("tar"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("zip"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("unzip"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("find"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("sort"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("netstat"));
-        assert!(LINUX_TO_WINDOWS_MAP.contains_key("traceroute"));
+    #[cfg(not(target_os = "windows"))]
+    fn test_translate_with_explanations() {
+        let translator = CommandTranslator::new(true).with_explanations(true);
+
+        let result = translator.translate("del /S /Q folder");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "rm -r -f folder");
+        assert_eq!(
+            result.explanations,
+            vec!["/S → -r (recursive)", "/F /Q → -f (force, quiet)"]
+        );
+
+        // Reverse direction uses the Linux -> Windows explainers
+        let result = translator.translate_reverse("rm -rf folder");
+        assert_eq!(result.final_command, "del /S /F /Q folder");
+        assert_eq!(
+            result.explanations,
+            vec!["-r → /S (recursive)", "-f → /F /Q (force, quiet)"]
+        );
+
+        // Explanations are collected across pipeline segments
+        let result = translator.translate_reverse("rm -rf a && ls -a");
+        assert_eq!(result.explanations.len(), 3);
     }
 
+    // ========== Permission Tests ==========
+
     #[test]
-    fn test_windows_to_linux_map_coverage() {
-        // Verify essential commands are in the map
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("dir"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("type"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("del"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("copy"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("move"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("md"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("rd"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("findstr"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("find"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("ping"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("ipconfig"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("tasklist"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("taskkill"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("xcopy"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("sort"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("netstat"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("tracert"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("cls"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("where"));
-        assert!(WINDOWS_TO_LINUX_MAP.contains_key("hostname"));
+    fn test_chmod_octal_to_icacls() {
+        assert_eq!(
+            chmod_to_icacls_args("755 script.sh"),
+            " script.sh /grant %USERNAME%:(M) /grant Users:(RX) /grant Everyone:(RX)"
+        );
+        // Read-write vs read-only owner
+        assert_eq!(
+            chmod_to_icacls_args("644 notes.txt"),
+            " notes.txt /grant %USERNAME%:(R,W) /grant Users:(R) /grant Everyone:(R)"
+        );
+        assert_eq!(
+            chmod_to_icacls_args("400 key.pem"),
+            " key.pem /grant %USERNAME%:(R) /deny %USERNAME%:(W)"
+        );
+        assert!(chmod_to_icacls_errors("755 script.sh").is_empty());
     }
 
     #[test]
-    fn test_identity_args() {
-        assert_eq!(identity_args("test"), "test");
-        assert_eq!(identity_args(""), "");
-        assert_eq!(identity_args("multiple args here"), "multiple args here");
+    fn test_chmod_symbolic_to_icacls() {
+        assert_eq!(
+            chmod_to_icacls_args("+x script.sh"),
+            " script.sh /grant %USERNAME%:(X)"
+        );
+        assert_eq!(
+            chmod_to_icacls_args("go-w file"),
+            " file /deny Users:(W) /deny Everyone:(W)"
+        );
+        assert_eq!(
+            chmod_to_icacls_args("a=r file"),
+            " file /grant:r Everyone:(R)"
+        );
+        assert!(chmod_to_icacls_errors("u+x,g-w file").is_empty());
     }
 
-    // ========== Pipeline Tests ==========
-
     #[test]
-    fn test_pipeline_detection() {
-        let translator = CommandTranslator::new(true);
+    fn test_chmod_recursive_and_multiple_files() {
+        assert_eq!(
+            chmod_to_icacls_args("-R u+w a b"),
+            " a /grant %USERNAME%:(W) /T && icacls b /grant %USERNAME%:(W) /T"
+        );
+    }
 
-        // Commands with pipes
-        assert!(translator.contains_pipeline_operators("ls | grep foo"));
-        assert!(translator.contains_pipeline_operators("cat file.txt | sort | uniq"));
+    #[test]
+    fn test_chmod_unmappable_modes() {
+        let (args, errors) = chmod_to_icacls("--reference=other file");
+        assert_eq!(args, " file");
+        assert!(matches!(
+            errors.as_slice(),
+            [TranslationError::PartialTranslation(_)]
+        ));
 
-        // Commands with redirects
-        assert!(translator.contains_pipeline_operators("echo hello > file.txt"));
-        assert!(translator.contains_pipeline_operators("cat < input.txt"));
-        assert!(translator.contains_pipeline_operators("echo hello >> file.txt"));
+        let (args, errors) = chmod_to_icacls("999 file");
+        assert_eq!(args, " file");
+        assert_eq!(errors.len(), 1);
 
-        // Commands with chaining
-        assert!(translator.contains_pipeline_operators("ls && echo done"));
-        assert!(translator.contains_pipeline_operators("ls || echo failed"));
-        assert!(translator.contains_pipeline_operators("ls; pwd"));
+        // Special bits are dropped with a warning, the rest still maps
+        let (args, errors) = chmod_to_icacls("4755 file");
+        assert!(args.contains("/grant %USERNAME%:(M)"));
+        assert_eq!(errors.len(), 1);
 
-        // Commands without pipelines
-        assert!(!translator.contains_pipeline_operators("ls -la"));
-        assert!(!translator.contains_pipeline_operators("cat file.txt"));
+        let (_, errors) = chmod_to_icacls("u+s file");
+        assert_eq!(errors.len(), 2);
     }
 
     #[test]
-    fn test_pipeline_parsing() {
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_chmod() {
         let translator = CommandTranslator::new(true);
 
-        // Single pipe
-        let segments = translator.parse_pipeline("ls | grep foo");
-        assert_eq!(segments.len(), 2);
-        assert_eq!(segments[0].command, "ls");
-        assert_eq!(segments[0].operator, Some(PipelineOperator::Pipe));
-        assert_eq!(segments[1].command, "grep foo");
-        assert!(segments[1].operator.is_none());
-
-        // Multiple pipes
-        let segments = translator.parse_pipeline("cat file.txt | sort | uniq");
-        assert_eq!(segments.len(), 3);
-        assert_eq!(segments[0].command, "cat file.txt");
-        assert_eq!(segments[1].command, "sort");
-        assert_eq!(segments[2].command, "uniq");
+        let result = translator.translate("chmod 755 script.sh");
+        assert!(result.translated);
+        assert!(result.final_command.starts_with("icacls script.sh /grant"));
+        assert!(result.errors.is_empty());
 
-        // Output redirect
-        let segments = translator.parse_pipeline("echo hello > output.txt");
-        assert_eq!(segments.len(), 2);
-        assert_eq!(segments[0].command, "echo hello");
-        assert_eq!(segments[0].operator, Some(PipelineOperator::RedirectOut));
-        assert_eq!(segments[1].command, "output.txt");
+        let result = translator.translate("chmod +x script.sh");
+        assert_eq!(
+            result.final_command,
+            "icacls script.sh /grant %USERNAME%:(X)"
+        );
 
-        // Command chaining with &&
-        let segments = translator.parse_pipeline("ls && echo done");
-        assert_eq!(segments.len(), 2);
-        assert_eq!(segments[0].command, "ls");
-        assert_eq!(segments[0].operator, Some(PipelineOperator::And));
-        assert_eq!(segments[1].command, "echo done");
+        let result = translator.translate("chmod --reference=a b");
+        assert_eq!(result.final_command, "icacls b");
+        assert_eq!(result.errors.len(), 1);
     }
 
     #[test]
     #[cfg(not(target_os = "windows"))]
-    fn test_pipeline_translation_linux() {
+    fn test_reverse_chmod_reports_errors() {
         let translator = CommandTranslator::new(true);
 
-        // dir | findstr -> ls | grep
-        let result = translator.translate("dir | findstr foo");
+        let result = translator.translate_reverse("chmod 755 script.sh");
         assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("ls"));
-        assert!(result.final_command.contains("|"));
-        assert!(result.final_command.contains("grep"));
+        assert!(result.final_command.starts_with("icacls script.sh /grant"));
+        assert!(result.errors.is_empty());
 
-        // type file.txt | sort -> cat file.txt | sort
-        let result = translator.translate("type file.txt | sort");
-        assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("cat"));
+        let result = translator.translate_reverse("chmod --reference=a b");
+        assert_eq!(result.final_command, "icacls b");
+        assert_eq!(result.errors.len(), 1);
 
-        // Command chaining: cls && dir -> clear && ls
-        let result = translator.translate("cls && dir");
-        assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("clear"));
-        assert!(result.final_command.contains("&&"));
-        assert!(result.final_command.contains("ls"));
+        // Errors surface through pipelines too
+        let result = translator.translate_reverse("chmod 9 a && ls");
+        assert_eq!(result.errors.len(), 1);
     }
 
+    // ========== Find Tests ==========
+
     #[test]
-    #[cfg(target_os = "windows")]
-    fn test_pipeline_translation_windows() {
+    fn test_find_errors() {
+        assert!(find_errors(". -type f -name \"*.rs\"").is_empty());
+        assert!(find_errors(". -type d").is_empty());
+
+        let errors = find_errors(". -mtime -7 -name x");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("-mtime"));
+
+        assert_eq!(find_errors(". -type l -empty").len(), 2);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_reverse_find_type_and_name() {
         let translator = CommandTranslator::new(true);
 
-        // ls | grep -> dir | findstr
-        let result = translator.translate("ls | grep foo");
+        let result = translator.translate_reverse("find . -type f -name \"*.rs\"");
         assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("dir"));
-        assert!(result.final_command.contains("|"));
-        assert!(result.final_command.contains("findstr"));
+        assert_eq!(result.final_command, "dir /S /B /A-D .\\*.rs");
+        assert!(result.errors.is_empty());
 
-        // cat file.txt | sort -> type file.txt | sort
-        let result = translator.translate("cat file.txt | sort");
-        assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("type"));
+        let result = translator.translate_reverse("find src -type d");
+        assert_eq!(result.final_command, "dir /S /B /AD src\\*");
 
-        // Command chaining: clear && ls -> cls && dir
-        let result = translator.translate("clear && ls");
-        assert!(result.translated);
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains("cls"));
-        assert!(result.final_command.contains("&&"));
-        assert!(result.final_command.contains("dir"));
+        // Unsupported predicates are dropped with a warning, values aren't taken as paths
+        let result = translator.translate_reverse("find . -mtime +7 -name *.log");
+        assert_eq!(result.final_command, "dir /S /B .\\*.log");
+        assert_eq!(result.errors.len(), 1);
     }
 
     #[test]
-    fn test_pipeline_with_redirect() {
+    #[cfg(target_os = "windows")]
+    fn test_linux_to_windows_find() {
         let translator = CommandTranslator::new(true);
 
-        // Output redirect should be preserved
-        let result = translator.translate("echo hello > output.txt");
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains(">"));
-        assert!(result.final_command.contains("output.txt"));
+        let result = translator.translate("find . -type f -name \"*.rs\"");
+        assert!(result.translated);
+        assert_eq!(result.final_command, "dir /S /B /A-D .\\*.rs");
+        assert!(result.errors.is_empty());
 
-        // Append redirect
-        let result = translator.translate("echo hello >> output.txt");
-        assert!(result.has_pipeline);
-        assert!(result.final_command.contains(">>"));
+        let result = translator.translate("find . -mtime -1");
+        assert_eq!(result.errors.len(), 1);
     }
 
-    // ========== Error Handling Tests ==========
+    // ========== Script Translation Tests ==========
 
     #[test]
-    fn test_translation_error_display() {
-        let err = TranslationError::UnknownCommand("foo".to_string());
-        assert!(format!("{}", err).contains("Unknown command"));
-        assert!(format!("{}", err).contains("foo"));
+    #[cfg(not(target_os = "windows"))]
+    fn test_translate_script() {
+        let translator = CommandTranslator::new(true);
+        let script =
+            "# setup\ncls\n\n    dir /A | findstr foo\n  # indented comment\nunknowncmd arg\n";
 
-        let err = TranslationError::InvalidSyntax("bad syntax".to_string());
-        assert!(format!("{}", err).contains("Invalid syntax"));
+        let results = translator.translate_script(script);
+        assert_eq!(results.len(), 4);
 
-        let err = TranslationError::UnsupportedOperator(">>>".to_string());
-        assert!(format!("{}", err).contains("Unsupported operator"));
+        assert!(results[0].translated);
+        assert_eq!(results[0].final_command, "clear");
 
-        let err = TranslationError::PartialTranslation("some parts failed".to_string());
-        assert!(format!("{}", err).contains("Partial translation"));
+        // Blank lines are kept as no-op results
+        assert!(!results[1].translated);
+        assert_eq!(results[1].final_command, "");
+
+        // Indentation is preserved and pipelines are translated
+        assert!(results[2].translated);
+        assert!(results[2].has_pipeline);
+        assert!(results[2].final_command.starts_with("    ls -a | grep"));
+        assert_eq!(results[2].original_command, "    dir /A | findstr foo");
+
+        assert!(!results[3].translated);
+        assert_eq!(results[3].final_command, "unknowncmd arg");
     }
 
     #[test]
-    fn test_translation_result_errors() {
+    #[cfg(target_os = "windows")]
+    fn test_translate_script() {
         let translator = CommandTranslator::new(true);
+        let script = "#!/bin/sh\n# list files\nclear\n\n  ls -a | grep foo\n";
 
-        // Unknown command should have no errors (just not translated)
-        let result = translator.translate("unknowncommand");
-        assert!(!result.translated);
-        // The command is passed through, not an error
+        let results = translator.translate_script(script);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].final_command, "cls");
+        assert!(!results[1].translated);
+        assert!(results[2].has_pipeline);
+        assert!(results[2].final_command.starts_with("  dir /A | findstr"));
+    }
 
-        // Valid translation should have no errors
-        #[cfg(not(target_os = "windows"))]
-        {
-            let result = translator.translate("dir");
-            assert!(result.translated);
-            assert!(result.errors.is_empty());
-        }
+    #[test]
+    fn test_translate_script_handles_crlf() {
+        let translator = CommandTranslator::new(true);
+        let results = translator.translate_script("# c\r\nfoo\r\n\r\n");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].final_command, "foo");
+        assert_eq!(results[1].final_command, "");
+    }
 
-        #[cfg(target_os = "windows")]
-        {
-            let result = translator.translate("ls");
-            assert!(result.translated);
-            assert!(result.errors.is_empty());
-        }
+    // ========== Quoted Argument Tests ==========
+
+    #[test]
+    fn test_tokenize_args() {
+        assert_eq!(
+            tokenize_args("-r \"My Documents\" 'other dir' dest"),
+            vec!["-r", "\"My Documents\"", "'other dir'", "dest"]
+        );
+        assert_eq!(tokenize_args("  a   b  "), vec!["a", "b"]);
+        assert_eq!(
+            tokenize_args("\"it's here\" 'say \"hi\"'"),
+            vec!["\"it's here\"", "'say \"hi\"'"]
+        );
+        assert_eq!(
+            tokenize_args("pre\"fix mid\"post x"),
+            vec!["pre\"fix mid\"post", "x"]
+        );
+        assert!(tokenize_args("").is_empty());
     }
 
     #[test]
-    fn test_pipeline_operator_from_str() {
+    fn test_extract_paths_quoted() {
         assert_eq!(
-            PipelineOperator::from_str("|"),
-            Some(PipelineOperator::Pipe)
+            extract_paths("-rf \"old files\" tmp"),
+            vec!["\"old files\"", "tmp"]
         );
         assert_eq!(
-            PipelineOperator::from_str(">"),
-            Some(PipelineOperator::RedirectOut)
+            extract_paths_windows("/S /Q \"C:\\My Documents\""),
+            vec!["\"C:\\My Documents\""]
         );
+    }
+
+    #[test]
+    fn test_quoted_paths_in_translators() {
         assert_eq!(
-            PipelineOperator::from_str(">>"),
-            Some(PipelineOperator::RedirectAppend)
+            cp_to_copy_args("\"My Documents\" dest"),
+            " \"My Documents\" dest"
         );
         assert_eq!(
-            PipelineOperator::from_str("<"),
-            Some(PipelineOperator::RedirectIn)
+            mv_to_move_args("'old name.txt' 'new name.txt'"),
+            " 'old name.txt' 'new name.txt'"
         );
         assert_eq!(
-            PipelineOperator::from_str("&&"),
-            Some(PipelineOperator::And)
+            rm_to_del_args("-f \"my file.txt\""),
+            " /F /Q \"my file.txt\""
         );
-        assert_eq!(PipelineOperator::from_str("||"), Some(PipelineOperator::Or));
+
         assert_eq!(
-            PipelineOperator::from_str(";"),
-            Some(PipelineOperator::Semicolon)
+            copy_to_cp_args("\"My Documents\" dest"),
+            " \"My Documents\" dest"
         );
-        assert_eq!(PipelineOperator::from_str("invalid"), None);
+        assert_eq!(del_to_rm_args("/S \"old files\""), " -r \"old files\"");
     }
 
     #[test]
-    fn test_pipeline_operator_as_str() {
-        assert_eq!(PipelineOperator::Pipe.as_str(), "|");
-        assert_eq!(PipelineOperator::RedirectOut.as_str(), ">");
-        assert_eq!(PipelineOperator::RedirectAppend.as_str(), ">>");
-        assert_eq!(PipelineOperator::RedirectIn.as_str(), "<");
-        assert_eq!(PipelineOperator::And.as_str(), "&&");
-        assert_eq!(PipelineOperator::Or.as_str(), "||");
-        assert_eq!(PipelineOperator::Semicolon.as_str(), ";");
+    #[cfg(not(target_os = "windows"))]
+    fn test_quoted_paths_translation() {
+        let translator = CommandTranslator::new(true);
+
+        let result = translator.translate_reverse("cp \"My Documents\" dest");
+        assert_eq!(result.final_command, "copy \"My Documents\" dest");
+
+        let result = translator.translate_reverse("mv \"a b\" \"c d\"");
+        assert_eq!(result.final_command, "move \"a b\" \"c d\"");
+
+        let result = translator.translate_reverse("rm -rf \"old files\"");
+        assert_eq!(result.final_command, "del /S /F /Q \"old files\"");
+
+        let result = translator.translate("copy \"My Documents\" dest");
+        assert_eq!(result.final_command, "cp \"My Documents\" dest");
     }
 
+    // ========== Known Commands Tests ==========
+
     #[test]
-    fn test_complex_pipeline() {
+    fn test_known_commands_sorted_and_unique() {
         let translator = CommandTranslator::new(true);
+        let commands = translator.known_commands();
 
-        // Complex pipeline with multiple operators
-        let result = translator.translate("echo start; ls | grep test && echo done || echo failed");
-        assert!(result.has_pipeline);
-        // The command should be processed
-        assert!(!result.final_command.is_empty());
+        assert!(commands.windows(2).all(|w| w[0].0 < w[1].0));
     }
 
     #[test]
-    fn test_pipeline_with_quotes() {
+    fn test_known_commands_windows_direction() {
+        let commands = CommandTranslator::known_commands_for(OsType::Windows);
+
+        assert!(commands.contains(&("ls", "List directory contents")));
+        assert_eq!(commands.len(), LINUX_TO_WINDOWS_MAP.len());
+        assert!(CommandTranslator::known_commands_for(OsType::Unknown).is_empty());
+    }
+
+    #[test]
+    fn test_known_commands_macos_direction() {
+        let commands = CommandTranslator::known_commands_for(OsType::MacOs);
+
+        // BSD adjustments are included alongside the Windows -> Linux commands
+        assert!(commands.iter().any(|&(cmd, _)| cmd == "sed"));
+        assert!(commands.iter().any(|&(cmd, _)| cmd == "taskkill"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_known_commands_linux_direction() {
         let translator = CommandTranslator::new(true);
+        let commands = translator.known_commands();
 
-        // Operators inside quotes should not be treated as pipeline operators
-        // Note: This is a simplified test - full quote handling is complex
-        let segments = translator.parse_pipeline("echo 'hello world'");
-        assert_eq!(segments.len(), 1);
-        assert_eq!(segments[0].command, "echo 'hello world'");
+        assert!(commands.contains(&("dir", "List directory contents")));
+        assert_eq!(commands.len(), WINDOWS_TO_LINUX_MAP.len());
+        assert!(!commands.iter().any(|&(cmd, _)| cmd == "ls"));
     }
 }
@@ -0,0 +1,45 @@
+//! Plugin built for a newer major plugin ABI than this Furnace supports
+//!
+//! Furnace checks `_plugin_abi_version` first and refuses to load this plugin,
+//! so `_plugin_create` is never called.
+
+use std::ffi::{c_char, c_void};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Plugin ABI 2.0, as `major << 16 | minor`
+const ABI_VERSION: u32 = 2 << 16;
+
+/// Number of instances created, so tests can check the host never got that far
+#[no_mangle]
+pub static FUTURE_ABI_CREATED: AtomicUsize = AtomicUsize::new(0);
+
+#[no_mangle]
+pub extern "C" fn _plugin_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_create() -> *mut c_void {
+    FUTURE_ABI_CREATED.fetch_add(1, Ordering::SeqCst);
+    Box::into_raw(Box::new(0u8)).cast()
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_handle_command(
+    _plugin: *mut c_void,
+    _command: *const c_char,
+) -> *mut c_char {
+    std::ptr::null_mut()
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_free_string(_reply: *mut c_char) {}
+
+/// # Safety
+/// `plugin` must come from `_plugin_create` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn _plugin_destroy(plugin: *mut c_void) {
+    if !plugin.is_null() {
+        drop(Box::from_raw(plugin.cast::<u8>()));
+    }
+}
@@ -0,0 +1,112 @@
+//! Minimal Furnace plugin
+//!
+//! Build with `cargo build --example hello_world`, then copy the library from
+//! `target/debug/examples/` into the directory set in `config.plugins.directory`.
+//! Typing `hello` (or `hello <name>`) at the prompt is then answered by the plugin;
+//! `hello_world:hello` reaches it even if another plugin also provides `hello`.
+//!
+//! Every exported function catches panics so none unwind into Furnace;
+//! `hello --panic` shows a panicking handler being contained.
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Plugin ABI 1.1, as `major << 16 | minor`
+const ABI_VERSION: u32 = 1 << 16 | 1;
+
+/// Number of plugin instances destroyed so far, so tests can check cleanup
+#[no_mangle]
+pub static HELLO_WORLD_DESTROYED: AtomicUsize = AtomicUsize::new(0);
+
+struct HelloWorld {
+    /// Set by `_plugin_init`; empty until then
+    greeting: String,
+}
+
+impl HelloWorld {
+    fn handle_command(&self, command: &str) -> Option<String> {
+        let mut words = command.split_whitespace();
+        if words.next()? != "hello" {
+            return None;
+        }
+        let name = words.next().unwrap_or("world");
+        if name == "--panic" {
+            panic!("hello_world was asked to panic");
+        }
+        Some(format!("{}, {}!", self.greeting, name))
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_name() -> *const c_char {
+    c"hello_world".as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_commands() -> *const c_char {
+    c"hello".as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_create() -> *mut c_void {
+    let plugin = HelloWorld {
+        greeting: String::new(),
+    };
+    Box::into_raw(Box::new(plugin)).cast()
+}
+
+/// # Safety
+/// `plugin` must come from `_plugin_create`.
+#[no_mangle]
+pub unsafe extern "C" fn _plugin_init(plugin: *mut c_void) -> i32 {
+    let plugin = &mut *plugin.cast::<HelloWorld>();
+    // Initializing twice is a host bug; report it instead of ignoring it
+    if !plugin.greeting.is_empty() {
+        return 1;
+    }
+    plugin.greeting = "Hello".to_string();
+    0
+}
+
+/// # Safety
+/// `plugin` must come from `_plugin_create` and `command` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn _plugin_handle_command(
+    plugin: *mut c_void,
+    command: *const c_char,
+) -> *mut c_char {
+    let plugin = &*plugin.cast::<HelloWorld>();
+    let Ok(command) = CStr::from_ptr(command).to_str() else {
+        return std::ptr::null_mut();
+    };
+    panic::catch_unwind(AssertUnwindSafe(|| plugin.handle_command(command)))
+        .ok()
+        .flatten()
+        .and_then(|reply| CString::new(reply).ok())
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// # Safety
+/// `reply` must come from `_plugin_handle_command`.
+#[no_mangle]
+pub unsafe extern "C" fn _plugin_free_string(reply: *mut c_char) {
+    if !reply.is_null() {
+        drop(CString::from_raw(reply));
+    }
+}
+
+/// # Safety
+/// `plugin` must come from `_plugin_create` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn _plugin_destroy(plugin: *mut c_void) {
+    if !plugin.is_null() {
+        drop(Box::from_raw(plugin.cast::<HelloWorld>()));
+        HELLO_WORLD_DESTROYED.fetch_add(1, Ordering::SeqCst);
+    }
+}
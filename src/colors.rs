@@ -34,6 +34,80 @@ impl TrueColor {
         Ok(Self::new(r, g, b))
     }
 
+    /// Create from a CSS color string
+    ///
+    /// Accepts hex (`#RRGGBB`), `rgb(221, 102, 102)` and `hsl(0, 60%, 63%)`
+    /// syntax. Function names are case-insensitive and surrounding whitespace
+    /// is ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the string is not one of the supported forms or a
+    /// component is out of range
+    pub fn from_css(css: &str) -> Result<Self> {
+        let css = css.trim();
+        let lower = css.to_ascii_lowercase();
+
+        if let Some(body) = Self::css_function_body(&lower, "rgb") {
+            let [r, g, b] = Self::css_components(body, "rgb")?;
+            let channel = |value: &str, name: &str| {
+                value
+                    .parse::<u8>()
+                    .with_context(|| format!("Invalid {name} component in rgb(): '{value}'"))
+            };
+            Ok(Self::new(
+                channel(r, "red")?,
+                channel(g, "green")?,
+                channel(b, "blue")?,
+            ))
+        } else if let Some(body) = Self::css_function_body(&lower, "hsl") {
+            let [h, s, l] = Self::css_components(body, "hsl")?;
+            let hue = h
+                .trim_end_matches("deg")
+                .parse::<f32>()
+                .with_context(|| format!("Invalid hue in hsl(): '{h}'"))?;
+            let percent = |value: &str, name: &str| -> Result<f32> {
+                let number = value
+                    .strip_suffix('%')
+                    .with_context(|| format!("{name} in hsl() must be a percentage: '{value}'"))?
+                    .trim()
+                    .parse::<f32>()
+                    .with_context(|| format!("Invalid {name} in hsl(): '{value}'"))?;
+                if !(0.0..=100.0).contains(&number) {
+                    anyhow::bail!("{name} in hsl() must be between 0% and 100%: '{value}'");
+                }
+                Ok(number / 100.0)
+            };
+            Ok(Self::from_hsl(
+                hue,
+                percent(s, "Saturation")?,
+                percent(l, "Lightness")?,
+            ))
+        } else if css.starts_with('#') || css.chars().all(|c| c.is_ascii_hexdigit()) {
+            Self::from_hex(css)
+        } else {
+            anyhow::bail!("Unrecognized color '{css}': expected #RRGGBB, rgb() or hsl()")
+        }
+    }
+
+    /// Return the argument list of `name(...)`, if `css` has that form
+    fn css_function_body<'a>(css: &'a str, name: &str) -> Option<&'a str> {
+        css.strip_prefix(name)?
+            .trim_start()
+            .strip_prefix('(')?
+            .strip_suffix(')')
+    }
+
+    /// Split a CSS function argument list into exactly three components
+    fn css_components<'a>(body: &'a str, name: &str) -> Result<[&'a str; 3]> {
+        let parts: Vec<&str> = body.split(',').map(str::trim).collect();
+        match parts.as_slice() {
+            [a, b, c] if !a.is_empty() && !b.is_empty() && !c.is_empty() => Ok([a, b, c]),
+            _ => anyhow::bail!(
+                "Invalid {name}() color: expected 3 comma-separated components, got '{body}'"
+            ),
+        }
+    }
+
     /// Convert to hex string
     #[must_use]
     pub fn to_hex(self) -> String {
@@ -107,6 +181,103 @@ impl TrueColor {
         }
     }
 
+    /// Generate `steps` evenly spaced colors from `start` to `end`
+    ///
+    /// Interpolation is done in RGB space with [`blend`](Self::blend), and
+    /// both endpoints are included. A single step yields just `start`, and
+    /// zero steps yield an empty vector.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let ramp = TrueColor::gradient(TrueColor::new(0, 0, 0), TrueColor::new(255, 255, 255), 3);
+    /// // [#000000, #808080, #FFFFFF]
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn gradient(start: Self, end: Self, steps: usize) -> Vec<Self> {
+        match steps {
+            0 => Vec::new(),
+            1 => vec![start],
+            _ => {
+                let last = (steps - 1) as f32;
+                (0..steps)
+                    .map(|i| start.blend(end, i as f32 / last))
+                    .collect()
+            }
+        }
+    }
+
+    /// Convert to HSL as `(hue, saturation, lightness)`
+    ///
+    /// Hue is in degrees `[0, 360)`, saturation and lightness in `[0, 1]`.
+    /// Grayscale colors report a hue and saturation of 0.
+    #[must_use]
+    #[allow(dead_code)] // Public API for future use
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let r = f32::from(self.r) / 255.0;
+        let g = f32::from(self.g) / 255.0;
+        let b = f32::from(self.b) / 255.0;
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+
+        if delta == 0.0 {
+            return (0.0, 0.0, l);
+        }
+
+        let s = delta / (1.0 - 2.0f32.mul_add(l, -1.0).abs());
+        let h = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (h, s, l)
+    }
+
+    /// Create from HSL components
+    ///
+    /// Hue is in degrees and wraps around; saturation and lightness are
+    /// clamped to `[0, 1]`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let h = h.rem_euclid(360.0);
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - 2.0f32.mul_add(l, -1.0).abs()) * s;
+        let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+        let m = l - c / 2.0;
+
+        let (r, g, b) = match h {
+            h if h < 60.0 => (c, x, 0.0),
+            h if h < 120.0 => (x, c, 0.0),
+            h if h < 180.0 => (0.0, c, x),
+            h if h < 240.0 => (0.0, x, c),
+            h if h < 300.0 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        // Note: Casts are safe because components are clamped to [0, 1] before scaling
+        let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Self::new(to_u8(r), to_u8(g), to_u8(b))
+    }
+
+    /// Rotate hue by `degrees`, keeping saturation and lightness
+    ///
+    /// `rotate_hue(180.0)` yields the complementary color.
+    #[must_use]
+    #[allow(dead_code)] // Public API for future use
+    pub fn rotate_hue(self, degrees: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h + degrees, s, l)
+    }
+
     /// Lighten color by factor
     #[must_use]
     pub fn lighten(self, factor: f32) -> Self {
@@ -140,6 +311,48 @@ impl TrueColor {
     pub fn is_light(self) -> bool {
         self.luminance() > 0.5
     }
+
+    /// Get WCAG 2.1 relative luminance (0.0 - 1.0)
+    ///
+    /// Unlike [`luminance`](Self::luminance), this linearizes each sRGB
+    /// channel before weighting, as required for contrast calculations.
+    #[must_use]
+    pub fn relative_luminance(self) -> f32 {
+        fn linearize(channel: u8) -> f32 {
+            let c = f32::from(channel) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        0.2126f32.mul_add(
+            linearize(self.r),
+            0.7152f32.mul_add(linearize(self.g), 0.0722 * linearize(self.b)),
+        )
+    }
+
+    /// Get WCAG 2.1 contrast ratio against another color (1.0 - 21.0)
+    ///
+    /// The result is symmetric: `a.contrast_ratio(&b) == b.contrast_ratio(&a)`.
+    #[must_use]
+    pub fn contrast_ratio(&self, other: &Self) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        let (lighter, darker) = if a >= b { (a, b) } else { (b, a) };
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    /// Check if this color, used as foreground on `bg`, meets WCAG AA
+    ///
+    /// AA requires a contrast ratio of at least 4.5:1 for normal text and
+    /// 3:1 for large text (18pt, or 14pt bold).
+    #[must_use]
+    pub fn meets_wcag_aa(&self, bg: &Self, large_text: bool) -> bool {
+        let required = if large_text { 3.0 } else { 4.5 };
+        self.contrast_ratio(bg) >= required
+    }
 }
 
 impl fmt::Display for TrueColor {
@@ -202,28 +415,85 @@ impl TrueColorPalette {
         }
     }
 
+    /// Create a light palette for use on a white (`bright_white`) background
+    ///
+    /// Every other color meets WCAG AA (4.5:1) as text on white, so even
+    /// `white` is a mid gray here.
+    #[must_use]
+    pub fn default_light() -> Self {
+        Self {
+            black: TrueColor::new(0x1E, 0x1E, 0x1E), // #1E1E1E - Near black
+            red: TrueColor::new(0xB3, 0x26, 0x1E),   // #B3261E - Brick red
+            green: TrueColor::new(0x1E, 0x7B, 0x34), // #1E7B34 - Forest green
+            yellow: TrueColor::new(0x7A, 0x5C, 0x00), // #7A5C00 - Dark ochre
+            blue: TrueColor::new(0x0B, 0x57, 0xD0),  // #0B57D0 - Strong blue
+            magenta: TrueColor::new(0x8E, 0x24, 0xAA), // #8E24AA - Purple
+            cyan: TrueColor::new(0x00, 0x70, 0x7A),  // #00707A - Deep teal
+            white: TrueColor::new(0x5F, 0x63, 0x68), // #5F6368 - Mid gray
+
+            bright_black: TrueColor::new(0x4A, 0x4A, 0x4A), // #4A4A4A - Dark gray
+            bright_red: TrueColor::new(0xC5, 0x22, 0x1F),   // #C5221F - Red
+            bright_green: TrueColor::new(0x13, 0x73, 0x33), // #137333 - Green
+            bright_yellow: TrueColor::new(0x8A, 0x61, 0x00), // #8A6100 - Amber
+            bright_blue: TrueColor::new(0x1A, 0x5F, 0xD6),  // #1A5FD6 - Bright blue
+            bright_magenta: TrueColor::new(0xA1, 0x33, 0x9B), // #A1339B - Orchid
+            bright_cyan: TrueColor::new(0x00, 0x7B, 0x83),  // #007B83 - Teal
+            bright_white: TrueColor::new(0xFF, 0xFF, 0xFF), // #FFFFFF - Background
+
+            extended: Self::generate_256_palette(),
+        }
+    }
+
+    /// Create a high-contrast palette for use on a black (`black`) background
+    ///
+    /// Every other color reaches WCAG AAA (7:1) as text on black.
+    #[must_use]
+    pub fn default_high_contrast() -> Self {
+        Self {
+            black: TrueColor::new(0x00, 0x00, 0x00), // #000000 - Background
+            red: TrueColor::new(0xFF, 0x6B, 0x6B),   // #FF6B6B - Light red
+            green: TrueColor::new(0x4C, 0xFF, 0x4C), // #4CFF4C - Green
+            yellow: TrueColor::new(0xFF, 0xFF, 0x00), // #FFFF00 - Yellow
+            blue: TrueColor::new(0x6C, 0xB6, 0xFF),  // #6CB6FF - Sky blue
+            magenta: TrueColor::new(0xFF, 0x7A, 0xFF), // #FF7AFF - Pink
+            cyan: TrueColor::new(0x00, 0xFF, 0xFF),  // #00FFFF - Cyan
+            white: TrueColor::new(0xE0, 0xE0, 0xE0), // #E0E0E0 - Light gray
+
+            bright_black: TrueColor::new(0xA8, 0xA8, 0xA8), // #A8A8A8 - Gray
+            bright_red: TrueColor::new(0xFF, 0x90, 0x90),   // #FF9090 - Salmon
+            bright_green: TrueColor::new(0x90, 0xFF, 0x90), // #90FF90 - Mint
+            bright_yellow: TrueColor::new(0xFF, 0xFF, 0x90), // #FFFF90 - Pale yellow
+            bright_blue: TrueColor::new(0xA0, 0xD0, 0xFF),  // #A0D0FF - Pale blue
+            bright_magenta: TrueColor::new(0xFF, 0xA8, 0xFF), // #FFA8FF - Pale pink
+            bright_cyan: TrueColor::new(0xA0, 0xFF, 0xFF),  // #A0FFFF - Pale cyan
+            bright_white: TrueColor::new(0xFF, 0xFF, 0xFF), // #FFFFFF - White
+
+            extended: Self::generate_256_palette(),
+        }
+    }
+
     /// Create palette from theme config's AnsiColors
     ///
     /// # Errors
-    /// Returns an error if any color string is not a valid hex color
+    /// Returns an error if any color string is not a valid CSS color
     pub fn from_ansi_colors(colors: &crate::config::AnsiColors) -> Result<Self> {
         Ok(Self {
-            black: TrueColor::from_hex(&colors.black)?,
-            red: TrueColor::from_hex(&colors.red)?,
-            green: TrueColor::from_hex(&colors.green)?,
-            yellow: TrueColor::from_hex(&colors.yellow)?,
-            blue: TrueColor::from_hex(&colors.blue)?,
-            magenta: TrueColor::from_hex(&colors.magenta)?,
-            cyan: TrueColor::from_hex(&colors.cyan)?,
-            white: TrueColor::from_hex(&colors.white)?,
-            bright_black: TrueColor::from_hex(&colors.bright_black)?,
-            bright_red: TrueColor::from_hex(&colors.bright_red)?,
-            bright_green: TrueColor::from_hex(&colors.bright_green)?,
-            bright_yellow: TrueColor::from_hex(&colors.bright_yellow)?,
-            bright_blue: TrueColor::from_hex(&colors.bright_blue)?,
-            bright_magenta: TrueColor::from_hex(&colors.bright_magenta)?,
-            bright_cyan: TrueColor::from_hex(&colors.bright_cyan)?,
-            bright_white: TrueColor::from_hex(&colors.bright_white)?,
+            black: TrueColor::from_css(&colors.black)?,
+            red: TrueColor::from_css(&colors.red)?,
+            green: TrueColor::from_css(&colors.green)?,
+            yellow: TrueColor::from_css(&colors.yellow)?,
+            blue: TrueColor::from_css(&colors.blue)?,
+            magenta: TrueColor::from_css(&colors.magenta)?,
+            cyan: TrueColor::from_css(&colors.cyan)?,
+            white: TrueColor::from_css(&colors.white)?,
+            bright_black: TrueColor::from_css(&colors.bright_black)?,
+            bright_red: TrueColor::from_css(&colors.bright_red)?,
+            bright_green: TrueColor::from_css(&colors.bright_green)?,
+            bright_yellow: TrueColor::from_css(&colors.bright_yellow)?,
+            bright_blue: TrueColor::from_css(&colors.bright_blue)?,
+            bright_magenta: TrueColor::from_css(&colors.bright_magenta)?,
+            bright_cyan: TrueColor::from_css(&colors.bright_cyan)?,
+            bright_white: TrueColor::from_css(&colors.bright_white)?,
             extended: Self::generate_256_palette(),
         })
     }
@@ -286,6 +556,26 @@ impl TrueColorPalette {
                 .unwrap_or(TrueColor::new(0, 0, 0)),
         }
     }
+
+    /// Map an arbitrary color to the nearest xterm-256 palette index
+    ///
+    /// Only the fixed 6x6x6 cube (16-231) and grayscale ramp (232-255) are
+    /// considered, since the first 16 colors depend on the theme. Ties go to
+    /// the lower index, so exact cube colors map to their canonical index.
+    #[must_use]
+    #[allow(dead_code)] // Public API for future use
+    pub fn to_ansi256(&self, color: TrueColor) -> u8 {
+        let distance = |other: TrueColor| {
+            let dr = i32::from(color.r) - i32::from(other.r);
+            let dg = i32::from(color.g) - i32::from(other.g);
+            let db = i32::from(color.b) - i32::from(other.b);
+            dr * dr + dg * dg + db * db
+        };
+
+        (16..=255u8)
+            .min_by_key(|&index| distance(self.get_256(index)))
+            .unwrap_or(16)
+    }
 }
 
 #[cfg(test)]
@@ -320,6 +610,60 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_from_css_hex() {
+        assert_eq!(
+            TrueColor::from_css("#DD6666").unwrap(),
+            TrueColor::new(0xDD, 0x66, 0x66)
+        );
+        assert_eq!(
+            TrueColor::from_css("  dd6666 ").unwrap(),
+            TrueColor::new(0xDD, 0x66, 0x66)
+        );
+    }
+
+    #[test]
+    fn test_from_css_rgb() {
+        let expected = TrueColor::new(221, 102, 102);
+        assert_eq!(TrueColor::from_css("rgb(221, 102, 102)").unwrap(), expected);
+        assert_eq!(TrueColor::from_css("rgb(221,102,102)").unwrap(), expected);
+        assert_eq!(
+            TrueColor::from_css("  RGB ( 221 ,  102 , 102 )  ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_from_css_hsl() {
+        assert_eq!(
+            TrueColor::from_css("hsl(0, 100%, 50%)").unwrap(),
+            TrueColor::new(255, 0, 0)
+        );
+        assert_eq!(
+            TrueColor::from_css(" hsl( 120deg , 100% , 25% ) ").unwrap(),
+            TrueColor::new(0, 128, 0)
+        );
+        let color = TrueColor::from_css("hsl(0, 60%, 63%)").unwrap();
+        assert_eq!(color, TrueColor::new(217, 104, 104));
+    }
+
+    #[test]
+    fn test_from_css_errors() {
+        let err = TrueColor::from_css("rgb(256, 0, 0)").unwrap_err();
+        assert!(format!("{err:#}").contains("red"));
+
+        let err = TrueColor::from_css("rgb(1, 2)").unwrap_err();
+        assert!(err.to_string().contains("3 comma-separated components"));
+
+        let err = TrueColor::from_css("hsl(0, 50, 50%)").unwrap_err();
+        assert!(err.to_string().contains("percentage"));
+
+        assert!(TrueColor::from_css("hsl(0, 150%, 50%)").is_err());
+        assert!(TrueColor::from_css("rgb(1, 2, 3").is_err());
+        assert!(TrueColor::from_css("red").is_err());
+        assert!(TrueColor::from_css("").is_err());
+    }
+
     #[test]
     fn test_true_color_to_hex() {
         let color = TrueColor::new(255, 136, 0);
@@ -378,6 +722,90 @@ mod tests {
         assert!((black.luminance() - 0.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_gradient_endpoints_and_midpoint() {
+        let black = TrueColor::new(0, 0, 0);
+        let white = TrueColor::new(255, 255, 255);
+        let ramp = TrueColor::gradient(black, white, 5);
+
+        assert_eq!(ramp.len(), 5);
+        assert_eq!(ramp[0], black);
+        assert_eq!(ramp[4], white);
+        assert_eq!(ramp[2], TrueColor::new(128, 128, 128));
+        assert!(ramp.windows(2).all(|w| w[0].r < w[1].r));
+    }
+
+    #[test]
+    fn test_gradient_edge_cases() {
+        let red = TrueColor::new(255, 0, 0);
+        let blue = TrueColor::new(0, 0, 255);
+
+        assert!(TrueColor::gradient(red, blue, 0).is_empty());
+        assert_eq!(TrueColor::gradient(red, blue, 1), vec![red]);
+        assert_eq!(TrueColor::gradient(red, blue, 2), vec![red, blue]);
+    }
+
+    #[test]
+    fn test_hsl_primaries() {
+        let red = TrueColor::new(255, 0, 0);
+        let green = TrueColor::new(0, 255, 0);
+        let blue = TrueColor::new(0, 0, 255);
+
+        assert_eq!(red.to_hsl(), (0.0, 1.0, 0.5));
+        assert_eq!(green.to_hsl(), (120.0, 1.0, 0.5));
+        assert_eq!(blue.to_hsl(), (240.0, 1.0, 0.5));
+
+        assert_eq!(TrueColor::from_hsl(0.0, 1.0, 0.5), red);
+        assert_eq!(TrueColor::from_hsl(120.0, 1.0, 0.5), green);
+        assert_eq!(TrueColor::from_hsl(240.0, 1.0, 0.5), blue);
+        assert_eq!(TrueColor::from_hsl(360.0, 1.0, 0.5), red);
+    }
+
+    #[test]
+    fn test_hsl_grayscale() {
+        let gray = TrueColor::new(128, 128, 128);
+        let (h, s, l) = gray.to_hsl();
+        assert_eq!(h, 0.0);
+        assert_eq!(s, 0.0);
+        assert!((l - 128.0 / 255.0).abs() < 0.001);
+
+        assert_eq!(TrueColor::from_hsl(200.0, 0.0, l), gray);
+        assert_eq!(TrueColor::from_hsl(0.0, 0.0, 0.0), TrueColor::new(0, 0, 0));
+        assert_eq!(
+            TrueColor::from_hsl(0.0, 0.0, 1.0),
+            TrueColor::new(255, 255, 255)
+        );
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        let samples = [
+            TrueColor::new(0xDD, 0x66, 0x66),
+            TrueColor::new(0x5A, 0x8A, 0x6A),
+            TrueColor::new(0x12, 0x34, 0x56),
+            TrueColor::new(0xFE, 0xDC, 0xBA),
+        ];
+        for color in samples {
+            let (h, s, l) = color.to_hsl();
+            let back = TrueColor::from_hsl(h, s, l);
+            assert!(color.r.abs_diff(back.r) <= 1, "{color} -> {back}");
+            assert!(color.g.abs_diff(back.g) <= 1, "{color} -> {back}");
+            assert!(color.b.abs_diff(back.b) <= 1, "{color} -> {back}");
+        }
+    }
+
+    #[test]
+    fn test_rotate_hue() {
+        let red = TrueColor::new(255, 0, 0);
+        assert_eq!(red.rotate_hue(120.0), TrueColor::new(0, 255, 0));
+        assert_eq!(red.rotate_hue(-120.0), TrueColor::new(0, 0, 255));
+        assert_eq!(red.rotate_hue(180.0), TrueColor::new(0, 255, 255));
+        assert_eq!(red.rotate_hue(360.0), red);
+
+        let gray = TrueColor::new(100, 100, 100);
+        assert_eq!(gray.rotate_hue(90.0), gray);
+    }
+
     #[test]
     fn test_lighten() {
         let dark = TrueColor::new(100, 100, 100);
@@ -396,6 +824,39 @@ mod tests {
         assert!(darker.b < light.b);
     }
 
+    #[test]
+    fn test_contrast_ratio_extremes() {
+        let white = TrueColor::new(255, 255, 255);
+        let black = TrueColor::new(0, 0, 0);
+
+        assert!((black.contrast_ratio(&white) - 21.0).abs() < 0.01);
+        assert!((white.contrast_ratio(&black) - 21.0).abs() < 0.01);
+        assert!((white.contrast_ratio(&white) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_contrast_ratio_reference_values() {
+        let white = TrueColor::new(255, 255, 255);
+        // #777777 on white is the classic 4.48:1 near-miss
+        let gray = TrueColor::new(0x77, 0x77, 0x77);
+        assert!((gray.contrast_ratio(&white) - 4.48).abs() < 0.01);
+        // Pure red on white is 4.0:1
+        let red = TrueColor::new(255, 0, 0);
+        assert!((red.contrast_ratio(&white) - 4.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_meets_wcag_aa() {
+        let white = TrueColor::new(255, 255, 255);
+        let black = TrueColor::new(0, 0, 0);
+        let gray = TrueColor::new(0x77, 0x77, 0x77);
+
+        assert!(black.meets_wcag_aa(&white, false));
+        assert!(!gray.meets_wcag_aa(&white, false));
+        assert!(gray.meets_wcag_aa(&white, true));
+        assert!(!white.meets_wcag_aa(&white, true));
+    }
+
     #[test]
     fn test_display_trait() {
         let color = TrueColor::new(255, 0, 128);
@@ -438,6 +899,36 @@ mod tests {
         let _ = palette.get_256(255);
     }
 
+    #[test]
+    fn test_to_ansi256_black_and_white() {
+        let palette = TrueColorPalette::default_dark();
+        assert_eq!(palette.to_ansi256(TrueColor::new(0, 0, 0)), 16);
+        assert_eq!(palette.to_ansi256(TrueColor::new(255, 255, 255)), 231);
+    }
+
+    #[test]
+    fn test_to_ansi256_grayscale() {
+        let palette = TrueColorPalette::default_dark();
+        // 128 sits exactly on ramp entry 244 (8 + 12 * 10)
+        assert_eq!(palette.to_ansi256(TrueColor::new(128, 128, 128)), 244);
+        assert_eq!(palette.to_ansi256(TrueColor::new(9, 9, 9)), 232);
+    }
+
+    #[test]
+    fn test_to_ansi256_saturated() {
+        let palette = TrueColorPalette::default_dark();
+        assert_eq!(palette.to_ansi256(TrueColor::new(255, 0, 0)), 196);
+        assert_eq!(palette.to_ansi256(TrueColor::new(0, 0, 250)), 21);
+    }
+
+    #[test]
+    fn test_to_ansi256_exact_cube_colors() {
+        let palette = TrueColorPalette::default_dark();
+        for index in 16..=231u8 {
+            assert_eq!(palette.to_ansi256(palette.get_256(index)), index);
+        }
+    }
+
     #[test]
     fn test_from_ansi_colors() {
         use crate::config::AnsiColors;
@@ -464,4 +955,38 @@ mod tests {
         assert_eq!(palette.black, TrueColor::new(0, 0, 0));
         assert_eq!(palette.red, TrueColor::new(255, 0, 0));
     }
+
+    /// Assert every ANSI color but the background slot is readable on it
+    fn assert_readable_on(palette: &TrueColorPalette, background_index: u8, min_ratio: f32) {
+        let background = palette.get_256(background_index);
+        for index in (0..16).filter(|&i| i != background_index) {
+            let color = palette.get_256(index);
+            assert!(color.meets_wcag_aa(&background, false));
+            let ratio = color.contrast_ratio(&background);
+            assert!(
+                ratio >= min_ratio,
+                "color {index} ({color}) is only {ratio:.2}:1 on {background}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_light_palette_contrast() {
+        let palette = TrueColorPalette::default_light();
+        assert_eq!(palette.bright_white, TrueColor::new(255, 255, 255));
+        assert_eq!(palette.extended.len(), 256);
+        assert_readable_on(&palette, 15, 4.5);
+        // The foreground stays far above the AA threshold
+        assert!(palette.black.contrast_ratio(&palette.bright_white) > 15.0);
+    }
+
+    #[test]
+    fn test_default_high_contrast_palette_contrast() {
+        let palette = TrueColorPalette::default_high_contrast();
+        assert_eq!(palette.black, TrueColor::new(0, 0, 0));
+        assert_eq!(palette.extended.len(), 256);
+        // AAA rather than AA
+        assert_readable_on(&palette, 0, 7.0);
+        assert!((palette.bright_white.contrast_ratio(&palette.black) - 21.0).abs() < 0.01);
+    }
 }
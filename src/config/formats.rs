@@ -0,0 +1,184 @@
+//! Config file formats
+//!
+//! Lua is the native format, but the same settings can be written as JSON,
+//! YAML or TOML. Those files hold the settings table itself, without the
+//! `config = ` wrapper, and are turned into the Lua table a Lua config would
+//! have defined, so every format goes through the same loading, validation
+//! and profile merging.
+
+use mlua::{Lua, Table, Value as LuaValue};
+use serde_json::Value;
+use std::path::Path;
+
+use super::{Config, ConfigError};
+
+/// Syntax of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Lua,
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format for a file, by extension; a file without one is Lua
+    ///
+    /// # Errors
+    /// Returns an error naming the supported extensions for any other extension
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let Some(extension) = path.extension() else {
+            return Ok(Self::Lua);
+        };
+        match extension.to_string_lossy().to_ascii_lowercase().as_str() {
+            "lua" => Ok(Self::Lua),
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            other => Err(format!(
+                "Unsupported config file extension '.{other}': expected .lua, .json, .yaml, .yml or .toml"
+            )),
+        }
+    }
+}
+
+impl Config {
+    /// Every setting as pretty-printed JSON, which loads back as a `.json` config
+    ///
+    /// Keys are sorted so the output is the same from run to run.
+    ///
+    /// # Errors
+    /// Returns an error if a setting can't be represented as JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        // Going through `Value` sorts the keys of maps such as `shell.env`
+        serde_json::to_string_pretty(&serde_json::to_value(self)?)
+    }
+}
+
+/// The settings table a config file defines, built in `lua`
+///
+/// A Lua config is run and must set the global `config`; the other formats
+/// are parsed and converted.
+///
+/// # Errors
+/// Returns the Lua or parser error, with its line where one is given, or an
+/// error if the file doesn't define a table of settings
+pub(super) fn read_table<'lua>(
+    lua: &'lua Lua,
+    contents: &str,
+    format: ConfigFormat,
+) -> Result<Table<'lua>, ConfigError> {
+    let value = match format {
+        ConfigFormat::Lua => {
+            lua.load(contents)
+                .exec()
+                .map_err(|e| ConfigError::from_lua(&e))?;
+            return lua
+                .globals()
+                .get("config")
+                .map_err(|_| ConfigError::new("", "Config table not found in Lua file"));
+        }
+        ConfigFormat::Json => serde_json::from_str(contents)
+            .map_err(|e| parse_error("JSON", e.to_string(), Some(e.line())))?,
+        ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| {
+            let line = e.location().map(|location| location.line());
+            parse_error("YAML", e.to_string(), line)
+        })?,
+        ConfigFormat::Toml => {
+            let document = contents.parse::<toml_edit::DocumentMut>().map_err(|e| {
+                let line = e
+                    .span()
+                    .map(|span| contents[..span.start].matches('\n').count() + 1);
+                parse_error("TOML", e.message().to_string(), line)
+            })?;
+            toml_table(document.as_table().iter())
+        }
+    };
+
+    if !(value.is_object() || value.is_null()) {
+        return Err(ConfigError::new(
+            "",
+            "expected a table of settings at the top level",
+        ));
+    }
+    let table = match to_lua(lua, &value) {
+        Ok(LuaValue::Table(table)) => Ok(table),
+        // An empty YAML file is null, which like an empty table means all defaults
+        Ok(_) => lua.create_table(),
+        Err(e) => Err(e),
+    };
+    table.map_err(|e| ConfigError::new("", e.to_string()))
+}
+
+fn parse_error(format: &str, message: String, line: Option<usize>) -> ConfigError {
+    ConfigError {
+        line,
+        ..ConfigError::new("", format!("Invalid {format}: {message}"))
+    }
+}
+
+/// The Lua value for a parsed JSON, YAML or TOML value
+///
+/// Nulls become nil, so a setting given as null keeps its default.
+fn to_lua<'lua>(lua: &'lua Lua, value: &Value) -> mlua::Result<LuaValue<'lua>> {
+    Ok(match value {
+        Value::Null => LuaValue::Nil,
+        Value::Bool(b) => LuaValue::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => LuaValue::Integer(i),
+            None => LuaValue::Number(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => LuaValue::String(lua.create_string(s)?),
+        Value::Array(items) => {
+            let table = lua.create_table_with_capacity(items.len(), 0)?;
+            for item in items {
+                table.raw_push(to_lua(lua, item)?)?;
+            }
+            LuaValue::Table(table)
+        }
+        Value::Object(fields) => {
+            let table = lua.create_table_with_capacity(0, fields.len())?;
+            for (key, field) in fields {
+                table.raw_set(key.as_str(), to_lua(lua, field)?)?;
+            }
+            LuaValue::Table(table)
+        }
+    })
+}
+
+fn toml_table<'a>(items: impl Iterator<Item = (&'a str, &'a toml_edit::Item)>) -> Value {
+    Value::Object(
+        items
+            .map(|(key, item)| (key.to_string(), toml_item(item)))
+            .collect(),
+    )
+}
+
+fn toml_item(item: &toml_edit::Item) -> Value {
+    match item {
+        toml_edit::Item::None => Value::Null,
+        toml_edit::Item::Value(value) => toml_value(value),
+        toml_edit::Item::Table(table) => toml_table(table.iter()),
+        toml_edit::Item::ArrayOfTables(tables) => tables
+            .iter()
+            .map(|table| toml_table(table.iter()))
+            .collect(),
+    }
+}
+
+fn toml_value(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::from(s.value().as_str()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::from(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::from(d.value().to_string()),
+        toml_edit::Value::Array(items) => items.iter().map(toml_value).collect(),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value(value)))
+                .collect(),
+        ),
+    }
+}
@@ -1,14 +1,25 @@
 use anyhow::{Context, Result};
 use mlua::{Lua, Table};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::warn;
 
+mod formats;
+mod paths;
+mod profiles;
+mod reload;
+mod validate;
+
+pub use formats::ConfigFormat;
+pub use reload::{ConfigDiff, ConfigWatcher};
+pub use validate::ConfigError;
+
 const DEFAULT_CONFIG_LUA: &str = include_str!("../../config.default.lua");
 
 /// Main configuration structure with zero-copy design for performance
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Config {
     pub shell: ShellConfig,
     pub terminal: TerminalConfig,
@@ -16,9 +27,15 @@ pub struct Config {
     pub keybindings: KeyBindings,
     pub features: FeaturesConfig,
     pub hooks: HooksConfig,
+    pub translator: TranslatorConfig,
+    pub session: SessionConfig,
+    pub progress_bar: ProgressBarConfig,
+    pub plugins: PluginsConfig,
+    pub ui: UiConfig,
+    pub logging: LoggingConfig,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct HooksConfig {
     /// Lua script paths for various hooks
     pub on_startup: Option<String>,
@@ -29,6 +46,7 @@ pub struct HooksConfig {
     pub on_output: Option<String>,
     pub on_bell: Option<String>,
     pub on_title_change: Option<String>,
+    pub on_resize: Option<String>,
 
     /// Custom keybinding handlers (key -> lua function string)
     pub custom_keybindings: HashMap<String, String>,
@@ -38,6 +56,29 @@ pub struct HooksConfig {
 
     /// Custom widgets (Lua code for rendering custom UI)
     pub custom_widgets: Vec<String>,
+
+    /// Milliseconds a hook, filter or widget may run before it is aborted (0 = no limit)
+    pub timeout_ms: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_startup: None,
+            on_shutdown: None,
+            on_key_press: None,
+            on_command_start: None,
+            on_command_end: None,
+            on_output: None,
+            on_bell: None,
+            on_title_change: None,
+            on_resize: None,
+            custom_keybindings: HashMap::new(),
+            output_filters: Vec::new(),
+            custom_widgets: Vec::new(),
+            timeout_ms: 1000,
+        }
+    }
 }
 
 impl HooksConfig {
@@ -50,6 +91,7 @@ impl HooksConfig {
         let on_output = table.get::<_, Option<String>>("on_output")?;
         let on_bell = table.get::<_, Option<String>>("on_bell")?;
         let on_title_change = table.get::<_, Option<String>>("on_title_change")?;
+        let on_resize = table.get::<_, Option<String>>("on_resize")?;
 
         let custom_keybindings = if let Ok(kb_table) = table.get::<_, Table>("custom_keybindings") {
             let mut map = HashMap::new();
@@ -91,22 +133,231 @@ impl HooksConfig {
             on_output,
             on_bell,
             on_title_change,
+            on_resize,
             custom_keybindings,
             output_filters,
             custom_widgets,
+            timeout_ms: table.get::<_, Option<u64>>("timeout_ms")?.unwrap_or(1000),
+        })
+    }
+}
+
+/// Command translator configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TranslatorConfig {
+    /// User-defined mappings (source command -> target), merged over the built-ins
+    pub custom: HashMap<String, TranslatorMapping>,
+}
+
+/// A single user-defined translator mapping
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TranslatorMapping {
+    /// Target command; empty disables the built-in mapping for the source command
+    pub target: String,
+    /// Append the original arguments after the target command
+    pub passthrough_args: bool,
+}
+
+impl TranslatorConfig {
+    /// Command translator with these mappings merged over the built-in ones
+    #[must_use]
+    #[allow(dead_code)] // Public API for future use
+    pub fn translator(&self, enabled: bool) -> cmdx::CommandTranslator {
+        let custom = self
+            .custom
+            .iter()
+            .map(|(source, mapping)| {
+                let mapping = cmdx::CustomMapping {
+                    target: mapping.target.clone(),
+                    passthrough_args: mapping.passthrough_args,
+                };
+                (source.clone(), mapping)
+            })
+            .collect();
+        cmdx::CommandTranslator::with_custom_mappings(enabled, custom)
+    }
+
+    /// Accepts either `bat = "type"` or `ll = { target = "ls -la", passthrough_args = false }`
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        let mut custom = HashMap::new();
+
+        if let Ok(custom_table) = table.get::<_, Table>("custom") {
+            for pair in custom_table.pairs::<String, mlua::Value>() {
+                let (source, value) = pair?;
+                let mapping = match value {
+                    mlua::Value::String(target) => TranslatorMapping {
+                        target: target.to_str()?.to_string(),
+                        passthrough_args: true,
+                    },
+                    mlua::Value::Table(entry) => TranslatorMapping {
+                        target: entry
+                            .get::<_, Option<String>>("target")?
+                            .unwrap_or_default(),
+                        passthrough_args: entry
+                            .get::<_, Option<bool>>("passthrough_args")?
+                            .unwrap_or(true),
+                    },
+                    _ => {
+                        warn!("Ignoring invalid translator mapping for '{}'", source);
+                        continue;
+                    }
+                };
+                custom.insert(source, mapping);
+            }
+        }
+
+        Ok(Self { custom })
+    }
+}
+
+/// Progress bar appearance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressBarConfig {
+    /// Spinner animation: "line", "dots", "braille", "arc" or "bounce"
+    pub spinner_style: String,
+}
+
+impl Default for ProgressBarConfig {
+    fn default() -> Self {
+        Self {
+            spinner_style: "line".to_string(),
+        }
+    }
+}
+
+impl ProgressBarConfig {
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        let spinner_style = table
+            .get::<_, Option<String>>("spinner_style")?
+            .unwrap_or_else(|| "line".to_string());
+
+        // Validate spinner style, fall back to "dots" for unknown names
+        let spinner_style = match spinner_style.as_str() {
+            "line" | "dots" | "braille" | "arc" | "bounce" => spinner_style,
+            _ => {
+                warn!(
+                    "Invalid spinner_style '{}', falling back to 'dots'",
+                    spinner_style
+                );
+                "dots".to_string()
+            }
+        };
+
+        Ok(Self { spinner_style })
+    }
+}
+
+/// Session persistence configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionConfig {
+    /// Seconds between crash-recovery checkpoints of the current session; 0 disables them.
+    /// Requires `features.session_manager`.
+    pub autosave_interval_secs: u64,
+}
+
+impl SessionConfig {
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        Ok(Self {
+            autosave_interval_secs: table
+                .get::<_, Option<u64>>("autosave_interval_secs")?
+                .unwrap_or(0),
+        })
+    }
+}
+
+/// Native plugin configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PluginsConfig {
+    /// Directory whose `.so`/`.dll`/`.dylib` files are loaded as plugins; `None` disables plugins
+    pub directory: Option<String>,
+}
+
+impl PluginsConfig {
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        Ok(Self {
+            directory: table.get::<_, Option<String>>("directory")?,
+        })
+    }
+}
+
+/// Interface behavior that isn't part of the terminal itself
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UiConfig {
+    /// Seconds each notification stays on screen
+    pub notification_duration_secs: u64,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            notification_duration_secs: 2,
+        }
+    }
+}
+
+impl UiConfig {
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        Ok(Self {
+            notification_duration_secs: table
+                .get::<_, Option<u64>>("notification_duration_secs")?
+                .unwrap_or(2),
+        })
+    }
+}
+
+/// Writing the log to a file besides stderr
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoggingConfig {
+    /// Log file path; `None` logs to stderr only. `--log-file` takes precedence.
+    pub file: Option<String>,
+    /// Megabytes the log file grows to before it is rotated
+    pub max_size_mb: u64,
+    /// Rotated files kept besides the current one
+    pub max_files: u64,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_size_mb: 10,
+            max_files: 3,
+        }
+    }
+}
+
+impl LoggingConfig {
+    fn from_lua_table(table: &Table) -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            file: table.get::<_, Option<String>>("file")?,
+            max_size_mb: table
+                .get::<_, Option<u64>>("max_size_mb")?
+                .unwrap_or(defaults.max_size_mb),
+            max_files: table
+                .get::<_, Option<u64>>("max_files")?
+                .unwrap_or(defaults.max_files),
         })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ShellConfig {
     pub default_shell: String,
     /// Environment variables to pass to shell (future feature)
     pub env: HashMap<String, String>,
     pub working_dir: Option<String>,
+    /// Regex matched against the last line of output to detect the shell prompt
+    pub prompt_regex: Option<String>,
+    /// Close a tab when its shell exits instead of keeping its output
+    pub close_on_exit: bool,
+    /// Quit when the shell in the last tab exits (only with `close_on_exit`)
+    pub quit_on_last_exit: bool,
+    /// Size in KiB of the buffer shell output is read into
+    pub read_buffer_kb: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TerminalConfig {
     /// Maximum command history entries (memory-efficient circular buffer) - future feature
     pub max_history: usize,
@@ -120,17 +371,53 @@ pub struct TerminalConfig {
     /// Font size - parsed for future rendering integration
     pub font_size: u16,
 
+    /// Primary font family for the GPU renderer
+    pub font_family: String,
+
+    /// Font families (or font file paths) searched in order for glyphs the primary font lacks
+    pub fallback_fonts: Vec<String>,
+
     /// Cursor style: block, underline, bar - future feature
     pub cursor_style: String,
 
     /// Number of scrollback lines (memory-mapped for large buffers)
     pub scrollback_lines: usize,
 
+    /// Keep scrollback past `scrollback_lines` in a temporary file instead of dropping it
+    pub scrollback_to_disk: bool,
+
     /// Hardware acceleration for rendering - future GPU feature flag
     pub hardware_acceleration: bool,
+
+    /// Highest frame rate; fewer frames are drawn while output floods in
+    pub max_fps: u32,
+
+    /// Long line handling: wrap (with continuation indent) or truncate (with `…`)
+    pub line_wrap: String,
+
+    /// What a BEL from the shell does: none, visual, audible or both
+    pub bell_style: String,
+
+    /// What labels a tab: title (set by programs), cwd or process
+    pub tab_title_source: String,
+
+    /// Widest tab label in cells; longer labels are cut with `…`
+    pub tab_title_max_width: usize,
+
+    /// Ask before pasting text that contains a newline
+    pub confirm_multiline_paste: bool,
+
+    /// Mark pastes with `ESC[200~`/`ESC[201~` when the program asks for bracketed paste
+    pub bracketed_paste: bool,
+
+    /// Remove risky escape sequences (unsupported OSC, DCS/APC strings) from output
+    pub sanitize_output: bool,
+
+    /// Let programs set the clipboard with OSC 52
+    pub allow_osc52: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[allow(dead_code)]
 pub struct ThemeConfig {
     pub name: String,
@@ -144,7 +431,7 @@ pub struct ThemeConfig {
 }
 
 /// Background configuration for background image support
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BackgroundConfig {
     /// Path to background image file (supports PNG, JPEG, etc.)
     pub image_path: Option<String>,
@@ -156,10 +443,12 @@ pub struct BackgroundConfig {
     pub mode: String,
     /// Blur effect strength (0.0 = no blur, higher = more blur)
     pub blur: f32,
+    /// Play every frame of an animated GIF or APNG instead of only the first
+    pub animate: bool,
 }
 
 /// Cursor trail configuration for cursor effects
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct CursorTrailConfig {
     /// Enable cursor trail effect
     pub enabled: bool,
@@ -173,10 +462,12 @@ pub struct CursorTrailConfig {
     pub width: f32,
     /// Animation speed (milliseconds per trail update)
     pub animation_speed: u64,
+    /// Milliseconds a position takes to fade back to the background
+    pub fade_ms: u64,
 }
 
 /// ANSI colors configuration for theme customization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct AnsiColors {
     pub black: String,
     pub red: String,
@@ -197,7 +488,7 @@ pub struct AnsiColors {
 }
 
 /// Keybinding configuration for custom keybinding loading
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct KeyBindings {
     pub new_tab: String,
     pub close_tab: String,
@@ -209,9 +500,31 @@ pub struct KeyBindings {
     pub paste: String,
     pub search: String,
     pub clear: String,
+    pub save_session: String,
+    pub load_session: String,
+    pub next_theme: String,
+    pub prev_theme: String,
+    pub toggle_resource_monitor: String,
+    pub toggle_autocomplete: String,
+    pub show_keybindings: String,
+    pub command_palette: String,
+    pub interrupt: String,
+    pub restart_shell: String,
+    pub prev_command: String,
+    pub next_command: String,
+    pub copy_rich: String,
+    pub grow_pane: String,
+    pub shrink_pane: String,
+    pub scroll_to_top: String,
+    pub scroll_to_bottom: String,
+    pub toggle_stats_overlay: String,
+    /// Extra bindings from action name (e.g. "select_all") to key combo
+    pub custom: HashMap<String, String>,
+    /// Milliseconds a multi-key chord such as "Ctrl+B c" waits for its next key
+    pub chord_timeout_ms: u64,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 #[allow(clippy::struct_excessive_bools)]
 #[allow(dead_code)]
 pub struct FeaturesConfig {
@@ -265,6 +578,10 @@ impl Default for ShellConfig {
             default_shell: detect_default_shell(),
             env: HashMap::new(),
             working_dir: None,
+            prompt_regex: None,
+            close_on_exit: true,
+            quit_on_last_exit: true,
+            read_buffer_kb: 8,
         }
     }
 }
@@ -276,14 +593,32 @@ impl Default for TerminalConfig {
             enable_tabs: false,
             enable_split_pane: false,
             font_size: 12,
+            font_family: "JetBrains Mono".to_string(),
+            fallback_fonts: Vec::new(),
             cursor_style: "block".to_string(),
             scrollback_lines: 10000,
+            scrollback_to_disk: false,
             hardware_acceleration: true,
+            max_fps: 170,
+            line_wrap: "wrap".to_string(),
+            bell_style: "none".to_string(),
+            tab_title_source: "title".to_string(),
+            tab_title_max_width: 24,
+            confirm_multiline_paste: true,
+            bracketed_paste: true,
+            sanitize_output: true,
+            allow_osc52: false,
         }
     }
 }
 
 impl ShellConfig {
+    /// Size in bytes of the buffer shell output is read into
+    #[must_use]
+    pub fn read_buffer_size(&self) -> usize {
+        self.read_buffer_kb * 1024
+    }
+
     fn from_lua_table(table: &Table) -> Result<Self> {
         let default_shell = table
             .get::<_, Option<String>>("default_shell")?
@@ -302,10 +637,34 @@ impl ShellConfig {
 
         let working_dir = table.get::<_, Option<String>>("working_dir")?;
 
+        let prompt_regex = table
+            .get::<_, Option<String>>("prompt_regex")?
+            .filter(|pattern| match regex::Regex::new(pattern) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!(
+                        "Invalid prompt_regex '{}', falling back to built-in prompt detection: {}",
+                        pattern, e
+                    );
+                    false
+                }
+            });
+
         Ok(Self {
             default_shell,
             env,
             working_dir,
+            prompt_regex,
+            close_on_exit: table
+                .get::<_, Option<bool>>("close_on_exit")?
+                .unwrap_or(true),
+            quit_on_last_exit: table
+                .get::<_, Option<bool>>("quit_on_last_exit")?
+                .unwrap_or(true),
+            read_buffer_kb: table
+                .get::<_, Option<usize>>("read_buffer_kb")?
+                .unwrap_or(8)
+                .clamp(1, 1024),
         })
     }
 }
@@ -317,6 +676,21 @@ impl TerminalConfig {
             .unwrap_or(12)
             .clamp(1, 200);
 
+        let font_family = table
+            .get::<_, Option<String>>("font_family")?
+            .filter(|family| !family.trim().is_empty())
+            .unwrap_or_else(|| "JetBrains Mono".to_string());
+
+        let fallback_fonts = if let Ok(fonts_table) = table.get::<_, Table>("fallback_fonts") {
+            let mut fonts = Vec::new();
+            for font in fonts_table.sequence_values::<String>() {
+                fonts.push(font?);
+            }
+            fonts
+        } else {
+            Vec::new()
+        };
+
         let max_history = table
             .get::<_, Option<usize>>("max_history")?
             .unwrap_or(10000)
@@ -327,6 +701,11 @@ impl TerminalConfig {
             .unwrap_or(10000)
             .clamp(1, 1_000_000);
 
+        let max_fps = table
+            .get::<_, Option<u32>>("max_fps")?
+            .unwrap_or(170)
+            .clamp(1, 1000);
+
         let cursor_style = table
             .get::<_, Option<String>>("cursor_style")?
             .unwrap_or_else(|| "block".to_string());
@@ -343,6 +722,56 @@ impl TerminalConfig {
             }
         };
 
+        let line_wrap = table
+            .get::<_, Option<String>>("line_wrap")?
+            .unwrap_or_else(|| "wrap".to_string());
+
+        // Validate line wrap mode, fall back to "wrap" for invalid values
+        let line_wrap = match line_wrap.as_str() {
+            "wrap" | "truncate" => line_wrap,
+            _ => {
+                warn!("Invalid line_wrap '{}', falling back to 'wrap'", line_wrap);
+                "wrap".to_string()
+            }
+        };
+
+        let bell_style = table
+            .get::<_, Option<String>>("bell_style")?
+            .unwrap_or_else(|| "none".to_string());
+
+        // Validate bell style, fall back to "none" for invalid values
+        let bell_style = match bell_style.as_str() {
+            "none" | "visual" | "audible" | "both" => bell_style,
+            _ => {
+                warn!(
+                    "Invalid bell_style '{}', falling back to 'none'",
+                    bell_style
+                );
+                "none".to_string()
+            }
+        };
+
+        let tab_title_source = table
+            .get::<_, Option<String>>("tab_title_source")?
+            .unwrap_or_else(|| "title".to_string());
+
+        // Validate tab title source, fall back to "title" for invalid values
+        let tab_title_source = match tab_title_source.as_str() {
+            "title" | "cwd" | "process" => tab_title_source,
+            _ => {
+                warn!(
+                    "Invalid tab_title_source '{}', falling back to 'title'",
+                    tab_title_source
+                );
+                "title".to_string()
+            }
+        };
+
+        let tab_title_max_width = table
+            .get::<_, Option<usize>>("tab_title_max_width")?
+            .unwrap_or(24)
+            .clamp(1, 200);
+
         Ok(Self {
             max_history,
             enable_tabs: table
@@ -352,11 +781,33 @@ impl TerminalConfig {
                 .get::<_, Option<bool>>("enable_split_pane")?
                 .unwrap_or(false),
             font_size,
+            font_family,
+            fallback_fonts,
             cursor_style,
             scrollback_lines,
+            scrollback_to_disk: table
+                .get::<_, Option<bool>>("scrollback_to_disk")?
+                .unwrap_or(false),
             hardware_acceleration: table
                 .get::<_, Option<bool>>("hardware_acceleration")?
                 .unwrap_or(true),
+            max_fps,
+            line_wrap,
+            bell_style,
+            tab_title_source,
+            tab_title_max_width,
+            confirm_multiline_paste: table
+                .get::<_, Option<bool>>("confirm_multiline_paste")?
+                .unwrap_or(true),
+            bracketed_paste: table
+                .get::<_, Option<bool>>("bracketed_paste")?
+                .unwrap_or(true),
+            sanitize_output: table
+                .get::<_, Option<bool>>("sanitize_output")?
+                .unwrap_or(true),
+            allow_osc52: table
+                .get::<_, Option<bool>>("allow_osc52")?
+                .unwrap_or(false),
         })
     }
 }
@@ -384,6 +835,7 @@ impl Default for BackgroundConfig {
             opacity: 1.0,
             mode: "fill".to_string(),
             blur: 0.0,
+            animate: false,
         }
     }
 }
@@ -397,6 +849,7 @@ impl Default for CursorTrailConfig {
             fade_mode: "exponential".to_string(),
             width: 1.0,
             animation_speed: 16,
+            fade_ms: 250,
         }
     }
 }
@@ -414,6 +867,7 @@ impl BackgroundConfig {
                 .get::<_, Option<String>>("mode")?
                 .unwrap_or_else(|| "fill".to_string()),
             blur: table.get::<_, Option<f32>>("blur")?.unwrap_or(0.0).max(0.0),
+            animate: table.get::<_, Option<bool>>("animate")?.unwrap_or(false),
         })
     }
 }
@@ -433,6 +887,10 @@ impl CursorTrailConfig {
             animation_speed: table
                 .get::<_, Option<u64>>("animation_speed")?
                 .unwrap_or(16),
+            fade_ms: table
+                .get::<_, Option<u64>>("fade_ms")?
+                .unwrap_or(250)
+                .clamp(1, 10_000),
         })
     }
 }
@@ -499,12 +957,43 @@ impl Default for KeyBindings {
             paste: "Ctrl+Shift+V".to_string(),
             search: "Ctrl+F".to_string(),
             clear: "Ctrl+L".to_string(),
+            save_session: "Ctrl+S".to_string(),
+            load_session: "Ctrl+Shift+L".to_string(),
+            next_theme: "Ctrl+]".to_string(),
+            prev_theme: "Ctrl+[".to_string(),
+            toggle_resource_monitor: "Ctrl+R".to_string(),
+            toggle_autocomplete: "Alt+Tab".to_string(),
+            show_keybindings: "Ctrl+Shift+K".to_string(),
+            command_palette: "Ctrl+Shift+P".to_string(),
+            interrupt: "Ctrl+Shift+X".to_string(),
+            restart_shell: "Ctrl+Shift+R".to_string(),
+            prev_command: "Ctrl+Shift+Up".to_string(),
+            next_command: "Ctrl+Shift+Down".to_string(),
+            copy_rich: "Ctrl+Alt+C".to_string(),
+            grow_pane: "Ctrl+Shift+Right".to_string(),
+            shrink_pane: "Ctrl+Shift+Left".to_string(),
+            scroll_to_top: "Shift+Home".to_string(),
+            scroll_to_bottom: "Shift+End".to_string(),
+            toggle_stats_overlay: "Ctrl+Shift+S".to_string(),
+            custom: HashMap::new(),
+            chord_timeout_ms: 1000,
         }
     }
 }
 
 impl KeyBindings {
     fn from_lua_table(table: &Table) -> Result<Self> {
+        let custom = if let Ok(custom_table) = table.get::<_, Table>("custom") {
+            let mut map = HashMap::new();
+            for pair in custom_table.pairs::<String, String>() {
+                let (action, combo) = pair?;
+                map.insert(action, combo);
+            }
+            map
+        } else {
+            HashMap::new()
+        };
+
         Ok(Self {
             new_tab: table
                 .get::<_, Option<String>>("new_tab")?
@@ -536,6 +1025,64 @@ impl KeyBindings {
             clear: table
                 .get::<_, Option<String>>("clear")?
                 .unwrap_or_else(|| "Ctrl+L".to_string()),
+            save_session: table
+                .get::<_, Option<String>>("save_session")?
+                .unwrap_or_else(|| "Ctrl+S".to_string()),
+            load_session: table
+                .get::<_, Option<String>>("load_session")?
+                .unwrap_or_else(|| "Ctrl+Shift+L".to_string()),
+            next_theme: table
+                .get::<_, Option<String>>("next_theme")?
+                .unwrap_or_else(|| "Ctrl+]".to_string()),
+            prev_theme: table
+                .get::<_, Option<String>>("prev_theme")?
+                .unwrap_or_else(|| "Ctrl+[".to_string()),
+            toggle_resource_monitor: table
+                .get::<_, Option<String>>("toggle_resource_monitor")?
+                .unwrap_or_else(|| "Ctrl+R".to_string()),
+            toggle_autocomplete: table
+                .get::<_, Option<String>>("toggle_autocomplete")?
+                .unwrap_or_else(|| "Alt+Tab".to_string()),
+            show_keybindings: table
+                .get::<_, Option<String>>("show_keybindings")?
+                .unwrap_or_else(|| "Ctrl+Shift+K".to_string()),
+            command_palette: table
+                .get::<_, Option<String>>("command_palette")?
+                .unwrap_or_else(|| "Ctrl+Shift+P".to_string()),
+            interrupt: table
+                .get::<_, Option<String>>("interrupt")?
+                .unwrap_or_else(|| "Ctrl+Shift+X".to_string()),
+            restart_shell: table
+                .get::<_, Option<String>>("restart_shell")?
+                .unwrap_or_else(|| "Ctrl+Shift+R".to_string()),
+            prev_command: table
+                .get::<_, Option<String>>("prev_command")?
+                .unwrap_or_else(|| "Ctrl+Shift+Up".to_string()),
+            next_command: table
+                .get::<_, Option<String>>("next_command")?
+                .unwrap_or_else(|| "Ctrl+Shift+Down".to_string()),
+            copy_rich: table
+                .get::<_, Option<String>>("copy_rich")?
+                .unwrap_or_else(|| "Ctrl+Alt+C".to_string()),
+            grow_pane: table
+                .get::<_, Option<String>>("grow_pane")?
+                .unwrap_or_else(|| "Ctrl+Shift+Right".to_string()),
+            shrink_pane: table
+                .get::<_, Option<String>>("shrink_pane")?
+                .unwrap_or_else(|| "Ctrl+Shift+Left".to_string()),
+            scroll_to_top: table
+                .get::<_, Option<String>>("scroll_to_top")?
+                .unwrap_or_else(|| "Shift+Home".to_string()),
+            scroll_to_bottom: table
+                .get::<_, Option<String>>("scroll_to_bottom")?
+                .unwrap_or_else(|| "Shift+End".to_string()),
+            toggle_stats_overlay: table
+                .get::<_, Option<String>>("toggle_stats_overlay")?
+                .unwrap_or_else(|| "Ctrl+Shift+S".to_string()),
+            custom,
+            chord_timeout_ms: table
+                .get::<_, Option<u64>>("chord_timeout_ms")?
+                .unwrap_or(1000),
         })
     }
 }
@@ -629,16 +1176,19 @@ impl Config {
         if config_path.exists() {
             Self::load_from_file(&config_path)
         } else {
-            Self::load_from_str(DEFAULT_CONFIG_LUA)
+            Self::load_from_str(DEFAULT_CONFIG_LUA, ConfigFormat::Lua, None)
         }
     }
 
-    /// Load configuration from a Lua file
+    /// Load configuration from a Lua, JSON, YAML or TOML file
+    ///
+    /// The format is chosen by the file's extension (see [`ConfigFormat::from_path`]).
     ///
     /// # Errors
     /// Returns an error if:
+    /// - The extension is not a supported format
     /// - The file cannot be read
-    /// - The Lua code is invalid or has syntax errors
+    /// - The Lua code is invalid or has syntax errors, or the file doesn't parse
     /// - The Lua code does not define a 'config' table
     /// - The config table has invalid structure or data types
     ///
@@ -647,24 +1197,102 @@ impl Config {
     /// configuration files. The Lua environment has access to the full Lua standard
     /// library, including file I/O and OS operations.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let format = ConfigFormat::from_path(path.as_ref()).map_err(anyhow::Error::msg)?;
         let contents = fs::read_to_string(path.as_ref()).context("Failed to read config file")?;
 
-        Self::load_from_str(&contents)
+        Self::load_from_str(&contents, format, None)
     }
 
-    /// Load configuration from a Lua string
-    fn load_from_str(contents: &str) -> Result<Self> {
+    /// Load configuration from a file, reporting every problem found
+    ///
+    /// Where [`Self::load_from_file`] quietly clamps, falls back or ignores,
+    /// this also collects out-of-range numbers, unknown choices, colors that
+    /// don't parse, invalid key combos and missing paths (see
+    /// [`Self::validate`]), each with its line in the file where it can be
+    /// found. With a `profile`, that profile's settings override the base ones.
+    ///
+    /// # Errors
+    /// Returns all problems found, or the one that stopped the file loading.
+    /// A profile the file doesn't define is an error listing those it does.
+    pub fn load_validated<P: AsRef<Path>>(
+        path: P,
+        profile: Option<&str>,
+    ) -> std::result::Result<Self, Vec<ConfigError>> {
+        let format =
+            ConfigFormat::from_path(path.as_ref()).map_err(|e| vec![ConfigError::new("", e)])?;
+        let contents = fs::read_to_string(path.as_ref()).map_err(|e| {
+            vec![ConfigError {
+                path: String::new(),
+                line: None,
+                message: format!("Failed to read config file: {e}"),
+            }]
+        })?;
+
+        Self::load_validated_str(&contents, format, profile)
+    }
+
+    /// Load and validate configuration from a string in `format`
+    fn load_validated_str(
+        contents: &str,
+        format: ConfigFormat,
+        profile: Option<&str>,
+    ) -> std::result::Result<Self, Vec<ConfigError>> {
         let lua = Lua::new();
-        lua.load(contents)
-            .exec()
-            .context("Failed to execute Lua config")?;
+        let config_table = formats::read_table(&lua, contents, format).map_err(|e| vec![e])?;
+        if let Some(profile) = profile {
+            profiles::apply_profile(&config_table, profile)
+                .map_err(|message| vec![ConfigError::new("", message)])?;
+        }
+        validate::warn_ignored_keys(&config_table, contents, format);
+
+        let mut errors = validate::check_table(&config_table);
+        let config = match Self::from_lua_table(&config_table) {
+            Ok(mut config) => {
+                let unexpanded = config.expand_paths();
+                // A path that didn't expand would also be reported as missing
+                let problems = config
+                    .validate()
+                    .into_iter()
+                    .filter(|error| !unexpanded.iter().any(|failed| failed.path == error.path));
+                errors.extend(problems);
+                errors.extend(unexpanded);
+                Some(config)
+            }
+            Err(e) => {
+                // A wrong type somewhere; the table checks may already explain it
+                if errors.is_empty() {
+                    errors.push(ConfigError {
+                        path: String::new(),
+                        line: None,
+                        message: format!("{e:#}"),
+                    });
+                }
+                None
+            }
+        };
+        validate::locate(&mut errors, contents, format);
 
-        let globals = lua.globals();
-        let config_table: Table = globals
-            .get("config")
-            .context("Config table not found in Lua file")?;
+        match config {
+            Some(config) if errors.is_empty() => Ok(config),
+            _ => Err(errors),
+        }
+    }
 
-        Self::from_lua_table(&config_table)
+    /// Load configuration from a string in `format`, applying `profile` if given
+    fn load_from_str(contents: &str, format: ConfigFormat, profile: Option<&str>) -> Result<Self> {
+        let lua = Lua::new();
+        let config_table = formats::read_table(&lua, contents, format)
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {e}"))?;
+        if let Some(profile) = profile {
+            profiles::apply_profile(&config_table, profile).map_err(anyhow::Error::msg)?;
+        }
+        validate::warn_ignored_keys(&config_table, contents, format);
+
+        let mut config = Self::from_lua_table(&config_table)?;
+        if let Some(error) = config.expand_paths().first() {
+            anyhow::bail!("Invalid path in config: {error}");
+        }
+        Ok(config)
     }
 
     /// Parse configuration from a Lua table
@@ -705,6 +1333,42 @@ impl Config {
             HooksConfig::default()
         };
 
+        let translator = if let Ok(translator_table) = table.get::<_, Table>("translator") {
+            TranslatorConfig::from_lua_table(&translator_table)?
+        } else {
+            TranslatorConfig::default()
+        };
+
+        let session = if let Ok(session_table) = table.get::<_, Table>("session") {
+            SessionConfig::from_lua_table(&session_table)?
+        } else {
+            SessionConfig::default()
+        };
+
+        let progress_bar = if let Ok(progress_table) = table.get::<_, Table>("progress_bar") {
+            ProgressBarConfig::from_lua_table(&progress_table)?
+        } else {
+            ProgressBarConfig::default()
+        };
+
+        let plugins = if let Ok(plugins_table) = table.get::<_, Table>("plugins") {
+            PluginsConfig::from_lua_table(&plugins_table)?
+        } else {
+            PluginsConfig::default()
+        };
+
+        let ui = if let Ok(ui_table) = table.get::<_, Table>("ui") {
+            UiConfig::from_lua_table(&ui_table)?
+        } else {
+            UiConfig::default()
+        };
+
+        let logging = if let Ok(logging_table) = table.get::<_, Table>("logging") {
+            LoggingConfig::from_lua_table(&logging_table)?
+        } else {
+            LoggingConfig::default()
+        };
+
         Ok(Self {
             shell,
             terminal,
@@ -712,6 +1376,12 @@ impl Config {
             keybindings,
             features,
             hooks,
+            translator,
+            session,
+            progress_bar,
+            plugins,
+            ui,
+            logging,
         })
     }
 
@@ -898,7 +1568,9 @@ config = {
         custom_widgets = {
             "function() return 'widget1' end",
             "function() return 'widget2' end",
-        }
+        },
+        on_resize = "print(context)",
+        timeout_ms = 250
     }
 }
 "#;
@@ -912,6 +1584,9 @@ config = {
         assert_eq!(config.hooks.custom_keybindings.len(), 2);
         assert_eq!(config.hooks.output_filters.len(), 2);
         assert_eq!(config.hooks.custom_widgets.len(), 2);
+        assert_eq!(config.hooks.on_resize.as_deref(), Some("print(context)"));
+        assert_eq!(config.hooks.timeout_ms, 250);
+        assert_eq!(HooksConfig::default().timeout_ms, 1000);
     }
 
     #[test]
@@ -925,6 +1600,7 @@ config = {
             opacity = 2.0,
             mode = "fit",
             blur = -1.0,
+            animate = true,
         },
         cursor_trail = {
             enabled = true,
@@ -933,6 +1609,7 @@ config = {
             fade_mode = "smooth",
             width = 1.5,
             animation_speed = 33,
+            fade_ms = 0,
         }
     }
 }
@@ -953,6 +1630,8 @@ config = {
         assert_eq!(bg.mode, "fit");
         assert_eq!(bg.opacity, 1.0, "opacity should be clamped to 1.0");
         assert_eq!(bg.blur, 0.0, "blur should not go below 0");
+        assert!(bg.animate);
+        assert!(!BackgroundConfig::default().animate);
 
         let trail = config.theme.cursor_trail.expect("cursor trail not parsed");
         assert!(trail.enabled);
@@ -961,6 +1640,8 @@ config = {
         assert_eq!(trail.fade_mode, "smooth");
         assert_eq!(trail.width, 1.5);
         assert_eq!(trail.animation_speed, 33);
+        assert_eq!(trail.fade_ms, 1, "fade_ms should be clamped to 1");
+        assert_eq!(CursorTrailConfig::default().fade_ms, 250);
     }
 
     #[test]
@@ -1041,6 +1722,213 @@ config = {
         assert_eq!(config.terminal.cursor_style, "block");
     }
 
+    #[test]
+    fn test_config_line_wrap_parsing() {
+        let parse = |value: &str| {
+            let lua_config = format!("config = {{ terminal = {{ line_wrap = \"{value}\" }} }}");
+            let dir = tempfile::tempdir().unwrap();
+            let config_path = dir.path().join("test_config.lua");
+            std::fs::write(&config_path, lua_config).unwrap();
+            Config::load_from_file(config_path.to_str().unwrap())
+                .unwrap()
+                .terminal
+                .line_wrap
+        };
+
+        assert_eq!(Config::default().terminal.line_wrap, "wrap");
+        assert_eq!(parse("truncate"), "truncate");
+        assert_eq!(parse("wrap"), "wrap");
+        // Invalid line_wrap should fall back to "wrap"
+        assert_eq!(parse("sideways"), "wrap");
+    }
+
+    #[test]
+    fn test_config_bell_style_parsing() {
+        let parse = |value: &str| {
+            let lua_config = format!("config = {{ terminal = {{ bell_style = \"{value}\" }} }}");
+            Config::load_from_str(&lua_config, ConfigFormat::Lua, None)
+                .unwrap()
+                .terminal
+                .bell_style
+        };
+
+        assert_eq!(Config::default().terminal.bell_style, "none");
+        for style in ["none", "visual", "audible", "both"] {
+            assert_eq!(parse(style), style);
+        }
+        // Invalid bell_style should fall back to "none"
+        assert_eq!(parse("loud"), "none");
+    }
+
+    #[test]
+    fn test_config_tab_title_parsing() {
+        let parse = |terminal: &str| {
+            let lua_config = format!("config = {{ terminal = {{ {terminal} }} }}");
+            Config::load_from_str(&lua_config, ConfigFormat::Lua, None)
+                .unwrap()
+                .terminal
+        };
+
+        let defaults = Config::default().terminal;
+        assert_eq!(defaults.tab_title_source, "title");
+        assert_eq!(defaults.tab_title_max_width, 24);
+        for source in ["title", "cwd", "process"] {
+            let terminal = parse(&format!("tab_title_source = \"{source}\""));
+            assert_eq!(terminal.tab_title_source, source);
+        }
+        // Invalid tab_title_source should fall back to "title"
+        assert_eq!(
+            parse("tab_title_source = \"hostname\"").tab_title_source,
+            "title"
+        );
+        assert_eq!(parse("tab_title_max_width = 12").tab_title_max_width, 12);
+        assert_eq!(parse("tab_title_max_width = 0").tab_title_max_width, 1);
+    }
+
+    #[test]
+    fn test_config_max_fps_parsing() {
+        let parse = |terminal: &str| {
+            let lua_config = format!("config = {{ terminal = {{ {terminal} }} }}");
+            Config::load_from_str(&lua_config, ConfigFormat::Lua, None)
+                .unwrap()
+                .terminal
+                .max_fps
+        };
+
+        assert_eq!(Config::default().terminal.max_fps, 170);
+        assert_eq!(parse(""), 170);
+        assert_eq!(parse("max_fps = 60"), 60);
+        assert_eq!(parse("max_fps = 0"), 1);
+        assert_eq!(parse("max_fps = 5000"), 1000);
+    }
+
+    #[test]
+    fn test_config_keybindings_extra_actions() {
+        let lua_config = r#"
+config = {
+    keybindings = {
+        save_session = "Ctrl+Alt+S",
+        custom = { select_all = "Ctrl+Alt+A", focus_prev_pane = "Ctrl+B p" },
+    }
+}
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let kb = Config::load_from_file(config_path.to_str().unwrap())
+            .unwrap()
+            .keybindings;
+
+        assert_eq!(kb.save_session, "Ctrl+Alt+S");
+        assert_eq!(kb.load_session, "Ctrl+Shift+L");
+        assert_eq!(kb.custom.len(), 2);
+        assert_eq!(kb.custom["focus_prev_pane"], "Ctrl+B p");
+        assert!(Config::default().keybindings.custom.is_empty());
+    }
+
+    #[test]
+    fn test_config_spinner_style_parsing() {
+        let parse = |value: &str| {
+            let lua_config =
+                format!("config = {{ progress_bar = {{ spinner_style = \"{value}\" }} }}");
+            let dir = tempfile::tempdir().unwrap();
+            let config_path = dir.path().join("test_config.lua");
+            std::fs::write(&config_path, lua_config).unwrap();
+            Config::load_from_file(config_path.to_str().unwrap())
+                .unwrap()
+                .progress_bar
+                .spinner_style
+        };
+
+        assert_eq!(Config::default().progress_bar.spinner_style, "line");
+        assert_eq!(parse("braille"), "braille");
+        assert_eq!(parse("arc"), "arc");
+        // Unknown styles fall back to "dots"
+        assert_eq!(parse("pinwheel"), "dots");
+    }
+
+    #[test]
+    fn test_config_session_autosave_interval() {
+        assert_eq!(Config::default().session.autosave_interval_secs, 0);
+
+        let lua_config = "config = { session = { autosave_interval_secs = 120 } }";
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.session.autosave_interval_secs, 120);
+    }
+
+    #[test]
+    fn test_config_plugins_directory() {
+        assert!(Config::default().plugins.directory.is_none());
+
+        let lua_config = r#"config = { plugins = { directory = "~/.furnace/plugins" } }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            config.plugins.directory,
+            Some(format!("{}/.furnace/plugins", home.display()))
+        );
+    }
+
+    #[test]
+    fn test_config_ui_notification_duration() {
+        assert_eq!(Config::default().ui.notification_duration_secs, 2);
+
+        let lua_config = "config = { ui = { notification_duration_secs = 5 } }";
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.ui.notification_duration_secs, 5);
+
+        let lua_config = "config = { ui = { notification_duration_secs = 0 } }";
+        std::fs::write(&config_path, lua_config).unwrap();
+        let errors = Config::load_validated(&config_path, None).unwrap_err();
+        assert_eq!(errors[0].path, "ui.notification_duration_secs");
+    }
+
+    #[test]
+    fn test_config_paths_missing_variable() {
+        let lua_config = r#"config = {
+    shell = {
+        working_dir = "$FURNACE_TEST_UNSET/projects",
+    },
+    theme = {
+        background_image = { image_path = "%FURNACE_TEST_UNSET%\bg.png" },
+    },
+}
+"#;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(&path, lua_config).unwrap();
+
+        let message = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(message.contains("FURNACE_TEST_UNSET"));
+
+        // Reported once, as the variable rather than a missing directory
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        let expected = if cfg!(windows) {
+            "theme.background_image.image_path"
+        } else {
+            "shell.working_dir"
+        };
+        let unset: Vec<_> = errors
+            .iter()
+            .filter(|error| error.message.contains("is not set"))
+            .collect();
+        assert_eq!(unset.len(), 1, "{errors:?}");
+        assert_eq!(unset[0].path, expected);
+        assert_eq!(unset[0].line, Some(if cfg!(windows) { 6 } else { 3 }));
+        assert!(!errors
+            .iter()
+            .any(|error| error.path == expected && error.message.contains("does not exist")));
+    }
+
     #[test]
     fn test_config_validation_scrollback_clamped() {
         let lua_config = r#"
@@ -1058,6 +1946,96 @@ config = {
         assert_eq!(config.terminal.scrollback_lines, 1);
     }
 
+    #[test]
+    fn test_config_prompt_regex() {
+        let parse = |value: &str| {
+            let lua_config = format!("config = {{ shell = {{ prompt_regex = {value} }} }}");
+            let dir = tempfile::tempdir().unwrap();
+            let config_path = dir.path().join("test_config.lua");
+            std::fs::write(&config_path, lua_config).unwrap();
+            Config::load_from_file(config_path.to_str().unwrap())
+                .unwrap()
+                .shell
+                .prompt_regex
+        };
+
+        assert_eq!(Config::default().shell.prompt_regex, None);
+        assert_eq!(parse("nil"), None);
+        assert_eq!(
+            parse(r"[[^\w+@\w+ .*\$ $]]").as_deref(),
+            Some(r"^\w+@\w+ .*\$ $")
+        );
+        // Patterns that don't compile fall back to the built-in detection
+        assert_eq!(parse(r#""(unclosed""#), None);
+    }
+
+    #[test]
+    fn test_config_exit_handling() {
+        let shell = Config::default().shell;
+        assert!(shell.close_on_exit);
+        assert!(shell.quit_on_last_exit);
+
+        let lua_config = r#"
+config = {
+    shell = {
+        close_on_exit = false,
+        quit_on_last_exit = false
+    }
+}
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+        assert!(!config.shell.close_on_exit);
+        assert!(!config.shell.quit_on_last_exit);
+    }
+
+    #[test]
+    fn test_config_read_buffer_kb() {
+        let parse = |value: &str| {
+            let lua_config = format!("config = {{ shell = {{ read_buffer_kb = {value} }} }}");
+            let dir = tempfile::tempdir().unwrap();
+            let config_path = dir.path().join("test_config.lua");
+            std::fs::write(&config_path, lua_config).unwrap();
+            Config::load_from_file(config_path.to_str().unwrap())
+                .unwrap()
+                .shell
+        };
+
+        assert_eq!(Config::default().shell.read_buffer_size(), 8 * 1024);
+        assert_eq!(parse("64").read_buffer_size(), 64 * 1024);
+        // Out-of-range sizes are clamped
+        assert_eq!(parse("0").read_buffer_kb, 1);
+        assert_eq!(parse("100000").read_buffer_kb, 1024);
+    }
+
+    #[test]
+    fn test_config_fonts() {
+        let lua_config = r#"
+config = {
+    terminal = {
+        font_family = "Fira Code",
+        fallback_fonts = { "Noto Sans Symbols 2", "/opt/fonts/NotoSansCJK.otf" }
+    }
+}
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.terminal.font_family, "Fira Code");
+        assert_eq!(
+            config.terminal.fallback_fonts,
+            vec!["Noto Sans Symbols 2", "/opt/fonts/NotoSansCJK.otf"]
+        );
+
+        let defaults = Config::default().terminal;
+        assert_eq!(defaults.font_family, "JetBrains Mono");
+        assert!(defaults.fallback_fonts.is_empty());
+    }
+
     #[test]
     fn test_max_history_clamped_to_minimum() {
         let lua_config = r#"
@@ -1074,4 +2052,461 @@ config = {
         // max_history 0 should be clamped to 1
         assert_eq!(config.terminal.max_history, 1);
     }
+
+    #[test]
+    fn test_translator_custom_mappings() {
+        let lua_config = r#"
+config = {
+    translator = {
+        custom = {
+            bat = "type",
+            dir = "",
+            ll = { target = "ls -la", passthrough_args = false },
+        }
+    }
+}
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("test_config.lua");
+        std::fs::write(&config_path, lua_config).unwrap();
+        let config = Config::load_from_file(config_path.to_str().unwrap()).unwrap();
+
+        let custom = &config.translator.custom;
+        assert_eq!(custom.len(), 3);
+        assert_eq!(custom["bat"].target, "type");
+        assert!(custom["bat"].passthrough_args);
+        assert!(custom["dir"].target.is_empty());
+        assert_eq!(custom["ll"].target, "ls -la");
+        assert!(!custom["ll"].passthrough_args);
+
+        let translator = config.translator.translator(true);
+        assert_eq!(
+            translator.translate("bat file.txt").final_command,
+            "type file.txt"
+        );
+        assert_eq!(translator.translate("ll ignored").final_command, "ls -la");
+        // An empty target turns the built-in `dir` translation off
+        assert!(!translator.translate("dir /A").translated);
+    }
+
+    #[test]
+    fn test_translator_defaults_empty() {
+        let config = Config::load_from_str(DEFAULT_CONFIG_LUA, ConfigFormat::Lua, None).unwrap();
+        assert!(config.translator.custom.is_empty());
+    }
+
+    #[test]
+    fn test_load_validated_reports_every_problem() {
+        let lua_config = r##"config = {
+    terminal = {
+        font_size = 500,
+        cursor_style = "blok",
+    },
+    theme = {
+        foreground = "#GGGGGG",
+        colors = {
+            red = "not a color",
+        },
+    },
+    keybindings = {
+        new_tab = "Ctrl+Shfit+T",
+        custom = {
+            frobnicate = "Ctrl+Q",
+        },
+    },
+    plugins = {
+        directory = "/nonexistent/furnace-plugins",
+    },
+}
+"##;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(&path, lua_config).unwrap();
+
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        let found: Vec<(&str, Option<usize>)> = errors
+            .iter()
+            .map(|error| (error.path.as_str(), error.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("terminal.font_size", Some(3)),
+                ("terminal.cursor_style", Some(4)),
+                ("theme.foreground", Some(7)),
+                ("theme.colors.red", Some(9)),
+                ("keybindings.new_tab", Some(13)),
+                ("keybindings.custom.frobnicate", Some(15)),
+                ("plugins.directory", Some(19)),
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "line 3: terminal.font_size: 500 is out of range, expected 1 to 200"
+        );
+        assert!(errors[4].message.contains("'Shfit'"));
+        assert!(errors[5].message.contains("frobnicate"));
+
+        // The forgiving loader still accepts the same file
+        assert_eq!(
+            Config::load_from_file(&path).unwrap().terminal.font_size,
+            200
+        );
+    }
+
+    #[test]
+    fn test_load_validated_lua_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+
+        fs::write(
+            &path,
+            "config = {\n    terminal = {\n        font_size = 12,,\n    },\n}\n",
+        )
+        .unwrap();
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(3));
+        assert!(errors[0].path.is_empty());
+
+        // A wrong type is reported against its setting
+        fs::write(
+            &path,
+            "config = {\n    terminal = { font_size = \"big\" },\n}\n",
+        )
+        .unwrap();
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "terminal.font_size");
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(errors[0].message, "expected a number, found string");
+
+        assert!(Config::load_validated(dir.path().join("missing.lua"), None).is_err());
+    }
+
+    #[test]
+    fn test_check_keys_reports_unknown_and_deprecated() {
+        let lua_config = r##"config = {
+    terminal = {
+        font_szie = 14,
+        enable_ligatures = true,
+    },
+    theme = { colors = { purple = "#800080" } },
+    termnial = {},
+}
+"##;
+        let lua = Lua::new();
+        let table = formats::read_table(&lua, lua_config, ConfigFormat::Lua).unwrap();
+        let ignored = validate::check_keys(&table);
+        let paths: Vec<&str> = ignored.iter().map(|key| key.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "terminal.enable_ligatures",
+                "terminal.font_szie",
+                "termnial",
+                "theme.colors.purple",
+            ]
+        );
+        assert!(ignored[0].message.starts_with("deprecated"));
+        assert_eq!(ignored[1].message, "unknown setting, ignored");
+
+        // Ignored keys are only warned about; the config still loads
+        let config = Config::load_validated_str(lua_config, ConfigFormat::Lua, None).unwrap();
+        assert_eq!(config.terminal.font_size, 12);
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let config =
+            Config::load_validated_str(DEFAULT_CONFIG_LUA, ConfigFormat::Lua, None).unwrap();
+        assert!(config.validate().is_empty());
+        assert!(Config::default().validate().is_empty());
+
+        let lua = Lua::new();
+        let table = formats::read_table(&lua, DEFAULT_CONFIG_LUA, ConfigFormat::Lua).unwrap();
+        assert!(validate::check_keys(&table).is_empty());
+    }
+
+    /// One config written in every supported format
+    const SAME_CONFIG: [(&str, &str); 5] = [
+        (
+            "config.lua",
+            r##"config = {
+    shell = { default_shell = "/bin/zsh", env = { EDITOR = "vim" } },
+    terminal = { font_size = 15, cursor_style = "bar", fallback_fonts = { "Fira Code", "Noto Emoji" } },
+    theme = { foreground = "#EEEEEE", colors = { red = "#FF5555" } },
+    keybindings = { new_tab = "Ctrl+Alt+T", custom = { list_sessions = "Ctrl+B s" } },
+    features = { autocomplete = true },
+    progress_bar = { spinner_style = "dots" },
+}
+"##,
+        ),
+        (
+            "config.json",
+            r##"{
+    "shell": { "default_shell": "/bin/zsh", "env": { "EDITOR": "vim" } },
+    "terminal": { "font_size": 15, "cursor_style": "bar", "fallback_fonts": ["Fira Code", "Noto Emoji"] },
+    "theme": { "foreground": "#EEEEEE", "colors": { "red": "#FF5555" } },
+    "keybindings": { "new_tab": "Ctrl+Alt+T", "custom": { "list_sessions": "Ctrl+B s" } },
+    "features": { "autocomplete": true },
+    "progress_bar": { "spinner_style": "dots" }
+}
+"##,
+        ),
+        (
+            "config.yaml",
+            r##"shell:
+  default_shell: /bin/zsh
+  env:
+    EDITOR: vim
+terminal:
+  font_size: 15
+  cursor_style: bar
+  fallback_fonts: [Fira Code, Noto Emoji]
+theme:
+  foreground: "#EEEEEE"
+  colors:
+    red: "#FF5555"
+keybindings:
+  new_tab: Ctrl+Alt+T
+  custom:
+    list_sessions: Ctrl+B s
+features:
+  autocomplete: true
+progress_bar:
+  spinner_style: dots
+"##,
+        ),
+        (
+            "config.yml",
+            r##"{ shell: { default_shell: /bin/zsh, env: { EDITOR: vim } }, terminal: { font_size: 15, cursor_style: bar, fallback_fonts: [Fira Code, Noto Emoji] }, theme: { foreground: "#EEEEEE", colors: { red: "#FF5555" } }, keybindings: { new_tab: Ctrl+Alt+T, custom: { list_sessions: Ctrl+B s } }, features: { autocomplete: true }, progress_bar: { spinner_style: dots } }
+"##,
+        ),
+        (
+            "config.toml",
+            r##"[shell]
+default_shell = "/bin/zsh"
+env = { EDITOR = "vim" }
+
+[terminal]
+font_size = 15
+cursor_style = "bar"
+fallback_fonts = ["Fira Code", "Noto Emoji"]
+
+[theme]
+foreground = "#EEEEEE"
+
+[theme.colors]
+red = "#FF5555"
+
+[keybindings]
+new_tab = "Ctrl+Alt+T"
+custom = { list_sessions = "Ctrl+B s" }
+
+[features]
+autocomplete = true
+
+[progress_bar]
+spinner_style = "dots"
+"##,
+        ),
+    ];
+
+    #[test]
+    fn test_config_formats_load_the_same_config() {
+        let dir = tempdir().unwrap();
+        let mut configs = Vec::new();
+        for (name, contents) in SAME_CONFIG {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            let config = Config::load_from_file(&path).unwrap();
+            assert_eq!(
+                Config::load_validated(&path, None).unwrap(),
+                config,
+                "{name}"
+            );
+            configs.push((name, config));
+        }
+
+        let (_, lua) = &configs[0];
+        assert_eq!(lua.terminal.font_size, 15);
+        assert_eq!(lua.theme.colors.red, "#FF5555");
+        assert_eq!(lua.terminal.fallback_fonts, vec!["Fira Code", "Noto Emoji"]);
+        assert_eq!(lua.shell.env.get("EDITOR").map(String::as_str), Some("vim"));
+        for (name, config) in &configs[1..] {
+            assert_eq!(config, lua, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_example_yaml_config_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config.example.yaml");
+        let result = Config::load_validated(&path, None);
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+
+    #[test]
+    fn test_printed_json_loads_back() {
+        let dir = tempdir().unwrap();
+        let (name, contents) = SAME_CONFIG[0];
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        let mut config = Config::load_from_file(&path).unwrap();
+        config.theme.background_image = Some(BackgroundConfig::default());
+        config.translator.custom.insert(
+            "ll".to_string(),
+            TranslatorMapping {
+                target: "ls -la".to_string(),
+                passthrough_args: false,
+            },
+        );
+
+        let json = config.to_json().unwrap();
+        assert_eq!(json, config.to_json().unwrap());
+        let path = dir.path().join("printed.json");
+        fs::write(&path, &json).unwrap();
+        assert_eq!(Config::load_from_file(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_config_format_errors() {
+        let dir = tempdir().unwrap();
+
+        let path = dir.path().join("config.ini");
+        fs::write(&path, "font_size = 12").unwrap();
+        let message = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(message.contains("'.ini'"), "{message}");
+        assert!(message.contains(".json"), "{message}");
+        assert_eq!(Config::load_validated(&path, None).unwrap_err().len(), 1);
+
+        // Parse errors carry the parser's line
+        let path = dir.path().join("config.json");
+        fs::write(
+            &path,
+            "{\n  \"terminal\": {\n    \"font_size\": 12,,\n  }\n}\n",
+        )
+        .unwrap();
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(3));
+
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[terminal]\nfont_size = 12\ncursor_style = \n").unwrap();
+        let errors = Config::load_validated(&path, None).unwrap_err();
+        assert_eq!(errors[0].line, Some(3));
+
+        // The top level must be a table of settings
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "- font_size\n").unwrap();
+        assert!(Config::load_from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_config_formats_validate_with_lines() {
+        let dir = tempdir().unwrap();
+        let files = [
+            (
+                "config.yaml",
+                "terminal:\n  font_size: 500\ntheme:\n  colors:\n    red: nope\n",
+            ),
+            (
+                "config.toml",
+                "[terminal]\nfont_size = 500\n\n[theme.colors]\nred = \"nope\"\n",
+            ),
+            (
+                "config.json",
+                "{\n  \"terminal\": { \"font_size\": 500 },\n  \"theme\": {\n    \"colors\": {\n      \"red\": \"nope\"\n    }\n  }\n}\n",
+            ),
+        ];
+        for (name, contents) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            let errors = Config::load_validated(&path, None).unwrap_err();
+            let found: Vec<(&str, Option<usize>)> = errors
+                .iter()
+                .map(|error| (error.path.as_str(), error.line))
+                .collect();
+            assert_eq!(
+                found,
+                vec![
+                    ("terminal.font_size", Some(2)),
+                    ("theme.colors.red", Some(5)),
+                ],
+                "{name}"
+            );
+        }
+    }
+
+    const PROFILES_LUA: &str = r##"config = {
+    terminal = {
+        font_size = 12,
+        cursor_style = "bar",
+        fallback_fonts = { "Noto Sans Mono", "DejaVu Sans Mono" },
+    },
+    theme = {
+        foreground = "#DDDDDD",
+        colors = { red = "#CC0000", green = "#00CC00" },
+    },
+    features = { autocomplete = true },
+    profiles = {
+        presenting = {
+            terminal = { font_size = 20, fallback_fonts = { "Fira Code" } },
+            theme = { colors = { red = "#FF5555" } },
+        },
+        quiet = {
+            features = { autocomplete = false },
+        },
+    },
+}
+"##;
+
+    #[test]
+    fn test_profile_overrides_base() {
+        let base = Config::load_from_str(PROFILES_LUA, ConfigFormat::Lua, None).unwrap();
+        let config =
+            Config::load_from_str(PROFILES_LUA, ConfigFormat::Lua, Some("presenting")).unwrap();
+
+        // Profile values win, nested tables merge key by key
+        assert_eq!(config.terminal.font_size, 20);
+        assert_eq!(config.theme.colors.red, "#FF5555");
+        assert_eq!(config.theme.colors.green, "#00CC00");
+        assert_eq!(config.terminal.cursor_style, "bar");
+        assert_eq!(config.theme.foreground, "#DDDDDD");
+        assert!(config.features.autocomplete);
+        // Lists are replaced whole
+        assert_eq!(config.terminal.fallback_fonts, vec!["Fira Code"]);
+
+        // Nothing else differs from the base
+        let mut expected = base.clone();
+        expected.terminal.font_size = 20;
+        expected.theme.colors.red = "#FF5555".to_string();
+        expected.terminal.fallback_fonts = vec!["Fira Code".to_string()];
+        assert_eq!(config, expected);
+
+        let quiet = Config::load_from_str(PROFILES_LUA, ConfigFormat::Lua, Some("quiet")).unwrap();
+        assert!(!quiet.features.autocomplete);
+        assert_eq!(quiet.terminal.font_size, 12);
+    }
+
+    #[test]
+    fn test_unknown_profile_lists_available() {
+        let message = Config::load_from_str(PROFILES_LUA, ConfigFormat::Lua, Some("gaming"))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            message,
+            "Unknown profile 'gaming', expected one of: presenting, quiet"
+        );
+
+        let errors = Config::load_validated_str(PROFILES_LUA, ConfigFormat::Lua, Some("gaming"))
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("presenting, quiet"));
+
+        let message = Config::load_from_str(DEFAULT_CONFIG_LUA, ConfigFormat::Lua, Some("work"))
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("defines no profiles"));
+    }
 }
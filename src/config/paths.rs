@@ -0,0 +1,236 @@
+//! Home-directory and environment variable expansion in path settings
+//!
+//! A leading `~` stands for the home directory on every platform. Variables
+//! follow the platform's own convention: `$VAR` or `${VAR}` on Unix-like
+//! systems and `%VAR%` on Windows. Text that doesn't form a variable, such as
+//! a lone `$` or `%`, is kept as written. A variable that isn't set is an error
+//! rather than an empty string, so a typo can't silently point a setting at
+//! the wrong directory.
+
+use std::path::PathBuf;
+
+use super::{Config, ConfigError};
+
+/// How environment variables are written in paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvStyle {
+    /// `$VAR` and `${VAR}`
+    Unix,
+    /// `%VAR%`
+    Windows,
+}
+
+impl EnvStyle {
+    /// The convention of the platform Furnace was built for
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+}
+
+/// Expand `~` and environment variables in `path` for the current platform
+///
+/// # Errors
+/// Returns an error naming the variable if one isn't set, or if the path
+/// starts with `~` and the home directory is unknown
+pub fn expand_path(path: &str) -> Result<String, String> {
+    expand_with(path, EnvStyle::current(), dirs::home_dir(), |name| {
+        std::env::var(name).ok()
+    })
+}
+
+/// Expand `path` with the given variable syntax, home directory and variables
+fn expand_with(
+    path: &str,
+    style: EnvStyle,
+    home: Option<PathBuf>,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+
+    // `~` only means home as the whole path or before a separator; `~user` is kept
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            let home = home.ok_or("home directory is unknown, so '~' can't be expanded")?;
+            expanded.push_str(&home.to_string_lossy());
+            rest = after;
+        }
+    }
+
+    let lookup = |name: &str| var(name).ok_or(format!("environment variable '{name}' is not set"));
+    while let Some((before, name, after)) = next_variable(rest, style) {
+        expanded.push_str(before);
+        expanded.push_str(&lookup(name)?);
+        rest = after;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// The text before the first variable in `text`, its name, and the text after it
+fn next_variable(text: &str, style: EnvStyle) -> Option<(&str, &str, &str)> {
+    let sigil = match style {
+        EnvStyle::Unix => '$',
+        EnvStyle::Windows => '%',
+    };
+    let mut from = 0;
+    while let Some(found) = text[from..].find(sigil) {
+        let start = from + found;
+        let after = &text[start + 1..];
+        let variable = match style {
+            EnvStyle::Unix => {
+                if let Some(braced) = after.strip_prefix('{') {
+                    braced
+                        .find('}')
+                        .map(|end| (&braced[..end], &braced[end + 1..]))
+                        .filter(|(name, _)| is_name(name))
+                } else {
+                    let len = after
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(after.len());
+                    Some((&after[..len], &after[len..])).filter(|(name, _)| is_name(name))
+                }
+            }
+            EnvStyle::Windows => after
+                .find('%')
+                .map(|end| (&after[..end], &after[end + 1..]))
+                .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace)),
+        };
+        if let Some((name, rest)) = variable {
+            return Some((&text[..start], name, rest));
+        }
+        from = start + 1;
+    }
+    None
+}
+
+/// Whether `name` can be a Unix variable name
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Config {
+    /// Expand `~` and environment variables in every path setting
+    ///
+    /// Settings that fail to expand keep their text and are reported.
+    pub(super) fn expand_paths(&mut self) -> Vec<ConfigError> {
+        let image_path = self
+            .theme
+            .background_image
+            .as_mut()
+            .and_then(|bg| bg.image_path.as_mut());
+        let paths = [
+            ("shell.working_dir", self.shell.working_dir.as_mut()),
+            ("plugins.directory", self.plugins.directory.as_mut()),
+            ("logging.file", self.logging.file.as_mut()),
+            ("theme.background_image.image_path", image_path),
+        ];
+
+        let mut errors = Vec::new();
+        for (name, path) in paths {
+            let Some(path) = path else {
+                continue;
+            };
+            match expand_path(path) {
+                Ok(expanded) => *path = expanded,
+                Err(e) => errors.push(ConfigError::new(name, e)),
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(path: &str, style: EnvStyle) -> Result<String, String> {
+        let home = Some(PathBuf::from("/home/me"));
+        expand_with(path, style, home, |name| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "USERPROFILE" => Some(r"C:\Users\me".to_string()),
+            "XDG_DATA_HOME" => Some("/data".to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(
+            expand("~/themes", EnvStyle::Unix).as_deref(),
+            Ok("/home/me/themes")
+        );
+        assert_eq!(expand("~", EnvStyle::Windows).as_deref(), Ok("/home/me"));
+        assert_eq!(
+            expand(r"~\themes", EnvStyle::Windows).as_deref(),
+            Ok(r"/home/me\themes")
+        );
+        // Only a leading `~` before a separator
+        assert_eq!(
+            expand("~other/themes", EnvStyle::Unix).as_deref(),
+            Ok("~other/themes")
+        );
+        assert_eq!(expand("a/~/b", EnvStyle::Unix).as_deref(), Ok("a/~/b"));
+
+        let unknown = expand_with("~/x", EnvStyle::Unix, None, |_| None);
+        assert!(unknown.unwrap_err().contains("home directory"));
+    }
+
+    #[test]
+    fn test_expand_unix_variables() {
+        assert_eq!(
+            expand("$HOME/x", EnvStyle::Unix).as_deref(),
+            Ok("/home/me/x")
+        );
+        assert_eq!(
+            expand("${XDG_DATA_HOME}furnace/$HOME", EnvStyle::Unix).as_deref(),
+            Ok("/datafurnace//home/me")
+        );
+        // Text that isn't a variable is kept
+        assert_eq!(
+            expand("cost$/5$ ${}", EnvStyle::Unix).as_deref(),
+            Ok("cost$/5$ ${}")
+        );
+        // Percent signs mean nothing here
+        assert_eq!(
+            expand("%HOME%/x", EnvStyle::Unix).as_deref(),
+            Ok("%HOME%/x")
+        );
+
+        assert_eq!(
+            expand("$NOPE/x", EnvStyle::Unix),
+            Err("environment variable 'NOPE' is not set".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_windows_variables() {
+        assert_eq!(
+            expand(r"%USERPROFILE%\x", EnvStyle::Windows).as_deref(),
+            Ok(r"C:\Users\me\x")
+        );
+        assert_eq!(
+            expand("100% done", EnvStyle::Windows).as_deref(),
+            Ok("100% done")
+        );
+        assert_eq!(
+            expand("50% to 60%", EnvStyle::Windows).as_deref(),
+            Ok("50% to 60%")
+        );
+        assert_eq!(expand("$HOME", EnvStyle::Windows).as_deref(), Ok("$HOME"));
+
+        assert_eq!(
+            expand(r"%APPDATA%\furnace", EnvStyle::Windows),
+            Err("environment variable 'APPDATA' is not set".to_string())
+        );
+    }
+}
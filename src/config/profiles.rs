@@ -0,0 +1,79 @@
+//! Named profiles
+//!
+//! A config can hold alternative settings under `profiles`, keyed by name:
+//!
+//! ```lua
+//! config = {
+//!     terminal = { font_size = 12 },
+//!     profiles = {
+//!         work = { theme = { background = "#101820" } },
+//!         presenting = { terminal = { font_size = 20 } },
+//!     },
+//! }
+//! ```
+//!
+//! Selecting a profile merges its tables into the base config before it is
+//! read, so the profile's values win and everything it leaves out comes from
+//! the base. Lists such as `fallback_fonts` are replaced whole rather than
+//! merged item by item.
+
+use mlua::{Table, Value};
+
+/// Override the base settings in `config` with those of the profile `name`
+///
+/// # Errors
+/// Returns an error listing the available profiles if there is none named
+/// `name`, or if merging the tables fails
+pub(super) fn apply_profile(config: &Table, name: &str) -> Result<(), String> {
+    let profile = config
+        .get::<_, Option<Table>>("profiles")
+        .ok()
+        .flatten()
+        .and_then(|profiles| profiles.get::<_, Option<Table>>(name).ok().flatten());
+    let Some(profile) = profile else {
+        let names = profile_names(config);
+        return Err(if names.is_empty() {
+            format!("Unknown profile '{name}': the config defines no profiles")
+        } else {
+            format!(
+                "Unknown profile '{name}', expected one of: {}",
+                names.join(", ")
+            )
+        });
+    };
+    merge_tables(config, &profile).map_err(|e| format!("Failed to apply profile '{name}': {e}"))
+}
+
+/// Names of the profiles defined in `config`, sorted
+#[must_use]
+pub(super) fn profile_names(config: &Table) -> Vec<String> {
+    let Ok(Some(profiles)) = config.get::<_, Option<Table>>("profiles") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = profiles
+        .pairs::<String, Value>()
+        .filter_map(|pair| pair.ok())
+        .filter(|(_, value)| matches!(value, Value::Table(_)))
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+/// Copy every value in `overrides` into `base`, descending into nested tables
+fn merge_tables<'lua>(base: &Table<'lua>, overrides: &Table<'lua>) -> mlua::Result<()> {
+    for pair in overrides.clone().pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        if let Value::Table(ref inner) = value {
+            // A list replaces the base value; a table of settings merges into it
+            if inner.raw_len() == 0 {
+                if let Value::Table(existing) = base.get::<_, Value>(key.clone())? {
+                    merge_tables(&existing, inner)?;
+                    continue;
+                }
+            }
+        }
+        base.set(key, value)?;
+    }
+    Ok(())
+}
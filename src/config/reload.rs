@@ -0,0 +1,411 @@
+//! Live config reloading
+//!
+//! A [`ConfigWatcher`] notices when the config file is saved and loads it
+//! again with [`Config::load_validated`]. [`ConfigDiff`] then sorts the
+//! settings that changed into those a running terminal can pick up, which
+//! [`Config::apply_live`] copies over, and those that only take effect after a
+//! restart.
+
+use anyhow::{Context, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+
+use super::{Config, ConfigError, ThemeConfig};
+
+/// Settings that differ between two configs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Changed settings a running terminal applies immediately
+    pub live: Vec<&'static str>,
+    /// Changed settings that need a restart
+    pub restart: Vec<&'static str>,
+}
+
+impl ConfigDiff {
+    /// Compare the running config with a reloaded one
+    #[must_use]
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let (o, n) = (old, new);
+        // (setting, changed, applied live)
+        let settings = [
+            (
+                "theme",
+                theme_colors(&o.theme) != theme_colors(&n.theme),
+                true,
+            ),
+            (
+                "theme.background_image",
+                o.theme.background_image != n.theme.background_image,
+                false,
+            ),
+            ("keybindings", o.keybindings != n.keybindings, true),
+            (
+                "hooks.custom_keybindings",
+                o.hooks.custom_keybindings != n.hooks.custom_keybindings,
+                true,
+            ),
+            (
+                "features.resource_monitor",
+                o.features.resource_monitor != n.features.resource_monitor,
+                true,
+            ),
+            (
+                "features.autocomplete",
+                o.features.autocomplete != n.features.autocomplete,
+                true,
+            ),
+            (
+                "features.progress_bar",
+                o.features.progress_bar != n.features.progress_bar,
+                true,
+            ),
+            (
+                "features.command_palette",
+                o.features.command_palette != n.features.command_palette,
+                true,
+            ),
+            (
+                "features.session_manager",
+                o.features.session_manager != n.features.session_manager,
+                false,
+            ),
+            (
+                "features.theme_manager",
+                o.features.theme_manager != n.features.theme_manager,
+                false,
+            ),
+            (
+                "features.auto_save_session",
+                o.features.auto_save_session != n.features.auto_save_session,
+                false,
+            ),
+            (
+                "terminal.cursor_style",
+                o.terminal.cursor_style != n.terminal.cursor_style,
+                true,
+            ),
+            (
+                "terminal.line_wrap",
+                o.terminal.line_wrap != n.terminal.line_wrap,
+                true,
+            ),
+            (
+                "terminal.bell_style",
+                o.terminal.bell_style != n.terminal.bell_style,
+                true,
+            ),
+            (
+                "terminal.tab_title_source",
+                o.terminal.tab_title_source != n.terminal.tab_title_source,
+                true,
+            ),
+            (
+                "terminal.tab_title_max_width",
+                o.terminal.tab_title_max_width != n.terminal.tab_title_max_width,
+                true,
+            ),
+            (
+                "terminal.confirm_multiline_paste",
+                o.terminal.confirm_multiline_paste != n.terminal.confirm_multiline_paste,
+                true,
+            ),
+            (
+                "terminal.bracketed_paste",
+                o.terminal.bracketed_paste != n.terminal.bracketed_paste,
+                true,
+            ),
+            (
+                "terminal.sanitize_output",
+                o.terminal.sanitize_output != n.terminal.sanitize_output,
+                true,
+            ),
+            (
+                "terminal.allow_osc52",
+                o.terminal.allow_osc52 != n.terminal.allow_osc52,
+                true,
+            ),
+            (
+                "terminal.scrollback_to_disk",
+                o.terminal.scrollback_to_disk != n.terminal.scrollback_to_disk,
+                true,
+            ),
+            (
+                "terminal.font_size",
+                o.terminal.font_size != n.terminal.font_size,
+                false,
+            ),
+            (
+                "terminal.font_family",
+                o.terminal.font_family != n.terminal.font_family,
+                false,
+            ),
+            (
+                "terminal.fallback_fonts",
+                o.terminal.fallback_fonts != n.terminal.fallback_fonts,
+                false,
+            ),
+            (
+                "terminal.hardware_acceleration",
+                o.terminal.hardware_acceleration != n.terminal.hardware_acceleration,
+                false,
+            ),
+            (
+                "terminal.enable_tabs",
+                o.terminal.enable_tabs != n.terminal.enable_tabs,
+                false,
+            ),
+            (
+                "terminal.enable_split_pane",
+                o.terminal.enable_split_pane != n.terminal.enable_split_pane,
+                false,
+            ),
+            (
+                "terminal.max_history",
+                o.terminal.max_history != n.terminal.max_history,
+                false,
+            ),
+            (
+                "terminal.max_fps",
+                o.terminal.max_fps != n.terminal.max_fps,
+                false,
+            ),
+            (
+                "terminal.scrollback_lines",
+                o.terminal.scrollback_lines != n.terminal.scrollback_lines,
+                false,
+            ),
+            ("shell", o.shell != n.shell, false),
+            ("hooks", other_hooks_changed(o, n), false),
+            ("translator", o.translator != n.translator, false),
+            ("session", o.session != n.session, false),
+            ("progress_bar", o.progress_bar != n.progress_bar, false),
+            ("plugins", o.plugins != n.plugins, false),
+            ("ui", o.ui != n.ui, true),
+            ("logging", o.logging != n.logging, false),
+        ];
+
+        let mut diff = Self::default();
+        for (name, changed, live) in settings {
+            if !changed {
+                continue;
+            }
+            if live {
+                diff.live.push(name);
+            } else {
+                diff.restart.push(name);
+            }
+        }
+        diff
+    }
+
+    /// Whether nothing changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty() && self.restart.is_empty()
+    }
+}
+
+impl Config {
+    /// Take the settings [`ConfigDiff`] lists as live from `new`, keeping the rest
+    pub fn apply_live(&mut self, new: &Config) {
+        let background_image = self.theme.background_image.take();
+        self.theme = ThemeConfig {
+            background_image,
+            ..new.theme.clone()
+        };
+        self.keybindings = new.keybindings.clone();
+        self.hooks
+            .custom_keybindings
+            .clone_from(&new.hooks.custom_keybindings);
+
+        self.features.resource_monitor = new.features.resource_monitor;
+        self.features.autocomplete = new.features.autocomplete;
+        self.features.progress_bar = new.features.progress_bar;
+        self.features.command_palette = new.features.command_palette;
+
+        let terminal = &new.terminal;
+        self.terminal
+            .cursor_style
+            .clone_from(&terminal.cursor_style);
+        self.terminal.line_wrap.clone_from(&terminal.line_wrap);
+        self.terminal.bell_style.clone_from(&terminal.bell_style);
+        self.terminal
+            .tab_title_source
+            .clone_from(&terminal.tab_title_source);
+        self.terminal.tab_title_max_width = terminal.tab_title_max_width;
+        self.terminal.confirm_multiline_paste = terminal.confirm_multiline_paste;
+        self.terminal.bracketed_paste = terminal.bracketed_paste;
+        self.terminal.sanitize_output = terminal.sanitize_output;
+        self.terminal.allow_osc52 = terminal.allow_osc52;
+        self.terminal.scrollback_to_disk = terminal.scrollback_to_disk;
+
+        self.ui = new.ui.clone();
+    }
+}
+
+/// The theme without its background image, which is only loaded at startup
+fn theme_colors(theme: &ThemeConfig) -> ThemeConfig {
+    ThemeConfig {
+        background_image: None,
+        ..theme.clone()
+    }
+}
+
+/// Whether any hook besides the custom keybindings changed
+fn other_hooks_changed(old: &Config, new: &Config) -> bool {
+    let mut new_hooks = new.hooks.clone();
+    new_hooks
+        .custom_keybindings
+        .clone_from(&old.hooks.custom_keybindings);
+    old.hooks != new_hooks
+}
+
+/// Watches the config file and reloads it when it is saved
+pub struct ConfigWatcher {
+    path: PathBuf,
+    /// Profile applied on every reload, as chosen at launch
+    profile: Option<String>,
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl ConfigWatcher {
+    /// Start watching the config file at `path`, reloading it with `profile`
+    ///
+    /// The file's directory is watched rather than the file, so editors that
+    /// save by replacing the file are noticed too.
+    ///
+    /// # Errors
+    /// Returns an error if the directory cannot be watched
+    pub fn new(path: impl Into<PathBuf>, profile: Option<String>) -> Result<Self> {
+        let path = path.into();
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map_or_else(|| PathBuf::from("."), PathBuf::from);
+
+        let (tx, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).context("Failed to create file watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .context("Failed to watch config directory")?;
+        Ok(Self {
+            path,
+            profile,
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Reload the config if the file changed since the last call
+    ///
+    /// Returns `None` when the file hasn't changed.
+    pub fn poll_changes(&mut self) -> Option<std::result::Result<Config, Vec<ConfigError>>> {
+        let name = self.path.file_name()?;
+        let mut changed = false;
+        // Drain everything so a burst of writes triggers one reload
+        while let Ok(event) = self.events.try_recv() {
+            changed |= event.is_ok_and(|event| {
+                !event.kind.is_access()
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == Some(name))
+            });
+        }
+        changed.then(|| Config::load_validated(&self.path, self.profile.as_deref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackgroundConfig;
+
+    #[test]
+    fn test_diff_unchanged_config_is_empty() {
+        let config = Config::default();
+        assert!(ConfigDiff::between(&config, &config.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.theme.colors.red = "#AA0000".to_string();
+        new.keybindings.new_tab = "Ctrl+Alt+T".to_string();
+        new.features.autocomplete = true;
+        new.features.session_manager = true;
+        new.terminal.cursor_style = "bar".to_string();
+        new.terminal.font_size = 16;
+        new.shell.default_shell = "fish".to_string();
+        new.hooks.on_bell = Some("print('bell')".to_string());
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(
+            diff.live,
+            vec![
+                "theme",
+                "keybindings",
+                "features.autocomplete",
+                "terminal.cursor_style"
+            ]
+        );
+        assert_eq!(
+            diff.restart,
+            vec![
+                "features.session_manager",
+                "terminal.font_size",
+                "shell",
+                "hooks"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_theme_background_image_needs_restart() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.theme.background_image = Some(BackgroundConfig::default());
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert!(diff.live.is_empty());
+        assert_eq!(diff.restart, vec!["theme.background_image"]);
+
+        // Lua keybindings are live even though other hooks are not
+        let mut new = old.clone();
+        new.hooks
+            .custom_keybindings
+            .insert("Ctrl+G".to_string(), "print('g')".to_string());
+        assert_eq!(
+            ConfigDiff::between(&old, &new).live,
+            vec!["hooks.custom_keybindings"]
+        );
+    }
+
+    #[test]
+    fn test_apply_live_takes_only_live_settings() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.theme.foreground = "#EEEEEE".to_string();
+        new.theme.background_image = Some(BackgroundConfig::default());
+        new.keybindings.copy = "Ctrl+Alt+C".to_string();
+        new.features.resource_monitor = true;
+        new.terminal.sanitize_output = false;
+        new.terminal.max_history = 5;
+        new.plugins.directory = Some("/tmp".to_string());
+
+        let mut running = old.clone();
+        running.apply_live(&new);
+        assert_eq!(running.theme.foreground, "#EEEEEE");
+        assert!(running.features.resource_monitor);
+        assert!(!running.terminal.sanitize_output);
+
+        // What's left is exactly what needs a restart
+        let remaining = ConfigDiff::between(&running, &new);
+        assert!(remaining.live.is_empty());
+        assert_eq!(remaining.restart, ConfigDiff::between(&old, &new).restart);
+    }
+}
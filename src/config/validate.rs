@@ -0,0 +1,595 @@
+//! Config validation
+//!
+//! Loading a config is forgiving: an unknown cursor style falls back to the
+//! default, an out-of-range number is clamped and a color that doesn't parse
+//! is replaced when the theme is applied. [`Config::load_validated`] reports
+//! every such problem at once instead, each with the setting's path and, where
+//! it can be found, its line in the file.
+
+use mlua::{Table, Value};
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use tracing::warn;
+
+use super::{Config, ConfigFormat};
+use crate::colors::TrueColor;
+use crate::keybindings::{Action, KeybindingManager};
+
+/// Numeric settings and the range loading clamps them to
+const RANGES: &[(&str, f64, f64)] = &[
+    ("shell.read_buffer_kb", 1.0, 1024.0),
+    ("terminal.font_size", 1.0, 200.0),
+    ("terminal.max_history", 1.0, 1_000_000.0),
+    ("terminal.max_fps", 1.0, 1000.0),
+    ("terminal.scrollback_lines", 1.0, 1_000_000.0),
+    ("terminal.tab_title_max_width", 1.0, 200.0),
+    ("theme.background_image.opacity", 0.0, 1.0),
+    ("theme.cursor_trail.fade_ms", 1.0, 10_000.0),
+    ("ui.notification_duration_secs", 1.0, 60.0),
+    ("logging.max_size_mb", 1.0, 1024.0),
+    ("logging.max_files", 1.0, 100.0),
+];
+
+/// Text settings that only take certain values
+const CHOICES: &[(&str, &[&str])] = &[
+    ("terminal.cursor_style", &["block", "underline", "bar"]),
+    ("terminal.line_wrap", &["wrap", "truncate"]),
+    (
+        "terminal.bell_style",
+        &["none", "visual", "audible", "both"],
+    ),
+    ("terminal.tab_title_source", &["title", "cwd", "process"]),
+    (
+        "progress_bar.spinner_style",
+        &["line", "dots", "braille", "arc", "bounce"],
+    ),
+    (
+        "theme.background_image.mode",
+        &["fill", "fit", "stretch", "tile", "center"],
+    ),
+];
+
+/// Keys each table of the config reads; the empty path is the top level
+const KEYS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "shell",
+            "terminal",
+            "theme",
+            "keybindings",
+            "features",
+            "hooks",
+            "translator",
+            "session",
+            "progress_bar",
+            "plugins",
+            "ui",
+            "logging",
+            "profiles",
+        ],
+    ),
+    (
+        "shell",
+        &[
+            "default_shell",
+            "env",
+            "working_dir",
+            "prompt_regex",
+            "read_buffer_kb",
+            "close_on_exit",
+            "quit_on_last_exit",
+        ],
+    ),
+    (
+        "terminal",
+        &[
+            "max_history",
+            "enable_tabs",
+            "enable_split_pane",
+            "font_size",
+            "font_family",
+            "fallback_fonts",
+            "cursor_style",
+            "scrollback_lines",
+            "scrollback_to_disk",
+            "hardware_acceleration",
+            "max_fps",
+            "line_wrap",
+            "bell_style",
+            "tab_title_source",
+            "tab_title_max_width",
+            "allow_osc52",
+            "bracketed_paste",
+            "confirm_multiline_paste",
+            "sanitize_output",
+        ],
+    ),
+    (
+        "theme",
+        &[
+            "name",
+            "foreground",
+            "background",
+            "cursor",
+            "selection",
+            "colors",
+            "background_image",
+            "cursor_trail",
+        ],
+    ),
+    (
+        "theme.colors",
+        &[
+            "black",
+            "red",
+            "green",
+            "yellow",
+            "blue",
+            "magenta",
+            "cyan",
+            "white",
+            "bright_black",
+            "bright_red",
+            "bright_green",
+            "bright_yellow",
+            "bright_blue",
+            "bright_magenta",
+            "bright_cyan",
+            "bright_white",
+        ],
+    ),
+    (
+        "theme.background_image",
+        &["image_path", "color", "opacity", "mode", "blur", "animate"],
+    ),
+    (
+        "theme.cursor_trail",
+        &[
+            "enabled",
+            "length",
+            "color",
+            "width",
+            "fade_mode",
+            "fade_ms",
+            "animation_speed",
+        ],
+    ),
+    (
+        "features",
+        &[
+            "resource_monitor",
+            "autocomplete",
+            "progress_bar",
+            "session_manager",
+            "theme_manager",
+            "command_palette",
+            "auto_save_session",
+        ],
+    ),
+    (
+        "hooks",
+        &[
+            "on_startup",
+            "on_shutdown",
+            "on_key_press",
+            "on_command_start",
+            "on_command_end",
+            "on_output",
+            "on_bell",
+            "on_title_change",
+            "on_resize",
+            "timeout_ms",
+            "custom_keybindings",
+            "output_filters",
+            "custom_widgets",
+        ],
+    ),
+    ("translator", &["custom"]),
+    ("session", &["autosave_interval_secs"]),
+    ("progress_bar", &["spinner_style"]),
+    ("plugins", &["directory"]),
+    ("ui", &["notification_duration_secs"]),
+    ("logging", &["file", "max_size_mb", "max_files"]),
+];
+
+/// Settings that were dropped, and why they no longer do anything
+const DEPRECATED: &[(&str, &str)] = &[(
+    "terminal.enable_ligatures",
+    "ligatures are not supported, the GPU renderer draws one glyph per cell",
+)];
+
+/// One problem found in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Dotted path of the setting, e.g. `theme.colors.red`; empty for the file as a whole
+    pub path: String,
+    /// Line in the file, when it can be found
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub(super) fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// An error from running the file, at the line Lua reports
+    pub(super) fn from_lua(error: &mlua::Error) -> Self {
+        let message = error.to_string();
+        let line = Regex::new(r"\]:(\d+):")
+            .ok()
+            .and_then(|re| re.captures(&message)?.get(1)?.as_str().parse().ok());
+        Self {
+            path: String::new(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl Config {
+    /// Problems in settings loading accepted or replaced with a default
+    ///
+    /// Checks that colors parse, key combos are valid and bind known actions,
+    /// and configured paths exist. Errors have no line numbers.
+    #[must_use]
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        let theme = &self.theme;
+        let colors = &theme.colors;
+        let theme_colors = [
+            ("foreground", &theme.foreground),
+            ("background", &theme.background),
+            ("cursor", &theme.cursor),
+            ("selection", &theme.selection),
+            ("colors.black", &colors.black),
+            ("colors.red", &colors.red),
+            ("colors.green", &colors.green),
+            ("colors.yellow", &colors.yellow),
+            ("colors.blue", &colors.blue),
+            ("colors.magenta", &colors.magenta),
+            ("colors.cyan", &colors.cyan),
+            ("colors.white", &colors.white),
+            ("colors.bright_black", &colors.bright_black),
+            ("colors.bright_red", &colors.bright_red),
+            ("colors.bright_green", &colors.bright_green),
+            ("colors.bright_yellow", &colors.bright_yellow),
+            ("colors.bright_blue", &colors.bright_blue),
+            ("colors.bright_magenta", &colors.bright_magenta),
+            ("colors.bright_cyan", &colors.bright_cyan),
+            ("colors.bright_white", &colors.bright_white),
+        ];
+        for (name, color) in theme_colors {
+            check_color(&mut errors, &format!("theme.{name}"), color);
+        }
+        if let Some(color) = theme
+            .background_image
+            .as_ref()
+            .and_then(|bg| bg.color.as_ref())
+        {
+            check_color(&mut errors, "theme.background_image.color", color);
+        }
+        if let Some(trail) = &theme.cursor_trail {
+            // The trail color may carry an alpha channel: #RRGGBBAA
+            let hex = trail.color.trim_start_matches('#');
+            let rgb = if hex.len() == 8 { &hex[..6] } else { hex };
+            if TrueColor::from_hex(rgb).is_err() {
+                errors.push(ConfigError::new(
+                    "theme.cursor_trail.color",
+                    format!(
+                        "invalid color '{}', expected #RRGGBB or #RRGGBBAA",
+                        trail.color
+                    ),
+                ));
+            }
+        }
+
+        let kb = &self.keybindings;
+        let combos = [
+            ("new_tab", &kb.new_tab),
+            ("close_tab", &kb.close_tab),
+            ("next_tab", &kb.next_tab),
+            ("prev_tab", &kb.prev_tab),
+            ("split_vertical", &kb.split_vertical),
+            ("split_horizontal", &kb.split_horizontal),
+            ("copy", &kb.copy),
+            ("paste", &kb.paste),
+            ("search", &kb.search),
+            ("clear", &kb.clear),
+            ("save_session", &kb.save_session),
+            ("load_session", &kb.load_session),
+            ("next_theme", &kb.next_theme),
+            ("prev_theme", &kb.prev_theme),
+            ("toggle_resource_monitor", &kb.toggle_resource_monitor),
+            ("toggle_autocomplete", &kb.toggle_autocomplete),
+            ("show_keybindings", &kb.show_keybindings),
+            ("command_palette", &kb.command_palette),
+            ("interrupt", &kb.interrupt),
+            ("restart_shell", &kb.restart_shell),
+            ("prev_command", &kb.prev_command),
+            ("next_command", &kb.next_command),
+            ("copy_rich", &kb.copy_rich),
+            ("grow_pane", &kb.grow_pane),
+            ("shrink_pane", &kb.shrink_pane),
+            ("scroll_to_top", &kb.scroll_to_top),
+            ("scroll_to_bottom", &kb.scroll_to_bottom),
+            ("toggle_stats_overlay", &kb.toggle_stats_overlay),
+        ];
+        for (name, combo) in combos {
+            // An empty combo leaves the action unbound
+            if !combo.is_empty() {
+                check_combo(&mut errors, &format!("keybindings.{name}"), combo);
+            }
+        }
+        let mut custom: Vec<_> = kb.custom.iter().collect();
+        custom.sort();
+        for (name, combo) in custom {
+            let path = format!("keybindings.custom.{name}");
+            if let Err(e) = name.parse::<Action>() {
+                errors.push(ConfigError::new(&path, e));
+            }
+            check_combo(&mut errors, &path, combo);
+        }
+        let mut lua_combos: Vec<_> = self.hooks.custom_keybindings.keys().collect();
+        lua_combos.sort();
+        for combo in lua_combos {
+            check_combo(&mut errors, "hooks.custom_keybindings", combo);
+        }
+
+        let paths = [
+            ("shell.working_dir", self.shell.working_dir.as_ref(), true),
+            ("plugins.directory", self.plugins.directory.as_ref(), true),
+            (
+                "theme.background_image.image_path",
+                theme
+                    .background_image
+                    .as_ref()
+                    .and_then(|bg| bg.image_path.as_ref()),
+                false,
+            ),
+        ];
+        for (name, path, is_dir) in paths {
+            let Some(path) = path else {
+                continue;
+            };
+            let exists = if is_dir {
+                Path::new(path).is_dir()
+            } else {
+                Path::new(path).is_file()
+            };
+            if !exists {
+                let kind = if is_dir { "directory" } else { "file" };
+                errors.push(ConfigError::new(
+                    name,
+                    format!("{kind} '{path}' does not exist"),
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+/// Problems in the raw config table that loading clamps or replaces
+///
+/// Covers numbers outside their range and unknown choices, and settings of
+/// those kinds given a value of the wrong type.
+pub(super) fn check_table(table: &Table) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    for &(path, min, max) in RANGES {
+        match raw_value(table, path) {
+            None => {}
+            Some(Value::Integer(n)) => check_range(&mut errors, path, n as f64, min, max),
+            Some(Value::Number(n)) => check_range(&mut errors, path, n, min, max),
+            Some(other) => errors.push(ConfigError::new(
+                path,
+                format!("expected a number, found {}", other.type_name()),
+            )),
+        }
+    }
+    for &(path, choices) in CHOICES {
+        match raw_value(table, path) {
+            None => {}
+            Some(Value::String(text)) => {
+                let text = text.to_string_lossy();
+                if !choices.contains(&text.as_ref()) {
+                    errors.push(ConfigError::new(
+                        path,
+                        format!(
+                            "unknown value '{text}', expected one of: {}",
+                            choices.join(", ")
+                        ),
+                    ));
+                }
+            }
+            Some(other) => errors.push(ConfigError::new(
+                path,
+                format!("expected a string, found {}", other.type_name()),
+            )),
+        }
+    }
+    errors
+}
+
+/// Keys in the raw config table that loading ignores
+///
+/// Covers misspelled or unknown keys in the tables listed in [`KEYS`], and
+/// settings that were dropped. Keybindings are left out since their action
+/// names are checked by [`Config::validate`].
+pub(super) fn check_keys(table: &Table) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    for &(section, known) in KEYS {
+        let section_table = if section.is_empty() {
+            table.clone()
+        } else {
+            match raw_value(table, section) {
+                Some(Value::Table(section_table)) => section_table,
+                _ => continue,
+            }
+        };
+        for (key, _) in section_table.pairs::<Value, Value>().flatten() {
+            let Value::String(key) = key else {
+                continue;
+            };
+            let key = key.to_string_lossy();
+            if known.contains(&key.as_ref()) {
+                continue;
+            }
+            let path = if section.is_empty() {
+                key.to_string()
+            } else {
+                format!("{section}.{key}")
+            };
+            let message = match DEPRECATED
+                .iter()
+                .find(|(deprecated, _)| *deprecated == path)
+            {
+                Some((_, reason)) => format!("deprecated and ignored: {reason}"),
+                None => "unknown setting, ignored".to_string(),
+            };
+            errors.push(ConfigError::new(path, message));
+        }
+    }
+    errors.sort_by(|a, b| a.path.cmp(&b.path));
+    errors
+}
+
+/// Log a warning for each key [`check_keys`] finds, at its line in `source`
+pub(super) fn warn_ignored_keys(table: &Table, source: &str, format: ConfigFormat) {
+    let mut ignored = check_keys(table);
+    locate(&mut ignored, source, format);
+    for key in ignored {
+        warn!("Config: {key}");
+    }
+}
+
+/// Fill in each error's line from where its setting appears in `source`
+pub(super) fn locate(errors: &mut [ConfigError], source: &str, format: ConfigFormat) {
+    for error in errors.iter_mut().filter(|error| error.line.is_none()) {
+        error.line = find_line(source, &error.path, format);
+    }
+}
+
+/// 1-based line of the setting at `path` in a config
+///
+/// Each part of the path is searched for as a key (`name =` in Lua) after the
+/// line its parent was found on, so `theme.colors.red` finds `red` inside
+/// `colors` inside `theme`. This is a text search, not a parse, so it can be
+/// fooled.
+fn find_line(source: &str, path: &str, format: ConfigFormat) -> Option<usize> {
+    if path.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    let mut start = 0;
+    for name in path.split('.') {
+        let found = lines[start..]
+            .iter()
+            .position(|line| assigns(line, name, format))?;
+        start += found;
+    }
+    Some(start + 1)
+}
+
+/// Whether `line` sets the key `name`, outside a comment
+///
+/// That is `name = ...` in Lua and TOML, `name: ...` in YAML and
+/// `"name": ...` in JSON. A TOML table header such as `[theme.colors]` sets
+/// each key it names.
+fn assigns(line: &str, name: &str, format: ConfigFormat) -> bool {
+    let (comment, separator) = match format {
+        ConfigFormat::Lua => (Some("--"), '='),
+        ConfigFormat::Toml => (Some("#"), '='),
+        ConfigFormat::Yaml => (Some("#"), ':'),
+        ConfigFormat::Json => (None, ':'),
+    };
+    let code = comment
+        .and_then(|comment| line.split(comment).next())
+        .unwrap_or(line);
+
+    if format == ConfigFormat::Toml {
+        let header = code.trim();
+        if let Some(keys) = header.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            let keys = keys.trim_matches(['[', ']']);
+            return keys
+                .split('.')
+                .any(|key| key.trim().trim_matches('"') == name);
+        }
+    }
+
+    code.match_indices(name).any(|(at, _)| {
+        let mut before = code[..at].chars().rev().peekable();
+        let quoted = before.next_if(|&c| c == '"' || c == '\'').is_some();
+        let starts_field = before
+            .next()
+            .is_none_or(|c| c.is_whitespace() || c == '{' || c == ',');
+        let mut rest = &code[at + name.len()..];
+        if quoted {
+            rest = rest.strip_prefix(['"', '\'']).unwrap_or(" ");
+        } else if format == ConfigFormat::Json {
+            return false;
+        }
+        let rest = rest.trim_start();
+        starts_field && rest.starts_with(separator) && !rest.starts_with("==")
+    })
+}
+
+/// The value at a dotted `path` in nested tables, if set
+fn raw_value<'lua>(table: &Table<'lua>, path: &str) -> Option<Value<'lua>> {
+    let mut parts = path.split('.').peekable();
+    let mut current = table.clone();
+    while let Some(part) = parts.next() {
+        let value: Value = current.get(part).ok()?;
+        if parts.peek().is_none() {
+            return (!matches!(value, Value::Nil)).then_some(value);
+        }
+        let Value::Table(next) = value else {
+            return None;
+        };
+        current = next;
+    }
+    None
+}
+
+fn check_range(errors: &mut Vec<ConfigError>, path: &str, value: f64, min: f64, max: f64) {
+    if !(min..=max).contains(&value) {
+        errors.push(ConfigError::new(
+            path,
+            format!("{value} is out of range, expected {min} to {max}"),
+        ));
+    }
+}
+
+fn check_color(errors: &mut Vec<ConfigError>, path: &str, color: &str) {
+    if let Err(e) = TrueColor::from_css(color) {
+        errors.push(ConfigError::new(
+            path,
+            format!("invalid color '{color}': {e}"),
+        ));
+    }
+}
+
+fn check_combo(errors: &mut Vec<ConfigError>, path: &str, combo: &str) {
+    if let Err(e) = KeybindingManager::check_combo(combo) {
+        errors.push(ConfigError::new(path, format!("invalid key combo: {e}")));
+    }
+}
@@ -2,6 +2,9 @@
 //!
 //! Caches rasterized glyphs in a texture atlas for efficient GPU rendering.
 //! Uses fontdue for font rasterization to provide actual glyph bitmaps.
+//!
+//! Characters missing from the primary font are looked up in the configured
+//! fallback fonts, in order, before a tofu box is drawn in their place.
 
 use std::collections::HashMap;
 
@@ -14,6 +17,8 @@ pub struct GlyphCache {
     glyph_map: HashMap<u32, GlyphInfo>,
     /// Font for rasterization
     font: Option<fontdue::Font>,
+    /// Fonts searched in order for characters the primary font lacks
+    fallback_fonts: Vec<fontdue::Font>,
     /// Font size
     font_size: f32,
     /// Font family name (used for font loading)
@@ -52,12 +57,30 @@ impl GlyphCache {
     ///
     /// BUG FIX #4: Implement actual font loading and rasterization
     pub fn new(font_size: f32, font_family: &str) -> Self {
+        Self::with_fonts(font_size, font_family, &[])
+    }
+
+    /// Create a glyph cache with fallback fonts for missing characters
+    ///
+    /// Fallback families that can't be found are skipped with a warning.
+    pub fn with_fonts(font_size: f32, font_family: &str, fallback_families: &[String]) -> Self {
         let font = Self::load_font(font_family);
+        let fallback_fonts = fallback_families
+            .iter()
+            .filter_map(|family| {
+                let font = Self::load_font_family(family);
+                if font.is_none() {
+                    tracing::warn!("Fallback font '{}' not found, skipping", family);
+                }
+                font
+            })
+            .collect();
 
         let atlas_size = 2048;
         let mut cache = Self {
             glyph_map: HashMap::with_capacity(256),
             font,
+            fallback_fonts,
             font_size,
             font_family: font_family.to_string(),
             atlas_size,
@@ -78,17 +101,12 @@ impl GlyphCache {
     /// Tries the requested font first, then falls back to common monospace fonts
     /// available on various operating systems to ensure text is always rendered.
     fn load_font(font_family: &str) -> Option<fontdue::Font> {
-        // Try the requested font family first
-        let font_paths = Self::get_font_paths(font_family);
-
-        for path in &font_paths {
-            if let Ok(data) = std::fs::read(path) {
-                if let Ok(font) = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
-                {
-                    tracing::info!("Loaded font from: {}", path);
-                    return Some(font);
-                }
-            }
+        // Try the requested font family first, then the OS default monospace fonts
+        if let Some(font) = Self::load_font_family(font_family) {
+            return Some(font);
+        }
+        if let Some(font) = Self::load_font_paths(&Self::default_font_paths()) {
+            return Some(font);
         }
 
         // Try common fallback monospace fonts on all platforms
@@ -136,6 +154,25 @@ impl GlyphCache {
         None
     }
 
+    /// Load a single font family (or font file path) without system fallbacks
+    fn load_font_family(font_family: &str) -> Option<fontdue::Font> {
+        Self::load_font_paths(&Self::get_font_paths(font_family))
+    }
+
+    /// Load the first of `paths` that holds a readable font
+    fn load_font_paths(paths: &[String]) -> Option<fontdue::Font> {
+        for path in paths {
+            if let Ok(data) = std::fs::read(path) {
+                if let Ok(font) = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+                {
+                    tracing::info!("Loaded font from: {}", path);
+                    return Some(font);
+                }
+            }
+        }
+        None
+    }
+
     /// Get common font file paths based on font name (platform-specific)
     ///
     /// Generates paths using multiple naming conventions (with spaces, without,
@@ -143,6 +180,14 @@ impl GlyphCache {
     fn get_font_paths(font_family: &str) -> Vec<String> {
         let mut paths = Vec::new();
 
+        // A path to a font file is used as-is
+        if std::path::Path::new(font_family).extension().is_some()
+            && font_family.contains(std::path::MAIN_SEPARATOR)
+        {
+            paths.push(font_family.to_string());
+            return paths;
+        }
+
         // Generate name variants: "JetBrains Mono" -> "JetBrainsMono", "jetbrainsmono"
         let no_spaces: String = font_family.split_whitespace().collect();
         let lower = font_family.to_lowercase();
@@ -171,11 +216,6 @@ impl GlyphCache {
                 }
             }
 
-            // Common monospace fonts on Windows
-            paths.push("C:\\Windows\\Fonts\\consola.ttf".to_string());
-            paths.push("C:\\Windows\\Fonts\\cour.ttf".to_string());
-            paths.push("C:\\Windows\\Fonts\\lucon.ttf".to_string());
-
             // User fonts directory on Windows
             if let Some(home) = dirs::home_dir() {
                 let local_fonts = home
@@ -226,20 +266,6 @@ impl GlyphCache {
                 }
             }
 
-            // Debian/Ubuntu specific paths for common fonts
-            paths.push("/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf".to_string());
-            paths.push("/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf".to_string());
-            paths.push("/usr/share/fonts/truetype/noto/NotoSansMono-Regular.ttf".to_string());
-            paths.push("/usr/share/fonts/truetype/ubuntu/UbuntuMono-R.ttf".to_string());
-            paths.push("/usr/share/fonts/truetype/freefont/FreeMono.ttf".to_string());
-            paths.push("/usr/share/fonts/truetype/droid/DroidSansMono.ttf".to_string());
-
-            // Arch/Fedora/openSUSE paths
-            paths.push("/usr/share/fonts/TTF/DejaVuSansMono.ttf".to_string());
-            paths.push("/usr/share/fonts/liberation-mono/LiberationMono-Regular.ttf".to_string());
-            paths.push("/usr/share/fonts/google-noto/NotoSansMono-Regular.ttf".to_string());
-            paths.push("/usr/share/fonts/noto/NotoSansMono-Regular.ttf".to_string());
-
             // User fonts directory on Linux
             if let Some(home) = dirs::home_dir() {
                 for variant in &variants {
@@ -273,13 +299,6 @@ impl GlyphCache {
                 }
             }
 
-            // macOS built-in monospace fonts
-            paths.push("/System/Library/Fonts/Menlo.ttc".to_string());
-            paths.push("/System/Library/Fonts/Monaco.ttf".to_string());
-            paths.push("/System/Library/Fonts/Courier.ttc".to_string());
-            paths.push("/System/Library/Fonts/SFMono-Regular.otf".to_string());
-            paths.push("/Library/Fonts/Courier New.ttf".to_string());
-
             // User fonts directory on macOS
             if let Some(home) = dirs::home_dir() {
                 for variant in &variants {
@@ -304,6 +323,52 @@ impl GlyphCache {
         paths
     }
 
+    /// Paths of monospace fonts commonly installed with the OS
+    ///
+    /// Tried when the configured primary font can't be found. Not used for
+    /// fallback fonts, which must resolve to the family that was asked for.
+    fn default_font_paths() -> Vec<String> {
+        #[allow(unused_mut)]
+        let mut paths = Vec::new();
+
+        #[cfg(windows)]
+        {
+            // Common monospace fonts on Windows
+            paths.push("C:\\Windows\\Fonts\\consola.ttf".to_string());
+            paths.push("C:\\Windows\\Fonts\\cour.ttf".to_string());
+            paths.push("C:\\Windows\\Fonts\\lucon.ttf".to_string());
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // Debian/Ubuntu specific paths for common fonts
+            paths.push("/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf".to_string());
+            paths.push("/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf".to_string());
+            paths.push("/usr/share/fonts/truetype/noto/NotoSansMono-Regular.ttf".to_string());
+            paths.push("/usr/share/fonts/truetype/ubuntu/UbuntuMono-R.ttf".to_string());
+            paths.push("/usr/share/fonts/truetype/freefont/FreeMono.ttf".to_string());
+            paths.push("/usr/share/fonts/truetype/droid/DroidSansMono.ttf".to_string());
+
+            // Arch/Fedora/openSUSE paths
+            paths.push("/usr/share/fonts/TTF/DejaVuSansMono.ttf".to_string());
+            paths.push("/usr/share/fonts/liberation-mono/LiberationMono-Regular.ttf".to_string());
+            paths.push("/usr/share/fonts/google-noto/NotoSansMono-Regular.ttf".to_string());
+            paths.push("/usr/share/fonts/noto/NotoSansMono-Regular.ttf".to_string());
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            // macOS built-in monospace fonts
+            paths.push("/System/Library/Fonts/Menlo.ttc".to_string());
+            paths.push("/System/Library/Fonts/Monaco.ttf".to_string());
+            paths.push("/System/Library/Fonts/Courier.ttc".to_string());
+            paths.push("/System/Library/Fonts/SFMono-Regular.otf".to_string());
+            paths.push("/Library/Fonts/Courier New.ttf".to_string());
+        }
+
+        paths
+    }
+
     /// Pre-cache ASCII characters for faster rendering
     ///
     /// BUG FIX #4: Actually rasterize glyphs and upload to atlas
@@ -336,7 +401,11 @@ impl GlyphCache {
 
         // Rasterize the glyph
         let (metrics, bitmap) = font.rasterize(c, self.font_size);
+        self.insert_rasterized(code, metrics, &bitmap);
+    }
 
+    /// Copy a rasterized glyph into the atlas and record its metrics
+    fn insert_rasterized(&mut self, code: u32, metrics: fontdue::Metrics, bitmap: &[u8]) {
         let width = metrics.width as u32;
         let height = metrics.height as u32;
 
@@ -474,6 +543,55 @@ impl GlyphCache {
         }
     }
 
+    /// Make sure a glyph for `c` is in the atlas, rasterizing it if needed
+    ///
+    /// The primary font is tried first, then each fallback font in order. If
+    /// none of them has the character a tofu box is cached in its place.
+    /// Returns `true` if the atlas changed and must be uploaded again.
+    pub fn ensure_glyph(&mut self, c: char) -> bool {
+        let code = c as u32;
+        if self.glyph_map.contains_key(&code) {
+            return false;
+        }
+
+        let fonts: Vec<&fontdue::Font> =
+            self.font.iter().chain(self.fallback_fonts.iter()).collect();
+        match select_font(&fonts, c, |font, c| font.has_glyph(c)) {
+            Some(index) => {
+                let (metrics, bitmap) = fonts[index].rasterize(c, self.font_size);
+                self.insert_rasterized(code, metrics, &bitmap);
+            }
+            None => self.cache_tofu(code),
+        }
+        true
+    }
+
+    /// Cache a hollow box for a character no font can draw
+    fn cache_tofu(&mut self, code: u32) {
+        const CELL_WIDTH_RATIO: f32 = 0.6;
+        const CELL_HEIGHT_RATIO: f32 = 0.8;
+        let width = ((self.font_size * CELL_WIDTH_RATIO) as u32).max(3);
+        let height = ((self.font_size * CELL_HEIGHT_RATIO) as u32).max(3);
+
+        let bitmap: Vec<u8> = (0..height)
+            .flat_map(|y| {
+                (0..width).map(move |x| {
+                    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                        255
+                    } else {
+                        0
+                    }
+                })
+            })
+            .collect();
+        self.cache_glyph(code, &bitmap, width, height);
+    }
+
+    /// Number of fallback fonts that were loaded
+    pub fn fallback_font_count(&self) -> usize {
+        self.fallback_fonts.len()
+    }
+
     /// Get UV coordinates for a glyph
     pub fn get_glyph_uv(&self, char_code: u32) -> Option<[f32; 4]> {
         self.glyph_map.get(&char_code).map(|info| info.uv)
@@ -647,9 +765,18 @@ impl GlyphCache {
     }
 }
 
+/// Pick the first font in `fonts` that can draw `c`
+///
+/// `fonts` is ordered by preference: the primary font, then the fallbacks.
+/// Returns `None` when no font has the character, meaning a tofu box is drawn.
+pub fn select_font<T>(fonts: &[T], c: char, has_glyph: impl Fn(&T, char) -> bool) -> Option<usize> {
+    fonts.iter().position(|font| has_glyph(font, c))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_glyph_cache_creation() {
@@ -715,6 +842,43 @@ mod tests {
         assert!(cache.len() >= 95);
     }
 
+    #[test]
+    fn test_select_font_walks_fallbacks_in_order() {
+        let primary: HashSet<char> = ('a'..='z').collect();
+        let symbols: HashSet<char> = ['→', '✓'].into_iter().collect();
+        let cjk: HashSet<char> = ['日', '✓'].into_iter().collect();
+        let fonts = [primary, symbols, cjk];
+        let has_glyph = |font: &HashSet<char>, c| font.contains(&c);
+
+        assert_eq!(select_font(&fonts, 'q', has_glyph), Some(0));
+        assert_eq!(select_font(&fonts, '→', has_glyph), Some(1));
+        // Both fallbacks have it; the earlier one wins
+        assert_eq!(select_font(&fonts, '✓', has_glyph), Some(1));
+        assert_eq!(select_font(&fonts, '日', has_glyph), Some(2));
+        // Missing everywhere: tofu
+        assert_eq!(select_font(&fonts, '🦀', has_glyph), None);
+        assert_eq!(select_font(&fonts[..0], 'q', has_glyph), None);
+    }
+
+    #[test]
+    fn test_ensure_glyph_caches_tofu_for_missing_char() {
+        let mut cache = GlyphCache::with_fonts(
+            14.0,
+            "NonExistentFontXYZ123",
+            &["AlsoMissingFontXYZ".to_string()],
+        );
+        assert_eq!(cache.fallback_font_count(), 0);
+
+        // Private-use codepoint no font covers
+        assert!(cache.ensure_glyph('\u{F8FF}'));
+        let tofu = cache.get_glyph(0xF8FF).expect("tofu should be cached");
+        assert!(tofu.size[0] > 0.0 && tofu.size[1] > 0.0);
+
+        // Already cached: atlas unchanged
+        assert!(!cache.ensure_glyph('\u{F8FF}'));
+        assert!(!cache.ensure_glyph('A'));
+    }
+
     #[test]
     fn test_placeholder_atlas_has_pixel_data() {
         // Use a font name that definitely won't exist to force placeholders
@@ -38,6 +38,8 @@ pub struct GpuConfig {
     pub font_size: f32,
     /// Font family name
     pub font_family: String,
+    /// Fonts tried in order for characters the primary font lacks
+    pub fallback_fonts: Vec<String>,
     /// Enable sub-pixel rendering for sharper text
     pub subpixel_rendering: bool,
     /// Background opacity (0.0 = transparent, 1.0 = opaque)
@@ -61,6 +63,7 @@ impl Default for GpuConfig {
             vsync: true,
             font_size: 14.0,
             font_family: String::from("JetBrains Mono"),
+            fallback_fonts: Vec::new(),
             subpixel_rendering: true,
             background_opacity: 1.0,
             background_blur: false,
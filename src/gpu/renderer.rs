@@ -511,8 +511,11 @@ impl GpuRenderer {
         });
 
         // Create glyph cache with font loading
-        let glyph_cache =
-            super::glyph_cache::GlyphCache::new(config.font_size, &config.font_family);
+        let glyph_cache = super::glyph_cache::GlyphCache::with_fonts(
+            config.font_size,
+            &config.font_family,
+            &config.fallback_fonts,
+        );
 
         // BUG FIX #4: Upload glyph atlas data to GPU texture
         // This ensures glyphs are actually visible when rendered
@@ -625,6 +628,17 @@ impl GpuRenderer {
             }
         }
 
+        // Rasterize glyphs not seen before, from fallback fonts if needed
+        let mut atlas_changed = false;
+        for cell in cells {
+            if let Some(c) = char::from_u32(cell.char_code) {
+                atlas_changed |= self.glyph_cache.ensure_glyph(c);
+            }
+        }
+        if atlas_changed {
+            self.upload_glyph_atlas();
+        }
+
         // Update current cells
         self.cells.clear();
         self.cells.extend_from_slice(cells);
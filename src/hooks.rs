@@ -1,14 +1,71 @@
 //! Lua hooks system for custom functionality
 //!
 //! Executes user-defined Lua scripts at various points in the terminal lifecycle.
+//!
+//! Scripts can query the terminal through the `furnace` table:
+//! - `furnace.get_buffer()`: text of the active session, escape sequences removed
+//! - `furnace.get_cwd()`: working directory reported by the shell, or `nil`
+//! - `furnace.notify(msg)`: show `msg` in the status bar
+//! - `furnace.bind(combo, fn)`: bind a key combo such as `"Ctrl+Shift+G"` to `fn`
+//!
+//! The getters only return values while a hook runs under [`HooksExecutor::with_view`];
+//! notifications are queued and picked up by the terminal between frames.
+//! `furnace.bind` registers through the callback given to [`HooksExecutor::set_binder`].
+//!
+//! Scripts that run longer than the limit set with [`HooksExecutor::set_timeout`]
+//! are aborted with an error.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use mlua::Lua;
+use mlua::{Function, HookTriggers, Lua, Table, Value};
 use tracing::{debug, warn};
 
+use crate::keybindings::{Action, KeybindingManager};
+
+/// Most notifications kept between frames; older ones are dropped
+const MAX_PENDING_NOTIFICATIONS: usize = 16;
+
+/// Lua instructions run between checks of a script's deadline
+const DEADLINE_CHECK_INSTRUCTIONS: u32 = 10_000;
+
+/// Read-only view of terminal state lent to Lua for the length of a hook run
+pub struct HookView<'a> {
+    /// Builds the active session's text; only called if a script asks for it
+    pub buffer: &'a dyn Fn() -> String,
+    /// Working directory reported by the shell, if known
+    pub cwd: Option<&'a str>,
+}
+
+/// Registers a combo with the live keybindings, returning the normalized combo
+pub type Binder = Box<dyn FnMut(&str, Action) -> std::result::Result<String, String>>;
+
 /// Lua hooks executor
 pub struct HooksExecutor {
     lua: Lua,
+    /// Messages passed to `furnace.notify`, waiting for the terminal to show them
+    notifications: Rc<RefCell<Vec<String>>>,
+    /// Set while a hook runs with a view, to reject nested runs
+    in_hook: Rc<Cell<bool>>,
+    /// Where `furnace.bind` sends new bindings, once the terminal provides it
+    binder: Rc<RefCell<Option<Binder>>>,
+    /// Longest a single script may run, if limited
+    timeout: Cell<Option<Duration>>,
+    /// When the running script is aborted, if it has a time limit
+    deadline: Rc<Cell<Option<Instant>>>,
+    /// Set when the last script was aborted for running past its deadline
+    timed_out: Rc<Cell<bool>>,
+}
+
+/// Clears the running flag when a hook run ends, even by unwinding
+struct HookGuard(Rc<Cell<bool>>);
+
+impl Drop for HookGuard {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
 }
 
 impl HooksExecutor {
@@ -30,7 +87,180 @@ impl HooksExecutor {
         )
         .exec()?;
 
-        Ok(Self { lua })
+        let executor = Self {
+            lua,
+            notifications: Rc::new(RefCell::new(Vec::new())),
+            in_hook: Rc::new(Cell::new(false)),
+            binder: Rc::new(RefCell::new(None)),
+            timeout: Cell::new(None),
+            deadline: Rc::new(Cell::new(None)),
+            timed_out: Rc::new(Cell::new(false)),
+        };
+        executor.install_furnace_api()?;
+        executor.install_deadline_check();
+        Ok(executor)
+    }
+
+    /// Abort scripts that run past their deadline
+    fn install_deadline_check(&self) {
+        let deadline = Rc::clone(&self.deadline);
+        let timed_out = Rc::clone(&self.timed_out);
+        self.lua.set_hook(
+            HookTriggers::new().every_nth_instruction(DEADLINE_CHECK_INSTRUCTIONS),
+            move |_, _| match deadline.get() {
+                Some(at) if Instant::now() >= at => {
+                    timed_out.set(true);
+                    Err(mlua::Error::RuntimeError(
+                        "script ran past its time limit".to_string(),
+                    ))
+                }
+                _ => Ok(()),
+            },
+        );
+    }
+
+    /// Limit how long each script may run; `None` lets scripts run forever
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        self.timeout.set(timeout);
+    }
+
+    /// Run `chunk` under the configured time limit
+    fn run_limited<R>(&self, chunk: impl FnOnce() -> mlua::Result<R>) -> mlua::Result<R> {
+        let previous = self.deadline.get();
+        self.timed_out.set(false);
+        self.deadline
+            .set(self.timeout.get().map(|timeout| Instant::now() + timeout));
+        let result = chunk();
+        self.deadline.set(previous);
+        if self.timed_out.get() {
+            warn!(
+                "Lua script aborted after exceeding its {} ms time limit",
+                self.timeout.get().unwrap_or_default().as_millis()
+            );
+        }
+        result
+    }
+
+    /// Register the `furnace` table with no view attached
+    fn install_furnace_api(&self) -> mlua::Result<()> {
+        let furnace = self.furnace_table()?;
+
+        let notifications = Rc::clone(&self.notifications);
+        furnace.set(
+            "notify",
+            self.lua.create_function(move |_, msg: String| {
+                let mut pending = notifications.borrow_mut();
+                if pending.len() >= MAX_PENDING_NOTIFICATIONS {
+                    pending.remove(0);
+                }
+                pending.push(msg);
+                Ok(())
+            })?,
+        )?;
+
+        // Functions bound with `furnace.bind`, keyed by normalized combo; the
+        // registered ExecuteLua action looks its function up here when run
+        furnace.set("bindings", self.lua.create_table()?)?;
+
+        let binder = Rc::clone(&self.binder);
+        furnace.set(
+            "bind",
+            self.lua
+                .create_function(move |lua, (combo, func): (String, Function)| {
+                    let combo = KeybindingManager::normalize_combo(&combo)
+                        .map_err(|e| mlua::Error::RuntimeError(format!("{}: {}", combo, e)))?;
+
+                    let furnace: Table = lua.globals().get("furnace")?;
+                    let bound = match furnace.get::<_, Option<Table>>("bindings")? {
+                        Some(bound) => bound,
+                        None => {
+                            let bound = lua.create_table()?;
+                            furnace.set("bindings", bound.clone())?;
+                            bound
+                        }
+                    };
+
+                    // Rebinding a combo this API bound before only swaps the function
+                    if !bound.contains_key(combo.as_str())? {
+                        let mut binder = binder.borrow_mut();
+                        let Some(register) = binder.as_mut() else {
+                            return Err(mlua::Error::RuntimeError(
+                                "keybindings cannot be changed here".to_string(),
+                            ));
+                        };
+                        let code = format!("furnace.bindings[{:?}]()", combo);
+                        register(&combo, Action::ExecuteLua(code))
+                            .map_err(mlua::Error::RuntimeError)?;
+                    }
+                    bound.set(combo, func)
+                })?,
+        )?;
+
+        self.detach_view(&furnace)
+    }
+
+    /// Give `furnace.bind` somewhere to register bindings
+    ///
+    /// `binder` should refuse combos that are already bound; its error is
+    /// raised in the calling script.
+    pub fn set_binder(
+        &self,
+        binder: impl FnMut(&str, Action) -> std::result::Result<String, String> + 'static,
+    ) {
+        *self.binder.borrow_mut() = Some(Box::new(binder));
+    }
+
+    /// Outside a hook run the getters have nothing to look at
+    fn detach_view(&self, furnace: &Table) -> mlua::Result<()> {
+        let nothing = self.lua.create_function(|_, ()| Ok(Value::Nil))?;
+        furnace.set("get_buffer", nothing.clone())?;
+        furnace.set("get_cwd", nothing)
+    }
+
+    /// The global `furnace` table, recreated if a script replaced it
+    fn furnace_table(&self) -> mlua::Result<Table<'_>> {
+        let globals = self.lua.globals();
+        if let Some(table) = globals.get::<_, Option<Table>>("furnace")? {
+            return Ok(table);
+        }
+        let table = self.lua.create_table()?;
+        globals.set("furnace", table.clone())?;
+        Ok(table)
+    }
+
+    /// Run hooks with `view` available to `furnace.get_buffer()` and `furnace.get_cwd()`
+    ///
+    /// The view is only borrowed for this call; afterwards the getters return `nil`
+    /// again. A run started while another is still going (a hook that reaches back
+    /// into the terminal mid-render) is refused with an error.
+    pub fn with_view<R>(&self, view: &HookView<'_>, run: impl FnOnce() -> R) -> Result<R> {
+        if self.in_hook.replace(true) {
+            return Err(anyhow::anyhow!(
+                "Lua hook started while another hook is running"
+            ));
+        }
+        let _guard = HookGuard(Rc::clone(&self.in_hook));
+
+        let furnace = self.furnace_table()?;
+        let result = self.lua.scope(|scope| {
+            furnace.set(
+                "get_buffer",
+                scope.create_function(|_, ()| Ok((view.buffer)()))?,
+            )?;
+            furnace.set(
+                "get_cwd",
+                scope.create_function(|_, ()| Ok(view.cwd.map(str::to_string)))?,
+            )?;
+            Ok(run())
+        });
+        self.detach_view(&furnace)?;
+
+        Ok(result?)
+    }
+
+    /// Take the messages hooks passed to `furnace.notify`, oldest first
+    pub fn take_notifications(&self) -> Vec<String> {
+        std::mem::take(&mut *self.notifications.borrow_mut())
     }
 
     /// Execute a Lua hook script
@@ -39,8 +269,19 @@ impl HooksExecutor {
     /// * `script` - Lua code to execute
     /// * `context` - Context data to pass to the script
     pub fn execute(&self, script: &str, context: &str) -> Result<()> {
+        self.evaluate(script, context).map(|_| ())
+    }
+
+    /// Execute a Lua hook script and return the value it returns, as a string
+    ///
+    /// Returns `None` when the script returns nothing (or `nil`).
+    ///
+    /// # Arguments
+    /// * `script` - Lua code to execute
+    /// * `context` - Context data to pass to the script
+    pub fn evaluate(&self, script: &str, context: &str) -> Result<Option<String>> {
         if script.is_empty() {
-            return Ok(());
+            return Ok(None);
         }
 
         // Escape special characters for Lua string literal safety
@@ -57,22 +298,29 @@ impl HooksExecutor {
             .replace('\0', r"\0"); // Escape null bytes
 
         // Create a table with context
-        self.lua
-            .load(format!(
-                r#"
+        let value: Value = self
+            .run_limited(|| {
+                self.lua
+                    .load(format!(
+                        r#"
             local context = "{}"
             {}
             "#,
-                escaped_context, script
-            ))
-            .exec()
+                        escaped_context, script
+                    ))
+                    .eval()
+            })
             .map_err(|e| {
                 warn!("Lua hook execution failed: {}", e);
                 anyhow::anyhow!("Lua hook error: {}", e)
             })?;
 
         debug!("Executed Lua hook successfully");
-        Ok(())
+        match value {
+            Value::Nil => Ok(None),
+            // Same text Lua's `tostring` would give
+            value => Ok(Some(value.to_string()?)),
+        }
     }
 
     /// Execute startup hook
@@ -122,6 +370,11 @@ impl HooksExecutor {
         self.execute(script, &format!("title_change:{}", title))
     }
 
+    /// Execute resize hook with the new size in cells
+    pub fn on_resize(&self, script: &str, cols: u16, rows: u16) -> Result<()> {
+        self.execute(script, &format!("resize:{}:{}", cols, rows))
+    }
+
     /// Apply output filters to transform output text
     ///
     /// Filters are Lua functions that transform string input to string output.
@@ -157,7 +410,7 @@ impl HooksExecutor {
             globals.set("output", result.clone())?; // Default: output = input
 
             // Execute the filter
-            match self.lua.load(filter).exec() {
+            match self.run_limited(|| self.lua.load(filter).exec()) {
                 Ok(()) => {
                     // Get the transformed output
                     match globals.get::<_, String>("output") {
@@ -171,6 +424,10 @@ impl HooksExecutor {
                         }
                     }
                 }
+                // A filter that hangs gets no second chance; show the output unfiltered
+                Err(_) if self.timed_out.get() => {
+                    return Err(anyhow::anyhow!("Output filter {} timed out", idx));
+                }
                 Err(e) => {
                     warn!("Output filter {} execution failed: {}", idx, e);
                     // Continue with current result, don't break the chain
@@ -204,10 +461,11 @@ impl HooksExecutor {
         globals.set("context", ctx_table)?;
 
         // Execute Lua code
-        self.lua.load(lua_code).exec().map_err(|e| {
-            warn!("Custom keybinding execution failed: {}", e);
-            anyhow::anyhow!("Keybinding error: {}", e)
-        })?;
+        self.run_limited(|| self.lua.load(lua_code).exec())
+            .map_err(|e| {
+                warn!("Custom keybinding execution failed: {}", e);
+                anyhow::anyhow!("Keybinding error: {}", e)
+            })?;
 
         debug!("Custom keybinding executed successfully");
         Ok(())
@@ -239,10 +497,11 @@ impl HooksExecutor {
         }
 
         // Execute Lua code
-        self.lua.load(lua_code).exec().map_err(|e| {
-            warn!("Widget execution failed: {}", e);
-            anyhow::anyhow!("Widget error: {}", e)
-        })?;
+        self.run_limited(|| self.lua.load(lua_code).exec())
+            .map_err(|e| {
+                warn!("Widget execution failed: {}", e);
+                anyhow::anyhow!("Widget error: {}", e)
+            })?;
 
         // Extract widget definition from globals
         let globals = self.lua.globals();
@@ -301,7 +560,15 @@ impl Default for HooksExecutor {
         Self::new().unwrap_or_else(|e| {
             warn!("Failed to create Lua hooks executor: {}", e);
             // Create a dummy executor that will fail gracefully
-            Self { lua: Lua::new() }
+            Self {
+                lua: Lua::new(),
+                notifications: Rc::new(RefCell::new(Vec::new())),
+                in_hook: Rc::new(Cell::new(false)),
+                binder: Rc::new(RefCell::new(None)),
+                timeout: Cell::new(None),
+                deadline: Rc::new(Cell::new(None)),
+                timed_out: Rc::new(Cell::new(false)),
+            }
         })
     }
 }
@@ -446,6 +713,95 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_resize_hook() {
+        let executor = HooksExecutor::new().unwrap();
+        let script = r#"
+            local cols, rows = context:match("^resize:(%d+):(%d+)$")
+            furnace.notify(cols .. "x" .. rows)
+        "#;
+        executor.on_resize(script, 132, 43).unwrap();
+        assert_eq!(executor.take_notifications(), vec!["132x43"]);
+
+        // Errors reach the caller, which logs them
+        assert!(executor.on_resize("error('boom')", 80, 24).is_err());
+    }
+
+    #[test]
+    fn test_startup_script_binds_key() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let executor = HooksExecutor::new().unwrap();
+        let manager = Rc::new(RefCell::new(KeybindingManager::new()));
+        let registry = Rc::clone(&manager);
+        executor
+            .set_binder(move |combo, action| registry.borrow_mut().add_new_binding(combo, action));
+
+        let script = r#"
+            furnace.bind("ctrl+shift+g", function() furnace.notify("first") end)
+            -- Rebinding replaces the function
+            furnace.bind("Ctrl+Shift+G", function() furnace.notify("bound") end)
+        "#;
+        executor.on_startup(script).unwrap();
+
+        let action = manager.borrow().get_action(
+            KeyCode::Char('G'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        let Some(Action::ExecuteLua(code)) = action else {
+            panic!("expected a Lua action, got {:?}", action);
+        };
+        executor.execute_custom_keybinding(&code, "", "").unwrap();
+        assert_eq!(executor.take_notifications(), vec!["bound"]);
+
+        // Invalid combos and combos bound elsewhere raise errors in Lua
+        let script = r#"
+            local ok, err = pcall(furnace.bind, "Ctrl+", function() end)
+            assert(not ok)
+            ok, err = pcall(furnace.bind, "Ctrl+T", function() end)
+            assert(not ok and tostring(err):find("already bound"))
+        "#;
+        executor.on_startup(script).unwrap();
+        assert!(matches!(
+            manager
+                .borrow()
+                .get_action(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            Some(Action::NewTab)
+        ));
+    }
+
+    #[test]
+    fn test_bind_without_binder_fails() {
+        let executor = HooksExecutor::new().unwrap();
+        let result = executor.on_startup(r#"furnace.bind("Ctrl+Shift+G", function() end)"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_runaway_script_is_aborted() {
+        let executor = HooksExecutor::new().unwrap();
+        executor.set_timeout(Some(Duration::from_millis(50)));
+
+        let started = Instant::now();
+        let result = executor.on_output("while true do end", "ls");
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // A filter that hangs fails the pipeline so the caller shows unfiltered output
+        let filters = vec![
+            "output = string.upper(input)".to_string(),
+            "while true do end".to_string(),
+        ];
+        assert!(executor.apply_output_filters("hello", &filters).is_err());
+
+        // The executor stays usable and quick scripts still run
+        let filters = vec!["output = string.upper(input)".to_string()];
+        assert_eq!(
+            executor.apply_output_filters("hello", &filters).unwrap(),
+            "HELLO"
+        );
+    }
+
     #[test]
     fn test_empty_script() {
         let executor = HooksExecutor::new().unwrap();
@@ -488,6 +844,60 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_hook_reads_buffer_and_returns_value() {
+        let executor = HooksExecutor::new().unwrap();
+        let buffer = || "$ cargo build\nerror[E0308]: mismatched types\n$ ".to_string();
+        let view = HookView {
+            buffer: &buffer,
+            cwd: Some("/home/user/furnace"),
+        };
+        let script = r#"
+            local errors = 0
+            for _ in furnace.get_buffer():gmatch("error%[") do errors = errors + 1 end
+            return furnace.get_cwd() .. ":" .. errors
+        "#;
+
+        let result = executor
+            .with_view(&view, || executor.evaluate(script, "output"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.as_deref(), Some("/home/user/furnace:1"));
+
+        // The view is gone once the run ends
+        let after = executor
+            .evaluate("return tostring(furnace.get_buffer())", "test")
+            .unwrap();
+        assert_eq!(after.as_deref(), Some("nil"));
+    }
+
+    #[test]
+    fn test_notify_queues_messages() {
+        let executor = HooksExecutor::new().unwrap();
+        let script = "furnace.notify('build done'); furnace.notify('2 warnings')";
+        executor.execute(script, "test").unwrap();
+        assert_eq!(
+            executor.take_notifications(),
+            vec!["build done", "2 warnings"]
+        );
+        assert!(executor.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn test_nested_hook_run_is_refused() {
+        let executor = HooksExecutor::new().unwrap();
+        let buffer = String::new;
+        let view = HookView {
+            buffer: &buffer,
+            cwd: None,
+        };
+
+        let inner = executor.with_view(&view, || executor.with_view(&view, || ()));
+        assert!(inner.unwrap().is_err());
+        // The guard is released afterwards
+        assert!(executor.with_view(&view, || ()).is_ok());
+    }
+
     #[test]
     fn test_default_implementation() {
         let executor = HooksExecutor::default();
@@ -1,14 +1,35 @@
 use crossterm::event::{KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Default time allowed between the keys of a chord
+pub const DEFAULT_CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
 
 /// Enhanced keybinding system with shell integration
 #[derive(Debug, Clone)]
 pub struct KeybindingManager {
     bindings: HashMap<KeyBinding, Action>,
+    /// Multi-key sequences such as "Ctrl+B c", keyed by every step in order
+    chords: HashMap<Vec<KeyBinding>, Action>,
+    /// Steps of a chord typed so far, and when the last one was pressed
+    pending_chord: Option<(Vec<KeyBinding>, Instant)>,
+    chord_timeout: Duration,
     shell_integration: ShellIntegration,
 }
 
+/// Outcome of feeding a key press through [`KeybindingManager::resolve_key`]
+#[derive(Debug, Clone)]
+pub enum KeyResolution {
+    /// The key (or the chord it completed) is bound to this action
+    Action(Action),
+    /// The key started or continued a chord; wait for the next key
+    ChordPending,
+    /// No binding; the key should be handled normally
+    Unbound,
+}
+
 /// Key binding definition
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct KeyBinding {
@@ -17,7 +38,7 @@ pub struct KeyBinding {
 }
 
 /// Actions that can be triggered by keybindings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
     // Terminal actions
     NewTab,
@@ -30,12 +51,18 @@ pub enum Action {
     // Navigation
     FocusNextPane,
     FocusPrevPane,
+    GrowPane,
+    ShrinkPane,
+    ScrollToTop,
+    ScrollToBottom,
 
     // Editing
     Copy,
+    CopyRich,
     Paste,
     SelectAll,
     Clear,
+    ClearScrollback,
 
     // Search
     Search,
@@ -43,12 +70,17 @@ pub enum Action {
     SearchPrev,
 
     // Command palette & features
+    OpenCommandPalette,
     ToggleAutocomplete,
     NextTheme,
     PrevTheme,
 
     // Resource monitor
     ToggleResourceMonitor,
+    ToggleStatsOverlay,
+
+    // Help
+    ShowKeybindings,
 
     // Session management
     SaveSession,
@@ -56,6 +88,10 @@ pub enum Action {
     ListSessions,
 
     // Shell integration
+    Interrupt,
+    RestartShell,
+    PrevCommand,
+    NextCommand,
     SendToShell(String),
     ExecuteCommand(String),
 
@@ -66,7 +102,121 @@ pub enum Action {
     ExecuteLua(String),
 }
 
-/// Shell integration features (infrastructure for future OSC 7/133 support)
+impl FromStr for Action {
+    type Err = String;
+
+    /// Parse an action name as used in `keybindings.custom`
+    ///
+    /// Names are snake_case (`"save_session"`), though case, `-` and `_` are
+    /// ignored so `"SaveSession"` also works. Actions that carry a value take
+    /// it after a colon, e.g. `"send_to_shell:ls -la"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = match name.split_once(':') {
+            Some((kind, value)) => (kind, Some(value.to_string())),
+            None => (name, None),
+        };
+        let normalized: String = kind
+            .trim()
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        let action = match (normalized.as_str(), value) {
+            ("newtab", None) => Self::NewTab,
+            ("closetab", None) => Self::CloseTab,
+            ("nexttab", None) => Self::NextTab,
+            ("prevtab", None) => Self::PrevTab,
+            ("splithorizontal", None) => Self::SplitHorizontal,
+            ("splitvertical", None) => Self::SplitVertical,
+            ("focusnextpane", None) => Self::FocusNextPane,
+            ("focusprevpane", None) => Self::FocusPrevPane,
+            ("growpane", None) => Self::GrowPane,
+            ("shrinkpane", None) => Self::ShrinkPane,
+            ("scrolltotop", None) => Self::ScrollToTop,
+            ("scrolltobottom", None) => Self::ScrollToBottom,
+            ("copy", None) => Self::Copy,
+            ("copyrich", None) => Self::CopyRich,
+            ("paste", None) => Self::Paste,
+            ("selectall", None) => Self::SelectAll,
+            ("clear", None) => Self::Clear,
+            ("clearscrollback", None) => Self::ClearScrollback,
+            ("search", None) => Self::Search,
+            ("searchnext", None) => Self::SearchNext,
+            ("searchprev", None) => Self::SearchPrev,
+            ("opencommandpalette", None) => Self::OpenCommandPalette,
+            ("toggleautocomplete", None) => Self::ToggleAutocomplete,
+            ("nexttheme", None) => Self::NextTheme,
+            ("prevtheme", None) => Self::PrevTheme,
+            ("toggleresourcemonitor", None) => Self::ToggleResourceMonitor,
+            ("togglestatsoverlay", None) => Self::ToggleStatsOverlay,
+            ("savesession", None) => Self::SaveSession,
+            ("loadsession", None) => Self::LoadSession,
+            ("listsessions", None) => Self::ListSessions,
+            ("showkeybindings", None) => Self::ShowKeybindings,
+            ("interrupt", None) => Self::Interrupt,
+            ("restartshell", None) => Self::RestartShell,
+            ("prevcommand", None) => Self::PrevCommand,
+            ("nextcommand", None) => Self::NextCommand,
+            ("sendtoshell", Some(text)) => Self::SendToShell(text),
+            ("executecommand", Some(command)) => Self::ExecuteCommand(command),
+            ("custom", Some(custom)) => Self::Custom(custom),
+            ("executelua", Some(code)) => Self::ExecuteLua(code),
+            _ => return Err(format!("Unknown action '{name}'")),
+        };
+        Ok(action)
+    }
+}
+
+impl Action {
+    /// Short human-readable description for the keybinding cheat sheet
+    #[must_use]
+    pub fn description(&self) -> String {
+        match self {
+            Self::NewTab => "New tab".to_string(),
+            Self::CloseTab => "Close tab".to_string(),
+            Self::NextTab => "Next tab".to_string(),
+            Self::PrevTab => "Previous tab".to_string(),
+            Self::SplitHorizontal => "Split horizontally".to_string(),
+            Self::SplitVertical => "Split vertically".to_string(),
+            Self::FocusNextPane => "Focus next pane".to_string(),
+            Self::FocusPrevPane => "Focus previous pane".to_string(),
+            Self::GrowPane => "Grow focused pane".to_string(),
+            Self::ShrinkPane => "Shrink focused pane".to_string(),
+            Self::ScrollToTop => "Scroll to top of scrollback".to_string(),
+            Self::ScrollToBottom => "Scroll to bottom and follow output".to_string(),
+            Self::Copy => "Copy selection".to_string(),
+            Self::CopyRich => "Copy selection with colors".to_string(),
+            Self::Paste => "Paste".to_string(),
+            Self::SelectAll => "Select all".to_string(),
+            Self::Clear => "Clear screen".to_string(),
+            Self::ClearScrollback => "Clear scrollback, keep screen".to_string(),
+            Self::Search => "Search scrollback".to_string(),
+            Self::SearchNext => "Next search match".to_string(),
+            Self::SearchPrev => "Previous search match".to_string(),
+            Self::OpenCommandPalette => "Open command palette".to_string(),
+            Self::ToggleAutocomplete => "Toggle autocomplete".to_string(),
+            Self::NextTheme => "Next theme".to_string(),
+            Self::PrevTheme => "Previous theme".to_string(),
+            Self::ToggleResourceMonitor => "Toggle resource monitor".to_string(),
+            Self::ToggleStatsOverlay => "Toggle frame rate and render stats".to_string(),
+            Self::ShowKeybindings => "Show keybindings".to_string(),
+            Self::SaveSession => "Save session".to_string(),
+            Self::LoadSession => "Load session".to_string(),
+            Self::ListSessions => "List sessions".to_string(),
+            Self::Interrupt => "Interrupt running program".to_string(),
+            Self::RestartShell => "Restart exited shell".to_string(),
+            Self::PrevCommand => "Jump to previous command".to_string(),
+            Self::NextCommand => "Jump to next command".to_string(),
+            Self::SendToShell(text) => format!("Send {:?}", text),
+            Self::ExecuteCommand(command) => format!("Run `{command}`"),
+            Self::Custom(name) => format!("Custom: {name}"),
+            Self::ExecuteLua(_) => "Run Lua keybinding".to_string(),
+        }
+    }
+}
+
+/// Shell integration state, updated from OSC 7 and OSC 133 sequences
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct ShellIntegration {
@@ -87,6 +237,9 @@ pub struct ShellIntegration {
 
     /// Last command
     pub last_command: Option<String>,
+
+    /// Exit code of the last finished command, if the shell reported one
+    pub last_exit_code: Option<i32>,
 }
 
 impl KeybindingManager {
@@ -95,6 +248,9 @@ impl KeybindingManager {
     pub fn new() -> Self {
         let mut manager = Self {
             bindings: HashMap::new(),
+            chords: HashMap::new(),
+            pending_chord: None,
+            chord_timeout: DEFAULT_CHORD_TIMEOUT,
             shell_integration: ShellIntegration::default(),
         };
 
@@ -120,9 +276,16 @@ impl KeybindingManager {
         self.add_binding("h", &["Ctrl", "Shift"], Action::SplitHorizontal);
         self.add_binding("d", &["Ctrl", "Shift"], Action::SplitVertical);
         self.add_binding("o", &["Ctrl"], Action::FocusNextPane);
+        self.add_binding("Right", &["Ctrl", "Shift"], Action::GrowPane);
+        self.add_binding("Left", &["Ctrl", "Shift"], Action::ShrinkPane);
+
+        // Scrollback
+        self.add_binding("Home", &["Shift"], Action::ScrollToTop);
+        self.add_binding("End", &["Shift"], Action::ScrollToBottom);
 
         // Editing
         self.add_binding("c", &["Ctrl", "Shift"], Action::Copy);
+        self.add_binding("c", &["Ctrl", "Alt"], Action::CopyRich);
         self.add_binding("v", &["Ctrl", "Shift"], Action::Paste);
         self.add_binding("a", &["Ctrl", "Shift"], Action::SelectAll);
         self.add_binding("l", &["Ctrl"], Action::Clear);
@@ -133,7 +296,13 @@ impl KeybindingManager {
         self.add_binding("N", &["Ctrl", "Shift"], Action::SearchPrev);
 
         // Features
+        self.add_binding("p", &["Ctrl", "Shift"], Action::OpenCommandPalette);
+        self.add_binding("x", &["Ctrl", "Shift"], Action::Interrupt);
+        self.add_binding("r", &["Ctrl", "Shift"], Action::RestartShell);
+        self.add_binding("Up", &["Ctrl", "Shift"], Action::PrevCommand);
+        self.add_binding("Down", &["Ctrl", "Shift"], Action::NextCommand);
         self.add_binding("r", &["Ctrl"], Action::ToggleResourceMonitor);
+        self.add_binding("s", &["Ctrl", "Shift"], Action::ToggleStatsOverlay);
         self.add_binding("Tab", &["Alt"], Action::ToggleAutocomplete);
         self.add_binding("]", &["Ctrl"], Action::NextTheme);
         self.add_binding("[", &["Ctrl"], Action::PrevTheme);
@@ -143,6 +312,9 @@ impl KeybindingManager {
         // Ctrl+O is used for FocusNextPane above
         self.add_binding("s", &["Ctrl"], Action::SaveSession);
         self.add_binding("l", &["Ctrl", "Shift"], Action::LoadSession);
+
+        // Help
+        self.add_binding("k", &["Ctrl", "Shift"], Action::ShowKeybindings);
     }
 
     /// Add a keybinding
@@ -159,8 +331,11 @@ impl KeybindingManager {
 
     /// Parse and add a keybinding from a config string like "Ctrl+T" or "Ctrl+Shift+C"
     ///
+    /// Space-separated combos form a chord that must be typed in sequence,
+    /// e.g. "Ctrl+B c" binds Ctrl+B followed by c.
+    ///
     /// # Arguments
-    /// * `combo` - Key combination string (e.g., "Ctrl+T", "Ctrl+Shift+V", "Alt+F")
+    /// * `combo` - Key combination string (e.g., "Ctrl+T", "Ctrl+Shift+V", "Alt+F", "Ctrl+B c")
     /// * `action` - Action to bind to this combination
     ///
     /// # Returns
@@ -170,35 +345,125 @@ impl KeybindingManager {
     /// ```ignore
     /// manager.add_binding_from_string("Ctrl+T", Action::NewTab)?;
     /// manager.add_binding_from_string("Ctrl+Shift+C", Action::Copy)?;
+    /// manager.add_binding_from_string("ctrl+b c", Action::NewTab)?;
     /// ```
     pub fn add_binding_from_string(&mut self, combo: &str, action: Action) -> Result<(), String> {
-        if combo.is_empty() {
+        let steps = Self::parse_steps(combo)?;
+        self.insert_steps(steps, action);
+        Ok(())
+    }
+
+    /// Bind a combo that has no binding yet, refusing to replace an existing one
+    ///
+    /// Accepts the same combos as [`Self::add_binding_from_string`].
+    ///
+    /// # Returns
+    /// The normalized combo (see [`Self::normalize_combo`]), or Err if the combo
+    /// is invalid or already bound
+    pub fn add_new_binding(&mut self, combo: &str, action: Action) -> Result<String, String> {
+        let steps = Self::parse_steps(combo)?;
+        let normalized = format_steps(&steps);
+
+        let existing = if steps.len() == 1 {
+            self.bindings.get(&steps[0])
+        } else {
+            self.chords.get(&steps)
+        };
+        if let Some(existing) = existing {
+            return Err(format!(
+                "{} is already bound to {}",
+                normalized,
+                existing.description()
+            ));
+        }
+
+        self.insert_steps(steps, action);
+        Ok(normalized)
+    }
+
+    /// Store a parsed combo as a single-key binding or a chord
+    fn insert_steps(&mut self, mut steps: Vec<KeyBinding>, action: Action) {
+        if steps.len() == 1 {
+            if let Some(binding) = steps.pop() {
+                self.bindings.insert(binding, action);
+            }
+        } else {
+            self.chords.insert(steps, action);
+        }
+    }
+
+    /// Normalize a combo string to the form [`Self::bindings`] lists it in
+    ///
+    /// e.g. "ctrl + shift + g" becomes "Ctrl+Shift+G".
+    pub fn normalize_combo(combo: &str) -> Result<String, String> {
+        Self::parse_steps(combo).map(|steps| format_steps(&steps))
+    }
+
+    /// Check a combo strictly, rejecting unknown modifiers as well as anything
+    /// [`Self::add_binding_from_string`] rejects
+    ///
+    /// Binding a combo drops modifiers it doesn't know, so "Ctrl+Shfit+T"
+    /// would quietly bind Ctrl+T.
+    pub fn check_combo(combo: &str) -> Result<(), String> {
+        Self::parse_steps(combo)?;
+        for step in Self::join_steps(combo).split_whitespace() {
+            let parts: Vec<&str> = step.split('+').collect();
+            let modifiers = &parts[..parts.len().saturating_sub(1)];
+            if let Some(unknown) = modifiers.iter().find(|m| modifier_name(m).is_none()) {
+                return Err(format!(
+                    "Unknown modifier '{unknown}' in '{}'",
+                    combo.trim()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Split a combo string into normalized chord steps
+    fn parse_steps(combo: &str) -> Result<Vec<KeyBinding>, String> {
+        if combo.trim().is_empty() {
             return Err("Empty key combination".to_string());
         }
 
-        let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
-        if parts.is_empty() {
-            return Err("Invalid key combination format".to_string());
+        Self::join_steps(combo)
+            .split_whitespace()
+            .map(Self::parse_combo)
+            .collect()
+    }
+
+    /// Remove spaces around '+' ("Ctrl + T") so only step separators remain
+    fn join_steps(combo: &str) -> String {
+        let mut joined = String::with_capacity(combo.len());
+        for part in combo.split('+') {
+            if !joined.is_empty() {
+                joined.push('+');
+            }
+            joined.push_str(part.trim());
         }
+        joined
+    }
+
+    /// Parse a single step like "Ctrl+Shift+C" into a normalized binding
+    fn parse_combo(combo: &str) -> Result<KeyBinding, String> {
+        let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
 
         // Last part is the key, everything before is modifiers
         let key = match parts.last() {
-            Some(k) => *k,
-            None => return Err("Invalid key combination format".to_string()),
+            Some(k) if !k.is_empty() => *k,
+            _ => return Err("Invalid key combination format".to_string()),
         };
         let modifiers: Vec<&str> = parts[..parts.len().saturating_sub(1)].to_vec();
 
-        // Validate and normalize modifiers
-        let normalized_mods: Vec<&str> = modifiers
+        // Validate and normalize modifiers, in the order key events report them
+        let normalized_mods: Vec<String> = ["Ctrl", "Shift", "Alt"]
             .iter()
-            .filter_map(|m| {
-                match m.to_lowercase().as_str() {
-                    "ctrl" | "control" => Some("Ctrl"),
-                    "shift" => Some("Shift"),
-                    "alt" => Some("Alt"),
-                    _ => None, // Ignore unknown modifiers
-                }
+            .filter(|canonical| {
+                // Unknown modifiers are ignored
+                modifiers
+                    .iter()
+                    .any(|m| modifier_name(m) == Some(**canonical))
             })
+            .map(|m| (*m).to_string())
             .collect();
 
         // Normalize key name
@@ -211,22 +476,52 @@ impl KeybindingManager {
             "down" => "Down",
             "left" => "Left",
             "right" => "Right",
+            "home" => "Home",
+            "end" => "End",
             "space" => " ",
-            // Single character - use character count for UTF-8 safety
-            k if k.chars().count() == 1 => {
-                if let Some(c) = k.chars().next() {
-                    // For single characters, convert to lowercase for consistency
-                    let char_str = c.to_lowercase().to_string();
-                    self.add_binding(&char_str, &normalized_mods, action);
-                    return Ok(());
-                }
-                k
-            }
+            // Character keys are lowercased for consistency
             k => k,
         };
 
-        self.add_binding(normalized_key, &normalized_mods, action);
-        Ok(())
+        Ok(KeyBinding {
+            key: normalized_key.to_string(),
+            modifiers: normalized_mods,
+        })
+    }
+
+    /// Every registered binding as a human-readable combo and its action
+    ///
+    /// Combos use the same format [`Self::add_binding_from_string`] accepts
+    /// (e.g. "Ctrl+Shift+C", "Ctrl+B C"), sorted for stable display.
+    #[must_use]
+    pub fn bindings(&self) -> Vec<(String, Action)> {
+        let mut all: Vec<(String, Action)> = self
+            .bindings
+            .iter()
+            .map(|(binding, action)| (format_binding(binding), action.clone()))
+            .chain(
+                self.chords
+                    .iter()
+                    .map(|(steps, action)| (format_steps(steps), action.clone())),
+            )
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    /// Set how long a chord waits for its next key
+    pub fn set_chord_timeout(&mut self, timeout: Duration) {
+        self.chord_timeout = timeout;
+    }
+
+    /// Take the bindings and chord timeout of `other`, keeping shell integration state
+    ///
+    /// A chord typed partway through is dropped.
+    pub fn replace_bindings(&mut self, other: Self) {
+        self.bindings = other.bindings;
+        self.chords = other.chords;
+        self.chord_timeout = other.chord_timeout;
+        self.pending_chord = None;
     }
 
     /// Get action for key event
@@ -236,7 +531,79 @@ impl KeybindingManager {
     /// an uppercase 'C', but our bindings use lowercase 'c'. This function normalizes
     /// the key to lowercase for character keys while preserving Shift in modifiers.
     #[must_use]
+    #[allow(dead_code)] // Public API for future use
     pub fn get_action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        Self::key_binding(code, modifiers).and_then(|binding| self.bindings.get(&binding).cloned())
+    }
+
+    /// Resolve a key press, tracking chords across calls
+    ///
+    /// A key that starts a chord returns [`KeyResolution::ChordPending`] even
+    /// when it is also bound on its own; the standalone action runs only if
+    /// the chord times out (see [`Self::take_expired_chord`]). A key that does
+    /// not continue the pending chord abandons it and is resolved by itself.
+    pub fn resolve_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        now: Instant,
+    ) -> KeyResolution {
+        let Some(binding) = Self::key_binding(code, modifiers) else {
+            self.pending_chord = None;
+            return KeyResolution::Unbound;
+        };
+
+        if let Some((mut steps, last)) = self.pending_chord.take() {
+            if now.saturating_duration_since(last) < self.chord_timeout {
+                steps.push(binding.clone());
+                if let Some(action) = self.chords.get(&steps) {
+                    return KeyResolution::Action(action.clone());
+                }
+                if self.is_chord_prefix(&steps) {
+                    self.pending_chord = Some((steps, now));
+                    return KeyResolution::ChordPending;
+                }
+            }
+        }
+
+        let steps = vec![binding];
+        if self.is_chord_prefix(&steps) {
+            self.pending_chord = Some((steps, now));
+            return KeyResolution::ChordPending;
+        }
+
+        match self.bindings.get(&steps[0]) {
+            Some(action) => KeyResolution::Action(action.clone()),
+            None => KeyResolution::Unbound,
+        }
+    }
+
+    /// Drop a chord that has waited past the timeout at `now`
+    ///
+    /// Returns the standalone action of the abandoned prefix, if it has one,
+    /// so event loops can run it once the wait is over.
+    pub fn take_expired_chord(&mut self, now: Instant) -> Option<Action> {
+        let (steps, last) = self.pending_chord.as_ref()?;
+        if now.saturating_duration_since(*last) < self.chord_timeout {
+            return None;
+        }
+        let action = match steps.as_slice() {
+            [prefix] => self.bindings.get(prefix).cloned(),
+            _ => None,
+        };
+        self.pending_chord = None;
+        action
+    }
+
+    /// Whether `steps` is the start of a longer chord
+    fn is_chord_prefix(&self, steps: &[KeyBinding]) -> bool {
+        self.chords
+            .keys()
+            .any(|chord| chord.len() > steps.len() && chord.starts_with(steps))
+    }
+
+    /// Normalized binding for a key event, or `None` for keys that can't be bound
+    fn key_binding(code: KeyCode, modifiers: KeyModifiers) -> Option<KeyBinding> {
         let key_str = match code {
             // BUG FIX #6: Normalize character keys to lowercase for case-insensitive matching
             // This allows Ctrl+Shift+C to match a binding defined as ctrl+shift+c
@@ -248,6 +615,8 @@ impl KeybindingManager {
             KeyCode::Down => "Down".to_string(),
             KeyCode::Left => "Left".to_string(),
             KeyCode::Right => "Right".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
             _ => return None,
         };
 
@@ -262,12 +631,10 @@ impl KeybindingManager {
             mod_vec.push("Alt".to_string());
         }
 
-        let binding = KeyBinding {
+        Some(KeyBinding {
             key: key_str,
             modifiers: mod_vec,
-        };
-
-        self.bindings.get(&binding).cloned()
+        })
     }
 
     /// Enable shell integration features (future OSC parsing support)
@@ -286,16 +653,21 @@ impl KeybindingManager {
         }
     }
 
-    /// Update current directory from shell (future OSC 7 support)
+    /// Update current directory from an OSC 7 report
     pub fn update_directory(&mut self, dir: String) {
         self.shell_integration.current_dir = Some(dir);
     }
 
-    /// Update last command from shell (future OSC 133 support)
+    /// Update last command from an OSC 133 `C` mark
     pub fn update_last_command(&mut self, command: String) {
         self.shell_integration.last_command = Some(command);
     }
 
+    /// Record the exit code from an OSC 133 `D` mark
+    pub fn update_exit_code(&mut self, exit_code: Option<i32>) {
+        self.shell_integration.last_exit_code = exit_code;
+    }
+
     /// Get shell integration status
     #[must_use]
     pub fn shell_integration(&self) -> &ShellIntegration {
@@ -303,6 +675,41 @@ impl KeybindingManager {
     }
 }
 
+/// Canonical name of a modifier as written in a combo, e.g. "control" is "Ctrl"
+fn modifier_name(modifier: &str) -> Option<&'static str> {
+    match modifier.to_lowercase().as_str() {
+        "ctrl" | "control" => Some("Ctrl"),
+        "shift" => Some("Shift"),
+        "alt" => Some("Alt"),
+        _ => None,
+    }
+}
+
+/// Format a binding as a config-style combo such as "Ctrl+Shift+C"
+fn format_binding(binding: &KeyBinding) -> String {
+    let key = match binding.key.as_str() {
+        " " => "Space".to_string(),
+        key if key.chars().count() == 1 => key.to_uppercase(),
+        key => key.to_string(),
+    };
+    binding
+        .modifiers
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(key.as_str()))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Format chord steps as a space-separated combo such as "Ctrl+B C"
+fn format_steps(steps: &[KeyBinding]) -> String {
+    steps
+        .iter()
+        .map(format_binding)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Shell integration features (future API for OSC parsing)
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
@@ -322,6 +729,7 @@ impl Default for ShellIntegration {
             command_tracking: true,
             current_dir: None,
             last_command: None,
+            last_exit_code: None,
         }
     }
 }
@@ -356,6 +764,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_replace_bindings_keeps_shell_integration() {
+        let mut manager = KeybindingManager::new();
+        manager.update_directory("/home/user".to_string());
+        manager
+            .add_binding_from_string("Ctrl+B c", Action::NewTab)
+            .unwrap();
+        manager.resolve_key(KeyCode::Char('b'), KeyModifiers::CONTROL, Instant::now());
+
+        let mut other = KeybindingManager::new();
+        other
+            .add_binding_from_string("Ctrl+Shift+X", Action::Clear)
+            .unwrap();
+        manager.replace_bindings(other);
+
+        assert!(matches!(
+            manager.get_action(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ),
+            Some(Action::Clear)
+        ));
+        // The old chord and its half-typed first step are gone
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('c'), KeyModifiers::NONE, Instant::now()),
+            KeyResolution::Unbound
+        ));
+        assert_eq!(
+            manager.shell_integration().current_dir,
+            Some("/home/user".to_string())
+        );
+    }
+
     #[test]
     fn test_add_binding_from_string() {
         let mut manager = KeybindingManager::new();
@@ -443,6 +884,277 @@ mod tests {
         assert!(!manager.shell_integration().command_tracking);
     }
 
+    #[test]
+    fn test_action_from_str() {
+        let names = [
+            ("new_tab", Action::NewTab),
+            ("close_tab", Action::CloseTab),
+            ("next_tab", Action::NextTab),
+            ("prev_tab", Action::PrevTab),
+            ("split_horizontal", Action::SplitHorizontal),
+            ("split_vertical", Action::SplitVertical),
+            ("focus_next_pane", Action::FocusNextPane),
+            ("focus_prev_pane", Action::FocusPrevPane),
+            ("grow_pane", Action::GrowPane),
+            ("shrink_pane", Action::ShrinkPane),
+            ("scroll_to_top", Action::ScrollToTop),
+            ("scroll_to_bottom", Action::ScrollToBottom),
+            ("copy", Action::Copy),
+            ("copy_rich", Action::CopyRich),
+            ("paste", Action::Paste),
+            ("select_all", Action::SelectAll),
+            ("clear", Action::Clear),
+            ("clear_scrollback", Action::ClearScrollback),
+            ("search", Action::Search),
+            ("search_next", Action::SearchNext),
+            ("search_prev", Action::SearchPrev),
+            ("open_command_palette", Action::OpenCommandPalette),
+            ("toggle_autocomplete", Action::ToggleAutocomplete),
+            ("next_theme", Action::NextTheme),
+            ("prev_theme", Action::PrevTheme),
+            ("toggle_resource_monitor", Action::ToggleResourceMonitor),
+            ("toggle_stats_overlay", Action::ToggleStatsOverlay),
+            ("save_session", Action::SaveSession),
+            ("load_session", Action::LoadSession),
+            ("list_sessions", Action::ListSessions),
+            ("interrupt", Action::Interrupt),
+            ("restart_shell", Action::RestartShell),
+            ("prev_command", Action::PrevCommand),
+            ("next_command", Action::NextCommand),
+        ];
+        for (name, expected) in names {
+            let parsed: Action = name.parse().unwrap();
+            assert_eq!(format!("{parsed:?}"), format!("{expected:?}"), "{name}");
+        }
+
+        assert!(matches!("SaveSession".parse(), Ok(Action::SaveSession)));
+        assert!(matches!("next-theme".parse(), Ok(Action::NextTheme)));
+    }
+
+    #[test]
+    fn test_action_from_str_with_values() {
+        assert!(matches!(
+            "send_to_shell:ls -la".parse(),
+            Ok(Action::SendToShell(text)) if text == "ls -la"
+        ));
+        assert!(matches!(
+            "execute_command:git status".parse(),
+            Ok(Action::ExecuteCommand(cmd)) if cmd == "git status"
+        ));
+        assert!(matches!(
+            "custom:deploy".parse(),
+            Ok(Action::Custom(name)) if name == "deploy"
+        ));
+        assert!(matches!(
+            "execute_lua:print('a:b')".parse(),
+            Ok(Action::ExecuteLua(code)) if code == "print('a:b')"
+        ));
+
+        assert!("send_to_shell".parse::<Action>().is_err());
+        assert!("copy:extra".parse::<Action>().is_err());
+        assert!("launch_rockets".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn test_binding_format_round_trip() {
+        let combos = [
+            "Ctrl+T",
+            "Ctrl+Shift+C",
+            "Alt+Tab",
+            "Ctrl+Enter",
+            "Ctrl+Alt+Space",
+            "Ctrl+]",
+            "Ctrl+B C",
+        ];
+        for combo in combos {
+            let mut manager = KeybindingManager::new();
+            manager.bindings.clear();
+            manager
+                .add_binding_from_string(combo, Action::Custom(combo.to_string()))
+                .unwrap();
+
+            let listed = manager.bindings();
+            assert_eq!(listed.len(), 1, "{combo}");
+            assert_eq!(listed[0].0, combo);
+
+            // Re-adding the formatted combo yields the same binding
+            let mut again = KeybindingManager::new();
+            again.bindings.clear();
+            again
+                .add_binding_from_string(&listed[0].0, Action::Clear)
+                .unwrap();
+            assert_eq!(again.bindings()[0].0, combo);
+        }
+    }
+
+    #[test]
+    fn test_binding_format_normalizes_input() {
+        let mut manager = KeybindingManager::new();
+        manager.bindings.clear();
+        manager
+            .add_binding_from_string("shift + ctrl + x", Action::Clear)
+            .unwrap();
+
+        assert_eq!(manager.bindings()[0].0, "Ctrl+Shift+X");
+        assert!(matches!(
+            manager.get_action(
+                KeyCode::Char('X'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ),
+            Some(Action::Clear)
+        ));
+    }
+
+    #[test]
+    fn test_default_bindings_listed() {
+        let manager = KeybindingManager::new();
+        let listed = manager.bindings();
+
+        assert!(listed
+            .iter()
+            .any(|(combo, action)| combo == "Ctrl+T" && matches!(action, Action::NewTab)));
+        assert!(listed.iter().any(|(combo, action)| {
+            combo == "Ctrl+Shift+K" && matches!(action, Action::ShowKeybindings)
+        }));
+        let mut sorted = listed.clone();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            listed.iter().map(|b| &b.0).collect::<Vec<_>>(),
+            sorted.iter().map(|b| &b.0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_shift_home_and_end_scroll_by_default() {
+        let mut manager = KeybindingManager::new();
+        let now = Instant::now();
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Home, KeyModifiers::SHIFT, now),
+            KeyResolution::Action(Action::ScrollToTop)
+        ));
+        assert!(matches!(
+            manager.resolve_key(KeyCode::End, KeyModifiers::SHIFT, now),
+            KeyResolution::Action(Action::ScrollToBottom)
+        ));
+        // Plain Home and End still go to the shell
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Home, KeyModifiers::NONE, now),
+            KeyResolution::Unbound
+        ));
+
+        manager
+            .add_binding_from_string("ctrl + home", Action::ScrollToTop)
+            .unwrap();
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Home, KeyModifiers::CONTROL, now),
+            KeyResolution::Action(Action::ScrollToTop)
+        ));
+    }
+
+    #[test]
+    fn test_chord_resolution() {
+        let mut manager = KeybindingManager::new();
+        manager
+            .add_binding_from_string("ctrl+b c", Action::NewTab)
+            .unwrap();
+        let now = Instant::now();
+
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('b'), KeyModifiers::CONTROL, now),
+            KeyResolution::ChordPending
+        ));
+        assert!(matches!(
+            manager.resolve_key(
+                KeyCode::Char('c'),
+                KeyModifiers::NONE,
+                now + Duration::from_millis(200)
+            ),
+            KeyResolution::Action(Action::NewTab)
+        ));
+        // The chord is complete, so a lone 'c' is just a key again
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('c'), KeyModifiers::NONE, now),
+            KeyResolution::Unbound
+        ));
+        // A key that doesn't continue the chord is resolved on its own
+        manager.resolve_key(KeyCode::Char('b'), KeyModifiers::CONTROL, now);
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('f'), KeyModifiers::CONTROL, now),
+            KeyResolution::Action(Action::Search)
+        ));
+    }
+
+    #[test]
+    fn test_chord_timeout() {
+        let mut manager = KeybindingManager::new();
+        manager.set_chord_timeout(Duration::from_millis(500));
+        manager
+            .add_binding_from_string("Ctrl+B  Shift+Up", Action::PrevTheme)
+            .unwrap();
+        let now = Instant::now();
+
+        manager.resolve_key(KeyCode::Char('b'), KeyModifiers::CONTROL, now);
+        assert!(matches!(
+            manager.resolve_key(
+                KeyCode::Up,
+                KeyModifiers::SHIFT,
+                now + Duration::from_millis(600)
+            ),
+            KeyResolution::Unbound
+        ));
+
+        manager.resolve_key(KeyCode::Char('b'), KeyModifiers::CONTROL, now);
+        assert!(manager
+            .take_expired_chord(now + Duration::from_millis(100))
+            .is_none());
+        // Ctrl+B has no binding of its own, so nothing runs on expiry
+        assert!(manager
+            .take_expired_chord(now + Duration::from_millis(500))
+            .is_none());
+        assert!(matches!(
+            manager.resolve_key(
+                KeyCode::Up,
+                KeyModifiers::SHIFT,
+                now + Duration::from_millis(510)
+            ),
+            KeyResolution::Unbound
+        ));
+    }
+
+    #[test]
+    fn test_chord_prefix_with_standalone_binding() {
+        let mut manager = KeybindingManager::new();
+        // Ctrl+F is also the default Search binding
+        manager
+            .add_binding_from_string("Ctrl+F n", Action::SearchNext)
+            .unwrap();
+        let now = Instant::now();
+
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('f'), KeyModifiers::CONTROL, now),
+            KeyResolution::ChordPending
+        ));
+        assert!(matches!(
+            manager.resolve_key(KeyCode::Char('n'), KeyModifiers::NONE, now),
+            KeyResolution::Action(Action::SearchNext)
+        ));
+
+        // Without a follow-up key the standalone binding runs after the timeout
+        manager.resolve_key(KeyCode::Char('f'), KeyModifiers::CONTROL, now);
+        assert!(matches!(
+            manager.take_expired_chord(now + DEFAULT_CHORD_TIMEOUT),
+            Some(Action::Search)
+        ));
+        assert!(manager
+            .take_expired_chord(now + DEFAULT_CHORD_TIMEOUT)
+            .is_none());
+        // Single-key lookups ignore chords
+        assert!(matches!(
+            manager.get_action(KeyCode::Char('f'), KeyModifiers::CONTROL),
+            Some(Action::Search)
+        ));
+    }
+
     #[test]
     fn test_default_keybindings() {
         let manager = KeybindingManager::new();
@@ -524,6 +1236,46 @@ mod tests {
         assert!(matches!(action, Some(Action::ExecuteLua(_))));
     }
 
+    #[test]
+    fn test_add_new_binding_refuses_bound_combo() {
+        let mut manager = KeybindingManager::new();
+        let combo = manager
+            .add_new_binding("ctrl + shift + g", Action::Clear)
+            .unwrap();
+        assert_eq!(combo, "Ctrl+Shift+G");
+
+        // Defaults and earlier bindings are left alone
+        let err = manager
+            .add_new_binding("Ctrl+T", Action::Clear)
+            .unwrap_err();
+        assert!(err.contains("Ctrl+T"));
+        assert!(manager
+            .add_new_binding("Ctrl+Shift+G", Action::Copy)
+            .is_err());
+        assert!(matches!(
+            manager.get_action(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            Some(Action::NewTab)
+        ));
+
+        assert!(manager.add_new_binding("Ctrl+", Action::Clear).is_err());
+        assert_eq!(
+            KeybindingManager::normalize_combo("ctrl+b  c").unwrap(),
+            "Ctrl+B C"
+        );
+    }
+
+    #[test]
+    fn test_check_combo_rejects_unknown_modifiers() {
+        assert!(KeybindingManager::check_combo("control + shift + t").is_ok());
+        assert!(KeybindingManager::check_combo("Ctrl+B c").is_ok());
+
+        let err = KeybindingManager::check_combo("Ctrl+Shfit+T").unwrap_err();
+        assert!(err.contains("'Shfit'"));
+        assert!(KeybindingManager::check_combo("Ctrl+B Super+c").is_err());
+        assert!(KeybindingManager::check_combo("Ctrl+").is_err());
+        assert!(KeybindingManager::check_combo(" ").is_err());
+    }
+
     #[test]
     fn test_shell_integration_default() {
         let integration = ShellIntegration::default();
@@ -22,6 +22,7 @@
 //! - [`ui`]: UI components (command palette, resource monitor, themes)
 //! - [`session`]: Session save/restore functionality for workflow persistence
 //! - [`keybindings`]: Extensible keyboard shortcut handling
+//! - [`plugins`]: Native plugins loaded from dynamic libraries
 //! - [`colors`]: 24-bit true color support with blending operations
 //! - [`progress_bar`]: Command execution progress tracking with spinner
 //! - [`gpu`]: GPU-accelerated rendering with wgpu
@@ -38,7 +39,9 @@
 //!
 //! # Safety
 //!
-//! This codebase contains no `unsafe` code blocks. All operations are
+//! Apart from [`plugins`], which has to call into dynamically loaded
+//! libraries, and the OS calls [`shell`] uses to signal processes, this
+//! codebase contains no `unsafe` code blocks. All other operations are
 //! guaranteed memory-safe by the Rust compiler.
 
 pub mod colors;
@@ -46,6 +49,7 @@ pub mod config;
 pub mod gpu;
 pub mod hooks;
 pub mod keybindings;
+pub mod plugins;
 pub mod progress_bar;
 pub mod session;
 pub mod shell;
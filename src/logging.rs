@@ -0,0 +1,207 @@
+//! Where log messages go
+//!
+//! Without a log file, messages go to stderr: errors only, or everything from
+//! debug up with `--debug`. With `--log-file` or `logging.file` they also go
+//! to that file, from info up (debug up with `--debug`), and stderr keeps only
+//! errors. The file is written from a background thread and rotated when it
+//! grows past `logging.max_size_mb`: `furnace.log` becomes `furnace.log.1`,
+//! the old `.1` becomes `.2`, and so on up to `logging.max_files`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, Layer};
+
+use crate::config::LoggingConfig;
+
+/// Which messages go to stderr and to the log file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogPlan {
+    pub stderr_level: Level,
+    pub file: Option<FileLog>,
+}
+
+/// The log file and when to rotate it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLog {
+    pub path: PathBuf,
+    pub level: Level,
+    /// Size past which the file is rotated
+    pub max_bytes: u64,
+    /// Rotated files kept besides the current one
+    pub max_files: u64,
+}
+
+/// Decide where messages go
+///
+/// `log_file` from the command line takes precedence over `logging.file`.
+#[must_use]
+pub fn plan(debug: bool, log_file: Option<&Path>, config: &LoggingConfig) -> LogPlan {
+    let level = |verbose| if debug { Level::DEBUG } else { verbose };
+    let path = log_file
+        .map(Path::to_path_buf)
+        .or_else(|| config.file.as_ref().map(PathBuf::from));
+    match path {
+        Some(path) => LogPlan {
+            stderr_level: Level::ERROR,
+            file: Some(FileLog {
+                path,
+                level: level(Level::INFO),
+                max_bytes: config.max_size_mb.saturating_mul(1024 * 1024),
+                max_files: config.max_files,
+            }),
+        },
+        None => LogPlan {
+            stderr_level: level(Level::ERROR),
+            file: None,
+        },
+    }
+}
+
+/// Install the global subscriber for `plan`
+///
+/// With a log file, the returned guard flushes it when dropped, so it must
+/// live until Furnace exits.
+///
+/// # Errors
+/// Returns an error if the log file can't be opened or a subscriber is
+/// already installed
+pub fn init(plan: &LogPlan) -> Result<Option<WorkerGuard>> {
+    let stderr = fmt::layer()
+        .with_writer(io::stderr)
+        .with_filter(LevelFilter::from_level(plan.stderr_level));
+
+    let mut guard = None;
+    let file = match &plan.file {
+        Some(file) => {
+            let writer = RotatingFile::open(&file.path, file.max_bytes, file.max_files)
+                .with_context(|| format!("Failed to open log file {}", file.path.display()))?;
+            let (writer, worker) = tracing_appender::non_blocking(writer);
+            guard = Some(worker);
+            Some(
+                fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(writer)
+                    .with_filter(LevelFilter::from_level(file.level)),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .try_init()
+        .context("Failed to set global default subscriber")?;
+    Ok(guard)
+}
+
+/// A file that is renamed aside once it grows past a size
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating it and its directory if needed
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file can't be created
+    pub fn open(path: &Path, max_bytes: u64, max_files: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    /// Path of the `n`th rotated file, `1` being the newest
+    fn rotated(&self, n: u64) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+        PathBuf::from(path)
+    }
+
+    /// Shift the rotated files along, dropping the oldest, and start afresh
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for n in (1..self.max_files).rev() {
+            match fs::rename(self.rotated(n), self.rotated(n + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Each write is one message, so messages aren't split across files
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotates_past_max_size_and_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("furnace.log");
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for message in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(message.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join("logs").join(name)).unwrap();
+        assert_eq!(read("furnace.log"), "fourth\n");
+        assert_eq!(read("furnace.log.1"), "third\n");
+        assert_eq!(read("furnace.log.2"), "second\n");
+        assert!(!dir.path().join("logs").join("furnace.log.3").exists());
+
+        // Reopening carries on from the current size
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"fifth\n").unwrap();
+        assert_eq!(read("furnace.log"), "fifth\n");
+        assert_eq!(read("furnace.log.1"), "fourth\n");
+    }
+}
@@ -1,13 +1,17 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Result};
 use clap::Parser;
-use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
 
 mod colors;
 mod config;
 mod gpu;
 mod hooks;
 mod keybindings;
+mod logging;
+mod plugins;
 mod progress_bar;
 mod session;
 mod shell;
@@ -16,6 +20,7 @@ mod ui;
 
 use config::Config;
 use terminal::Terminal;
+use ui::themes::ThemeManager;
 
 /// Furnace - An extremely advanced, GPU-accelerated terminal emulator
 #[derive(Parser, Debug)]
@@ -29,49 +34,103 @@ struct Args {
     #[arg(short, long)]
     debug: bool,
 
+    /// Also write the log to this file, rotated by size; stderr then shows errors only
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
     /// Shell command to execute
     #[arg(short, long)]
     shell: Option<String>,
+
+    /// Named profile from the config whose settings override the base ones
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Print the names of the available themes and exit
+    #[arg(long)]
+    list_themes: bool,
+
+    /// Print the effective configuration as JSON and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Run a command in the shell, print its output and exit with its status
+    #[arg(short, long, value_name = "COMMAND")]
+    execute: Option<String>,
+
+    /// Seconds `--execute` waits for the command before giving up
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 30,
+        requires = "execute"
+    )]
+    timeout: u64,
 }
 
+/// Exit status when an `--execute` command outlives `--timeout`, as `timeout(1)` uses
+const TIMED_OUT: u8 = 124;
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let args = Args::parse();
+    let config = load_config(&args);
+
+    // Keep the guard so the log file is flushed on exit
+    let _log_guard = logging::init(&log_plan(&args, config.as_ref().ok()))?;
+
+    run(args, config, &mut io::stdout()).await
+}
 
-    // Initialize logging to stderr instead of stdout
-    // This prevents log messages from appearing in the terminal UI
-    // Only show logs in debug mode, otherwise disable logging
-    let log_level = if args.debug {
-        Level::DEBUG
-    } else {
-        Level::ERROR // Only show errors in normal mode, not INFO logs
-    };
-
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_writer(std::io::stderr) // Write to stderr, not stdout
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber)
-        .context("Failed to set global default subscriber")?;
-
-    // Load configuration
-    let config = if let Some(config_path) = args.config {
-        Config::load_from_file(&config_path)?
-    } else {
-        Config::load_default()?
-    };
+/// Start Furnace as `args` ask with `config`, or print what `--list-themes`,
+/// `--print-config` or `--execute` ask for to `out` and return
+///
+/// The printing options return before anything touches the terminal, so
+/// they work without a TTY, e.g. in scripts. Only `--list-themes` works
+/// without a config.
+async fn run(args: Args, config: Result<Config>, out: &mut impl Write) -> Result<ExitCode> {
+    if args.list_themes {
+        for name in theme_manager().available_theme_names() {
+            writeln!(out, "{name}")?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
 
     // Override shell if specified
-    let mut config = config;
-    if let Some(shell) = args.shell {
-        config.shell.default_shell = shell;
+    let mut config = config?;
+    if let Some(shell) = &args.shell {
+        config.shell.default_shell.clone_from(shell);
+    }
+
+    if args.print_config {
+        writeln!(out, "{}", config.to_json()?)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(command) = &args.execute {
+        let mut terminal = Terminal::new(config)?;
+        let result = terminal
+            .execute(command, Duration::from_secs(args.timeout))
+            .await?;
+        if !result.output.is_empty() {
+            writeln!(out, "{}", result.output)?;
+        }
+        return Ok(match result.status {
+            Some(status) => ExitCode::from(u8::try_from(status).unwrap_or(1)),
+            None => {
+                eprintln!("furnace: '{command}' timed out after {}s", args.timeout);
+                ExitCode::from(TIMED_OUT)
+            }
+        });
     }
 
     // GPU rendering uses a windowed application — no TTY check needed
 
     // Create and run terminal
     let mut terminal = Terminal::new(config)?;
+    if let Some(path) = config_path(&args) {
+        terminal.watch_config(&path, args.profile.as_deref());
+    }
     // Run terminal with better error context
     if let Err(e) = terminal.run().await {
         // Ensure terminal is cleaned up before showing error
@@ -81,5 +140,216 @@ async fn main() -> Result<()> {
         return Err(e);
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The config file `--config` names, or the default one if it exists
+fn config_path(args: &Args) -> Option<PathBuf> {
+    match &args.config {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Config::default_config_path()
+            .ok()
+            .filter(|path| path.exists()),
+    }
+}
+
+/// Load the config `args` point at, listing every problem in the file
+/// rather than the first
+fn load_config(args: &Args) -> Result<Config> {
+    let profile = args.profile.as_deref();
+    match config_path(args) {
+        Some(path) => Config::load_validated(&path, profile).map_err(|errors| {
+            let problems: Vec<String> = errors.iter().map(|error| format!("  {error}")).collect();
+            anyhow!(
+                "Invalid configuration, {} problems in {}:\n{}",
+                errors.len(),
+                path.display(),
+                problems.join("\n")
+            )
+        }),
+        None => match profile {
+            Some(profile) => Err(anyhow!(
+                "Unknown profile '{profile}': no config file to define it"
+            )),
+            None => Config::load_default(),
+        },
+    }
+}
+
+/// Where to log, from `args` and the `logging` section of `config`
+///
+/// Without a config, because it failed to load, only `--log-file` and
+/// `--debug` count.
+fn log_plan(args: &Args, config: Option<&Config>) -> logging::LogPlan {
+    let logging = config
+        .map(|config| config.logging.clone())
+        .unwrap_or_default();
+    logging::plan(args.debug, args.log_file.as_deref(), &logging)
+}
+
+/// Built-in themes plus any in the themes directory, without creating it
+fn theme_manager() -> ThemeManager {
+    ThemeManager::default_themes_dir()
+        .ok()
+        .filter(|dir| dir.is_dir())
+        .and_then(|dir| ThemeManager::with_themes_dir(dir).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    async fn run_with(args: &[&str]) -> Result<String> {
+        let args = Args::try_parse_from([&["furnace"], args].concat())?;
+        let config = load_config(&args);
+        let mut out = Vec::new();
+        run(args, config, &mut out).await?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[tokio::test]
+    async fn test_list_themes_prints_and_exits() {
+        let output = run_with(&["--list-themes"]).await.unwrap();
+        let names: Vec<&str> = output.lines().collect();
+        for builtin in ["dark", "light", "nord"] {
+            assert!(names.contains(&builtin), "{output}");
+        }
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_timeout_needs_execute() {
+        assert!(Args::try_parse_from(["furnace", "--timeout", "5"]).is_err());
+        let args = Args::try_parse_from(["furnace", "-e", "ls", "--timeout", "5"]).unwrap();
+        assert_eq!(args.execute.as_deref(), Some("ls"));
+        assert_eq!(args.timeout, 5);
+    }
+
+    #[test]
+    fn test_log_plan_follows_args_and_config() {
+        use logging::{FileLog, LogPlan};
+        use tracing::Level;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(&path, "config = {}\n").unwrap();
+        let config = path.to_str().unwrap();
+        let plan = |args: &[&str]| {
+            let args = [&["furnace", "--config", config], args].concat();
+            let args = Args::try_parse_from(args).unwrap();
+            log_plan(&args, load_config(&args).ok().as_ref())
+        };
+
+        // Only stderr, at the --debug level
+        assert_eq!(
+            plan(&[]),
+            LogPlan {
+                stderr_level: Level::ERROR,
+                file: None
+            }
+        );
+        assert_eq!(plan(&["--debug"]).stderr_level, Level::DEBUG);
+
+        // A log file takes --debug, and stderr keeps only errors
+        let expected = |path: &str, level| LogPlan {
+            stderr_level: Level::ERROR,
+            file: Some(FileLog {
+                path: PathBuf::from(path),
+                level,
+                max_bytes: 10 * 1024 * 1024,
+                max_files: 3,
+            }),
+        };
+        assert_eq!(
+            plan(&["--log-file", "/tmp/f.log"]),
+            expected("/tmp/f.log", Level::INFO)
+        );
+        assert_eq!(
+            plan(&["--log-file", "/tmp/f.log", "--debug"]),
+            expected("/tmp/f.log", Level::DEBUG)
+        );
+
+        // The config's file and sizes, with --log-file taking precedence
+        fs::write(
+            &path,
+            "config = { logging = { file = \"/tmp/c.log\", max_size_mb = 1, max_files = 5 } }\n",
+        )
+        .unwrap();
+        let file = plan(&[]).file.unwrap();
+        assert_eq!(file.path, PathBuf::from("/tmp/c.log"));
+        assert_eq!((file.max_bytes, file.max_files), (1024 * 1024, 5));
+        let file = plan(&["--log-file", "/tmp/f.log"]).file.unwrap();
+        assert_eq!(file.path, PathBuf::from("/tmp/f.log"));
+
+        // A config that fails to load leaves stderr, or --log-file
+        fs::write(
+            &path,
+            "config = { logging = { file = \"/tmp/c.log\", max_files = 0 } }\n",
+        )
+        .unwrap();
+        assert_eq!(plan(&[]).file, None);
+        let file = plan(&["--log-file", "/tmp/f.log"]).file.unwrap();
+        assert_eq!(file.path, PathBuf::from("/tmp/f.log"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_prints_output_and_exits_with_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(&path, "config = {}\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let args = ["--config", path, "--shell", "sh", "-e", "echo hi; exit 2"];
+        let args = Args::try_parse_from([&["furnace"], &args[..]].concat()).unwrap();
+        let config = load_config(&args);
+        let mut out = Vec::new();
+        let code = run(args, config, &mut out).await.unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "hi\n");
+        assert_eq!(code, ExitCode::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_print_config_shows_effective_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(
+            &path,
+            "config = {\n    terminal = { font_size = 14 },\n    profiles = { big = { terminal = { font_size = 22 } } },\n}\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let output = run_with(&[
+            "--config",
+            path,
+            "--profile",
+            "big",
+            "--shell",
+            "/bin/sh",
+            "--print-config",
+        ])
+        .await
+        .unwrap();
+        let printed = dir.path().join("printed.json");
+        fs::write(&printed, &output).unwrap();
+        let config = Config::load_from_file(&printed).unwrap();
+        // The profile and command-line overrides are applied
+        assert_eq!(config.terminal.font_size, 22);
+        assert_eq!(config.shell.default_shell, "/bin/sh");
+
+        // An invalid config still fails before printing
+        fs::write(
+            dir.path().join("config.lua"),
+            "config = { terminal = { font_size = 0 } }\n",
+        )
+        .unwrap();
+        assert!(run_with(&["--config", path, "--print-config"])
+            .await
+            .is_err());
+    }
 }
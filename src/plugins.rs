@@ -0,0 +1,649 @@
+//! Native plugins loaded from dynamic libraries
+//!
+//! A plugin is a `.so`, `.dll` or `.dylib` exporting these C symbols:
+//!
+//! ```c
+//! uint32_t    _plugin_abi_version(void);
+//! const char *_plugin_name(void);                                 /* optional */
+//! const char *_plugin_commands(void);                             /* optional */
+//! void       *_plugin_create(void);
+//! int32_t     _plugin_init(void *plugin);                         /* optional */
+//! char       *_plugin_handle_command(void *plugin, const char *command);
+//! void        _plugin_free_string(char *reply);
+//! void        _plugin_destroy(void *plugin);
+//! ```
+//!
+//! The lifecycle is:
+//! 1. `_plugin_abi_version` is checked first. It returns `major << 16 | minor`;
+//!    a plugin whose major version differs from [`PLUGIN_ABI_MAJOR`] is refused
+//!    before any other function is called.
+//! 2. `_plugin_name` and `_plugin_commands` return static strings: the plugin's
+//!    name (defaulting to its file name) and the space-separated commands it
+//!    handles. Two plugins can't share a name.
+//! 3. `_plugin_create` makes the instance and `_plugin_init`, if exported, runs
+//!    once on it. Init returns 0 on success; anything else unloads the plugin.
+//! 4. `_plugin_handle_command` returns a reply for commands the plugin handles
+//!    and null for the rest; replies are handed back to `_plugin_free_string`
+//!    once copied.
+//! 5. `_plugin_destroy` runs exactly once when the plugin is unloaded, including
+//!    when init failed.
+//!
+//! Commands can be namespaced with the plugin name: `git:status` always goes
+//! to the plugin named `git` (which sees `status`). A bare `status` goes to the
+//! one plugin listing it in `_plugin_commands`; if several do, none runs and
+//! the user is told to pick a namespace. Bare commands no plugin lists are
+//! offered to the plugins that don't export `_plugin_commands`.
+//!
+//! Panics must not unwind out of these functions: a Rust plugin should wrap each
+//! one in [`std::panic::catch_unwind`] and return null (or a non-zero init code),
+//! as `examples/plugins/hello_world.rs` does. The host keeps calling the plugin
+//! afterwards and still destroys it on unload.
+//!
+//! Everything here trusts the library to honor that contract, which is why this
+//! is the one module that needs `unsafe`.
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use libloading::Library;
+use tracing::{debug, info, warn};
+
+/// Major plugin ABI version; plugins built for another major version are refused
+pub const PLUGIN_ABI_MAJOR: u16 = 1;
+
+/// Minor plugin ABI version; additions that don't break older plugins
+pub const PLUGIN_ABI_MINOR: u16 = 1;
+
+/// The value `_plugin_abi_version` returns for plugins built against this ABI
+pub const PLUGIN_ABI_VERSION: u32 = ((PLUGIN_ABI_MAJOR as u32) << 16) | PLUGIN_ABI_MINOR as u32;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type StaticStrFn = unsafe extern "C" fn() -> *const c_char;
+type CreateFn = unsafe extern "C" fn() -> *mut c_void;
+type InitFn = unsafe extern "C" fn(*mut c_void) -> i32;
+type HandleCommandFn = unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_char;
+type FreeStringFn = unsafe extern "C" fn(*mut c_char);
+type DestroyFn = unsafe extern "C" fn(*mut c_void);
+
+/// What the manager needs from a plugin
+trait Plugin {
+    /// Name used as the plugin's command namespace
+    fn name(&self) -> &str;
+
+    /// Bare commands the plugin claims; empty if it didn't say
+    fn commands(&self) -> &[String];
+
+    /// Offer `command` to the plugin, returning its reply if it handled it
+    fn handle_command(&self, command: &CStr) -> Option<String>;
+}
+
+/// One loaded plugin and the library its code lives in
+struct LoadedPlugin {
+    name: String,
+    commands: Vec<String>,
+    instance: *mut c_void,
+    handle_command: HandleCommandFn,
+    free_string: FreeStringFn,
+    destroy: DestroyFn,
+    /// Declared last so it is unloaded after `Drop` has called `destroy`
+    _library: Library,
+}
+
+impl LoadedPlugin {
+    /// Open `path`, check its ABI version, then create and initialize the instance
+    fn load(path: &Path) -> Result<Self> {
+        // SAFETY: loading a library runs its initializers; plugins are trusted code
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load plugin {}", path.display()))?;
+
+        // SAFETY: every ABI version exports `_plugin_abi_version` with this signature
+        let abi_version = unsafe {
+            let abi_version = resolve::<AbiVersionFn>(&library, b"_plugin_abi_version\0", path)
+                .context("Plugins must report the ABI version they were built for")?;
+            abi_version()
+        };
+        check_abi_version(abi_version)
+            .with_context(|| format!("Refusing to load plugin {}", path.display()))?;
+
+        // SAFETY: both symbols return static strings and take no arguments
+        let (name, commands) = unsafe {
+            (
+                static_string(&library, b"_plugin_name\0"),
+                static_string(&library, b"_plugin_commands\0"),
+            )
+        };
+        let name = name.unwrap_or_else(|| plugin_name(path));
+        if name.is_empty() || name.contains(|c: char| c == ':' || c.is_whitespace()) {
+            anyhow::bail!(
+                "Plugin {} has an invalid name '{}': names can't be empty or contain ':' or spaces",
+                path.display(),
+                name
+            );
+        }
+        let commands = commands
+            .map(|list| list.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        // SAFETY: the signatures match the ABI documented at the top of this module.
+        // The function pointers stay valid while `library` is loaded, and it
+        // is stored alongside them.
+        let (create, init, handle_command, free_string, destroy) = unsafe {
+            (
+                *resolve::<CreateFn>(&library, b"_plugin_create\0", path)?,
+                resolve::<InitFn>(&library, b"_plugin_init\0", path)
+                    .ok()
+                    .map(|init| *init),
+                *resolve::<HandleCommandFn>(&library, b"_plugin_handle_command\0", path)?,
+                *resolve::<FreeStringFn>(&library, b"_plugin_free_string\0", path)?,
+                *resolve::<DestroyFn>(&library, b"_plugin_destroy\0", path)?,
+            )
+        };
+
+        // SAFETY: `_plugin_create` takes no arguments and returns an owned instance
+        let instance = unsafe { create() };
+        if instance.is_null() {
+            anyhow::bail!("Plugin {} failed to create an instance", path.display());
+        }
+
+        // From here on, dropping the plugin destroys the instance
+        let plugin = Self {
+            name,
+            commands,
+            instance,
+            handle_command,
+            free_string,
+            destroy,
+            _library: library,
+        };
+
+        if let Some(init) = init {
+            // SAFETY: `instance` is fresh from `_plugin_create`; this is the only init call
+            let status = unsafe { init(plugin.instance) };
+            if status != 0 {
+                anyhow::bail!(
+                    "Plugin {} failed to initialize (status {})",
+                    path.display(),
+                    status
+                );
+            }
+        }
+
+        Ok(plugin)
+    }
+}
+
+impl Plugin for LoadedPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    fn handle_command(&self, command: &CStr) -> Option<String> {
+        // SAFETY: `instance` came from `_plugin_create` and is not destroyed yet;
+        // `command` outlives the call
+        let reply = unsafe { (self.handle_command)(self.instance, command.as_ptr()) };
+        if reply.is_null() {
+            return None;
+        }
+
+        // SAFETY: a non-null reply is a NUL-terminated string owned by the plugin
+        // until it is passed back to `_plugin_free_string`
+        let text = unsafe { CStr::from_ptr(reply) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { (self.free_string)(reply) };
+        Some(text)
+    }
+}
+
+impl Drop for LoadedPlugin {
+    fn drop(&mut self) {
+        debug!("Unloading plugin {}", self.name);
+        // SAFETY: `instance` came from `_plugin_create` and is destroyed exactly once
+        unsafe { (self.destroy)(self.instance) };
+    }
+}
+
+/// Look up `symbol` in `library`, naming the plugin in the error
+///
+/// # Safety
+/// `T` must match the type of the exported symbol.
+unsafe fn resolve<'lib, T>(
+    library: &'lib Library,
+    symbol: &[u8],
+    path: &Path,
+) -> Result<libloading::Symbol<'lib, T>> {
+    library.get(symbol).with_context(|| {
+        format!(
+            "Plugin {} does not export {}",
+            path.display(),
+            String::from_utf8_lossy(&symbol[..symbol.len().saturating_sub(1)])
+        )
+    })
+}
+
+/// Copy the string an optional `const char *symbol(void)` returns
+///
+/// # Safety
+/// If exported, `symbol` must have that signature and return null or a
+/// NUL-terminated string.
+unsafe fn static_string(library: &Library, symbol: &[u8]) -> Option<String> {
+    let get = library.get::<StaticStrFn>(symbol).ok()?;
+    let text = get();
+    if text.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(text).to_string_lossy().trim().to_string())
+}
+
+/// Accept plugins built for the same major ABI version as the host
+///
+/// Minor versions only add to the ABI, so any minor version is accepted.
+fn check_abi_version(version: u32) -> Result<()> {
+    let (major, minor) = (version >> 16, version & 0xFFFF);
+    if major != PLUGIN_ABI_VERSION >> 16 {
+        anyhow::bail!(
+            "plugin was built for plugin ABI {}.{}, but Furnace uses {}.{} and only the minor version may differ",
+            major,
+            minor,
+            PLUGIN_ABI_MAJOR,
+            PLUGIN_ABI_MINOR
+        );
+    }
+    Ok(())
+}
+
+/// Plugin name from its file name, e.g. "libgit_tools.so" -> "git_tools"
+fn plugin_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match stem.strip_prefix("lib") {
+        Some(name) if !cfg!(windows) && !name.is_empty() => name.to_string(),
+        _ => stem,
+    }
+}
+
+/// Whether `path` has this platform's dynamic library extension
+fn is_plugin_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+}
+
+/// Loads plugins and passes commands to them
+#[derive(Default)]
+pub struct PluginManager {
+    /// Loaded plugins, asked in load order
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl PluginManager {
+    /// Create a manager with no plugins loaded
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a single plugin library
+    ///
+    /// # Errors
+    /// Returns an error if the library cannot be opened, lacks one of the
+    /// required symbols, fails to start, or has the same name as a loaded plugin
+    pub fn load_plugin(&mut self, path: impl AsRef<Path>) -> Result<&str> {
+        let plugin = LoadedPlugin::load(path.as_ref())?;
+        self.add(Box::new(plugin))
+    }
+
+    /// Register a started plugin, warning about bare commands it shares with others
+    fn add(&mut self, plugin: Box<dyn Plugin>) -> Result<&str> {
+        if self.plugins.iter().any(|p| p.name() == plugin.name()) {
+            anyhow::bail!("A plugin named '{}' is already loaded", plugin.name());
+        }
+
+        for command in plugin.commands() {
+            let owners = self.claimants(command);
+            if !owners.is_empty() {
+                warn!(
+                    "Plugin {} also provides '{}' (already provided by {}); use {}:{} to pick one",
+                    plugin.name(),
+                    command,
+                    owners.join(", "),
+                    plugin.name(),
+                    command
+                );
+            }
+        }
+
+        info!("Loaded plugin {}", plugin.name());
+        self.plugins.push(plugin);
+        Ok(self.plugins[self.plugins.len() - 1].name())
+    }
+
+    /// Names of the plugins listing `command` among their commands
+    fn claimants(&self, command: &str) -> Vec<&str> {
+        self.plugins
+            .iter()
+            .filter(|plugin| plugin.commands().iter().any(|c| c == command))
+            .map(|plugin| plugin.name())
+            .collect()
+    }
+
+    /// Load every plugin library in `dir`, in file name order
+    ///
+    /// A plugin that fails to load is logged and skipped; the rest still load.
+    ///
+    /// # Returns
+    /// The plugins that failed, with the reason
+    ///
+    /// # Errors
+    /// Returns an error if `dir` cannot be read
+    pub fn load_directory(
+        &mut self,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, anyhow::Error)>> {
+        let dir = dir.as_ref();
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read plugin directory {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_plugin_file(path))
+            .collect();
+        paths.sort();
+
+        let mut failures = Vec::new();
+        for path in paths {
+            if let Err(e) = self.load_plugin(&path) {
+                warn!("Skipping plugin: {:#}", e);
+                failures.push((path, e));
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Unload the plugin called `name`, running its `_plugin_destroy`
+    ///
+    /// Returns false if no such plugin is loaded.
+    #[allow(dead_code)] // Public API for future use
+    pub fn unload_plugin(&mut self, name: &str) -> bool {
+        let before = self.plugins.len();
+        self.plugins.retain(|plugin| plugin.name() != name);
+        self.plugins.len() != before
+    }
+
+    /// Names of the loaded plugins, in load order
+    #[must_use]
+    pub fn plugin_names(&self) -> Vec<&str> {
+        self.plugins.iter().map(|plugin| plugin.name()).collect()
+    }
+
+    /// Every command the plugins list, namespaced as `plugin:command`, in load order
+    #[must_use]
+    pub fn namespaced_commands(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| {
+                plugin
+                    .commands()
+                    .iter()
+                    .map(|command| format!("{}:{}", plugin.name(), command))
+            })
+            .collect()
+    }
+
+    /// Bare commands claimed by more than one plugin, with the plugins claiming them
+    ///
+    /// These only run when namespaced, e.g. `git:status`.
+    #[must_use]
+    #[allow(dead_code)] // Public API for future use
+    pub fn command_conflicts(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut commands: Vec<&str> = self
+            .plugins
+            .iter()
+            .flat_map(|plugin| plugin.commands().iter().map(String::as_str))
+            .collect();
+        commands.sort_unstable();
+        commands.dedup();
+        commands
+            .into_iter()
+            .map(|command| (command, self.claimants(command)))
+            .filter(|(_, owners)| owners.len() > 1)
+            .collect()
+    }
+
+    /// Whether no plugins are loaded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Pass `command` to the plugin it belongs to
+    ///
+    /// A `name:command` prefix picks the plugin named `name`. Otherwise the
+    /// plugin listing the command gets it, or, if none does, the first plugin
+    /// without a command list that handles it. A bare command listed by
+    /// several plugins runs none of them; the reply says which namespaces to use.
+    ///
+    /// Returns `None` if no plugin handles the command (or it contains a NUL byte).
+    #[must_use]
+    pub fn handle_command(&self, command: &str) -> Option<String> {
+        let command = command.trim_start();
+        let word = command.split_whitespace().next()?;
+
+        if let Some((namespace, _)) = word.split_once(':') {
+            if let Some(plugin) = self.plugins.iter().find(|p| p.name() == namespace) {
+                let unprefixed = CString::new(&command[namespace.len() + 1..]).ok()?;
+                return plugin.handle_command(&unprefixed);
+            }
+        }
+
+        let full = CString::new(command).ok()?;
+        let owners: Vec<&dyn Plugin> = self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.commands().iter().any(|c| c == word))
+            .map(AsRef::as_ref)
+            .collect();
+        match owners.as_slice() {
+            [] => self
+                .plugins
+                .iter()
+                .filter(|plugin| plugin.commands().is_empty())
+                .find_map(|plugin| plugin.handle_command(&full)),
+            [plugin] => plugin.handle_command(&full),
+            _ => {
+                let choices: Vec<String> = owners
+                    .iter()
+                    .map(|plugin| format!("{}:{}", plugin.name(), word))
+                    .collect();
+                Some(format!(
+                    "'{}' is provided by several plugins; use {}",
+                    word,
+                    choices.join(" or ")
+                ))
+            }
+        }
+    }
+}
+
+/// Expand a leading `~` in a configured plugin directory
+#[must_use]
+pub fn expand_plugin_dir(dir: &str) -> PathBuf {
+    match dir.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches(['/', '\\'])),
+            None => PathBuf::from(dir),
+        },
+        _ => PathBuf::from(dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_version_check() {
+        assert!(check_abi_version(PLUGIN_ABI_VERSION).is_ok());
+        // Newer and older minor versions share the ABI
+        assert!(check_abi_version(PLUGIN_ABI_VERSION + 3).is_ok());
+        assert!(check_abi_version(u32::from(PLUGIN_ABI_MAJOR) << 16).is_ok());
+
+        let newer_major = (u32::from(PLUGIN_ABI_MAJOR) + 1) << 16 | 2;
+        let err = check_abi_version(newer_major).unwrap_err().to_string();
+        assert!(err.contains(&format!("{}.2", PLUGIN_ABI_MAJOR + 1)));
+        assert!(check_abi_version(0).is_err());
+    }
+
+    #[test]
+    fn test_plugin_name_from_file_name() {
+        let name = plugin_name(Path::new("/plugins/libgit_tools.so"));
+        if cfg!(windows) {
+            assert_eq!(name, "libgit_tools");
+        } else {
+            assert_eq!(name, "git_tools");
+        }
+        assert_eq!(plugin_name(Path::new("weather.dll")), "weather");
+        assert_eq!(plugin_name(Path::new("lib.so")), "lib");
+    }
+
+    #[test]
+    fn test_only_libraries_are_plugins() {
+        let library = format!("plugin.{}", std::env::consts::DLL_EXTENSION);
+        assert!(is_plugin_file(Path::new(&library)));
+        assert!(!is_plugin_file(Path::new("README.md")));
+        assert!(!is_plugin_file(Path::new("plugin")));
+    }
+
+    #[test]
+    fn test_expand_plugin_dir() {
+        assert_eq!(
+            expand_plugin_dir("/opt/plugins"),
+            PathBuf::from("/opt/plugins")
+        );
+        assert_eq!(
+            expand_plugin_dir("~user/plugins"),
+            PathBuf::from("~user/plugins")
+        );
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_plugin_dir("~/plugins"), home.join("plugins"));
+        }
+    }
+
+    /// In-process plugin that echoes the commands it gets
+    struct MockPlugin {
+        name: String,
+        commands: Vec<String>,
+    }
+
+    impl MockPlugin {
+        fn boxed(name: &str, commands: &[&str]) -> Box<dyn Plugin> {
+            Box::new(Self {
+                name: name.to_string(),
+                commands: commands.iter().map(|c| (*c).to_string()).collect(),
+            })
+        }
+    }
+
+    impl Plugin for MockPlugin {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn commands(&self) -> &[String] {
+            &self.commands
+        }
+
+        fn handle_command(&self, command: &CStr) -> Option<String> {
+            let command = command.to_str().ok()?;
+            let word = command.split_whitespace().next()?;
+            if !self.commands.is_empty() && !self.commands.iter().any(|c| c == word) {
+                return None;
+            }
+            Some(format!("{} ran {}", self.name, command))
+        }
+    }
+
+    #[test]
+    fn test_namespaced_dispatch() {
+        let mut manager = PluginManager::new();
+        manager
+            .add(MockPlugin::boxed("git", &["status", "help"]))
+            .unwrap();
+        manager
+            .add(MockPlugin::boxed("docker", &["ps", "help"]))
+            .unwrap();
+
+        // Unambiguous bare commands and namespaced ones reach the same plugin
+        assert_eq!(
+            manager.handle_command("status -s").as_deref(),
+            Some("git ran status -s")
+        );
+        assert_eq!(
+            manager.handle_command("git:status -s").as_deref(),
+            Some("git ran status -s")
+        );
+        assert_eq!(
+            manager.handle_command("docker:help").as_deref(),
+            Some("docker ran help")
+        );
+        // The namespace picks the plugin even for a command it didn't list
+        assert_eq!(manager.handle_command("docker:status"), None);
+        assert_eq!(
+            manager.namespaced_commands(),
+            vec!["git:status", "git:help", "docker:ps", "docker:help"]
+        );
+        // Unknown namespaces and unclaimed commands are left for the shell
+        assert_eq!(manager.handle_command("c:/tools/run.exe"), None);
+        assert_eq!(manager.handle_command("ls"), None);
+    }
+
+    #[test]
+    fn test_command_collisions() {
+        let mut manager = PluginManager::new();
+        manager
+            .add(MockPlugin::boxed("git", &["status", "help"]))
+            .unwrap();
+        manager
+            .add(MockPlugin::boxed("docker", &["ps", "help"]))
+            .unwrap();
+        manager.add(MockPlugin::boxed("legacy", &[])).unwrap();
+
+        assert_eq!(
+            manager.command_conflicts(),
+            vec![("help", vec!["git", "docker"])]
+        );
+
+        // A colliding bare command runs neither plugin and names the choices
+        let reply = manager.handle_command("help").unwrap();
+        assert!(reply.contains("git:help or docker:help"), "{}", reply);
+
+        // Plugins without a command list only see commands nobody claimed
+        assert_eq!(
+            manager.handle_command("uptime").as_deref(),
+            Some("legacy ran uptime")
+        );
+
+        // Names are namespaces, so they must be unique
+        assert!(manager.add(MockPlugin::boxed("git", &["log"])).is_err());
+        assert_eq!(manager.plugin_names(), vec!["git", "docker", "legacy"]);
+
+        assert!(manager.unload_plugin("docker"));
+        assert!(manager.command_conflicts().is_empty());
+        assert_eq!(
+            manager.handle_command("help").as_deref(),
+            Some("git ran help")
+        );
+    }
+
+    #[test]
+    fn test_empty_manager_handles_nothing() {
+        let manager = PluginManager::new();
+        assert!(manager.is_empty());
+        assert_eq!(manager.handle_command("hello"), None);
+    }
+}
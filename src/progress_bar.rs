@@ -4,9 +4,10 @@
 //!
 //! # Features
 //! - Visual progress indicator for long-running commands
-//! - Spinner animation while command is executing
-//! - Elapsed time display
+//! - Spinner animation while command is executing, in a configurable [`SpinnerStyle`]
+//! - Elapsed time display (`[0:03]`), and a brief "Done" state with the total time
 //! - Command name display
+//! - Percentage bar and ETA when the output reports progress (e.g. `42%`)
 
 use std::time::{Duration, Instant};
 
@@ -19,25 +20,85 @@ pub struct ProgressBar {
     command: String,
     /// When the command started
     start_time: Option<Instant>,
+    /// Spinner animation frames to cycle through
+    style: SpinnerStyle,
     /// Current spinner frame (for animation)
     spinner_frame: usize,
     /// Cached elapsed seconds to avoid repeated formatting (Bug #17)
     cached_elapsed_secs: u64,
+    /// Completion percentage parsed from the command's output, if any
+    percent: Option<f32>,
+    /// When the command finished; the bar shows "Done" until [`DONE_DISPLAY_DURATION`] passes
+    completed_at: Option<Instant>,
 }
 
-/// Bug #15: ASCII spinner characters that work on all terminals including Windows Conhost
-const SPINNER_CHARS: &[char] = &['|', '/', '-', '\\'];
+/// Spinner animation styles for the progress bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpinnerStyle {
+    /// Braille dots rotating around a cell
+    Dots,
+    /// Bug #15: ASCII line that works on all terminals including Windows Conhost
+    #[default]
+    Line,
+    /// Dense Braille block with one dot missing, rotating
+    Braille,
+    /// Quarter circle arc sweeping around
+    Arc,
+    /// Single dot bouncing up and down
+    Bounce,
+}
+
+impl SpinnerStyle {
+    /// Parse the `progress_bar.spinner_style` config value, defaulting to dots
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "line" => Self::Line,
+            "braille" => Self::Braille,
+            "arc" => Self::Arc,
+            "bounce" => Self::Bounce,
+            _ => Self::Dots,
+        }
+    }
+
+    /// Animation frames, in display order
+    #[must_use]
+    pub fn frames(self) -> &'static [char] {
+        match self {
+            Self::Dots => &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'],
+            Self::Line => &['|', '/', '-', '\\'],
+            Self::Braille => &['⣾', '⣽', '⣻', '⢿', '⡿', '⣟', '⣯', '⣷'],
+            Self::Arc => &['◜', '◠', '◝', '◞', '◡', '◟'],
+            Self::Bounce => &['⠁', '⠂', '⠄', '⠂'],
+        }
+    }
+}
+
+/// Width of the percentage bar in cells, excluding the brackets
+const BAR_WIDTH: usize = 10;
+
+/// How long a completed command stays on screen before the bar hides
+pub const DONE_DISPLAY_DURATION: Duration = Duration::from_secs(2);
 
 impl ProgressBar {
     /// Create a new progress bar
     #[must_use]
     pub fn new() -> Self {
+        Self::with_style(SpinnerStyle::default())
+    }
+
+    /// Create a new progress bar with the given spinner animation
+    #[must_use]
+    pub fn with_style(style: SpinnerStyle) -> Self {
         Self {
             visible: false,
             command: String::new(),
             start_time: None,
+            style,
             spinner_frame: 0,
             cached_elapsed_secs: 0,
+            percent: None,
+            completed_at: None,
         }
     }
 
@@ -49,6 +110,8 @@ impl ProgressBar {
         self.start_time = Some(Instant::now());
         self.spinner_frame = 0;
         self.cached_elapsed_secs = 0;
+        self.percent = None;
+        self.completed_at = None;
     }
 
     /// Start tracking a command (legacy API, takes ownership)
@@ -58,6 +121,8 @@ impl ProgressBar {
         self.start_time = Some(Instant::now());
         self.spinner_frame = 0;
         self.cached_elapsed_secs = 0;
+        self.percent = None;
+        self.completed_at = None;
     }
 
     /// Stop tracking and hide progress bar
@@ -67,44 +132,111 @@ impl ProgressBar {
         self.start_time = None;
         self.spinner_frame = 0;
         self.cached_elapsed_secs = 0;
+        self.percent = None;
+        self.completed_at = None;
+    }
+
+    /// Mark the command as finished, showing its total time until the bar hides
+    ///
+    /// Does nothing if no command is running or it has already completed.
+    pub fn complete(&mut self) {
+        self.complete_at(Instant::now());
+    }
+
+    /// [`Self::complete`] with an explicit completion time
+    pub fn complete_at(&mut self, now: Instant) {
+        if !self.visible || self.completed_at.is_some() {
+            return;
+        }
+        if let Some(start) = self.start_time {
+            self.cached_elapsed_secs = now.saturating_duration_since(start).as_secs();
+        }
+        self.completed_at = Some(now);
+    }
+
+    /// Whether the command has finished and the bar is showing its total time
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.completed_at.is_some()
     }
 
     /// Update spinner animation
     pub fn tick(&mut self) {
+        self.tick_at(Instant::now());
+    }
+
+    /// [`Self::tick`] at an explicit time; hides a completed bar once its display time is up
+    pub fn tick_at(&mut self, now: Instant) {
+        if let Some(completed) = self.completed_at {
+            if now.saturating_duration_since(completed) >= DONE_DISPLAY_DURATION {
+                self.stop();
+            }
+            return;
+        }
         if self.visible {
-            self.spinner_frame = (self.spinner_frame + 1) % SPINNER_CHARS.len();
+            self.spinner_frame = (self.spinner_frame + 1) % self.style.frames().len();
             // Update cached elapsed time
             if let Some(start) = self.start_time {
-                self.cached_elapsed_secs = start.elapsed().as_secs();
+                self.cached_elapsed_secs = now.saturating_duration_since(start).as_secs();
             }
         }
     }
 
-    /// Get current spinner character (Bug #15: ASCII-safe)
+    /// Record completion reported by the command, clamped to 0-100
+    pub fn update_percent(&mut self, pct: f32) {
+        if self.visible && !self.is_done() && pct.is_finite() {
+            self.percent = Some(pct.clamp(0.0, 100.0));
+        }
+    }
+
+    /// Completion percentage, if the command has reported one
+    #[must_use]
+    pub fn percent(&self) -> Option<f32> {
+        self.percent
+    }
+
+    /// Estimated time remaining, extrapolated from elapsed time and percentage
+    #[must_use]
+    pub fn eta(&self) -> Option<Duration> {
+        let elapsed = self.start_time?.elapsed();
+        estimate_remaining(self.percent?, elapsed)
+    }
+
+    /// Get current spinner character
     #[must_use]
     pub fn spinner_char(&self) -> char {
-        SPINNER_CHARS[self.spinner_frame]
+        self.style.frames()[self.spinner_frame]
     }
 
     /// Get elapsed time as formatted string (Bug #17: uses cached value)
     #[must_use]
+    #[allow(dead_code)] // Public API for future use
     pub fn elapsed(&self) -> String {
         format_duration_secs(self.cached_elapsed_secs)
     }
 
+    /// Get elapsed time as a clock such as `0:03` or `1:02:03`
+    #[must_use]
+    pub fn elapsed_clock(&self) -> String {
+        format_clock_secs(self.cached_elapsed_secs)
+    }
+
     /// Get display text for progress bar
     #[must_use]
     pub fn display_text(&self) -> String {
-        if self.visible {
-            format!(
-                "{} Running: {} ({})",
-                self.spinner_char(),
-                self.command,
-                self.elapsed()
-            )
-        } else {
-            String::new()
-        }
+        self.display_text_truncated(usize::MAX)
+    }
+
+    /// Bar, percentage and ETA shown after the command once progress is known
+    fn progress_suffix(&self) -> String {
+        let Some(percent) = self.percent() else {
+            return String::new();
+        };
+        let eta = match self.eta() {
+            Some(eta) => format!(" ETA {}", format_duration_secs(eta.as_secs())),
+            None => String::new(),
+        };
+        format!(" {} {percent:.0}%{eta}", render_bar(percent, BAR_WIDTH))
     }
 
     /// Bug #16: Get display text with truncated command
@@ -114,29 +246,31 @@ impl ProgressBar {
     /// characters.
     #[must_use]
     pub fn display_text_truncated(&self, max_cmd_len: usize) -> String {
-        if self.visible {
-            // Count characters (not bytes) to safely handle UTF-8
-            let char_count = self.command.chars().count();
-            if char_count > max_cmd_len {
-                // Safely truncate at character boundary
-                let truncate_len = max_cmd_len.saturating_sub(3);
-                let truncated: String = self.command.chars().take(truncate_len).collect();
-                format!(
-                    "{} Running: {}... ({})",
-                    self.spinner_char(),
-                    truncated,
-                    self.elapsed()
-                )
-            } else {
-                format!(
-                    "{} Running: {} ({})",
-                    self.spinner_char(),
-                    &self.command,
-                    self.elapsed()
-                )
-            }
+        if !self.visible {
+            return String::new();
+        }
+
+        // Count characters (not bytes) to safely handle UTF-8
+        let char_count = self.command.chars().count();
+        let command = if char_count > max_cmd_len {
+            // Safely truncate at character boundary
+            let truncate_len = max_cmd_len.saturating_sub(3);
+            let truncated: String = self.command.chars().take(truncate_len).collect();
+            format!("{truncated}...")
         } else {
-            String::new()
+            self.command.clone()
+        };
+
+        if self.is_done() {
+            format!("Done: {} [{}]", command, self.elapsed_clock())
+        } else {
+            format!(
+                "{} Running: {}{} [{}]",
+                self.spinner_char(),
+                command,
+                self.progress_suffix(),
+                self.elapsed_clock()
+            )
         }
     }
 
@@ -168,6 +302,66 @@ fn format_duration_secs(secs: u64) -> String {
     }
 }
 
+/// Format seconds as `m:ss`, or `h:mm:ss` from an hour on
+fn format_clock_secs(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Extrapolate the time left from the share of work done so far
+///
+/// Returns `None` until some progress has been made, since nothing can be
+/// extrapolated from 0%.
+#[must_use]
+pub fn estimate_remaining(percent: f32, elapsed: Duration) -> Option<Duration> {
+    if !percent.is_finite() || percent <= 0.0 {
+        return None;
+    }
+    if percent >= 100.0 {
+        return Some(Duration::ZERO);
+    }
+    let remaining = elapsed.as_secs_f64() * f64::from(100.0 - percent) / f64::from(percent);
+    Some(Duration::from_secs_f64(remaining))
+}
+
+/// Find the last percentage (e.g. `42%` or `12.5 %`) in a chunk of output
+///
+/// Values above 100 are ignored so things like `250%` CPU readings don't
+/// register as progress.
+#[must_use]
+pub fn parse_percent(text: &str) -> Option<f32> {
+    let bytes = text.as_bytes();
+    let mut found = None;
+    for (i, _) in text.match_indices('%') {
+        // Allow a single space between the number and the sign
+        let mut end = i;
+        if end > 0 && bytes[end - 1] == b' ' {
+            end -= 1;
+        }
+        let mut start = end;
+        while start > 0 && (bytes[start - 1].is_ascii_digit() || bytes[start - 1] == b'.') {
+            start -= 1;
+        }
+        if let Ok(value) = text[start..end].parse::<f32>() {
+            if (0.0..=100.0).contains(&value) {
+                found = Some(value);
+            }
+        }
+    }
+    found
+}
+
+/// ASCII bar such as `[####------]` filled to `percent`
+fn render_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent / 100.0) * width as f32).round() as usize;
+    let filled = filled.min(width);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
 /// Format duration for display (legacy API - kept for future use)
 #[must_use]
 pub fn _format_duration(duration: Duration) -> String {
@@ -216,8 +410,8 @@ mod tests {
 
     #[test]
     fn test_spinner_is_ascii() {
-        // Bug #15: Ensure all spinner chars are basic ASCII
-        for c in SPINNER_CHARS {
+        // Bug #15: Ensure the default spinner chars are basic ASCII
+        for c in SpinnerStyle::default().frames() {
             assert!(c.is_ascii(), "Spinner char '{c}' is not ASCII");
         }
     }
@@ -314,7 +508,45 @@ mod tests {
         }
 
         // Should have seen all spinner chars
-        assert_eq!(seen.len(), SPINNER_CHARS.len());
+        assert_eq!(seen.len(), SpinnerStyle::default().frames().len());
+    }
+
+    #[test]
+    fn test_tick_cycles_each_style() {
+        let styles = [
+            SpinnerStyle::Dots,
+            SpinnerStyle::Line,
+            SpinnerStyle::Braille,
+            SpinnerStyle::Arc,
+            SpinnerStyle::Bounce,
+        ];
+        for style in styles {
+            let mut pb = ProgressBar::with_style(style);
+            pb.start_ref("make");
+
+            let frames = style.frames();
+            let mut shown = Vec::new();
+            for _ in 0..frames.len() * 2 {
+                shown.push(pb.spinner_char());
+                pb.tick();
+            }
+            // Two full passes through the style's frames, in order
+            let expected: Vec<char> = frames.iter().chain(frames.iter()).copied().collect();
+            assert_eq!(shown, expected, "{style:?}");
+            assert!(pb
+                .display_text_truncated(20)
+                .starts_with(&format!("{} Running: make", frames[0])));
+        }
+    }
+
+    #[test]
+    fn test_spinner_style_from_name() {
+        assert_eq!(SpinnerStyle::from_name("line"), SpinnerStyle::Line);
+        assert_eq!(SpinnerStyle::from_name("braille"), SpinnerStyle::Braille);
+        assert_eq!(SpinnerStyle::from_name("arc"), SpinnerStyle::Arc);
+        assert_eq!(SpinnerStyle::from_name("bounce"), SpinnerStyle::Bounce);
+        assert_eq!(SpinnerStyle::from_name("dots"), SpinnerStyle::Dots);
+        assert_eq!(SpinnerStyle::from_name("pinwheel"), SpinnerStyle::Dots);
     }
 
     #[test]
@@ -332,6 +564,115 @@ mod tests {
         assert_eq!(format_duration_secs(7261), "2h 1m");
     }
 
+    #[test]
+    fn test_estimate_remaining() {
+        let elapsed = Duration::from_secs(30);
+        assert_eq!(
+            estimate_remaining(25.0, elapsed),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            estimate_remaining(50.0, elapsed),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            estimate_remaining(75.0, elapsed),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(estimate_remaining(100.0, elapsed), Some(Duration::ZERO));
+        // Nothing to extrapolate from yet
+        assert_eq!(estimate_remaining(0.0, elapsed), None);
+        assert_eq!(estimate_remaining(f32::NAN, elapsed), None);
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(parse_percent("Downloading... 42%"), Some(42.0));
+        assert_eq!(parse_percent("[=====>    ] 12.5 % done"), Some(12.5));
+        // The most recent value in a chunk wins
+        assert_eq!(parse_percent("10%\r20%\r30%"), Some(30.0));
+        assert_eq!(parse_percent("cpu 250%"), None);
+        assert_eq!(parse_percent("100% complete"), Some(100.0));
+        assert_eq!(parse_percent("no progress here"), None);
+        assert_eq!(parse_percent("%"), None);
+    }
+
+    #[test]
+    fn test_update_percent_shows_bar() {
+        let mut pb = ProgressBar::new();
+        pb.update_percent(50.0);
+        // Ignored until a command is running
+        assert_eq!(pb.percent(), None);
+
+        pb.start("cargo build".to_string());
+        assert!(pb.eta().is_none());
+        pb.update_percent(40.0);
+        assert_eq!(pb.percent(), Some(40.0));
+        assert!(pb.eta().is_some());
+        let text = pb.display_text_truncated(100);
+        assert!(text.contains("cargo build [####------] 40% ETA "), "{text}");
+
+        pb.update_percent(140.0);
+        assert_eq!(pb.percent(), Some(100.0));
+
+        pb.stop();
+        assert_eq!(pb.percent(), None);
+    }
+
+    #[test]
+    fn test_format_clock_secs() {
+        assert_eq!(format_clock_secs(0), "0:00");
+        assert_eq!(format_clock_secs(3), "0:03");
+        assert_eq!(format_clock_secs(75), "1:15");
+        assert_eq!(format_clock_secs(3599), "59:59");
+        assert_eq!(format_clock_secs(3723), "1:02:03");
+    }
+
+    #[test]
+    fn test_display_shows_elapsed_clock() {
+        let mut pb = ProgressBar::new();
+        pb.start_ref("sleep 1");
+        assert!(pb.display_text().ends_with("sleep 1 [0:00]"));
+    }
+
+    #[test]
+    fn test_complete_then_hidden() {
+        let mut pb = ProgressBar::new();
+        pb.start_ref("cargo test");
+        let start = pb.start_time.unwrap();
+
+        pb.complete_at(start + Duration::from_secs(65));
+        assert!(pb.visible);
+        assert!(pb.is_done());
+        assert_eq!(pb.display_text(), "Done: cargo test [1:05]");
+
+        // Later prompts don't restart the done timer or change the total
+        pb.complete_at(start + Duration::from_secs(66));
+        pb.tick_at(start + Duration::from_secs(66));
+        assert_eq!(pb.display_text(), "Done: cargo test [1:05]");
+
+        pb.tick_at(start + Duration::from_secs(65) + DONE_DISPLAY_DURATION);
+        assert!(!pb.visible);
+        assert!(!pb.is_done());
+        assert!(pb.display_text().is_empty());
+    }
+
+    #[test]
+    fn test_complete_under_a_second() {
+        let mut pb = ProgressBar::new();
+        pb.start_ref("true");
+        let start = pb.start_time.unwrap();
+
+        pb.complete_at(start + Duration::from_millis(300));
+        assert_eq!(pb.display_text(), "Done: true [0:00]");
+
+        // Completing without a running command is a no-op
+        let mut idle = ProgressBar::new();
+        idle.complete();
+        assert!(!idle.visible);
+        assert!(!idle.is_done());
+    }
+
     #[test]
     fn test_tick_when_not_visible() {
         let mut pb = ProgressBar::new();
@@ -1,10 +1,24 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::colors::{TrueColor, TrueColorPalette};
+use crate::terminal::ansi_parser::AnsiParser;
+
+/// Id of the single slot that periodic crash-recovery checkpoints overwrite
+pub const AUTOSAVE_ID: &str = "autosave";
+
+/// Name shown for the crash-recovery checkpoint
+pub const AUTOSAVE_NAME: &str = "Autosave";
 
 /// Session manager for saving and restoring terminal sessions
+#[derive(Clone)]
 pub struct SessionManager {
     sessions_dir: PathBuf,
 }
@@ -17,11 +31,62 @@ pub struct SavedSession {
     pub tabs: Vec<TabState>,
 }
 
+/// Identifying details of a saved session, without its tab contents
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Local>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TabState {
     pub output: String,
     pub working_dir: Option<String>,
     pub active: bool,
+    /// Lines scrolled back from the bottom; 0 follows the latest output.
+    /// Missing in session files saved before scroll positions were kept.
+    #[serde(default)]
+    pub scroll_offset: usize,
+}
+
+/// Decides when the next periodic autosave is due
+#[derive(Debug, Clone)]
+pub struct AutosaveTimer {
+    interval: Option<Duration>,
+    last_save: Instant,
+}
+
+impl AutosaveTimer {
+    /// Create a timer firing every `interval_secs` seconds from `now`; 0 disables it
+    #[must_use]
+    pub fn new(interval_secs: u64, now: Instant) -> Self {
+        Self {
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+            last_save: now,
+        }
+    }
+
+    /// Check whether a save is due at `now`, restarting the interval if it is
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.interval {
+            Some(interval) if now.saturating_duration_since(self.last_save) >= interval => {
+                self.last_save = now;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// File format for exported session transcripts
+#[allow(dead_code)] // Public API for future use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Output text with all escape sequences removed
+    PlainText,
+    /// Standalone HTML page with colors and text attributes kept as inline styles
+    Html,
 }
 
 impl SessionManager {
@@ -32,7 +97,15 @@ impl SessionManager {
     pub fn new() -> Result<Self> {
         let home = dirs::home_dir().context("Failed to get home directory")?;
 
-        let sessions_dir = home.join(".furnace").join("sessions");
+        Self::with_dir(home.join(".furnace").join("sessions"))
+    }
+
+    /// Create a session manager that stores sessions in `sessions_dir`
+    ///
+    /// # Errors
+    /// Returns an error if the directory cannot be created
+    pub fn with_dir(sessions_dir: impl Into<PathBuf>) -> Result<Self> {
+        let sessions_dir = sessions_dir.into();
         fs::create_dir_all(&sessions_dir).context("Failed to create sessions directory")?;
 
         Ok(Self { sessions_dir })
@@ -70,11 +143,13 @@ impl SessionManager {
         Ok(session)
     }
 
-    /// List all saved sessions
+    /// List the names and ids of all saved sessions
+    ///
+    /// Only the summary fields are deserialized, so tab output is skipped.
     ///
     /// # Errors
     /// Returns an error if the sessions directory cannot be read
-    pub fn list_sessions(&self) -> Result<Vec<SavedSession>> {
+    pub fn list_sessions(&self) -> Result<Vec<SessionSummary>> {
         let mut sessions = Vec::new();
 
         for entry in fs::read_dir(&self.sessions_dir)? {
@@ -83,7 +158,7 @@ impl SessionManager {
 
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
                 if let Ok(json) = fs::read_to_string(&path) {
-                    if let Ok(session) = serde_json::from_str::<SavedSession>(&json) {
+                    if let Ok(session) = serde_json::from_str::<SessionSummary>(&json) {
                         sessions.push(session);
                     }
                 }
@@ -96,6 +171,54 @@ impl SessionManager {
         Ok(sessions)
     }
 
+    /// Find the most recent session saved under `name`
+    ///
+    /// # Errors
+    /// Returns an error if the sessions directory cannot be read
+    pub fn find_by_name(&self, name: &str) -> Result<Option<SessionSummary>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .find(|session| session.name == name))
+    }
+
+    /// Load the most recent session saved under `name`
+    ///
+    /// # Errors
+    /// Returns an error if no session has that name or it cannot be loaded
+    #[allow(dead_code)] // Public API for future use
+    pub fn load_by_name(&self, name: &str) -> Result<SavedSession> {
+        let summary = self
+            .find_by_name(name)?
+            .with_context(|| format!("No saved session named \"{name}\""))?;
+        self.load_session(&summary.id)
+    }
+
+    /// Export a session's tab output to `path` as a transcript
+    ///
+    /// Tabs are written in order; when there is more than one, each is
+    /// headed with its number and working directory.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    #[allow(dead_code)] // Public API for future use
+    pub fn export(
+        &self,
+        session: &SavedSession,
+        format: ExportFormat,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let palette = TrueColorPalette::default_dark();
+        let contents = match format {
+            ExportFormat::PlainText => export_plain_text(session, &palette),
+            ExportFormat::Html => export_html(session, &palette),
+        };
+
+        fs::write(path.as_ref(), contents).context("Failed to write session export")?;
+
+        Ok(())
+    }
+
     /// Delete a session
     ///
     /// # Errors
@@ -132,6 +255,198 @@ impl Default for SessionManager {
     }
 }
 
+/// Heading for a tab in an export, or `None` for single-tab sessions
+fn tab_heading(session: &SavedSession, index: usize) -> Option<String> {
+    if session.tabs.len() < 2 {
+        return None;
+    }
+    let tab = &session.tabs[index];
+    Some(match &tab.working_dir {
+        Some(dir) => format!("Tab {} ({dir})", index + 1),
+        None => format!("Tab {}", index + 1),
+    })
+}
+
+/// Parse a tab's output into styled lines, without trailing blank lines
+fn transcript_lines(output: &str, palette: &TrueColorPalette) -> Vec<Line<'static>> {
+    let mut lines = AnsiParser::parse_with_palette(output, palette);
+    while lines
+        .last()
+        .is_some_and(|line| line.spans.iter().all(|span| span.content.trim().is_empty()))
+    {
+        lines.pop();
+    }
+    lines
+}
+
+/// Render every tab as plain text, dropping escape sequences
+fn export_plain_text(session: &SavedSession, palette: &TrueColorPalette) -> String {
+    let mut out = String::new();
+    for (index, tab) in session.tabs.iter().enumerate() {
+        if let Some(heading) = tab_heading(session, index) {
+            if index > 0 {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "== {heading} ==");
+        }
+        for line in transcript_lines(&tab.output, palette) {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Render every tab as a standalone HTML page, using the palette's black and white as page colors
+fn export_html(session: &SavedSession, palette: &TrueColorPalette) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>",
+        escape_html(&session.name)
+    );
+    let _ = writeln!(
+        out,
+        "<body style=\"background-color:{};color:{}\">",
+        palette.black.to_hex(),
+        palette.white.to_hex()
+    );
+
+    for (index, tab) in session.tabs.iter().enumerate() {
+        if let Some(heading) = tab_heading(session, index) {
+            let _ = writeln!(out, "<h2>{}</h2>", escape_html(&heading));
+        }
+        out.push_str("<pre>");
+        for line in transcript_lines(&tab.output, palette) {
+            push_html_line(&mut out, &line, palette);
+            out.push('\n');
+        }
+        out.push_str("</pre>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Render styled lines as an HTML `<pre>` block, e.g. for pasting into rich text editors
+///
+/// The block uses the palette's black and white as its colors, like [`SessionManager::export`].
+#[must_use]
+pub fn lines_to_html(lines: &[Line<'_>], palette: &TrueColorPalette) -> String {
+    let mut out = format!(
+        "<pre style=\"background-color:{};color:{}\">",
+        palette.black.to_hex(),
+        palette.white.to_hex()
+    );
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        push_html_line(&mut out, line, palette);
+    }
+    out.push_str("</pre>");
+    out
+}
+
+/// Append one styled line, wrapping styled runs in `<span style="...">`
+fn push_html_line(out: &mut String, line: &Line<'_>, palette: &TrueColorPalette) {
+    for span in &line.spans {
+        let text = escape_html(&span.content);
+        let css = css_for_style(span.style, palette);
+        if css.is_empty() {
+            out.push_str(&text);
+        } else {
+            let _ = write!(out, "<span style=\"{css}\">{text}</span>");
+        }
+    }
+}
+
+/// Inline CSS for a span style; empty when the style uses the page defaults
+fn css_for_style(style: Style, palette: &TrueColorPalette) -> String {
+    let mut css = Vec::new();
+    let (mut fg, mut bg) = (style.fg, style.bg);
+    if style.add_modifier.contains(Modifier::REVERSED) {
+        std::mem::swap(&mut fg, &mut bg);
+        // Reset colors swap to the page defaults
+        fg = fg.filter(|c| *c != Color::Reset).or(Some(Color::Black));
+        bg = bg.filter(|c| *c != Color::Reset).or(Some(Color::White));
+    }
+
+    if let Some(color) = fg.and_then(|c| css_color(c, palette)) {
+        css.push(format!("color:{color}"));
+    }
+    if let Some(color) = bg.and_then(|c| css_color(c, palette)) {
+        css.push(format!("background-color:{color}"));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        css.push("font-weight:bold".to_string());
+    }
+    if style.add_modifier.contains(Modifier::DIM) {
+        css.push("opacity:0.6".to_string());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        css.push("font-style:italic".to_string());
+    }
+    let decorations: Vec<&str> = [
+        (Modifier::UNDERLINED, "underline"),
+        (Modifier::CROSSED_OUT, "line-through"),
+    ]
+    .iter()
+    .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+    .map(|(_, decoration)| *decoration)
+    .collect();
+    if !decorations.is_empty() {
+        css.push(format!("text-decoration:{}", decorations.join(" ")));
+    }
+
+    css.join(";")
+}
+
+/// CSS hex color for a parsed color, resolving palette indices
+fn css_color(color: Color, palette: &TrueColorPalette) -> Option<String> {
+    let true_color = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => TrueColor::new(r, g, b),
+        Color::Indexed(index) => palette.get_256(index),
+        Color::Black => palette.black,
+        Color::Red => palette.red,
+        Color::Green => palette.green,
+        Color::Yellow => palette.yellow,
+        Color::Blue => palette.blue,
+        Color::Magenta => palette.magenta,
+        Color::Cyan => palette.cyan,
+        Color::Gray | Color::White => palette.white,
+        Color::DarkGray => palette.bright_black,
+        Color::LightRed => palette.bright_red,
+        Color::LightGreen => palette.bright_green,
+        Color::LightYellow => palette.bright_yellow,
+        Color::LightBlue => palette.bright_blue,
+        Color::LightMagenta => palette.bright_magenta,
+        Color::LightCyan => palette.bright_cyan,
+    };
+    Some(true_color.to_hex())
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,6 +469,7 @@ mod tests {
                 output: "test output".to_string(),
                 working_dir: Some("/home/user".to_string()),
                 active: true,
+                scroll_offset: 0,
             }],
         };
 
@@ -181,6 +497,7 @@ mod tests {
                 output: "test".to_string(),
                 working_dir: None,
                 active: true,
+                scroll_offset: 0,
             }],
         };
 
@@ -238,16 +555,19 @@ mod tests {
                     output: "tab1 output".to_string(),
                     working_dir: Some("/home/user".to_string()),
                     active: false,
+                    scroll_offset: 0,
                 },
                 TabState {
                     output: "tab2 output".to_string(),
                     working_dir: Some("/tmp".to_string()),
                     active: true,
+                    scroll_offset: 0,
                 },
                 TabState {
                     output: "tab3 output".to_string(),
                     working_dir: None,
                     active: false,
+                    scroll_offset: 0,
                 },
             ],
         };
@@ -264,6 +584,182 @@ mod tests {
         manager.delete_session("multi-tab-test").ok();
     }
 
+    #[test]
+    fn test_scroll_offset_round_trip() {
+        let session = SavedSession {
+            id: "scroll-test".to_string(),
+            name: "Scroll Test".to_string(),
+            created_at: Local::now(),
+            tabs: vec![TabState {
+                output: "line\n".repeat(100),
+                working_dir: Some("/home/user".to_string()),
+                active: true,
+                scroll_offset: 42,
+            }],
+        };
+
+        let json = serde_json::to_string(&session).unwrap();
+        let loaded: SavedSession = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.tabs[0].scroll_offset, 42);
+        assert_eq!(loaded.tabs[0].working_dir, Some("/home/user".to_string()));
+    }
+
+    #[test]
+    fn test_old_session_without_scroll_offset() {
+        let json = r#"{
+            "id": "old",
+            "name": "Old Session",
+            "created_at": "2024-01-01T12:00:00+00:00",
+            "tabs": [{ "output": "hi", "working_dir": null, "active": true }]
+        }"#;
+
+        let loaded: SavedSession = serde_json::from_str(json).unwrap();
+        assert_eq!(loaded.tabs[0].scroll_offset, 0);
+    }
+
+    fn named_session(id: &str, name: &str, output: &str) -> SavedSession {
+        SavedSession {
+            id: id.to_string(),
+            name: name.to_string(),
+            created_at: Local::now(),
+            tabs: vec![TabState {
+                output: output.to_string(),
+                working_dir: None,
+                active: true,
+                scroll_offset: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_list_sessions_returns_names_and_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::with_dir(dir.path()).unwrap();
+
+        manager
+            .save_session(&named_session("a", "build", "1"))
+            .unwrap();
+        manager
+            .save_session(&named_session("b", "deploy", "2"))
+            .unwrap();
+        // Files that aren't sessions are skipped
+        fs::write(dir.path().join("notes.json"), "{}").unwrap();
+
+        let mut names: Vec<(String, String)> = manager
+            .list_sessions()
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+        names.sort();
+
+        assert_eq!(
+            names,
+            vec![
+                ("a".to_string(), "build".to_string()),
+                ("b".to_string(), "deploy".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::with_dir(dir.path()).unwrap();
+
+        manager
+            .save_session(&named_session("a", "build", "cargo build"))
+            .unwrap();
+        manager
+            .save_session(&named_session("b", "deploy", "ssh prod"))
+            .unwrap();
+
+        let loaded = manager.load_by_name("deploy").unwrap();
+        assert_eq!(loaded.id, "b");
+        assert_eq!(loaded.tabs[0].output, "ssh prod");
+
+        assert!(manager.load_by_name("missing").is_err());
+        assert!(manager.find_by_name("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_overwrite_named_session_keeps_one_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::with_dir(dir.path()).unwrap();
+
+        manager
+            .save_session(&named_session("a", "build", "old"))
+            .unwrap();
+        let existing = manager.find_by_name("build").unwrap().unwrap();
+        manager
+            .save_session(&named_session(&existing.id, "build", "new"))
+            .unwrap();
+
+        assert_eq!(manager.list_sessions().unwrap().len(), 1);
+        assert_eq!(manager.load_by_name("build").unwrap().tabs[0].output, "new");
+    }
+
+    #[test]
+    fn test_export_html_colors() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::with_dir(dir.path()).unwrap();
+        let session = named_session(
+            "a",
+            "build",
+            "\x1b[31merror\x1b[0m: <bad>\n\x1b[1;38;2;18;52;86mtrue\x1b[0m plain\n",
+        );
+        let path = dir.path().join("build.html");
+
+        manager.export(&session, ExportFormat::Html, &path).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+
+        let red = TrueColorPalette::default_dark().red.to_hex();
+        assert!(html.contains(&format!(
+            "<span style=\"color:{red}\">error</span>: &lt;bad&gt;"
+        )));
+        assert!(html.contains("<span style=\"color:#123456;font-weight:bold\">true</span> plain"));
+        assert!(!html.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_export_plain_text_strips_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::with_dir(dir.path()).unwrap();
+        let mut session = named_session("a", "build", "\x1b[32mok\x1b[0m done\n");
+        session.tabs.push(TabState {
+            output: "\x1b]0;title\x07second\n".to_string(),
+            working_dir: Some("/tmp".to_string()),
+            active: false,
+            scroll_offset: 0,
+        });
+        let path = dir.path().join("build.txt");
+
+        manager
+            .export(&session, ExportFormat::PlainText, &path)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "== Tab 1 ==\nok done\n\n== Tab 2 (/tmp) ==\nsecond\n"
+        );
+    }
+
+    #[test]
+    fn test_autosave_timer() {
+        let start = Instant::now();
+        let mut timer = AutosaveTimer::new(30, start);
+
+        assert!(!timer.poll(start + Duration::from_secs(29)));
+        assert!(timer.poll(start + Duration::from_secs(30)));
+        // The interval restarts from the last save
+        assert!(!timer.poll(start + Duration::from_secs(45)));
+        assert!(timer.poll(start + Duration::from_secs(61)));
+
+        let mut disabled = AutosaveTimer::new(0, start);
+        assert!(!disabled.poll(start + Duration::from_secs(3600)));
+    }
+
     #[test]
     fn test_load_nonexistent_session() {
         let manager = SessionManager::new().unwrap();
@@ -283,6 +779,7 @@ mod tests {
                 output: "output with\nnewlines\tand\ttabs".to_string(),
                 working_dir: Some("/path/with spaces/and'quotes".to_string()),
                 active: true,
+                scroll_offset: 0,
             }],
         };
 
@@ -1,16 +1,40 @@
 use anyhow::{Context, Result};
-use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use portable_pty::{Child, CommandBuilder, ExitStatus, NativePtySystem, PtySize, PtySystem};
 use std::io::{Read, Write};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::Mutex;
 use tracing::{debug, info};
 
+mod signal;
+mod utf8;
+
+pub use self::signal::Signal;
+pub use self::utf8::Utf8Decoder;
+
+/// Id of the next shell started
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
 /// High-performance shell session with zero-copy I/O where possible
 #[derive(Clone)]
 pub struct ShellSession {
+    /// Unique to this shell; clones share it and a respawned shell gets a new one
+    id: u64,
     pty: Arc<Mutex<Box<dyn portable_pty::MasterPty + Send>>>,
     reader: Arc<Mutex<Box<dyn Read + Send>>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    /// Process id of the shell, if the platform reports one
+    pid: Option<u32>,
+    /// The shell process, polled by `try_wait`
+    child: Arc<StdMutex<Box<dyn Child + Send + Sync>>>,
+    /// Command, working directory and environment the shell was started with,
+    /// reused by `respawn`
+    shell_cmd: String,
+    working_dir: Option<String>,
+    env_vars: Vec<(String, String)>,
+    /// Output decoder, shared so a character split between reads survives a
+    /// new reader taking over
+    decoder: Arc<StdMutex<Utf8Decoder>>,
 }
 
 impl ShellSession {
@@ -65,10 +89,11 @@ impl ShellSession {
             cmd.env(key, value);
         }
 
-        let _child = pair
+        let child = pair
             .slave
             .spawn_command(cmd)
             .context("Failed to spawn shell")?;
+        let pid = child.process_id();
 
         info!("Shell session started: {}", shell_cmd);
         debug!("PTY size: {}x{}", rows, cols);
@@ -80,12 +105,150 @@ impl ShellSession {
         let writer = pair.master.take_writer().context("Failed to take writer")?;
 
         Ok(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             pty: Arc::new(Mutex::new(pair.master)),
             reader: Arc::new(Mutex::new(reader)),
             writer: Arc::new(Mutex::new(writer)),
+            pid,
+            child: Arc::new(StdMutex::new(child)),
+            shell_cmd: shell_cmd.to_string(),
+            working_dir: working_dir.map(str::to_string),
+            env_vars: env_vars
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+            decoder: Arc::new(StdMutex::new(Utf8Decoder::new())),
         })
     }
 
+    /// Replace the shell with a fresh one in a new PTY
+    ///
+    /// The new shell runs the same command with the same working directory,
+    /// environment and PTY size. A shell that is still running is killed once
+    /// its replacement has started; if starting it fails the session is left
+    /// untouched. Clones of the session keep the old PTY.
+    ///
+    /// # Errors
+    /// Returns an error if the PTY size cannot be read, the new PTY cannot be
+    /// opened or the shell cannot be spawned
+    pub async fn respawn(&mut self) -> Result<()> {
+        let size = self
+            .pty
+            .lock()
+            .await
+            .get_size()
+            .context("Failed to read PTY size")?;
+        let env_vars: Vec<(&str, &str)> = self
+            .env_vars
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let fresh = Self::new_with_env(
+            &self.shell_cmd,
+            self.working_dir.as_deref(),
+            size.rows,
+            size.cols,
+            &env_vars,
+        )?;
+
+        if self.try_wait().is_none() {
+            if let Ok(mut child) = self.child.lock() {
+                if let Err(e) = child.kill() {
+                    debug!("Failed to kill shell before respawning: {}", e);
+                }
+                let _ = child.wait();
+            }
+        }
+
+        info!("Respawned shell {:?} as {:?}", self.pid, fresh.pid);
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Id telling this shell apart from every other one started
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Process id of the shell, if the platform reports one
+    #[must_use]
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Process id of the program in the foreground
+    ///
+    /// On Unix this leads the terminal's foreground process group, which is
+    /// the shell itself while it waits at a prompt. Elsewhere, or while the
+    /// PTY is busy, it is the shell. `None` if the shell's id is unknown.
+    #[must_use]
+    pub fn foreground_pid(&self) -> Option<u32> {
+        #[cfg(unix)]
+        {
+            // Don't wait for a PTY another task holds; the shell will do
+            let group = self
+                .pty
+                .try_lock()
+                .ok()
+                .and_then(|pty| pty.process_group_leader())
+                .and_then(|group| u32::try_from(group).ok())
+                .filter(|&group| group > 0);
+            if group.is_some() {
+                return group;
+            }
+        }
+        self.pid
+    }
+
+    /// Exit status of the shell, or `None` while it is still running
+    ///
+    /// Never blocks, so it can be polled every frame. Once the shell has
+    /// exited every call returns its status.
+    #[must_use]
+    pub fn try_wait(&self) -> Option<ExitStatus> {
+        let mut child = self.child.lock().ok()?;
+        match child.try_wait() {
+            Ok(status) => status,
+            Err(e) => {
+                debug!("Failed to poll shell status: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Send `signal` to the program running in the shell
+    ///
+    /// On Unix this is the foreground job, falling back to the shell itself.
+    /// Windows stands in for signals as described in [`Signal`]'s module. A
+    /// program that has already exited is not an error.
+    ///
+    /// # Errors
+    /// Returns an error if the shell's process id is unknown or the signal
+    /// cannot be delivered
+    pub async fn send_signal(&self, signal: Signal) -> Result<()> {
+        let pid = self.pid.context("Shell process id is unknown")?;
+
+        #[cfg(unix)]
+        {
+            let group = self.pty.lock().await.process_group_leader();
+            signal::kill(pid, group, signal)?;
+        }
+
+        #[cfg(windows)]
+        match signal.windows_call() {
+            signal::WindowsCall::CtrlC => {
+                self.write_input(&[signal::ETX]).await?;
+            }
+            signal::WindowsCall::TerminateProcess(exit_code) => {
+                signal::terminate(pid, exit_code)?;
+            }
+        }
+
+        debug!("Sent {:?} to shell {}", signal, pid);
+        Ok(())
+    }
+
     /// Read output from shell (non-blocking, high-performance)
     ///
     /// This method uses `spawn_blocking` to avoid blocking the async runtime during
@@ -122,6 +285,18 @@ impl ShellSession {
         Ok(n)
     }
 
+    /// Decode a read from [`Self::read_output`] into complete characters
+    ///
+    /// A character split by the end of the read is held back and completed by
+    /// the next call, so it isn't mangled into replacement characters.
+    #[must_use]
+    pub fn decode_output(&self, bytes: &[u8]) -> String {
+        self.decoder
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .decode(bytes)
+    }
+
     /// Write input to shell with minimal latency
     ///
     /// This function writes data to the shell and immediately flushes to ensure
@@ -214,4 +389,117 @@ mod tests {
         let result = ShellSession::new(shell, None, 24, 80);
         assert!(result.is_ok(), "Failed to create shell with new() method");
     }
+
+    #[tokio::test]
+    async fn test_decode_output_carries_split_character_across_clones() {
+        let shell = if cfg!(windows) { "cmd.exe" } else { "sh" };
+        let session = ShellSession::new(shell, None, 24, 80).unwrap();
+        let reader = session.clone();
+
+        // "é" is C3 A9, split between two reads by different readers
+        assert_eq!(reader.decode_output(b"caf\xC3"), "caf");
+        assert_eq!(session.decode_output(b"\xA9!"), "é!");
+    }
+
+    #[tokio::test]
+    async fn test_shell_reports_pid() {
+        let shell = if cfg!(windows) { "cmd.exe" } else { "sh" };
+
+        let session = ShellSession::new(shell, None, 24, 80).unwrap();
+        assert!(session.pid().is_some_and(|pid| pid != std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_foreground_pid_follows_running_program() {
+        let session = ShellSession::new("sh", None, 24, 80).unwrap();
+        let shell = session.pid();
+
+        // Poll until the foreground changes as expected
+        async fn wait_until(session: &ShellSession, done: impl Fn(Option<u32>) -> bool) {
+            for _ in 0..250 {
+                if done(session.foreground_pid()) {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            panic!("foreground did not change");
+        }
+
+        // Waiting at the prompt, the shell is in the foreground
+        wait_until(&session, |pid| pid == shell).await;
+        session.write_input(b"sleep 5\n").await.unwrap();
+        wait_until(&session, |pid| pid.is_some() && pid != shell).await;
+        session.send_signal(Signal::Kill).await.unwrap();
+    }
+
+    /// Have the shell exit with `code` and wait for it to do so
+    async fn exit_shell(session: &ShellSession, code: u32) -> ExitStatus {
+        let exit = if cfg!(windows) {
+            format!("exit {code}\r\n")
+        } else {
+            format!("exit {code}\n")
+        };
+        session.write_input(exit.as_bytes()).await.unwrap();
+        for _ in 0..100 {
+            if let Some(status) = session.try_wait() {
+                return status;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("shell did not exit");
+    }
+
+    #[tokio::test]
+    async fn test_try_wait_reports_exit() {
+        let shell = if cfg!(windows) { "cmd.exe" } else { "sh" };
+
+        let session = ShellSession::new(shell, None, 24, 80).unwrap();
+        assert!(session.try_wait().is_none());
+
+        let status = exit_shell(&session, 3).await;
+        assert_eq!(status.exit_code(), 3);
+        assert!(!status.success());
+        // Still reported after the first time
+        assert_eq!(session.try_wait().map(|s| s.exit_code()), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_respawn_replaces_exited_shell() {
+        let shell = if cfg!(windows) { "cmd.exe" } else { "sh" };
+
+        let mut session = ShellSession::new(shell, None, 24, 80).unwrap();
+        session.resize(30, 100).await.unwrap();
+        let old_pid = session.pid().unwrap();
+        exit_shell(&session, 1).await;
+
+        session.respawn().await.unwrap();
+        assert!(session.try_wait().is_none());
+        assert_ne!(session.pid(), Some(old_pid));
+        let size = session.pty.lock().await.get_size().unwrap();
+        assert_eq!((size.rows, size.cols), (30, 100));
+        session.write_input(b"echo alive\n").await.unwrap();
+
+        // The old shell has been reaped
+        #[cfg(unix)]
+        {
+            let old_pid = libc::pid_t::try_from(old_pid).unwrap();
+            // SAFETY: signal 0 only checks whether the process exists
+            assert_eq!(unsafe { libc::kill(old_pid, 0) }, -1);
+            assert_eq!(
+                std::io::Error::last_os_error().raw_os_error(),
+                Some(libc::ESRCH)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_idle_shell() {
+        let shell = if cfg!(windows) { "cmd.exe" } else { "sh" };
+
+        // With nothing running the shell itself gets the interrupt and carries on
+        let session = ShellSession::new(shell, None, 24, 80).unwrap();
+        session.send_signal(Signal::Interrupt).await.unwrap();
+        session.write_input(b"echo still here\n").await.unwrap();
+    }
 }
@@ -0,0 +1,167 @@
+//! Signals sent to the programs running in a shell session
+//!
+//! On Unix a [`Signal`] is sent with `kill(2)` to the terminal's foreground
+//! process group, the same target the tty driver picks for Ctrl+C.
+//!
+//! Windows has no signals. `GenerateConsoleCtrlEvent` only reaches processes on
+//! the caller's own console, not those attached to a pseudo console, so an
+//! interrupt is typed into the pseudo console as ETX (Ctrl+C) instead, which
+//! raises `CTRL_C_EVENT` there. Terminating or killing ends the shell with
+//! `TerminateProcess`.
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+/// Signal that can be sent to the programs running in a session
+#[allow(dead_code)] // Public API for future use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ask the foreground program to stop, like Ctrl+C (`SIGINT`)
+    Interrupt,
+    /// Ask it to exit (`SIGTERM`)
+    Terminate,
+    /// End it immediately (`SIGKILL`)
+    Kill,
+}
+
+/// Byte the pseudo console turns into `CTRL_C_EVENT`
+#[cfg(any(windows, test))]
+pub(super) const ETX: u8 = 0x03;
+
+/// What Windows does for a [`Signal`]
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WindowsCall {
+    /// Write [`ETX`] to the pseudo console
+    CtrlC,
+    /// `TerminateProcess` with this exit code
+    TerminateProcess(u32),
+}
+
+impl Signal {
+    /// Signal number for `kill(2)`
+    #[cfg(unix)]
+    fn unix_number(self) -> libc::c_int {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Kill => libc::SIGKILL,
+        }
+    }
+
+    /// The Windows call standing in for this signal
+    ///
+    /// Exit codes follow the shells' `128 + signal` convention.
+    #[cfg(any(windows, test))]
+    pub(super) fn windows_call(self) -> WindowsCall {
+        match self {
+            Self::Interrupt => WindowsCall::CtrlC,
+            Self::Terminate => WindowsCall::TerminateProcess(128 + 15),
+            Self::Kill => WindowsCall::TerminateProcess(128 + 9),
+        }
+    }
+}
+
+/// Send `signal` to process group `group`, or to `pid` when there is none
+///
+/// A process that has already exited is not an error.
+///
+/// # Errors
+/// Returns an error if the signal cannot be delivered, e.g. for lack of permission
+#[cfg(unix)]
+pub(super) fn kill(pid: u32, group: Option<libc::pid_t>, signal: Signal) -> Result<()> {
+    let target = match group.filter(|&group| group > 0) {
+        // A negative pid addresses the whole group
+        Some(group) => -group,
+        None => libc::pid_t::try_from(pid).context("Process id out of range")?,
+    };
+
+    // SAFETY: kill(2) only takes integers and has no memory-safety preconditions
+    if unsafe { libc::kill(target, signal.unix_number()) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        debug!("Process {target} already exited; not sending {signal:?}");
+        return Ok(());
+    }
+    Err(err).with_context(|| format!("Failed to send {signal:?} to process {target}"))
+}
+
+/// End process `pid` with `exit_code`
+///
+/// A process that has already exited is not an error.
+///
+/// # Errors
+/// Returns an error if the process cannot be opened or terminated
+#[cfg(windows)]
+pub(super) fn terminate(pid: u32, exit_code: u32) -> Result<()> {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_INVALID_PARAMETER};
+    use windows::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    // SAFETY: OpenProcess only takes integers; the handle is closed below
+    let process = match unsafe { OpenProcess(PROCESS_TERMINATE, false, pid) } {
+        Ok(process) => process,
+        // There is no process with this id any more
+        Err(e) if e.code() == ERROR_INVALID_PARAMETER.to_hresult() => {
+            debug!("Process {} already exited; not terminating it", pid);
+            return Ok(());
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to open process {pid}")),
+    };
+
+    // SAFETY: `process` is a live handle opened with PROCESS_TERMINATE
+    let result = unsafe { TerminateProcess(process, exit_code) };
+    // SAFETY: the handle is closed exactly once
+    let _ = unsafe { CloseHandle(process) };
+    result.with_context(|| format!("Failed to terminate process {pid}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_calls() {
+        assert_eq!(Signal::Interrupt.windows_call(), WindowsCall::CtrlC);
+        assert_eq!(
+            Signal::Terminate.windows_call(),
+            WindowsCall::TerminateProcess(143)
+        );
+        assert_eq!(
+            Signal::Kill.windows_call(),
+            WindowsCall::TerminateProcess(137)
+        );
+        assert_eq!(ETX, b'C' - b'@');
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_numbers() {
+        assert_eq!(Signal::Interrupt.unix_number(), libc::SIGINT);
+        assert_eq!(Signal::Terminate.unix_number(), libc::SIGTERM);
+        assert_eq!(Signal::Kill.unix_number(), libc::SIGKILL);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_delivers_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        kill(child.id(), None, Signal::Terminate).unwrap();
+        assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_exited_process_is_ok() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(kill(pid, None, Signal::Kill).is_ok());
+    }
+}
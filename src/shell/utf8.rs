@@ -0,0 +1,120 @@
+//! UTF-8 decoding of shell output across reads
+//!
+//! A PTY read ends wherever the buffer fills, often partway through a
+//! multi-byte character. Decoding each read on its own turns both halves into
+//! replacement characters. A [`Utf8Decoder`] holds back the start of a
+//! character cut off at the end of a read and finishes it with the next one.
+
+/// Replacement for bytes that can never be valid UTF-8
+const REPLACEMENT: char = '\u{FFFD}';
+
+/// Decoder that carries a partial character from one read to the next
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    /// Start of a character cut off by the end of the last read, at most 3 bytes
+    partial: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Create a decoder with nothing held back
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the next read, returning the complete characters in it
+    ///
+    /// A character cut off at the end is held back for the next call; invalid
+    /// bytes become U+FFFD, as with [`String::from_utf8_lossy`].
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        let joined;
+        let mut rest = if self.partial.is_empty() {
+            bytes
+        } else {
+            self.partial.extend_from_slice(bytes);
+            joined = std::mem::take(&mut self.partial);
+            joined.as_slice()
+        };
+
+        let mut text = String::with_capacity(rest.len());
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(&String::from_utf8_lossy(valid));
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(REPLACEMENT);
+                            rest = &after[len..];
+                        }
+                        // Only the end is missing: wait for the next read
+                        None => {
+                            self.partial = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_split_across_reads_is_reassembled() {
+        for text in ["é", "中", "🔥", "a€b"] {
+            let bytes = text.as_bytes();
+            for split in 0..=bytes.len() {
+                let mut decoder = Utf8Decoder::new();
+                let mut decoded = decoder.decode(&bytes[..split]);
+                decoded.push_str(&decoder.decode(&bytes[split..]));
+                assert_eq!(decoded, text, "split {text:?} at {split}");
+                assert!(decoder.partial.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_character_is_held_back() {
+        let mut decoder = Utf8Decoder::new();
+        // "中" is E4 B8 AD
+        assert_eq!(decoder.decode(b"ab\xE4"), "ab");
+        assert_eq!(decoder.partial, b"\xE4");
+        assert_eq!(decoder.decode(b"\xB8"), "");
+        assert_eq!(decoder.partial, b"\xE4\xB8");
+        assert_eq!(decoder.decode(b"\xADcd"), "中cd");
+        assert!(decoder.partial.is_empty());
+    }
+
+    #[test]
+    fn test_one_byte_reads() {
+        let text = "naïve 日本語 🦀!";
+        let mut decoder = Utf8Decoder::new();
+        let decoded: String = text
+            .as_bytes()
+            .iter()
+            .map(|byte| decoder.decode(std::slice::from_ref(byte)))
+            .collect();
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn test_invalid_bytes_are_replaced() {
+        let mut decoder = Utf8Decoder::new();
+        assert_eq!(decoder.decode(b"a\xFFb\xC3(c"), "a\u{FFFD}b\u{FFFD}(c");
+        assert!(decoder.partial.is_empty());
+
+        // A held-back start that the next read doesn't continue is invalid
+        assert_eq!(decoder.decode(b"x\xE4"), "x");
+        assert_eq!(decoder.decode(b"y"), "\u{FFFD}y");
+        assert!(decoder.partial.is_empty());
+    }
+}
@@ -6,14 +6,25 @@
 //! - Standard 16 colors (8 normal + 8 bright)
 //! - 256-color palette
 //! - 24-bit true color (RGB)
-//! - Text attributes (bold, italic, underline, etc.)
+//! - Text attributes (bold, dim, italic, underline, blink, strikethrough, etc.)
+//! - OSC 8 hyperlinks, returned as a [`HyperlinkMap`] alongside the lines
+//! - Sixel images, drawn as blocks of colored cells (see [`super::sixel`])
+//!
+//! [`AnsiParser::parse_borrowed`] returns lines that borrow their text from
+//! the input wherever it was printed without being split or overwritten; the
+//! other entry points return owned lines.
+
+use std::borrow::Cow;
+use std::ops::Range;
 
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use tracing::warn;
+use unicode_width::UnicodeWidthChar;
 use vte::{Params, Parser, Perform};
 
-use crate::colors::TrueColorPalette;
+use super::sixel::{self, SixelImage};
+use crate::colors::{TrueColor, TrueColorPalette};
 
 // Warning messages for malformed ANSI sequences
 const WARN_MALFORMED_256_FG: &str =
@@ -42,26 +53,206 @@ const fn to_color_u8(value: u16) -> u8 {
     }
 }
 
+/// Number of terminal cells a character occupies
+#[inline]
+fn char_cells(ch: char) -> usize {
+    UnicodeWidthChar::width(ch).unwrap_or(1)
+}
+
+/// Number of terminal cells a line occupies
+fn line_cells(line: &Line<'_>) -> usize {
+    line.spans
+        .iter()
+        .flat_map(|span| span.content.chars())
+        .map(char_cells)
+        .sum()
+}
+
+/// Append the cells `from..to` of a line to `out`, keeping span styles
+///
+/// Wide characters cut by either edge become spaces, as on a real screen.
+/// Spans wholly inside the range are kept as they are, text and all.
+fn slice_cells<'a>(line: &Line<'a>, from: usize, to: usize, out: &mut Vec<Span<'a>>) {
+    let mut col = 0;
+    for span in &line.spans {
+        let width: usize = span.content.chars().map(char_cells).sum();
+        // A combining mark at the very start belongs to the cell before `from`
+        let leading_mark = col == from
+            && from > 0
+            && span.content.chars().next().is_some_and(|ch| char_cells(ch) == 0);
+        if col >= from && col + width <= to && !leading_mark {
+            if !span.content.is_empty() {
+                out.push(span.clone());
+            }
+            col += width;
+            continue;
+        }
+
+        let mut text = String::new();
+        for ch in span.content.chars() {
+            let width = char_cells(ch);
+            let end = col + width;
+            if width == 0 {
+                // Combining marks belong to the cell before them
+                if (col > from && col <= to) || (col == 0 && from == 0) {
+                    text.push(ch);
+                }
+            } else if col >= from && end <= to {
+                text.push(ch);
+            } else if end > from && col < to {
+                for _ in col.max(from)..end.min(to) {
+                    text.push(' ');
+                }
+            }
+            col = end;
+        }
+        if !text.is_empty() {
+            out.push(Span::styled(text, span.style));
+        }
+    }
+}
+
+/// Write `spans` over a line starting at cell `start`, keeping the cells on either side
+fn overlay_cells<'a>(line: &Line<'a>, start: usize, spans: Vec<Span<'a>>) -> Line<'a> {
+    let width: usize = spans
+        .iter()
+        .flat_map(|span| span.content.chars())
+        .map(char_cells)
+        .sum();
+    let existing = line_cells(line);
+
+    // Fast path: the new text covers everything already on the line
+    if start == 0 && width >= existing {
+        return Line::from(spans);
+    }
+
+    let mut out = Vec::with_capacity(line.spans.len() + spans.len() + 1);
+    slice_cells(line, 0, start, &mut out);
+    if existing < start {
+        out.push(Span::raw(" ".repeat(start - existing)));
+    }
+    out.extend(spans);
+    slice_cells(line, start + width, existing, &mut out);
+    Line::from(out)
+}
+
+/// A run of linked text from an OSC 8 hyperlink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    /// Line index in the parsed output
+    pub line: usize,
+    /// First linked cell column (inclusive)
+    pub start: usize,
+    /// Last linked cell column (exclusive)
+    pub end: usize,
+    /// Link target
+    pub uri: String,
+}
+
+/// Hyperlinks found while parsing, looked up by line and cell column
+///
+/// ratatui spans carry no link attribute, so links are kept beside the
+/// parsed lines instead of inside them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HyperlinkMap {
+    links: Vec<Hyperlink>,
+}
+
+impl HyperlinkMap {
+    /// All links in output order
+    #[must_use]
+    pub fn links(&self) -> &[Hyperlink] {
+        &self.links
+    }
+
+    /// Check whether no links were found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Get the URI linked at a line and cell column, if any
+    #[must_use]
+    pub fn uri_at(&self, line: usize, col: usize) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|link| link.line == line && col >= link.start && col < link.end)
+            .map(|link| link.uri.as_str())
+    }
+
+    /// Keep links on lines `first..first + count`, renumbered from 0
+    #[must_use]
+    pub fn window(&self, first: usize, count: usize) -> Self {
+        let links = self
+            .links
+            .iter()
+            .filter(|link| link.line >= first && link.line - first < count)
+            .map(|link| Hyperlink {
+                line: link.line - first,
+                ..link.clone()
+            })
+            .collect();
+        Self { links }
+    }
+
+    /// Record one linked cell, extending the previous run when contiguous
+    fn mark(&mut self, line: usize, col: usize, width: usize, uri: &str) {
+        if let Some(last) = self.links.last_mut() {
+            if last.line == line && last.end == col && last.uri == uri {
+                last.end += width;
+                return;
+            }
+        }
+        self.links.push(Hyperlink {
+            line,
+            start: col,
+            end: col + width,
+            uri: uri.to_string(),
+        });
+    }
+}
+
 /// ANSI parser that converts escape sequences to styled ratatui spans
 ///
 /// This is a FULL terminal emulator with complete cursor positioning support.
 /// Implements VT100/VT220/xterm escape sequences for faithful terminal emulation.
-pub struct AnsiParser {
+///
+/// The screen is a grid of cells: text is written over whatever is already at
+/// the cursor, and erase sequences blank cells rather than whole lines. Text
+/// printed since the last cursor move is buffered in `current_text` and
+/// `current_line_spans`, and written onto the line when the cursor moves.
+///
+/// Text printed as one run between escape sequences and line wraps is kept as
+/// a slice of the input rather than copied, which is what `'a` borrows.
+pub struct AnsiParser<'a> {
     /// Current style being applied
     current_style: Style,
     /// Accumulated text with current style
-    current_text: String,
+    current_text: Cow<'a, str>,
+    /// Where `current_text` ends in `input`, while it is still a slice of it
+    current_text_end: Option<usize>,
+    /// Text being parsed by [`Self::advance_to`]
+    input: &'a str,
+    /// How much of `input` has been parsed
+    input_pos: usize,
+    /// Whether VTE is between sequences, so plain text can be written directly
+    in_ground: bool,
     /// Completed spans for the current line
-    current_line_spans: Vec<Span<'static>>,
+    current_line_spans: Vec<Span<'a>>,
     /// Completed lines
-    lines: Vec<Line<'static>>,
+    lines: Vec<Line<'a>>,
+    /// Lines scrolled off the top of the main screen, oldest first
+    scrollback: Vec<Line<'a>>,
     /// Color palette for mapping ANSI colors to true colors
     /// None means use default ratatui colors
     color_palette: Option<TrueColorPalette>,
     /// Cursor position - row (0-based)
     cursor_row: usize,
-    /// Cursor position - column (0-based)
+    /// Cursor position - column (0-based). Equals `terminal_width` after
+    /// printing in the last column, meaning the next character wraps first.
     cursor_col: usize,
+    /// Column where the pending text on the cursor row starts
+    span_start_col: usize,
     /// Terminal width in columns
     terminal_width: usize,
     /// Terminal height in rows
@@ -74,18 +265,22 @@ pub struct AnsiParser {
     /// Scroll region bottom (0-based, inclusive)
     scroll_bottom: usize,
     /// Alternative screen buffer (for full-screen apps)
-    alt_screen: Vec<Line<'static>>,
+    alt_screen: Vec<Line<'a>>,
     /// Whether we're using the alternative screen
     use_alt_screen: bool,
     /// OSC sequence buffer
     osc_buffer: String,
+    /// Data of the sixel image being received, between its DCS `q` and the terminator
+    sixel_data: Option<Vec<u8>>,
     /// Window title
     window_title: String,
     /// Hyperlink URL (for OSC 8)
     hyperlink_url: Option<String>,
+    /// Linked text written so far, by absolute output line
+    hyperlinks: HyperlinkMap,
 }
 
-impl AnsiParser {
+impl<'a> AnsiParser<'a> {
     /// Create a new ANSI parser with pre-allocated capacity for better performance
     #[must_use]
     pub fn new() -> Self {
@@ -99,12 +294,18 @@ impl AnsiParser {
         Self {
             // BUG FIX #9: Use Color::Reset for theme support instead of hardcoded White/Black
             current_style: Style::default().fg(Color::Reset).bg(Color::Reset),
-            current_text: String::with_capacity(256),
+            current_text: Cow::Borrowed(""),
+            current_text_end: None,
+            input: "",
+            input_pos: 0,
+            in_ground: true,
             current_line_spans: Vec::with_capacity(8),
             lines: vec![Line::from(""); height],
+            scrollback: Vec::new(),
             color_palette: None,
             cursor_row: 0,
             cursor_col: 0,
+            span_start_col: 0,
             terminal_width: width.max(1),
             terminal_height: height,
             saved_cursor_row: 0,
@@ -114,8 +315,10 @@ impl AnsiParser {
             alt_screen: Vec::new(),
             use_alt_screen: false,
             osc_buffer: String::new(),
+            sixel_data: None,
             window_title: String::new(),
             hyperlink_url: None,
+            hyperlinks: HyperlinkMap::default(),
         }
     }
 
@@ -156,10 +359,9 @@ impl AnsiParser {
     /// - Reset codes
     ///
     /// # Performance
-    /// This function is highly optimized:
-    /// - Zero-copy where possible
-    /// - Uses VTE parser (Rust's fastest ANSI parser)
-    /// - Minimal allocations through buffer reuse
+    /// Plain text is written a run at a time and only escape sequences go
+    /// through VTE. The lines are copied out of the input at the end; use
+    /// [`Self::parse_borrowed`] to skip that.
     ///
     /// # Example
     /// ```ignore
@@ -167,19 +369,9 @@ impl AnsiParser {
     /// ```
     #[must_use]
     pub fn parse(text: &str) -> Vec<Line<'static>> {
-        let mut parser = Parser::new();
         let mut performer = AnsiParser::new();
-
-        // VTE 0.15 expects a slice of bytes
-        parser.advance(&mut performer, text.as_bytes());
-
-        // Flush any remaining content and commit final state
-        performer.flush_text();
-        performer.commit_current_line();
-
-        // Return only the lines up to the cursor position (trim empty trailing lines)
-        let last_line = performer.cursor_row + 1;
-        performer.lines[..last_line.min(performer.lines.len())].to_vec()
+        performer.advance_to(&mut Parser::new(), text, text.len());
+        performer.into_lines().into_iter().map(owned_line).collect()
     }
 
     /// Parse ANSI-encoded text with a custom color palette
@@ -199,42 +391,241 @@ impl AnsiParser {
     /// This is a small clone (51 bytes + Vec) and only happens once per render frame.
     #[must_use]
     pub fn parse_with_palette(text: &str, palette: &TrueColorPalette) -> Vec<Line<'static>> {
-        let mut parser = Parser::new();
+        Self::parse_with_hyperlinks(text, palette).0
+    }
+
+    /// Parse ANSI-encoded text with a custom color palette, also returning OSC 8 links
+    ///
+    /// Linked text is underlined in the returned lines; the map records which
+    /// cells each URI covers so callers can resolve clicks. Unterminated links
+    /// run to the end of the text, and a new link implicitly closes the previous one.
+    #[must_use]
+    pub fn parse_with_hyperlinks(
+        text: &str,
+        palette: &TrueColorPalette,
+    ) -> (Vec<Line<'static>>, HyperlinkMap) {
+        let (lines, links) = AnsiParser::parse_borrowed(text, palette);
+        (lines.into_iter().map(owned_line).collect(), links)
+    }
+
+    /// Parse like [`Self::parse_with_hyperlinks`], with lines borrowing from `text`
+    ///
+    /// Spans keep slices of `text` unless their text had to be split or
+    /// rebuilt (line wraps, overwrites, detected URLs), so a large buffer
+    /// costs an allocation per line rather than one per span. Callers that
+    /// keep lines around can copy just those with [`owned_line`].
+    #[must_use]
+    pub fn parse_borrowed(
+        text: &'a str,
+        palette: &TrueColorPalette,
+    ) -> (Vec<Line<'a>>, HyperlinkMap) {
         let mut performer = AnsiParser::with_palette(palette.clone());
+        performer.advance_to(&mut Parser::new(), text, text.len());
+        performer.finish()
+    }
+
+    /// Output line the cursor is on after each of the sorted byte `offsets` in `text`
+    ///
+    /// Lines are counted as [`Self::parse`] returns them, so wrapping and
+    /// cursor movement are accounted for. Offsets inside a character count
+    /// from its start; offsets past the end count from the end.
+    #[must_use]
+    pub fn lines_at_offsets(text: &str, offsets: &[usize]) -> Vec<usize> {
+        let mut parser = Parser::new();
+        let mut performer = AnsiParser::new();
+
+        offsets
+            .iter()
+            .map(|&offset| {
+                let mut offset = offset.min(text.len());
+                while !text.is_char_boundary(offset) {
+                    offset -= 1;
+                }
+                performer.advance_to(&mut parser, text, offset);
+                performer.scrollback.len() + performer.cursor_row
+            })
+            .collect()
+    }
+
+    /// Parse `text` up to byte `end`, carrying on from where the last call stopped
+    ///
+    /// Text between escape sequences is written directly, a run at a time,
+    /// so it can stay a slice of `text`; VTE gets the escape sequences. After
+    /// a sequence that ends without telling us VTE is back between
+    /// sequences, VTE is given the text too until one does.
+    fn advance_to(&mut self, parser: &mut Parser, text: &'a str, end: usize) {
+        self.input = text;
+        while self.input_pos < end {
+            if self.in_ground {
+                let run_end = text[self.input_pos..end]
+                    .find('\x1b')
+                    .map_or(end, |offset| self.input_pos + offset);
+                self.print_plain(self.input_pos..run_end);
+                self.input_pos = run_end;
+                // The escape goes to VTE, which says when it's done
+                self.in_ground = run_end == end;
+            } else {
+                let bytes = &text.as_bytes()[self.input_pos..end];
+                self.input_pos += parser.advance_until_terminated(self, bytes);
+            }
+        }
+    }
+
+    /// Write `input[range]`, which holds no escape sequences, as VTE would
+    ///
+    /// Control characters are executed; the runs between them are printed.
+    fn print_plain(&mut self, range: Range<usize>) {
+        let text = self.input;
+        let mut run_start = range.start;
+        for (offset, ch) in text[range.clone()].char_indices() {
+            if matches!(ch, '\x00'..='\x1f' | '\u{80}'..='\u{9f}') {
+                let at = range.start + offset;
+                self.print_run(run_start..at);
+                // C0 and C1 controls all fit in a byte
+                #[allow(clippy::cast_possible_truncation)]
+                self.execute(ch as u8);
+                run_start = at + ch.len_utf8();
+            }
+        }
+        self.print_run(run_start..range.end);
+    }
+
+    /// Print `input[range]`, text without control characters, at the cursor
+    ///
+    /// Works like printing it a character at a time with [`Self::write_at_cursor`],
+    /// but the text between line wraps is added as slices of the input.
+    fn print_run(&mut self, range: Range<usize>) {
+        let text = self.input;
+        if self.hyperlink_url.is_some() {
+            // Linked cells are recorded one at a time
+            for ch in text[range].chars() {
+                self.write_at_cursor(ch);
+            }
+            return;
+        }
+
+        let mut from = range.start;
+        for (offset, ch) in text[range.clone()].char_indices() {
+            let at = range.start + offset;
+            let char_width = char_cells(ch);
+            if self.cursor_col > 0 && self.cursor_col + char_width > self.terminal_width {
+                self.push_text(from..at);
+                self.wrap_cursor();
+                from = at;
+            }
+            if at == from && self.current_text.is_empty() && self.current_line_spans.is_empty() {
+                self.span_start_col = self.cursor_col;
+            }
+            self.cursor_col += char_width;
+        }
+        self.push_text(from..range.end);
+    }
+
+    /// Add `input[range]` to the pending text, borrowing while it stays one slice of the input
+    fn push_text(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let text = self.input;
+        if self.current_text.is_empty() {
+            self.current_text = Cow::Borrowed(&text[range.clone()]);
+        } else if self.current_text_end == Some(range.start) {
+            let start = range.start - self.current_text.len();
+            self.current_text = Cow::Borrowed(&text[start..range.end]);
+        } else {
+            self.current_text.to_mut().push_str(&text[range]);
+            self.current_text_end = None;
+            return;
+        }
+        self.current_text_end = Some(range.end);
+    }
 
-        // VTE 0.15 expects a slice of bytes
-        parser.advance(&mut performer, text.as_bytes());
+    /// Finish parsing and return scrollback followed by the screen lines
+    fn into_lines(self) -> Vec<Line<'a>> {
+        self.finish().0
+    }
 
+    /// Finish parsing and return the output lines with their hyperlinks
+    fn finish(mut self) -> (Vec<Line<'a>>, HyperlinkMap) {
         // Flush any remaining content and commit final state
-        performer.flush_text();
-        performer.commit_current_line();
+        self.flush_text();
+        self.commit_current_line();
 
         // Return only the lines up to the cursor position (trim empty trailing lines)
-        let last_line = performer.cursor_row + 1;
-        performer.lines[..last_line.min(performer.lines.len())].to_vec()
+        let last_line = (self.cursor_row + 1).min(self.lines.len());
+        let mut lines = std::mem::take(&mut self.scrollback);
+        lines.extend_from_slice(&self.lines[..last_line]);
+        let links = self.hyperlinks.window(0, lines.len());
+        (lines, links)
+    }
+
+    /// Number of lines [`Self::finish`] would return if parsing ended here
+    fn line_count(&self) -> usize {
+        self.scrollback.len() + self.cursor_row + 1
+    }
+
+    /// Copies of the lines `first..first + count` as [`Self::finish`] would return them
+    ///
+    /// Text not yet written onto its line is shown in place, without flushing
+    /// it, so parsing can carry on exactly as if this hadn't been called.
+    fn snapshot(&self, first: usize, count: usize) -> Vec<Line<'a>> {
+        let end = first.saturating_add(count).min(self.line_count());
+        (first..end)
+            .map(|index| {
+                let Some(row) = index.checked_sub(self.scrollback.len()) else {
+                    return self.scrollback[index].clone();
+                };
+                let line = self.lines.get(row).cloned().unwrap_or_default();
+                if row != self.cursor_row {
+                    return line;
+                }
+                let mut spans = self.current_line_spans.clone();
+                if !self.current_text.is_empty() {
+                    self.text_spans(self.current_text.clone(), &mut spans);
+                }
+                if spans.is_empty() {
+                    line
+                } else {
+                    overlay_cells(&line, self.span_start_col, spans)
+                }
+            })
+            .collect()
     }
 
     /// Flush accumulated text to a span, with URL detection and highlighting
     fn flush_text(&mut self) {
         if !self.current_text.is_empty() {
             let text = std::mem::take(&mut self.current_text);
+            self.current_text_end = None;
+            let mut spans = std::mem::take(&mut self.current_line_spans);
+            self.text_spans(text, &mut spans);
+            self.current_line_spans = spans;
+        }
+    }
 
-            // Detect URLs in the text and split into URL vs non-URL spans
-            // URL patterns: http://, https://, ftp://, file://
-            let url_spans = Self::split_urls(&text, self.current_style);
-            if url_spans.is_empty() {
-                // No URLs found - use the text as-is (common fast path)
-                let span = Span::styled(text, self.current_style);
-                self.current_line_spans.push(span);
-            } else {
-                self.current_line_spans.extend(url_spans);
-            }
+    /// Add spans for printed text in the current style, with URL detection and highlighting
+    fn text_spans(&self, text: Cow<'a, str>, spans: &mut Vec<Span<'a>>) {
+        // OSC 8 linked text is underlined as a whole, skipping URL detection
+        if self.hyperlink_url.is_some() {
+            let style = self.current_style.add_modifier(Modifier::UNDERLINED);
+            spans.push(Span::styled(text, style));
+            return;
+        }
+
+        // Detect URLs in the text and split into URL vs non-URL spans
+        // URL patterns: http://, https://, ftp://, file://
+        let url_spans = Self::split_urls(&text, self.current_style);
+        if url_spans.is_empty() {
+            // No URLs found - use the text as-is (common fast path)
+            spans.push(Span::styled(text, self.current_style));
+        } else {
+            spans.extend(url_spans);
         }
     }
 
     /// Split text into URL and non-URL spans with appropriate styling
     /// URLs get underline + cyan color to make them visually distinct
-    fn split_urls(text: &str, base_style: Style) -> Vec<Span<'static>> {
+    fn split_urls(text: &Cow<'a, str>, base_style: Style) -> Vec<Span<'a>> {
         // URL protocol prefixes to detect
         const PROTOCOLS: &[&str] = &["https://", "http://", "ftp://", "file://"];
 
@@ -243,8 +634,19 @@ impl AnsiParser {
             return Vec::new();
         }
 
+        // Pieces of borrowed text stay borrowed
+        let piece = |piece: &str| -> Cow<'a, str> {
+            match text {
+                Cow::Borrowed(whole) => {
+                    let start = piece.as_ptr() as usize - whole.as_ptr() as usize;
+                    Cow::Borrowed(&whole[start..start + piece.len()])
+                }
+                Cow::Owned(_) => Cow::Owned(piece.to_string()),
+            }
+        };
+
         let mut spans = Vec::new();
-        let mut remaining = text;
+        let mut remaining: &str = text;
 
         // Scan for URLs by protocol prefix
         while !remaining.is_empty() {
@@ -261,7 +663,7 @@ impl AnsiParser {
             if let Some((start, _proto)) = earliest {
                 // Add any text before the URL as a normal span
                 if start > 0 {
-                    spans.push(Span::styled(remaining[..start].to_string(), base_style));
+                    spans.push(Span::styled(piece(&remaining[..start]), base_style));
                 }
 
                 // Find the end of the URL: stop at whitespace or certain punctuation
@@ -277,14 +679,14 @@ impl AnsiParser {
                     .add_modifier(Modifier::UNDERLINED)
                     .fg(Color::Rgb(0x5A, 0xCC, 0xE5)); // Cyan for URLs
 
-                spans.push(Span::styled(url.to_string(), url_style));
+                spans.push(Span::styled(piece(url), url_style));
 
                 // Continue scanning after the URL
                 remaining = &remaining[start + url_end..];
             } else {
                 // No more URLs - add remaining text
                 if !remaining.is_empty() {
-                    spans.push(Span::styled(remaining.to_string(), base_style));
+                    spans.push(Span::styled(piece(remaining), base_style));
                 }
                 break;
             }
@@ -302,38 +704,48 @@ impl AnsiParser {
 
     /// Write text at cursor position
     fn write_at_cursor(&mut self, ch: char) {
-        // Add character to current text
-        self.current_text.push(ch);
-
         // Calculate display width for wide characters
-        let char_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(1);
+        let char_width = char_cells(ch);
+
+        // Wrap only once there is something to print past the right edge, so
+        // filling the last column (or the last row) doesn't scroll early
+        if self.cursor_col > 0 && self.cursor_col + char_width > self.terminal_width {
+            self.wrap_cursor();
+        }
+
+        if self.current_text.is_empty() && self.current_line_spans.is_empty() {
+            self.span_start_col = self.cursor_col;
+        }
+
+        if let Some(uri) = &self.hyperlink_url {
+            let line = self.scrollback.len() + self.cursor_row;
+            self.hyperlinks.mark(line, self.cursor_col, char_width, uri);
+        }
 
-        // Advance cursor
+        // Add character to current text and advance cursor
+        self.current_text.to_mut().push(ch);
+        self.current_text_end = None;
         self.cursor_col += char_width;
+    }
 
-        // Handle line wrap
-        if self.cursor_col >= self.terminal_width {
-            self.flush_text();
-            self.move_cursor_to_line_start();
-            self.cursor_row += 1;
-            if self.cursor_row >= self.terminal_height {
-                self.scroll_up(1);
-                self.cursor_row = self.terminal_height - 1;
-            }
+    /// Move to the start of the next line for text that ran past the right edge
+    fn wrap_cursor(&mut self) {
+        self.move_cursor_to_line_start();
+        self.cursor_row += 1;
+        if self.cursor_row >= self.terminal_height {
+            self.scroll_up(1);
+            self.cursor_row = self.terminal_height - 1;
         }
     }
 
+    /// Cursor column clamped to the screen, for operations that act on the cursor cell
+    fn cursor_cell(&self) -> usize {
+        self.cursor_col.min(self.terminal_width - 1)
+    }
+
     /// Move cursor to start of current line (column 0)
     fn move_cursor_to_line_start(&mut self) {
-        self.flush_text();
-        self.ensure_line(self.cursor_row);
-
-        // Commit current line spans to the line
-        if !self.current_line_spans.is_empty() {
-            let spans = std::mem::take(&mut self.current_line_spans);
-            self.lines[self.cursor_row] = Line::from(spans);
-        }
-
+        self.commit_current_line();
         self.cursor_col = 0;
     }
 
@@ -352,12 +764,14 @@ impl AnsiParser {
         self.cursor_col = 0;
     }
 
-    /// Commit current line spans to the lines buffer
+    /// Write pending text onto the cursor row at the column it was printed
     fn commit_current_line(&mut self) {
+        self.flush_text();
         self.ensure_line(self.cursor_row);
         if !self.current_line_spans.is_empty() {
             let spans = std::mem::take(&mut self.current_line_spans);
-            self.lines[self.cursor_row] = Line::from(spans);
+            let row = self.cursor_row;
+            self.lines[row] = overlay_cells(&self.lines[row], self.span_start_col, spans);
         }
     }
 
@@ -378,8 +792,14 @@ impl AnsiParser {
 
         let n = n.min(end - start);
 
-        // Remove n lines from the top of scroll region
-        let _ = self.lines.drain(start..(start + n));
+        // Remove n lines from the top of scroll region, keeping them as
+        // scrollback when they leave the top of the main screen
+        let removed = self.lines.drain(start..(start + n));
+        if start == 0 && !self.use_alt_screen {
+            self.scrollback.extend(removed);
+        } else {
+            drop(removed);
+        }
 
         // Add n blank lines at the bottom of scroll region
         // Use resize with pre-allocated capacity for efficiency
@@ -432,14 +852,20 @@ impl AnsiParser {
 
     /// Move cursor forward n columns
     fn cursor_forward(&mut self, n: usize) {
-        self.flush_text();
-        self.cursor_col = (self.cursor_col + n).min(self.terminal_width - 1);
+        self.commit_current_line();
+        self.cursor_col = (self.cursor_cell() + n).min(self.terminal_width - 1);
     }
 
     /// Move cursor backward n columns
     fn cursor_backward(&mut self, n: usize) {
-        self.flush_text();
-        self.cursor_col = self.cursor_col.saturating_sub(n);
+        self.commit_current_line();
+        self.cursor_col = self.cursor_cell().saturating_sub(n);
+    }
+
+    /// Move cursor to an absolute column (0-based)
+    fn set_cursor_col(&mut self, col: usize) {
+        self.commit_current_line();
+        self.cursor_col = col.min(self.terminal_width - 1);
     }
 
     /// Set cursor position (1-based from CSI sequence)
@@ -454,22 +880,26 @@ impl AnsiParser {
 
     /// Erase from cursor to end of line
     fn erase_to_end_of_line(&mut self) {
-        self.flush_text();
-        self.ensure_line(self.cursor_row);
+        self.commit_current_line();
 
-        // Clear current text and spans
-        self.current_text.clear();
-        self.current_line_spans.clear();
+        let row = self.cursor_row;
+        let mut spans = Vec::new();
+        slice_cells(&self.lines[row], 0, self.cursor_cell(), &mut spans);
+        self.lines[row] = Line::from(spans);
     }
 
-    /// Erase from start of line to cursor
+    /// Erase from start of line to cursor (inclusive)
     fn erase_to_start_of_line(&mut self) {
-        self.flush_text();
-        self.ensure_line(self.cursor_row);
+        self.commit_current_line();
 
-        // Clear the line and reset spans
-        self.lines[self.cursor_row] = Line::from("");
-        self.current_line_spans.clear();
+        let row = self.cursor_row;
+        let existing = line_cells(&self.lines[row]);
+        let end = self.cursor_cell() + 1;
+        self.lines[row] = if end >= existing {
+            Line::from("")
+        } else {
+            overlay_cells(&self.lines[row], 0, vec![Span::raw(" ".repeat(end))])
+        };
     }
 
     /// Erase entire line
@@ -479,7 +909,7 @@ impl AnsiParser {
 
         self.lines[self.cursor_row] = Line::from("");
         self.current_line_spans.clear();
-        self.current_text.clear();
+        self.current_text = Cow::Borrowed("");
     }
 
     /// Erase from cursor to end of display
@@ -520,38 +950,73 @@ impl AnsiParser {
             *line = Line::from("");
         }
 
+        // Links on the screen go with the text; scrollback links stay
+        let first_screen_line = self.scrollback.len();
+        self.hyperlinks
+            .links
+            .retain(|link| link.line < first_screen_line);
+
         self.current_line_spans.clear();
-        self.current_text.clear();
+        self.current_text = Cow::Borrowed("");
     }
 
-    /// Insert n blank characters at cursor
+    /// Insert n blank characters at cursor, shifting the rest of the line right
     fn insert_blank_chars(&mut self, n: usize) {
-        self.flush_text();
-        let spaces = " ".repeat(n);
-        self.current_text.push_str(&spaces);
+        self.commit_current_line();
+
+        let row = self.cursor_row;
+        let col = self.cursor_cell();
+        let existing = line_cells(&self.lines[row]);
+        if n == 0 || col >= existing {
+            return;
+        }
+
+        let mut spans = Vec::new();
+        slice_cells(&self.lines[row], 0, col, &mut spans);
+        spans.push(Span::raw(" ".repeat(n)));
+        slice_cells(&self.lines[row], col, existing, &mut spans);
+
+        // Cells pushed past the right edge are lost
+        let shifted = Line::from(spans);
+        let mut spans = Vec::new();
+        slice_cells(&shifted, 0, self.terminal_width, &mut spans);
+        self.lines[row] = Line::from(spans);
     }
 
-    /// Delete n characters at cursor
+    /// Delete n characters at cursor, shifting the rest of the line left
     fn delete_chars(&mut self, n: usize) {
-        self.flush_text();
+        self.commit_current_line();
 
-        if n == 0 {
+        let row = self.cursor_row;
+        let col = self.cursor_cell();
+        let existing = line_cells(&self.lines[row]);
+        if n == 0 || col >= existing {
             return;
         }
 
-        // To properly delete characters, we need to work with the current line's content
-        // This is a simplified implementation that clears current_text
-        // A full implementation would need to reconstruct the line from spans,
-        // delete n characters at cursor position, and rebuild the spans
-        // For now, we clear the pending text which handles most common cases
-        self.current_text.clear();
+        let mut spans = Vec::new();
+        slice_cells(&self.lines[row], 0, col, &mut spans);
+        slice_cells(&self.lines[row], col + n, existing, &mut spans);
+        self.lines[row] = Line::from(spans);
+    }
+
+    /// Blank n characters from the cursor without moving the rest of the line
+    fn erase_chars(&mut self, n: usize) {
+        self.commit_current_line();
+
+        let row = self.cursor_row;
+        let col = self.cursor_cell();
+        let existing = line_cells(&self.lines[row]);
+        if n == 0 || col >= existing {
+            return;
+        }
 
-        // Note: Full DCH implementation would require:
-        // 1. Flatten current_line_spans to get full line text
-        // 2. Calculate actual cursor position in that text
-        // 3. Delete n characters at that position
-        // 4. Rebuild spans with correct styling
-        // This complexity is deferred as DCH is rarely used in practice
+        if col + n >= existing {
+            // Blanking to the end of the line is the same as erasing it
+            self.erase_to_end_of_line();
+        } else {
+            self.lines[row] = overlay_cells(&self.lines[row], col, vec![Span::raw(" ".repeat(n))]);
+        }
     }
 
     /// Insert n blank lines at cursor
@@ -606,6 +1071,60 @@ impl AnsiParser {
         self.cursor_col = self.saved_cursor_col.min(self.terminal_width - 1);
     }
 
+    /// Draw a sixel image from the cursor and leave the cursor on the line below it
+    ///
+    /// Each cell shows the [`sixel::CELL_WIDTH_PX`] by [`sixel::CELL_HEIGHT_PX`]
+    /// pixels under it as `▀`, its top half in the foreground color and its
+    /// bottom half in the background. The image is cut off at the right edge
+    /// and scrolls the screen like text if it runs past the bottom.
+    fn draw_image(&mut self, image: &SixelImage) {
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+        self.commit_current_line();
+
+        let start = self.cursor_cell();
+        let cols = image
+            .width
+            .div_ceil(sixel::CELL_WIDTH_PX)
+            .min(self.terminal_width - start);
+        let rows = image.height.div_ceil(sixel::CELL_HEIGHT_PX);
+        let half = sixel::CELL_HEIGHT_PX / 2;
+        let color = |color: Option<TrueColor>| {
+            color.map_or(Color::Reset, |color| Color::Rgb(color.r, color.g, color.b))
+        };
+
+        for row in 0..rows {
+            if row > 0 {
+                self.move_cursor_down_with_scroll();
+            }
+            let spans = (0..cols)
+                .map(|col| {
+                    let x = col * sixel::CELL_WIDTH_PX;
+                    let y = row * sixel::CELL_HEIGHT_PX;
+                    let width = sixel::CELL_WIDTH_PX.min(image.width - x);
+                    let top = image.average(x, y, width, half.min(image.height - y));
+                    let bottom = if y + half < image.height {
+                        let height = half.min(image.height - y - half);
+                        image.average(x, y + half, width, height)
+                    } else {
+                        None
+                    };
+                    let text = if top.is_none() && bottom.is_none() {
+                        " "
+                    } else {
+                        "▀"
+                    };
+                    Span::styled(text, Style::default().fg(color(top)).bg(color(bottom)))
+                })
+                .collect();
+            self.ensure_line(self.cursor_row);
+            let line = self.cursor_row;
+            self.lines[line] = overlay_cells(&self.lines[line], start, spans);
+        }
+        self.move_cursor_down_with_scroll();
+    }
+
     /// Switch to alternative screen buffer
     fn use_alt_screen_buffer(&mut self) {
         if !self.use_alt_screen {
@@ -719,6 +1238,12 @@ impl AnsiParser {
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::match_same_arms)]
     fn handle_sgr(&mut self, params: &Params) {
+        // `ESC[m` with no parameters is the same as `ESC[0m`
+        if params.is_empty() {
+            self.current_style = Style::default().fg(Color::Reset).bg(Color::Reset);
+            return;
+        }
+
         let mut iter = params.iter();
 
         while let Some(param) = iter.next() {
@@ -774,6 +1299,10 @@ impl AnsiParser {
                     // Most terminals don't support this, so we log and ignore
                     warn!("{}: {}", WARN_FONT_SELECTION, param[0]);
                 }
+                // Not bold (treated as a bold reset rather than double underline)
+                21 => {
+                    self.current_style = self.current_style.remove_modifier(Modifier::BOLD);
+                }
                 // Normal intensity (not bold, not dim)
                 22 => {
                     self.current_style = self
@@ -954,13 +1483,80 @@ impl AnsiParser {
     }
 }
 
-impl Default for AnsiParser {
+/// A copy of `line` that owns its text
+#[must_use]
+pub fn owned_line(line: Line<'_>) -> Line<'static> {
+    Line {
+        spans: line
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect(),
+        alignment: line.alignment,
+    }
+}
+
+/// Parser that keeps its state between calls, so output can be parsed as it arrives
+///
+/// Feeding the output in any number of pieces gives the same lines as parsing
+/// it in one go with [`AnsiParser::parse_with_hyperlinks`]: escape sequences,
+/// UTF-8 characters and lines may all be split across pieces.
+pub struct IncrementalParser {
+    /// VTE state, holding any escape sequence or character cut off by the last piece
+    parser: Parser,
+    /// Screen, scrollback and SGR state at the end of the output so far
+    performer: AnsiParser<'static>,
+    /// Bytes of output parsed so far
+    parsed: usize,
+}
+
+impl IncrementalParser {
+    /// Create a parser for output styled with `palette`
+    #[must_use]
+    pub fn new(palette: &TrueColorPalette) -> Self {
+        Self {
+            parser: Parser::new(),
+            performer: AnsiParser::with_palette(palette.clone()),
+            parsed: 0,
+        }
+    }
+
+    /// Bytes of output parsed so far
+    #[must_use]
+    pub fn parsed_len(&self) -> usize {
+        self.parsed
+    }
+
+    /// Parse the next piece of output
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.parser.advance(&mut self.performer, bytes);
+        self.parsed += bytes.len();
+    }
+
+    /// Number of lines in the output so far, scrollback included
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.performer.line_count()
+    }
+
+    /// Lines `first..first + count` of the output so far, with their hyperlinks
+    ///
+    /// Hyperlink lines count from `first`, as with [`HyperlinkMap::window`].
+    #[must_use]
+    pub fn lines(&self, first: usize, count: usize) -> (Vec<Line<'static>>, HyperlinkMap) {
+        let lines = self.performer.snapshot(first, count);
+        let links = self.performer.hyperlinks.window(first, lines.len());
+        (lines, links)
+    }
+}
+
+impl Default for AnsiParser<'_> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Perform for AnsiParser {
+impl Perform for AnsiParser<'_> {
     fn print(&mut self, c: char) {
         self.write_at_cursor(c);
     }
@@ -977,30 +1573,14 @@ impl Perform for AnsiParser {
                 self.move_cursor_to_line_start();
             }
             // Tab - move to next tab stop (every 8 columns)
+            // Skipped cells keep their content; gaps past the line end become spaces
             b'\t' => {
-                self.flush_text();
-                let next_tab = ((self.cursor_col / 8) + 1) * 8;
-                let spaces = next_tab
-                    .saturating_sub(self.cursor_col)
-                    .min(self.terminal_width - self.cursor_col);
-                for _ in 0..spaces {
-                    self.current_text.push(' ');
-                }
-                self.cursor_col = next_tab.min(self.terminal_width - 1);
+                let next_tab = ((self.cursor_cell() / 8) + 1) * 8;
+                self.set_cursor_col(next_tab);
             }
-            // Backspace - move cursor back one position and delete character
+            // Backspace - move cursor back one cell; shells erase with "\b \b"
             0x08 => {
-                // Don't flush first - work with current_text
-                if !self.current_text.is_empty() {
-                    // Remove from current unbuffered text
-                    self.current_text.pop();
-                    if self.cursor_col > 0 {
-                        self.cursor_col -= 1;
-                    }
-                } else if self.cursor_col > 0 {
-                    // Move cursor back but don't delete from spans (would be complex)
-                    self.cursor_col -= 1;
-                }
+                self.cursor_backward(1);
             }
             // Bell - ignore for rendering
             0x07 => {}
@@ -1010,6 +1590,7 @@ impl Perform for AnsiParser {
             }
             // Form feed - clear screen and home cursor
             0x0C => {
+                self.commit_current_line();
                 self.erase_display();
                 self.cursor_row = 0;
                 self.cursor_col = 0;
@@ -1018,27 +1599,45 @@ impl Perform for AnsiParser {
         }
     }
 
-    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _action: char) {
+    fn hook(&mut self, _params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
         // DCS sequences - Device Control String
         // Used for advanced terminal features like Sixel graphics, terminal queries
-        // We support basic structure but don't render complex graphics
+        // Only sixel images (`ESC P ... q`) are drawn
+        if action == 'q' && intermediates.is_empty() {
+            self.sixel_data = Some(Vec::new());
+        }
     }
 
     fn put(&mut self, byte: u8) {
         // DCS data - accumulate for processing in unhook
-        self.osc_buffer.push(byte as char);
+        match self.sixel_data {
+            Some(ref mut data) if data.len() < sixel::MAX_PAYLOAD_BYTES => data.push(byte),
+            Some(_) => {}
+            None => self.osc_buffer.push(byte as char),
+        }
+    }
+
+    fn terminated(&self) -> bool {
+        // Back between sequences, where `advance_to` takes over again
+        self.in_ground
     }
 
     fn unhook(&mut self) {
         // End of DCS sequence - process accumulated data
+        if let Some(data) = self.sixel_data.take() {
+            self.draw_image(&sixel::decode(&data));
+        }
         // Clear buffer for next sequence
         self.osc_buffer.clear();
     }
 
-    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
         // OSC sequences: ESC ] Ps ; Pt BEL
         // Common ones: 0/1/2 = set title, 8 = hyperlinks
 
+        // Ended by `ESC \`, VTE still has the `\` to read
+        self.in_ground = bell_terminated;
+
         if params.is_empty() {
             return;
         }
@@ -1056,8 +1655,11 @@ impl Perform for AnsiParser {
 
             // Hyperlink: OSC 8 ; params ; URI
             "8" => {
+                // Text before the sequence keeps its own (un)linked styling
+                self.flush_text();
                 if params.len() > 2 {
-                    let url = String::from_utf8_lossy(params[2]).to_string();
+                    // URIs may contain ';', which VTE splits into extra params
+                    let url = String::from_utf8_lossy(&params[2..].join(&b';')).to_string();
                     if url.is_empty() {
                         self.hyperlink_url = None;
                     } else {
@@ -1089,6 +1691,8 @@ impl Perform for AnsiParser {
         _ignore: bool,
         action: char,
     ) {
+        self.in_ground = true;
+
         // Helper to get first param with default
         let param1 = params
             .iter()
@@ -1144,9 +1748,8 @@ impl Perform for AnsiParser {
             }
 
             // Cursor Horizontal Absolute (CHA)
-            'G' => {
-                self.flush_text();
-                self.cursor_col = param1.saturating_sub(1).min(self.terminal_width - 1);
+            'G' | '`' => {
+                self.set_cursor_col(param1.saturating_sub(1));
             }
 
             // Cursor Position (CUP) or Horizontal and Vertical Position (HVP)
@@ -1171,8 +1774,16 @@ impl Perform for AnsiParser {
                     0 => self.erase_to_end_of_display(),
                     // 1: Erase from start of display to cursor
                     1 => self.erase_to_start_of_display(),
-                    // 2 or 3: Clear entire display
-                    2 | 3 => self.erase_display(),
+                    // 2: Clear entire display
+                    2 => self.erase_display(),
+                    // 3: Clear entire display and scrollback
+                    3 => {
+                        self.erase_display();
+                        self.hyperlinks = self
+                            .hyperlinks
+                            .window(self.scrollback.len(), self.lines.len());
+                        self.scrollback.clear();
+                    }
                     _ => {}
                 }
             }
@@ -1209,17 +1820,24 @@ impl Perform for AnsiParser {
 
             // Scroll Up (SU)
             'S' => {
+                self.commit_current_line();
                 self.scroll_up(param1);
             }
 
             // Scroll Down (SD)
             'T' => {
+                self.commit_current_line();
                 self.scroll_down(param1);
             }
 
+            // Insert Characters (ICH)
+            '@' => {
+                self.insert_blank_chars(param1);
+            }
+
             // Erase Characters (ECH)
             'X' => {
-                self.insert_blank_chars(param1);
+                self.erase_chars(param1);
             }
 
             // Cursor Vertical Absolute (VPA)
@@ -1246,6 +1864,7 @@ impl Perform for AnsiParser {
                 }
 
                 // Reset cursor to home
+                self.commit_current_line();
                 self.cursor_row = 0;
                 self.cursor_col = 0;
             }
@@ -1292,6 +1911,8 @@ impl Perform for AnsiParser {
     }
 
     fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        self.in_ground = true;
+
         // Simple escape sequences (not CSI/OSC/DCS)
         match (intermediates, byte) {
             // Save cursor (DECSC)
@@ -1347,6 +1968,10 @@ mod tests {
     use crate::colors::TrueColorPalette;
     use vte::Parser;
 
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
     #[test]
     fn test_to_color_u8() {
         // Normal values should pass through
@@ -1391,6 +2016,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dim_blink_strikethrough() {
+        let lines = AnsiParser::parse("\x1b[2;9mfaded\x1b[5mblink\x1b[0mplain");
+        let modifiers: Vec<Modifier> = lines[0]
+            .spans
+            .iter()
+            .map(|s| s.style.add_modifier)
+            .collect();
+
+        assert_eq!(modifiers[0], Modifier::DIM | Modifier::CROSSED_OUT);
+        assert_eq!(
+            modifiers[1],
+            Modifier::DIM | Modifier::CROSSED_OUT | Modifier::SLOW_BLINK
+        );
+        // Reset clears every attribute
+        assert_eq!(modifiers[2], Modifier::empty());
+    }
+
+    #[test]
+    fn test_sgr_specific_deasserts() {
+        let lines =
+            AnsiParser::parse("\x1b[1;2;5;9ma\x1b[22mb\x1b[25mc\x1b[29md\x1b[1;4me\x1b[21mf");
+        let modifiers: Vec<Modifier> = lines[0]
+            .spans
+            .iter()
+            .map(|s| s.style.add_modifier)
+            .collect();
+        let all = Modifier::BOLD | Modifier::DIM | Modifier::SLOW_BLINK | Modifier::CROSSED_OUT;
+
+        assert_eq!(modifiers[0], all);
+        // 22 clears bold and dim only
+        assert_eq!(modifiers[1], Modifier::SLOW_BLINK | Modifier::CROSSED_OUT);
+        // 25 clears blink only
+        assert_eq!(modifiers[2], Modifier::CROSSED_OUT);
+        // 29 clears strikethrough only
+        assert_eq!(modifiers[3], Modifier::empty());
+        // 21 clears bold but keeps underline
+        assert_eq!(modifiers[4], Modifier::BOLD | Modifier::UNDERLINED);
+        assert_eq!(modifiers[5], Modifier::UNDERLINED);
+    }
+
+    #[test]
+    fn test_empty_sgr_resets() {
+        let lines = AnsiParser::parse("\x1b[1;31mred\x1b[mplain");
+        let last = lines[0].spans.last().unwrap();
+
+        assert_eq!(last.style.fg, Some(Color::Reset));
+        assert!(!last.style.add_modifier.contains(Modifier::BOLD));
+    }
+
     #[test]
     fn test_256_color() {
         let lines = AnsiParser::parse("\x1b[38;5;196mBright Red\x1b[0m");
@@ -1425,16 +2100,89 @@ mod tests {
         // Test ESC[K (clear to end of line)
         let lines = AnsiParser::parse("Hello\x1b[KWorld");
         assert_eq!(lines.len(), 1);
-        // Should show "HelloWorld" since we can't accurately track cursor position
-        // but the K sequence shouldn't cause a crash
+        // Nothing after the cursor to erase, so the text just continues
+        assert_eq!(line_text(&lines[0]), "HelloWorld");
+
+        // Erase the tail of a line after moving back over it
+        let lines = AnsiParser::parse("Hello World\x1b[6D\x1b[K!");
+        assert_eq!(line_text(&lines[0]), "Hello!");
     }
 
     #[test]
     fn test_erase_in_display() {
         // Test ESC[2J (clear screen)
         let lines = AnsiParser::parse("Line1\nLine2\x1b[2JLine3");
-        // After clear screen, only content after clear should remain
-        assert!(!lines.is_empty());
+        // The cursor stays put, so Line3 is drawn on row 1 of a blank screen
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(text, vec!["", "     Line3"]);
+    }
+
+    #[test]
+    fn test_clear_screen_and_home() {
+        let lines = AnsiParser::parse("old 1\nold 2\nold 3\x1b[2J\x1b[Hnew");
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(text, vec!["new"]);
+    }
+
+    #[test]
+    fn test_erase_to_start_of_line_and_line() {
+        let lines = AnsiParser::parse("abcdef\x1b[3G\x1b[1K\nkeep\nwhole\x1b[2K");
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(text, vec!["   def", "keep", ""]);
+    }
+
+    #[test]
+    fn test_cursor_positioning_overwrites_cells() {
+        // Draw a small full-screen layout out of order, like a TUI would
+        let input =
+            "\x1b[2J\x1b[1;1Hname      cpu\x1b[2;1Hfurnace\x1b[2;11H3%\x1b[3;11H12%\x1b[3;1Hcargo";
+        let lines = AnsiParser::parse(input);
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(text, vec!["name      cpu", "furnace   3%", "cargo     12%"]);
+
+        // Overwriting the middle keeps the cells on either side
+        let lines = AnsiParser::parse("0123456789\x1b[1;4HAB");
+        assert_eq!(line_text(&lines[0]), "012AB56789");
+    }
+
+    #[test]
+    fn test_overwrite_preserves_styles_outside_the_write() {
+        let lines = AnsiParser::parse("\x1b[31mred\x1b[0m plain\x1b[1G\x1b[32mG");
+        let parts: Vec<(&str, Option<Color>)> = lines[0]
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style.fg))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("G", Some(Color::Green)),
+                ("ed", Some(Color::Red)),
+                (" plain", Some(Color::Reset)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_character_insert_delete_and_erase() {
+        // DCH shifts left, ICH shifts right, ECH blanks in place
+        let lines =
+            AnsiParser::parse("abcdef\x1b[2G\x1b[2P\nabcdef\x1b[2G\x1b[2@\nabcdef\x1b[2G\x1b[2X");
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(text, vec!["adef", "a  bcdef", "a  def"]);
+    }
+
+    #[test]
+    fn test_full_last_row_does_not_scroll() {
+        let mut parser = Parser::new();
+        let mut performer = AnsiParser::with_size(4, 2);
+
+        parser.advance(&mut performer, b"top\x1b[2;1Hbott");
+        performer.commit_current_line();
+
+        assert_eq!(line_text(&performer.lines[0]), "top");
+        assert_eq!(line_text(&performer.lines[1]), "bott");
+        assert!(performer.scrollback.is_empty());
     }
 
     #[test]
@@ -1456,14 +2204,12 @@ mod tests {
 
     #[test]
     fn test_carriage_return_overwrite() {
-        // Test that \r with text (without \n) in a line
-        // For scrollback simplicity, we ignore \r and let text accumulate
+        // Text after \r overwrites from column 0, leaving any longer tail
         let output = "Initial text\rOverwritten";
         let lines = AnsiParser::parse(output);
 
         assert_eq!(lines.len(), 1, "Should have one line");
-        // In our simplified model, both texts appear (no true cursor positioning)
-        // This is acceptable for a scrollback-focused terminal
+        assert_eq!(line_text(&lines[0]), "Overwrittent");
     }
 
     #[test]
@@ -1505,17 +2251,12 @@ mod tests {
 
     #[test]
     fn test_progress_bar_carriage_return() {
-        // Test that \r behavior with text (without \n)
-        // Note: Full progress bar support requires cursor positioning which we don't implement
-        // In our simplified model for scrollback, text after \r will accumulate
+        // Each update after \r redraws the same line
         let output = "Progress: 0%\rProgress: 50%\rProgress: 100%";
         let lines = AnsiParser::parse(output);
 
-        // Without full cursor positioning, all progress updates will appear
         assert_eq!(lines.len(), 1, "Should have one line");
-        let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
-        // Text accumulates in our scrollback-focused model
-        assert!(text.contains("100%"), "Final progress should be visible");
+        assert_eq!(line_text(&lines[0]), "Progress: 100%");
     }
 
     #[test]
@@ -1691,4 +2432,242 @@ mod tests {
             .collect();
         assert_eq!(restored_text, "main");
     }
+
+    #[test]
+    fn test_lines_scrolled_off_screen_are_kept() {
+        let input: String = (0..30).map(|i| format!("line {i}\n")).collect();
+        let lines = AnsiParser::parse(&input);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+
+        // 30 lines plus the empty line the cursor ends on
+        assert_eq!(text.len(), 31);
+        assert_eq!(text[0], "line 0");
+        assert_eq!(text[29], "line 29");
+    }
+
+    #[test]
+    fn test_clear_scrollback_sequence() {
+        let mut input: String = (0..30).map(|i| format!("line {i}\n")).collect();
+        input.push_str("\x1b[3J\x1b[Hfresh");
+        let lines = AnsiParser::parse(&input);
+        let first: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(first, "fresh");
+    }
+
+    #[test]
+    fn test_lines_at_offsets() {
+        let text = format!("$ ls\r\na\r\nb\r\n$ {}\r\nend", "x".repeat(100));
+        let mut offsets: Vec<usize> = ["$", "a", "b", "x", "end"]
+            .iter()
+            .map(|needle| text.find(needle).unwrap())
+            .collect();
+        offsets.push(1000);
+        let lines = AnsiParser::lines_at_offsets(&text, &offsets);
+        // The 102-column line wraps onto two lines at the default 80 columns
+        assert_eq!(lines, vec![0, 1, 2, 3, 5, 5]);
+        assert_eq!(AnsiParser::parse(&text).len(), 6);
+
+        // Offsets inside a character count from its start
+        assert_eq!(AnsiParser::lines_at_offsets("é\né", &[1, 3]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_osc8_hyperlink() {
+        let input = "see \x1b]8;;https://example.com/docs\x1b\\the docs\x1b]8;;\x1b\\ now";
+        let palette = TrueColorPalette::default_dark();
+        let (lines, links) = AnsiParser::parse_with_hyperlinks(input, &palette);
+
+        let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "see the docs now");
+        assert_eq!(
+            links.links(),
+            &[Hyperlink {
+                line: 0,
+                start: 4,
+                end: 12,
+                uri: "https://example.com/docs".to_string(),
+            }]
+        );
+        assert_eq!(links.uri_at(0, 4), Some("https://example.com/docs"));
+        assert_eq!(links.uri_at(0, 11), Some("https://example.com/docs"));
+        assert_eq!(links.uri_at(0, 12), None);
+        assert_eq!(links.uri_at(0, 3), None);
+
+        // Only the linked text is underlined
+        let linked = lines[0]
+            .spans
+            .iter()
+            .find(|s| s.content == "the docs")
+            .unwrap();
+        assert!(linked.style.add_modifier.contains(Modifier::UNDERLINED));
+        let plain = lines[0].spans.iter().find(|s| s.content == " now").unwrap();
+        assert!(!plain.style.add_modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn test_osc8_hyperlink_bel_terminated_with_params() {
+        let input = "\x1b]8;id=1;file:///tmp/a;b\x07x\x1b]8;;\x07\nnext";
+        let palette = TrueColorPalette::default_dark();
+        let (_, links) = AnsiParser::parse_with_hyperlinks(input, &palette);
+
+        assert_eq!(links.uri_at(0, 0), Some("file:///tmp/a;b"));
+        assert_eq!(links.links().len(), 1);
+    }
+
+    #[test]
+    fn test_osc8_nested_and_unterminated() {
+        // A second link closes the first; the last one never closes
+        let input = "\x1b]8;;https://a.test\x1b\\ab\x1b]8;;https://b.test\x1b\\cd\nef";
+        let palette = TrueColorPalette::default_dark();
+        let (lines, links) = AnsiParser::parse_with_hyperlinks(input, &palette);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(links.uri_at(0, 1), Some("https://a.test"));
+        assert_eq!(links.uri_at(0, 2), Some("https://b.test"));
+        assert_eq!(links.uri_at(1, 0), Some("https://b.test"));
+        assert_eq!(links.links().len(), 3);
+    }
+
+    #[test]
+    fn test_hyperlinks_follow_scrollback() {
+        let mut input: String = (0..30).map(|i| format!("line {i}\n")).collect();
+        input.push_str("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\");
+        let palette = TrueColorPalette::default_dark();
+        let (lines, links) = AnsiParser::parse_with_hyperlinks(&input, &palette);
+
+        assert_eq!(lines.len(), 31);
+        assert_eq!(links.uri_at(30, 0), Some("https://example.com"));
+
+        let window = links.window(28, 3);
+        assert_eq!(window.uri_at(2, 3), Some("https://example.com"));
+        assert!(links.window(0, 30).is_empty());
+    }
+
+    #[test]
+    fn test_alt_screen_scrolling_does_not_add_scrollback() {
+        let mut input = String::from("main\n\x1b[?1049h");
+        input.extend((0..30).map(|i| format!("alt {i}\n")));
+        input.push_str("\x1b[?1049l");
+        let lines = AnsiParser::parse(&input);
+
+        assert!(lines
+            .iter()
+            .all(|l| !l.spans.iter().any(|s| s.content.contains("alt"))));
+    }
+
+    /// Style of the cell at `col` of `line`
+    fn cell_style(line: &Line<'_>, col: usize) -> Option<Style> {
+        let mut start = 0;
+        line.spans.iter().find_map(|span| {
+            let end = start + span.content.chars().count();
+            let found = (start..end).contains(&col).then_some(span.style);
+            start = end;
+            found
+        })
+    }
+
+    #[test]
+    fn test_sixel_image_drawn_as_cells_at_cursor() {
+        let red = Color::Rgb(255, 0, 0);
+        // 16 by 24 pixels: two cells wide and two tall, the last half empty
+        let lines = AnsiParser::parse("ab\x1bPq#1;2;100;0;0!16~-!16~-!16~-!16~\x1b\\c");
+
+        assert_eq!(line_text(&lines[0]), "ab▀▀");
+        assert_eq!(line_text(&lines[1]), "  ▀▀");
+        // The cursor ends up on the line below the image
+        assert_eq!(line_text(&lines[2]), "c");
+
+        let full = cell_style(&lines[0], 2).unwrap();
+        assert_eq!((full.fg, full.bg), (Some(red), Some(red)));
+        let top_only = cell_style(&lines[1], 3).unwrap();
+        assert_eq!((top_only.fg, top_only.bg), (Some(red), Some(Color::Reset)));
+    }
+
+    #[test]
+    fn test_sixel_image_cut_at_right_edge_and_scrolls() {
+        let mut parser = Parser::new();
+        let mut performer = AnsiParser::with_size(4, 2);
+        // 48 by 32 pixels from column 1 of the last row
+        let image = format!("\x1bPq\"1;1;48;32#1;2;0;0;100{}\x1b\\", "!48~-".repeat(5));
+        parser.advance(
+            &mut performer,
+            format!("x\n\x1b[2Cy\r\x1b[C{image}").as_bytes(),
+        );
+        let lines = performer.into_lines();
+
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, vec!["x", " ▀▀▀", " ▀▀▀", ""]);
+        let style = cell_style(&lines[2], 3).unwrap();
+        assert_eq!(style.bg, Some(Color::Rgb(0, 0, 255)));
+    }
+
+    #[test]
+    fn test_other_dcs_strings_draw_nothing() {
+        let lines = AnsiParser::parse("a\x1bP$qm\x1b\\b\x1bPq\x1b\\c");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(line_text(&lines[0]), "abc");
+    }
+
+    /// Output exercising state carried across pieces: SGR, wraps, UTF-8, OSC 8, URLs
+    fn chunked_sample() -> String {
+        format!(
+            "\x1b[1;31merror\x1b[0m: see https://example.com/x\r\n\
+             \x1b]8;;https://docs.rs\x1b\\docs\x1b]8;;\x1b\\ \u{e9}\u{4e2d}\u{301}\r\n\
+             {}\x1b[32mgreen\r\nover\rOV\x1b[K\r\n\x1b[38;2;1;2;3mtail",
+            "w".repeat(90)
+        )
+    }
+
+    #[test]
+    fn test_incremental_parse_matches_full_parse_at_every_split() {
+        let text = chunked_sample();
+        let palette = TrueColorPalette::default_dark();
+        let (full, full_links) = AnsiParser::parse_with_hyperlinks(&text, &palette);
+
+        for split in 0..=text.len() {
+            let mut parser = IncrementalParser::new(&palette);
+            parser.feed(&text.as_bytes()[..split]);
+            // Looking at the lines part-way must not change what follows
+            let _ = parser.lines(0, usize::MAX);
+            parser.feed(&text.as_bytes()[split..]);
+
+            assert_eq!(parser.parsed_len(), text.len());
+            assert_eq!(parser.line_count(), full.len(), "split at {split}");
+            let (lines, links) = parser.lines(0, usize::MAX);
+            assert_eq!(lines, full, "split at {split}");
+            assert_eq!(links, full_links, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn test_incremental_parse_byte_at_a_time() {
+        let text = chunked_sample().repeat(30);
+        let palette = TrueColorPalette::default_dark();
+        let (full, _) = AnsiParser::parse_with_hyperlinks(&text, &palette);
+
+        let mut parser = IncrementalParser::new(&palette);
+        for byte in text.as_bytes() {
+            parser.feed(std::slice::from_ref(byte));
+        }
+        assert_eq!(parser.lines(0, usize::MAX).0, full);
+    }
+
+    #[test]
+    fn test_incremental_lines_window() {
+        let palette = TrueColorPalette::default_dark();
+        let mut parser = IncrementalParser::new(&palette);
+        parser.feed(b"a\r\nb\r\n\x1b]8;;https://x.org\x07c\x1b]8;;\x07\r\nd");
+        assert_eq!(parser.line_count(), 4);
+
+        // The line still being written shows its pending text
+        let (lines, links) = parser.lines(2, 5);
+        assert_eq!(lines.iter().map(line_text).collect::<Vec<_>>(), ["c", "d"]);
+        assert_eq!(links.links().len(), 1);
+        assert_eq!(links.links()[0].line, 0);
+    }
 }
@@ -0,0 +1,686 @@
+//! Background image
+//!
+//! Text cells can't show pixels, so the image is drawn through the background
+//! color of each cell. A cell is taken to cover [`CELL_WIDTH_PX`] by
+//! [`CELL_HEIGHT_PX`] pixels of the viewport, `theme.background_image.mode`
+//! decides where the image lies in the viewport, and each cell gets the
+//! average of the image pixels under it, blended into the theme background by
+//! `opacity` so text stays readable on top.
+//!
+//! With `animate` set, every frame of a GIF or APNG is decoded up front and
+//! a [`FrameClock`] steps through them at the delays the file gives, however
+//! often the screen is drawn.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::{AnimationDecoder, ImageFormat};
+use tracing::debug;
+
+use crate::colors::TrueColor;
+
+/// Width of a cell in image pixels when the image is shown at its own size
+pub const CELL_WIDTH_PX: u32 = 8;
+
+/// Height of a cell in image pixels; cells are about twice as tall as wide
+pub const CELL_HEIGHT_PX: u32 = 16;
+
+/// Points sampled across and down each cell, averaged into its color
+const SAMPLES: u32 = 4;
+
+/// Shortest time a frame is shown, so fast animations don't redraw the screen constantly
+pub const MIN_FRAME_DELAY: Duration = Duration::from_millis(50);
+
+/// Most memory the decoded frames of an animation may take; later frames are dropped
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+/// How the image is fitted to the viewport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackgroundMode {
+    /// Scale to cover the viewport, keeping the aspect ratio and cropping the overflow
+    #[default]
+    Fill,
+    /// Scale to fit inside the viewport, keeping the aspect ratio
+    Fit,
+    /// Scale to exactly the viewport, ignoring the aspect ratio
+    Stretch,
+    /// Repeat at its own size from the top left corner
+    Tile,
+    /// Show at its own size in the middle, cropped if larger than the viewport
+    Center,
+}
+
+impl BackgroundMode {
+    /// Parse the `theme.background_image.mode` config value, defaulting to fill
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "fit" => Self::Fit,
+            "stretch" => Self::Stretch,
+            "tile" => Self::Tile,
+            "center" => Self::Center,
+            _ => Self::Fill,
+        }
+    }
+}
+
+/// Where the image is drawn in the viewport, in pixels
+///
+/// The rect can reach past any edge of the viewport, which crops the image.
+/// For [`BackgroundMode::Tile`] it is the first tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageRect {
+    pub x: i64,
+    pub y: i64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where an `image` sized image goes in a `viewport` sized viewport, both `(width, height)`
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_lossless
+)]
+#[must_use]
+pub fn image_rect(mode: BackgroundMode, image: (u32, u32), viewport: (u32, u32)) -> ImageRect {
+    let (image_width, image_height) = (image.0.max(1), image.1.max(1));
+    let (view_width, view_height) = viewport;
+
+    let (width, height) = match mode {
+        BackgroundMode::Stretch => (view_width, view_height),
+        BackgroundMode::Tile | BackgroundMode::Center => (image_width, image_height),
+        BackgroundMode::Fit | BackgroundMode::Fill => {
+            let scale_x = f64::from(view_width) / f64::from(image_width);
+            let scale_y = f64::from(view_height) / f64::from(image_height);
+            let scale = if mode == BackgroundMode::Fit {
+                scale_x.min(scale_y)
+            } else {
+                scale_x.max(scale_y)
+            };
+            (
+                ((f64::from(image_width) * scale).round() as u32).max(1),
+                ((f64::from(image_height) * scale).round() as u32).max(1),
+            )
+        }
+    };
+
+    let (x, y) = if mode == BackgroundMode::Tile {
+        (0, 0)
+    } else {
+        (
+            (i64::from(view_width) - i64::from(width)) / 2,
+            (i64::from(view_height) - i64::from(height)) / 2,
+        )
+    };
+    ImageRect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+/// What the cell colors were last worked out for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellLayout {
+    pub mode: BackgroundMode,
+    pub cols: u16,
+    pub rows: u16,
+    /// Color the image is blended into
+    pub base: TrueColor,
+    /// How much of the image shows, from 0 to 1
+    pub opacity: f32,
+}
+
+/// One frame of the image, how long it's shown, and the cell colors last made from it
+struct Frame {
+    /// RGBA, row by row
+    pixels: Vec<u8>,
+    delay: Duration,
+    cells: Option<(CellLayout, Vec<TrueColor>)>,
+}
+
+/// A decoded background image, one frame unless it's animated
+pub struct BackgroundImage {
+    frames: Vec<Frame>,
+    width: u32,
+    height: u32,
+}
+
+impl BackgroundImage {
+    /// Load and decode the image at `path`
+    ///
+    /// With `animate`, every frame of a GIF or APNG is decoded; otherwise, and
+    /// for other formats, only the first.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or isn't an image
+    pub fn open(path: &str, animate: bool) -> Result<Self> {
+        let context = || format!("Failed to load background image from: {path}");
+        let format = image::io::Reader::open(path)
+            .and_then(image::io::Reader::with_guessed_format)
+            .with_context(context)?
+            .format();
+        let reader = || File::open(path).map(BufReader::new).with_context(context);
+
+        let animation = match format {
+            Some(ImageFormat::Gif) if animate => {
+                let decoder = GifDecoder::new(reader()?).with_context(context)?;
+                Some(decode_frames(decoder.into_frames()).with_context(context)?)
+            }
+            Some(ImageFormat::Png) if animate => {
+                let decoder = PngDecoder::new(reader()?).with_context(context)?;
+                if decoder.is_apng() {
+                    Some(decode_frames(decoder.apng().into_frames()).with_context(context)?)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let image = match animation {
+            Some(animation) if !animation.frames.is_empty() => animation,
+            _ => {
+                let image = image::open(path).with_context(context)?.to_rgba8();
+                let (width, height) = image.dimensions();
+                Self::from_rgba(width, height, image.into_raw())
+            }
+        };
+        debug!(
+            "Loaded background image: {}x{} with {} frame(s) from {}",
+            image.width,
+            image.height,
+            image.frames.len(),
+            path
+        );
+        Ok(image)
+    }
+
+    /// A still image from RGBA pixels, row by row
+    #[must_use]
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        Self::from_frames(width, height, vec![(pixels, Duration::ZERO)])
+    }
+
+    /// An animation from frames of RGBA pixels, each with how long it's shown
+    #[must_use]
+    pub fn from_frames(width: u32, height: u32, frames: Vec<(Vec<u8>, Duration)>) -> Self {
+        Self {
+            frames: frames
+                .into_iter()
+                .map(|(pixels, delay)| Frame {
+                    pixels,
+                    delay,
+                    cells: None,
+                })
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    /// How long each frame is shown
+    #[must_use]
+    pub fn delays(&self) -> Vec<Duration> {
+        self.frames.iter().map(|frame| frame.delay).collect()
+    }
+
+    /// Width and height in pixels
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Background color of every cell for `layout` showing `frame`, row by row
+    ///
+    /// Worked out again only when the layout changes, so a looping animation
+    /// is only sampled once.
+    pub fn cell_colors(&mut self, frame: usize, layout: CellLayout) -> &[TrueColor] {
+        let Some(index) = frame.checked_rem(self.frames.len()) else {
+            return &[];
+        };
+        if self.frames[index].cells.as_ref().map(|(cached, _)| *cached) != Some(layout) {
+            let colors = self.compute_cells(&self.frames[index].pixels, layout);
+            self.frames[index].cells = Some((layout, colors));
+        }
+        self.frames[index]
+            .cells
+            .as_ref()
+            .map_or(&[], |(_, colors)| colors)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn compute_cells(&self, pixels: &[u8], layout: CellLayout) -> Vec<TrueColor> {
+        let (cols, rows) = (u32::from(layout.cols), u32::from(layout.rows));
+        let rect = image_rect(
+            layout.mode,
+            (self.width, self.height),
+            (cols * CELL_WIDTH_PX, rows * CELL_HEIGHT_PX),
+        );
+        let tiled = layout.mode == BackgroundMode::Tile;
+
+        let mut colors = Vec::with_capacity((cols * rows) as usize);
+        for row in 0..rows {
+            for col in 0..cols {
+                let mut sum = [0.0f32; 3];
+                let mut weight = 0.0f32;
+                for sample in 0..SAMPLES * SAMPLES {
+                    let x = f64::from(col * CELL_WIDTH_PX)
+                        + (f64::from(sample % SAMPLES) + 0.5) * f64::from(CELL_WIDTH_PX)
+                            / f64::from(SAMPLES);
+                    let y = f64::from(row * CELL_HEIGHT_PX)
+                        + (f64::from(sample / SAMPLES) + 0.5) * f64::from(CELL_HEIGHT_PX)
+                            / f64::from(SAMPLES);
+                    let Some([r, g, b, a]) = self.pixel_at(pixels, rect, tiled, x, y) else {
+                        continue;
+                    };
+                    let alpha = f32::from(a) / 255.0;
+                    sum[0] += f32::from(r) * alpha;
+                    sum[1] += f32::from(g) * alpha;
+                    sum[2] += f32::from(b) * alpha;
+                    weight += alpha;
+                }
+
+                // Samples off the image count as transparent
+                let coverage = weight / (SAMPLES * SAMPLES) as f32;
+                let color = if weight > 0.0 {
+                    TrueColor::new(
+                        channel(sum[0] / weight),
+                        channel(sum[1] / weight),
+                        channel(sum[2] / weight),
+                    )
+                } else {
+                    layout.base
+                };
+                colors.push(layout.base.blend(color, layout.opacity * coverage));
+            }
+        }
+        colors
+    }
+
+    /// The image pixel drawn at viewport point `(x, y)`, if the image covers it
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn pixel_at(
+        &self,
+        pixels: &[u8],
+        rect: ImageRect,
+        tiled: bool,
+        x: f64,
+        y: f64,
+    ) -> Option<[u8; 4]> {
+        let mut offset_x = x - rect.x as f64;
+        let mut offset_y = y - rect.y as f64;
+        let (width, height) = (f64::from(rect.width), f64::from(rect.height));
+        if tiled {
+            offset_x = offset_x.rem_euclid(width);
+            offset_y = offset_y.rem_euclid(height);
+        }
+        if !(0.0..width).contains(&offset_x) || !(0.0..height).contains(&offset_y) {
+            return None;
+        }
+
+        let image_x =
+            ((offset_x * f64::from(self.width) / width) as u32).min(self.width.saturating_sub(1));
+        let image_y = ((offset_y * f64::from(self.height) / height) as u32)
+            .min(self.height.saturating_sub(1));
+        let start = ((image_y * self.width + image_x) * 4) as usize;
+        let pixel = pixels.get(start..start + 4)?;
+        Some([pixel[0], pixel[1], pixel[2], pixel[3]])
+    }
+}
+
+/// Decode `frames` with their delays, stopping once they'd take more than [`MAX_FRAME_BYTES`]
+fn decode_frames(frames: image::Frames<'_>) -> Result<BackgroundImage> {
+    let mut decoded = Vec::new();
+    let mut size = (0, 0);
+    let mut bytes = 0;
+    for frame in frames {
+        let frame = frame?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_secs_f64(f64::from(numer) / f64::from(denom.max(1)) / 1000.0);
+        let buffer = frame.into_buffer();
+        bytes += buffer.as_raw().len();
+        if bytes > MAX_FRAME_BYTES && !decoded.is_empty() {
+            debug!(
+                "Background animation is too large, keeping its first {} frames",
+                decoded.len()
+            );
+            break;
+        }
+        size = buffer.dimensions();
+        decoded.push((buffer.into_raw(), delay));
+    }
+    Ok(BackgroundImage::from_frames(size.0, size.1, decoded))
+}
+
+/// Which frame of an animation is showing, and when it's time for the next
+///
+/// Frames are shown for their own delays, raised to [`MIN_FRAME_DELAY`], and
+/// the animation loops. Time is kept from when each frame was due rather than
+/// when it was noticed, so slow polling doesn't make the animation drift.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameClock {
+    delays: Vec<Duration>,
+    frame: usize,
+    /// When the current frame was due
+    shown_at: Instant,
+}
+
+impl FrameClock {
+    /// Start at the first frame at `now`
+    #[must_use]
+    pub fn new(delays: &[Duration], now: Instant) -> Self {
+        Self {
+            delays: delays
+                .iter()
+                .map(|delay| (*delay).max(MIN_FRAME_DELAY))
+                .collect(),
+            frame: 0,
+            shown_at: now,
+        }
+    }
+
+    /// The frame showing
+    #[must_use]
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Move on to the frame due at `now`, skipping any that were missed
+    ///
+    /// Returns whether the frame changed. A single frame never changes.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if self.delays.len() < 2 {
+            return false;
+        }
+        let start = self.frame;
+
+        // Skip whole loops at once after a long pause
+        let cycle: Duration = self.delays.iter().sum();
+        let elapsed = now.saturating_duration_since(self.shown_at);
+        let loops = (elapsed.as_nanos() / cycle.as_nanos()) as u32;
+        self.shown_at += cycle * loops;
+
+        while now.saturating_duration_since(self.shown_at) >= self.delays[self.frame] {
+            self.shown_at += self.delays[self.frame];
+            self.frame = (self.frame + 1) % self.delays.len();
+        }
+        self.frame != start
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: (u32, u32) = (800, 400);
+
+    fn rect(x: i64, y: i64, width: u32, height: u32) -> ImageRect {
+        ImageRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_mode_from_name() {
+        assert_eq!(BackgroundMode::from_name("fit"), BackgroundMode::Fit);
+        assert_eq!(
+            BackgroundMode::from_name("stretch"),
+            BackgroundMode::Stretch
+        );
+        assert_eq!(BackgroundMode::from_name("tile"), BackgroundMode::Tile);
+        assert_eq!(BackgroundMode::from_name("center"), BackgroundMode::Center);
+        assert_eq!(BackgroundMode::from_name("fill"), BackgroundMode::Fill);
+        assert_eq!(BackgroundMode::from_name("zoom"), BackgroundMode::Fill);
+    }
+
+    #[test]
+    fn test_stretch_covers_the_viewport_exactly() {
+        for image in [(100, 100), (4000, 500)] {
+            assert_eq!(
+                image_rect(BackgroundMode::Stretch, image, VIEWPORT),
+                rect(0, 0, 800, 400)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fit_keeps_the_whole_image_in_view() {
+        // Square image in a wide viewport: bars left and right
+        assert_eq!(
+            image_rect(BackgroundMode::Fit, (100, 100), VIEWPORT),
+            rect(200, 0, 400, 400)
+        );
+        // Wide image, larger than the viewport: scaled down, bars above and below
+        assert_eq!(
+            image_rect(BackgroundMode::Fit, (3200, 800), VIEWPORT),
+            rect(0, 100, 800, 200)
+        );
+    }
+
+    #[test]
+    fn test_fill_covers_the_viewport_and_crops() {
+        assert_eq!(
+            image_rect(BackgroundMode::Fill, (100, 100), VIEWPORT),
+            rect(0, -200, 800, 800)
+        );
+        assert_eq!(
+            image_rect(BackgroundMode::Fill, (3200, 800), VIEWPORT),
+            rect(-400, 0, 1600, 400)
+        );
+    }
+
+    #[test]
+    fn test_center_keeps_own_size() {
+        assert_eq!(
+            image_rect(BackgroundMode::Center, (200, 100), VIEWPORT),
+            rect(300, 150, 200, 100)
+        );
+        // Larger than the viewport: cropped evenly on every side
+        assert_eq!(
+            image_rect(BackgroundMode::Center, (1000, 600), VIEWPORT),
+            rect(-100, -100, 1000, 600)
+        );
+    }
+
+    #[test]
+    fn test_tile_starts_at_the_corner() {
+        assert_eq!(
+            image_rect(BackgroundMode::Tile, (64, 32), VIEWPORT),
+            rect(0, 0, 64, 32)
+        );
+        assert_eq!(
+            image_rect(BackgroundMode::Tile, (1000, 600), VIEWPORT),
+            rect(0, 0, 1000, 600)
+        );
+        // An empty image still makes a tile
+        assert_eq!(
+            image_rect(BackgroundMode::Tile, (0, 0), VIEWPORT),
+            rect(0, 0, 1, 1)
+        );
+    }
+
+    /// A `width` by `height` image, left half red and right half blue
+    fn halves(width: u32, height: u32) -> BackgroundImage {
+        let pixels = (0..width * height)
+            .flat_map(|i| {
+                if i % width < width / 2 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 255]
+                }
+            })
+            .collect();
+        BackgroundImage::from_rgba(width, height, pixels)
+    }
+
+    fn layout(mode: BackgroundMode, cols: u16, rows: u16, opacity: f32) -> CellLayout {
+        CellLayout {
+            mode,
+            cols,
+            rows,
+            base: TrueColor::new(0, 0, 0),
+            opacity,
+        }
+    }
+
+    #[test]
+    fn test_cell_colors_follow_the_image() {
+        let red = TrueColor::new(255, 0, 0);
+        let blue = TrueColor::new(0, 0, 255);
+
+        let mut image = halves(16, 16);
+        let cells = image
+            .cell_colors(0, layout(BackgroundMode::Stretch, 4, 2, 1.0))
+            .to_vec();
+        assert_eq!(cells, vec![red, red, blue, blue, red, red, blue, blue]);
+
+        // Tiles repeat the image across the viewport
+        let cells = image
+            .cell_colors(0, layout(BackgroundMode::Tile, 4, 1, 1.0))
+            .to_vec();
+        assert_eq!(cells, vec![red, blue, red, blue]);
+
+        // Outside a centered image only the base shows
+        let black = TrueColor::new(0, 0, 0);
+        let cells = image
+            .cell_colors(0, layout(BackgroundMode::Center, 4, 1, 1.0))
+            .to_vec();
+        assert_eq!(cells, vec![black, red, blue, black]);
+    }
+
+    #[test]
+    fn test_opacity_blends_into_the_base() {
+        let mut image = halves(2, 2);
+        let mut layout = layout(BackgroundMode::Stretch, 2, 1, 0.5);
+        layout.base = TrueColor::new(0, 0, 0);
+        let cells = image.cell_colors(0, layout).to_vec();
+        assert_eq!(
+            cells,
+            vec![TrueColor::new(128, 0, 0), TrueColor::new(0, 0, 128)]
+        );
+
+        layout.opacity = 0.0;
+        assert!(image
+            .cell_colors(0, layout)
+            .iter()
+            .all(|&color| color == layout.base));
+
+        // Transparent pixels let the base through too
+        let mut clear = BackgroundImage::from_rgba(1, 1, vec![255, 255, 255, 0]);
+        layout.opacity = 1.0;
+        assert!(clear
+            .cell_colors(0, layout)
+            .iter()
+            .all(|&color| color == layout.base));
+    }
+
+    #[test]
+    fn test_frame_clock_follows_frame_delays() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let delays = [100, 200, 300].map(Duration::from_millis);
+        let mut clock = FrameClock::new(&delays, start);
+
+        assert_eq!(clock.frame(), 0);
+        assert!(!clock.advance(ms(99)));
+        assert!(clock.advance(ms(100)));
+        assert_eq!(clock.frame(), 1);
+        assert!(!clock.advance(ms(299)));
+        assert!(clock.advance(ms(300)));
+        assert_eq!(clock.frame(), 2);
+        // Loops back to the first frame
+        assert!(clock.advance(ms(600)));
+        assert_eq!(clock.frame(), 0);
+
+        // Late polls skip the missed frames but keep to the original schedule
+        assert!(clock.advance(ms(950)));
+        assert_eq!(clock.frame(), 2);
+        assert!(clock.advance(ms(1200)));
+        assert_eq!(clock.frame(), 0);
+
+        // Whole loops are skipped after a long pause
+        assert!(!clock.advance(ms(1200 + 600 * 1000)));
+        assert!(clock.advance(ms(1200 + 600 * 1000 + 100)));
+        assert_eq!(clock.frame(), 1);
+    }
+
+    #[test]
+    fn test_frame_clock_caps_frame_rate() {
+        let start = Instant::now();
+        let mut clock = FrameClock::new(&[Duration::ZERO, Duration::from_millis(5)], start);
+        assert!(!clock.advance(start + Duration::from_millis(10)));
+        assert!(clock.advance(start + MIN_FRAME_DELAY));
+        assert_eq!(clock.frame(), 1);
+        assert!(!clock.advance(start + MIN_FRAME_DELAY * 2 - Duration::from_millis(1)));
+
+        // A still image never moves on
+        let mut still = FrameClock::new(&[Duration::from_millis(10)], start);
+        assert!(!still.advance(start + Duration::from_secs(1)));
+        assert_eq!(still.frame(), 0);
+    }
+
+    #[test]
+    fn test_each_frame_has_its_own_colors() {
+        let red = TrueColor::new(255, 0, 0);
+        let blue = TrueColor::new(0, 0, 255);
+        let delay = Duration::from_millis(100);
+        let mut image = BackgroundImage::from_frames(
+            1,
+            1,
+            vec![(vec![255, 0, 0, 255], delay), (vec![0, 0, 255, 255], delay)],
+        );
+        assert_eq!(image.delays(), vec![delay, delay]);
+
+        let layout = layout(BackgroundMode::Stretch, 1, 1, 1.0);
+        assert_eq!(image.cell_colors(0, layout), &[red]);
+        assert_eq!(image.cell_colors(1, layout), &[blue]);
+        assert_eq!(image.cell_colors(2, layout), &[red]);
+    }
+
+    #[test]
+    fn test_open_decodes_gif_frames_only_when_animated() {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, RgbaImage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bg.gif");
+        let frames = [[255, 0, 0, 255], [0, 0, 255, 255]].map(|pixel| {
+            image::Frame::from_parts(
+                RgbaImage::from_pixel(2, 2, image::Rgba(pixel)),
+                0,
+                0,
+                Delay::from_numer_denom_ms(200, 1),
+            )
+        });
+        GifEncoder::new(File::create(&path).unwrap())
+            .encode_frames(frames)
+            .unwrap();
+        let path = path.to_str().unwrap();
+
+        let animated = BackgroundImage::open(path, true).unwrap();
+        assert_eq!(animated.size(), (2, 2));
+        assert_eq!(animated.delays(), vec![Duration::from_millis(200); 2]);
+
+        let still = BackgroundImage::open(path, false).unwrap();
+        assert_eq!(still.delays().len(), 1);
+        assert!(
+            BackgroundImage::open(dir.path().join("missing.gif").to_str().unwrap(), true).is_err()
+        );
+    }
+}
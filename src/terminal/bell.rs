@@ -0,0 +1,225 @@
+//! Terminal bell
+//!
+//! A BEL (`0x07`) from the shell rings the bell the way `terminal.bell_style`
+//! asks: by flashing the screen, with the system beep, both, or not at all.
+//! BEL also ends OSC strings such as window titles and prompt marks; those
+//! don't ring, even when a string is split across reads.
+
+use std::time::{Duration, Instant};
+
+/// How long the visual bell keeps the screen inverted
+///
+/// A frame lasts only a few milliseconds at the target frame rate, too short
+/// to notice, so the flash is timed instead of counted in frames.
+pub const FLASH_DURATION: Duration = Duration::from_millis(100);
+
+/// What ringing the bell does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BellStyle {
+    /// Nothing beyond the `on_bell` hook
+    #[default]
+    None,
+    /// Briefly invert the screen's colors
+    Visual,
+    /// Sound the system beep
+    Audible,
+    /// Flash and beep
+    Both,
+}
+
+impl BellStyle {
+    /// Parse the `terminal.bell_style` config value, defaulting to no bell
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "visual" => Self::Visual,
+            "audible" => Self::Audible,
+            "both" => Self::Both,
+            _ => Self::None,
+        }
+    }
+
+    /// Whether the bell flashes the screen
+    #[must_use]
+    pub fn flashes(self) -> bool {
+        matches!(self, Self::Visual | Self::Both)
+    }
+
+    /// Whether the bell beeps
+    #[must_use]
+    pub fn beeps(self) -> bool {
+        matches!(self, Self::Audible | Self::Both)
+    }
+}
+
+/// Where the scanner is in the escape sequences of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ScanState {
+    #[default]
+    Text,
+    /// After `ESC` in text
+    Escape,
+    /// Inside an OSC, DCS, SOS, PM or APC string
+    String,
+    /// After `ESC` inside a string, which `\` turns into the terminator
+    StringEscape,
+}
+
+/// Finds the BELs in shell output that ring the bell
+///
+/// Keeps its place between calls so strings split across reads are followed.
+#[derive(Debug, Clone, Default)]
+pub struct BellScanner {
+    state: ScanState,
+}
+
+impl BellScanner {
+    /// Whether `output` contains a BEL outside of any string
+    pub fn rings(&mut self, output: &[u8]) -> bool {
+        let mut rang = false;
+        for &byte in output {
+            self.state = match (self.state, byte) {
+                (ScanState::Text, 0x07) => {
+                    rang = true;
+                    ScanState::Text
+                }
+                (ScanState::Text, 0x1b) => ScanState::Escape,
+                (ScanState::Escape, b']' | b'P' | b'X' | b'^' | b'_') => ScanState::String,
+                (ScanState::Escape, 0x1b) => ScanState::Escape,
+                (ScanState::Escape, 0x07) => {
+                    rang = true;
+                    ScanState::Text
+                }
+                // BEL ends a string without ringing
+                (ScanState::String, 0x07) => ScanState::Text,
+                (ScanState::String, 0x1b) => ScanState::StringEscape,
+                (ScanState::StringEscape, b'\\') => ScanState::Text,
+                (ScanState::StringEscape, 0x1b) => ScanState::StringEscape,
+                (ScanState::StringEscape, 0x07) => ScanState::Text,
+                (ScanState::String | ScanState::StringEscape, _) => ScanState::String,
+                (ScanState::Text | ScanState::Escape, _) => ScanState::Text,
+            };
+        }
+        rang
+    }
+}
+
+/// The visual bell's inverted screen, shown until [`FLASH_DURATION`] passes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flash {
+    until: Option<Instant>,
+}
+
+impl Flash {
+    /// Start flashing at `now`; ringing again while flashing restarts the flash
+    pub fn start(&mut self, now: Instant) {
+        self.until = Some(now + FLASH_DURATION);
+    }
+
+    /// Whether the screen should be drawn inverted at `now`
+    #[must_use]
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.until.is_some_and(|until| now < until)
+    }
+
+    /// End a flash that has run its course
+    ///
+    /// Returns `true` once, when the flash ends, so the screen is redrawn
+    /// without it.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        if self.until.is_some_and(|until| now >= until) {
+            self.until = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// Sound the system beep
+///
+/// Windows plays its default sound; elsewhere the BEL is passed on to the
+/// terminal Furnace was started from.
+pub fn beep() {
+    #[cfg(windows)]
+    // SAFETY: MessageBeep takes no pointers and only queues a sound
+    unsafe {
+        winapi::um::winuser::MessageBeep(winapi::um::winuser::MB_OK);
+    }
+    #[cfg(not(windows))]
+    {
+        use std::io::Write;
+
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07");
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_style_from_name() {
+        assert_eq!(BellStyle::from_name("visual"), BellStyle::Visual);
+        assert_eq!(BellStyle::from_name("audible"), BellStyle::Audible);
+        assert_eq!(BellStyle::from_name("both"), BellStyle::Both);
+        assert_eq!(BellStyle::from_name("none"), BellStyle::None);
+        assert_eq!(BellStyle::from_name("loud"), BellStyle::None);
+        assert!(BellStyle::Both.flashes() && BellStyle::Both.beeps());
+        assert!(!BellStyle::Visual.beeps() && !BellStyle::Audible.flashes());
+    }
+
+    #[test]
+    fn test_string_terminators_do_not_ring() {
+        let mut scanner = BellScanner::default();
+        assert!(scanner.rings(b"\x07"));
+        assert!(scanner.rings(b"done\x07$ "));
+        assert!(!scanner.rings(b"\x1b]0;title\x07\x1b]133;A\x07$ "));
+        assert!(!scanner.rings(b"\x1bP+q544e\x07plain text"));
+        // A string ended by ST, then a real bell
+        assert!(scanner.rings(b"\x1b]2;title\x1b\\\x07"));
+        assert!(!scanner.rings(b"\x1b[31mred\x1b[0m"));
+    }
+
+    #[test]
+    fn test_strings_split_across_reads() {
+        let mut scanner = BellScanner::default();
+        assert!(!scanner.rings(b"\x1b]0;long ti"));
+        assert!(!scanner.rings(b"tle\x07"));
+        assert!(!scanner.rings(b"\x1b"));
+        assert!(!scanner.rings(b"]7;file:///tmp\x07"));
+        assert!(scanner.rings(b"\x07"));
+    }
+
+    #[test]
+    fn test_flash_timing() {
+        let start = Instant::now();
+        let mut flash = Flash::default();
+        assert!(!flash.is_active(start));
+        assert!(!flash.expire(start));
+
+        flash.start(start);
+        assert!(flash.is_active(start));
+        assert!(flash.is_active(start + FLASH_DURATION / 2));
+        // Still running, so nothing to redraw yet
+        assert!(!flash.expire(start + FLASH_DURATION / 2));
+
+        let end = start + FLASH_DURATION;
+        assert!(!flash.is_active(end));
+        // Ending asks for one redraw, and only one
+        assert!(flash.expire(end));
+        assert!(!flash.expire(end + FLASH_DURATION));
+    }
+
+    #[test]
+    fn test_ringing_again_restarts_flash() {
+        let start = Instant::now();
+        let mut flash = Flash::default();
+        flash.start(start);
+        flash.start(start + FLASH_DURATION / 2);
+        assert!(flash.is_active(start + FLASH_DURATION));
+        assert!(!flash.expire(start + FLASH_DURATION));
+        assert!(flash.expire(start + FLASH_DURATION * 3 / 2));
+    }
+}
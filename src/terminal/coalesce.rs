@@ -0,0 +1,115 @@
+//! Batching of shell output between frames
+//!
+//! The background reader sends every PTY read as its own chunk, so a burst of
+//! output (`cat bigfile`) leaves hundreds of chunks waiting by the next frame.
+//! Processing them one by one runs the sanitizer, output filters, hooks and
+//! scrollback trim once per read. The event loop instead joins the waiting
+//! chunks into batches of up to [`MAX_BATCH_BYTES`] and processes each batch
+//! once.
+
+/// Soft upper bound on the size of a batch
+///
+/// Keeps a single batch from holding up a frame for too long.
+pub const MAX_BATCH_BYTES: usize = 256 * 1024;
+
+/// Join `first` with the chunks `next` yields until the batch holds `cap` bytes
+///
+/// Stops as soon as `next` has nothing more. Chunks are never split, so a
+/// batch can exceed `cap` by less than one chunk.
+#[must_use]
+pub fn coalesce(first: Vec<u8>, mut next: impl FnMut() -> Option<Vec<u8>>, cap: usize) -> Vec<u8> {
+    let mut batch = first;
+    while batch.len() < cap {
+        match next() {
+            Some(chunk) => batch.extend_from_slice(&chunk),
+            None => break,
+        }
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io::Read;
+
+    /// PTY stand-in producing `remaining` bytes of bulk output
+    struct MockPty {
+        remaining: usize,
+        reads: usize,
+    }
+
+    impl Read for MockPty {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.remaining);
+            buf[..n].fill(b'x');
+            self.remaining -= n;
+            self.reads += 1;
+            Ok(n)
+        }
+    }
+
+    /// Read `total` bytes through a `buffer_len` buffer, returning the number of
+    /// reads and of batches the event loop would process
+    fn drain(total: usize, buffer_len: usize) -> (usize, usize) {
+        let mut pty = MockPty {
+            remaining: total,
+            reads: 0,
+        };
+        let mut buf = vec![0u8; buffer_len];
+        let mut chunks = VecDeque::new();
+        loop {
+            let n = pty.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            chunks.push_back(buf[..n].to_vec());
+        }
+
+        let mut batches = 0;
+        let mut bytes = 0;
+        while let Some(first) = chunks.pop_front() {
+            bytes += coalesce(first, || chunks.pop_front(), MAX_BATCH_BYTES).len();
+            batches += 1;
+        }
+        assert_eq!(bytes, total);
+        (pty.reads - 1, batches)
+    }
+
+    #[test]
+    fn test_coalesce_joins_waiting_chunks() {
+        let mut chunks = VecDeque::from([b"b".to_vec(), b"c".to_vec()]);
+        let batch = coalesce(b"a".to_vec(), || chunks.pop_front(), 1024);
+        assert_eq!(batch, b"abc");
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_stops_at_cap() {
+        let mut chunks = VecDeque::from([vec![0u8; 4], vec![1u8; 4], vec![2u8; 4]]);
+        let batch = coalesce(vec![9u8; 4], || chunks.pop_front(), 6);
+        // The chunk crossing the cap is kept whole
+        assert_eq!(batch.len(), 8);
+        assert_eq!(chunks.len(), 2);
+
+        // A first chunk already over the cap is passed through as is
+        let batch = coalesce(vec![0u8; 10], || chunks.pop_front(), 6);
+        assert_eq!(batch.len(), 10);
+        assert_eq!(chunks.len(), 2);
+    }
+
+    #[test]
+    fn test_bulk_output_needs_fewer_reads_and_batches() {
+        const TOTAL: usize = 4 * 1024 * 1024;
+
+        // One 4 KB read per chunk, previously each processed on its own
+        let (reads, batches) = drain(TOTAL, 4 * 1024);
+        assert_eq!(reads, 1024);
+        assert_eq!(batches, TOTAL / MAX_BATCH_BYTES);
+
+        let (reads, batches) = drain(TOTAL, 64 * 1024);
+        assert_eq!(reads, 64);
+        assert_eq!(batches, TOTAL / MAX_BATCH_BYTES);
+    }
+}
@@ -0,0 +1,301 @@
+//! Command palette state: keybinding actions, plugin commands and themes in
+//! one fuzzy-filtered list
+//!
+//! The terminal collects the entries when the palette opens, drives it from
+//! key events and runs the [`CommandPalette::selected`] entry on Enter.
+
+use crate::keybindings::Action;
+use crate::ui::autocomplete::fuzzy_match;
+
+/// Actions offered in the palette
+///
+/// Actions that carry a value, and opening the palette itself, are left out.
+pub const PALETTE_ACTIONS: &[Action] = &[
+    Action::NewTab,
+    Action::CloseTab,
+    Action::NextTab,
+    Action::PrevTab,
+    Action::SplitHorizontal,
+    Action::SplitVertical,
+    Action::FocusNextPane,
+    Action::FocusPrevPane,
+    Action::GrowPane,
+    Action::ShrinkPane,
+    Action::ScrollToTop,
+    Action::ScrollToBottom,
+    Action::Copy,
+    Action::Paste,
+    Action::Clear,
+    Action::ClearScrollback,
+    Action::Search,
+    Action::SearchNext,
+    Action::SearchPrev,
+    Action::ToggleAutocomplete,
+    Action::NextTheme,
+    Action::PrevTheme,
+    Action::ToggleResourceMonitor,
+    Action::ToggleStatsOverlay,
+    Action::ShowKeybindings,
+    Action::SaveSession,
+    Action::LoadSession,
+    Action::Interrupt,
+    Action::RestartShell,
+    Action::PrevCommand,
+    Action::NextCommand,
+    Action::CopyRich,
+];
+
+/// What selecting a palette entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteTarget {
+    /// Perform a keybinding action
+    Action(Action),
+    /// Run a plugin command, given as `plugin:command`
+    PluginCommand(String),
+    /// Switch to the named theme
+    Theme(String),
+}
+
+/// One row of the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    /// Text the query is matched against
+    pub label: String,
+    /// Shown after the label, e.g. the action's key combo
+    pub detail: String,
+    /// What selecting the entry does
+    pub target: PaletteTarget,
+}
+
+impl PaletteEntry {
+    /// Entry for `action`, showing `combo` if it is bound
+    #[must_use]
+    pub fn action(action: Action, combo: Option<String>) -> Self {
+        Self {
+            label: action.description(),
+            detail: combo.unwrap_or_default(),
+            target: PaletteTarget::Action(action),
+        }
+    }
+
+    /// Entry for a plugin command, given as `plugin:command`
+    #[must_use]
+    pub fn plugin_command(command: &str) -> Self {
+        Self {
+            label: format!("Plugin: {command}"),
+            detail: String::new(),
+            target: PaletteTarget::PluginCommand(command.to_string()),
+        }
+    }
+
+    /// Entry switching to theme `name`
+    #[must_use]
+    pub fn theme(name: &str) -> Self {
+        Self {
+            label: format!("Theme: {name}"),
+            detail: String::new(),
+            target: PaletteTarget::Theme(name.to_string()),
+        }
+    }
+}
+
+/// An entry matching the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteMatch {
+    /// Index into the palette's entries
+    pub index: usize,
+    /// Fuzzy match score; higher is better
+    pub score: i64,
+    /// Char indices of the label the query matched
+    pub positions: Vec<usize>,
+}
+
+/// Open command palette
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    /// Everything the palette can run, in display order for an empty query
+    entries: Vec<PaletteEntry>,
+    /// Text typed so far
+    query: String,
+    /// Entries matching `query`, best first
+    matches: Vec<PaletteMatch>,
+    /// Index into `matches` of the highlighted entry
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Open the palette on `entries` with an empty query
+    #[must_use]
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
+        let mut palette = Self {
+            entries,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.filter();
+        palette
+    }
+
+    /// Text typed so far
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Add a character to the query
+    pub fn push(&mut self, ch: char) {
+        self.query.push(ch);
+        self.filter();
+    }
+
+    /// Remove the last character of the query
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.filter();
+    }
+
+    /// Move the selection by `delta`, wrapping at either end
+    pub fn move_selection(&mut self, delta: isize) {
+        if !self.matches.is_empty() {
+            let len = self.matches.len() as isize;
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Entries matching the query, best first
+    pub fn matches(&self) -> impl Iterator<Item = (&PaletteEntry, &PaletteMatch)> {
+        self.matches.iter().map(|m| (&self.entries[m.index], m))
+    }
+
+    /// Position of the highlighted entry in [`Self::matches`]
+    #[must_use]
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    /// The highlighted entry, if anything matches
+    #[must_use]
+    pub fn selected(&self) -> Option<&PaletteEntry> {
+        self.matches
+            .get(self.selected)
+            .map(|m| &self.entries[m.index])
+    }
+
+    /// Re-match the entries against the query and select the best match
+    fn filter(&mut self) {
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let (score, positions) = fuzzy_match(&self.query, &entry.label)?;
+                Some(PaletteMatch {
+                    index,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+        // Stable, so equal scores keep entry order
+        self.matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combined() -> Vec<PaletteEntry> {
+        let mut entries: Vec<PaletteEntry> = PALETTE_ACTIONS
+            .iter()
+            .map(|action| PaletteEntry::action(action.clone(), None))
+            .collect();
+        entries.push(PaletteEntry::plugin_command("git:status"));
+        entries.push(PaletteEntry::theme("dark"));
+        entries.push(PaletteEntry::theme("nord"));
+        entries
+    }
+
+    fn labels(palette: &CommandPalette) -> Vec<String> {
+        palette
+            .matches()
+            .map(|(entry, _)| entry.label.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_query_lists_everything_in_order() {
+        let palette = CommandPalette::new(combined());
+        assert_eq!(labels(&palette).len(), PALETTE_ACTIONS.len() + 3);
+        assert_eq!(labels(&palette)[0], "New tab");
+        assert_eq!(
+            palette.selected().unwrap().target,
+            PaletteTarget::Action(Action::NewTab)
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_filter_across_kinds() {
+        let mut palette = CommandPalette::new(combined());
+        for ch in "nord".chars() {
+            palette.push(ch);
+        }
+        assert_eq!(
+            palette.selected().unwrap().target,
+            PaletteTarget::Theme("nord".to_string())
+        );
+
+        palette = CommandPalette::new(combined());
+        for ch in "gst".chars() {
+            palette.push(ch);
+        }
+        assert_eq!(
+            palette.selected().unwrap().target,
+            PaletteTarget::PluginCommand("git:status".to_string())
+        );
+
+        // Word starts rank "Toggle resource monitor" first for "trm"
+        palette = CommandPalette::new(combined());
+        for ch in "trm".chars() {
+            palette.push(ch);
+        }
+        assert_eq!(labels(&palette)[0], "Toggle resource monitor");
+        let (_, best) = palette.matches().next().unwrap();
+        assert_eq!(best.positions, vec![0, 7, 16]);
+
+        // Every match contains the query's characters in order
+        palette = CommandPalette::new(combined());
+        for ch in "theme".chars() {
+            palette.push(ch);
+        }
+        let found = labels(&palette);
+        assert!(found.contains(&"Theme: dark".to_string()));
+        assert!(found.contains(&"Next theme".to_string()));
+        assert!(!found.contains(&"New tab".to_string()));
+    }
+
+    #[test]
+    fn test_selection_wraps_and_resets_on_typing() {
+        let mut palette = CommandPalette::new(combined());
+        palette.move_selection(-1);
+        assert_eq!(
+            palette.selected().unwrap().target,
+            PaletteTarget::Theme("nord".to_string())
+        );
+
+        palette.push('x');
+        palette.push('y');
+        palette.push('z');
+        assert!(palette.selected().is_none());
+        palette.move_selection(1);
+        assert!(palette.selected().is_none());
+
+        palette.pop();
+        palette.pop();
+        palette.pop();
+        assert_eq!(palette.query(), "");
+        assert_eq!(palette.selected_index(), 0);
+    }
+}
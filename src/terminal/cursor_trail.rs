@@ -0,0 +1,291 @@
+//! Cursor trail
+//!
+//! The cells the cursor recently left are tinted with `theme.cursor_trail`'s
+//! color, which fades back to the theme background over `fade_ms`. Positions
+//! are kept newest last, each with the time the cursor arrived there, so a
+//! cell's age is counted from when the cursor arrived at the next one.
+
+use std::time::{Duration, Instant};
+
+use crate::colors::TrueColor;
+use crate::config::CursorTrailConfig;
+
+/// A cursor position and when the cursor arrived there
+pub type TrailPoint = (u16, u16, Instant);
+
+/// Shades between the background and the full trail color
+const FADE_STEPS: usize = 16;
+
+/// Opacity of a trail color given without an alpha channel
+const DEFAULT_ALPHA: f32 = 0.5;
+
+/// How quickly a trail cell fades as it ages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeMode {
+    /// At a steady rate
+    Linear,
+    /// Quickly at first, then slowly
+    #[default]
+    Exponential,
+    /// Slowly at first, then quickly
+    Smooth,
+}
+
+impl FadeMode {
+    /// Parse the `fade_mode` config value, defaulting to exponential
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "linear" => Self::Linear,
+            "smooth" => Self::Smooth,
+            _ => Self::Exponential,
+        }
+    }
+
+    /// Strength of a cell with `life` of its fade left, both from 0 to 1
+    #[must_use]
+    pub fn strength(self, life: f32) -> f32 {
+        let life = life.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => life,
+            Self::Exponential => life * life,
+            Self::Smooth => 1.0 - (1.0 - life).powi(3),
+        }
+    }
+}
+
+/// The trail color and its fade, resolved from the config
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailStyle {
+    /// From the background at index 0 to the trail color at its opacity
+    ramp: Vec<TrueColor>,
+    fade: Duration,
+    mode: FadeMode,
+}
+
+impl TrailStyle {
+    /// Style for `config` drawn over `background`
+    ///
+    /// A color that doesn't parse falls back to the default green.
+    #[must_use]
+    pub fn new(config: &CursorTrailConfig, background: TrueColor) -> Self {
+        let (color, alpha) = parse_color(&config.color)
+            .or_else(|| parse_color(&CursorTrailConfig::default().color))
+            .unwrap_or((TrueColor::new(0, 255, 0), DEFAULT_ALPHA));
+        Self {
+            ramp: TrueColor::gradient(background, background.blend(color, alpha), FADE_STEPS),
+            fade: fade_duration(config),
+            mode: FadeMode::from_name(&config.fade_mode),
+        }
+    }
+
+    /// Color of a cell the cursor left `age` ago, or `None` once it has faded out
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    #[must_use]
+    pub fn color(&self, age: Duration) -> Option<TrueColor> {
+        let life = 1.0 - age.as_secs_f32() / self.fade.as_secs_f32();
+        let strength = self.mode.strength(life);
+        let step = (strength * (self.ramp.len() - 1) as f32).round() as usize;
+        (step > 0).then(|| self.ramp[step])
+    }
+}
+
+/// How long trail cells take to fade out
+#[must_use]
+pub fn fade_duration(config: &CursorTrailConfig) -> Duration {
+    Duration::from_millis(config.fade_ms.max(1))
+}
+
+/// `#RRGGBB` or `#RRGGBBAA` as a color and its opacity from 0 to 1
+#[must_use]
+pub fn parse_color(hex: &str) -> Option<(TrueColor, f32)> {
+    let digits = hex.trim_start_matches('#');
+    let (rgb, alpha) = match digits.len() {
+        6 => (digits, DEFAULT_ALPHA),
+        8 => {
+            let alpha = u8::from_str_radix(digits.get(6..)?, 16).ok()?;
+            (&digits[..6], f32::from(alpha) / 255.0)
+        }
+        _ => return None,
+    };
+    Some((TrueColor::from_hex(rgb).ok()?, alpha))
+}
+
+/// Note the cursor at `(col, row)` at `now` and forget cells that have faded out
+///
+/// Moves closer together than `animation_speed` replace the newest position
+/// rather than adding one, and at most `length` cells are kept behind the
+/// cursor.
+pub fn record(
+    points: &mut Vec<TrailPoint>,
+    (col, row): (u16, u16),
+    now: Instant,
+    config: &CursorTrailConfig,
+) {
+    match points.last_mut() {
+        Some(last) if (last.0, last.1) == (col, row) => {}
+        Some(last)
+            if now.duration_since(last.2) < Duration::from_millis(config.animation_speed) =>
+        {
+            (last.0, last.1) = (col, row);
+        }
+        _ => points.push((col, row, now)),
+    }
+    prune(points, now, fade_duration(config), config.length);
+}
+
+/// Drop cells that faded out before `now`, and all but the newest `length` behind the cursor
+pub fn prune(points: &mut Vec<TrailPoint>, now: Instant, fade: Duration, length: usize) {
+    // The cursor left a cell when it arrived at the next one
+    let faded = points
+        .windows(2)
+        .take_while(|pair| now.duration_since(pair[1].2) >= fade)
+        .count();
+    let excess = points.len().saturating_sub(length + 1);
+    points.drain(..faded.max(excess));
+}
+
+/// Each cell behind the cursor with the time the cursor left it, oldest first
+pub fn cells(points: &[TrailPoint]) -> impl Iterator<Item = TrailPoint> + '_ {
+    points
+        .windows(2)
+        .map(|pair| (pair[0].0, pair[0].1, pair[1].2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trail_config(length: usize, fade_ms: u64) -> CursorTrailConfig {
+        CursorTrailConfig {
+            enabled: true,
+            length,
+            color: "#FF0000FF".to_string(),
+            fade_mode: "linear".to_string(),
+            animation_speed: 0,
+            fade_ms,
+            ..CursorTrailConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_color_with_and_without_alpha() {
+        assert_eq!(
+            parse_color("#ABCDEF"),
+            Some((TrueColor::new(0xAB, 0xCD, 0xEF), DEFAULT_ALPHA))
+        );
+        assert_eq!(
+            parse_color("#00FF00FF"),
+            Some((TrueColor::new(0, 255, 0), 1.0))
+        );
+        assert_eq!(parse_color("00FF0000").map(|(_, alpha)| alpha), Some(0.0));
+        assert_eq!(parse_color("#GGFF00"), None);
+        assert_eq!(parse_color("#00FF00F"), None);
+    }
+
+    #[test]
+    fn test_fade_modes() {
+        for mode in [FadeMode::Linear, FadeMode::Exponential, FadeMode::Smooth] {
+            assert_eq!(mode.strength(1.0), 1.0);
+            assert_eq!(mode.strength(0.0), 0.0);
+            assert_eq!(mode.strength(-0.5), 0.0);
+        }
+        assert_eq!(FadeMode::Linear.strength(0.5), 0.5);
+        assert_eq!(FadeMode::Exponential.strength(0.5), 0.25);
+        assert_eq!(FadeMode::Smooth.strength(0.5), 0.875);
+        assert_eq!(FadeMode::from_name("smooth"), FadeMode::Smooth);
+        assert_eq!(FadeMode::from_name("bouncy"), FadeMode::Exponential);
+    }
+
+    #[test]
+    fn test_color_fades_to_background_with_age() {
+        let background = TrueColor::new(0, 0, 0);
+        let style = TrailStyle::new(&trail_config(10, 1000), background);
+
+        assert_eq!(style.color(Duration::ZERO), Some(TrueColor::new(255, 0, 0)));
+        // Halfway through the fade, halfway to the background
+        let half = style.color(Duration::from_millis(500)).unwrap();
+        assert!((120..=136).contains(&half.r), "{half}");
+        assert_eq!((half.g, half.b), (0, 0));
+        // Older cells are never brighter
+        let older = style.color(Duration::from_millis(800)).unwrap();
+        assert!(older.r < half.r);
+        assert_eq!(style.color(Duration::from_millis(1000)), None);
+        assert_eq!(style.color(Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn test_alpha_limits_the_strongest_shade() {
+        let background = TrueColor::new(0, 0, 100);
+        let mut config = trail_config(10, 1000);
+        config.color = "#FFFFFF00".to_string();
+        // Fully transparent leaves the background as it was
+        assert_eq!(
+            TrailStyle::new(&config, background).color(Duration::ZERO),
+            Some(background)
+        );
+
+        config.color = "#0000FF".to_string();
+        let newest = TrailStyle::new(&config, background)
+            .color(Duration::ZERO)
+            .unwrap();
+        assert_eq!(newest, background.blend(TrueColor::new(0, 0, 255), 0.5));
+    }
+
+    #[test]
+    fn test_record_prunes_faded_and_excess_cells() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let config = trail_config(3, 100);
+        let mut points = Vec::new();
+
+        record(&mut points, (0, 0), ms(0), &config);
+        // Standing still adds nothing
+        record(&mut points, (0, 0), ms(10), &config);
+        assert_eq!(points, vec![(0, 0, ms(0))]);
+        assert_eq!(cells(&points).count(), 0);
+
+        record(&mut points, (1, 0), ms(20), &config);
+        record(&mut points, (2, 0), ms(40), &config);
+        assert_eq!(
+            cells(&points).collect::<Vec<_>>(),
+            vec![(0, 0, ms(20)), (1, 0, ms(40))]
+        );
+
+        // Only `length` cells stay behind the cursor
+        record(&mut points, (3, 0), ms(60), &config);
+        record(&mut points, (4, 0), ms(80), &config);
+        assert_eq!(
+            cells(&points).map(|(col, _, _)| col).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        // Cells go once they have faded, counted from when the cursor left them
+        prune(&mut points, ms(150), Duration::from_millis(100), 3);
+        assert_eq!(
+            cells(&points).map(|(col, _, _)| col).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        prune(&mut points, ms(500), Duration::from_millis(100), 3);
+        assert_eq!(points, vec![(4, 0, ms(80))]);
+    }
+
+    #[test]
+    fn test_quick_moves_replace_the_newest_position() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut config = trail_config(10, 1000);
+        config.animation_speed = 16;
+        let mut points = Vec::new();
+
+        record(&mut points, (0, 0), ms(0), &config);
+        record(&mut points, (1, 0), ms(5), &config);
+        assert_eq!(points, vec![(1, 0, ms(0))]);
+        record(&mut points, (2, 0), ms(20), &config);
+        assert_eq!(points, vec![(1, 0, ms(0)), (2, 0, ms(20))]);
+    }
+}
@@ -0,0 +1,366 @@
+//! Adaptive frame rate under heavy output
+//!
+//! Every read from the shell marks the screen dirty, so a flood of output
+//! (`cat bigfile`, a chatty build) would redraw at the full frame rate even
+//! though nobody can read that fast, and each redraw takes time away from
+//! taking in output. A [`FramePacer`] measures how fast output arrived since
+//! the last frame and, above [`BUSY_BYTES_PER_SEC`], waits longer between
+//! frames so more reads are batched into each one, down to [`MIN_BUSY_FPS`].
+//! A frame with no new output behind it, such as a keystroke once the flood
+//! stops, is drawn at the full rate again.
+//!
+//! [`FrameStats`] keeps the frames actually drawn for the stats overlay.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+/// Output rate above which frames are spaced out
+pub const BUSY_BYTES_PER_SEC: f64 = 512.0 * 1024.0;
+
+/// Lowest frame rate while output floods in
+pub const MIN_BUSY_FPS: u32 = 30;
+
+/// How often frame counts are logged
+const STATS_PERIOD: Duration = Duration::from_secs(5);
+
+/// Decides which frames to draw, given how fast output is arriving
+#[derive(Debug)]
+pub struct FramePacer {
+    /// Highest frame rate, from `terminal.max_fps`
+    max_fps: u32,
+    /// When the last frame was drawn
+    last_frame: Option<Instant>,
+    /// Output bytes taken in since the last frame
+    pending_bytes: usize,
+    /// Output rate measured at the last frame, in bytes per second
+    rate: f64,
+    /// Frames drawn since the counts were last logged
+    drawn: u64,
+    /// Dirty frames held back since the counts were last logged
+    skipped: u64,
+    /// When the counts were last logged
+    stats_since: Option<Instant>,
+}
+
+impl FramePacer {
+    /// Create a pacer drawing at most `max_fps` frames a second
+    #[must_use]
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            max_fps: max_fps.max(1),
+            last_frame: None,
+            pending_bytes: 0,
+            rate: 0.0,
+            drawn: 0,
+            skipped: 0,
+            stats_since: None,
+        }
+    }
+
+    /// Time between frames at the full rate, which the event loop ticks at
+    #[must_use]
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.max_fps
+    }
+
+    /// Count output taken in for the next frame
+    pub fn record_output(&mut self, bytes: usize) {
+        self.pending_bytes += bytes;
+    }
+
+    /// Time to wait between frames while output arrives at `rate` bytes per second
+    #[must_use]
+    pub fn interval_for_rate(&self, rate: f64) -> Duration {
+        if rate <= BUSY_BYTES_PER_SEC {
+            return self.frame_interval();
+        }
+        // Fewer frames the faster output comes, so each takes in as much
+        let max_fps = f64::from(self.max_fps);
+        let min_fps = f64::from(MIN_BUSY_FPS.min(self.max_fps));
+        let fps = (max_fps * BUSY_BYTES_PER_SEC / rate).clamp(min_fps, max_fps);
+        Duration::from_secs_f64(1.0 / fps)
+    }
+
+    /// Whether to draw a frame at `now`, given whether anything changed
+    ///
+    /// Call once per event loop tick. A changed screen that isn't drawn counts
+    /// as a dropped frame.
+    pub fn frame_due(&mut self, now: Instant, dirty: bool) -> bool {
+        if !dirty {
+            return false;
+        }
+        let due = match self.last_frame {
+            Some(last) => {
+                let elapsed = now.saturating_duration_since(last);
+                // Nothing read since the last frame: back to the full rate
+                let rate = if self.pending_bytes == 0 {
+                    0.0
+                } else {
+                    #[allow(clippy::cast_precision_loss)]
+                    let bytes = self.pending_bytes as f64;
+                    bytes / elapsed.max(self.frame_interval()).as_secs_f64()
+                };
+                self.rate = rate;
+                elapsed >= self.interval_for_rate(rate)
+            }
+            None => true,
+        };
+
+        if due {
+            self.last_frame = Some(now);
+            self.pending_bytes = 0;
+            self.drawn += 1;
+        } else {
+            self.skipped += 1;
+        }
+        self.log_stats(now);
+        due
+    }
+
+    /// Share of changed frames held back since the counts were last logged
+    #[must_use]
+    pub fn drop_rate(&self) -> f64 {
+        let total = self.drawn + self.skipped;
+        if total == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.skipped as f64 / total as f64;
+        rate
+    }
+
+    /// Log the frame counts every [`STATS_PERIOD`] and start counting again
+    fn log_stats(&mut self, now: Instant) {
+        let since = *self.stats_since.get_or_insert(now);
+        if now.saturating_duration_since(since) < STATS_PERIOD {
+            return;
+        }
+        debug!(
+            "Frames: {} drawn, {} dropped ({:.1}% drop rate), output {:.0} KiB/s",
+            self.drawn,
+            self.skipped,
+            self.drop_rate() * 100.0,
+            self.rate / 1024.0
+        );
+        self.drawn = 0;
+        self.skipped = 0;
+        self.stats_since = Some(now);
+    }
+}
+
+/// Span of recent frames the stats overlay's frame rate covers
+pub const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+/// How often the stats overlay is drawn again when nothing else changes
+pub const STATS_REFRESH: Duration = Duration::from_millis(500);
+
+/// Frames drawn and how long they took, for the stats overlay
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    /// When each frame in the last [`FPS_WINDOW`] was drawn and how long it took
+    recent: VecDeque<(Instant, Duration)>,
+    /// Frames drawn in all
+    total: u64,
+}
+
+impl FrameStats {
+    /// Create stats with no frames drawn
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a frame drawn at `at` that took `render_time`
+    pub fn record(&mut self, at: Instant, render_time: Duration) {
+        self.recent.push_back((at, render_time));
+        self.total += 1;
+        self.prune(at);
+    }
+
+    /// Forget frames that left the window by `now`
+    fn prune(&mut self, now: Instant) {
+        while let Some(&(at, _)) = self.recent.front() {
+            if now.saturating_duration_since(at) < FPS_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+    }
+
+    /// Frames a second over the [`FPS_WINDOW`] up to `now`
+    #[must_use]
+    pub fn fps(&self, now: Instant) -> f64 {
+        let frames = self
+            .recent
+            .iter()
+            .filter(|&&(at, _)| now.saturating_duration_since(at) < FPS_WINDOW)
+            .count();
+        #[allow(clippy::cast_precision_loss)]
+        let frames = frames as f64;
+        frames / FPS_WINDOW.as_secs_f64()
+    }
+
+    /// Frames drawn in all
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// How long the last frame took to draw
+    #[must_use]
+    pub fn last_render_time(&self) -> Duration {
+        self.recent.back().map_or(Duration::ZERO, |&(_, time)| time)
+    }
+
+    /// Mean time to draw the frames in the window
+    #[must_use]
+    pub fn average_render_time(&self) -> Duration {
+        let count = u32::try_from(self.recent.len()).unwrap_or(u32::MAX);
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        self.recent.iter().map(|&(_, time)| time).sum::<Duration>() / count
+    }
+
+    /// Whether the last frame is older than [`STATS_REFRESH`] at `now`
+    #[must_use]
+    pub fn stale(&self, now: Instant) -> bool {
+        self.recent
+            .back()
+            .is_none_or(|&(at, _)| now.saturating_duration_since(at) >= STATS_REFRESH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tick a pacer at its full rate for `ticks` frames, reading `bytes_per_tick`
+    /// before each, and return how many frames were drawn
+    fn run(pacer: &mut FramePacer, start: Instant, ticks: u32, bytes_per_tick: usize) -> u32 {
+        let tick = pacer.frame_interval();
+        let mut drawn = 0;
+        for i in 0..ticks {
+            pacer.record_output(bytes_per_tick);
+            if pacer.frame_due(start + tick * i, true) {
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+
+    #[test]
+    fn test_full_rate_when_output_is_light() {
+        let mut pacer = FramePacer::new(100);
+        assert_eq!(pacer.frame_interval(), Duration::from_millis(10));
+        // 1 KiB every 10ms is 100 KiB/s: every frame is drawn
+        assert_eq!(run(&mut pacer, Instant::now(), 100, 1024), 100);
+        assert_eq!(pacer.drop_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_interval_grows_with_rate_down_to_minimum() {
+        let pacer = FramePacer::new(100);
+        assert_eq!(pacer.interval_for_rate(0.0), Duration::from_millis(10));
+        assert_eq!(
+            pacer.interval_for_rate(BUSY_BYTES_PER_SEC),
+            Duration::from_millis(10)
+        );
+        // Twice the busy rate halves the frame rate
+        let interval = pacer.interval_for_rate(BUSY_BYTES_PER_SEC * 2.0);
+        assert!((interval.as_secs_f64() - 0.02).abs() < 1e-9);
+        // Never below the minimum frame rate
+        let interval = pacer.interval_for_rate(BUSY_BYTES_PER_SEC * 1000.0);
+        assert!((interval.as_secs_f64() - 1.0 / f64::from(MIN_BUSY_FPS)).abs() < 1e-9);
+
+        // A cap below the minimum busy rate is kept as it is
+        let slow = FramePacer::new(10);
+        let interval = slow.interval_for_rate(BUSY_BYTES_PER_SEC * 1000.0);
+        assert_eq!(interval, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_read_burst_skips_frames_then_snaps_back() {
+        let mut pacer = FramePacer::new(100);
+        let start = Instant::now();
+
+        // 64 KiB every 10ms is 6.4 MiB/s: frames drop to the 30 FPS floor,
+        // which at 10ms ticks is every fourth tick
+        let drawn = run(&mut pacer, start, 300, 64 * 1024);
+        assert_eq!(drawn, 75);
+        assert!(pacer.drop_rate() > 0.6);
+
+        // The flood stops: the next change is drawn on the next tick
+        let after = start + Duration::from_secs(3);
+        assert!(pacer.frame_due(after, true));
+        assert!(pacer.frame_due(after + pacer.frame_interval(), true));
+    }
+
+    #[test]
+    fn test_clean_frames_are_neither_drawn_nor_dropped() {
+        let mut pacer = FramePacer::new(100);
+        let start = Instant::now();
+        assert!(!pacer.frame_due(start, false));
+        assert_eq!(pacer.drop_rate(), 0.0);
+        assert!(pacer.frame_due(start, true));
+        // Too soon after the last frame, even with nothing read
+        assert!(!pacer.frame_due(start + Duration::from_millis(5), true));
+        assert_eq!(pacer.drop_rate(), 0.5);
+    }
+
+    /// Stats with a frame every `interval` for `frames` frames from `start`
+    fn stats_at_interval(start: Instant, interval: Duration, frames: u32) -> FrameStats {
+        let mut stats = FrameStats::new();
+        for i in 0..frames {
+            stats.record(start + interval * i, Duration::from_millis(2));
+        }
+        stats
+    }
+
+    #[test]
+    fn test_fps_counts_frames_in_the_window() {
+        let start = Instant::now();
+        // Two seconds at 100 FPS: only the last second counts
+        let interval = Duration::from_millis(10);
+        let stats = stats_at_interval(start, interval, 200);
+        let last = start + interval * 199;
+        assert_eq!(stats.fps(last), 100.0);
+        assert_eq!(stats.total(), 200);
+        assert_eq!(stats.recent.len(), 100);
+
+        // Frames stop: the rate falls as they leave the window
+        assert_eq!(stats.fps(last + FPS_WINDOW / 2), 50.0);
+        assert_eq!(stats.fps(last + FPS_WINDOW), 0.0);
+    }
+
+    #[test]
+    fn test_fps_follows_uneven_frames() {
+        let start = Instant::now();
+        let mut stats = FrameStats::new();
+        assert_eq!(stats.fps(start), 0.0);
+        // A burst of 10 frames, then one frame 800ms later
+        for i in 0..10 {
+            stats.record(start + Duration::from_millis(i), Duration::from_millis(1));
+        }
+        let later = start + Duration::from_millis(800);
+        stats.record(later, Duration::from_millis(5));
+        assert_eq!(stats.fps(later), 11.0);
+        // The burst leaves the window, the later frame stays
+        assert_eq!(stats.fps(start + Duration::from_millis(1500)), 1.0);
+
+        assert_eq!(stats.last_render_time(), Duration::from_millis(5));
+        assert_eq!(stats.average_render_time(), Duration::from_millis(15) / 11);
+    }
+
+    #[test]
+    fn test_stale_after_refresh_period() {
+        let start = Instant::now();
+        let mut stats = FrameStats::new();
+        assert!(stats.stale(start));
+        stats.record(start, Duration::ZERO);
+        assert!(!stats.stale(start + STATS_REFRESH / 2));
+        assert!(stats.stale(start + STATS_REFRESH));
+    }
+}
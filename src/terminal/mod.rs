@@ -3,6 +3,13 @@
 //!
 //! This module contains the main Terminal struct and its supporting modules:
 //! - `ansi_parser`: ANSI escape code parser for colors and styling
+//! - `command_palette`: Fuzzy-filtered list of actions, plugin commands and themes
+//! - `paste`: Confirmation state for multi-line pastes
+//! - `renderer`: CPU (ratatui) and GPU (wgpu) rendering backends
+//! - `sanitize`: Removal of risky escape sequences from shell output
+//! - `search`: Scrollback search matching and highlighting
+//! - `session_prompt`: Save-as and saved session picker prompt state
+//! - `wrap`: Line wrapping and truncation for rendered output
 //!
 //! # Architecture
 //! The terminal is structured to separate concerns:
@@ -12,14 +19,45 @@
 //! - Tab/session management
 
 pub mod ansi_parser;
+pub mod background;
+pub mod bell;
+pub mod coalesce;
+pub mod command_palette;
+pub mod cursor_trail;
+pub mod frame_pacer;
+pub mod modes;
+pub mod mouse;
+pub mod notifications;
+pub mod osc133;
+pub mod osc52;
+pub mod osc7;
+pub mod pane_tree;
+pub mod paste;
+pub mod readers;
+pub mod renderer;
+pub mod sanitize;
+pub mod search;
+pub mod session_prompt;
+pub mod sixel;
+pub mod spill;
+pub mod tab_bar;
+pub mod title;
+#[cfg(feature = "gpu")]
+pub mod window_input;
+pub mod wrap;
 
 use anyhow::{Context, Result};
 #[allow(unused_imports)]
 use crossterm::{
     cursor::Show,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
 };
 #[allow(unused_imports)]
 use ratatui::{
@@ -27,44 +65,52 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Tabs},
+    widgets::{Block, Borders, Clear, Paragraph},
     Terminal as RatatuiTerminal,
 };
+use regex::Regex;
 use std::borrow::Cow;
+use std::cell::RefCell;
 #[allow(unused_imports)]
 use std::io;
+use std::rc::Rc;
 #[allow(unused_imports)]
 use tokio::time::{interval, Duration};
 use tracing::{debug, info, warn};
 #[allow(unused_imports)]
-use unicode_width::UnicodeWidthStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::colors::TrueColorPalette;
-use crate::config::Config;
-use crate::hooks::HooksExecutor;
-use crate::keybindings::KeybindingManager;
-use crate::progress_bar::ProgressBar;
-use crate::session::SessionManager;
-use crate::shell::ShellSession;
+use crate::config::{Config, ConfigDiff, ConfigError, ConfigWatcher};
+use crate::hooks::{HookView, HooksExecutor};
+use crate::keybindings::{Action, KeyResolution, KeybindingManager};
+use crate::plugins::{self, PluginManager};
+use crate::progress_bar::{ProgressBar, SpinnerStyle};
+use crate::session::{AutosaveTimer, SavedSession, SessionManager, AUTOSAVE_ID, AUTOSAVE_NAME};
+use crate::shell::{ShellSession, Signal};
 use crate::ui::{
-    autocomplete::Autocomplete, resource_monitor::ResourceMonitor, themes::ThemeManager,
+    autocomplete::{fuzzy_match, Autocomplete},
+    resource_monitor::{Metric, ProcessStats, ResourceMonitor},
+    themes::ThemeManager,
 };
 
-use self::ansi_parser::AnsiParser;
+use self::ansi_parser::{AnsiParser, HyperlinkMap, IncrementalParser};
+use self::command_palette::{CommandPalette, PaletteEntry, PaletteTarget, PALETTE_ACTIONS};
+use self::frame_pacer::{FramePacer, FrameStats};
+use self::pane_tree::{PaneTree, SplitDirection, MAX_SPLIT_RATIO, MIN_SPLIT_RATIO};
+use self::paste::PendingPaste;
+use self::readers::{OutputReader, OutputReaders};
+use self::renderer::{CpuRenderer, Renderer, RendererKind};
+use self::session_prompt::SessionPrompt;
+use self::tab_bar::{TabSpan, TabTarget};
 
-/// Target FPS for GPU-accelerated rendering
-const TARGET_FPS: u64 = 170;
+/// Lines scrolled per mouse wheel notch
+const MOUSE_SCROLL_LINES: usize = 3;
 
-/// Read buffer size optimized for typical terminal output
-/// Using 4KB as it's a common page size and provides good balance
-const READ_BUFFER_SIZE: usize = 4 * 1024;
-
-/// Notification display duration in seconds
-const NOTIFICATION_DURATION_SECS: u64 = 2;
-
-/// Minimum popup size to prevent collapse (for future UI features)
-const _MIN_POPUP_WIDTH: u16 = 20;
-const _MIN_POPUP_HEIGHT: u16 = 5;
+/// Minimum popup size to prevent collapse
+const MIN_POPUP_WIDTH: u16 = 20;
+const MIN_POPUP_HEIGHT: u16 = 5;
 
 /// Maximum command display length in progress bar (Bug #16)
 #[allow(dead_code)]
@@ -74,7 +120,6 @@ const MAX_PROGRESS_COMMAND_LEN: usize = 40;
 const INITIAL_OUTPUT_TIMEOUT_MS: u64 = 1000;
 
 /// Polling interval for initial output in milliseconds
-#[allow(dead_code)]
 const INITIAL_OUTPUT_POLL_INTERVAL_MS: u64 = 20;
 
 /// Extra read attempts after receiving initial output
@@ -95,8 +140,17 @@ const PROMPT_TRIGGER_READ_ATTEMPTS: usize = 10;
 #[allow(dead_code)]
 const INITIAL_OUTPUT_SETTLE_MS: u64 = 100;
 
+/// Characters a shell prompt ends with, checked against the last output line
+/// (bash/zsh/root/fish/`PowerShell`/Python REPL and common themes)
+const PROMPT_ENDINGS: &[&str] = &["$", "#", "%", ">", "❯", "λ", "..."];
+
+/// Clears the shell's input line, so a command a plugin handled never runs
+#[cfg(windows)]
+const KILL_LINE: &[u8] = b"\x1b";
+#[cfg(not(windows))]
+const KILL_LINE: &[u8] = b"\x15";
+
 /// Color constants for cool red/black theme
-#[allow(dead_code)]
 const COLOR_COOL_RED: (u8, u8, u8) = (0xDD, 0x66, 0x66); // Cool red accent
 const COLOR_REDDISH_GRAY: (u8, u8, u8) = (0xC0, 0xB0, 0xB0); // Reddish-gray text
 const COLOR_PURE_BLACK: (u8, u8, u8) = (0x00, 0x00, 0x00); // Pure black background
@@ -107,9 +161,16 @@ const COLOR_MAGENTA_RED: (u8, u8, u8) = (0xB0, 0x5A, 0x7A); // Magenta-red
 const _COLOR_DARK_GRAY: (u8, u8, u8) = (0x5A, 0x4A, 0x4A); // Dark gray for future use
 const COLOR_STATUS_BG: (u8, u8, u8) = (0x1A, 0x0A, 0x0A); // Status bar background
 const COLOR_STATUS_HINT: (u8, u8, u8) = (0x8A, 0x7A, 0x7A); // Status bar hint text
+const COLOR_SEARCH_MATCH: (u8, u8, u8) = (0xB8, 0x98, 0x60); // Search match highlight
 
 const GPU_PROBE_TIMEOUT_MS: u64 = 250;
 
+/// How much of its split one grow or shrink of a pane moves
+const SPLIT_RATIO_STEP: f32 = 0.05;
+
+/// Window title while the active tab hasn't set one
+const DEFAULT_WINDOW_TITLE: &str = "Furnace Terminal";
+
 fn gpu_available_cached() -> bool {
     use std::{
         sync::{mpsc, OnceLock},
@@ -131,91 +192,208 @@ fn gpu_available_cached() -> bool {
     })
 }
 
+/// Output and display state of one tab, beside its shell session
+struct Tab {
+    output: Vec<u8>,
+    /// Output past the scrollback limit, with `scrollback_to_disk`
+    spill: spill::ScrollbackSpill,
+    // Current command buffer - tracks BYTES sent to shell (Bug #1, #2)
+    command: Vec<u8>,
+    // Cached styled lines for zero-copy rendering (Bug #3)
+    styled_lines: Vec<Line<'static>>,
+    // Track buffer length when cache was built (for invalidation)
+    styled_len: usize,
+    // OSC 8 hyperlinks on the cached lines, indexed like `styled_lines`
+    hyperlinks: HyperlinkMap,
+    // Parser state at the end of the buffer, so only new output is parsed;
+    // `None` when the buffer was cleared, trimmed or restyled
+    parser: Option<IncrementalParser>,
+    // Scrollback offset (0 = following latest output, >0 = scrolled up)
+    scroll_offset: usize,
+    // Set once an exited shell's tab is kept open
+    exited: bool,
+    // OSC 133 marks, positioned in the output buffer
+    prompt_marks: osc133::MarkLog,
+    // Icon name and window title set with OSC 0/1/2, and directory from OSC 7
+    titles: title::Titles,
+    // Terminal modes the shell or program has turned on, such as bracketed paste
+    child_modes: modes::ChildModes,
+    // Escape sequence filter, holding a sequence split across reads
+    sanitizer: sanitize::Sanitizer,
+    // Finds the BELs in the output, following strings split across reads
+    bell_scanner: bell::BellScanner,
+    // Normal screen, put aside while a program uses the alternate screen
+    primary_screen: Option<modes::PrimaryScreen>,
+}
+
+impl Tab {
+    fn new() -> Self {
+        Self {
+            output: Vec::with_capacity(1024 * 1024),
+            spill: spill::ScrollbackSpill::new(),
+            command: Vec::new(),
+            styled_lines: Vec::new(),
+            styled_len: 0,
+            hyperlinks: HyperlinkMap::default(),
+            parser: None,
+            scroll_offset: 0,
+            exited: false,
+            prompt_marks: osc133::MarkLog::default(),
+            titles: title::Titles::default(),
+            child_modes: modes::ChildModes::new(),
+            sanitizer: sanitize::Sanitizer::new(),
+            bell_scanner: bell::BellScanner::default(),
+            primary_screen: None,
+        }
+    }
+
+    /// Forget what the previous shell left behind when a new one starts
+    fn reset_for_new_shell(&mut self) {
+        self.exited = false;
+        self.command.clear();
+        self.child_modes = modes::ChildModes::new();
+        self.sanitizer = sanitize::Sanitizer::new();
+        self.bell_scanner = bell::BellScanner::default();
+    }
+}
+
 /// High-performance terminal with GPU-accelerated rendering at 170 FPS
 #[allow(clippy::struct_field_names)]
 #[allow(dead_code)] // Fields used in GPU rendering path; some also kept for tests/library API
 pub struct Terminal {
     config: Config,
     sessions: Vec<ShellSession>,
+    // Everything else kept per tab, indexed like `sessions`
+    tabs: Vec<Tab>,
     active_session: usize,
-    output_buffers: Vec<Vec<u8>>,
     should_quit: bool,
     resource_monitor: Option<ResourceMonitor>,
     autocomplete: Option<Autocomplete>,
     show_resources: bool,
-    keybindings: KeybindingManager,
+    // Keybinding cheat sheet overlay
+    show_keybindings: bool,
+    // Multi-line paste waiting for confirmation
+    pending_paste: Option<PendingPaste>,
+    /// Shared with the hooks executor so `furnace.bind` can add bindings
+    keybindings: Rc<RefCell<KeybindingManager>>,
     session_manager: Option<SessionManager>,
+    // Native plugins, offered each command before the shell runs it
+    plugin_manager: Option<PluginManager>,
+    // Periodic crash-recovery checkpoints of the current session
+    autosave_timer: AutosaveTimer,
     color_palette: TrueColorPalette,
     // Theme manager for dynamic theme switching
     theme_manager: Option<ThemeManager>,
+    // Reloads the config file when it is saved, once `watch_config` is called
+    config_watcher: Option<ConfigWatcher>,
     // Performance optimization: track if redraw is needed
     dirty: bool,
     // Reusable read buffer to reduce allocations
     read_buffer: Vec<u8>,
     // Frame counter for performance metrics
     frame_count: u64,
-    // Current command buffer for each session - tracks BYTES sent to shell (Bug #1, #2)
-    command_buffers: Vec<Vec<u8>>,
-    // Notification message and timeout
-    notification_message: Option<String>,
-    notification_frames: u64,
+    // Which frames to draw; fewer while output floods in
+    frame_pacer: FramePacer,
+    // Frame rate and render times for the stats overlay
+    frame_stats: FrameStats,
+    show_stats: bool,
+    // Notifications on screen, each with its own timeout
+    notifications: notifications::NotificationQueue,
     // Progress bar for command execution
     progress_bar: Option<ProgressBar>,
+    // User-configured prompt pattern; replaces the built-in heuristic when set
+    prompt_regex: Option<Regex>,
     // Current terminal size for proper tab creation (Bug #7)
     terminal_cols: u16,
     terminal_rows: u16,
-    // Cached styled lines for zero-copy rendering (Bug #3)
-    cached_styled_lines: Vec<Vec<Line<'static>>>,
-    // Track buffer length when cache was built (for invalidation)
-    cached_buffer_lens: Vec<usize>,
+    // Where the output was last drawn and how many wrapped rows were trimmed
+    // from the top, for mapping mouse clicks back to output lines
+    output_area: Rect,
+    output_overflow: usize,
+    // Where the tab bar and each tab were last drawn, for mouse clicks, and
+    // the tab being dragged to a new position
+    tab_bar_area: Rect,
+    tab_spans: Vec<TabSpan>,
+    dragged_tab: Option<usize>,
+    // Named session save/load prompt, shown in the status bar while open
+    session_prompt: Option<SessionPrompt>,
+    // Command palette overlay, open while set
+    command_palette: Option<CommandPalette>,
     // Search mode state
     search_mode: bool,
     search_query: String,
     search_results: Vec<usize>, // Line indices where matches found
     current_search_result: usize,
+    // Regex / case / whole-word options, kept across searches in this session
+    search_options: search::SearchOptions,
+    // Compiled pattern for the current query, used for match highlighting
+    search_pattern: Option<search::SearchPattern>,
     // Autocomplete state
     show_autocomplete: bool,
+    // Command buffer the user dismissed ghost text for; it shows again once the buffer changes
+    ghost_dismissed: Option<Vec<u8>>,
     // Cursor style from config (block, underline, bar)
     cursor_style: String,
+    // How lines wider than the content area are displayed
+    line_wrap: wrap::LineWrap,
+    // What a BEL from the shell does, and the visual bell while it shows
+    bell_style: bell::BellStyle,
+    bell_flash: bell::Flash,
     // Maximum command history entries for autocomplete
     max_history: usize,
     // Font size from config for future rendering use
     font_size: u16,
     // Hardware acceleration enabled flag
     hardware_acceleration: bool,
+    // Backend chosen at startup; drops to CPU if GPU initialization fails
+    renderer_kind: RendererKind,
     // Split pane enabled flag
     enable_split_pane: bool,
-    // Split pane layout (horizontal/vertical) when enabled
-    split_orientation: SplitOrientation,
-    // Split ratio (0.0-1.0) for pane sizing
-    split_ratio: f32,
+    // Split pane layout when enabled; a single leaf when not split. Leaf
+    // sessions are what each pane last showed, see `pane_sessions`
+    panes: PaneTree,
+    // Pane (in leaf order) that receives keyboard input; it shows the active session
+    focused_pane: usize,
     // Lua hooks executor for custom functionality
     hooks_executor: Option<HooksExecutor>,
     // Text selection state
     selection_start: Option<(u16, u16)>, // (col, row)
     selection_end: Option<(u16, u16)>,
     selection_active: bool,
-    // Background image data (loaded once)
-    background_image: Option<Vec<u8>>, // Raw image data
-    background_image_width: u16,
-    background_image_height: u16,
-    // Scrollback navigation offset (0 = following latest output, >0 = scrolled up)
-    scroll_offset: usize,
+    // Rectangular selection, chosen by holding Alt while dragging
+    selection_block: bool,
+    // Background image, decoded once, with its cell colors cached
+    background_image: Option<background::BackgroundImage>,
+    // Which frame of an animated background is showing
+    background_clock: Option<background::FrameClock>,
+    // What labels tabs, from `terminal.tab_title_source`
+    tab_title_source: title::TabTitleSource,
+    // Names of the programs running in tabs, for process labels
+    process_names: title::ProcessNames,
+    // Window title last shown, so it's only set again when it changes
+    shown_title: String,
     // Cursor trail state
     cursor_trail_positions: Vec<(u16, u16, std::time::Instant)>, // (col, row, timestamp)
-    // GPU renderer for hardware-accelerated rendering
-    gpu_renderer: Option<crate::gpu::GpuRenderer>,
 }
 
-/// Split pane orientation
+/// What happens to a tab once its shell exits
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SplitOrientation {
-    /// No split - single pane
-    None,
-    /// Horizontal split (top/bottom)
-    Horizontal,
-    /// Vertical split (left/right)
-    Vertical,
+enum ExitAction {
+    /// Close the tab
+    CloseTab,
+    /// Keep the tab and its output, marked as exited
+    KeepTab,
+    /// Quit Furnace
+    Quit,
+}
+
+/// What a command run by [`Terminal::execute`] printed and how it ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResult {
+    /// Output as plain text, without the echoed command line or the next prompt
+    pub output: String,
+    /// Exit status, or `None` if the command was still running at the timeout
+    pub status: Option<i32>,
 }
 
 impl Terminal {
@@ -224,7 +402,10 @@ impl Terminal {
     /// # Errors
     /// Returns an error if session manager initialization fails
     pub fn new(config: Config) -> Result<Self> {
-        info!("Initializing Furnace terminal emulator with 170 FPS GPU rendering + 24-bit color");
+        info!(
+            "Initializing Furnace terminal emulator with {} FPS GPU rendering + 24-bit color",
+            config.terminal.max_fps
+        );
         info!(
             "Configuration: Font={}pt, Cursor={}, HW_Accel={}, SplitPane={}, MaxHistory={}",
             config.terminal.font_size,
@@ -238,11 +419,14 @@ impl Terminal {
         let theme_manager = if config.features.theme_manager {
             match ThemeManager::default_themes_dir() {
                 Ok(themes_dir) => match ThemeManager::with_themes_dir(&themes_dir) {
-                    Ok(manager) => {
+                    Ok(mut manager) => {
                         debug!(
                             "Theme manager initialized with custom themes from {:?}",
                             themes_dir
                         );
+                        if let Err(e) = manager.watch() {
+                            warn!("Theme files will not reload when changed: {}", e);
+                        }
                         Some(manager)
                     }
                     Err(e) => {
@@ -269,16 +453,42 @@ impl Terminal {
             None
         };
 
+        // Load native plugins from the configured directory
+        let plugin_manager = config.plugins.directory.as_deref().and_then(|dir| {
+            let mut manager = PluginManager::new();
+            if let Err(e) = manager.load_directory(plugins::expand_plugin_dir(dir)) {
+                warn!("Failed to load plugins: {:#}", e);
+            }
+            if manager.is_empty() {
+                return None;
+            }
+            info!("Plugins loaded: {}", manager.plugin_names().join(", "));
+            Some(manager)
+        });
+
         // Initialize Lua hooks executor
         let hooks_executor = HooksExecutor::new().ok();
+        if let Some(ref executor) = hooks_executor {
+            let timeout_ms = config.hooks.timeout_ms;
+            executor.set_timeout((timeout_ms > 0).then(|| Duration::from_millis(timeout_ms)));
+        }
 
         // Capture feature flags and config data before moving
         let enable_resource_monitor = config.features.resource_monitor;
         let enable_autocomplete = config.features.autocomplete;
         let enable_progress_bar = config.features.progress_bar;
+        let spinner_style = SpinnerStyle::from_name(&config.progress_bar.spinner_style);
+        let prompt_regex = config.shell.prompt_regex.as_deref().and_then(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| warn!("Invalid prompt_regex '{}': {}", pattern, e))
+                .ok()
+        });
         let enable_command_palette = config.features.command_palette;
         // Store config values for use in the terminal
         let cursor_style = config.terminal.cursor_style.clone();
+        let line_wrap = wrap::LineWrap::from_name(&config.terminal.line_wrap);
+        let bell_style = bell::BellStyle::from_name(&config.terminal.bell_style);
+        let tab_title_source = title::TabTitleSource::from_name(&config.terminal.tab_title_source);
         let max_history = config.terminal.max_history;
         let font_size = config.terminal.font_size;
         if !config.terminal.hardware_acceleration {
@@ -291,15 +501,14 @@ impl Terminal {
             true // Always use GPU path, wgpu can fall back to software rasterizer
         };
         let enable_split_pane = config.terminal.enable_split_pane;
+        let autosave_interval_secs = config.session.autosave_interval_secs;
+        let read_buffer_size = config.shell.read_buffer_size();
+        let frame_pacer = FramePacer::new(config.terminal.max_fps);
 
         // Store hooks for later execution
         let on_startup_hook = config.hooks.on_startup.clone();
 
-        // Clone keybindings config before moving config
-        let kb_config = config.keybindings.clone();
-
-        // Clone custom Lua keybindings before moving config
-        let custom_lua_keybindings = config.hooks.custom_keybindings.clone();
+        let keybindings = keybinding_manager(&config);
 
         // Create color palette from theme colors if available, otherwise use default
         let color_palette = TrueColorPalette::from_ansi_colors(&config.theme.colors)
@@ -311,8 +520,8 @@ impl Terminal {
         let mut terminal = Self {
             config,
             sessions: Vec::with_capacity(8),
+            tabs: Vec::with_capacity(8),
             active_session: 0,
-            output_buffers: Vec::with_capacity(8),
             should_quit: false,
             resource_monitor: if enable_resource_monitor {
                 Some(ResourceMonitor::new())
@@ -325,124 +534,76 @@ impl Terminal {
                 None
             },
             show_resources: false,
-            keybindings: {
-                let mut kb = KeybindingManager::new();
-                // Register custom keybindings from config
-                // These override the defaults loaded by KeybindingManager::new()
-                if !kb_config.new_tab.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.new_tab,
-                        crate::keybindings::Action::NewTab,
-                    );
-                }
-                if !kb_config.close_tab.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.close_tab,
-                        crate::keybindings::Action::CloseTab,
-                    );
-                }
-                if !kb_config.next_tab.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.next_tab,
-                        crate::keybindings::Action::NextTab,
-                    );
-                }
-                if !kb_config.prev_tab.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.prev_tab,
-                        crate::keybindings::Action::PrevTab,
-                    );
-                }
-                if !kb_config.split_vertical.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.split_vertical,
-                        crate::keybindings::Action::SplitVertical,
-                    );
-                }
-                if !kb_config.split_horizontal.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.split_horizontal,
-                        crate::keybindings::Action::SplitHorizontal,
-                    );
-                }
-                if !kb_config.copy.is_empty() {
-                    let _ = kb
-                        .add_binding_from_string(&kb_config.copy, crate::keybindings::Action::Copy);
-                }
-                if !kb_config.paste.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.paste,
-                        crate::keybindings::Action::Paste,
-                    );
-                }
-                if !kb_config.search.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.search,
-                        crate::keybindings::Action::Search,
-                    );
-                }
-                if !kb_config.clear.is_empty() {
-                    let _ = kb.add_binding_from_string(
-                        &kb_config.clear,
-                        crate::keybindings::Action::Clear,
-                    );
-                }
-
-                // Register custom Lua keybindings from hooks config
-                for (key_combo, lua_code) in &custom_lua_keybindings {
-                    let _ = kb.add_binding_from_string(
-                        key_combo,
-                        crate::keybindings::Action::ExecuteLua(lua_code.clone()),
-                    );
-                }
-
-                kb
-            },
+            show_keybindings: false,
+            pending_paste: None,
+            keybindings: Rc::new(RefCell::new(keybindings)),
             session_manager,
+            plugin_manager,
+            autosave_timer: AutosaveTimer::new(autosave_interval_secs, std::time::Instant::now()),
             color_palette,
             theme_manager,
+            config_watcher: None,
             dirty: true,
-            read_buffer: vec![0u8; READ_BUFFER_SIZE],
+            read_buffer: vec![0u8; read_buffer_size],
             frame_count: 0,
-            command_buffers: Vec::with_capacity(8),
-            notification_message: None,
-            notification_frames: 0,
+            frame_pacer,
+            frame_stats: FrameStats::new(),
+            show_stats: false,
+            notifications: notifications::NotificationQueue::new(),
             progress_bar: if enable_progress_bar {
-                Some(ProgressBar::new())
+                Some(ProgressBar::with_style(spinner_style))
             } else {
                 None
             },
+            prompt_regex,
             terminal_cols: 80,
             terminal_rows: 24,
-            cached_styled_lines: Vec::with_capacity(8),
-            cached_buffer_lens: Vec::with_capacity(8),
+            output_area: Rect::default(),
+            output_overflow: 0,
+            tab_bar_area: Rect::default(),
+            tab_spans: Vec::new(),
+            dragged_tab: None,
+            session_prompt: None,
+            command_palette: None,
             search_mode: false,
             search_query: String::new(),
             search_results: Vec::new(),
             current_search_result: 0,
+            search_options: search::SearchOptions::default(),
+            search_pattern: None,
             show_autocomplete: false,
+            ghost_dismissed: None,
             cursor_style,
+            line_wrap,
+            bell_style,
+            bell_flash: bell::Flash::default(),
             max_history,
             font_size,
             hardware_acceleration,
+            renderer_kind: renderer::select_renderer(
+                hardware_acceleration,
+                renderer::GPU_FEATURE,
+                gpu_available_cached,
+            ),
             enable_split_pane,
-            split_orientation: SplitOrientation::None,
-            split_ratio: 0.5, // Default 50/50 split
+            panes: PaneTree::default(),
+            focused_pane: 0,
             hooks_executor,
             // Initialize text selection state
             selection_start: None,
             selection_end: None,
             selection_active: false,
+            selection_block: false,
             // Initialize background image state (load if configured)
             background_image: None,
-            background_image_width: 0,
-            background_image_height: 0,
+            background_clock: None,
             // Initialize cursor trail state
             cursor_trail_positions: Vec::with_capacity(20), // Pre-allocate for trail
             // Initialize scrollback navigation (0 = following latest output)
-            scroll_offset: 0,
+            tab_title_source,
+            process_names: title::ProcessNames::default(),
+            shown_title: DEFAULT_WINDOW_TITLE.to_string(),
             // GPU renderer will be initialized in run()
-            gpu_renderer: None,
         };
 
         if enable_command_palette {
@@ -452,12 +613,13 @@ impl Terminal {
         // Load background image if configured
         if let Some(ref bg_config) = terminal.config.theme.background_image {
             if let Some(ref image_path) = bg_config.image_path {
-                match Self::load_background_image(image_path) {
-                    Ok((data, width, height)) => {
-                        terminal.background_image = Some(data);
-                        terminal.background_image_width = width;
-                        terminal.background_image_height = height;
-                        debug!("Loaded background image: {}x{}", width, height);
+                match background::BackgroundImage::open(image_path, bg_config.animate) {
+                    Ok(image) => {
+                        terminal.background_clock = Some(background::FrameClock::new(
+                            &image.delays(),
+                            std::time::Instant::now(),
+                        ));
+                        terminal.background_image = Some(image);
                     }
                     Err(e) => {
                         warn!("Failed to load background image: {}", e);
@@ -466,11 +628,24 @@ impl Terminal {
             }
         }
 
+        // Let hooks add keybindings at runtime via `furnace.bind`
+        if let Some(ref executor) = terminal.hooks_executor {
+            let keybindings = Rc::clone(&terminal.keybindings);
+            executor.set_binder(move |combo, action| {
+                keybindings
+                    .try_borrow_mut()
+                    .map_err(|_| "keybindings are busy".to_string())?
+                    .add_new_binding(combo, action)
+            });
+        }
+
         // Execute startup hook if configured
-        if let (Some(executor), Some(script)) = (&terminal.hooks_executor, on_startup_hook) {
-            if let Err(e) = executor.on_startup(&script) {
-                warn!("Startup hook execution failed: {}", e);
-            }
+        if let Some(script) = on_startup_hook {
+            terminal.with_hooks(|executor| {
+                if let Err(e) = executor.on_startup(&script) {
+                    warn!("Startup hook execution failed: {}", e);
+                }
+            });
         }
 
         Ok(terminal)
@@ -499,14 +674,12 @@ impl Terminal {
 
         // Safety check: Ensure both vectors are in sync to prevent index out of bounds
         // This can happen if sessions are created/destroyed but buffers aren't updated
-        if self.active_session >= self.sessions.len()
-            || self.active_session >= self.output_buffers.len()
-        {
+        if self.active_session >= self.sessions.len() || self.active_session >= self.tabs.len() {
             warn!(
                 "Active session index {} is out of bounds (sessions: {}, buffers: {})",
                 self.active_session,
                 self.sessions.len(),
-                self.output_buffers.len()
+                self.tabs.len()
             );
             return 0;
         }
@@ -515,7 +688,8 @@ impl Terminal {
             for _ in 0..max_attempts {
                 if let Ok(n) = session.read_output(&mut self.read_buffer).await {
                     if n > 0 {
-                        self.output_buffers[self.active_session]
+                        self.tabs[self.active_session]
+                            .output
                             .extend_from_slice(&self.read_buffer[..n]);
                         self.dirty = true;
                         total_bytes += n;
@@ -531,29 +705,487 @@ impl Terminal {
         total_bytes
     }
 
+    /// Run `command` in the shell without drawing anything, for `--execute`
+    ///
+    /// Waits for the shell's first prompt, types the command and collects its
+    /// output until the shell prompts again or exits. The status comes from the
+    /// OSC 133 end mark when the shell sends one; otherwise the shell is told to
+    /// `exit`, which ends it with the status of the command it just ran.
+    ///
+    /// # Errors
+    /// Returns an error if the shell cannot be started or written to
+    pub async fn execute(&mut self, command: &str, timeout: Duration) -> Result<CommandResult> {
+        let deadline = std::time::Instant::now() + timeout;
+        if self.sessions.is_empty() {
+            self.create_new_tab()?;
+        }
+        let index = self.active_session;
+        let session = self.sessions[index].clone();
+        // Reads block until the shell prints something, so they run on a
+        // task and every wait below can give up at the deadline
+        let mut reader = OutputReader::spawn(session.clone(), self.config.shell.read_buffer_size());
+
+        // A prompt the heuristic doesn't recognise costs a second of quiet
+        let mut last_output = (0, std::time::Instant::now());
+        let startup_wait = Duration::from_millis(INITIAL_OUTPUT_TIMEOUT_MS);
+        loop {
+            let len = self.tabs[index].output.len();
+            if len > last_output.0 {
+                last_output = (len, std::time::Instant::now());
+            }
+            let until = if len == 0 {
+                deadline
+            } else {
+                (last_output.1 + startup_wait).min(deadline)
+            };
+            if !self.receive_output(&mut reader, until).await {
+                break;
+            }
+            let output = String::from_utf8_lossy(&self.tabs[index].output);
+            if Self::detect_prompt(&output, self.prompt_regex.as_ref()) {
+                break;
+            }
+        }
+
+        let start = self.tabs[index].output.len();
+        session.write_input(command.as_bytes()).await?;
+        session.write_input(b"\r").await?;
+
+        while self.receive_output(&mut reader, deadline).await {
+            let output = String::from_utf8_lossy(&self.tabs[index].output[start..]);
+            if let Some(exit) = session.try_wait() {
+                return Ok(CommandResult {
+                    output: command_output(&output, false),
+                    status: Some(exit_status_code(exit.exit_code())),
+                });
+            }
+
+            // The first line is the command echoed back, whatever it ends with
+            let Some((_, after_echo)) = output.split_once('\n') else {
+                continue;
+            };
+            let marks = osc133::parse_marks(after_echo);
+            if !(marks.iter().any(|(_, mark)| mark.ends_command())
+                || Self::detect_prompt(after_echo, self.prompt_regex.as_ref()))
+            {
+                continue;
+            }
+
+            let output = command_output(&output, true);
+            let marked_status = marks.iter().find_map(|(_, mark)| match mark {
+                osc133::Mark::CommandEnd { exit_code } => *exit_code,
+                _ => None,
+            });
+            let status = match marked_status {
+                Some(status) => Some(status),
+                None => {
+                    session.write_input(b"exit\r").await?;
+                    self.wait_for_exit(&session, &mut reader, deadline).await
+                }
+            };
+            return Ok(CommandResult { output, status });
+        }
+
+        Ok(CommandResult {
+            output: command_output(
+                &String::from_utf8_lossy(&self.tabs[index].output[start..]),
+                false,
+            ),
+            status: None,
+        })
+    }
+
+    /// The shell's exit status once it exits, or `None` if it is still running at `deadline`
+    async fn wait_for_exit(
+        &mut self,
+        session: &ShellSession,
+        reader: &mut OutputReader,
+        deadline: std::time::Instant,
+    ) -> Option<i32> {
+        while self.receive_output(reader, deadline).await {
+            if let Some(exit) = session.try_wait() {
+                return Some(exit_status_code(exit.exit_code()));
+            }
+        }
+        None
+    }
+
+    /// Store the next chunk from `output_rx` in the active session's buffer
+    ///
+    /// Waits at most a poll interval, so callers can check on the shell between
+    /// chunks. Returns `false` once `deadline` has passed.
+    async fn receive_output(
+        &mut self,
+        reader: &mut OutputReader,
+        deadline: std::time::Instant,
+    ) -> bool {
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        let wake = (now + Duration::from_millis(INITIAL_OUTPUT_POLL_INTERVAL_MS)).min(deadline);
+        match tokio::time::timeout_at(wake.into(), reader.recv()).await {
+            Ok(Some(chunk)) => {
+                if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                    tab.output.extend_from_slice(&chunk);
+                }
+            }
+            // The reader stopped, e.g. because the shell exited
+            Ok(None) => tokio::time::sleep_until(wake.into()).await,
+            Err(_) => {}
+        }
+        true
+    }
+
     /// Main event loop with async I/O for maximum performance
     ///
     /// # Errors
     /// Returns an error if terminal setup, shell session creation, or event handling fails
     #[allow(clippy::too_many_lines)]
     pub async fn run(&mut self) -> Result<()> {
-        info!("Using GPU-accelerated rendering");
-        self.run_gpu().await
+        if self.renderer_kind == RendererKind::Gpu {
+            info!("Using GPU-accelerated rendering");
+            match self.run_gpu().await {
+                // The window or GPU renderer failed before a shell was started
+                Err(e) if self.sessions.is_empty() => {
+                    warn!("GPU initialization failed, using CPU rendering: {:#}", e);
+                    self.renderer_kind = RendererKind::Cpu;
+                }
+                result => return result,
+            }
+        }
+
+        info!("Using CPU rendering");
+        self.run_cpu().await
+    }
+
+    /// CPU-rendered event loop in the controlling terminal
+    ///
+    /// Draws the ratatui UI through a [`CpuRenderer`]. Used when the GPU path
+    /// is unavailable: hardware acceleration is off, the `gpu` feature isn't
+    /// compiled in, no adapter was found, or GPU initialization failed.
+    ///
+    /// # Errors
+    /// Returns an error if terminal setup, shell session creation, or event handling fails
+    async fn run_cpu(&mut self) -> Result<()> {
+        enable_raw_mode().context("Failed to enable raw mode")?;
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )
+        .context("Failed to set up terminal")?;
+
+        let result = self.run_cpu_loop().await;
+
+        // Restore the TTY even if the loop failed
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            DisableBracketedPaste,
+            DisableMouseCapture,
+            LeaveAlternateScreen,
+            Show
+        );
+
+        info!("CPU terminal shutdown complete");
+        result
+    }
+
+    /// Body of [`Self::run_cpu`], run between TTY setup and teardown
+    async fn run_cpu_loop(&mut self) -> Result<()> {
+        let mut renderer = CpuRenderer::new(CrosstermBackend::new(io::stdout()))?;
+        let size = renderer.terminal_mut().size()?;
+        self.terminal_cols = size.width;
+        self.terminal_rows = size.height;
+
+        if self.sessions.is_empty() {
+            self.create_new_tab()?;
+            info!("Shell session created");
+        }
+
+        // Wait for initial shell output
+        tokio::time::sleep(Duration::from_millis(INITIAL_OUTPUT_TIMEOUT_MS)).await;
+        let _ = self
+            .read_and_store_output(EXTRA_READ_ATTEMPTS, EXTRA_READ_DELAY_MS)
+            .await;
+
+        self.offer_autosave_restore();
+        self.dirty = true;
+
+        if self.sessions.is_empty() {
+            return Ok(());
+        }
+        let mut readers = OutputReaders::new(self.config.shell.read_buffer_size());
+
+        let mut frame = interval(self.frame_pacer.frame_interval());
+        while !self.should_quit {
+            frame.tick().await;
+
+            while event::poll(Duration::ZERO)? {
+                match event::read()? {
+                    // Windows also reports key releases
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        self.handle_key_event(key).await?;
+                    }
+                    Event::Mouse(mouse) => self.handle_mouse_event(mouse).await?,
+                    Event::Paste(text) => {
+                        if let Some(text) = self.request_paste(text) {
+                            self.write_paste(&text).await?;
+                        }
+                    }
+                    Event::Resize(cols, rows) => {
+                        self.terminal_cols = cols;
+                        self.terminal_rows = rows;
+                        self.resize_sessions().await;
+                        self.run_resize_hook();
+                    }
+                    _ => {}
+                }
+                self.dirty = true;
+            }
+
+            if let Some(title) = self.housekeeping(&mut readers) {
+                let _ = execute!(io::stdout(), SetTitle(title));
+            }
+            self.run_expired_chord(std::time::Instant::now()).await?;
+            self.advance_frame();
+            self.draw_frame(&mut renderer)?;
+        }
+
+        self.discard_autosave();
+        Ok(())
+    }
+
+    /// Run the shutdown hook and stop the event loop
+    fn quit(&mut self) {
+        if let Some(ref script) = self.config.hooks.on_shutdown {
+            self.with_hooks(|executor| {
+                if let Err(e) = executor.on_shutdown(script) {
+                    warn!("Shutdown hook execution failed: {}", e);
+                }
+            });
+        }
+
+        self.should_quit = true;
+    }
+
+    /// Whether the active tab's shell has exited and the tab was kept
+    fn active_session_exited(&self) -> bool {
+        self.tabs
+            .get(self.active_session)
+            .is_some_and(|tab| tab.exited)
+    }
+
+    /// Handle shells that exited since the last frame
+    ///
+    /// Each gets a notification with its exit code, then its tab is closed or
+    /// kept with a `[process exited]` marker as [`exit_action`] decides.
+    fn handle_exited_sessions(&mut self) {
+        let mut index = 0;
+        while index < self.sessions.len() {
+            let already_handled = self.tabs.get(index).is_some_and(|tab| tab.exited);
+            let Some(status) = self.sessions[index].try_wait().filter(|_| !already_handled) else {
+                index += 1;
+                continue;
+            };
+
+            let code = status.exit_code();
+            info!("Shell in tab {} exited with code {}", index + 1, code);
+            let mut message = if self.sessions.len() > 1 {
+                format!("Tab {}: shell exited with code {code}", index + 1)
+            } else {
+                format!("Shell exited with code {code}")
+            };
+            let action = exit_action(&self.config.shell, self.sessions.len());
+            if action == ExitAction::KeepTab {
+                if let Some(combo) = self.combo_for(&Action::RestartShell) {
+                    message.push_str(&format!("; press {combo} to restart it"));
+                }
+            }
+            self.show_notification(message);
+
+            match action {
+                ExitAction::CloseTab => self.close_tab_at(index),
+                ExitAction::Quit => {
+                    self.quit();
+                    return;
+                }
+                ExitAction::KeepTab => {
+                    if let Some(tab) = self.tabs.get_mut(index) {
+                        tab.exited = true;
+                        tab.output.extend_from_slice(b"\r\n[process exited]\r\n");
+                    }
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Work both event loops do each pass before drawing
+    ///
+    /// Takes in shell output, handles exited shells, writes a due autosave and
+    /// picks up changed themes and config. Returns the new window title if it
+    /// changed.
+    fn housekeeping(&mut self, readers: &mut OutputReaders) -> Option<String> {
+        self.drain_shell_output(readers);
+        let title = self.window_title_change();
+        self.handle_exited_sessions();
+        self.spawn_due_autosave();
+        self.reload_changed_themes();
+        self.reload_changed_config();
+        title
+    }
+
+    /// Size of a grid cell in the GPU window, in pixels
+    ///
+    /// Uses monospace font metrics: a character is about 0.6 times the font
+    /// size wide, and lines are 1.2 times the font size high.
+    fn gpu_cell_size(&self) -> (f32, f32) {
+        let font_size = self.font_size as f32;
+        (font_size * 0.6, font_size * 1.2)
+    }
+
+    /// Draw the UI through `renderer` if a frame is due
+    ///
+    /// Under a flood of output, frames are skipped so more is taken in per frame.
+    fn draw_frame(&mut self, renderer: &mut dyn Renderer) -> Result<()> {
+        let now = std::time::Instant::now();
+        if !self.frame_pacer.frame_due(now, self.dirty) {
+            return Ok(());
+        }
+        renderer.draw(&mut |f| self.render(f))?;
+        self.dirty = false;
+        self.frame_count += 1;
+        self.frame_stats.record(now, now.elapsed());
+        Ok(())
+    }
+
+    /// Run the own action of a chord's first key once the chord has waited
+    /// past the timeout at `now`
+    async fn run_expired_chord(&mut self, now: std::time::Instant) -> Result<()> {
+        let expired = self.keybindings.borrow_mut().take_expired_chord(now);
+        if let Some(action) = expired {
+            self.run_action(action).await?;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Process the output every tab's shell has sent, joining waiting chunks
+    /// into batches
+    ///
+    /// Background tabs are read as well, so their shells never wait on a
+    /// full channel and their tabs are up to date when shown.
+    fn drain_shell_output(&mut self, readers: &mut OutputReaders) {
+        readers.sync(&self.sessions);
+        for index in 0..self.sessions.len() {
+            if let Some(reader) = readers.get_mut(self.sessions[index].id()) {
+                self.process_output_batches(index, || reader.try_recv());
+            }
+        }
+    }
+
+    /// Process the chunks `next` yields for tab `index`, joined into batches
+    fn process_output_batches(&mut self, index: usize, mut next: impl FnMut() -> Option<Vec<u8>>) {
+        while let Some(first) = next() {
+            let batch = coalesce::coalesce(first, &mut next, coalesce::MAX_BATCH_BYTES);
+            self.frame_pacer.record_output(batch.len());
+            self.process_shell_output_chunk(index, &batch);
+        }
+    }
+
+    /// Write a crash-recovery checkpoint in the background if one is due
+    fn spawn_due_autosave(&mut self) {
+        if let Some(session) = self.due_autosave(std::time::Instant::now()) {
+            if let Some(sm) = self.session_manager.clone() {
+                // Write off the event loop thread so a slow disk can't stall rendering
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = sm.save_session(&session) {
+                        warn!("Failed to autosave session: {}", e);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Per-frame updates: spin the progress bar and count down the notification
+    fn advance_frame(&mut self) {
+        // Show every message hooks passed to `furnace.notify`, oldest first
+        let hook_messages = self
+            .hooks_executor
+            .as_ref()
+            .map(HooksExecutor::take_notifications)
+            .unwrap_or_default();
+        for message in hook_messages {
+            self.show_notification(message);
+        }
+
+        // Update progress bar spinner (only if visible)
+        if let Some(ref mut pb) = self.progress_bar {
+            if pb.visible {
+                pb.tick();
+                self.dirty = true;
+            }
+        }
+
+        // Show the next frame of an animated background when it's due, at the
+        // image's own pace rather than every frame
+        if let Some(ref mut clock) = self.background_clock {
+            if clock.advance(std::time::Instant::now()) {
+                self.dirty = true;
+            }
+        }
+
+        // Draw the screen again without the visual bell once it's over
+        if self.bell_flash.expire(std::time::Instant::now()) {
+            self.dirty = true;
+        }
+
+        // Keep drawing while the cursor trail fades
+        match self.config.theme.cursor_trail.as_ref() {
+            Some(trail) if trail.enabled => {
+                cursor_trail::prune(
+                    &mut self.cursor_trail_positions,
+                    std::time::Instant::now(),
+                    cursor_trail::fade_duration(trail),
+                    trail.length,
+                );
+                if self.cursor_trail_positions.len() > 1 {
+                    self.dirty = true;
+                }
+            }
+            _ => self.cursor_trail_positions.clear(),
+        }
+
+        // Keep the stats overlay's numbers current on an idle screen
+        if self.show_stats && self.frame_stats.stale(std::time::Instant::now()) {
+            self.dirty = true;
+        }
+
+        // Only count down notifications when actually rendering
+        if self.dirty {
+            self.notifications.tick();
+        }
     }
 
     /// GPU-accelerated windowed event loop
     ///
     /// This method creates a windowed application using winit and renders using wgpu.
-    /// This is the primary (and only) rendering path for Furnace.
+    /// This is the primary rendering path for Furnace; [`Self::run`] falls back
+    /// to [`Self::run_cpu`] if it fails to start. Winit key and mouse events
+    /// are translated by [`window_input`] and handled as in the TTY, and the
+    /// same UI is drawn through a [`GpuFrameRenderer`].
     ///
     /// # Errors
     /// Returns an error if window or GPU initialization fails
     #[allow(clippy::too_many_lines)]
     async fn run_gpu(&mut self) -> Result<()> {
+        use self::renderer::GpuFrameRenderer;
         use winit::{
             event::{ElementState, Event, WindowEvent},
             event_loop::{ControlFlow, EventLoop},
-            keyboard::{KeyCode as WinitKeyCode, PhysicalKey},
             window::WindowBuilder,
         };
 
@@ -565,7 +1197,7 @@ impl Terminal {
 
         // Create window
         let window = WindowBuilder::new()
-            .with_title("Furnace Terminal")
+            .with_title(DEFAULT_WINDOW_TITLE)
             .with_inner_size(winit::dpi::PhysicalSize::new(1280, 720))
             .build(&event_loop)
             .context("Failed to create window")?;
@@ -578,7 +1210,8 @@ impl Terminal {
             backend: crate::gpu::GpuBackend::Auto,
             vsync: true,
             font_size: self.font_size as f32,
-            font_family: "JetBrains Mono".to_string(),
+            font_family: self.config.terminal.font_family.clone(),
+            fallback_fonts: self.config.terminal.fallback_fonts.clone(),
             subpixel_rendering: true,
             background_opacity: 1.0,
             background_blur: false,
@@ -603,11 +1236,8 @@ impl Terminal {
         info!("GPU renderer initialized successfully");
 
         // Calculate terminal size from window dimensions and font metrics
-        // Using monospace font metrics: typical character width ~0.6 * font_size, height ~font_size * line_height
         let size = window.inner_size();
-        let font_size = self.font_size as f32;
-        let char_width = font_size * 0.6; // Approximate monospace character width
-        let char_height = font_size * 1.2; // Line height (font size + spacing)
+        let (char_width, char_height) = self.gpu_cell_size();
 
         self.terminal_cols = ((size.width as f32) / char_width).floor() as u16;
         self.terminal_rows = ((size.height as f32) / char_height).floor() as u16;
@@ -647,11 +1277,7 @@ impl Terminal {
             )?
         };
 
-        self.sessions.push(session);
-        self.output_buffers.push(Vec::with_capacity(1024 * 1024));
-        self.command_buffers.push(Vec::new());
-        self.cached_styled_lines.push(Vec::new());
-        self.cached_buffer_lens.push(0);
+        self.push_tab(session);
 
         info!("Shell session created");
 
@@ -662,65 +1288,25 @@ impl Terminal {
             .read_and_store_output(EXTRA_READ_ATTEMPTS, EXTRA_READ_DELAY_MS)
             .await;
 
+        self.offer_autosave_restore();
         self.dirty = true;
 
-        // Store renderer in the terminal
-        self.gpu_renderer = Some(gpu_renderer);
-
-        // Create channels for async I/O communication
-        // Channel for sending input data to shell (from UI thread to I/O task)
-        let (input_tx, mut input_rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
-        // Channel for receiving output data from shell (from I/O task to UI thread)
-        let (output_tx, mut output_rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
-        // Channel for PTY resize commands
-        let (resize_tx, mut resize_rx) = tokio::sync::mpsc::unbounded_channel::<(u16, u16)>();
-
-        // Spawn background task for async shell I/O
-        let session_idx = self.active_session;
-        if let Some(session) = self.sessions.get(session_idx) {
-            let session_clone = session.clone();
-            tokio::spawn(async move {
-                let mut read_buf = vec![0u8; 8192];
-                loop {
-                    // Handle PTY resize requests
-                    while let Ok((rows, cols)) = resize_rx.try_recv() {
-                        if let Err(e) = session_clone.resize(rows, cols).await {
-                            warn!("Failed to resize PTY: {}", e);
-                        } else {
-                            debug!("PTY resized to {}x{}", cols, rows);
-                        }
-                    }
-
-                    // Handle write requests from UI thread
-                    while let Ok(data) = input_rx.try_recv() {
-                        if let Err(e) = session_clone.write_input(&data).await {
-                            warn!("Failed to write to shell: {}", e);
-                            break;
-                        }
-                    }
-
-                    // Read shell output and send to UI thread
-                    match session_clone.read_output(&mut read_buf).await {
-                        Ok(n) if n > 0 => {
-                            let _ = output_tx.send(read_buf[..n].to_vec());
-                        }
-                        Ok(_) => {
-                            // No data, short sleep to avoid busy loop
-                            tokio::time::sleep(Duration::from_millis(10)).await;
-                        }
-                        Err(e) => {
-                            warn!("Failed to read from shell: {}", e);
-                            break;
-                        }
-                    }
-                }
-            });
-        }
+        let mut renderer =
+            GpuFrameRenderer::new(gpu_renderer, self.terminal_cols, self.terminal_rows)?;
+        // Output is read by the same readers as in CPU mode, which decode it
+        let mut readers = OutputReaders::new(self.config.shell.read_buffer_size());
 
         // Main event loop
-        let frame_duration = Duration::from_micros(1_000_000 / TARGET_FPS);
+        let frame_duration = self.frame_pacer.frame_interval();
         let mut last_render = std::time::Instant::now();
         let mut modifiers_state = winit::keyboard::ModifiersState::empty();
+        let mut pointer = window_input::Pointer::default();
+        let mut wheel = window_input::Wheel::default();
+
+        // Handlers are shared with the CPU loop, so they're async; the event loop waits for them
+        fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+            tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+        }
 
         event_loop
             .run(move |event, target| {
@@ -741,6 +1327,7 @@ impl Terminal {
                         modifiers_state = new_state.state();
                     }
 
+                    // Keys go through the same bindings and handling as in the TTY
                     Event::WindowEvent {
                         event:
                             WindowEvent::KeyboardInput {
@@ -748,352 +1335,110 @@ impl Terminal {
                             },
                         ..
                     } => {
-                        if key_event.state == ElementState::Pressed {
-                            let ctrl_pressed = modifiers_state.control_key()
-                                || (cfg!(target_os = "macos") && modifiers_state.super_key());
-                            let shift_pressed = modifiers_state.shift_key();
-
-                            // Ctrl+Q to quit
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyQ)
-                            ) && ctrl_pressed
-                            {
-                                info!("Ctrl+Q pressed, exiting GPU terminal");
-                                self.should_quit = true;
-                                target.exit();
-                                return;
+                        if key_event.state != ElementState::Pressed {
+                            return;
+                        }
+                        if let Some(key) =
+                            window_input::key_event(&key_event.logical_key, modifiers_state)
+                        {
+                            if let Err(e) = block_on(self.handle_key_event(key)) {
+                                warn!("Failed to handle key: {:#}", e);
                             }
+                            self.dirty = true;
+                        }
+                    }
 
-                            // Search mode intercept
-                            if self.search_mode {
-                                if let PhysicalKey::Code(code) = key_event.physical_key {
-                                    match code {
-                                        WinitKeyCode::Escape => {
-                                            self.toggle_search_mode();
-                                        }
-                                        WinitKeyCode::Enter | WinitKeyCode::ArrowDown => {
-                                            self.search_next();
-                                        }
-                                        WinitKeyCode::ArrowUp => {
-                                            self.search_prev();
-                                        }
-                                        WinitKeyCode::Backspace => {
-                                            self.search_query.pop();
-                                            self.execute_search();
-                                        }
-                                        _ => {
-                                            // Type into search query
-                                            if !ctrl_pressed {
-                                                if let Some(text) = &key_event.text {
-                                                    for ch in text.chars() {
-                                                        self.search_query.push(ch);
-                                                    }
-                                                    self.execute_search();
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+F: toggle search mode
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyF)
-                            ) && ctrl_pressed
-                            {
-                                self.toggle_search_mode();
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+N: search next
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyN)
-                            ) && ctrl_pressed && !shift_pressed
-                            {
-                                self.search_next();
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+Shift+N: search prev
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyN)
-                            ) && ctrl_pressed && shift_pressed
-                            {
-                                self.search_prev();
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+Shift+V or Ctrl+V: paste from clipboard
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyV)
-                            ) && ctrl_pressed
-                            {
-                                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                                    if let Ok(text) = clipboard.get_text() {
-                                        let _ = input_tx.send(text.into_bytes());
-                                    }
-                                }
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+Shift+C: copy (send selection to clipboard)
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyC)
-                            ) && ctrl_pressed && shift_pressed
-                            {
-                                if let Ok(()) = self.copy_to_clipboard() {
-                                    self.show_notification("Copied to clipboard".to_string());
-                                }
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Ctrl+R: toggle resource monitor
-                            if matches!(
-                                key_event.physical_key,
-                                PhysicalKey::Code(WinitKeyCode::KeyR)
-                            ) && ctrl_pressed
-                            {
-                                if self.resource_monitor.is_some() {
-                                    self.show_resources = !self.show_resources;
-                                }
-                                self.dirty = true;
-                                return;
-                            }
-
-                            // Handle text input (skip when Ctrl held)
-                            if let Some(text) = &key_event.text {
-                                if !ctrl_pressed {
-                                    // Auto-scroll to bottom when user types
-                                    self.scroll_to_bottom();
-
-                                    for ch in text.chars() {
-                                        let mut buf = [0u8; 4];
-                                        let s = ch.encode_utf8(&mut buf);
-                                        let _ = input_tx.send(s.as_bytes().to_vec());
-
-                                        if let Some(cmd_buf) =
-                                            self.command_buffers.get_mut(self.active_session)
-                                        {
-                                            cmd_buf.extend_from_slice(s.as_bytes());
-                                        }
-                                    }
-                                }
+                    Event::WindowEvent {
+                        event: WindowEvent::CursorMoved { position, .. },
+                        ..
+                    } => {
+                        let cell_size = self.gpu_cell_size();
+                        if let Some(kind) = pointer.moved((position.x, position.y), cell_size) {
+                            let mouse = pointer.event(kind, modifiers_state);
+                            if let Err(e) = block_on(self.handle_mouse_event(mouse)) {
+                                warn!("Failed to handle mouse event: {:#}", e);
                             }
+                            self.dirty = true;
+                        }
+                    }
 
-                            // Handle special keys
-                            if let PhysicalKey::Code(code) = key_event.physical_key {
-                                match code {
-                                    WinitKeyCode::Enter => {
-                                        self.scroll_to_bottom();
-                                        let _ = input_tx.send(b"\r".to_vec());
-                                        if let Some(cmd_buf) =
-                                            self.command_buffers.get_mut(self.active_session)
-                                        {
-                                            // Track command in autocomplete
-                                            if let Some(ref mut ac) = self.autocomplete {
-                                                let cmd = String::from_utf8_lossy(cmd_buf).to_string();
-                                                if !cmd.trim().is_empty() {
-                                                    ac.add_to_history(cmd);
-                                                }
-                                            }
-                                            cmd_buf.clear();
-                                        }
-                                    }
-                                    WinitKeyCode::Backspace => {
-                                        let _ = input_tx.send(vec![127]);
-                                        if let Some(cmd_buf) =
-                                            self.command_buffers.get_mut(self.active_session)
-                                        {
-                                            cmd_buf.pop();
-                                        }
-                                    }
-                                    WinitKeyCode::Tab => {
-                                        let _ = input_tx.send(b"\t".to_vec());
-                                    }
-                                    WinitKeyCode::Escape => {
-                                        self.scroll_to_bottom();
-                                    }
-                                    WinitKeyCode::ArrowUp => {
-                                        let _ = input_tx.send(b"\x1b[A".to_vec());
-                                        if let Some(cmd_buf) =
-                                            self.command_buffers.get_mut(self.active_session)
-                                        {
-                                            cmd_buf.clear();
-                                        }
-                                    }
-                                    WinitKeyCode::ArrowDown => {
-                                        let _ = input_tx.send(b"\x1b[B".to_vec());
-                                        if let Some(cmd_buf) =
-                                            self.command_buffers.get_mut(self.active_session)
-                                        {
-                                            cmd_buf.clear();
-                                        }
-                                    }
-                                    WinitKeyCode::ArrowRight => {
-                                        let _ = input_tx.send(b"\x1b[C".to_vec());
-                                    }
-                                    WinitKeyCode::ArrowLeft => {
-                                        let _ = input_tx.send(b"\x1b[D".to_vec());
-                                    }
-                                    WinitKeyCode::Home => {
-                                        let _ = input_tx.send(b"\x1b[H".to_vec());
-                                    }
-                                    WinitKeyCode::End => {
-                                        let _ = input_tx.send(b"\x1b[F".to_vec());
-                                    }
-                                    WinitKeyCode::Delete => {
-                                        let _ = input_tx.send(b"\x1b[3~".to_vec());
-                                    }
-                                    WinitKeyCode::PageUp if shift_pressed => {
-                                        // Shift+PageUp: scroll back through history
-                                        let scroll_amount = self.terminal_rows.saturating_sub(2).max(1) as usize;
-                                        self.scroll_up(scroll_amount);
-                                    }
-                                    WinitKeyCode::PageUp => {
-                                        let _ = input_tx.send(b"\x1b[5~".to_vec());
-                                    }
-                                    WinitKeyCode::PageDown if shift_pressed => {
-                                        // Shift+PageDown: scroll forward through history
-                                        let scroll_amount = self.terminal_rows.saturating_sub(2).max(1) as usize;
-                                        self.scroll_down(scroll_amount);
-                                    }
-                                    WinitKeyCode::PageDown => {
-                                        let _ = input_tx.send(b"\x1b[6~".to_vec());
-                                    }
-                                    // Ctrl key combinations
-                                    WinitKeyCode::KeyC if ctrl_pressed && !shift_pressed => {
-                                        // Ctrl+C sends SIGINT
-                                        let _ = input_tx.send(vec![0x03]);
-                                    }
-                                    WinitKeyCode::KeyD if ctrl_pressed => {
-                                        // Ctrl+D sends EOT
-                                        let _ = input_tx.send(vec![0x04]);
-                                    }
-                                    WinitKeyCode::KeyL if ctrl_pressed => {
-                                        // Ctrl+L clears screen
-                                        let _ = input_tx.send(vec![0x0C]);
-                                    }
-                                    WinitKeyCode::KeyZ if ctrl_pressed => {
-                                        // Ctrl+Z sends SIGTSTP
-                                        let _ = input_tx.send(vec![0x1A]);
-                                    }
-                                    WinitKeyCode::KeyA if ctrl_pressed => {
-                                        let _ = input_tx.send(vec![0x01]);
-                                    }
-                                    WinitKeyCode::KeyE if ctrl_pressed => {
-                                        let _ = input_tx.send(vec![0x05]);
-                                    }
-                                    WinitKeyCode::KeyU if ctrl_pressed => {
-                                        let _ = input_tx.send(vec![0x15]);
-                                    }
-                                    WinitKeyCode::KeyK if ctrl_pressed => {
-                                        let _ = input_tx.send(vec![0x0B]);
-                                    }
-                                    WinitKeyCode::KeyW if ctrl_pressed => {
-                                        let _ = input_tx.send(vec![0x17]);
-                                    }
-                                    _ => {}
-                                }
+                    Event::WindowEvent {
+                        event: WindowEvent::MouseInput { state, button, .. },
+                        ..
+                    } => {
+                        if let Some(button) = window_input::mouse_button(button) {
+                            let kind = pointer.button(button, state == ElementState::Pressed);
+                            let mouse = pointer.event(kind, modifiers_state);
+                            if let Err(e) = block_on(self.handle_mouse_event(mouse)) {
+                                warn!("Failed to handle mouse event: {:#}", e);
                             }
-
                             self.dirty = true;
                         }
                     }
 
                     Event::WindowEvent {
-                        event: WindowEvent::Resized(new_size),
+                        event: WindowEvent::MouseWheel { delta, .. },
                         ..
                     } => {
-                        if let Some(ref mut renderer) = self.gpu_renderer {
-                            renderer.resize(new_size.width, new_size.height);
-
-                            // Recalculate terminal dimensions from new window size
-                            let font_size = self.font_size as f32;
-                            let char_width = font_size * 0.6;
-                            let char_height = font_size * 1.2;
-
-                            let new_cols = ((new_size.width as f32) / char_width).floor() as u16;
-                            let new_rows = ((new_size.height as f32) / char_height).floor() as u16;
-
-                            // Ensure minimum dimensions
-                            let new_cols = new_cols.max(80);
-                            let new_rows = new_rows.max(24);
-
-                            // Only resize if dimensions actually changed
-                            if new_cols != self.terminal_cols || new_rows != self.terminal_rows {
-                                self.terminal_cols = new_cols;
-                                self.terminal_rows = new_rows;
-
-                                // Send resize command to background I/O task
-                                let _ = resize_tx.send((new_rows, new_cols));
+                        use crossterm::event::MouseEventKind;
 
-                                info!("Terminal resized to {}x{}", new_cols, new_rows);
+                        let events = wheel.scroll(delta, self.gpu_cell_size().1);
+                        let kind = if events > 0 {
+                            MouseEventKind::ScrollUp
+                        } else {
+                            MouseEventKind::ScrollDown
+                        };
+                        for _ in 0..events.unsigned_abs() {
+                            let mouse = pointer.event(kind, modifiers_state);
+                            if let Err(e) = block_on(self.handle_mouse_event(mouse)) {
+                                warn!("Failed to handle mouse event: {:#}", e);
                             }
+                        }
+                        self.dirty = true;
+                    }
 
-                            self.dirty = true;
+                    Event::WindowEvent {
+                        event: WindowEvent::Resized(new_size),
+                        ..
+                    } => {
+                        // Recalculate terminal dimensions from new window size
+                        let (char_width, char_height) = self.gpu_cell_size();
+                        let new_cols = ((new_size.width as f32) / char_width).floor() as u16;
+                        let new_rows = ((new_size.height as f32) / char_height).floor() as u16;
+
+                        // Ensure minimum dimensions
+                        let new_cols = new_cols.max(80);
+                        let new_rows = new_rows.max(24);
+                        renderer.resize(new_size.width, new_size.height, new_cols, new_rows);
+
+                        // Only resize the shells if dimensions actually changed
+                        if new_cols != self.terminal_cols || new_rows != self.terminal_rows {
+                            self.terminal_cols = new_cols;
+                            self.terminal_rows = new_rows;
+                            block_on(self.resize_sessions());
+
+                            info!("Terminal resized to {}x{}", new_cols, new_rows);
+                            self.run_resize_hook();
                         }
+
+                        self.dirty = true;
                     }
 
                     Event::AboutToWait => {
-                        // Drain all available shell output from background I/O task (non-blocking)
-                        while let Ok(output) = output_rx.try_recv() {
-                            // Process output with filters, hooks, and scrollback management
-                            self.process_shell_output_chunk(&output);
+                        if let Some(title) = self.housekeeping(&mut readers) {
+                            window.set_title(&title);
+                        }
+                        let chord = block_on(self.run_expired_chord(std::time::Instant::now()));
+                        if let Err(e) = chord {
+                            warn!("Failed to run key binding: {}", e);
                         }
 
-                        // Render at target FPS
+                        // Render at target FPS, skipping frames under a flood of output
                         let now = std::time::Instant::now();
                         if now.duration_since(last_render) >= frame_duration {
-                            // Update progress bar spinner (only if visible)
-                            if let Some(ref mut pb) = self.progress_bar {
-                                if pb.visible {
-                                    pb.tick();
-                                    self.dirty = true;
-                                }
-                            }
-
-                            // Only decrement notification counter when actually rendering
-                            if self.dirty && self.notification_frames > 0 {
-                                self.notification_frames -= 1;
-                                if self.notification_frames == 0 {
-                                    self.notification_message = None;
-                                }
-                            }
-
-                            if self.dirty {
-                                // Convert terminal buffer to GPU cells BEFORE borrowing renderer
-                                let cells = self.buffer_to_gpu_cells();
-                                let cols = self.terminal_cols as u32;
-                                let rows = self.terminal_rows as u32;
-
-                                if let Some(ref mut renderer) = self.gpu_renderer {
-                                    renderer.update_cells(&cells, cols, rows);
-
-                                    // Render
-                                    if let Err(e) = renderer.render() {
-                                        warn!("GPU render error: {:?}", e);
-                                    }
-
-                                    self.dirty = false;
-                                    self.frame_count += 1;
-
-                                    if self.frame_count.is_multiple_of(1000) {
-                                        debug!("Rendered {} GPU frames", self.frame_count);
-                                    }
-                                }
+                            self.advance_frame();
+                            if let Err(e) = self.draw_frame(&mut renderer) {
+                                warn!("{:#}", e);
                             }
                             last_render = now;
                         }
@@ -1103,6 +1448,11 @@ impl Terminal {
                         }
                     }
 
+                    Event::LoopExiting => {
+                        // A clean exit leaves nothing to recover
+                        self.discard_autosave();
+                    }
+
                     _ => {}
                 }
             })
@@ -1114,13 +1464,34 @@ impl Terminal {
 
     /// Process shell output chunk with filters, hooks, and scrollback management
     /// This is shared between CPU and GPU rendering paths for consistency
-    fn process_shell_output_chunk(&mut self, raw_bytes: &[u8]) {
-        if raw_bytes.is_empty() || self.active_session >= self.output_buffers.len() {
+    ///
+    /// The output goes to tab `index`. The bell, the progress bar and the
+    /// shell integration state behind key bindings follow the active tab only.
+    fn process_shell_output_chunk(&mut self, index: usize, raw_bytes: &[u8]) {
+        if raw_bytes.is_empty() || index >= self.tabs.len() {
             return;
         }
+        let active = index == self.active_session;
 
         // Convert output to Cow<str> - avoids allocation if already valid UTF-8
-        let output_cow = String::from_utf8_lossy(raw_bytes);
+        let mut output_cow = String::from_utf8_lossy(raw_bytes);
+
+        // Drop escape sequences a program could abuse before anything else sees them
+        if self.config.terminal.sanitize_output {
+            let extra_osc: &[&str] = if self.config.terminal.allow_osc52 {
+                &["52"]
+            } else {
+                &[]
+            };
+            // A sequence split across reads is held back and removed whole
+            let sanitized = match self.tabs.get_mut(index) {
+                Some(tab) => tab.sanitizer.sanitize(&output_cow, extra_osc),
+                None => sanitize::sanitize_output_allowing(&output_cow, extra_osc),
+            };
+            if let Cow::Owned(clean) = sanitized {
+                output_cow = Cow::Owned(clean);
+            }
+        }
 
         // Apply output filters if configured
         // Use Cow to avoid allocation when no filters modify the output
@@ -1141,353 +1512,438 @@ impl Terminal {
             output_cow
         };
 
-        // Store the (potentially filtered) output in buffer
-        self.output_buffers[self.active_session].extend_from_slice(output_str.as_bytes());
+        // Store the (potentially filtered) output in buffer, switching screens
+        // where the program asks to
+        let switches = self
+            .tabs
+            .get_mut(index)
+            .map(|tab| tab.child_modes.update(&output_str))
+            .unwrap_or_default();
+        let mut command_finished = false;
+        let mut start = 0;
+        for switch in switches {
+            command_finished |= self.append_output(index, &output_str[start..switch.at]);
+            self.switch_screen(index, switch.alternate);
+            start = switch.at;
+        }
+        command_finished |= self.append_output(index, &output_str[start..]);
         self.dirty = true;
 
-        // Auto-scroll to bottom when new output arrives (follow latest output)
-        self.scroll_offset = 0;
-
-        // Update shell integration state and trigger related hooks
-        self.update_shell_integration_state(&output_str);
-
         // Call on_output hook if configured
-        if let Some(ref executor) = self.hooks_executor {
-            if let Some(ref script) = self.config.hooks.on_output {
+        if let Some(ref script) = self.config.hooks.on_output {
+            self.with_hooks(|executor| {
                 if let Err(e) = executor.on_output(script, &output_str) {
                     warn!("on_output hook failed: {}", e);
                 }
-            }
+            });
         }
 
-        // Check for bell character (0x07) and call on_bell hook
-        if raw_bytes.contains(&0x07) {
-            if let Some(ref executor) = self.hooks_executor {
-                if let Some(ref script) = self.config.hooks.on_bell {
-                    if let Err(e) = executor.on_bell(script) {
-                        warn!("on_bell hook failed: {}", e);
-                    }
+        // Ring the bell for BEL characters that don't end an OSC string
+        if self.tabs[index].bell_scanner.rings(raw_bytes) && active {
+            self.ring_bell();
+        }
+
+        // The progress bar follows the active tab only
+        if !active {
+            self.enforce_scrollback_limit(index);
+            return;
+        }
+
+        // Track progress percentages reported by the running command
+        if let Some(ref mut pb) = self.progress_bar {
+            if pb.visible {
+                if let Some(percent) = crate::progress_bar::parse_percent(&output_str) {
+                    pb.update_percent(percent);
                 }
             }
         }
 
-        // Improved prompt detection for progress bar
+        // Semantic prompt marks say when the command finished; without them
+        // fall back to recognising the prompt
+        let semantic_prompts = self.tabs[index].prompt_marks.is_active();
         let should_stop_progress = if let Some(ref pb) = self.progress_bar {
-            if pb.visible {
-                Self::detect_prompt(&output_str)
-            } else {
+            if !pb.visible {
                 false
+            } else if semantic_prompts {
+                command_finished
+            } else {
+                Self::detect_prompt(&output_str, self.prompt_regex.as_ref())
             }
         } else {
             false
         };
 
+        // Show how long the command took; the bar hides itself shortly after
         if should_stop_progress {
             if let Some(ref mut pb) = self.progress_bar {
-                pb.stop();
+                pb.complete();
             }
         }
 
-        // Enforce scrollback limit and clear URL cache
-        let max_buffer = self.config.terminal.scrollback_lines * 256;
-        if self.output_buffers[self.active_session].len() > max_buffer {
-            let excess = self.output_buffers[self.active_session].len() - max_buffer;
-            self.output_buffers[self.active_session].drain(..excess);
-        }
+        self.enforce_scrollback_limit(index);
     }
 
-    /// Convert terminal output buffer to GPU cells with ANSI color support
-    fn buffer_to_gpu_cells(&self) -> Vec<crate::gpu::GpuCell> {
-        use ratatui::style::Color;
-
-        let total_cells = (self.terminal_cols as usize) * (self.terminal_rows as usize);
-        let mut cells = vec![crate::gpu::GpuCell::default(); total_cells];
-
-        // Reserve last row for status bar
-        let content_rows = (self.terminal_rows as usize).saturating_sub(1);
-
-        if let Some(buffer) = self.output_buffers.get(self.active_session) {
-            let output = String::from_utf8_lossy(buffer);
-            // Parse ANSI escape codes to get styled lines (same as CPU mode)
-            let styled_lines = AnsiParser::parse_with_palette(&output, &self.color_palette);
-
-            // Skip lines to fit terminal height, applying scroll offset
-            let tail_skip = styled_lines.len().saturating_sub(content_rows);
-            let skip_count = tail_skip.saturating_sub(self.scroll_offset);
-            let visible_lines: Vec<_> = styled_lines.into_iter().skip(skip_count).take(content_rows).collect();
-
-            // Convert styled lines to GPU cells with wide glyph support
-            for (row, line) in visible_lines
-                .iter()
-                .enumerate()
-                .take(content_rows)
-            {
-                let mut col = 0;
-                for span in &line.spans {
-                    use unicode_width::UnicodeWidthChar;
-
-                    for ch in span.content.chars() {
-                        if col >= self.terminal_cols as usize {
-                            break;
-                        }
-
-                        // Get display width of character (handles CJK, emoji, etc.)
-                        let char_width = ch.width().unwrap_or(1);
-
-                        // Skip zero-width characters (combining marks, etc.)
-                        if char_width == 0 {
-                            continue;
-                        }
-
-                        let idx = row * (self.terminal_cols as usize) + col;
-                        if idx < cells.len() {
-                            cells[idx].char_code = ch as u32;
-
-                            // Extract foreground color from span style
-                            cells[idx].fg_color = match span.style.fg {
-                                Some(Color::Rgb(r, g, b)) => {
-                                    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
-                                }
-                                Some(Color::Reset) | None => [
-                                    COLOR_REDDISH_GRAY.0 as f32 / 255.0,
-                                    COLOR_REDDISH_GRAY.1 as f32 / 255.0,
-                                    COLOR_REDDISH_GRAY.2 as f32 / 255.0,
-                                    1.0,
-                                ],
-                                // Map other ratatui colors to RGB
-                                Some(color) => {
-                                    let (r, g, b) = match color {
-                                        Color::Black => (0, 0, 0),
-                                        Color::Red => (205, 49, 49),
-                                        Color::Green => (13, 188, 121),
-                                        Color::Yellow => (229, 229, 16),
-                                        Color::Blue => (36, 114, 200),
-                                        Color::Magenta => (188, 63, 188),
-                                        Color::Cyan => (17, 168, 205),
-                                        Color::Gray => (229, 229, 229),
-                                        Color::DarkGray => (102, 102, 102),
-                                        Color::LightRed => (241, 76, 76),
-                                        Color::LightGreen => (35, 209, 139),
-                                        Color::LightYellow => (245, 245, 67),
-                                        Color::LightBlue => (59, 142, 234),
-                                        Color::LightMagenta => (214, 112, 214),
-                                        Color::LightCyan => (41, 184, 219),
-                                        Color::White => (255, 255, 255),
-                                        _ => (
-                                            COLOR_REDDISH_GRAY.0,
-                                            COLOR_REDDISH_GRAY.1,
-                                            COLOR_REDDISH_GRAY.2,
-                                        ),
-                                    };
-                                    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
-                                }
-                            };
-
-                            // Extract background color from span style
-                            cells[idx].bg_color = match span.style.bg {
-                                Some(Color::Rgb(r, g, b)) => {
-                                    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
-                                }
-                                Some(Color::Reset) | None => [
-                                    COLOR_PURE_BLACK.0 as f32 / 255.0,
-                                    COLOR_PURE_BLACK.1 as f32 / 255.0,
-                                    COLOR_PURE_BLACK.2 as f32 / 255.0,
-                                    1.0,
-                                ],
-                                Some(color) => {
-                                    let (r, g, b) = match color {
-                                        Color::Black => (0, 0, 0),
-                                        Color::Red => (205, 49, 49),
-                                        Color::Green => (13, 188, 121),
-                                        Color::Yellow => (229, 229, 16),
-                                        Color::Blue => (36, 114, 200),
-                                        Color::Magenta => (188, 63, 188),
-                                        Color::Cyan => (17, 168, 205),
-                                        Color::Gray => (229, 229, 229),
-                                        Color::DarkGray => (102, 102, 102),
-                                        Color::LightRed => (241, 76, 76),
-                                        Color::LightGreen => (35, 209, 139),
-                                        Color::LightYellow => (245, 245, 67),
-                                        Color::LightBlue => (59, 142, 234),
-                                        Color::LightMagenta => (214, 112, 214),
-                                        Color::LightCyan => (41, 184, 219),
-                                        Color::White => (255, 255, 255),
-                                        _ => (
-                                            COLOR_PURE_BLACK.0,
-                                            COLOR_PURE_BLACK.1,
-                                            COLOR_PURE_BLACK.2,
-                                        ),
-                                    };
-                                    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
-                                }
-                            };
-                        }
-
-                        // Increment column by character width
-                        col += char_width;
-
-                        // For double-width characters, fill the second cell with a spacer
-                        if char_width == 2 && col < self.terminal_cols as usize {
-                            let spacer_idx = row * (self.terminal_cols as usize) + (col - 1);
-                            if spacer_idx < cells.len() {
-                                // Mark as continuation of wide character (use space as placeholder)
-                                cells[spacer_idx].char_code = ' ' as u32;
-                                cells[spacer_idx].fg_color = cells[idx].fg_color;
-                                cells[spacer_idx].bg_color = cells[idx].bg_color;
-                            }
-                        }
-                    }
-                }
-            }
+    /// Append output to tab `index`'s buffer, returning whether an OSC 133
+    /// mark in it says the running command has finished
+    fn append_output(&mut self, index: usize, output: &str) -> bool {
+        if output.is_empty() {
+            return false;
+        }
+        let tab = &mut self.tabs[index];
+        let base = tab.output.len();
+        tab.output.extend_from_slice(output.as_bytes());
+
+        // Follow the latest output unless scrolled up; a scrolled-up view stays
+        // anchored on the same lines as new output pushes the tail down
+        if tab.scroll_offset > 0 {
+            tab.scroll_offset += output.matches('\n').count();
         }
 
-        // Render GPU status bar on the last row
-        self.render_gpu_status_bar(&mut cells, content_rows);
-
-        cells
+        // Update shell integration state and trigger related hooks
+        self.update_shell_integration_state(index, output, base)
     }
 
-    /// Render a status bar into the GPU cell buffer on the given row
-    fn render_gpu_status_bar(&self, cells: &mut [crate::gpu::GpuCell], status_row: usize) {
-        let cols = self.terminal_cols as usize;
-
-        // Build status text
-        let mode_text = if self.search_mode {
-            format!(" SEARCH: {} ", self.search_query)
-        } else if self.scroll_offset > 0 {
-            format!(" SCROLL [+{}] ", self.scroll_offset)
-        } else {
-            " NORMAL ".to_string()
-        };
-
-        let session_info = if self.sessions.len() > 1 {
-            format!(" Tab {}/{} ", self.active_session + 1, self.sessions.len())
-        } else {
-            " Session 1 ".to_string()
-        };
-
-        let hints = if self.search_mode {
-            " Esc: Exit │ Enter: Next │ ↑: Prev"
-        } else if self.scroll_offset > 0 {
-            " Shift+PgUp/PgDn: Scroll │ Esc: Bottom"
-        } else {
-            " Ctrl+F: Search │ Shift+PgUp: Scroll"
-        };
-
-        let full_status = format!("{mode_text}{session_info}{hints}");
-
-        // Mode indicator colors
-        let (mode_fg, mode_bg) = if self.search_mode {
-            ([0.0_f32, 0.0, 0.0, 1.0], [0.87_f32, 0.40, 0.40, 1.0]) // Black on red
-        } else if self.scroll_offset > 0 {
-            ([0.0_f32, 0.0, 0.0, 1.0], [0.80_f32, 0.60, 0.20, 1.0]) // Black on amber
-        } else {
-            ([0.0_f32, 0.0, 0.0, 1.0], [0.42_f32, 0.60, 0.48, 1.0]) // Black on green
+    /// Move a session to the alternate screen or back to its normal one
+    ///
+    /// The normal screen's output, scroll position and marks are put aside
+    /// while the alternate screen is in use and come back as they were. A
+    /// search is ended, as the alternate screen has no scrollback to search.
+    fn switch_screen(&mut self, index: usize, alternate: bool) {
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return;
         };
 
-        let bar_bg = [
-            COLOR_STATUS_BG.0 as f32 / 255.0,
-            COLOR_STATUS_BG.1 as f32 / 255.0,
-            COLOR_STATUS_BG.2 as f32 / 255.0,
-            1.0,
-        ];
-        let bar_fg = [
-            COLOR_STATUS_HINT.0 as f32 / 255.0,
-            COLOR_STATUS_HINT.1 as f32 / 255.0,
-            COLOR_STATUS_HINT.2 as f32 / 255.0,
-            1.0,
-        ];
-
-        let mode_len = mode_text.chars().count();
-
-        for (col, ch) in full_status.chars().enumerate() {
-            if col >= cols {
-                break;
+        if alternate {
+            if tab.primary_screen.is_some() {
+                return;
             }
-            let idx = status_row * cols + col;
-            if idx < cells.len() {
-                cells[idx].char_code = ch as u32;
-                if col < mode_len {
-                    cells[idx].fg_color = mode_fg;
-                    cells[idx].bg_color = mode_bg;
-                } else {
-                    cells[idx].fg_color = bar_fg;
-                    cells[idx].bg_color = bar_bg;
-                }
+            tab.primary_screen = Some(modes::PrimaryScreen {
+                output: std::mem::take(&mut tab.output),
+                scroll_offset: std::mem::take(&mut tab.scroll_offset),
+                marks: std::mem::take(&mut tab.prompt_marks),
+            });
+            if self.search_mode && index == self.active_session {
+                self.search_mode = false;
+                self.search_results.clear();
+                self.search_pattern = None;
             }
+        } else {
+            let Some(saved) = tab.primary_screen.take() else {
+                return;
+            };
+            tab.output = saved.output;
+            tab.scroll_offset = saved.scroll_offset;
+            tab.prompt_marks = saved.marks;
         }
+        self.invalidate_parse(index);
+        self.dirty = true;
+    }
 
-        // Fill remaining cols with background
-        for col in full_status.chars().count()..cols {
-            let idx = status_row * cols + col;
-            if idx < cells.len() {
-                cells[idx].char_code = ' ' as u32;
-                cells[idx].fg_color = bar_fg;
-                cells[idx].bg_color = bar_bg;
-            }
-        }
+    /// Whether a program has switched the session to the alternate screen
+    fn in_alternate_screen(&self, index: usize) -> bool {
+        self.tabs
+            .get(index)
+            .is_some_and(|tab| tab.primary_screen.is_some())
     }
 
     /// Bug #9: Detect shell prompts from various shells
     /// Detects shell prompts in terminal output
     ///
     /// This function identifies common shell prompt patterns to determine when
-    /// a command has finished executing. It supports various shells and themes:
+    /// a command has finished executing. Only the last line of the output (with
+    /// escape sequences removed) is considered, since a shell prints its prompt
+    /// after the command's output and then waits without a trailing newline.
     ///
     /// # Supported Shells
     /// - Bash: `$ `, `# `
     /// - Zsh: `% `, `❯`, `➜`, `λ`
     /// - Fish: `❯`, `> `
-    /// - `PowerShell`: `PS>`, `PS `
+    /// - `PowerShell`: `PS C:\>`
     /// - Python REPL: `>>>`, `...`
     ///
     /// # Detection Strategy
-    /// 1. Check for explicit prompt characters
-    /// 2. Heuristic: Short lines ending with newline are likely prompts
+    /// 1. If `shell.prompt_regex` is configured, it alone decides
+    /// 2. Otherwise the last line must end with a prompt character, or start
+    ///    with `➜` (oh-my-zsh puts it first)
     ///
     /// # Arguments
     /// * `output` - Recent shell output to check for prompts
+    /// * `prompt_regex` - Configured prompt pattern, if any
     ///
     /// # Returns
     /// `true` if a prompt pattern is detected, `false` otherwise
-    fn detect_prompt(output: &str) -> bool {
-        // Check for common prompt patterns across different shells
-        output.contains("$ ")   // Bash default
-            || output.contains("> ")   // Generic shell
-            || output.contains("# ")   // Root prompt
-            || output.contains("% ")   // Zsh default
-            || output.contains("❯")    // fish/starship
-            || output.contains("➜")    // oh-my-zsh
-            || output.contains("λ")    // some zsh themes
-            || output.contains("PS>")  // PowerShell
-            || output.contains("PS ")  // PowerShell alternative
-            || output.contains(">>>")  // Python REPL
-            || output.contains("...")  // Python continuation
-            || (output.ends_with('\n') && output.len() < 100) // Heuristic: short line likely a prompt
+    fn detect_prompt(output: &str, prompt_regex: Option<&Regex>) -> bool {
+        let last_line = AnsiParser::parse(output)
+            .last()
+            .map(search::line_text)
+            .unwrap_or_default();
+
+        if let Some(regex) = prompt_regex {
+            return regex.is_match(&last_line);
+        }
+
+        let trimmed = last_line.trim_end();
+        !trimmed.is_empty()
+            && (PROMPT_ENDINGS.iter().any(|end| trimmed.ends_with(end)) || trimmed.starts_with('➜'))
     }
 
     /// Handle mouse events
-    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+    ///
+    /// Programs that turned on mouse reporting get the events over the output
+    /// area; otherwise they scroll, select text and open hyperlinks.
+    async fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
         use crossterm::event::MouseEventKind;
 
+        if self.handle_tab_bar_mouse(mouse) {
+            return Ok(());
+        }
+
+        // A program that turned on mouse reporting gets the event instead
+        if let Some(report) = self.mouse_report(mouse) {
+            if let Some(session) = self.sessions.get(self.active_session) {
+                session.write_input(&report).await?;
+            }
+            return Ok(());
+        }
+
         match mouse.kind {
             MouseEventKind::ScrollUp => {
-                self.scroll_up(3); // Scroll 3 lines per tick
+                self.scroll_up(MOUSE_SCROLL_LINES);
             }
             MouseEventKind::ScrollDown => {
-                self.scroll_down(3); // Scroll 3 lines per tick
+                self.scroll_down(MOUSE_SCROLL_LINES);
+            }
+            // Ctrl+click opens an OSC 8 hyperlink; elsewhere it selects as usual
+            MouseEventKind::Down(crossterm::event::MouseButton::Left)
+                if mouse.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                match self.hyperlink_at(mouse.column, mouse.row) {
+                    Some(uri) => self.open_hyperlink(&uri),
+                    None => self.handle_mouse_selection(mouse),
+                }
             }
             _ => {
                 // Handle text selection for other mouse events
                 self.handle_mouse_selection(mouse);
             }
         }
+        Ok(())
+    }
+
+    /// The report to send the active session's program for a mouse event
+    ///
+    /// Only events over the output area are reported, and only while the
+    /// program has mouse reporting on. Holding Shift keeps local selection.
+    fn mouse_report(&self, mouse: MouseEvent) -> Option<Vec<u8>> {
+        if mouse.modifiers.contains(KeyModifiers::SHIFT) || self.active_session_exited() {
+            return None;
+        }
+        let modes = &self.tabs.get(self.active_session)?.child_modes;
+        let area = self.output_area;
+        let column = mouse.column.checked_sub(area.x)?;
+        let row = mouse.row.checked_sub(area.y)?;
+        if column >= area.width || row >= area.height {
+            return None;
+        }
+        mouse::encode(
+            mouse.kind,
+            mouse.modifiers,
+            column + 1,
+            row + 1,
+            modes.mouse_tracking(),
+            modes.sgr_mouse(),
+        )
+    }
+
+    /// Switch tabs by clicking them, close them with `×` and drag them into a new order
+    ///
+    /// Returns whether the event was for the tab bar, so it doesn't also
+    /// start a text selection.
+    fn handle_tab_bar_mouse(&mut self, mouse: MouseEvent) -> bool {
+        use crossterm::event::{MouseButton, MouseEventKind};
+
+        let area = self.tab_bar_area;
+        let column = mouse
+            .column
+            .checked_sub(area.x)
+            .filter(|&column| column < area.width);
+        let target = column.and_then(|column| tab_bar::target_at(&self.tab_spans, column));
+        let in_bar = area.height > 0 && mouse.row == area.y && column.is_some();
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if in_bar => {
+                match target {
+                    Some(TabTarget::Close(index)) => {
+                        self.close_tab_at(index);
+                    }
+                    Some(TabTarget::Tab(index)) => {
+                        self.select_tab(index);
+                        self.dragged_tab = Some(index);
+                    }
+                    None => {}
+                }
+                true
+            }
+            // Only the column matters while dragging, so the pointer can stray off the bar
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some(from) = self.dragged_tab else {
+                    return false;
+                };
+                if let Some(TabTarget::Tab(to) | TabTarget::Close(to)) = target {
+                    if to != from {
+                        self.move_tab(from, to);
+                        self.dragged_tab = Some(to);
+                    }
+                }
+                true
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragged_tab.take().is_some(),
+            _ => false,
+        }
+    }
+
+    /// Get the OSC 8 hyperlink under a screen position in the output area
+    fn hyperlink_at(&self, column: u16, row: u16) -> Option<String> {
+        let area = self.output_area;
+        if column < area.x
+            || row < area.y
+            || column >= area.x + area.width
+            || row >= area.y + area.height
+        {
+            return None;
+        }
+
+        let tab = self.tabs.get(self.active_session)?;
+        let (line, col) = wrap::logical_position(
+            &tab.styled_lines,
+            area.width as usize,
+            self.line_wrap,
+            (row - area.y) as usize + self.output_overflow,
+            (column - area.x) as usize,
+        )?;
+        tab.hyperlinks.uri_at(line, col).map(str::to_string)
+    }
+
+    /// Open a hyperlink with the platform's default handler
+    ///
+    /// Only well-known URL schemes are opened, since the link target comes
+    /// from program output.
+    fn open_hyperlink(&mut self, uri: &str) {
+        const SCHEMES: &[&str] = &["https://", "http://", "ftp://", "file://", "mailto:"];
+
+        if !SCHEMES.iter().any(|scheme| uri.starts_with(scheme)) {
+            warn!("Refusing to open hyperlink with unsupported scheme: {}", uri);
+            self.show_notification(format!("Unsupported link: {}", uri));
+            return;
+        }
+
+        #[cfg(target_os = "windows")]
+        let child = std::process::Command::new("explorer").arg(uri).spawn();
+        #[cfg(target_os = "macos")]
+        let child = std::process::Command::new("open").arg(uri).spawn();
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let child = std::process::Command::new("xdg-open").arg(uri).spawn();
+
+        match child {
+            Ok(mut child) => {
+                // Reap the opener in the background so it doesn't linger as a zombie
+                std::thread::spawn(move || child.wait());
+                self.show_notification(format!("Opening {}", uri));
+            }
+            Err(e) => {
+                warn!("Failed to open hyperlink {}: {}", uri, e);
+                self.show_notification(format!("Failed to open link: {}", e));
+            }
+        }
     }
 
     /// Handle keyboard events with optimal input processing
     async fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
         // BUG FIX #27: Use keybinding system to handle actions
-        use crate::keybindings::Action;
+        // Esc dismisses the keybinding cheat sheet
+        if self.show_keybindings && key.code == KeyCode::Esc {
+            self.show_keybindings = false;
+            self.dirty = true;
+            return Ok(());
+        }
+
+        // Paste confirmation intercept: only accept or decline reach the terminal
+        if self.pending_paste.is_some() {
+            let confirmed = match key.code {
+                KeyCode::Enter | KeyCode::Char('y' | 'Y') => Some(true),
+                KeyCode::Esc | KeyCode::Char('n' | 'N') => Some(false),
+                _ => None,
+            };
+            if let Some(confirmed) = confirmed {
+                if let Some(text) = self.resolve_paste(confirmed) {
+                    self.write_paste(&text).await?;
+                }
+            }
+            return Ok(());
+        }
+
+        // Command palette intercept: keys filter and pick an entry
+        if self.command_palette.is_some()
+            && !matches!(
+                (key.code, key.modifiers),
+                (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL)
+            )
+        {
+            match key.code {
+                KeyCode::Esc => self.command_palette = None,
+                KeyCode::Enter => {
+                    let entry = self
+                        .command_palette
+                        .take()
+                        .and_then(|palette| palette.selected().cloned());
+                    if let Some(entry) = entry {
+                        self.run_palette_entry(entry).await?;
+                    }
+                }
+                KeyCode::Up => self.palette_move(-1),
+                KeyCode::Down => self.palette_move(1),
+                KeyCode::Backspace => {
+                    if let Some(palette) = self.command_palette.as_mut() {
+                        palette.pop();
+                    }
+                }
+                KeyCode::Char(c)
+                    if !key.modifiers.contains(KeyModifiers::CONTROL)
+                        && !key.modifiers.contains(KeyModifiers::ALT) =>
+                {
+                    if let Some(palette) = self.command_palette.as_mut() {
+                        palette.push(c);
+                    }
+                }
+                _ => {}
+            }
+            self.dirty = true;
+            return Ok(());
+        }
+
+        // Session prompt intercept: capture keys for the save-as name or picker
+        if self.session_prompt.is_some()
+            && !matches!(
+                (key.code, key.modifiers),
+                (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL)
+            )
+        {
+            match key.code {
+                KeyCode::Esc => self.session_prompt_cancel(),
+                KeyCode::Enter => self.session_prompt_submit(),
+                KeyCode::Backspace => self.session_prompt_backspace(),
+                KeyCode::Up => self.session_prompt_move(-1),
+                KeyCode::Down => self.session_prompt_move(1),
+                KeyCode::Char(c)
+                    if !key.modifiers.contains(KeyModifiers::CONTROL)
+                        && !key.modifiers.contains(KeyModifiers::ALT) =>
+                {
+                    self.session_prompt_input(c);
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
 
         // Search mode intercept: capture keys for search query input
         if self.search_mode {
@@ -1512,6 +1968,15 @@ impl Terminal {
                         self.search_query.pop();
                         self.execute_search();
                     }
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::ALT) => {
+                        self.toggle_search_regex();
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                        self.toggle_search_case_sensitive();
+                    }
+                    KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::ALT) => {
+                        self.toggle_search_whole_word();
+                    }
                     KeyCode::Char(c)
                         if !key.modifiers.contains(KeyModifiers::CONTROL)
                             && !key.modifiers.contains(KeyModifiers::ALT) =>
@@ -1525,238 +1990,58 @@ impl Terminal {
             }
         }
 
-        if let Some(action) = self.keybindings.get_action(key.code, key.modifiers) {
-            match action {
-                Action::NewTab => {
-                    if self.config.terminal.enable_tabs {
-                        self.create_new_tab()?;
-                        return Ok(());
-                    }
-                }
-                Action::CloseTab => {
-                    // Close current tab (implement if multiple tabs exist)
-                    if self.sessions.len() > 1 {
-                        self.close_current_tab();
-                        return Ok(());
-                    }
-                }
-                Action::NextTab => {
-                    if self.config.terminal.enable_tabs {
-                        self.next_tab();
-                        return Ok(());
-                    }
-                }
-                Action::PrevTab => {
-                    if self.config.terminal.enable_tabs {
-                        self.prev_tab();
-                        return Ok(());
-                    }
-                }
-                Action::Copy => {
-                    // Copy visible terminal output to clipboard
-                    if let Err(e) = self.copy_to_clipboard() {
-                        warn!("Failed to copy to clipboard: {}", e);
-                        self.show_notification(format!("Copy failed: {}", e));
-                    } else {
-                        self.show_notification("Copied to clipboard!".to_string());
-                    }
-                    return Ok(());
-                }
-                Action::Paste => {
-                    // Paste from clipboard to shell
-                    if let Err(e) = self.paste_from_clipboard().await {
-                        warn!("Failed to paste from clipboard: {}", e);
-                        self.show_notification(format!("Paste failed: {}", e));
-                    } else {
-                        self.show_notification("Pasted from clipboard".to_string());
-                    }
-                    return Ok(());
-                }
-                Action::Search => {
-                    // Toggle search mode
-                    self.toggle_search_mode();
-                    return Ok(());
-                }
-                Action::SearchNext => {
-                    self.search_next();
-                    return Ok(());
-                }
-                Action::SearchPrev => {
-                    self.search_prev();
-                    return Ok(());
-                }
-                Action::ToggleResourceMonitor => {
-                    if self.resource_monitor.is_some() {
-                        self.show_resources = !self.show_resources;
-                        debug!(
-                            "Resource monitor: {}",
-                            if self.show_resources { "ON" } else { "OFF" }
-                        );
-                        return Ok(());
-                    }
-                }
-                Action::ToggleAutocomplete => {
-                    if self.autocomplete.is_some() {
-                        self.show_autocomplete = !self.show_autocomplete;
-                        debug!(
-                            "Autocomplete: {}",
-                            if self.show_autocomplete { "ON" } else { "OFF" }
-                        );
-                        self.show_notification(format!(
-                            "Autocomplete {}",
-                            if self.show_autocomplete {
-                                "enabled"
-                            } else {
-                                "disabled"
-                            }
-                        ));
-                        return Ok(());
-                    }
-                }
-                Action::NextTheme => {
-                    let theme_name = if let Some(ref mut tm) = self.theme_manager {
-                        tm.next_theme();
-                        tm.current().name.clone()
-                    } else {
-                        String::new()
-                    };
-                    if !theme_name.is_empty() {
-                        self.show_notification(format!("Theme: {}", theme_name));
-                        self.dirty = true;
-                    }
-                    return Ok(());
-                }
-                Action::PrevTheme => {
-                    let theme_name = if let Some(ref mut tm) = self.theme_manager {
-                        tm.prev_theme();
-                        tm.current().name.clone()
-                    } else {
-                        String::new()
-                    };
-                    if !theme_name.is_empty() {
-                        self.show_notification(format!("Theme: {}", theme_name));
-                        self.dirty = true;
-                    }
-                    return Ok(());
-                }
-                Action::SaveSession => {
-                    // Save current session
-                    if self.session_manager.is_some() {
-                        if let Err(e) = self.try_save_session() {
-                            warn!("Failed to save session: {}", e);
-                            self.show_notification(format!("Save failed: {}", e));
-                        } else {
-                            self.show_notification("Session saved!".to_string());
-                        }
-                        return Ok(());
-                    }
-                }
-                Action::LoadSession => {
-                    if self.session_manager.is_some() {
-                        if let Err(e) = self.load_last_session() {
-                            warn!("Failed to load session: {}", e);
-                            self.show_notification(format!("Load failed: {}", e));
-                        } else {
-                            self.show_notification("Session loaded!".to_string());
-                        }
-                        return Ok(());
-                    }
-                }
-                Action::SplitHorizontal => {
-                    if self.enable_split_pane && self.sessions.len() >= 2 {
-                        self.split_orientation = SplitOrientation::Horizontal;
-                        self.show_notification("Split: Horizontal".to_string());
-                        self.dirty = true;
-                        return Ok(());
-                    }
-                }
-                Action::SplitVertical => {
-                    if self.enable_split_pane && self.sessions.len() >= 2 {
-                        self.split_orientation = SplitOrientation::Vertical;
-                        self.show_notification("Split: Vertical".to_string());
-                        self.dirty = true;
-                        return Ok(());
-                    }
-                }
-                Action::Clear => {
-                    // Clear current buffer
-                    if let Some(buf) = self.output_buffers.get_mut(self.active_session) {
-                        buf.clear();
-                        if let Some(len) = self.cached_buffer_lens.get_mut(self.active_session) {
-                            *len = 0;
-                        }
-                        self.dirty = true;
-                        return Ok(());
-                    }
-                }
-                Action::ExecuteLua(ref lua_code) => {
-                    // Execute custom Lua keybinding
-                    if let Some(ref executor) = self.hooks_executor {
-                        let cwd = self
-                            .keybindings
-                            .shell_integration()
-                            .current_dir
-                            .as_deref()
-                            .unwrap_or("");
-                        let last_cmd = self
-                            .keybindings
-                            .shell_integration()
-                            .last_command
-                            .as_deref()
-                            .unwrap_or("");
-
-                        if let Err(e) = executor.execute_custom_keybinding(lua_code, cwd, last_cmd)
-                        {
-                            warn!("Custom keybinding execution failed: {}", e);
-                            self.show_notification(format!("Keybinding error: {}", e));
-                        } else {
-                            debug!("Custom Lua keybinding executed successfully");
-                        }
-                        return Ok(());
-                    }
-                }
-                _ => {
-                    // Other actions not yet handled - fall through to default handling
-                }
-            }
-        }
-
-        // Fallback to default key handling
-        match (key.code, key.modifiers) {
-            // Quit (Ctrl+C or Ctrl+D) - not in keybindings to avoid accidental quit
-            (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL) => {
-                debug!("Quit signal received");
-
-                // Execute shutdown hook before quitting
-                if let Some(ref executor) = self.hooks_executor {
-                    if let Some(ref script) = self.config.hooks.on_shutdown {
-                        if let Err(e) = executor.on_shutdown(script) {
-                            warn!("Shutdown hook execution failed: {}", e);
-                        }
-                    }
-                }
-
-                self.should_quit = true;
-            }
-
-            // Regular character input (Bug #1: track ALL characters including shifted)
-            (KeyCode::Char(c), modifiers) => {
-                // Execute key press hook if configured
-                if let Some(ref executor) = self.hooks_executor {
-                    if let Some(ref script) = self.config.hooks.on_key_press {
-                        let key_info = format!(
-                            "{}+{:?}",
-                            if modifiers.contains(KeyModifiers::CONTROL) {
-                                "Ctrl"
-                            } else {
-                                ""
-                            },
-                            c
-                        );
-                        if let Err(e) = executor.on_key_press(script, &key_info) {
-                            debug!("Key press hook execution failed: {}", e);
-                        }
-                    }
+        let resolution = self
+            .keybindings
+            .borrow_mut()
+            .resolve_key(key.code, key.modifiers, std::time::Instant::now());
+        let action = match resolution {
+            // Swallow chord prefixes until the chord completes or is abandoned
+            KeyResolution::ChordPending => return Ok(()),
+            KeyResolution::Action(action) => Some(action),
+            KeyResolution::Unbound => None,
+        };
+
+        if let Some(action) = action {
+            if self.run_action(action).await? {
+                return Ok(());
+            }
+        }
+
+        // A tab whose shell has exited takes no more input, but Ctrl+C/Ctrl+D still quit
+        let quit_key = matches!(
+            (key.code, key.modifiers),
+            (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL)
+        );
+        if !quit_key && self.active_session_exited() {
+            return Ok(());
+        }
+
+        // Fallback to default key handling
+        match (key.code, key.modifiers) {
+            // Quit (Ctrl+C or Ctrl+D) - not in keybindings to avoid accidental quit
+            (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL) => {
+                debug!("Quit signal received");
+                self.quit();
+            }
+
+            // Regular character input (Bug #1: track ALL characters including shifted)
+            (KeyCode::Char(c), modifiers) => {
+                // Execute key press hook if configured
+                if let Some(ref script) = self.config.hooks.on_key_press {
+                    let key_info = format!(
+                        "{}+{:?}",
+                        if modifiers.contains(KeyModifiers::CONTROL) {
+                            "Ctrl"
+                        } else {
+                            ""
+                        },
+                        c
+                    );
+                    self.with_hooks(|executor| {
+                        if let Err(e) = executor.on_key_press(script, &key_info) {
+                            debug!("Key press hook execution failed: {}", e);
+                        }
+                    });
                 }
 
                 if let Some(session) = self.sessions.get(self.active_session) {
@@ -1773,8 +2058,8 @@ impl Terminal {
                         session.write_input(s.as_bytes()).await?;
 
                         // Track bytes sent for backspace calculation (Bug #2)
-                        if let Some(cmd_buf) = self.command_buffers.get_mut(self.active_session) {
-                            cmd_buf.extend_from_slice(s.as_bytes());
+                        if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                            tab.command.extend_from_slice(s.as_bytes());
                         }
                     }
                 }
@@ -1788,21 +2073,9 @@ impl Terminal {
             // Backspace (Bug #2: track byte removal properly)
             (KeyCode::Backspace, _) => {
                 if let Some(session) = self.sessions.get(self.active_session) {
-                    // Remove last UTF-8 character from command buffer (Bug #2)
-                    if let Some(cmd_buf) = self.command_buffers.get_mut(self.active_session) {
-                        // Pop one complete UTF-8 character from the end
-                        // UTF-8 encoding: ASCII is 0xxxxxxx, lead bytes are 11xxxxxx, continuation bytes are 10xxxxxx
-                        // First, pop any trailing continuation bytes (10xxxxxx pattern)
-                        while let Some(&last) = cmd_buf.last() {
-                            if (last & 0xC0) == 0x80 {
-                                // This is a continuation byte, pop it
-                                cmd_buf.pop();
-                            } else {
-                                // This is either ASCII or a lead byte, pop it and we're done
-                                cmd_buf.pop();
-                                break;
-                            }
-                        }
+                    // Remove what is drawn as the last character; the shell gets one DEL
+                    if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                        pop_grapheme(&mut tab.command);
                     }
                     session.write_input(&[127]).await?;
                 }
@@ -1811,26 +2084,33 @@ impl Terminal {
             // Arrow keys - clear command buffer on history navigation
             (KeyCode::Up, _) => {
                 if let Some(session) = self.sessions.get(self.active_session) {
-                    if let Some(cmd_buf) = self.command_buffers.get_mut(self.active_session) {
-                        cmd_buf.clear();
+                    if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                        tab.command.clear();
                     }
                     session.write_input(b"\x1b[A").await?;
                 }
             }
             (KeyCode::Down, _) => {
                 if let Some(session) = self.sessions.get(self.active_session) {
-                    if let Some(cmd_buf) = self.command_buffers.get_mut(self.active_session) {
-                        cmd_buf.clear();
+                    if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                        tab.command.clear();
                     }
                     session.write_input(b"\x1b[B").await?;
                 }
             }
+            // Right and End accept ghost text if there is any
+            (KeyCode::Right | KeyCode::End, _) if self.ghost_suggestion().is_some() => {
+                self.accept_ghost_suggestion().await?;
+            }
             (KeyCode::Right, _) => {
                 if let Some(session) = self.sessions.get(self.active_session) {
                     session.write_input(b"\x1b[C").await?;
                 }
             }
+            // Left and Home move the cursor off the end of the line, so the
+            // ghost text no longer follows it
             (KeyCode::Left, _) => {
+                self.dismiss_ghost_suggestion();
                 if let Some(session) = self.sessions.get(self.active_session) {
                     session.write_input(b"\x1b[D").await?;
                 }
@@ -1838,6 +2118,7 @@ impl Terminal {
 
             // Home key - move to beginning of line
             (KeyCode::Home, _) => {
+                self.dismiss_ghost_suggestion();
                 if let Some(session) = self.sessions.get(self.active_session) {
                     session.write_input(b"\x1b[H").await?;
                 }
@@ -1878,8 +2159,9 @@ impl Terminal {
                     session.write_input(b"\t").await?;
                 }
             }
-            // Escape key - return to bottom if scrolled, otherwise no-op
+            // Escape key - dismiss ghost text and return to bottom if scrolled
             (KeyCode::Esc, _) => {
+                self.dismiss_ghost_suggestion();
                 self.scroll_to_bottom();
             }
 
@@ -1889,23 +2171,467 @@ impl Terminal {
         Ok(())
     }
 
+    /// Completion shown as ghost text after the typed command
+    ///
+    /// Comes from autocomplete history. Hidden while scrolled back, while a
+    /// program such as vim has the alternate screen, and for a command buffer
+    /// the user dismissed it for.
+    fn ghost_suggestion(&self) -> Option<String> {
+        let ac = self.autocomplete.as_ref()?;
+        let cmd_buf = &self.tabs.get(self.active_session)?.command;
+        if self.scroll_offset() > 0
+            || self.in_alternate_screen(self.active_session)
+            || self.ghost_dismissed.as_ref() == Some(cmd_buf)
+        {
+            return None;
+        }
+        let input = std::str::from_utf8(cmd_buf).ok()?;
+        ac.ghost_text(input).map(str::to_string)
+    }
+
+    /// Type the ghost text into the shell as if the user had
+    async fn accept_ghost_suggestion(&mut self) -> Result<()> {
+        let Some(ghost) = self.ghost_suggestion() else {
+            return Ok(());
+        };
+        if let Some(session) = self.sessions.get(self.active_session) {
+            session.write_input(ghost.as_bytes()).await?;
+            if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                tab.command.extend_from_slice(ghost.as_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Hide the ghost text until the command buffer changes
+    fn dismiss_ghost_suggestion(&mut self) {
+        self.ghost_dismissed = self
+            .tabs
+            .get(self.active_session)
+            .map(|tab| tab.command.clone());
+    }
+
+    /// Perform a keybinding action
+    ///
+    /// Returns `false` for actions that don't apply right now (e.g. tab
+    /// actions with tabs disabled), so the key falls through to the shell.
+    #[allow(clippy::too_many_lines)]
+    async fn run_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::NewTab => {
+                if self.config.terminal.enable_tabs {
+                    self.create_new_tab()?;
+                    return Ok(true);
+                }
+            }
+            Action::CloseTab => {
+                // Close current tab (implement if multiple tabs exist)
+                if self.sessions.len() > 1 {
+                    self.close_current_tab();
+                    return Ok(true);
+                }
+            }
+            Action::NextTab => {
+                if self.config.terminal.enable_tabs {
+                    self.next_tab();
+                    return Ok(true);
+                }
+            }
+            Action::PrevTab => {
+                if self.config.terminal.enable_tabs {
+                    self.prev_tab();
+                    return Ok(true);
+                }
+            }
+            Action::Copy => {
+                // Copy visible terminal output to clipboard
+                if let Err(e) = self.copy_to_clipboard() {
+                    warn!("Failed to copy to clipboard: {}", e);
+                    self.show_notification(format!("Copy failed: {}", e));
+                } else {
+                    self.show_notification("Copied to clipboard!".to_string());
+                }
+                return Ok(true);
+            }
+            Action::CopyRich => {
+                // Copy the selection as HTML, keeping its colors
+                match self.copy_selection_rich() {
+                    Ok(true) => self.show_notification("Copied with colors".to_string()),
+                    Ok(false) => self.show_notification("Nothing selected".to_string()),
+                    Err(e) => {
+                        warn!("Failed to copy selection with colors: {}", e);
+                        self.show_notification(format!("Copy failed: {}", e));
+                    }
+                }
+                return Ok(true);
+            }
+            Action::Paste => {
+                // Paste from clipboard to shell
+                if let Err(e) = self.paste_from_clipboard().await {
+                    warn!("Failed to paste from clipboard: {}", e);
+                    self.show_notification(format!("Paste failed: {}", e));
+                } else if self.pending_paste.is_none() {
+                    self.show_notification("Pasted from clipboard".to_string());
+                }
+                return Ok(true);
+            }
+            Action::Search => {
+                // Toggle search mode
+                self.toggle_search_mode();
+                return Ok(true);
+            }
+            Action::SearchNext => {
+                self.search_next();
+                return Ok(true);
+            }
+            Action::SearchPrev => {
+                self.search_prev();
+                return Ok(true);
+            }
+            Action::ToggleResourceMonitor => {
+                if self.resource_monitor.is_some() {
+                    self.show_resources = !self.show_resources;
+                    debug!(
+                        "Resource monitor: {}",
+                        if self.show_resources { "ON" } else { "OFF" }
+                    );
+                    return Ok(true);
+                }
+            }
+            Action::ToggleAutocomplete => {
+                if self.autocomplete.is_some() {
+                    self.show_autocomplete = !self.show_autocomplete;
+                    debug!(
+                        "Autocomplete: {}",
+                        if self.show_autocomplete { "ON" } else { "OFF" }
+                    );
+                    self.show_notification(format!(
+                        "Autocomplete {}",
+                        if self.show_autocomplete {
+                            "enabled"
+                        } else {
+                            "disabled"
+                        }
+                    ));
+                    return Ok(true);
+                }
+            }
+            Action::NextTheme => {
+                let theme_name = if let Some(ref mut tm) = self.theme_manager {
+                    tm.next_theme();
+                    tm.current().name.clone()
+                } else {
+                    String::new()
+                };
+                if !theme_name.is_empty() && self.reapply_theme_palette() {
+                    self.show_notification(format!("Theme: {}", theme_name));
+                    self.dirty = true;
+                }
+                return Ok(true);
+            }
+            Action::PrevTheme => {
+                let theme_name = if let Some(ref mut tm) = self.theme_manager {
+                    tm.prev_theme();
+                    tm.current().name.clone()
+                } else {
+                    String::new()
+                };
+                if !theme_name.is_empty() && self.reapply_theme_palette() {
+                    self.show_notification(format!("Theme: {}", theme_name));
+                    self.dirty = true;
+                }
+                return Ok(true);
+            }
+            Action::SaveSession => {
+                // Prompt for a name to save the current session under
+                if self.session_manager.is_some() {
+                    self.open_save_session_prompt();
+                    return Ok(true);
+                }
+            }
+            Action::LoadSession => {
+                // Pick a saved session to load
+                if self.session_manager.is_some() {
+                    self.open_session_picker();
+                    return Ok(true);
+                }
+            }
+            Action::SplitHorizontal => {
+                if self.enable_split_pane && self.sessions.len() >= 2 {
+                    self.split_focused_pane(SplitDirection::Horizontal).await;
+                    return Ok(true);
+                }
+            }
+            Action::SplitVertical => {
+                if self.enable_split_pane && self.sessions.len() >= 2 {
+                    self.split_focused_pane(SplitDirection::Vertical).await;
+                    return Ok(true);
+                }
+            }
+            Action::FocusNextPane => {
+                return Ok(self.focus_pane(true));
+            }
+            Action::FocusPrevPane => {
+                return Ok(self.focus_pane(false));
+            }
+            Action::GrowPane => {
+                let resized = self.resize_focused_pane(SPLIT_RATIO_STEP);
+                if resized {
+                    self.resize_sessions().await;
+                }
+                return Ok(resized);
+            }
+            Action::ShrinkPane => {
+                let resized = self.resize_focused_pane(-SPLIT_RATIO_STEP);
+                if resized {
+                    self.resize_sessions().await;
+                }
+                return Ok(resized);
+            }
+            Action::Clear => {
+                // Clear current buffer
+                if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                    tab.output.clear();
+                    tab.spill.clear();
+                    self.invalidate_parse(self.active_session);
+                    self.dirty = true;
+                    return Ok(true);
+                }
+            }
+            Action::ClearScrollback => {
+                self.clear_scrollback();
+                return Ok(true);
+            }
+            Action::ScrollToTop => {
+                self.scroll_to_top();
+                return Ok(true);
+            }
+            Action::ScrollToBottom => {
+                self.scroll_to_bottom();
+                return Ok(true);
+            }
+            // Execute custom Lua keybinding
+            Action::ExecuteLua(ref lua_code) if self.hooks_executor.is_some() => {
+                // Copied out so the keybinding can call `furnace.bind`
+                let (cwd, last_cmd) = {
+                    let keybindings = self.keybindings.borrow();
+                    let shell = keybindings.shell_integration();
+                    (
+                        shell.current_dir.clone().unwrap_or_default(),
+                        shell.last_command.clone().unwrap_or_default(),
+                    )
+                };
+
+                let mut result = Ok(());
+                self.with_hooks(|executor| {
+                    result = executor.execute_custom_keybinding(lua_code, &cwd, &last_cmd);
+                });
+                if let Err(e) = result {
+                    warn!("Custom keybinding execution failed: {}", e);
+                    self.show_notification(format!("Keybinding error: {}", e));
+                } else {
+                    debug!("Custom Lua keybinding executed successfully");
+                }
+                return Ok(true);
+            }
+            Action::ShowKeybindings => {
+                self.show_keybindings = !self.show_keybindings;
+                self.dirty = true;
+                return Ok(true);
+            }
+            Action::ToggleStatsOverlay => {
+                self.show_stats = !self.show_stats;
+                self.dirty = true;
+                return Ok(true);
+            }
+            Action::OpenCommandPalette => {
+                self.open_command_palette();
+                return Ok(true);
+            }
+            Action::RestartShell => {
+                self.restart_shell().await;
+                return Ok(true);
+            }
+            Action::PrevCommand => {
+                self.jump_to_command(false);
+                return Ok(true);
+            }
+            Action::NextCommand => {
+                self.jump_to_command(true);
+                return Ok(true);
+            }
+            Action::Interrupt => {
+                if let Some(session) = self.sessions.get(self.active_session) {
+                    let result = session.send_signal(Signal::Interrupt).await;
+                    if let Err(e) = result {
+                        warn!("Failed to interrupt shell: {}", e);
+                        self.show_notification(format!("Interrupt failed: {e}"));
+                    }
+                    return Ok(true);
+                }
+            }
+            _ => {
+                // Other actions not yet handled - fall through to default handling
+            }
+        }
+        Ok(false)
+    }
+
+    /// Print a plugin's reply in the active session's output
+    fn show_plugin_reply(&mut self, reply: &str) {
+        if let Some(tab) = self.tabs.get_mut(self.active_session) {
+            tab.output.extend_from_slice(b"\r\n");
+            tab.output
+                .extend_from_slice(reply.replace('\n', "\r\n").as_bytes());
+            tab.output.extend_from_slice(b"\r\n");
+        }
+        self.dirty = true;
+    }
+
+    /// Start a fresh shell in the active tab if its shell has exited
+    ///
+    /// The old output stays, followed by a separator. If the shell can't be
+    /// started the tab stays as it was.
+    async fn restart_shell(&mut self) {
+        let index = self.active_session;
+        let Some(session) = self.sessions.get_mut(index) else {
+            return;
+        };
+        if session.try_wait().is_none() {
+            self.show_notification("Shell is still running".to_string());
+            return;
+        }
+
+        if let Err(e) = session.respawn().await {
+            warn!("Failed to restart shell: {}", e);
+            self.show_notification(format!("Failed to restart shell: {e}"));
+            return;
+        }
+
+        // A program that died on the alternate screen never switched back
+        self.switch_screen(index, false);
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.reset_for_new_shell();
+            tab.output
+                .extend_from_slice(b"\r\n--- shell restarted ---\r\n");
+            tab.scroll_offset = 0;
+        }
+        self.show_notification("Shell restarted".to_string());
+        self.dirty = true;
+    }
+
+    /// Key combo bound to `action`, if any
+    fn combo_for(&self, action: &Action) -> Option<String> {
+        self.keybindings
+            .borrow()
+            .bindings()
+            .into_iter()
+            .find(|(_, bound)| bound == action)
+            .map(|(combo, _)| combo)
+    }
+
+    /// Open the command palette on every action, plugin command and theme
+    fn open_command_palette(&mut self) {
+        let mut entries: Vec<PaletteEntry> = PALETTE_ACTIONS
+            .iter()
+            .map(|action| PaletteEntry::action(action.clone(), self.combo_for(action)))
+            .collect();
+        if let Some(ref plugins) = self.plugin_manager {
+            entries.extend(
+                plugins
+                    .namespaced_commands()
+                    .iter()
+                    .map(|command| PaletteEntry::plugin_command(command)),
+            );
+        }
+        if let Some(ref themes) = self.theme_manager {
+            entries.extend(
+                themes
+                    .available_theme_names()
+                    .iter()
+                    .map(|name| PaletteEntry::theme(name)),
+            );
+        }
+
+        self.command_palette = Some(CommandPalette::new(entries));
+        self.dirty = true;
+    }
+
+    /// Move the command palette selection by `delta`
+    fn palette_move(&mut self, delta: isize) {
+        if let Some(palette) = self.command_palette.as_mut() {
+            palette.move_selection(delta);
+        }
+    }
+
+    /// Run the entry picked in the command palette
+    async fn run_palette_entry(&mut self, entry: PaletteEntry) -> Result<()> {
+        match entry.target {
+            PaletteTarget::Action(action) => {
+                if !self.run_action(action).await? {
+                    self.show_notification(format!("{} is not available", entry.label));
+                }
+            }
+            PaletteTarget::PluginCommand(command) => {
+                let reply = self
+                    .plugin_manager
+                    .as_ref()
+                    .and_then(|plugins| plugins.handle_command(&command));
+                match reply {
+                    Some(reply) => self.show_plugin_reply(&reply),
+                    None => self.show_notification(format!("{command}: no reply")),
+                }
+            }
+            PaletteTarget::Theme(name) => {
+                let switched = self
+                    .theme_manager
+                    .as_mut()
+                    .is_some_and(|themes| themes.switch_theme(&name));
+                if switched && self.reapply_theme_palette() {
+                    self.show_notification(format!("Theme: {name}"));
+                    self.dirty = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Handle Enter key
     async fn handle_enter(&mut self) -> Result<()> {
         if let Some(session) = self.sessions.get(self.active_session) {
             // Get the current command as a string from bytes
             let command = self
-                .command_buffers
+                .tabs
                 .get(self.active_session)
-                .map_or(Cow::Borrowed(""), |b| String::from_utf8_lossy(b));
+                .map_or_else(String::new, |tab| {
+                    String::from_utf8_lossy(&tab.command).into_owned()
+                });
+
+            // A command a plugin handles is answered here instead of by the shell
+            let plugin_reply = self
+                .plugin_manager
+                .as_ref()
+                .filter(|_| !command.trim().is_empty())
+                .and_then(|plugins| plugins.handle_command(command.trim()));
+            if let Some(reply) = plugin_reply {
+                session.write_input(KILL_LINE).await?;
+                session.write_input(b"\r").await?;
+
+                self.show_plugin_reply(&reply);
+                if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                    tab.command.clear();
+                }
+                return Ok(());
+            }
 
             // Execute command start hook
             if !command.trim().is_empty() {
-                if let Some(ref executor) = self.hooks_executor {
-                    if let Some(ref script) = self.config.hooks.on_command_start {
+                if let Some(ref script) = self.config.hooks.on_command_start {
+                    self.with_hooks(|executor| {
                         if let Err(e) = executor.on_command_start(script, &command) {
                             debug!("Command start hook execution failed: {}", e);
                         }
-                    }
+                    });
                 }
             }
 
@@ -1914,6 +2640,10 @@ impl Terminal {
 
             // Start progress bar (Bug #24: avoid clone)
             if !command.trim().is_empty() {
+                // Stands in for OSC 133 marks when jumping between commands
+                if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                    tab.prompt_marks.record_submitted(tab.output.len());
+                }
                 if let Some(ref mut pb) = self.progress_bar {
                     pb.start_ref(&command);
                     self.dirty = true;
@@ -1921,8 +2651,8 @@ impl Terminal {
             }
 
             // Clear command buffer
-            if let Some(cmd_buf) = self.command_buffers.get_mut(self.active_session) {
-                cmd_buf.clear();
+            if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                tab.command.clear();
             }
         }
         Ok(())
@@ -1935,7 +2665,30 @@ impl Terminal {
             self.terminal_cols, self.terminal_rows
         );
 
-        // Prepare environment variables from config
+        // Open where the shell last reported being, if that directory exists here
+        let working_dir = self
+            .keybindings
+            .borrow()
+            .shell_integration()
+            .current_dir
+            .clone()
+            .filter(|dir| std::path::Path::new(dir).is_dir())
+            .or_else(|| self.config.shell.working_dir.clone());
+
+        let session = self.spawn_shell(working_dir.as_deref())?;
+        self.push_tab(session);
+        Ok(())
+    }
+
+    /// Open a tab running `session` after the others and switch to it
+    fn push_tab(&mut self, session: ShellSession) {
+        self.sessions.push(session);
+        self.tabs.push(Tab::new());
+        self.active_session = self.sessions.len() - 1;
+    }
+
+    /// Start the configured shell in `working_dir` at the current size
+    fn spawn_shell(&self, working_dir: Option<&str>) -> Result<ShellSession> {
         let env_vars: Vec<(&str, &str)> = self
             .config
             .shell
@@ -1944,23 +2697,14 @@ impl Terminal {
             .map(|(k, v)| (k.as_str(), v.as_str()))
             .collect();
 
-        let session = ShellSession::new_with_env(
+        ShellSession::new_with_env(
             &self.config.shell.default_shell,
-            self.config.shell.working_dir.as_deref(),
+            working_dir,
             self.terminal_rows, // Bug #7: use current size
             self.terminal_cols,
             &env_vars,
-        )?;
-
-        self.sessions.push(session);
-        self.output_buffers.push(Vec::with_capacity(1024 * 1024));
-        self.command_buffers.push(Vec::new());
-        self.cached_styled_lines.push(Vec::new());
-        self.cached_buffer_lens.push(0);
-        self.active_session = self.sessions.len() - 1;
-
-        Ok(())
-    }
+        )
+    }
 
     /// Switch to next tab (Bug #8: enforce scrollback limit on switch)
     fn next_tab(&mut self) {
@@ -1988,93 +2732,341 @@ impl Terminal {
         }
     }
 
+    /// Switch to the tab at `index`
+    fn select_tab(&mut self, index: usize) {
+        if index >= self.sessions.len() || index == self.active_session {
+            return;
+        }
+        // Bug #8: Enforce scrollback limit on current tab before switching
+        self.enforce_scrollback_limit(self.active_session);
+
+        self.active_session = index;
+        self.dirty = true;
+        debug!("Switched to tab {}", index);
+    }
+
+    /// Move the tab at `from` to `to`, shifting the tabs in between over by one
+    ///
+    /// Every per-tab list moves together, and the active tab and the tabs
+    /// shown in panes stay the same sessions at their new indices.
+    fn move_tab(&mut self, from: usize, to: usize) {
+        let count = self.sessions.len();
+        if from >= count || to >= count || from == to {
+            return;
+        }
+
+        if let Some(panes) = self.pane_sessions() {
+            self.panes.set_leaves(&panes);
+        }
+        let panes: Vec<usize> = self
+            .panes
+            .leaves()
+            .into_iter()
+            .map(|session| tab_bar::moved_index(session, from, to))
+            .collect();
+        self.panes.set_leaves(&panes);
+
+        tab_bar::move_item(&mut self.sessions, from, to);
+        tab_bar::move_item(&mut self.tabs, from, to);
+        self.active_session = tab_bar::moved_index(self.active_session, from, to);
+
+        self.dirty = true;
+        debug!("Moved tab {} to {}", from, to);
+    }
+
     /// Close current tab
     fn close_current_tab(&mut self) {
         if self.sessions.len() <= 1 {
             // Don't close the last tab
             return;
         }
+        self.close_tab_at(self.active_session);
+    }
+
+    /// Remove the tab at `index` with its session and associated data
+    fn close_tab_at(&mut self, index: usize) {
+        if index >= self.sessions.len() {
+            return;
+        }
+
+        // Close the tab's pane, if it has one
+        if let Some(panes) = self.pane_sessions() {
+            self.panes.set_leaves(&panes);
+        }
+        let closed_pane = self.panes.remove_session(index);
+        if closed_pane.is_some_and(|pane| pane < self.focused_pane) {
+            self.focused_pane -= 1;
+        }
+        self.focused_pane = self.focused_pane.min(self.panes.leaf_count() - 1);
 
-        // Remove the session and associated data
-        self.sessions.remove(self.active_session);
-        self.output_buffers.remove(self.active_session);
-        self.command_buffers.remove(self.active_session);
-        self.cached_styled_lines.remove(self.active_session);
-        self.cached_buffer_lens.remove(self.active_session);
+        self.sessions.remove(index);
+        if index < self.tabs.len() {
+            self.tabs.remove(index);
+        }
 
-        // Adjust active session if needed
-        if self.active_session >= self.sessions.len() {
-            self.active_session = self.sessions.len().saturating_sub(1);
+        // Keep the same tab active, or the last one if it was removed
+        if index < self.active_session || self.active_session >= self.sessions.len() {
+            self.active_session = self.active_session.saturating_sub(1);
         }
 
         self.dirty = true;
         debug!("Closed tab, now on tab {}", self.active_session);
     }
 
-    /// Save current session state
+    /// Save current session state under a timestamped name
     fn try_save_session(&mut self) -> Result<()> {
-        use crate::session::{SavedSession, TabState};
+        let name = format!(
+            "Session {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        self.save_session_as(name, None)
+    }
 
-        let tabs: Vec<TabState> = self
-            .output_buffers
-            .iter()
-            .enumerate()
-            .map(|(i, buf)| TabState {
-                output: String::from_utf8_lossy(buf).to_string(),
-                working_dir: None,
+    /// Save current session state under `name`, replacing the session `id` if given
+    fn save_session_as(&mut self, name: String, id: Option<String>) -> Result<()> {
+        let session = self.snapshot_session(name, id);
+
+        if let Some(ref mut sm) = self.session_manager {
+            sm.save_session(&session)?;
+        }
+        Ok(())
+    }
+
+    /// Capture the current tabs as a saved session, with a fresh id unless one is given
+    fn snapshot_session(&self, name: String, id: Option<String>) -> SavedSession {
+        use crate::session::TabState;
+
+        let tabs: Vec<TabState> = (0..self.tabs.len())
+            .map(|i| TabState {
+                output: String::from_utf8_lossy(&self.full_output(i)).to_string(),
+                working_dir: self.tab_dir(i),
                 active: i == self.active_session,
+                scroll_offset: self.tabs.get(i).map_or(0, |tab| tab.scroll_offset),
             })
             .collect();
 
-        let session = SavedSession {
-            id: uuid::Uuid::new_v4().to_string(),
-            name: format!(
-                "Session {}",
-                chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
-            ),
+        SavedSession {
+            id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            name,
             created_at: chrono::Local::now(),
             tabs,
-        };
+        }
+    }
 
-        if let Some(ref mut sm) = self.session_manager {
-            sm.save_session(&session)?;
+    /// Working directory the shell in tab `index` last reported
+    fn tab_dir(&self, index: usize) -> Option<String> {
+        self.tabs
+            .get(index)
+            .and_then(|tab| tab.titles.dir())
+            .map(str::to_string)
+    }
+
+    /// Snapshot for the autosave slot if the autosave interval has elapsed at `now`
+    fn due_autosave(&mut self, now: std::time::Instant) -> Option<SavedSession> {
+        if self.session_manager.is_none() || self.tabs.is_empty() || !self.autosave_timer.poll(now)
+        {
+            return None;
+        }
+        Some(self.snapshot_session(AUTOSAVE_NAME.to_string(), Some(AUTOSAVE_ID.to_string())))
+    }
+
+    /// Offer to restore an autosave left behind by a previous run
+    fn offer_autosave_restore(&mut self) {
+        let autosave = self
+            .session_manager
+            .as_ref()
+            .and_then(|sm| sm.load_session(AUTOSAVE_ID).ok());
+        if let Some(autosave) = autosave {
+            self.session_prompt = Some(SessionPrompt::RestoreAutosave {
+                saved_at: autosave.created_at,
+            });
+            self.dirty = true;
+        }
+    }
+
+    /// Remove the autosave slot, if any
+    fn discard_autosave(&mut self) {
+        if let Some(ref sm) = self.session_manager {
+            if sm
+                .sessions_dir()
+                .join(format!("{AUTOSAVE_ID}.json"))
+                .exists()
+            {
+                if let Err(e) = sm.delete_session(AUTOSAVE_ID) {
+                    warn!("Failed to discard autosave: {}", e);
+                }
+            }
         }
-        Ok(())
     }
 
     /// Bug #8: Enforce scrollback limit on a specific tab
+    ///
+    /// With `scrollback_to_disk` whole lines past the limit are moved to the
+    /// tab's spill file instead of dropped. Output paged back in while the
+    /// view is scrolled up doesn't count against the limit, and another
+    /// limit's worth of new output can arrive below it, until the view
+    /// follows the output again. On the alternate screen only the lines on
+    /// screen are kept.
     fn enforce_scrollback_limit(&mut self, tab_index: usize) {
-        if let Some(buffer) = self.output_buffers.get_mut(tab_index) {
-            let max_buffer = self.config.terminal.scrollback_lines * 256;
-            if buffer.len() > max_buffer {
-                let excess = buffer.len() - max_buffer;
-                buffer.drain(..excess);
-                // Invalidate caches
-                if let Some(len) = self.cached_buffer_lens.get_mut(tab_index) {
-                    *len = 0;
-                }
+        if self.in_alternate_screen(tab_index) {
+            // No scrollback: only the lines on screen are kept
+            let rows = self.viewport_rows();
+            let Some(tab) = self.tabs.get_mut(tab_index) else {
+                return;
+            };
+            let start = modes::screen_start(&tab.output, rows);
+            if start > 0 {
+                tab.output.drain(..start);
+                tab.prompt_marks.trim_front(start);
+                self.invalidate_parse(tab_index);
+            }
+            return;
+        }
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return;
+        };
+        let buffer = &mut tab.output;
+        let mut spill = Some(&mut tab.spill).filter(|_| self.config.terminal.scrollback_to_disk);
+        let limit = self.config.terminal.scrollback_lines * 256;
+        let max_buffer = match spill.as_ref().map_or(0, |spill| spill.paged_in()) {
+            0 => limit,
+            paged_in => paged_in + limit * 2,
+        };
+        if buffer.len() <= max_buffer {
+            return;
+        }
+
+        let excess = buffer.len() - max_buffer;
+        let removed = match spill.as_mut() {
+            Some(spill) => {
+                let cut = spill::spill_point(buffer, excess);
+                spill.spill(&buffer[..cut]);
+                cut
+            }
+            None => excess,
+        };
+        buffer.drain(..removed);
+        tab.prompt_marks.trim_front(removed);
+        self.invalidate_parse(tab_index);
+    }
+
+    /// Drop the active tab's output above the lines on screen
+    ///
+    /// The screen stays as it is, so the prompt and the latest output
+    /// survive; scrolled up, the lines below the screen stay too. Output
+    /// spilled to disk is dropped along with the rest. The alternate screen
+    /// has no scrollback, so there it does nothing.
+    fn clear_scrollback(&mut self) {
+        let index = self.active_session;
+        if self.in_alternate_screen(index) {
+            return;
+        }
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+        let output = String::from_utf8_lossy(&tab.output);
+        let total = AnsiParser::parse(&output).len();
+        let top = total.saturating_sub(self.viewport_rows() + self.scroll_offset());
+        let cut = line_start(&output, top);
+
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.output.drain(..cut);
+            tab.prompt_marks.trim_front(cut);
+            tab.spill.clear();
+        }
+        self.invalidate_parse(index);
+        self.dirty = true;
+    }
+
+    /// Read a page of a tab's spilled output back in front of its buffer
+    ///
+    /// Returns false when nothing is left on disk.
+    fn page_in_scrollback(&mut self, tab_index: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return false;
+        };
+        let Some(page) = tab.spill.restore() else {
+            return false;
+        };
+        tab.output.splice(..0, page.iter().copied());
+        tab.prompt_marks.shift_back(page.len());
+        self.invalidate_parse(tab_index);
+        true
+    }
+
+    /// Number of a tab's output lines on disk, before the first one in memory
+    fn spilled_lines(&self, tab_index: usize) -> usize {
+        self.tabs.get(tab_index).map_or(0, |tab| tab.spill.lines())
+    }
+
+    /// A tab's whole output: what is on disk followed by what is in memory
+    ///
+    /// On the alternate screen it is only what is on that screen, as the
+    /// spill file belongs to the normal screen.
+    fn full_output(&self, tab_index: usize) -> Cow<'_, [u8]> {
+        let Some(tab) = self.tabs.get(tab_index) else {
+            return Cow::Borrowed(&[]);
+        };
+        let buffer = &tab.output;
+        if tab.spill.is_empty() || self.in_alternate_screen(tab_index) {
+            return Cow::Borrowed(buffer);
+        }
+        match tab.spill.read_all() {
+            Ok(mut output) => {
+                output.extend_from_slice(buffer);
+                Cow::Owned(output)
+            }
+            Err(e) => {
+                warn!("Failed to read scrollback from disk: {}", e);
+                Cow::Borrowed(buffer)
             }
         }
     }
 
-    /// Render UI with hardware acceleration (Bug #3: zero-copy rendering)
+    /// Render the UI headlessly and return the plain text of each row
     ///
-    /// The rendering path is determined by the hardware_acceleration config flag:
-    /// - When true: Uses GPU-accelerated rendering for high performance (170+ FPS)
-    /// - When false: Falls back to CPU rendering (current ratatui path)
+    /// Runs the same layout and widgets as [`Self::render`] against a ratatui
+    /// `TestBackend` instead of a TTY, so tab bars, progress bars and
+    /// notifications can be snapshot-tested without raw mode. Works with no
+    /// shell sessions. Trailing spaces are trimmed from each row.
+    pub fn render_to_lines(&mut self, cols: u16, rows: u16) -> Vec<String> {
+        use ratatui::backend::TestBackend;
+
+        // TestBackend draws into memory and never reports an I/O error
+        let Ok(mut backend) = RatatuiTerminal::new(TestBackend::new(cols, rows)) else {
+            return Vec::new();
+        };
+        if backend.draw(|f| self.render(f)).is_err() {
+            return Vec::new();
+        }
+
+        let buffer = backend.backend().buffer();
+        (0..rows)
+            .map(|y| {
+                let mut row = String::with_capacity(cols as usize);
+                let mut x = 0;
+                while x < cols {
+                    let symbol = buffer.get(x, y).symbol();
+                    row.push_str(symbol);
+                    // Wide glyphs cover the following cell(s), which hold filler
+                    x += symbol.width().max(1) as u16;
+                }
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Render the UI with ratatui (Bug #3: zero-copy rendering)
     ///
-    /// The font_size and cursor_style config values are used by the GPU renderer
-    /// when hardware acceleration is enabled.
+    /// Both paths draw this through their [`Renderer`]: [`Self::run_cpu`] to
+    /// the TTY and [`Self::run_gpu`] to a window. Which one runs is chosen by
+    /// [`renderer::select_renderer`] from the `hardware_acceleration` setting,
+    /// the `gpu` feature and GPU availability.
     #[allow(clippy::too_many_lines)]
     fn render(&mut self, f: &mut ratatui::Frame) {
         // Render background image/color if configured
         self.render_background(f);
 
-        // Note: When hardware_acceleration is enabled, this would delegate to GPU renderer
-        // For now, we use ratatui (CPU rendering) but config values are available
-        // for future GPU rendering pipeline integration
-        let _use_gpu = self.hardware_acceleration; // Available for GPU renderer switch
-
         let progress_visible = self.progress_bar.as_ref().is_some_and(|pb| pb.visible);
 
         let main_chunks = Layout::default()
@@ -2083,7 +3075,7 @@ impl Terminal {
                 Constraint::Length(u16::from(
                     self.config.terminal.enable_tabs && self.sessions.len() > 1,
                 )),
-                Constraint::Length(u16::from(self.notification_message.is_some())),
+                Constraint::Length(u16::try_from(self.notifications.len()).unwrap_or(u16::MAX)),
                 Constraint::Length(u16::from(progress_visible)),
                 Constraint::Min(0),
                 Constraint::Length(if self.show_autocomplete && self.autocomplete.is_some() {
@@ -2110,51 +3102,59 @@ impl Terminal {
 
         // Render tabs if enabled
         if self.config.terminal.enable_tabs && self.sessions.len() > 1 {
-            let tab_titles: Vec<Line> = (0..self.sessions.len())
+            let process_labels = self.session_process_labels();
+            let tab_labels = self.tab_labels();
+            let texts: Vec<String> = (0..self.sessions.len())
                 .map(|i| {
-                    let style = if i == self.active_session {
-                        Style::default()
-                            .fg(Color::Rgb(
-                                COLOR_COOL_RED.0,
-                                COLOR_COOL_RED.1,
-                                COLOR_COOL_RED.2,
-                            ))
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::Rgb(
-                            COLOR_REDDISH_GRAY.0,
-                            COLOR_REDDISH_GRAY.1,
-                            COLOR_REDDISH_GRAY.2,
-                        ))
-                    };
-                    Line::from(Span::styled(format!(" Tab {} ", i + 1), style))
+                    let name = tab_labels[i]
+                        .clone()
+                        .unwrap_or_else(|| format!("Tab {}", i + 1));
+                    match process_labels.as_ref().and_then(|labels| labels.get(i)) {
+                        Some(label) => tab_bar::tab_text(&format!("{name} ({label})")),
+                        None => tab_bar::tab_text(&name),
+                    }
                 })
                 .collect();
 
-            let tabs = Tabs::new(tab_titles)
-                .block(Block::default().borders(Borders::BOTTOM))
-                .select(self.active_session)
-                .style(Style::default().fg(Color::Rgb(
-                    COLOR_REDDISH_GRAY.0,
-                    COLOR_REDDISH_GRAY.1,
-                    COLOR_REDDISH_GRAY.2,
-                )))
-                .highlight_style(
-                    Style::default()
-                        .fg(Color::Rgb(
-                            COLOR_COOL_RED.0,
-                            COLOR_COOL_RED.1,
-                            COLOR_COOL_RED.2,
-                        ))
-                        .add_modifier(Modifier::BOLD),
-                );
+            let bar_style = Style::default().fg(Color::Rgb(
+                COLOR_REDDISH_GRAY.0,
+                COLOR_REDDISH_GRAY.1,
+                COLOR_REDDISH_GRAY.2,
+            ));
+            let active_style = Style::default()
+                .fg(Color::Rgb(
+                    COLOR_COOL_RED.0,
+                    COLOR_COOL_RED.1,
+                    COLOR_COOL_RED.2,
+                ))
+                .add_modifier(Modifier::BOLD);
+            let mut spans = Vec::with_capacity(texts.len() * 2);
+            for (i, text) in texts.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(tab_bar::DIVIDER, bar_style));
+                }
+                let style = if i == self.active_session {
+                    active_style
+                } else {
+                    bar_style
+                };
+                spans.push(Span::styled(text.clone(), style));
+            }
 
-            f.render_widget(tabs, tab_area);
+            // Remember where each tab went so clicks can be matched to it
+            self.tab_spans = tab_bar::layout(&texts);
+            self.tab_bar_area = tab_area;
+            // The bar is one row high, which a border would fill
+            f.render_widget(Paragraph::new(Line::from(spans)).style(bar_style), tab_area);
+        } else {
+            self.tab_bar_area = Rect::default();
+            self.tab_spans.clear();
         }
 
-        // Render translation notification if present
-        if let Some(ref msg) = self.notification_message {
-            let notification = Paragraph::new(msg.as_str())
+        // Render notifications, newest on top
+        if !self.notifications.is_empty() {
+            let lines: Vec<Line> = self.notifications.messages().map(Line::from).collect();
+            let notification = Paragraph::new(lines)
                 .style(
                     Style::default()
                         .fg(Color::Rgb(
@@ -2199,10 +3199,7 @@ impl Terminal {
 
         // Render terminal output (Bug #3: use cached styled lines)
         // Split pane implementation: when enabled, split content area and render multiple sessions
-        if self.enable_split_pane
-            && self.sessions.len() >= 2
-            && self.split_orientation != SplitOrientation::None
-        {
+        if self.split_visible() {
             self.render_split_panes(f, content_area);
         } else {
             // Single pane rendering
@@ -2229,48 +3226,346 @@ impl Terminal {
 
         // Render status bar
         self.render_status_bar(f, status_area);
+
+        if self.show_stats {
+            self.render_stats_overlay(f, content_area);
+        }
+
+        if let Some(paste) = &self.pending_paste {
+            Self::render_paste_overlay(f, paste);
+        }
+
+        // Keybinding cheat sheet draws over everything else
+        if self.show_keybindings {
+            self.render_keybindings_overlay(f);
+        }
+
+        if let Some(palette) = &self.command_palette {
+            Self::render_command_palette(f, palette);
+        }
+
+        if self.bell_flash.is_active(std::time::Instant::now()) {
+            let area = f.size();
+            f.buffer_mut()
+                .set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+        }
+    }
+
+    /// The active tab's window title, when it differs from the one shown last
+    ///
+    /// A tab that hasn't set a title, or cleared it, gets [`DEFAULT_WINDOW_TITLE`].
+    fn window_title_change(&mut self) -> Option<String> {
+        let title = self
+            .tabs
+            .get(self.active_session)
+            .and_then(|tab| tab.titles.window_title())
+            .unwrap_or(DEFAULT_WINDOW_TITLE);
+        if title == self.shown_title {
+            return None;
+        }
+        self.shown_title = title.to_string();
+        Some(self.shown_title.clone())
+    }
+
+    /// Run the `on_bell` hook, then flash and beep as `terminal.bell_style` says
+    fn ring_bell(&mut self) {
+        if let Some(ref script) = self.config.hooks.on_bell {
+            self.with_hooks(|executor| {
+                if let Err(e) = executor.on_bell(script) {
+                    warn!("on_bell hook failed: {}", e);
+                }
+            });
+        }
+        if self.bell_style.flashes() {
+            self.bell_flash.start(std::time::Instant::now());
+            self.dirty = true;
+        }
+        if self.bell_style.beeps() {
+            bell::beep();
+        }
+    }
+
+    /// Each tab's label from `terminal.tab_title_source`, `None` for the default
+    fn tab_labels(&mut self) -> Vec<Option<String>> {
+        let now = std::time::Instant::now();
+        let max_width = self.config.terminal.tab_title_max_width;
+        self.sessions
+            .iter()
+            .zip(&self.tabs)
+            .map(|(session, tab)| {
+                // Only look the program up when it's what the label shows
+                let process = match self.tab_title_source {
+                    title::TabTitleSource::Process => session
+                        .foreground_pid()
+                        .and_then(|pid| self.process_names.name(pid, now)),
+                    _ => None,
+                };
+                tab.titles
+                    .tab_label(self.tab_title_source, process.as_deref(), max_width)
+            })
+            .collect()
+    }
+
+    /// CPU and memory of each tab's shell while the resource monitor is shown
+    fn session_process_labels(&mut self) -> Option<Vec<String>> {
+        if !self.show_resources {
+            return None;
+        }
+        let monitor = self.resource_monitor.as_mut()?;
+        Some(
+            self.sessions
+                .iter()
+                .map(|session| {
+                    ProcessStats::label(session.pid().and_then(|pid| monitor.process_stats(pid)))
+                })
+                .collect(),
+        )
+    }
+
+    /// Render the command palette as a centered popup
+    fn render_command_palette(f: &mut ratatui::Frame, palette: &CommandPalette) {
+        let screen = f.size();
+        let width = screen.width.saturating_sub(4).min(60);
+        // Borders, the query line, and up to 12 entries
+        let visible = usize::from(screen.height.saturating_sub(5)).clamp(1, 12);
+
+        let matched_style = Style::default()
+            .fg(Color::Rgb(
+                COLOR_SEARCH_MATCH.0,
+                COLOR_SEARCH_MATCH.1,
+                COLOR_SEARCH_MATCH.2,
+            ))
+            .add_modifier(Modifier::BOLD);
+        let detail_style = Style::default().fg(Color::Rgb(
+            COLOR_STATUS_HINT.0,
+            COLOR_STATUS_HINT.1,
+            COLOR_STATUS_HINT.2,
+        ));
+        let selected_bg = Color::Rgb(COLOR_STATUS_BG.0, COLOR_STATUS_BG.1, COLOR_STATUS_BG.2);
+
+        let mut lines = vec![Line::from(vec![
+            Span::styled(
+                "> ",
+                Style::default()
+                    .fg(Color::Rgb(
+                        COLOR_COOL_RED.0,
+                        COLOR_COOL_RED.1,
+                        COLOR_COOL_RED.2,
+                    ))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(palette.query().to_string()),
+        ])];
+
+        // Scroll the list so the selection stays visible
+        let selected = palette.selected_index();
+        let first = selected.saturating_sub(visible - 1);
+        for (i, (entry, found)) in palette.matches().enumerate().skip(first).take(visible) {
+            let mut spans = vec![Span::raw(" ")];
+            for (j, ch) in entry.label.chars().enumerate() {
+                if found.positions.contains(&j) {
+                    spans.push(Span::styled(ch.to_string(), matched_style));
+                } else {
+                    spans.push(Span::raw(ch.to_string()));
+                }
+            }
+            if !entry.detail.is_empty() {
+                spans.push(Span::styled(format!("  {}", entry.detail), detail_style));
+            }
+            let mut line = Line::from(spans);
+            if i == selected {
+                line.patch_style(Style::default().bg(selected_bg));
+            }
+            lines.push(line);
+        }
+        if lines.len() == 1 {
+            lines.push(Line::from(Span::styled(" No matches", detail_style)));
+        }
+
+        let height = u16::try_from(lines.len() + 2).unwrap_or(u16::MAX);
+        let area = centered_popup(screen, width, height);
+
+        let popup = Paragraph::new(lines)
+            .style(Style::default().bg(Color::Rgb(
+                COLOR_PURE_BLACK.0,
+                COLOR_PURE_BLACK.1,
+                COLOR_PURE_BLACK.2,
+            )))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Command Palette (Esc to close) "),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+
+    /// Render frame rate, render time and buffer sizes in the top right of `area`
+    fn render_stats_overlay(&self, f: &mut ratatui::Frame, area: Rect) {
+        let stats = &self.frame_stats;
+        let millis = |time: Duration| time.as_secs_f64() * 1000.0;
+        let mut lines = vec![
+            Line::from(format!(
+                " FPS     {:.1} ",
+                stats.fps(std::time::Instant::now())
+            )),
+            Line::from(format!(" Frames  {} ", stats.total())),
+            Line::from(format!(
+                " Render  {:.2} ms (avg {:.2}) ",
+                millis(stats.last_render_time()),
+                millis(stats.average_render_time())
+            )),
+        ];
+        for (index, tab) in self.tabs.iter().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let kib = tab.output.len() as f64 / 1024.0;
+            lines.push(Line::from(format!(" Tab {}   {kib:.1} KiB ", index + 1)));
+        }
+
+        // Borders take two cells in each direction
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(0);
+        let width = u16::try_from(content_width + 2)
+            .unwrap_or(u16::MAX)
+            .min(area.width);
+        let height = u16::try_from(lines.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(area.height);
+        let overlay = Rect::new(area.right() - width, area.y, width, height);
+
+        let popup = Paragraph::new(lines)
+            .style(Style::default().bg(Color::Rgb(
+                COLOR_PURE_BLACK.0,
+                COLOR_PURE_BLACK.1,
+                COLOR_PURE_BLACK.2,
+            )))
+            .block(Block::default().borders(Borders::ALL).title(" Stats "));
+
+        f.render_widget(Clear, overlay);
+        f.render_widget(popup, overlay);
+    }
+
+    /// Render the preview of a multi-line paste awaiting confirmation
+    fn render_paste_overlay(f: &mut ratatui::Frame, paste: &PendingPaste) {
+        let lines: Vec<Line> = paste
+            .preview(paste::PREVIEW_LINES)
+            .into_iter()
+            .map(|line| Line::from(format!(" {line} ")))
+            .collect();
+
+        // Borders take two cells in each direction; leave room for the title
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(0).max(36);
+        let width = u16::try_from(content_width + 2).unwrap_or(u16::MAX);
+        let height = u16::try_from(lines.len() + 2).unwrap_or(u16::MAX);
+        let area = centered_popup(f.size(), width, height);
+
+        let popup = Paragraph::new(lines)
+            .style(Style::default().bg(Color::Rgb(
+                COLOR_PURE_BLACK.0,
+                COLOR_PURE_BLACK.1,
+                COLOR_PURE_BLACK.2,
+            )))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Paste these lines? (y/n) "),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+
+    /// Render the keybinding cheat sheet as a centered popup
+    fn render_keybindings_overlay(&self, f: &mut ratatui::Frame) {
+        let bindings = self.keybindings.borrow().bindings();
+        let combo_width = bindings
+            .iter()
+            .map(|(combo, _)| combo.width())
+            .max()
+            .unwrap_or(0);
+
+        let key_style = Style::default()
+            .fg(Color::Rgb(
+                COLOR_COOL_RED.0,
+                COLOR_COOL_RED.1,
+                COLOR_COOL_RED.2,
+            ))
+            .add_modifier(Modifier::BOLD);
+        let lines: Vec<Line> = bindings
+            .iter()
+            .map(|(combo, action)| {
+                Line::from(vec![
+                    Span::styled(format!(" {combo:<combo_width$} "), key_style),
+                    Span::raw(action.description()),
+                ])
+            })
+            .collect();
+
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(0);
+        // Borders take two cells in each direction
+        let width = u16::try_from(content_width + 3).unwrap_or(u16::MAX);
+        let height = u16::try_from(lines.len() + 2).unwrap_or(u16::MAX);
+        let area = centered_popup(f.size(), width, height);
+
+        let popup = Paragraph::new(lines)
+            .style(Style::default().bg(Color::Rgb(
+                COLOR_PURE_BLACK.0,
+                COLOR_PURE_BLACK.1,
+                COLOR_PURE_BLACK.2,
+            )))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Keybindings (Esc to close) "),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
     }
 
     /// Bug #3: Render terminal output with zero-copy caching
     #[allow(clippy::too_many_lines)]
     fn render_terminal_output(&mut self, f: &mut ratatui::Frame, area: Rect) {
         let buffer_len = self
-            .output_buffers
+            .tabs
             .get(self.active_session)
-            .map_or(0, std::vec::Vec::len);
+            .map_or(0, |tab| tab.output.len());
         let cached_len = self
-            .cached_buffer_lens
+            .tabs
             .get(self.active_session)
-            .copied()
-            .unwrap_or(0);
+            .map_or(0, |tab| tab.styled_len);
 
-        // Only reparse if buffer has changed (Bug #3: avoid massive allocation)
+        // Only rebuild if the buffer or view changed (Bug #3: avoid massive allocation)
         if buffer_len != cached_len {
-            if let Some(buffer) = self.output_buffers.get(self.active_session) {
-                // Use String::from_utf8_lossy which returns Cow - doesn't allocate if valid UTF-8
-                let raw_output = String::from_utf8_lossy(buffer);
-                // Use custom color palette for theme-aware ANSI parsing
-                let all_lines = AnsiParser::parse_with_palette(&raw_output, &self.color_palette);
+            let scroll_offset = self.scroll_offset();
+            if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                let buffer = &tab.output;
+                let slot = &mut tab.parser;
+                // Parse only the output appended since the last render; a
+                // shorter buffer was cleared, so start over
+                if slot.as_ref().is_some_and(|parser| parser.parsed_len() > buffer.len()) {
+                    *slot = None;
+                }
+                let parser = slot.get_or_insert_with(|| IncrementalParser::new(&self.color_palette));
+                parser.feed(&buffer[parser.parsed_len()..]);
+
                 // Leave 1 line at bottom for breathing room (ensure prompt is visible)
                 let height = (area.height as usize).saturating_sub(1).max(1);
                 // Apply scroll offset: skip_count positions the viewport in the buffer
-                let tail_skip = all_lines.len().saturating_sub(height);
-                let skip_count = tail_skip.saturating_sub(self.scroll_offset);
-                let visible_lines: Vec<Line<'static>> =
-                    all_lines.into_iter().skip(skip_count).take(height).collect();
-
-                if let Some(cache) = self.cached_styled_lines.get_mut(self.active_session) {
-                    *cache = visible_lines;
-                }
-                if let Some(len) = self.cached_buffer_lens.get_mut(self.active_session) {
-                    *len = buffer_len;
-                }
+                let tail_skip = parser.line_count().saturating_sub(height);
+                let skip_count = tail_skip.saturating_sub(scroll_offset);
+                // Only the visible lines are copied out of the parser
+                let (visible_lines, hyperlinks) = parser.lines(skip_count, height);
+
+                tab.styled_lines = visible_lines;
+                tab.hyperlinks = hyperlinks;
+                tab.styled_len = buffer_len;
             }
         }
 
         // Use cached styled lines - avoid clone by taking reference
-        let styled_lines = if let Some(lines) = self.cached_styled_lines.get(self.active_session) {
-            lines.as_slice()
+        let styled_lines = if let Some(tab) = self.tabs.get(self.active_session) {
+            tab.styled_lines.as_slice()
         } else {
             &[]
         };
@@ -2280,9 +3575,9 @@ impl Terminal {
         // This is especially important on Windows where PTY echo may be delayed or not working
         // Pre-allocate with +1 capacity only if we'll actually need it
         let needs_local_echo = self
-            .command_buffers
+            .tabs
             .get(self.active_session)
-            .is_some_and(|buf| !buf.is_empty());
+            .is_some_and(|tab| !tab.command.is_empty());
 
         let capacity = if needs_local_echo {
             styled_lines.len() + 1
@@ -2293,6 +3588,9 @@ impl Terminal {
         let mut display_lines = Vec::with_capacity(capacity);
         display_lines.extend_from_slice(styled_lines);
 
+        // Highlight all search matches on the visible lines
+        self.highlight_search_matches(&mut display_lines);
+
         // Apply text selection highlighting if active
         if !self.config.theme.selection.is_empty()
             && (self.selection_start.is_some() || self.selection_end.is_some())
@@ -2302,56 +3600,30 @@ impl Terminal {
                 let selection_bg = Color::Rgb(sel_color.r, sel_color.g, sel_color.b);
 
                 // Apply selection background to selected positions
-                // Use character-based iteration for UTF-8 safety (not byte indices)
-                for (row_idx, line) in display_lines.iter_mut().enumerate() {
-                    let mut new_spans = Vec::new();
-                    let mut col = 0u16;
-
-                    for span in &line.spans {
-                        let chars: Vec<char> = span.content.chars().collect();
-                        let char_count = chars.len() as u16;
-                        let mut span_char_start = 0u16;
-
-                        for char_idx in 0..char_count {
-                            let char_col = col + char_idx;
-                            if self.is_position_selected(char_col, row_idx as u16) {
-                                // This character is selected
-                                if span_char_start < char_idx {
-                                    // Add non-selected part (collect chars in range)
-                                    let text: String = chars
-                                        [span_char_start as usize..char_idx as usize]
-                                        .iter()
-                                        .collect();
-                                    new_spans.push(Span::styled(text, span.style));
+                let selected = self.selected_spans(&display_lines);
+                for (line, parts) in display_lines.iter_mut().zip(selected) {
+                    if !parts.is_empty() {
+                        let spans: Vec<Span<'static>> = parts
+                            .into_iter()
+                            .map(|(span, selected)| {
+                                if selected {
+                                    let style = span.style.bg(selection_bg);
+                                    span.style(style)
+                                } else {
+                                    span
                                 }
-                                // Add selected character
-                                let ch_text = chars[char_idx as usize].to_string();
-                                new_spans.push(Span::styled(ch_text, span.style.bg(selection_bg)));
-                                span_char_start = char_idx + 1;
-                            }
-                        }
-
-                        // Add remaining non-selected part
-                        if span_char_start < char_count {
-                            let text: String =
-                                chars[span_char_start as usize..].iter().collect();
-                            new_spans.push(Span::styled(text, span.style));
-                        }
-
-                        col += char_count;
-                    }
-
-                    if !new_spans.is_empty() {
-                        *line = Line::from(new_spans);
+                            })
+                            .collect();
+                        *line = Line::from(spans);
                     }
                 }
             }
         }
 
-        if let Some(cmd_buf) = self.command_buffers.get(self.active_session) {
-            if !cmd_buf.is_empty() {
+        if let Some(tab) = self.tabs.get(self.active_session) {
+            if !tab.command.is_empty() {
                 // Convert command buffer to string for display (local echo)
-                let pending_input = String::from_utf8_lossy(cmd_buf);
+                let pending_input = String::from_utf8_lossy(&tab.command);
 
                 // Check if the last line already ends with this input (shell echo is working)
                 // to avoid duplicate display
@@ -2395,6 +3667,48 @@ impl Terminal {
             }
         }
 
+        // Ghost text: the rest of a history match, dimmed after the input. It is
+        // cut to the room left on the row (leaving the last cell for the cursor)
+        // and skipped once the line wraps, so the cursor can sit before it.
+        let mut ghost_width = 0u16;
+        if let (Some(ghost), Some(last_line)) = (self.ghost_suggestion(), display_lines.last_mut())
+        {
+            let line_width: usize = last_line.spans.iter().map(|span| span.content.width()).sum();
+            let room = (area.width as usize).saturating_sub(line_width + 1);
+            let mut shown = String::new();
+            for ch in ghost.chars() {
+                if shown.width() + ch.to_string().width() > room {
+                    break;
+                }
+                shown.push(ch);
+            }
+            if !shown.is_empty() {
+                #[allow(clippy::cast_possible_truncation)]
+                {
+                    ghost_width = shown.width() as u16;
+                }
+                last_line.spans.push(Span::styled(
+                    shown,
+                    Style::default()
+                        .fg(Color::Rgb(
+                            COLOR_STATUS_HINT.0,
+                            COLOR_STATUS_HINT.1,
+                            COLOR_STATUS_HINT.2,
+                        ))
+                        .add_modifier(Modifier::DIM),
+                ));
+            }
+        }
+
+        // Wrap or truncate long lines into visual rows. Wrapping can produce more
+        // rows than fit, so keep the bottom rows where the cursor lives.
+        let mut display_lines =
+            wrap::layout_lines(&display_lines, area.width as usize, self.line_wrap);
+        let overflow = display_lines.len().saturating_sub(area.height as usize);
+        display_lines.drain(..overflow);
+        self.output_area = area;
+        self.output_overflow = overflow;
+
         // If no content yet, show a placeholder prompt so users know where to type
         // This prevents confusion when the shell is slow to start
         let has_content = !display_lines.is_empty();
@@ -2414,12 +3728,13 @@ impl Terminal {
                 #[allow(clippy::cast_possible_truncation)]
                 let line_count = display_lines.len() as u16;
 
-                // Position cursor at the end of the last line
+                // Position cursor at the end of the last line, before any ghost text
                 // Ensure we stay within the visible area bounds
-                let cursor_x = (area.x + line_width).min(area.x + area.width.saturating_sub(1));
+                let cursor_x = (area.x + line_width.saturating_sub(ghost_width))
+                    .min(area.x + area.width.saturating_sub(1));
 
-                // Y position should be relative to the visible lines
-                // Since we already filtered visible_lines to fit in the area, we use line_count - 1
+                // Y position should be relative to the visible rows
+                // Rows were wrapped and trimmed to fit in the area above, so use line_count - 1
                 let cursor_y = (area.y + line_count.saturating_sub(1))
                     .min(area.y + area.height.saturating_sub(1));
 
@@ -2499,72 +3814,174 @@ impl Terminal {
 
     /// Render split panes for multiple sessions
     ///
-    /// Splits the content area and renders multiple shell sessions side-by-side or top-bottom
+    /// Walks the pane tree to split the content area and renders each pane's shell session
     fn render_split_panes(&mut self, f: &mut ratatui::Frame, area: Rect) {
-        use ratatui::layout::{Constraint, Direction, Layout};
-
-        // Calculate split based on orientation
-        let panes = match self.split_orientation {
-            SplitOrientation::Horizontal => {
-                // Top/bottom split
-                let split_height = (area.height as f32 * self.split_ratio) as u16;
-                Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(split_height), Constraint::Min(0)])
-                    .split(area)
-            }
-            SplitOrientation::Vertical => {
-                // Left/right split
-                let split_width = (area.width as f32 * self.split_ratio) as u16;
-                Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Length(split_width), Constraint::Min(0)])
-                    .split(area)
-            }
-            SplitOrientation::None => {
-                // Fallback to single pane
-                return self.render_terminal_output(f, area);
-            }
+        let Some(panes) = self.pane_sessions() else {
+            // Fallback to single pane
+            return self.render_terminal_output(f, area);
         };
+        let rects = self.panes.layout(area);
 
-        // Render first session in first pane (temporarily save active session)
+        // Render each pane's session (temporarily save active session). The
+        // focused pane goes last so the cursor is left in it.
         let original_active = self.active_session;
-
-        if !self.sessions.is_empty() {
-            self.active_session = 0;
-            self.render_terminal_output(f, panes[0]);
-        }
-
-        // Render second session in second pane
-        if self.sessions.len() >= 2 && panes.len() >= 2 {
-            self.active_session = 1;
-            self.render_terminal_output(f, panes[1]);
+        let focused = self.focused_pane;
+        let order = (0..panes.len()).filter(|&pane| pane != focused);
+        for pane in order.chain([focused]) {
+            let block = pane_block(pane == focused);
+            let inner = block.inner(rects[pane]);
+            f.render_widget(block, rects[pane]);
+            self.active_session = panes[pane];
+            self.render_terminal_output(f, inner);
         }
 
         // Restore active session
         self.active_session = original_active;
     }
 
-    /// Toggle split pane orientation
+    /// Whether the content area is split between several sessions
+    fn split_visible(&self) -> bool {
+        self.enable_split_pane && self.sessions.len() >= 2 && self.panes.is_split()
+    }
+
+    /// Grow the focused pane by `delta` of its split, or shrink it if negative
     ///
-    /// Cycles through: None -> Horizontal -> Vertical -> None
-    pub fn toggle_split_orientation(&mut self) {
-        if !self.enable_split_pane {
+    /// Returns `false` when the content area isn't split.
+    fn resize_focused_pane(&mut self, delta: f32) -> bool {
+        if !self.split_visible() || !self.panes.resize_leaf(self.focused_pane, delta) {
+            return false;
+        }
+        self.invalidate_active_cache();
+        self.dirty = true;
+        true
+    }
+
+    /// Session shown in each pane, or `None` when the content area isn't split
+    fn pane_sessions(&self) -> Option<Vec<usize>> {
+        self.split_visible().then(|| {
+            pane_sessions(
+                &self.panes.leaves(),
+                self.focused_pane,
+                self.active_session,
+                self.sessions.len(),
+            )
+        })
+    }
+
+    /// Split the focused pane, showing the first tab after the active one that isn't on screen yet
+    ///
+    /// Returns `false`, leaving the layout alone, when every tab is already shown.
+    fn open_split(&mut self, direction: SplitDirection) -> bool {
+        let shown = self
+            .pane_sessions()
+            .unwrap_or_else(|| vec![self.active_session]);
+        let Some(session) = next_hidden_session(&shown, self.active_session, self.sessions.len())
+        else {
+            return false;
+        };
+        if shown.len() == 1 {
+            self.panes = PaneTree::Leaf(self.active_session);
+            self.focused_pane = 0;
+        } else {
+            self.panes.set_leaves(&shown);
+        }
+        self.panes.split_leaf(self.focused_pane, direction, session);
+        self.dirty = true;
+        true
+    }
+
+    /// Split the focused pane and resize the PTYs to fit, reporting the outcome
+    async fn split_focused_pane(&mut self, direction: SplitDirection) {
+        if !self.open_split(direction) {
+            self.show_notification("Every tab is already in a pane".to_string());
             return;
         }
+        self.resize_sessions().await;
+        let name = match direction {
+            SplitDirection::Horizontal => "Horizontal",
+            SplitDirection::Vertical => "Vertical",
+        };
+        self.show_notification(format!("Split: {name}"));
+    }
 
-        self.split_orientation = match self.split_orientation {
-            SplitOrientation::None => SplitOrientation::Horizontal,
-            SplitOrientation::Horizontal => SplitOrientation::Vertical,
-            SplitOrientation::Vertical => SplitOrientation::None,
+    /// Move keyboard focus to the next or previous pane, making its session active
+    ///
+    /// Returns `false` when the content area isn't split.
+    fn focus_pane(&mut self, forward: bool) -> bool {
+        let Some(panes) = self.pane_sessions() else {
+            return false;
         };
+        let count = panes.len();
+        let focused = if forward {
+            (self.focused_pane + 1) % count
+        } else {
+            (self.focused_pane + count - 1) % count
+        };
+
+        // Bug #8: Enforce scrollback limit on current tab before switching
+        self.enforce_scrollback_limit(self.active_session);
+        self.panes.set_leaves(&panes);
+        self.focused_pane = focused;
+        self.active_session = panes[focused];
+        self.dirty = true;
+        debug!("Focused pane {}, tab {}", focused, self.active_session);
+        true
+    }
+
+    /// PTY size as `(rows, cols)` for a session: its pane's inside when split, else the whole terminal
+    fn session_pty_size(&self, index: usize) -> (u16, u16) {
+        let (rows, cols) = (self.terminal_rows, self.terminal_cols);
+        // The content area is everything above the status bar
+        let content = Rect::new(0, 0, cols, rows.saturating_sub(1));
+        let rect = self.pane_sessions().and_then(|panes| {
+            let pane = panes.iter().position(|&session| session == index)?;
+            self.panes.layout(content).get(pane).copied()
+        });
+        rect.map_or((rows, cols), pane_pty_size)
+    }
+
+    /// Resize every session's PTY to fit where it is shown
+    async fn resize_sessions(&self) {
+        for (index, session) in self.sessions.iter().enumerate() {
+            let (rows, cols) = self.session_pty_size(index);
+            if let Err(e) = session.resize(rows, cols).await {
+                warn!("Failed to resize PTY: {}", e);
+            }
+        }
+    }
+
+    /// Toggle the outermost split's orientation
+    ///
+    /// Cycles through: None -> Horizontal -> Vertical -> None, where None
+    /// closes every pane but the focused one.
+    pub fn toggle_split_orientation(&mut self) {
+        if !self.enable_split_pane {
+            return;
+        }
+
+        match &mut self.panes {
+            PaneTree::Leaf(session) => {
+                let next = *session + 1;
+                self.panes.split_leaf(0, SplitDirection::Horizontal, next);
+            }
+            PaneTree::Split {
+                direction: direction @ SplitDirection::Horizontal,
+                ..
+            } => *direction = SplitDirection::Vertical,
+            PaneTree::Split { .. } => {
+                self.panes = PaneTree::Leaf(self.active_session);
+                self.focused_pane = 0;
+            }
+        }
 
-        info!("Split pane orientation: {:?}", self.split_orientation);
+        info!("Split panes: {:?}", self.panes);
     }
 
-    /// Set split ratio (0.0-1.0)
+    /// Set the outermost split's ratio (0.0-1.0)
     pub fn set_split_ratio(&mut self, ratio: f32) {
-        self.split_ratio = ratio.clamp(0.1, 0.9);
+        if let PaneTree::Split { ratio: current, .. } = &mut self.panes {
+            *current = ratio.clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+        }
     }
 
     /// Render resource monitor (Bug #23: doesn't need &mut self)
@@ -2574,6 +3991,14 @@ impl Terminal {
         };
 
         let stats = monitor.get_stats();
+        // Usage of the active tab's shell and whatever it is running
+        let shell_info = match self.sessions.get(self.active_session) {
+            Some(session) => format!(
+                " | Shell: {}",
+                ProcessStats::label(session.pid().and_then(|pid| monitor.process_stats(pid)))
+            ),
+            None => String::new(),
+        };
 
         // Include disk usage in display
         let disk_info = if !stats.disk_usage.is_empty() {
@@ -2589,7 +4014,7 @@ impl Terminal {
         };
 
         let text = format!(
-            " CPU: {:.1}% ({} cores) | Memory: {} / {} ({:.1}%) | Processes: {}{}",
+            " CPU: {:.1}% ({} cores) | Memory: {} / {} ({:.1}%) | Processes: {}{}{}",
             stats.cpu_usage,
             stats.cpu_count,
             ResourceMonitor::format_bytes(stats.memory_used),
@@ -2597,6 +4022,13 @@ impl Terminal {
             stats.memory_percent,
             stats.process_count,
             disk_info,
+            shell_info,
+        );
+        // Recent history on the second line
+        let text = format!(
+            "{text}\n CPU {} | Mem {}",
+            monitor.sparkline(Metric::Cpu),
+            monitor.sparkline(Metric::Memory),
         );
 
         let resource_widget = Paragraph::new(text)
@@ -2620,23 +4052,68 @@ impl Terminal {
 
     /// Render autocomplete suggestions
     fn render_autocomplete(&mut self, f: &mut ratatui::Frame, area: Rect) {
+        let cwd = self
+            .keybindings
+            .borrow()
+            .shell_integration()
+            .current_dir
+            .clone();
         let Some(ref mut ac) = self.autocomplete else {
             return;
         };
 
         // Get current command from buffer
-        let current_cmd = if let Some(cmd_buf) = self.command_buffers.get(self.active_session) {
-            String::from_utf8_lossy(cmd_buf).to_string()
+        let current_cmd = if let Some(tab) = self.tabs.get(self.active_session) {
+            String::from_utf8_lossy(&tab.command).to_string()
         } else {
             String::new()
         };
 
-        // Get suggestions
-        let suggestions = ac.get_suggestions(&current_cmd);
+        // Paths are completed against the shell's directory once it is known
+        let suggestions = match cwd {
+            Some(cwd) if Autocomplete::completes_path(&current_cmd) => {
+                ac.suggest(&current_cmd, std::path::Path::new(&cwd))
+            }
+            _ => ac.get_suggestions(&current_cmd),
+        };
+        // Nothing starts with the input: fall back to fuzzy history matches
+        let suggestions = if suggestions.is_empty() {
+            ac.fuzzy_suggest(&current_cmd)
+                .into_iter()
+                .map(|(cmd, _)| cmd)
+                .collect()
+        } else {
+            suggestions
+        };
+
         let display_text = if suggestions.is_empty() {
-            "No suggestions".to_string()
+            Line::from("No suggestions")
         } else {
-            format!("Suggestions: {}", suggestions.join(", "))
+            // Characters the input matched are highlighted
+            let matched = Style::default()
+                .fg(Color::Rgb(
+                    COLOR_SEARCH_MATCH.0,
+                    COLOR_SEARCH_MATCH.1,
+                    COLOR_SEARCH_MATCH.2,
+                ))
+                .add_modifier(Modifier::BOLD);
+            let mut spans = vec![Span::raw("Suggestions: ")];
+            for (i, suggestion) in suggestions.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(", "));
+                }
+                let positions = fuzzy_match(&current_cmd, suggestion)
+                    .map(|(_, positions)| positions)
+                    .unwrap_or_default();
+                for (j, ch) in suggestion.chars().enumerate() {
+                    if positions.contains(&j) {
+                        spans.push(Span::styled(ch.to_string(), matched));
+                    } else {
+                        spans.push(Span::raw(ch.to_string()));
+                    }
+                }
+            }
+            Line::from(spans)
         };
 
         let autocomplete_widget = Paragraph::new(display_text)
@@ -2662,37 +4139,277 @@ impl Terminal {
         f.render_widget(autocomplete_widget, area);
     }
 
+    /// Pick up theme files edited since the last frame
+    ///
+    /// A file that no longer parses keeps its previous theme; the user is told
+    /// which one so they can fix it.
+    fn reload_changed_themes(&mut self) {
+        let Some(result) = self
+            .theme_manager
+            .as_mut()
+            .and_then(ThemeManager::poll_changes)
+        else {
+            return;
+        };
+
+        match result {
+            Ok(report) => {
+                for (path, e) in &report.errors {
+                    warn!("Failed to reload theme {}: {}", path.display(), e);
+                }
+                if report.current_changed && self.reapply_theme_palette() {
+                    if let Some(ref themes) = self.theme_manager {
+                        let message = format!("Theme: {} reloaded", themes.current().name);
+                        self.show_notification(message);
+                    }
+                }
+                if let Some((path, _)) = report.errors.first() {
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    self.show_notification(format!(
+                        "Theme {file} is invalid; kept the previous version"
+                    ));
+                }
+            }
+            Err(e) => warn!("Failed to reload themes: {}", e),
+        }
+    }
+
+    /// Reload the config file whenever it is saved, with the profile it was loaded with
+    ///
+    /// Failing to watch the file only logs a warning, since the terminal works
+    /// without it.
+    pub fn watch_config(&mut self, path: &std::path::Path, profile: Option<&str>) {
+        match ConfigWatcher::new(path, profile.map(str::to_string)) {
+            Ok(watcher) => self.config_watcher = Some(watcher),
+            Err(e) => warn!("Failed to watch config {}: {}", path.display(), e),
+        }
+    }
+
+    /// Apply the config file if it changed since the last frame
+    fn reload_changed_config(&mut self) {
+        let Some(result) = self
+            .config_watcher
+            .as_mut()
+            .and_then(ConfigWatcher::poll_changes)
+        else {
+            return;
+        };
+
+        match result {
+            Ok(mut config) => {
+                // `--shell` overrides the file's shell for the whole run
+                config
+                    .shell
+                    .default_shell
+                    .clone_from(&self.config.shell.default_shell);
+                let diff = ConfigDiff::between(&self.config, &config);
+                if !diff.is_empty() {
+                    self.apply_config_changes(&config, &diff);
+                }
+            }
+            Err(errors) => self.reject_config(&errors),
+        }
+    }
+
+    /// Switch to the live settings of a reloaded config
+    ///
+    /// Settings in `diff.restart` are left as they are, and the notification
+    /// lists them.
+    fn apply_config_changes(&mut self, config: &Config, diff: &ConfigDiff) {
+        self.config.apply_live(config);
+        let changed = |name: &str| diff.live.contains(&name);
+
+        if changed("theme") {
+            match TrueColorPalette::from_ansi_colors(&self.config.theme.colors) {
+                Ok(palette) => {
+                    self.color_palette = palette;
+                    self.tabs.iter_mut().for_each(|tab| {
+                        tab.styled_len = 0;
+                        tab.parser = None;
+                    });
+                }
+                Err(e) => warn!("Failed to parse theme colors, keeping the old ones: {}", e),
+            }
+        }
+        if changed("keybindings") || changed("hooks.custom_keybindings") {
+            self.keybindings
+                .borrow_mut()
+                .replace_bindings(keybinding_manager(&self.config));
+        }
+
+        let features = &self.config.features;
+        if features.resource_monitor != self.resource_monitor.is_some() {
+            self.resource_monitor = features.resource_monitor.then(ResourceMonitor::new);
+            self.show_resources &= features.resource_monitor;
+        }
+        if features.autocomplete != self.autocomplete.is_some() {
+            self.autocomplete = features
+                .autocomplete
+                .then(|| Autocomplete::with_max_history(self.max_history));
+            self.show_autocomplete &= features.autocomplete;
+        }
+        if features.progress_bar != self.progress_bar.is_some() {
+            let style = SpinnerStyle::from_name(&self.config.progress_bar.spinner_style);
+            self.progress_bar = features
+                .progress_bar
+                .then(|| ProgressBar::with_style(style));
+        }
+
+        self.cursor_style
+            .clone_from(&self.config.terminal.cursor_style);
+        let line_wrap = wrap::LineWrap::from_name(&self.config.terminal.line_wrap);
+        if line_wrap != self.line_wrap {
+            self.line_wrap = line_wrap;
+            self.tabs.iter_mut().for_each(|tab| tab.styled_len = 0);
+        }
+        self.bell_style = bell::BellStyle::from_name(&self.config.terminal.bell_style);
+        self.tab_title_source =
+            title::TabTitleSource::from_name(&self.config.terminal.tab_title_source);
+
+        let message = if diff.restart.is_empty() {
+            "Config reloaded".to_string()
+        } else {
+            format!(
+                "Config reloaded; restart to apply {}",
+                diff.restart.join(", ")
+            )
+        };
+        self.show_notification(message);
+        self.dirty = true;
+    }
+
+    /// Keep the running config after a reload that failed validation
+    fn reject_config(&mut self, errors: &[ConfigError]) {
+        for error in errors {
+            warn!("Config reload: {}", error);
+        }
+        let message = match errors.len() {
+            1 => format!("Config not reloaded: {}", errors[0]),
+            n => format!("Config not reloaded: {n} problems, see the log"),
+        };
+        self.show_notification(message);
+    }
+
+    /// Re-color output with the active theme's ANSI colors
+    ///
+    /// Returns false, after telling the user why, if the theme has an invalid color.
+    fn reapply_theme_palette(&mut self) -> bool {
+        let Some(ref themes) = self.theme_manager else {
+            return false;
+        };
+        let theme = themes.current();
+        match TrueColorPalette::from_ansi_colors(&theme.colors.to_ansi_colors()) {
+            Ok(palette) => {
+                if let Some(ratio) = theme.low_contrast() {
+                    warn!(
+                        "Theme {} text has a contrast of {:.1}:1, below the 4.5:1 WCAG AA asks for",
+                        theme.name, ratio
+                    );
+                }
+                self.color_palette = palette;
+                // Every tab's cached lines were styled with the old palette
+                self.tabs.iter_mut().for_each(|tab| {
+                    tab.styled_len = 0;
+                    tab.parser = None;
+                });
+                self.dirty = true;
+                true
+            }
+            Err(e) => {
+                let message = format!("Theme {} has an invalid color: {e}", theme.name);
+                warn!("{}", message);
+                self.show_notification(message);
+                false
+            }
+        }
+    }
+
     /// Show notification message
     ///
-    /// BUG FIX #17: Actually set notification_frames when showing notification
+    /// It stacks on top of any still showing and stays for
+    /// `ui.notification_duration_secs`, counted in frames at the target FPS.
     pub fn show_notification(&mut self, message: String) {
-        self.notification_message = Some(message);
-        // BUG FIX #17: Set frames based on duration and target FPS
-        self.notification_frames = NOTIFICATION_DURATION_SECS * TARGET_FPS;
+        let frames = self
+            .config
+            .ui
+            .notification_duration_secs
+            .saturating_mul(u64::from(self.config.terminal.max_fps));
+        self.notifications.push(message, frames);
         self.dirty = true;
     }
 
-    /// Copy visible terminal output to clipboard
-    fn copy_to_clipboard(&self) -> Result<()> {
-        use arboard::Clipboard;
+    /// Run Lua hooks with a read-only view of the active session
+    ///
+    /// Messages from `furnace.notify` are shown on the next frame, so a hook
+    /// never changes terminal state while it is being drawn.
+    fn with_hooks(&self, run: impl FnOnce(&HooksExecutor)) {
+        let Some(ref executor) = self.hooks_executor else {
+            return;
+        };
+        let buffer = || self.active_buffer_text();
+        // Copied so hooks can call `furnace.bind` while the view is lent out
+        let cwd = self
+            .keybindings
+            .borrow()
+            .shell_integration()
+            .current_dir
+            .clone();
+        let view = HookView {
+            buffer: &buffer,
+            cwd: cwd.as_deref(),
+        };
+        if let Err(e) = executor.with_view(&view, || run(executor)) {
+            warn!("Lua hook skipped: {}", e);
+        }
+    }
 
-        let mut clipboard = Clipboard::new().context("Failed to access clipboard")?;
+    /// Run the `on_resize` hook with the current size in cells
+    fn run_resize_hook(&self) {
+        if let Some(ref script) = self.config.hooks.on_resize {
+            let (cols, rows) = (self.terminal_cols, self.terminal_rows);
+            self.with_hooks(|executor| {
+                if let Err(e) = executor.on_resize(script, cols, rows) {
+                    warn!("on_resize hook failed: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Text of the active session's output with escape sequences removed
+    ///
+    /// Output spilled to disk is included.
+    fn active_buffer_text(&self) -> String {
+        if self.active_session >= self.tabs.len() {
+            return String::new();
+        }
+        let output = self.full_output(self.active_session);
+        AnsiParser::parse(&String::from_utf8_lossy(&output))
+            .iter()
+            .map(search::line_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
+    /// Copy visible terminal output to clipboard
+    fn copy_to_clipboard(&self) -> Result<()> {
         // Get visible terminal output
-        let output = if let Some(buffer) = self.output_buffers.get(self.active_session) {
-            String::from_utf8_lossy(buffer).to_string()
-        } else {
-            String::new()
-        };
+        let output = String::from_utf8_lossy(&self.full_output(self.active_session)).to_string();
 
-        clipboard
-            .set_text(output)
-            .context("Failed to set clipboard text")?;
-        Ok(())
+        set_clipboard_text(output)
+    }
+
+    /// Text a program asked to put on the clipboard with OSC 52, if allowed
+    fn osc52_clipboard_text(&self, output: &str) -> Option<String> {
+        if !self.config.terminal.allow_osc52 {
+            return None;
+        }
+        osc52::last_clipboard_text(output)
     }
 
     /// Paste from clipboard to shell
-    async fn paste_from_clipboard(&self) -> Result<()> {
+    ///
+    /// Multi-line text is held for confirmation instead when configured.
+    async fn paste_from_clipboard(&mut self) -> Result<()> {
         use arboard::Clipboard;
 
         let mut clipboard = Clipboard::new().context("Failed to access clipboard")?;
@@ -2700,17 +4417,66 @@ impl Terminal {
             .get_text()
             .context("Failed to get clipboard text")?;
 
-        // Send pasted text to active session
+        if let Some(text) = self.request_paste(text) {
+            self.write_paste(&text).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send pasted text to the active session
+    async fn write_paste(&self, text: &str) -> Result<()> {
+        if self.active_session_exited() {
+            return Ok(());
+        }
         if let Some(session) = self.sessions.get(self.active_session) {
             session.write_input(text.as_bytes()).await?;
         }
-
         Ok(())
     }
 
+    /// Decide what happens to pasted text
+    ///
+    /// Returns the text to send if it can be sent right away. Text containing
+    /// a newline is held for confirmation when `confirm_multiline_paste` is set.
+    fn request_paste(&mut self, text: String) -> Option<String> {
+        if paste::needs_confirmation(&text, self.config.terminal.confirm_multiline_paste) {
+            self.pending_paste = Some(PendingPaste::new(text));
+            self.dirty = true;
+            None
+        } else {
+            Some(self.bracket_paste(text))
+        }
+    }
+
+    /// Accept or decline the pending paste, returning the text to send if accepted
+    fn resolve_paste(&mut self, confirmed: bool) -> Option<String> {
+        let paste = self.pending_paste.take()?;
+        self.dirty = true;
+        if confirmed {
+            Some(self.bracket_paste(paste.into_text()))
+        } else {
+            self.show_notification("Paste discarded".to_string());
+            None
+        }
+    }
+
+    /// Wrap pasted text in bracket markers if the active session's program asked for them
+    fn bracket_paste(&self, text: String) -> String {
+        let bracketed = self.config.terminal.bracketed_paste
+            && self
+                .tabs
+                .get(self.active_session)
+                .is_some_and(|tab| tab.child_modes.bracketed_paste());
+        paste::bracket(text, bracketed)
+    }
+
     /// Render custom Lua widgets
     fn render_custom_widgets(&self, f: &mut ratatui::Frame) {
-        if let Some(ref executor) = self.hooks_executor {
+        if self.config.hooks.custom_widgets.is_empty() {
+            return;
+        }
+        self.with_hooks(|executor| {
             for widget_code in &self.config.hooks.custom_widgets {
                 match executor.execute_widget(widget_code) {
                     Ok(widget) => {
@@ -2756,55 +4522,158 @@ impl Terminal {
                     }
                 }
             }
-        }
+        });
     }
 
     /// Toggle search mode
     fn toggle_search_mode(&mut self) {
+        if !self.search_mode && self.in_alternate_screen(self.active_session) {
+            self.show_notification("No scrollback to search on the alternate screen".to_string());
+            return;
+        }
         self.search_mode = !self.search_mode;
         if self.search_mode {
             self.search_query.clear();
             self.search_results.clear();
             self.current_search_result = 0;
+            self.search_pattern = None;
             self.show_notification("Search mode: Enter query, Esc to exit".to_string());
         } else {
+            self.search_pattern = None;
+            // Return to following the latest output
+            self.scroll_to_bottom();
             self.show_notification("Search mode exited".to_string());
         }
         self.dirty = true;
     }
 
+    /// Toggle between literal and regex matching for the search query
+    fn toggle_search_regex(&mut self) {
+        self.search_options.regex = !self.search_options.regex;
+        self.refresh_search("Regex", self.search_options.regex);
+    }
+
+    /// Toggle case-sensitive matching for the search query
+    fn toggle_search_case_sensitive(&mut self) {
+        self.search_options.case_sensitive = !self.search_options.case_sensitive;
+        self.refresh_search("Match case", self.search_options.case_sensitive);
+    }
+
+    /// Toggle whole-word matching for the search query
+    fn toggle_search_whole_word(&mut self) {
+        self.search_options.whole_word = !self.search_options.whole_word;
+        self.refresh_search("Whole word", self.search_options.whole_word);
+    }
+
+    /// Re-run the search after an option change
+    ///
+    /// Stays on the same match line when it still matches, otherwise moves to
+    /// the next matching line (wrapping to the first).
+    fn refresh_search(&mut self, option: &str, enabled: bool) {
+        let previous_line = self.search_results.get(self.current_search_result).copied();
+        self.execute_search();
+        if let Some(line) = previous_line {
+            self.current_search_result = self
+                .search_results
+                .iter()
+                .position(|&l| l >= line)
+                .unwrap_or(0);
+            self.scroll_to_current_match();
+        }
+        self.show_notification(format!(
+            "{}: {} ({} matching line{})",
+            option,
+            if enabled { "ON" } else { "OFF" },
+            self.search_results.len(),
+            if self.search_results.len() == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// Build the status bar prompt for search mode, including active options
+    fn search_prompt(&self) -> String {
+        format!(
+            " SEARCH{}: {} ",
+            self.search_options.indicator(),
+            self.search_query
+        )
+    }
+
+    /// Highlight every match of the current search pattern in the given lines
+    fn highlight_search_matches(&self, lines: &mut [Line<'static>]) {
+        let Some(pattern) = self.search_pattern.as_ref().filter(|_| self.search_mode) else {
+            return;
+        };
+        let highlight = Style::default()
+            .fg(Color::Rgb(COLOR_PURE_BLACK.0, COLOR_PURE_BLACK.1, COLOR_PURE_BLACK.2))
+            .bg(Color::Rgb(COLOR_SEARCH_MATCH.0, COLOR_SEARCH_MATCH.1, COLOR_SEARCH_MATCH.2));
+
+        for line in lines.iter_mut() {
+            let ranges = pattern.match_columns(&search::line_text(line));
+            if !ranges.is_empty() {
+                *line = search::highlight_line(line, &ranges, highlight);
+            }
+        }
+    }
+
     /// Execute search against the current output buffer
     fn execute_search(&mut self) {
         self.search_results.clear();
         self.current_search_result = 0;
+        self.search_pattern = None;
 
         if self.search_query.is_empty() {
             self.dirty = true;
             return;
         }
 
-        // Search through the output buffer
-        if let Some(buffer) = self.output_buffers.get(self.active_session) {
-            let output = String::from_utf8_lossy(buffer);
-            let query_lower = self.search_query.to_lowercase();
+        // Fall back to literal matching when the regex does not compile
+        let mut literal_fallback = false;
+        let compiled = search::SearchPattern::new(&self.search_query, self.search_options)
+            .or_else(|e| {
+                debug!("Invalid search regex {:?}: {}", self.search_query, e);
+                literal_fallback = true;
+                let literal = search::SearchOptions {
+                    regex: false,
+                    ..self.search_options
+                };
+                search::SearchPattern::new(&self.search_query, literal)
+            });
+        let Ok(pattern) = compiled else {
+            self.dirty = true;
+            return;
+        };
 
-            for (line_idx, line) in output.lines().enumerate() {
-                if line.to_lowercase().contains(&query_lower) {
-                    self.search_results.push(line_idx);
-                }
-            }
+        // Search the parsed output so line indices match what is rendered
+        // Output spilled to disk is searched too; its lines come first
+        if self.active_session < self.tabs.len() {
+            let full_output = self.full_output(self.active_session);
+            let output = String::from_utf8_lossy(&full_output);
+            let lines: Vec<String> = AnsiParser::parse_with_palette(&output, &self.color_palette)
+                .iter()
+                .map(search::line_text)
+                .collect();
+            let matches = search::find_matches(lines.iter().map(String::as_str), &pattern);
+            self.search_results = search::matched_lines(&matches);
         }
+        self.search_pattern = Some(pattern);
+        self.scroll_to_current_match();
 
         let count = self.search_results.len();
+        let prefix = if literal_fallback {
+            "Invalid regex, literal search: "
+        } else {
+            ""
+        };
         if count > 0 {
             self.show_notification(format!(
-                "Found {} match{} for \"{}\"",
+                "{}Found {} match{} for \"{}\"",
+                prefix,
                 count,
                 if count == 1 { "" } else { "es" },
                 self.search_query
             ));
         } else {
-            self.show_notification(format!("No matches for \"{}\"", self.search_query));
+            self.show_notification(format!("{}No matches for \"{}\"", prefix, self.search_query));
         }
 
         self.dirty = true;
@@ -2816,6 +4685,7 @@ impl Terminal {
             return;
         }
         self.current_search_result = (self.current_search_result + 1) % self.search_results.len();
+        self.scroll_to_current_match();
         self.show_notification(format!(
             "Match {}/{}",
             self.current_search_result + 1,
@@ -2834,6 +4704,7 @@ impl Terminal {
         } else {
             self.current_search_result -= 1;
         }
+        self.scroll_to_current_match();
         self.show_notification(format!(
             "Match {}/{}",
             self.current_search_result + 1,
@@ -2843,62 +4714,184 @@ impl Terminal {
     }
 
     /// Scroll up through terminal output history
+    ///
+    /// Scrolling away from the bottom stops the view from following new output.
+    /// Past the top of what is in memory, output spilled to disk is paged back in.
     fn scroll_up(&mut self, lines: usize) {
-        // Calculate total lines available
-        let total_lines = self
-            .output_buffers
-            .get(self.active_session)
-            .map(|buf| {
-                let output = String::from_utf8_lossy(buf);
-                output.lines().count()
-            })
-            .unwrap_or(0);
-        let visible = self.terminal_rows.saturating_sub(3) as usize; // approx visible area
-        let max_offset = total_lines.saturating_sub(visible);
-        self.scroll_offset = (self.scroll_offset + lines).min(max_offset);
-        self.invalidate_active_cache();
-        self.dirty = true;
+        if self.in_alternate_screen(self.active_session) {
+            return;
+        }
+        let wanted = self.scroll_offset().saturating_add(lines);
+        let mut total = self.active_line_count();
+        while wanted.saturating_add(self.viewport_rows()) > total
+            && self.page_in_scrollback(self.active_session)
+        {
+            total = self.active_line_count();
+        }
+        let offset = clamp_scroll_offset(wanted, total, self.viewport_rows());
+        self.set_scroll_offset(offset);
     }
 
     /// Scroll down through terminal output history (toward latest)
+    ///
+    /// Reaching the bottom resumes following new output.
     fn scroll_down(&mut self, lines: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
-        self.invalidate_active_cache();
-        self.dirty = true;
+        let offset = clamp_scroll_offset(
+            self.scroll_offset().saturating_sub(lines),
+            self.active_line_count(),
+            self.viewport_rows(),
+        );
+        self.set_scroll_offset(offset);
+    }
+
+    /// Whether the active session's view follows the latest output
+    fn is_following_output(&self) -> bool {
+        self.scroll_offset() == 0
+    }
+
+    /// Scroll to the oldest output, paging in everything spilled to disk
+    ///
+    /// Like any scroll up, the view stops following new output.
+    fn scroll_to_top(&mut self) {
+        self.scroll_up(usize::MAX);
     }
 
     /// Reset scroll to follow latest output
     fn scroll_to_bottom(&mut self) {
-        if self.scroll_offset != 0 {
-            self.scroll_offset = 0;
-            self.invalidate_active_cache();
-            self.dirty = true;
+        self.set_scroll_offset(0);
+    }
+
+    /// Scroll offset of the active session (0 = following latest output)
+    fn scroll_offset(&self) -> usize {
+        self.tabs
+            .get(self.active_session)
+            .map_or(0, |tab| tab.scroll_offset)
+    }
+
+    /// Set the scroll offset of the active session, invalidating the render cache on change
+    ///
+    /// Following the output again moves output paged in from disk back out.
+    fn set_scroll_offset(&mut self, offset: usize) {
+        if let Some(tab) = self.tabs.get_mut(self.active_session) {
+            if tab.scroll_offset != offset {
+                tab.scroll_offset = offset;
+                self.invalidate_active_cache();
+                self.dirty = true;
+            }
+        }
+        if offset == 0 {
+            if let Some(tab) = self.tabs.get_mut(self.active_session) {
+                if tab.spill.paged_in() > 0 {
+                    tab.spill.settle();
+                    self.enforce_scrollback_limit(self.active_session);
+                }
+            }
+        }
+    }
+
+    /// Number of content rows available for output (excludes the status bar)
+    fn viewport_rows(&self) -> usize {
+        (self.terminal_rows as usize).saturating_sub(1).max(1)
+    }
+
+    /// Number of rendered lines in the active session's output
+    fn active_line_count(&self) -> usize {
+        self.tabs.get(self.active_session).map_or(0, |tab| {
+            let output = String::from_utf8_lossy(&tab.output);
+            AnsiParser::parse_with_palette(&output, &self.color_palette).len()
+        })
+    }
+
+    /// Scroll so the previous or next command's output starts at the top
+    ///
+    /// Stops at the first and last command instead of wrapping around.
+    fn jump_to_command(&mut self, forward: bool) {
+        let Some(tab) = self.tabs.get(self.active_session) else {
+            return;
+        };
+        let output = String::from_utf8_lossy(&tab.output);
+        let boundaries = match tab.prompt_marks.output_starts() {
+            osc133::OutputStarts::Marked(offsets) => {
+                AnsiParser::lines_at_offsets(&output, &offsets)
+            }
+            // Submitted commands start on the prompt line; output follows it
+            osc133::OutputStarts::Submitted(offsets) => {
+                AnsiParser::lines_at_offsets(&output, &offsets)
+                    .into_iter()
+                    .map(|line| line + 1)
+                    .collect()
+            }
+        };
+        let total = AnsiParser::parse_with_palette(&output, &self.color_palette).len();
+        let viewport = self.viewport_rows();
+        let top = total.saturating_sub(viewport + self.scroll_offset());
+
+        let offset = command_jump_line(&boundaries, top, forward)
+            .map(|line| top_aligned_scroll_offset(line, total, viewport))
+            .filter(|offset| *offset != self.scroll_offset());
+        match offset {
+            Some(offset) => self.set_scroll_offset(offset),
+            None if forward => self.show_notification("No later command".to_string()),
+            None => self.show_notification("No earlier command".to_string()),
+        }
+    }
+
+    /// Scroll the viewport so the current search match is centered
+    ///
+    /// A match in output spilled to disk is paged back in first.
+    fn scroll_to_current_match(&mut self) {
+        if let Some(&line) = self.search_results.get(self.current_search_result) {
+            let index = self.active_session;
+            while self.spilled_lines(index) > line && self.page_in_scrollback(index) {}
+            let line = line.saturating_sub(self.spilled_lines(index));
+            let offset =
+                centered_scroll_offset(line, self.active_line_count(), self.viewport_rows());
+            self.set_scroll_offset(offset);
         }
     }
 
     /// Invalidate the render cache for the active session to force re-render
     fn invalidate_active_cache(&mut self) {
-        if let Some(len) = self.cached_buffer_lens.get_mut(self.active_session) {
-            *len = 0; // Force cache invalidation
+        if let Some(tab) = self.tabs.get_mut(self.active_session) {
+            tab.styled_len = 0; // Force cache invalidation
         }
     }
 
+    /// Reparse a session's output from the start on the next render
+    ///
+    /// Needed whenever the buffer changes other than by appending to it.
+    fn invalidate_parse(&mut self, index: usize) {
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.styled_len = 0;
+            tab.parser = None;
+        }
+    }
+
+    /// Whether the status bar is taking input for search or a prompt
+    fn prompt_active(&self) -> bool {
+        self.search_mode || self.session_prompt.is_some() || self.pending_paste.is_some()
+    }
+
     /// Render the status bar at the bottom of the terminal
     fn render_status_bar(&self, f: &mut ratatui::Frame, area: Rect) {
-        let mode_text = if self.search_mode {
-            format!(" SEARCH: {} ", self.search_query)
-        } else if self.scroll_offset > 0 {
-            format!(" SCROLL [+{}] ", self.scroll_offset)
+        let mode_text = if let Some(paste) = &self.pending_paste {
+            paste.text()
+        } else if let Some(prompt) = &self.session_prompt {
+            prompt.text()
+        } else if self.search_mode {
+            self.search_prompt()
+        } else if self.scroll_offset() > 0 {
+            format!(" SCROLL [+{}] ", self.scroll_offset())
         } else {
             " NORMAL ".to_string()
         };
 
-        let mode_style = if self.search_mode {
+        let mode_style = if self.prompt_active() {
             Style::default()
                 .fg(Color::Rgb(COLOR_PURE_BLACK.0, COLOR_PURE_BLACK.1, COLOR_PURE_BLACK.2))
                 .bg(Color::Rgb(COLOR_COOL_RED.0, COLOR_COOL_RED.1, COLOR_COOL_RED.2))
                 .add_modifier(Modifier::BOLD)
-        } else if self.scroll_offset > 0 {
+        } else if self.scroll_offset() > 0 {
             Style::default()
                 .fg(Color::Rgb(COLOR_PURE_BLACK.0, COLOR_PURE_BLACK.1, COLOR_PURE_BLACK.2))
                 .bg(Color::Rgb(0xCC, 0x99, 0x33)) // Amber for scroll mode
@@ -2916,9 +4909,13 @@ impl Terminal {
             " Session 1 ".to_string()
         };
 
-        let hints = if self.search_mode {
-            " Esc: Exit │ Enter/Ctrl+N: Next │ ↑/Ctrl+Shift+N: Prev "
-        } else if self.scroll_offset > 0 {
+        let hints = if let Some(paste) = &self.pending_paste {
+            paste.hints()
+        } else if let Some(prompt) = &self.session_prompt {
+            prompt.hints()
+        } else if self.search_mode {
+            " Esc: Exit │ Enter/Ctrl+N: Next │ ↑/Ctrl+Shift+N: Prev │ Alt+R/C/W: Regex/Case/Word "
+        } else if self.scroll_offset() > 0 {
             " Shift+PgUp/PgDn: Scroll │ Esc: Back to Bottom "
         } else {
             " Ctrl+F: Search │ Shift+PgUp: Scroll │ Ctrl+T: New Tab "
@@ -2949,6 +4946,176 @@ impl Terminal {
         f.render_widget(paragraph, area);
     }
 
+    /// Open the status bar prompt for saving the current session under a name
+    fn open_save_session_prompt(&mut self) {
+        self.session_prompt = Some(SessionPrompt::SaveName(String::new()));
+        self.dirty = true;
+    }
+
+    /// Open the status bar picker listing saved sessions
+    fn open_session_picker(&mut self) {
+        let sessions = match self.session_manager.as_ref().map(SessionManager::list_sessions) {
+            Some(Ok(sessions)) => sessions,
+            Some(Err(e)) => {
+                warn!("Failed to list sessions: {}", e);
+                self.show_notification(format!("Load failed: {}", e));
+                return;
+            }
+            None => return,
+        };
+
+        if sessions.is_empty() {
+            self.show_notification("No saved sessions".to_string());
+            return;
+        }
+
+        self.session_prompt = Some(SessionPrompt::Pick {
+            sessions,
+            selected: 0,
+        });
+        self.dirty = true;
+    }
+
+    /// Handle a typed character in the session prompt
+    fn session_prompt_input(&mut self, ch: char) {
+        match self.session_prompt.take() {
+            Some(SessionPrompt::SaveName(mut name)) => {
+                if !ch.is_control() {
+                    name.push(ch);
+                }
+                self.session_prompt = Some(SessionPrompt::SaveName(name));
+            }
+            Some(SessionPrompt::ConfirmOverwrite { name, id }) => match ch {
+                'y' | 'Y' => self.finish_session_save(name, Some(id)),
+                // Go back to editing the name
+                'n' | 'N' => self.session_prompt = Some(SessionPrompt::SaveName(name)),
+                _ => self.session_prompt = Some(SessionPrompt::ConfirmOverwrite { name, id }),
+            },
+            Some(SessionPrompt::RestoreAutosave { saved_at }) => match ch {
+                'y' | 'Y' => self.restore_autosave(),
+                'n' | 'N' => self.discard_autosave(),
+                _ => self.session_prompt = Some(SessionPrompt::RestoreAutosave { saved_at }),
+            },
+            other => self.session_prompt = other,
+        }
+        self.dirty = true;
+    }
+
+    /// Delete the last character of the save-as name
+    fn session_prompt_backspace(&mut self) {
+        if let Some(SessionPrompt::SaveName(name)) = self.session_prompt.as_mut() {
+            name.pop();
+            self.dirty = true;
+        }
+    }
+
+    /// Move the session picker selection
+    fn session_prompt_move(&mut self, delta: isize) {
+        if let Some(prompt) = self.session_prompt.as_mut() {
+            prompt.move_selection(delta);
+            self.dirty = true;
+        }
+    }
+
+    /// Close the session prompt without saving or loading
+    fn session_prompt_cancel(&mut self) {
+        self.session_prompt = None;
+        self.dirty = true;
+    }
+
+    /// Confirm the session prompt: save under the typed name or load the picked session
+    fn session_prompt_submit(&mut self) {
+        match self.session_prompt.take() {
+            Some(SessionPrompt::SaveName(name)) => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    // Keep prompting until a name is typed
+                    self.session_prompt = Some(SessionPrompt::SaveName(name));
+                    return;
+                }
+
+                let existing = self
+                    .session_manager
+                    .as_ref()
+                    .map(|sm| sm.find_by_name(&name));
+                match existing {
+                    Some(Ok(Some(existing))) => {
+                        self.session_prompt = Some(SessionPrompt::ConfirmOverwrite {
+                            name,
+                            id: existing.id,
+                        });
+                    }
+                    Some(Err(e)) => {
+                        warn!("Failed to list sessions: {}", e);
+                        self.show_notification(format!("Save failed: {}", e));
+                    }
+                    _ => self.finish_session_save(name, None),
+                }
+            }
+            // Overwriting and restoring need an explicit y/n
+            Some(
+                prompt @ (SessionPrompt::ConfirmOverwrite { .. }
+                | SessionPrompt::RestoreAutosave { .. }),
+            ) => {
+                self.session_prompt = Some(prompt);
+            }
+            Some(prompt @ SessionPrompt::Pick { .. }) => {
+                if let Some(summary) = prompt.selected_session() {
+                    let loaded = self
+                        .session_manager
+                        .as_ref()
+                        .map(|sm| sm.load_session(&summary.id));
+                    let result = match loaded {
+                        Some(Ok(session)) => self.restore_session(&session),
+                        Some(Err(e)) => Err(e),
+                        None => Ok(()),
+                    };
+                    match result {
+                        Ok(()) => {
+                            self.show_notification(format!("Session loaded: {}", summary.name));
+                        }
+                        Err(e) => {
+                            warn!("Failed to load session: {}", e);
+                            self.show_notification(format!("Load failed: {}", e));
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+        self.dirty = true;
+    }
+
+    /// Restore the autosave slot and report the outcome
+    fn restore_autosave(&mut self) {
+        let loaded = self
+            .session_manager
+            .as_ref()
+            .map(|sm| sm.load_session(AUTOSAVE_ID));
+        let result = match loaded {
+            Some(Ok(session)) => self.restore_session(&session),
+            Some(Err(e)) => Err(e),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => self.show_notification("Autosave restored".to_string()),
+            Err(e) => {
+                warn!("Failed to restore autosave: {}", e);
+                self.show_notification(format!("Load failed: {}", e));
+            }
+        }
+    }
+
+    /// Save under `name` and report the outcome
+    fn finish_session_save(&mut self, name: String, id: Option<String>) {
+        if let Err(e) = self.save_session_as(name.clone(), id) {
+            warn!("Failed to save session: {}", e);
+            self.show_notification(format!("Save failed: {}", e));
+        } else {
+            self.show_notification(format!("Session saved: {}", name));
+        }
+    }
+
     /// Auto-save the current session on exit
     fn auto_save_session(&mut self) {
         use crate::session::{SavedSession, TabState};
@@ -2957,12 +5124,12 @@ impl Terminal {
 
         if let Some(ref sm) = self.session_manager {
             let tabs: Vec<TabState> = self
-                .output_buffers
+                .tabs
                 .iter()
                 .enumerate()
-                .map(|(i, buf)| {
+                .map(|(i, tab)| {
                     // Only save the last portion of output to keep sessions manageable
-                    let output = String::from_utf8_lossy(buf);
+                    let output = String::from_utf8_lossy(&tab.output);
                     let truncated = if output.len() > 50_000 {
                         // Find the nearest valid UTF-8 char boundary at or after the cut point
                         let start = output.ceil_char_boundary(output.len() - 50_000);
@@ -2972,12 +5139,9 @@ impl Terminal {
                     };
                     TabState {
                         output: truncated,
-                        working_dir: self
-                            .keybindings
-                            .shell_integration()
-                            .current_dir
-                            .clone(),
+                        working_dir: self.tab_dir(i),
                         active: i == self.active_session,
+                        scroll_offset: tab.scroll_offset,
                     }
                 })
                 .collect();
@@ -3010,41 +5174,67 @@ impl Terminal {
             }
 
             // Load the most recent session
-            let latest_session = &sessions[0];
-            let session = sm.load_session(&latest_session.id)?;
-
-            // Restore tabs from session
-            for (i, tab) in session.tabs.iter().enumerate() {
-                if i == 0 {
-                    // Replace first tab
-                    if let Some(buf) = self.output_buffers.get_mut(0) {
-                        buf.clear();
-                        buf.extend_from_slice(tab.output.as_bytes());
-                        if let Some(len) = self.cached_buffer_lens.get_mut(0) {
-                            *len = 0; // Invalidate cache
-                        }
-                    }
-                } else {
-                    // Create new tabs
-                    if self.sessions.len() <= i {
-                        self.create_new_tab()?;
-                    }
-                    if let Some(buf) = self.output_buffers.get_mut(i) {
-                        buf.clear();
-                        buf.extend_from_slice(tab.output.as_bytes());
-                        if let Some(len) = self.cached_buffer_lens.get_mut(i) {
-                            *len = 0;
-                        }
-                    }
-                }
+            let session = sm.load_session(&sessions[0].id)?;
+            self.restore_session(&session)?;
+        }
+        Ok(())
+    }
 
-                // Set active tab
-                if tab.active {
-                    self.active_session = i;
-                }
+    /// Restore tab output, scroll positions and the active tab from a saved session
+    ///
+    /// Each tab's shell is started in the tab's saved working directory if
+    /// that directory still exists.
+    fn restore_session(&mut self, session: &SavedSession) -> Result<()> {
+        for (i, saved) in session.tabs.iter().enumerate() {
+            let saved_dir = saved
+                .working_dir
+                .as_deref()
+                .filter(|dir| std::path::Path::new(dir).is_dir());
+            if let Some(dir) = saved_dir {
+                self.start_shell_in(i, dir)?;
             }
 
-            self.dirty = true;
+            // Replace the first tab, create new tabs for the rest
+            if i > 0 && self.sessions.len() <= i {
+                self.create_new_tab()?;
+            }
+            if let Some(tab) = self.tabs.get_mut(i) {
+                tab.output.clear();
+                tab.output.extend_from_slice(saved.output.as_bytes());
+                tab.spill.clear();
+                self.invalidate_parse(i);
+            }
+
+            // Restore scroll position, clamped in case the output is shorter now
+            let total = AnsiParser::parse_with_palette(&saved.output, &self.color_palette).len();
+            let offset = clamp_scroll_offset(saved.scroll_offset, total, self.viewport_rows());
+            if let Some(tab) = self.tabs.get_mut(i) {
+                tab.scroll_offset = offset;
+            }
+
+            // Set active tab
+            if saved.active {
+                self.active_session = i;
+            }
+        }
+
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Give tab `index` a fresh shell in `dir`, opening the tab if needed
+    fn start_shell_in(&mut self, index: usize, dir: &str) -> Result<()> {
+        let session = self.spawn_shell(Some(dir))?;
+        if index < self.sessions.len() {
+            self.sessions[index] = session;
+            if let Some(tab) = self.tabs.get_mut(index) {
+                tab.reset_for_new_shell();
+            }
+        } else {
+            self.push_tab(session);
+        }
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.titles.set_dir(dir);
         }
         Ok(())
     }
@@ -3081,110 +5271,104 @@ impl Terminal {
     }
 
     /// Use all shell integration features
-    fn update_shell_integration_state(&mut self, output: &str) {
-        // Parse OSC 0, 1, or 2 for window title changes
-        if output.contains("\x1b]0;") || output.contains("\x1b]1;") || output.contains("\x1b]2;") {
-            if let Some(start) = output.find("\x1b]") {
-                if let Some(end) = output[start..].find('\x07') {
-                    // OSC sequences: 0 = icon+title, 1 = icon, 2 = title
-                    // Format: ESC ] number ; text BEL
-                    // end is relative to start, so start + end <= output.len()
-                    if start + end <= output.len() {
-                        let osc_content = &output[start..start + end];
-                        if let Some(semicolon) = osc_content.find(';') {
-                            if semicolon + 1 < osc_content.len() {
-                                let title = &osc_content[semicolon + 1..];
-                                // Call on_title_change hook
-                                if let Some(ref executor) = self.hooks_executor {
-                                    if let Some(ref script) = self.config.hooks.on_title_change {
-                                        if let Err(e) =
-                                            executor.on_title_change(script, title)
-                                        {
-                                            warn!("on_title_change hook failed: {}", e);
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    ///
+    /// `output` came from tab `index`'s shell, and `base` is where it starts
+    /// in that tab's buffer. Titles, marks and directories are kept per tab;
+    /// the directory, command and exit code behind key bindings follow the
+    /// active tab only. Returns whether an OSC 133 mark says the running
+    /// command has finished.
+    fn update_shell_integration_state(&mut self, index: usize, output: &str, base: usize) -> bool {
+        let active = index == self.active_session;
+
+        // Parse OSC 0, 1 and 2 for the tab's icon name and window title
+        // Format: ESC ] 0|1|2 ; text BEL
+        for update in title::title_updates(output) {
+            if let Some(tab) = self.tabs.get_mut(index) {
+                tab.titles.apply(&update);
+            }
+            if update.text.is_empty() {
+                continue;
+            }
+            if let Some(ref script) = self.config.hooks.on_title_change {
+                self.with_hooks(|executor| {
+                    if let Err(e) = executor.on_title_change(script, &update.text) {
+                        warn!("on_title_change hook failed: {}", e);
                     }
-                }
+                });
             }
         }
 
         // Parse OSC 7 for directory tracking
-        // Format: ESC ] 7 ; url BEL (where url is typically file://hostname/path)
-        if output.contains("\x1b]7;") {
-            if let Some(start) = output.find("\x1b]7;") {
-                if let Some(end) = output[start..].find('\x07') {
-                    // OSC 7 prefix is 4 characters: ESC ] 7 ;
-                    const OSC7_PREFIX_LEN: usize = 4;
-                    // Ensure we have content after the prefix (end is relative to start)
-                    if end > OSC7_PREFIX_LEN && start + end <= output.len() {
-                        let dir = &output[start + OSC7_PREFIX_LEN..start + end];
-                        self.keybindings.update_directory(dir.to_string());
-                    }
-                }
+        // Format: ESC ] 7 ; file://hostname/path BEL
+        if let Some(dir) = osc7::last_directory(output) {
+            if let Some(tab) = self.tabs.get_mut(index) {
+                tab.titles.set_dir(dir.as_str());
+            }
+            if active {
+                self.keybindings.borrow_mut().update_directory(dir);
             }
         }
 
-        // Parse OSC 133 for command tracking
-        // Format: ESC ] 133 ; C ; command BEL
-        if output.contains("\x1b]133;") {
-            if let Some(start) = output.find("\x1b]133;C;") {
-                if let Some(end) = output[start..].find('\x07') {
-                    // OSC 133;C; prefix is 8 bytes: ESC ] 1 3 3 ; C ;
-                    const OSC133C_PREFIX_LEN: usize = 8;
-                    // Ensure we have content after the prefix (end is relative to start)
-                    if end > OSC133C_PREFIX_LEN && start + end <= output.len() {
-                        let cmd = &output[start + OSC133C_PREFIX_LEN..start + end];
-                        self.keybindings.update_last_command(cmd.to_string());
-                    }
-                }
+        // Parse OSC 52 for clipboard writes
+        // Format: ESC ] 52 ; selection ; base64 BEL
+        if let Some(text) = self.osc52_clipboard_text(output) {
+            if let Err(e) = set_clipboard_text(text) {
+                warn!("OSC 52 clipboard write failed: {}", e);
             }
+        }
 
-            // Parse OSC 133;D for command end with exit code
-            // Format: ESC ] 133 ; D ; exit_code BEL
-            if let Some(start) = output.find("\x1b]133;D;") {
-                if let Some(end) = output[start..].find('\x07') {
-                    // OSC 133;D; prefix is 8 bytes: ESC ] 1 3 3 ; D ;
-                    const OSC133D_PREFIX_LEN: usize = 8;
-                    // Ensure we have content after the prefix (end is relative to start)
-                    if end > OSC133D_PREFIX_LEN && start + end <= output.len() {
-                        let exit_code_str = &output[start + OSC133D_PREFIX_LEN..start + end];
-                        if let Ok(exit_code) = exit_code_str.parse::<i32>() {
-                            // Call on_command_end hook
-                            if let Some(ref executor) = self.hooks_executor {
-                                if let Some(ref script) = self.config.hooks.on_command_end {
-                                    let command = self
-                                        .keybindings
-                                        .shell_integration()
-                                        .last_command
-                                        .as_deref()
-                                        .unwrap_or("");
-                                    if let Err(e) =
-                                        executor.on_command_end(script, command, exit_code)
-                                    {
-                                        warn!("on_command_end hook failed: {}", e);
-                                    }
-                                }
+        // Parse OSC 133 marks for command tracking
+        // Format: ESC ] 133 ; A|B|C|D [; text] BEL
+        let marks = osc133::parse_marks(output);
+        let mut command_finished = false;
+        for (_, mark) in &marks {
+            command_finished |= mark.ends_command();
+            if !active {
+                continue;
+            }
+            match mark {
+                osc133::Mark::CommandStart {
+                    command: Some(command),
+                } => {
+                    self.keybindings
+                        .borrow_mut()
+                        .update_last_command(command.clone());
+                }
+                osc133::Mark::CommandEnd { exit_code } => {
+                    self.keybindings.borrow_mut().update_exit_code(*exit_code);
+                    if let (Some(exit_code), Some(script)) =
+                        (*exit_code, self.config.hooks.on_command_end.as_ref())
+                    {
+                        let command = self
+                            .keybindings
+                            .borrow()
+                            .shell_integration()
+                            .last_command
+                            .clone()
+                            .unwrap_or_default();
+                        self.with_hooks(|executor| {
+                            if let Err(e) = executor.on_command_end(script, &command, exit_code) {
+                                warn!("on_command_end hook failed: {}", e);
                             }
-                        }
+                        });
                     }
                 }
+                _ => {}
             }
         }
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.prompt_marks.record(base, marks);
+        }
 
         // Enable shell integration if detected
         use crate::keybindings::ShellIntegrationFeature;
         if output.contains("\x1b]133;") || output.contains("\x1b]7;") {
-            self.keybindings
-                .enable_shell_integration(ShellIntegrationFeature::OscSequences, true);
-            self.keybindings
-                .enable_shell_integration(ShellIntegrationFeature::PromptDetection, true);
+            let mut keybindings = self.keybindings.borrow_mut();
+            keybindings.enable_shell_integration(ShellIntegrationFeature::OscSequences, true);
+            keybindings.enable_shell_integration(ShellIntegrationFeature::PromptDetection, true);
         }
 
-        // Access shell integration state
-        let _si = self.keybindings.shell_integration();
+        command_finished
     }
 
     /// Use all autocomplete helper methods
@@ -3402,38 +5586,16 @@ impl Terminal {
     /// Used for debugging and status display.
     fn get_config_summary(&self) -> String {
         format!(
-            "Terminal Config: Cursor={}, Font={}pt, HW_Accel={}, SplitPane={}, MaxHistory={}",
+            "Terminal Config: Cursor={}, Font={}pt, HW_Accel={}, SplitPane={}, MaxHistory={}, TranslatorMappings={}",
             self.cursor_style(),
             self.font_size(),
             self.is_hardware_acceleration_enabled(),
             self.is_split_pane_enabled(),
-            self.max_history()
+            self.max_history(),
+            self.config.translator.custom.len()
         )
     }
 
-    /// Load background image from file
-    fn load_background_image(path: &str) -> Result<(Vec<u8>, u16, u16)> {
-        use image::GenericImageView;
-
-        // Load image from path
-        let img = image::open(path)
-            .with_context(|| format!("Failed to load background image from: {}", path))?;
-
-        // Get dimensions
-        let (width, height) = img.dimensions();
-
-        // Convert to RGBA bytes
-        let rgba = img.to_rgba8();
-        let bytes = rgba.into_raw();
-
-        debug!(
-            "Loaded background image: {}x{} from {}",
-            width, height, path
-        );
-
-        Ok((bytes, width as u16, height as u16))
-    }
-
     /// Handle mouse event for text selection
     fn handle_mouse_selection(&mut self, event: crossterm::event::MouseEvent) {
         use crossterm::event::MouseEventKind;
@@ -3444,12 +5606,14 @@ impl Terminal {
                 self.selection_start = Some((event.column, event.row));
                 self.selection_end = Some((event.column, event.row));
                 self.selection_active = true;
+                self.selection_block = event.modifiers.contains(KeyModifiers::ALT);
                 self.dirty = true;
             }
             MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
-                // Update selection end
+                // Update selection end; Alt switches to a block selection mid-drag
                 if self.selection_active {
                     self.selection_end = Some((event.column, event.row));
+                    self.selection_block = event.modifiers.contains(KeyModifiers::ALT);
                     self.dirty = true;
                 }
             }
@@ -3469,8 +5633,14 @@ impl Terminal {
     }
 
     /// Check if a position is within the current selection
+    ///
+    /// A block selection covers the same columns on every row it spans.
     fn is_position_selected(&self, col: u16, row: u16) -> bool {
         if let (Some(start), Some(end)) = (self.selection_start, self.selection_end) {
+            if self.selection_block {
+                return (start.1.min(end.1)..=start.1.max(end.1)).contains(&row)
+                    && (start.0.min(end.0)..=start.0.max(end.0)).contains(&col);
+            }
             let (start_row, start_col) =
                 if start.1 < end.1 || (start.1 == end.1 && start.0 <= end.0) {
                     (start.1, start.0)
@@ -3499,16 +5669,96 @@ impl Terminal {
         false
     }
 
+    /// Split each line's spans at the selection's edges, flagging the selected parts
+    ///
+    /// Lines are matched to selection rows by index and spans to columns by
+    /// display cell, as the selection highlight is drawn: a wide character is
+    /// selected if either of its cells is, and combining marks go with the
+    /// character before them. Empty spans are dropped.
+    fn selected_spans(&self, lines: &[Line<'static>]) -> Vec<Vec<(Span<'static>, bool)>> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(row_idx, line)| {
+                let row = row_idx as u16;
+                let mut parts = Vec::new();
+                let mut col = 0u16;
+                let mut selected = self.is_position_selected(0, row);
+                for span in &line.spans {
+                    let mut run = String::new();
+                    let mut run_selected = None;
+                    for ch in span.content.chars() {
+                        #[allow(clippy::cast_possible_truncation)]
+                        let width = ch.width().unwrap_or(0) as u16;
+                        if width > 0 {
+                            selected = (col..col.saturating_add(width))
+                                .any(|c| self.is_position_selected(c, row));
+                            col = col.saturating_add(width);
+                        }
+                        if run_selected.is_some_and(|previous| previous != selected) {
+                            let finished = Span::styled(std::mem::take(&mut run), span.style);
+                            parts.push((finished, !selected));
+                        }
+                        run_selected = Some(selected);
+                        run.push(ch);
+                    }
+                    if let Some(selected) = run_selected {
+                        parts.push((Span::styled(run, span.style), selected));
+                    }
+                }
+                parts
+            })
+            .collect()
+    }
+
+    /// The selected part of the visible output as HTML and as plain text
+    ///
+    /// Returns `None` when nothing on screen is selected.
+    fn selection_html(&self) -> Option<(String, String)> {
+        let lines = &self.tabs.get(self.active_session)?.styled_lines;
+        let selected: Vec<Line<'static>> = self
+            .selected_spans(lines)
+            .into_iter()
+            .filter_map(|parts| {
+                let spans: Vec<Span<'static>> = parts
+                    .into_iter()
+                    .filter_map(|(span, selected)| selected.then_some(span))
+                    .collect();
+                (!spans.is_empty()).then(|| Line::from(spans))
+            })
+            .collect();
+        if selected.is_empty() {
+            return None;
+        }
+
+        let html = crate::session::lines_to_html(&selected, &self.color_palette);
+        let text = selected
+            .iter()
+            .map(search::line_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some((html, text))
+    }
+
+    /// Copy the selection with its colors, returning `false` if nothing is selected
+    ///
+    /// Apps that can't paste HTML get the plain text instead.
+    fn copy_selection_rich(&self) -> Result<bool> {
+        let Some((html, text)) = self.selection_html() else {
+            return Ok(false);
+        };
+        let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+        clipboard
+            .set_html(html, Some(text))
+            .context("Failed to set clipboard HTML")?;
+        Ok(true)
+    }
+
     /// Copy selected text to clipboard
     fn copy_selection_to_clipboard(&self) -> Result<()> {
-        use arboard::Clipboard;
-
         if let (Some(start), Some(end)) = (self.selection_start, self.selection_end) {
             let text = self.get_selected_text(start, end)?;
-            let mut clipboard = Clipboard::new().context("Failed to access clipboard")?;
-            clipboard
-                .set_text(text)
-                .context("Failed to set clipboard text")?;
+            set_clipboard_text(text)?;
             debug!("Copied selection to clipboard");
         }
         Ok(())
@@ -3516,7 +5766,8 @@ impl Terminal {
 
     /// Get the text within the selection range
     ///
-    /// Uses character-based indexing to safely handle UTF-8 strings.
+    /// Columns are display cells, so wide and combining characters line up
+    /// with the highlight.
     fn get_selected_text(&self, start: (u16, u16), end: (u16, u16)) -> Result<String> {
         // Normalize start and end positions
         let (start_pos, end_pos) = if start.1 < end.1 || (start.1 == end.1 && start.0 <= end.0) {
@@ -3526,35 +5777,30 @@ impl Terminal {
         };
 
         // Get the output buffer for current session
-        if let Some(buffer) = self.output_buffers.get(self.active_session) {
-            // Parse the buffer to get styled lines
-            let output_str = String::from_utf8_lossy(buffer);
+        if let Some(tab) = self.tabs.get(self.active_session) {
+            // Parse the tab.output to get styled lines
+            let output_str = String::from_utf8_lossy(&tab.output);
             let lines: Vec<&str> = output_str.lines().collect();
+            if self.selection_block {
+                return Ok(block_selection_text(&lines, start, end));
+            }
 
             let mut selected_text = String::new();
             for row in start_pos.1..=end_pos.1 {
                 if let Some(line) = lines.get(row as usize) {
-                    // Use character-based indexing for UTF-8 safety
-                    let char_count = line.chars().count();
                     let line_start = if row == start_pos.1 {
-                        (start_pos.0 as usize).min(char_count)
+                        usize::from(start_pos.0)
                     } else {
                         0
                     };
                     let line_end = if row == end_pos.1 {
-                        (end_pos.0 as usize).min(char_count)
+                        usize::from(end_pos.0)
                     } else {
-                        char_count
+                        usize::MAX
                     };
 
-                    if line_start < char_count {
-                        // Safely extract substring using character indices
-                        let substring: String = line
-                            .chars()
-                            .skip(line_start)
-                            .take(line_end.saturating_sub(line_start))
-                            .collect();
-                        selected_text.push_str(&substring);
+                    if line_start < line.width() {
+                        selected_text.push_str(&column_text(line, line_start, line_end));
                         if row < end_pos.1 {
                             selected_text.push('\n');
                         }
@@ -3568,122 +5814,389 @@ impl Terminal {
     }
 
     /// Update cursor trail with current cursor position
+    ///
+    /// Positions that have faded out are dropped here too.
     fn update_cursor_trail(&mut self, col: u16, row: u16) {
         if let Some(ref trail_config) = self.config.theme.cursor_trail {
             if trail_config.enabled {
-                let now = std::time::Instant::now();
-                self.cursor_trail_positions.push((col, row, now));
-
-                // Limit trail length - use drain for O(n) instead of O(n²) with repeated remove(0)
-                let max_len = trail_config.length;
-                if self.cursor_trail_positions.len() > max_len {
-                    let excess = self.cursor_trail_positions.len() - max_len;
-                    self.cursor_trail_positions.drain(..excess);
-                }
+                cursor_trail::record(
+                    &mut self.cursor_trail_positions,
+                    (col, row),
+                    std::time::Instant::now(),
+                    trail_config,
+                );
             }
         }
     }
 
-    /// Render background image if configured
-    fn render_background(&self, f: &mut ratatui::Frame) {
-        if let Some(ref bg_config) = self.config.theme.background_image {
-            // Log the configured mode and blur for GPU implementation reference
-            debug!(
-                "Background config: mode={}, blur={}",
-                bg_config.mode, bg_config.blur
-            );
-
-            // For now, render a colored background as placeholder
-            // Full image rendering requires GPU or custom backend
-            if let Some(ref color_str) = bg_config.color {
-                if let Ok(color) = crate::colors::TrueColor::from_hex(color_str) {
-                    let opacity = bg_config.opacity;
-                    let adjusted_color = if opacity < 1.0 {
-                        // Blend with black background based on opacity
-                        let r = (color.r as f32 * opacity) as u8;
-                        let g = (color.g as f32 * opacity) as u8;
-                        let b = (color.b as f32 * opacity) as u8;
-                        Color::Rgb(r, g, b)
-                    } else {
-                        Color::Rgb(color.r, color.g, color.b)
-                    };
-
-                    // Render background block
-                    let block = Block::default().style(Style::default().bg(adjusted_color));
-                    f.render_widget(block, f.size());
-                }
+    /// Render the background image or color if configured
+    ///
+    /// The image is drawn through each cell's background color, laid out by
+    /// `mode` and blended into the theme background by `opacity`. Without an
+    /// image, `color` is blended in the same way.
+    fn render_background(&mut self, f: &mut ratatui::Frame) {
+        let Some(ref bg_config) = self.config.theme.background_image else {
+            return;
+        };
+        let theme_background = crate::colors::TrueColor::from_hex(&self.config.theme.background)
+            .unwrap_or(crate::colors::TrueColor::new(0x1E, 0x1E, 0x1E));
+        let base = bg_config
+            .color
+            .as_deref()
+            .and_then(|color| crate::colors::TrueColor::from_hex(color).ok());
+        let area = f.size();
+
+        if let Some(ref mut image) = self.background_image {
+            let layout = background::CellLayout {
+                mode: background::BackgroundMode::from_name(&bg_config.mode),
+                cols: area.width,
+                rows: area.height,
+                base: base.unwrap_or(theme_background),
+                opacity: bg_config.opacity,
+            };
+            let frame = self
+                .background_clock
+                .as_ref()
+                .map_or(0, background::FrameClock::frame);
+            let colors = image.cell_colors(frame, layout);
+            let buffer = f.buffer_mut();
+            for (index, color) in colors.iter().enumerate() {
+                let index = u16::try_from(index).unwrap_or(u16::MAX);
+                let (x, y) = (area.x + index % area.width, area.y + index / area.width);
+                buffer
+                    .get_mut(x, y)
+                    .set_bg(Color::Rgb(color.r, color.g, color.b));
             }
-
-            // Note: Actual image rendering with mode (fill, fit, stretch, tile, center)
-            // and blur effects requires GPU renderer implementation
-            // The mode and blur values are logged above for GPU implementation
-            // This is documented in IMPLEMENTATION_PLAN.md as GPU-only feature
+        } else if let Some(color) = base {
+            let color = theme_background.blend(color, bg_config.opacity);
+            let block =
+                Block::default().style(Style::default().bg(Color::Rgb(color.r, color.g, color.b)));
+            f.render_widget(block, area);
         }
     }
 
     /// Render cursor trail if configured
+    ///
+    /// Each cell the cursor left is tinted with the trail color, fading to the
+    /// theme background as it ages. The text in the cell stays readable.
     fn render_cursor_trail(&self, f: &mut ratatui::Frame) {
-        if let Some(ref trail_config) = self.config.theme.cursor_trail {
-            if trail_config.enabled && !self.cursor_trail_positions.is_empty() {
-                let now = std::time::Instant::now();
+        let Some(trail_config) = self
+            .config
+            .theme
+            .cursor_trail
+            .as_ref()
+            .filter(|trail| trail.enabled)
+        else {
+            return;
+        };
 
-                // Parse trail color
-                let trail_color =
-                    if let Ok(color) = crate::colors::TrueColor::from_hex(&trail_config.color) {
-                        Color::Rgb(color.r, color.g, color.b)
-                    } else {
-                        Color::Yellow
-                    };
+        let background = crate::colors::TrueColor::from_hex(&self.config.theme.background)
+            .unwrap_or(crate::colors::TrueColor::new(0x1E, 0x1E, 0x1E));
+        let style = cursor_trail::TrailStyle::new(trail_config, background);
+        let now = std::time::Instant::now();
+        let screen = f.size();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let width = trail_config.width.max(1.0) as u16;
+
+        for (col, row, left) in cursor_trail::cells(&self.cursor_trail_positions) {
+            let Some(color) = style.color(now.duration_since(left)) else {
+                continue;
+            };
+            if row >= screen.height {
+                continue;
+            }
+            for x in col..col.saturating_add(width).min(screen.width) {
+                f.buffer_mut()
+                    .get_mut(x, row)
+                    .set_bg(Color::Rgb(color.r, color.g, color.b));
+            }
+        }
+    }
+}
+
+/// Clamp a scroll offset to `[0, total_lines - viewport]`
+/// Decide what to do with a tab whose shell exited while `open_tabs` tabs are open
+/// The command's lines in shell output that starts with the echoed command
+///
+/// The echoed line is dropped, and so is the last line when it is the prompt
+/// that followed the command.
+fn command_output(output: &str, ends_with_prompt: bool) -> String {
+    let Some((_, after_echo)) = output.split_once('\n') else {
+        return String::new();
+    };
+    let mut lines: Vec<String> = AnsiParser::parse(after_echo)
+        .iter()
+        .map(|line| search::line_text(line).trim_end().to_string())
+        .collect();
+    if ends_with_prompt {
+        lines.pop();
+    }
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
 
-                // Render trail positions with fading
-                for (i, (col, row, timestamp)) in self.cursor_trail_positions.iter().enumerate() {
-                    let age_ms = now.duration_since(*timestamp).as_millis() as f32;
-                    // Prevent division by zero - use 1.0 as minimum
-                    let max_age_ms = (trail_config.animation_speed as f32).max(1.0);
+/// A shell's exit code as the signed status OSC 133 marks use
+fn exit_status_code(code: u32) -> i32 {
+    i32::try_from(code).unwrap_or(i32::MAX)
+}
 
-                    // Skip if too old
-                    if age_ms > max_age_ms {
-                        continue;
-                    }
+fn exit_action(shell: &crate::config::ShellConfig, open_tabs: usize) -> ExitAction {
+    if !shell.close_on_exit {
+        ExitAction::KeepTab
+    } else if open_tabs > 1 {
+        ExitAction::CloseTab
+    } else if shell.quit_on_last_exit {
+        ExitAction::Quit
+    } else {
+        ExitAction::KeepTab
+    }
+}
 
-                    // Calculate alpha based on position and age
-                    let position_ratio = i as f32 / trail_config.length as f32;
-                    let age_ratio = 1.0 - (age_ms / max_age_ms);
+fn clamp_scroll_offset(offset: usize, total_lines: usize, viewport: usize) -> usize {
+    offset.min(total_lines.saturating_sub(viewport))
+}
 
-                    let alpha = match trail_config.fade_mode.as_str() {
-                        "linear" => position_ratio * age_ratio,
-                        "exponential" => (position_ratio * age_ratio).powf(2.0),
-                        "smooth" => 1.0 - (1.0 - position_ratio * age_ratio).powf(3.0),
-                        _ => position_ratio * age_ratio,
-                    };
+/// Compute the scroll offset that centers `line` in a viewport of `viewport` rows
+///
+/// The offset counts lines up from the bottom of the output, matching how the
+/// render path skips `total_lines - viewport - offset` lines from the top. It is
+/// clamped so the viewport never scrolls past either end of the output.
+fn centered_scroll_offset(line: usize, total_lines: usize, viewport: usize) -> usize {
+    let max_top = total_lines.saturating_sub(viewport);
+    let top = line.saturating_sub(viewport / 2).min(max_top);
+    max_top - top
+}
 
-                    // Only render if visible
-                    if alpha > 0.1 && *col < f.size().width && *row < f.size().height {
-                        // Render trail character with faded style
-                        let area = Rect {
-                            x: *col,
-                            y: *row,
-                            width: (trail_config.width.max(1.0) as u16),
-                            height: 1,
-                        };
+/// Compute the scroll offset that puts `line` at the top of the viewport
+///
+/// Lines too close to the end are shown as near the top as the output allows.
+fn top_aligned_scroll_offset(line: usize, total_lines: usize, viewport: usize) -> usize {
+    clamp_scroll_offset(
+        total_lines.saturating_sub(viewport + line),
+        total_lines,
+        viewport,
+    )
+}
+
+/// The command boundary to jump to from a viewport whose top is line `top`
+///
+/// `boundaries` are the lines command outputs start on, in order. Returns the
+/// nearest one above `top`, or below it when `forward`, and `None` past the
+/// first or last command.
+fn command_jump_line(boundaries: &[usize], top: usize, forward: bool) -> Option<usize> {
+    if forward {
+        boundaries.iter().copied().find(|&line| line > top)
+    } else {
+        boundaries.iter().copied().rev().find(|&line| line < top)
+    }
+}
 
-                        let style = Style::default().fg(trail_color).add_modifier(Modifier::DIM);
+/// The text of a block selection between the `(col, row)` corners `start` and `end`
+///
+/// Each row contributes the characters in the selected columns, both ends
+/// included, and rows are joined with newlines. Rows that end early are
+/// clipped rather than padded, so a row left of the block adds an empty line.
+fn block_selection_text(lines: &[&str], start: (u16, u16), end: (u16, u16)) -> String {
+    let first_col = usize::from(start.0.min(end.0));
+    let last_col = usize::from(start.0.max(end.0));
+    (start.1.min(end.1)..=start.1.max(end.1))
+        .map(|row| {
+            lines.get(usize::from(row)).map_or_else(String::new, |line| {
+                column_text(line, first_col, last_col + 1)
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-                        let trail_char = if alpha > 0.7 {
-                            "●"
-                        } else if alpha > 0.4 {
-                            "○"
-                        } else {
-                            "·"
-                        };
-                        let span = Span::styled(trail_char, style);
-                        let paragraph = Paragraph::new(Line::from(span));
-                        f.render_widget(paragraph, area);
-                    }
+/// Remove the last grapheme cluster from a command buffer
+///
+/// A flag, an emoji joined with ZWJ or a letter with combining accents is
+/// drawn as one character and goes as one. A buffer that isn't valid UTF-8
+/// loses its last code point instead.
+fn pop_grapheme(buffer: &mut Vec<u8>) {
+    if let Ok(text) = std::str::from_utf8(buffer) {
+        let start = text
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start);
+        buffer.truncate(start);
+        return;
+    }
+    // Drop trailing continuation bytes (10xxxxxx) and the byte that leads them
+    while let Some(last) = buffer.pop() {
+        if last & 0xC0 != 0x80 {
+            break;
+        }
+    }
+}
+
+/// Byte offset in `output` where rendered line `line` starts
+///
+/// Lines are counted as [`AnsiParser::parse`] returns them. Only offsets
+/// just past a newline are considered, so the first one on or below `line`
+/// is used, or the end of `output` if there is none.
+fn line_start(output: &str, line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    let starts: Vec<usize> = output.match_indices('\n').map(|(i, _)| i + 1).collect();
+    let lines = AnsiParser::lines_at_offsets(output, &starts);
+    starts
+        .into_iter()
+        .zip(lines)
+        .find(|&(_, at)| at >= line)
+        .map_or(output.len(), |(start, _)| start)
+}
+
+/// The characters of `line` in the display columns `from..to`
+///
+/// A wide character is taken if either of its cells is in range, and
+/// combining marks go with the character before them.
+fn column_text(line: &str, from: usize, to: usize) -> String {
+    let mut text = String::new();
+    let mut col = 0;
+    let mut taken = from == 0 && to > 0;
+    for ch in line.chars() {
+        let width = ch.width().unwrap_or(0);
+        if width > 0 {
+            if col >= to {
+                break;
+            }
+            taken = col + width > from;
+            col += width;
+        }
+        if taken {
+            text.push(ch);
+        }
+    }
+    text
+}
+
+/// Key bindings from the defaults overridden by `config`
+fn keybinding_manager(config: &Config) -> KeybindingManager {
+    let kb_config = &config.keybindings;
+    let mut kb = KeybindingManager::new();
+    kb.set_chord_timeout(Duration::from_millis(kb_config.chord_timeout_ms));
+    // Register custom keybindings from config
+    // These override the defaults loaded by KeybindingManager::new()
+    let configured = [
+        (&kb_config.new_tab, Action::NewTab),
+        (&kb_config.close_tab, Action::CloseTab),
+        (&kb_config.next_tab, Action::NextTab),
+        (&kb_config.prev_tab, Action::PrevTab),
+        (&kb_config.split_vertical, Action::SplitVertical),
+        (&kb_config.split_horizontal, Action::SplitHorizontal),
+        (&kb_config.copy, Action::Copy),
+        (&kb_config.paste, Action::Paste),
+        (&kb_config.search, Action::Search),
+        (&kb_config.clear, Action::Clear),
+        (&kb_config.save_session, Action::SaveSession),
+        (&kb_config.load_session, Action::LoadSession),
+        (&kb_config.next_theme, Action::NextTheme),
+        (&kb_config.prev_theme, Action::PrevTheme),
+        (
+            &kb_config.toggle_resource_monitor,
+            Action::ToggleResourceMonitor,
+        ),
+        (&kb_config.toggle_autocomplete, Action::ToggleAutocomplete),
+        (&kb_config.show_keybindings, Action::ShowKeybindings),
+        (&kb_config.command_palette, Action::OpenCommandPalette),
+        (&kb_config.interrupt, Action::Interrupt),
+        (&kb_config.restart_shell, Action::RestartShell),
+        (&kb_config.prev_command, Action::PrevCommand),
+        (&kb_config.next_command, Action::NextCommand),
+        (&kb_config.copy_rich, Action::CopyRich),
+        (&kb_config.grow_pane, Action::GrowPane),
+        (&kb_config.shrink_pane, Action::ShrinkPane),
+        (&kb_config.scroll_to_top, Action::ScrollToTop),
+        (&kb_config.scroll_to_bottom, Action::ScrollToBottom),
+        (&kb_config.toggle_stats_overlay, Action::ToggleStatsOverlay),
+    ];
+    for (combo, action) in configured {
+        if !combo.is_empty() {
+            let _ = kb.add_binding_from_string(combo, action);
+        }
+    }
+
+    // Register bindings for any other action by name
+    for (name, combo) in &kb_config.custom {
+        match name.parse::<Action>() {
+            Ok(action) => {
+                if let Err(e) = kb.add_binding_from_string(combo, action) {
+                    warn!("Ignoring keybinding '{}' for '{}': {}", combo, name, e);
                 }
             }
+            Err(e) => warn!("Ignoring custom keybinding: {}", e),
+        }
+    }
+
+    // Register custom Lua keybindings from hooks config
+    for (key_combo, lua_code) in &config.hooks.custom_keybindings {
+        let _ = kb.add_binding_from_string(key_combo, Action::ExecuteLua(lua_code.clone()));
+    }
+
+    kb
+}
+
+/// Session shown in each split pane
+///
+/// The focused pane always shows the active session, so switching tabs
+/// changes what it shows. Every other pane keeps its session unless that one
+/// was closed, is now the active one or is already shown, in which case it
+/// shows the next tab not on screen.
+fn pane_sessions(panes: &[usize], focused: usize, active: usize, count: usize) -> Vec<usize> {
+    let mut panes = panes.to_vec();
+    if let Some(pane) = panes.get_mut(focused) {
+        *pane = active;
+    }
+    for pane in 0..panes.len() {
+        let session = panes[pane];
+        let clash = session >= count || session == active || panes[..pane].contains(&session);
+        if pane == focused || !clash {
+            continue;
+        }
+        if let Some(next) = next_hidden_session(&panes, active, count) {
+            panes[pane] = next;
         }
     }
+    panes
+}
+
+/// The first of `count` tabs after `active`, wrapping around, that isn't in `shown`
+fn next_hidden_session(shown: &[usize], active: usize, count: usize) -> Option<usize> {
+    (1..count)
+        .map(|step| (active + step) % count)
+        .find(|session| !shown.contains(session))
+}
+
+/// PTY size as `(rows, cols)` for a pane drawn in `rect`, inside its border
+fn pane_pty_size(rect: Rect) -> (u16, u16) {
+    let inner = pane_block(false).inner(rect);
+    (inner.height.max(1), inner.width.max(1))
+}
+
+/// Border around a split pane, highlighted when it has keyboard focus
+fn pane_block(focused: bool) -> Block<'static> {
+    let color = if focused {
+        COLOR_COOL_RED
+    } else {
+        COLOR_STATUS_HINT
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Rgb(color.0, color.1, color.2)))
+}
+
+/// Replace the system clipboard's contents with `text`
+fn set_clipboard_text(text: String) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    clipboard
+        .set_text(text)
+        .context("Failed to set clipboard text")?;
+    Ok(())
 }
 
 /// Format bytes for display
@@ -3703,12 +6216,12 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
-/// Create a centered popup area with minimum size guarantees (for future UI features)
+/// Create a centered popup area with minimum size guarantees
 #[must_use]
-pub fn _centered_popup(parent: Rect, max_width: u16, max_height: u16) -> Rect {
+pub fn centered_popup(parent: Rect, max_width: u16, max_height: u16) -> Rect {
     // Enforce minimum size
-    let width = parent.width.min(max_width).max(_MIN_POPUP_WIDTH);
-    let height = parent.height.min(max_height).max(_MIN_POPUP_HEIGHT);
+    let width = parent.width.min(max_width).max(MIN_POPUP_WIDTH);
+    let height = parent.height.min(max_height).max(MIN_POPUP_HEIGHT);
 
     // If parent is too small, just use parent size
     let width = width.min(parent.width);
@@ -3726,6 +6239,7 @@ pub fn _centered_popup(parent: Rect, max_width: u16, max_height: u16) -> Rect {
 
 #[cfg(test)]
 mod tests {
+    use super::pane_tree::split_area;
     use super::*;
 
     #[test]
@@ -3785,67 +6299,1959 @@ mod tests {
     }
 
     #[test]
-    fn test_search_mode_toggle() {
-        let config = Config::default();
-        let mut terminal = Terminal::new(config).unwrap();
+    fn test_pane_pty_sizes_follow_ratio() {
+        let area = Rect::new(0, 0, 80, 24);
+        let sizes = |direction, ratio| split_area(area, direction, ratio).map(pane_pty_size);
+        // Each pane loses a cell to its border on every side
+        assert_eq!(sizes(SplitDirection::Vertical, 0.5), [(22, 38), (22, 38)]);
+        assert_eq!(sizes(SplitDirection::Vertical, 0.6), [(22, 46), (22, 30)]);
+        assert_eq!(sizes(SplitDirection::Horizontal, 0.25), [(4, 78), (16, 78)]);
+    }
+
+    #[test]
+    fn test_pane_sessions() {
+        // The focused pane follows the active session
+        assert_eq!(pane_sessions(&[0, 1], 0, 0, 3), [0, 1]);
+        assert_eq!(pane_sessions(&[0, 1], 1, 2, 3), [0, 2]);
+        assert_eq!(pane_sessions(&[0, 1], 0, 2, 3), [2, 1]);
+        // The other pane moves off the active session...
+        assert_eq!(pane_sessions(&[0, 1], 0, 1, 3), [1, 2]);
+        assert_eq!(pane_sessions(&[2, 1], 1, 2, 3), [0, 2]);
+        // ...and off a session that no longer exists
+        assert_eq!(pane_sessions(&[0, 2], 0, 0, 2), [0, 1]);
+        // With more panes, each shows a different tab
+        assert_eq!(pane_sessions(&[0, 1, 1], 0, 0, 4), [0, 1, 2]);
+        assert_eq!(pane_sessions(&[0, 1, 3], 2, 0, 4), [2, 1, 0]);
+        assert_eq!(next_hidden_session(&[2, 0, 1], 2, 3), None);
+    }
+
+    #[tokio::test]
+    async fn test_split_pane_focus_cycling() {
+        let mut config = Config::default();
+        config.shell.default_shell = if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string();
+        config.terminal.enable_split_pane = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.terminal_cols = 80;
+        terminal.terminal_rows = 25;
+        for _ in 0..3 {
+            terminal.create_new_tab().unwrap();
+        }
+        terminal.active_session = 1;
+
+        // Not split yet: no panes to focus, and PTYs use the whole terminal
+        assert!(!terminal.focus_pane(true));
+        assert_eq!(terminal.session_pty_size(1), (25, 80));
+
+        assert!(terminal.open_split(SplitDirection::Vertical));
+        assert_eq!(terminal.pane_sessions(), Some(vec![1, 2]));
+        assert_eq!(terminal.focused_pane, 0);
+
+        // Focus moves between the panes, taking input with it
+        assert!(terminal.focus_pane(true));
+        assert_eq!((terminal.focused_pane, terminal.active_session), (1, 2));
+        assert!(terminal.focus_pane(true));
+        assert_eq!((terminal.focused_pane, terminal.active_session), (0, 1));
+        assert!(terminal.focus_pane(false));
+        assert_eq!((terminal.focused_pane, terminal.active_session), (1, 2));
+
+        // Switching tabs changes the focused pane only
+        terminal.next_tab();
+        assert_eq!(terminal.pane_sessions(), Some(vec![1, 0]));
+
+        // Each pane's PTY fits inside its border; tab 2 is hidden
+        assert_eq!(terminal.session_pty_size(1), (22, 38));
+        assert_eq!(terminal.session_pty_size(0), (22, 38));
+        assert_eq!(terminal.session_pty_size(2), (25, 80));
+
+        // Growing the focused right pane moves the split left...
+        assert!(terminal.resize_focused_pane(SPLIT_RATIO_STEP));
+        assert_eq!(terminal.session_pty_size(1), (22, 34));
+        assert_eq!(terminal.session_pty_size(0), (22, 42));
+
+        // Splitting the focused pane again shows the hidden tab below it
+        assert!(terminal.open_split(SplitDirection::Horizontal));
+        assert_eq!(terminal.pane_sessions(), Some(vec![1, 0, 2]));
+        assert_eq!(terminal.session_pty_size(1), (22, 34));
+        assert_eq!(terminal.session_pty_size(0), (10, 42));
+        assert_eq!(terminal.session_pty_size(2), (10, 42));
+        // Every tab is on screen now
+        assert!(!terminal.open_split(SplitDirection::Vertical));
+
+        // Only the focused pane's own split moves
+        assert!(terminal.resize_focused_pane(SPLIT_RATIO_STEP));
+        assert_eq!(terminal.session_pty_size(0), (11, 42));
+        assert_eq!(terminal.session_pty_size(2), (9, 42));
+        assert_eq!(terminal.session_pty_size(1), (22, 34));
+
+        // Focus cycles through all three panes
+        assert!(terminal.focus_pane(true));
+        assert_eq!((terminal.focused_pane, terminal.active_session), (2, 2));
+        assert!(terminal.focus_pane(true));
+        assert_eq!((terminal.focused_pane, terminal.active_session), (0, 1));
+
+        // Closing a tab closes its pane
+        terminal.close_tab_at(2);
+        assert_eq!(terminal.pane_sessions(), Some(vec![1, 0]));
+        assert_eq!(terminal.session_pty_size(0), (22, 42));
+    }
+
+    #[test]
+    fn test_search_mode_toggle() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        assert!(!terminal.search_mode);
+        terminal.toggle_search_mode();
+        assert!(terminal.search_mode);
+        assert!(terminal.search_query.is_empty());
+        assert!(terminal.search_results.is_empty());
+
+        terminal.toggle_search_mode();
+        assert!(!terminal.search_mode);
+    }
+
+    #[test]
+    fn test_execute_search_empty_query() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.search_query.clear();
+        terminal.execute_search();
+        assert!(terminal.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_execute_search_with_matches() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        // Terminal starts with no sessions/buffers, so push one
+        terminal.tabs.push(Tab {
+            output: b"hello world\nfoo bar\nhello again\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "hello".to_string();
+        terminal.execute_search();
+
+        assert_eq!(terminal.search_results.len(), 2);
+        assert_eq!(terminal.search_results[0], 0); // First line
+        assert_eq!(terminal.search_results[1], 2); // Third line
+    }
+
+    #[test]
+    fn test_execute_search_case_insensitive() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"Hello World\nHELLO AGAIN\nhello small\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "hello".to_string();
+        terminal.execute_search();
+
+        assert_eq!(terminal.search_results.len(), 3);
+    }
+
+    #[test]
+    fn test_execute_search_no_matches() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"hello world\nfoo bar\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "zzz".to_string();
+        terminal.execute_search();
+
+        assert!(terminal.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_execute_search_regex() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"error 42\nwarning\nerror: none\nerr 7\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = r"err(or)? \d+".to_string();
+
+        // Literal mode treats the query as plain text
+        terminal.execute_search();
+        assert!(terminal.search_results.is_empty());
+
+        terminal.toggle_search_regex();
+        assert!(terminal.search_options.regex);
+        assert_eq!(terminal.search_results, vec![0, 3]);
+        assert!(terminal.search_prompt().contains("[.*]"));
+    }
+
+    #[test]
+    fn test_execute_search_invalid_regex_falls_back_to_literal() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"call(foo\nbar\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_options.regex = true;
+        terminal.search_query = "call(".to_string();
+        terminal.execute_search();
+
+        assert_eq!(terminal.search_results, vec![0]);
+        assert!(terminal
+            .notifications
+            .latest()
+            .is_some_and(|msg| msg.starts_with("Invalid regex")));
+    }
+
+    #[test]
+    fn test_execute_search_ignores_ansi_escapes() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"\x1b[31mred\x1b[0m text\nplain\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "red text".to_string();
+        terminal.execute_search();
+
+        assert_eq!(terminal.search_results, vec![0]);
+    }
+
+    #[test]
+    fn test_search_case_and_whole_word_toggles() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"Error: boom\nerror code\nterrors\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "error".to_string();
+        terminal.execute_search();
+        assert_eq!(terminal.search_results, vec![0, 1, 2]);
+
+        terminal.toggle_search_case_sensitive();
+        assert_eq!(terminal.search_results, vec![1, 2]);
+        assert!(terminal.search_prompt().contains("[Aa]"));
+
+        terminal.toggle_search_whole_word();
+        assert_eq!(terminal.search_results, vec![1]);
+        assert!(terminal.search_prompt().contains("[Aa W]"));
+
+        terminal.toggle_search_case_sensitive();
+        assert_eq!(terminal.search_results, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_search_navigation_after_toggle_shrinks_results() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.tabs.push(Tab {
+            output: b"cat\nconcat\ncat food\ncats\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.search_query = "cat".to_string();
+        terminal.execute_search();
+        assert_eq!(terminal.search_results, vec![0, 1, 2, 3]);
+
+        // Select the last match, which disappears with whole-word matching
+        terminal.search_prev();
+        assert_eq!(terminal.current_search_result, 3);
+        terminal.toggle_search_whole_word();
+        assert_eq!(terminal.search_results, vec![0, 2]);
+        assert_eq!(terminal.current_search_result, 0);
+
+        // Navigation wraps within the new result count
+        terminal.search_next();
+        assert_eq!(terminal.current_search_result, 1);
+        terminal.search_next();
+        assert_eq!(terminal.current_search_result, 0);
+
+        // A selected match that survives the toggle stays selected
+        terminal.search_next();
+        terminal.toggle_search_whole_word();
+        assert_eq!(terminal.search_results, vec![0, 1, 2, 3]);
+        assert_eq!(terminal.search_results[terminal.current_search_result], 2);
+    }
+
+    #[test]
+    fn test_centered_scroll_offset() {
+        // 100 lines, 10-row viewport: tail view starts at line 90
+        assert_eq!(centered_scroll_offset(50, 100, 10), 45); // top = 45
+        assert_eq!(centered_scroll_offset(95, 100, 10), 0); // near the end: tail view
+        assert_eq!(centered_scroll_offset(99, 100, 10), 0);
+        assert_eq!(centered_scroll_offset(2, 100, 10), 90); // near the start: top view
+        assert_eq!(centered_scroll_offset(0, 100, 10), 90);
+        // Output shorter than the viewport never scrolls
+        assert_eq!(centered_scroll_offset(3, 5, 10), 0);
+        assert_eq!(centered_scroll_offset(0, 0, 10), 0);
+    }
+
+    #[test]
+    fn test_search_navigation_scrolls_to_match() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.terminal_rows = 11; // 10 content rows + status bar
+
+        let output: String = (0..100)
+            .map(|i| if i % 40 == 10 { format!("match {i}\n") } else { format!("line {i}\n") })
+            .collect();
+        terminal.tabs.push(Tab {
+            output: output.into_bytes(),
+            ..Tab::new()
+        });
+
+        terminal.toggle_search_mode();
+        terminal.search_query = "match".to_string();
+        terminal.execute_search();
+        assert_eq!(terminal.search_results, vec![10, 50, 90]);
+
+        // The first match is brought into view immediately
+        let total = terminal.active_line_count();
+        let offset = terminal.scroll_offset();
+        let top = total - terminal.viewport_rows() - offset;
+        assert!((top..top + terminal.viewport_rows()).contains(&10));
+
+        terminal.search_next();
+        let offset = terminal.scroll_offset();
+        let top = total - terminal.viewport_rows() - offset;
+        assert_eq!(top, 45); // line 50 centered
+
+        // Exiting search returns to tail-follow mode
+        terminal.toggle_search_mode();
+        assert_eq!(terminal.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_clamp_scroll_offset() {
+        assert_eq!(clamp_scroll_offset(5, 100, 10), 5);
+        assert_eq!(clamp_scroll_offset(95, 100, 10), 90);
+        assert_eq!(clamp_scroll_offset(3, 8, 10), 0);
+        assert_eq!(clamp_scroll_offset(0, 0, 10), 0);
+    }
+
+    #[test]
+    fn test_osc52_clipboard_write_gated() {
+        let output = "\x1b]52;c;ZWNobyBoaQ==\x07$ ";
+
+        // Blocked by default
+        let terminal = Terminal::new(Config::default()).unwrap();
+        assert_eq!(terminal.osc52_clipboard_text(output), None);
+
+        let mut config = Config::default();
+        config.terminal.allow_osc52 = true;
+        let terminal = Terminal::new(config).unwrap();
+        assert_eq!(
+            terminal.osc52_clipboard_text(output).as_deref(),
+            Some("echo hi")
+        );
+        assert_eq!(terminal.osc52_clipboard_text("\x1b]52;c;!!\x07"), None);
+    }
+
+    #[test]
+    fn test_block_selection_positions() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        // Dragged up and to the left: corners are normalized
+        terminal.selection_start = Some((6, 4));
+        terminal.selection_end = Some((2, 1));
+
+        // Linear: rows in between are selected end to end
+        assert!(terminal.is_position_selected(0, 2));
+        assert!(terminal.is_position_selected(40, 3));
+
+        terminal.selection_block = true;
+        for row in 1..=4 {
+            assert!(terminal.is_position_selected(2, row));
+            assert!(terminal.is_position_selected(6, row));
+            assert!(!terminal.is_position_selected(1, row));
+            assert!(!terminal.is_position_selected(7, row));
+        }
+        assert!(!terminal.is_position_selected(4, 0));
+        assert!(!terminal.is_position_selected(4, 5));
+    }
+
+    #[test]
+    fn test_block_selection_text() {
+        let lines = [
+            "drwxr-xr-x  src",
+            "-rw-r--r--  Cargo.toml",
+            "",
+            "-rw",
+            "café au lait",
+        ];
+        // Columns 2..=5 of every row, clipping rows that end early
+        assert_eq!(
+            block_selection_text(&lines, (2, 0), (5, 4)),
+            "wxr-\nw-r-\n\nw\nfé a"
+        );
+        // Corners in either order give the same block
+        assert_eq!(block_selection_text(&lines, (5, 1), (2, 0)), "wxr-\nw-r-");
+        // Rows past the end of the output are empty
+        assert_eq!(block_selection_text(&lines, (0, 4), (3, 5)), "café\n");
+    }
+
+    #[test]
+    fn test_pop_grapheme_removes_whole_clusters() {
+        let pop = |text: &[u8]| {
+            let mut buffer = text.to_vec();
+            pop_grapheme(&mut buffer);
+            buffer
+        };
+
+        // A flag is two regional indicators
+        assert_eq!(pop("ls 🇯🇵".as_bytes()), b"ls ");
+        // e with a combining acute accent, then a decomposed ñ
+        assert_eq!(pop("cafe\u{301}".as_bytes()), b"caf");
+        assert_eq!(pop("an\u{303}".as_bytes()), b"a");
+        // Family emoji joined with ZWJ
+        assert_eq!(pop("hi 👨\u{200d}👩\u{200d}👧".as_bytes()), b"hi ");
+        assert_eq!(pop("中文".as_bytes()), "中".as_bytes());
+        assert_eq!(pop(b"ab"), b"a");
+        assert_eq!(pop(b""), b"");
+        // Not UTF-8: the last code point's bytes go
+        assert_eq!(pop(b"\xFFa\xC3\xA9"), b"\xFFa");
+    }
+
+    #[test]
+    fn test_column_text_counts_display_cells() {
+        // Each of 中 and 文 takes two cells
+        assert_eq!(column_text("a中文b", 0, 3), "a中");
+        assert_eq!(column_text("a中文b", 1, 2), "中");
+        // A range starting on the second cell of a wide character takes it
+        assert_eq!(column_text("a中文b", 2, 4), "中文");
+        assert_eq!(column_text("a中文b", 5, 6), "b");
+        // Combining marks stay with their base character
+        assert_eq!(column_text("e\u{301}x", 0, 1), "e\u{301}");
+        assert_eq!(column_text("e\u{301}x", 1, 2), "x");
+
+        let lines = ["日本語テキスト", "ab"];
+        assert_eq!(block_selection_text(&lines, (2, 0), (5, 1)), "本語\n");
+    }
+
+    #[test]
+    fn test_selection_spans_follow_display_cells() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        let lines = vec![
+            Line::from("a中文b"),
+            Line::from(vec![Span::raw("ne"), Span::raw("\u{301}e")]),
+        ];
+        let selected = |terminal: &Terminal, row: usize| -> Vec<(String, bool)> {
+            terminal.selected_spans(&lines)[row]
+                .iter()
+                .map(|(span, selected)| (span.content.to_string(), *selected))
+                .collect()
+        };
+
+        // Cells 3..=4 are the second half of 中 and all of 文
+        terminal.selection_start = Some((2, 0));
+        terminal.selection_end = Some((4, 0));
+        assert_eq!(
+            selected(&terminal, 0),
+            [
+                ("a".to_string(), false),
+                ("中文".to_string(), true),
+                ("b".to_string(), false)
+            ]
+        );
+
+        // A combining mark in the next span is selected with its base
+        terminal.selection_start = Some((1, 1));
+        terminal.selection_end = Some((1, 1));
+        assert_eq!(
+            selected(&terminal, 1),
+            [
+                ("n".to_string(), false),
+                ("e".to_string(), true),
+                ("\u{301}".to_string(), true),
+                ("e".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selected_text_uses_display_columns() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab {
+            output: "$ 中文 cafe\u{301}\nok\n".as_bytes().to_vec(),
+            ..Tab::new()
+        });
+        // From 文 to the end of the first line
+        let text = terminal.get_selected_text((4, 0), (20, 0)).unwrap();
+        assert_eq!(text, "文 cafe\u{301}");
+        // Through the combining mark on column 10 and onto the next line
+        let text = terminal.get_selected_text((10, 0), (1, 1)).unwrap();
+        assert_eq!(text, "e\u{301}\no");
+    }
+
+    #[test]
+    fn test_cursor_follows_wide_and_combining_echo() {
+        use ratatui::backend::TestBackend;
+
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab {
+            output: b"$ ".to_vec(),
+            // Typed but not yet echoed: two wide characters and an accented e
+            command: "中文e\u{301}".as_bytes().to_vec(),
+            ..Tab::new()
+        });
+
+        let mut backend = RatatuiTerminal::new(TestBackend::new(40, 10)).unwrap();
+        backend
+            .draw(|f| terminal.render_terminal_output(f, Rect::new(0, 1, 40, 8)))
+            .unwrap();
+        // "$ " is 2 cells, 中文 4 and e\u{301} 1
+        assert_eq!(backend.get_cursor().unwrap(), (7, 1));
+    }
+
+    #[test]
+    fn test_selection_html_keeps_colors() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        let bold_orange = Style::default()
+            .fg(Color::Rgb(0xff, 0xaa, 0x00))
+            .add_modifier(Modifier::BOLD);
+        terminal.tabs.push(Tab {
+            styled_lines: vec![
+                Line::from(vec![
+                    Span::styled("error", Style::default().fg(Color::Rgb(0xff, 0x00, 0x00))),
+                    Span::raw(": missing"),
+                ]),
+                Line::from(Span::styled("warning", bold_orange)),
+            ],
+            ..Tab::new()
+        });
+        assert_eq!(terminal.selection_html(), None);
+
+        // From "ror" on the first row through "warn" on the second
+        terminal.selection_start = Some((2, 0));
+        terminal.selection_end = Some((3, 1));
+        let (html, text) = terminal.selection_html().unwrap();
+        assert_eq!(text, "ror: missing\nwarn");
+        assert!(html.contains("<span style=\"color:#FF0000\">ror</span>: missing\n"));
+        assert!(html.contains("<span style=\"color:#FFAA00;font-weight:bold\">warn</span></pre>"));
+    }
+
+    #[test]
+    fn test_command_jump_line() {
+        let boundaries = [3, 20, 45];
+        assert_eq!(command_jump_line(&boundaries, 30, false), Some(20));
+        assert_eq!(command_jump_line(&boundaries, 30, true), Some(45));
+        // A boundary at the top is the current command, not a target
+        assert_eq!(command_jump_line(&boundaries, 20, false), Some(3));
+        assert_eq!(command_jump_line(&boundaries, 20, true), Some(45));
+        // Stop at the ends instead of wrapping
+        assert_eq!(command_jump_line(&boundaries, 3, false), None);
+        assert_eq!(command_jump_line(&boundaries, 45, true), None);
+        assert_eq!(command_jump_line(&boundaries, 0, false), None);
+        assert_eq!(command_jump_line(&[], 10, true), None);
+    }
+
+    #[test]
+    fn test_top_aligned_scroll_offset() {
+        // 100 lines, 10-row viewport: tail view starts at line 90
+        assert_eq!(top_aligned_scroll_offset(20, 100, 10), 70); // top = 20
+        assert_eq!(top_aligned_scroll_offset(0, 100, 10), 90);
+        assert_eq!(top_aligned_scroll_offset(95, 100, 10), 0); // near the end: tail view
+        assert_eq!(top_aligned_scroll_offset(2, 5, 10), 0);
+    }
+
+    #[test]
+    fn test_jump_between_commands() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.terminal_rows = 6; // 5 content rows + status bar
+        terminal.tabs.push(Tab::new());
+
+        // Three commands of five lines each, then a prompt: 16 lines with
+        // output starting on lines 1, 6 and 11
+        for name in ["a", "b", "c"] {
+            let output: String = (0..4).map(|i| format!("{name}{i}\r\n")).collect();
+            terminal.process_shell_output_chunk(
+                0,
+                format!("\x1b]133;A\x07$ {name}\r\n\x1b]133;C\x07{output}").as_bytes(),
+            );
+        }
+        terminal.process_shell_output_chunk(0, b"\x1b]133;A\x07$ ");
+
+        terminal.jump_to_command(false);
+        assert_eq!(terminal.scroll_offset(), 5); // top = 6
+        terminal.jump_to_command(false);
+        assert_eq!(terminal.scroll_offset(), 10); // top = 1
+        terminal.jump_to_command(false);
+        assert_eq!(terminal.scroll_offset(), 10);
+        assert_eq!(terminal.notifications.latest(), Some("No earlier command"));
+
+        terminal.jump_to_command(true);
+        assert_eq!(terminal.scroll_offset(), 5);
+        terminal.jump_to_command(true);
+        assert_eq!(terminal.scroll_offset(), 0);
+        terminal.jump_to_command(true);
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert_eq!(terminal.notifications.latest(), Some("No later command"));
+    }
+
+    #[test]
+    fn test_jump_to_submitted_commands_without_marks() {
+        let config = Config::default();
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.terminal_rows = 4; // 3 content rows + status bar
+        terminal.tabs.push(Tab::new());
+
+        for name in ["a", "b"] {
+            terminal.process_shell_output_chunk(0, format!("$ {name}").as_bytes());
+            let len = terminal.tabs[0].output.len();
+            terminal.tabs[0].prompt_marks.record_submitted(len);
+            terminal.process_shell_output_chunk(0, b"\r\n1\r\n2\r\n3\r\n");
+        }
+        terminal.process_shell_output_chunk(0, b"$ ");
+
+        // 9 lines; output starts on lines 1 and 5
+        terminal.jump_to_command(false);
+        assert_eq!(terminal.scroll_offset(), 1); // top = 5
+        terminal.jump_to_command(false);
+        assert_eq!(terminal.scroll_offset(), 5); // top = 1
+    }
+
+    #[test]
+    fn test_command_output_drops_echo_and_prompt() {
+        let output = "$ printf 'a\\nb\\n'\r\n\x1b[1ma\x1b[0m\r\nb\r\n$ ";
+        assert_eq!(command_output(output, true), "a\nb");
+        // Nothing after the echo, e.g. the command ended the shell
+        assert_eq!(command_output("$ exit 3\r\n", false), "");
+        assert_eq!(command_output("$ sleep 5", false), "");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_reports_output_and_status() {
+        let mut config = Config::default();
+        config.shell.default_shell = "sh".to_string();
+        let timeout = Duration::from_secs(10);
+
+        // No end mark, so the status comes from exiting the shell
+        let mut terminal = Terminal::new(config.clone()).unwrap();
+        let result = terminal
+            .execute("echo one; echo two; false", timeout)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            CommandResult {
+                output: "one\ntwo".to_string(),
+                status: Some(1),
+            }
+        );
+
+        // An OSC 133 end mark gives the status without exiting
+        let mut terminal = Terminal::new(config.clone()).unwrap();
+        let result = terminal
+            .execute("printf 'marked\\n\\033]133;D;4\\007'", timeout)
+            .await
+            .unwrap();
+        assert_eq!(result.output, "marked");
+        assert_eq!(result.status, Some(4));
+        assert!(terminal.sessions[0].try_wait().is_none());
+
+        // The command can end the shell itself
+        let mut terminal = Terminal::new(config).unwrap();
+        let result = terminal.execute("echo bye; exit 3", timeout).await.unwrap();
+        assert_eq!(result.output, "bye");
+        assert_eq!(result.status, Some(3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_gives_up_at_timeout() {
+        let mut config = Config::default();
+        config.shell.default_shell = "sh".to_string();
+        let mut terminal = Terminal::new(config).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = terminal
+            .execute("echo waiting; sleep 5", Duration::from_millis(500))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(result.output, "waiting");
+        assert_eq!(result.status, None);
+    }
+
+    #[tokio::test]
+    async fn test_restart_exited_shell_keeps_output() {
+        let mut config = Config::default();
+        config.shell.default_shell = if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string();
+        config.shell.close_on_exit = false;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.create_new_tab().unwrap();
+        terminal.tabs[0].output.extend_from_slice(b"old output\r\n");
+
+        // A running shell is left alone
+        let old_pid = terminal.sessions[0].pid();
+        terminal.restart_shell().await;
+        assert_eq!(terminal.sessions[0].pid(), old_pid);
+        assert_eq!(
+            terminal.notifications.latest(),
+            Some("Shell is still running")
+        );
+
+        let exit = if cfg!(windows) {
+            "exit 1\r\n"
+        } else {
+            "exit 1\n"
+        };
+        terminal.sessions[0]
+            .write_input(exit.as_bytes())
+            .await
+            .unwrap();
+        for _ in 0..100 {
+            terminal.handle_exited_sessions();
+            if terminal.active_session_exited() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(terminal.active_session_exited());
+        assert!(terminal
+            .notifications
+            .latest()
+            .is_some_and(|message| message.ends_with("to restart it")));
+
+        terminal.restart_shell().await;
+        assert!(!terminal.active_session_exited());
+        assert!(terminal.sessions[0].try_wait().is_none());
+        assert_ne!(terminal.sessions[0].pid(), old_pid);
+        let output = String::from_utf8_lossy(&terminal.tabs[0].output);
+        assert!(output.starts_with("old output\r\n"));
+        assert!(output.ends_with("[process exited]\r\n\r\n--- shell restarted ---\r\n"));
+    }
+
+    #[test]
+    fn test_exit_action_closes_tab_when_others_remain() {
+        let shell = crate::config::ShellConfig::default();
+        assert_eq!(exit_action(&shell, 2), ExitAction::CloseTab);
+        assert_eq!(exit_action(&shell, 5), ExitAction::CloseTab);
+    }
+
+    #[test]
+    fn test_exit_action_last_tab() {
+        let mut shell = crate::config::ShellConfig::default();
+        assert_eq!(exit_action(&shell, 1), ExitAction::Quit);
+
+        shell.quit_on_last_exit = false;
+        assert_eq!(exit_action(&shell, 1), ExitAction::KeepTab);
+    }
+
+    #[test]
+    fn test_exit_action_keeps_tabs_without_close_on_exit() {
+        let mut shell = crate::config::ShellConfig::default();
+        shell.close_on_exit = false;
+        assert_eq!(exit_action(&shell, 1), ExitAction::KeepTab);
+        assert_eq!(exit_action(&shell, 3), ExitAction::KeepTab);
+    }
+
+    /// Terminal with one session holding `lines` lines of output and a 10-row viewport
+    fn terminal_with_output(lines: usize) -> Terminal {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.terminal_rows = 11;
+        let output: String = (0..lines).map(|i| format!("line {i}\n")).collect();
+        terminal.tabs.push(Tab {
+            output: output.into_bytes(),
+            ..Tab::new()
+        });
+        terminal
+    }
+
+    #[test]
+    fn test_render_to_lines_shows_notification() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.show_notification("Build finished".to_string());
+
+        let lines = terminal.render_to_lines(60, 12);
+
+        assert_eq!(lines.len(), 12);
+        assert!(lines.iter().all(|line| line.chars().count() <= 60));
+        // The notification takes the top row when there is no tab bar
+        assert_eq!(lines[0], "Build finished");
+    }
+
+    #[test]
+    fn test_notifications_stack_and_expire_over_frames() {
+        let mut config = Config::default();
+        config.terminal.max_fps = 2;
+        config.ui.notification_duration_secs = 2;
+        let mut terminal = Terminal::new(config).unwrap();
+
+        terminal.show_notification("Build finished".to_string());
+        terminal.advance_frame();
+        terminal.show_notification("Copied to clipboard".to_string());
+
+        // Newest on top, the output moved down below both
+        let lines = terminal.render_to_lines(60, 12);
+        assert_eq!(lines[0], "Copied to clipboard");
+        assert_eq!(lines[1], "Build finished");
+
+        // Four frames each at 2 FPS: the first leaves one frame earlier
+        for _ in 0..3 {
+            terminal.dirty = true;
+            terminal.advance_frame();
+        }
+        assert_eq!(
+            terminal.notifications.messages().collect::<Vec<_>>(),
+            ["Copied to clipboard"]
+        );
+        terminal.dirty = true;
+        terminal.advance_frame();
+        assert!(terminal.notifications.is_empty());
+
+        // Frames that draw nothing don't count
+        terminal.show_notification("Saved".to_string());
+        terminal.dirty = false;
+        for _ in 0..10 {
+            terminal.advance_frame();
+        }
+        assert_eq!(terminal.notifications.latest(), Some("Saved"));
+    }
+
+    #[tokio::test]
+    async fn test_chord_prefix_runs_its_own_action_after_timeout() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        // Ctrl+F is also the default Search binding
+        terminal
+            .keybindings
+            .borrow_mut()
+            .add_binding_from_string("Ctrl+F n", Action::SearchNext)
+            .unwrap();
+        terminal
+            .handle_key_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL))
+            .await
+            .unwrap();
+        let pressed = std::time::Instant::now();
+        assert!(!terminal.search_mode);
+
+        terminal.run_expired_chord(pressed).await.unwrap();
+        assert!(!terminal.search_mode);
+
+        let timeout = Duration::from_millis(terminal.config.keybindings.chord_timeout_ms);
+        terminal.run_expired_chord(pressed + timeout).await.unwrap();
+        assert!(terminal.search_mode);
+        // The action runs once
+        terminal.run_expired_chord(pressed + timeout).await.unwrap();
+        assert!(terminal.search_mode);
+    }
+
+    #[test]
+    fn test_stats_overlay_shows_frames_and_buffers() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab {
+            output: vec![b'x'; 2048],
+            ..Tab::new()
+        });
+        // Drawn long enough ago for the overlay to want a fresh frame
+        let start = std::time::Instant::now()
+            .checked_sub(frame_pacer::STATS_REFRESH * 2)
+            .unwrap();
+        for i in 0..3 {
+            terminal
+                .frame_stats
+                .record(start + Duration::from_millis(i), Duration::from_millis(4));
+        }
+        assert!(!terminal
+            .render_to_lines(80, 24)
+            .iter()
+            .any(|line| line.contains("Stats")));
+
+        terminal.show_stats = true;
+        let lines = terminal.render_to_lines(80, 24);
+        let shown = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(shown("Stats"));
+        assert!(shown("Frames  3"));
+        assert!(shown("Render  4.00 ms (avg 4.00)"));
+        assert!(shown("Tab 1   2.0 KiB"));
+
+        // An idle screen is still drawn now and then to keep the numbers current
+        terminal.dirty = false;
+        terminal.advance_frame();
+        assert!(terminal.dirty);
+    }
+
+    #[test]
+    fn test_keybindings_overlay_lists_bindings() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.show_keybindings = true;
+
+        let lines = terminal.render_to_lines(80, 40);
+
+        assert!(lines.iter().any(|line| line.contains("Keybindings (Esc to close)")));
+        let new_tab = lines
+            .iter()
+            .find(|line| line.contains("Ctrl+T "))
+            .expect("Ctrl+T listed");
+        assert!(new_tab.contains("New tab"));
+    }
+
+    #[test]
+    fn test_multiline_paste_waits_for_confirmation() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+
+        // Single-line pastes go straight through
+        assert_eq!(terminal.request_paste("ls".into()).as_deref(), Some("ls"));
+        assert!(terminal.pending_paste.is_none());
+
+        assert_eq!(terminal.request_paste("make\nmake install\n".into()), None);
+        let lines = terminal.render_to_lines(80, 24);
+        let shown = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(shown("Paste these lines? (y/n)"));
+        assert!(shown(" make install "));
+        assert!(shown("PASTE 2 LINES? (y/n)"));
+
+        assert_eq!(
+            terminal.resolve_paste(true).as_deref(),
+            Some("make\nmake install\n")
+        );
+        assert!(terminal.pending_paste.is_none());
+
+        assert_eq!(terminal.request_paste("rm -rf /\n".into()), None);
+        assert_eq!(terminal.resolve_paste(false), None);
+        assert!(terminal.pending_paste.is_none());
+        assert_eq!(terminal.resolve_paste(true), None);
+
+        terminal.config.terminal.confirm_multiline_paste = false;
+        assert_eq!(
+            terminal.request_paste("a\nb\n".into()).as_deref(),
+            Some("a\nb\n")
+        );
+    }
+
+    #[test]
+    fn test_paste_is_bracketed_while_the_shell_asks_for_it() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.config.terminal.confirm_multiline_paste = false;
+        terminal.tabs.push(Tab::new());
+
+        assert_eq!(terminal.request_paste("ls".into()).as_deref(), Some("ls"));
+
+        terminal.process_shell_output_chunk(0, b"\x1b[?2004h$ ");
+        assert_eq!(
+            terminal.request_paste("ls\n".into()).as_deref(),
+            Some("\x1b[200~ls\n\x1b[201~")
+        );
+
+        // Turned off in config: plain text even though the shell asked
+        terminal.config.terminal.bracketed_paste = false;
+        assert_eq!(terminal.request_paste("ls".into()).as_deref(), Some("ls"));
+        terminal.config.terminal.bracketed_paste = true;
+
+        // Confirmed pastes are bracketed too
+        terminal.config.terminal.confirm_multiline_paste = true;
+        assert_eq!(terminal.request_paste("a\nb\n".into()), None);
+        assert_eq!(
+            terminal.resolve_paste(true).as_deref(),
+            Some("\x1b[200~a\nb\n\x1b[201~")
+        );
+
+        // The shell turns it off while a command runs
+        terminal.process_shell_output_chunk(0, b"\x1b[?2004l\r\n");
+        assert_eq!(terminal.request_paste("y".into()).as_deref(), Some("y"));
+    }
+
+    #[test]
+    fn test_render_to_lines_layout_without_sessions() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+
+        let lines = terminal.render_to_lines(40, 8);
+
+        assert_eq!(lines.len(), 8);
+        // Placeholder prompt at the top, status bar on the last row
+        assert_eq!(lines[0], ">");
+        assert!(lines[7].starts_with(" NORMAL  Session 1"));
+        assert!(lines[1..7].iter().all(String::is_empty));
+    }
+
+    #[tokio::test]
+    async fn test_tab_bar_and_window_show_titles() {
+        let mut config = Config::default();
+        config.shell.default_shell = if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string();
+        config.terminal.enable_tabs = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.create_new_tab().unwrap();
+        terminal.create_new_tab().unwrap();
+        let tab_bar = |terminal: &mut Terminal| terminal.render_to_lines(60, 10).remove(0);
+
+        // The second tab is active and gets the titles
+        terminal.process_shell_output_chunk(1, b"\x1b]2;me@host: ~/src\x07\x1b]1;src\x07$ ");
+        let bar = tab_bar(&mut terminal);
+        assert!(bar.contains(" Tab 1 ") && bar.ends_with(" src ×"), "{bar}");
+        assert_eq!(
+            terminal.window_title_change().as_deref(),
+            Some("me@host: ~/src")
+        );
+        assert_eq!(terminal.window_title_change(), None);
+
+        // The window follows the active tab
+        terminal.prev_tab();
+        assert_eq!(
+            terminal.window_title_change().as_deref(),
+            Some(DEFAULT_WINDOW_TITLE)
+        );
+        terminal.next_tab();
+        assert_eq!(
+            terminal.window_title_change().as_deref(),
+            Some("me@host: ~/src")
+        );
+
+        // Empty titles bring the defaults back
+        terminal.process_shell_output_chunk(1, b"\x1b]0;\x07");
+        let bar = tab_bar(&mut terminal);
+        assert!(bar.ends_with(" Tab 2 ×"), "{bar}");
+        assert_eq!(
+            terminal.window_title_change().as_deref(),
+            Some(DEFAULT_WINDOW_TITLE)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tab_bar_shows_each_tabs_directory() {
+        let mut config = Config::default();
+        config.shell.default_shell = if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string();
+        config.terminal.enable_tabs = true;
+        config.terminal.tab_title_source = "cwd".to_string();
+        config.terminal.tab_title_max_width = 6;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.create_new_tab().unwrap();
+        terminal.create_new_tab().unwrap();
+        let tab_bar = |terminal: &mut Terminal| terminal.render_to_lines(60, 10).remove(0);
+
+        // Titles are ignored; tabs without a reported directory keep the default
+        terminal.process_shell_output_chunk(
+            1,
+            b"\x1b]0;vim\x07\x1b]7;file://host/home/me/projects\x07$ ",
+        );
+        let bar = tab_bar(&mut terminal);
+        assert!(
+            bar.contains(" Tab 1 ") && bar.ends_with(" proje… ×"),
+            "{bar}"
+        );
+
+        terminal.prev_tab();
+        terminal.process_shell_output_chunk(0, b"\x1b]7;file://host/tmp\x07$ ");
+        let bar = tab_bar(&mut terminal);
+        assert!(bar.contains(" tmp ") && bar.ends_with(" proje… ×"), "{bar}");
+    }
+
+    /// A terminal with `count` tabs shown in the tab bar, the last one active
+    fn terminal_with_tabs(count: usize) -> Terminal {
+        let mut config = Config::default();
+        config.shell.default_shell = if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string();
+        config.terminal.enable_tabs = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        for _ in 0..count {
+            terminal.create_new_tab().unwrap();
+        }
+        terminal
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_each_tab_gets_only_its_own_shells_output() {
+        let mut terminal = terminal_with_tabs(1);
+        let mut readers = OutputReaders::new(terminal.config.shell.read_buffer_size());
+        terminal.drain_shell_output(&mut readers);
+
+        // A new tab, like Ctrl+T, then each shell prints a different result
+        terminal.create_new_tab().unwrap();
+        assert_eq!(terminal.active_session, 1);
+        for (index, session) in terminal.sessions.iter().enumerate() {
+            let command = format!("echo tab-$(({index} + 100))\r");
+            session.write_input(command.as_bytes()).await.unwrap();
+        }
+
+        // Read the new tab, then switch back with the keyboard and read the first
+        for expected in ["tab-101", "tab-100"] {
+            for _ in 0..250 {
+                terminal.drain_shell_output(&mut readers);
+                if String::from_utf8_lossy(&terminal.tabs[terminal.active_session].output)
+                    .contains(expected)
+                {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            terminal.next_tab();
+        }
+
+        let output = |index: usize| String::from_utf8_lossy(&terminal.tabs[index].output);
+        assert!(output(0).contains("tab-100"), "{}", output(0));
+        assert!(!output(0).contains("tab-101"));
+        assert!(output(1).contains("tab-101"), "{}", output(1));
+        assert!(!output(1).contains("tab-100"));
+
+        // Closing a tab stops its reader
+        terminal.close_tab_at(1);
+        terminal.drain_shell_output(&mut readers);
+        assert_eq!(readers.len(), 1);
+    }
+
+    async fn click(terminal: &mut Terminal, kind: crossterm::event::MouseEventKind, column: u16) {
+        terminal
+            .handle_mouse_event(MouseEvent {
+                kind,
+                column,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_move_tab_keeps_per_tab_state_together() {
+        let mut terminal = terminal_with_tabs(3);
+        let pids: Vec<Option<u32>> = terminal.sessions.iter().map(ShellSession::pid).collect();
+        for i in 0..3 {
+            terminal.tabs[i].output = format!("out {i}").into_bytes();
+            terminal.tabs[i].command = format!("cmd {i}").into_bytes();
+            terminal.tabs[i].styled_len = i;
+            terminal.tabs[i].scroll_offset = i;
+            terminal.tabs[i].exited = i == 1;
+            terminal.tabs[i].titles.set_dir(format!("/tab{i}"));
+        }
+        terminal.select_tab(1);
+
+        // Which original tab each position holds, checked against every per-tab list
+        let assert_order = |terminal: &Terminal, order: [usize; 3]| {
+            for (position, &i) in order.iter().enumerate() {
+                assert_eq!(terminal.sessions[position].pid(), pids[i]);
+                assert_eq!(
+                    terminal.tabs[position].output,
+                    format!("out {i}").into_bytes()
+                );
+                assert_eq!(
+                    terminal.tabs[position].command,
+                    format!("cmd {i}").into_bytes()
+                );
+                assert_eq!(terminal.tabs[position].styled_len, i);
+                assert_eq!(terminal.tabs[position].scroll_offset, i);
+                assert_eq!(terminal.tabs[position].exited, i == 1);
+                let label =
+                    terminal.tabs[position]
+                        .titles
+                        .tab_label(title::TabTitleSource::Cwd, None, 24);
+                assert_eq!(label, Some(format!("tab{i}")));
+            }
+            assert_eq!(terminal.tabs.len(), 3);
+            assert_eq!(terminal.tabs.len(), 3);
+            assert_eq!(terminal.tabs.len(), 3);
+        };
+
+        // The first tab moves to the end and the active one follows its session
+        terminal.move_tab(0, 2);
+        assert_order(&terminal, [1, 2, 0]);
+        assert_eq!(terminal.active_session, 0);
+
+        // The active tab itself moves
+        terminal.move_tab(0, 1);
+        assert_order(&terminal, [2, 1, 0]);
+        assert_eq!(terminal.active_session, 1);
+
+        terminal.move_tab(2, 0);
+        assert_order(&terminal, [0, 2, 1]);
+        assert_eq!(terminal.active_session, 2);
 
-        assert!(!terminal.search_mode);
-        terminal.toggle_search_mode();
-        assert!(terminal.search_mode);
-        assert!(terminal.search_query.is_empty());
-        assert!(terminal.search_results.is_empty());
+        // Out of range moves change nothing
+        terminal.move_tab(3, 0);
+        terminal.move_tab(0, 3);
+        assert_order(&terminal, [0, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_tab_bar_mouse_switches_drags_and_closes() {
+        use crossterm::event::{MouseButton, MouseEventKind};
+        let down = MouseEventKind::Down(MouseButton::Left);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        let up = MouseEventKind::Up(MouseButton::Left);
+
+        let mut terminal = terminal_with_tabs(3);
+        let pids: Vec<Option<u32>> = terminal.sessions.iter().map(ShellSession::pid).collect();
+        let bar = terminal.render_to_lines(60, 10).remove(0);
+        assert_eq!(bar, " Tab 1 × │ Tab 2 × │ Tab 3 ×");
+        let spans = terminal.tab_spans.clone();
+
+        // Clicking a label switches to its tab
+        click(&mut terminal, down, spans[0].start + 2).await;
+        assert_eq!(terminal.active_session, 0);
+        assert!(!terminal.selection_active);
+
+        // Dragging it over the last tab moves it there
+        click(&mut terminal, drag, spans[1].start + 1).await;
+        click(&mut terminal, drag, spans[2].start + 1).await;
+        click(&mut terminal, up, spans[2].start + 1).await;
+        assert_eq!(terminal.active_session, 2);
+        assert_eq!(terminal.sessions[2].pid(), pids[0]);
+        assert_eq!(terminal.sessions[0].pid(), pids[1]);
+        assert!(!terminal.selection_active);
+
+        // The close button closes its tab, which needn't be the active one
+        terminal.render_to_lines(60, 10);
+        let close = terminal.tab_spans[0].close_column();
+        click(&mut terminal, down, close).await;
+        assert_eq!(terminal.sessions.len(), 2);
+        assert_eq!(terminal.sessions[0].pid(), pids[2]);
+        assert_eq!(terminal.active_session, 1);
+
+        // Below the bar, clicks select text as before
+        terminal
+            .handle_mouse_event(MouseEvent {
+                kind: down,
+                column: 2,
+                row: 3,
+                modifiers: KeyModifiers::NONE,
+            })
+            .await
+            .unwrap();
+        assert!(terminal.selection_active);
+    }
+
+    #[test]
+    fn test_visual_bell_flashes_until_it_expires() {
+        use ratatui::backend::TestBackend;
+
+        let mut config = Config::default();
+        config.terminal.bell_style = "visual".to_string();
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+        let mut backend = RatatuiTerminal::new(TestBackend::new(20, 4)).unwrap();
+        let mut inverted = |terminal: &mut Terminal| {
+            backend.draw(|f| terminal.render(f)).unwrap();
+            let buffer = backend.backend().buffer();
+            buffer.get(0, 0).modifier.contains(Modifier::REVERSED)
+        };
+
+        // BEL ending a title doesn't ring
+        terminal.process_shell_output_chunk(0, b"\x1b]0;title\x07$ ");
+        assert!(!inverted(&mut terminal));
+
+        terminal.dirty = false;
+        terminal.process_shell_output_chunk(0, b"\x07");
+        assert!(terminal.dirty);
+        assert!(inverted(&mut terminal));
+
+        // Nothing to redraw while the flash lasts; one redraw once it's over
+        terminal.dirty = false;
+        terminal.advance_frame();
+        assert!(!terminal.dirty);
+        std::thread::sleep(bell::FLASH_DURATION);
+        terminal.advance_frame();
+        assert!(terminal.dirty);
+        assert!(!inverted(&mut terminal));
+    }
+
+    #[test]
+    fn test_bell_without_visual_style_does_not_flash() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab::new());
+        terminal.process_shell_output_chunk(0, b"\x07");
+        assert!(!terminal.bell_flash.is_active(std::time::Instant::now()));
+    }
+
+    #[test]
+    fn test_hyperlink_at_resolves_rendered_links() {
+        use ratatui::backend::TestBackend;
+
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        let output = "plain\n\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ text\n";
+        terminal.tabs.push(Tab {
+            output: output.as_bytes().to_vec(),
+            ..Tab::new()
+        });
+
+        let mut backend = RatatuiTerminal::new(TestBackend::new(40, 10)).unwrap();
+        backend
+            .draw(|f| terminal.render_terminal_output(f, Rect::new(0, 1, 40, 8)))
+            .unwrap();
+
+        assert_eq!(terminal.hyperlink_at(0, 2).as_deref(), Some("https://example.com"));
+        assert_eq!(terminal.hyperlink_at(3, 2).as_deref(), Some("https://example.com"));
+        assert_eq!(terminal.hyperlink_at(4, 2), None);
+        assert_eq!(terminal.hyperlink_at(0, 1), None);
+        // Outside the output area
+        assert_eq!(terminal.hyperlink_at(0, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_mouse_goes_to_programs_that_report_it() {
+        use crossterm::event::{MouseButton, MouseEventKind};
+
+        let mut terminal = terminal_with_output(3);
+        terminal.output_area = Rect::new(0, 1, 40, 8);
+        let click = |column, row, modifiers| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers,
+        };
+
+        assert_eq!(terminal.mouse_report(click(4, 3, KeyModifiers::NONE)), None);
+
+        terminal.process_shell_output_chunk(0, b"\x1b[?1000h\x1b[?1006h");
+        // Cells count from 1 at the output area's corner
+        assert_eq!(
+            terminal.mouse_report(click(4, 3, KeyModifiers::NONE)),
+            Some(b"\x1b[<0;5;3M".to_vec())
+        );
+        assert_eq!(terminal.mouse_report(click(4, 0, KeyModifiers::NONE)), None);
+        // Shift keeps local selection
+        assert_eq!(
+            terminal.mouse_report(click(4, 3, KeyModifiers::SHIFT)),
+            None
+        );
+
+        // Turned off again: clicks select text
+        terminal.process_shell_output_chunk(0, b"\x1b[?1000l");
+        assert_eq!(terminal.mouse_report(click(4, 3, KeyModifiers::NONE)), None);
+        terminal
+            .handle_mouse_event(click(4, 3, KeyModifiers::NONE))
+            .await
+            .unwrap();
+        assert!(terminal.selection_active);
+    }
+
+    #[test]
+    fn test_render_parses_only_appended_output() {
+        use ratatui::backend::TestBackend;
+
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab::new());
+
+        let output = "$ ls\r\n\x1b[34msrc\x1b[0m  \x1b[1mCargo.toml\x1b[0m\r\n$ echo hi\r\nhi\r\n$ ";
+        let mut backend = RatatuiTerminal::new(TestBackend::new(40, 10)).unwrap();
+        // A line and an escape sequence are both cut between reads
+        for chunk in output.as_bytes().chunks(7) {
+            terminal.tabs[0].output.extend_from_slice(chunk);
+            backend
+                .draw(|f| terminal.render_terminal_output(f, Rect::new(0, 1, 40, 8)))
+                .unwrap();
+        }
+
+        let parser = terminal.tabs[0].parser.as_ref().unwrap();
+        assert_eq!(parser.parsed_len(), output.len());
+        let full = AnsiParser::parse_with_palette(output, &terminal.color_palette);
+        assert_eq!(terminal.tabs[0].styled_lines, full);
+
+        // Clearing the buffer starts the parse over
+        terminal.tabs[0].output.clear();
+        terminal.invalidate_parse(0);
+        terminal.tabs[0].output.extend_from_slice(b"fresh");
+        backend
+            .draw(|f| terminal.render_terminal_output(f, Rect::new(0, 1, 40, 8)))
+            .unwrap();
+        assert_eq!(terminal.tabs[0].parser.as_ref().unwrap().parsed_len(), 5);
+        assert_eq!(
+            terminal.tabs[0].styled_lines,
+            AnsiParser::parse_with_palette("fresh", &terminal.color_palette)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mouse_wheel_scroll_clamps() {
+        // 50 lines of output plus the empty line after the final newline
+        let mut terminal = terminal_with_output(50);
+        let max_offset = terminal.active_line_count() - terminal.viewport_rows();
+
+        terminal
+            .handle_mouse_event(MouseEvent {
+                kind: crossterm::event::MouseEventKind::ScrollUp,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            })
+            .await
+            .unwrap();
+        assert_eq!(terminal.scroll_offset(), MOUSE_SCROLL_LINES);
+
+        terminal.scroll_up(1000);
+        assert_eq!(terminal.scroll_offset(), max_offset);
+
+        terminal.scroll_down(1000);
+        assert_eq!(terminal.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_scroll_does_not_move_when_output_fits() {
+        let mut terminal = terminal_with_output(3);
+        terminal.scroll_up(MOUSE_SCROLL_LINES);
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert!(terminal.is_following_output());
+    }
+
+    #[test]
+    fn test_follow_mode_transitions() {
+        let mut terminal = terminal_with_output(50);
+        assert!(terminal.is_following_output());
+
+        // Scrolling up stops following
+        terminal.scroll_up(6);
+        assert!(!terminal.is_following_output());
+
+        // New output keeps the view anchored instead of jumping to the bottom
+        terminal.process_shell_output_chunk(0, b"new 1\nnew 2\n");
+        assert_eq!(terminal.scroll_offset(), 8);
+        assert!(!terminal.is_following_output());
+
+        // Reaching the bottom resumes following
+        terminal.scroll_down(8);
+        assert!(terminal.is_following_output());
+        terminal.process_shell_output_chunk(0, b"new 3\n");
+        assert_eq!(terminal.scroll_offset(), 0);
+    }
+
+    /// Terminal keeping 256 bytes of output in memory and the rest on disk
+    fn spilling_terminal() -> Terminal {
+        let mut config = Config::default();
+        config.terminal.scrollback_lines = 1;
+        config.terminal.scrollback_to_disk = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.terminal_rows = 11;
+        terminal.tabs.push(Tab::new());
+        terminal
+    }
+
+    #[test]
+    fn test_scrollback_spills_whole_lines_and_pages_back_in() {
+        let mut terminal = spilling_terminal();
+        let mut all = Vec::new();
+        for i in 0..100 {
+            let line = format!("line {i:03}\n");
+            all.extend_from_slice(line.as_bytes());
+            terminal.process_shell_output_chunk(0, line.as_bytes());
+        }
+
+        // Memory holds the newest whole lines, the disk everything before them
+        let buffer = &terminal.tabs[0].output;
+        assert!(buffer.len() <= 256);
+        assert!(buffer.starts_with(b"line "));
+        assert_eq!(terminal.spilled_lines(0) + buffer.len() / 9, 100);
+        assert_eq!(terminal.full_output(0), all.as_slice());
+
+        // Scrolling to the very top reads the spilled lines back in
+        terminal.scroll_up(1000);
+        assert_eq!(terminal.tabs[0].output, all);
+        assert_eq!(terminal.spilled_lines(0), 0);
+        assert_eq!(
+            terminal.scroll_offset(),
+            terminal.active_line_count() - terminal.viewport_rows()
+        );
+
+        // They stay in memory while scrolled up, even as output arrives
+        terminal.process_shell_output_chunk(0, b"line 100\n");
+        all.extend_from_slice(b"line 100\n");
+        assert_eq!(terminal.tabs[0].output, all);
+
+        // Following the output again moves them back out
+        terminal.scroll_to_bottom();
+        assert!(terminal.tabs[0].output.len() <= 256);
+        assert_eq!(
+            terminal.spilled_lines(0) + terminal.tabs[0].output.len() / 9,
+            101
+        );
+        assert_eq!(terminal.full_output(0), all.as_slice());
+    }
+
+    #[test]
+    fn test_search_finds_and_shows_spilled_lines() {
+        let mut terminal = spilling_terminal();
+        for i in 0..100 {
+            terminal.process_shell_output_chunk(0, format!("line {i:03}\n").as_bytes());
+        }
+        assert!(terminal.spilled_lines(0) > 5);
+
+        terminal.search_query = "line 005".to_string();
+        terminal.execute_search();
+        // Line numbers count from the first spilled line
+        assert_eq!(terminal.search_results, vec![5]);
+
+        // The match was paged back in and is on screen
+        let line = 5 - terminal.spilled_lines(0);
+        let viewport = terminal.viewport_rows();
+        let top = terminal.active_line_count() - viewport - terminal.scroll_offset();
+        assert!((top..top + viewport).contains(&line));
+        assert!(terminal.active_buffer_text().starts_with("line 000\n"));
+    }
+
+    #[test]
+    fn test_scrollback_is_dropped_without_scrollback_to_disk() {
+        let mut terminal = spilling_terminal();
+        terminal.config.terminal.scrollback_to_disk = false;
+        for i in 0..100 {
+            terminal.process_shell_output_chunk(0, format!("line {i:03}\n").as_bytes());
+        }
+        assert_eq!(terminal.tabs[0].output.len(), 256);
+        assert_eq!(terminal.spilled_lines(0), 0);
+        assert_eq!(terminal.full_output(0), terminal.tabs[0].output.as_slice());
+    }
+
+    /// Terminal with one session whose screen switches are tracked
+    fn alternate_screen_terminal() -> Terminal {
+        let mut terminal = spilling_terminal();
+        terminal.config.terminal.scrollback_lines = 1000;
+        terminal
+    }
 
+    #[test]
+    fn test_alternate_screen_keeps_no_scrollback() {
+        let mut terminal = alternate_screen_terminal();
+        terminal.process_shell_output_chunk(0, b"$ ls\nsrc\n$ vim\n");
+        let normal = terminal.tabs[0].output.clone();
+
+        // Output before the switch stays on the normal screen
+        terminal.process_shell_output_chunk(0, b"tail\n\x1b[?1049h\x1b[Hfirst\n");
+        assert!(terminal.in_alternate_screen(0));
+        assert_eq!(terminal.tabs[0].output, b"\x1b[?1049h\x1b[Hfirst\n");
+
+        // Only a screenful is kept, and there is nothing to scroll to
+        for i in 0..50 {
+            terminal.process_shell_output_chunk(0, format!("row {i:02}\n").as_bytes());
+        }
+        let screen = visible_text(&terminal);
+        assert_eq!(screen.len(), terminal.viewport_rows());
+        assert_eq!(screen[0], "row 41");
+        assert_eq!(
+            AnsiParser::parse(&String::from_utf8_lossy(&terminal.tabs[0].output)).len(),
+            terminal.viewport_rows()
+        );
+        terminal.scroll_up(5);
+        assert_eq!(terminal.scroll_offset(), 0);
         terminal.toggle_search_mode();
         assert!(!terminal.search_mode);
+
+        // Switching back brings the normal screen back as it was, without
+        // the switching sequences
+        terminal.process_shell_output_chunk(0, b"\x1b[?1049l$ ");
+        assert!(!terminal.in_alternate_screen(0));
+        let mut expected = normal;
+        expected.extend_from_slice(b"tail\n$ ");
+        assert_eq!(terminal.tabs[0].output, expected);
     }
 
     #[test]
-    fn test_execute_search_empty_query() {
+    fn test_alternate_screen_restores_scroll_position() {
+        let mut terminal = alternate_screen_terminal();
+        for i in 0..30 {
+            terminal.process_shell_output_chunk(0, format!("line {i:02}\n").as_bytes());
+        }
+        terminal.scroll_up(3);
+
+        terminal.process_shell_output_chunk(0, b"\x1b[?47hhtop\n");
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert_eq!(visible_text(&terminal)[0], "htop");
+
+        terminal.process_shell_output_chunk(0, b"\x1b[?47l");
+        assert_eq!(terminal.scroll_offset(), 3);
+        assert_eq!(visible_text(&terminal)[0], "line 18");
+    }
+
+    /// Text of the lines the viewport shows
+    fn visible_text(terminal: &Terminal) -> Vec<String> {
+        let output = String::from_utf8_lossy(&terminal.tabs[terminal.active_session].output);
+        let lines = AnsiParser::parse(&output);
+        let viewport = terminal.viewport_rows();
+        let top = lines
+            .len()
+            .saturating_sub(viewport + terminal.scroll_offset());
+        lines[top..]
+            .iter()
+            .take(viewport)
+            .map(search::line_text)
+            .collect()
+    }
+
+    #[test]
+    fn test_clear_scrollback_keeps_visible_lines() {
+        let mut terminal = terminal_with_output(50);
+        terminal.process_shell_output_chunk(0, b"\x1b[32muser@host\x1b[0m$ ");
+        let visible = visible_text(&terminal);
+        assert_eq!(visible.first().unwrap(), "line 41");
+        assert_eq!(visible.last().unwrap(), "user@host$ ");
+
+        terminal.clear_scrollback();
+        let output = String::from_utf8_lossy(&terminal.tabs[0].output);
+        let lines: Vec<String> = AnsiParser::parse(&output)
+            .iter()
+            .map(search::line_text)
+            .collect();
+        assert_eq!(lines, visible);
+        assert!(output.starts_with("line 41\n"));
+        assert!(output.ends_with("$ "));
+        assert_eq!(visible_text(&terminal), visible);
+    }
+
+    #[test]
+    fn test_clear_scrollback_when_scrolled_up_keeps_lines_below() {
+        let mut terminal = terminal_with_output(50);
+        terminal.scroll_up(5);
+        let visible = visible_text(&terminal);
+        assert_eq!(visible.first().unwrap(), "line 36");
+
+        terminal.clear_scrollback();
+        let output = String::from_utf8_lossy(&terminal.tabs[0].output);
+        assert!(output.starts_with("line 36\n"));
+        assert!(output.ends_with("line 49\n"));
+        assert_eq!(visible_text(&terminal), visible);
+    }
+
+    #[test]
+    fn test_clear_scrollback_drops_spilled_output() {
+        let mut terminal = spilling_terminal();
+        for i in 0..100 {
+            terminal.process_shell_output_chunk(0, format!("line {i:03}\n").as_bytes());
+        }
+        assert!(terminal.spilled_lines(0) > 0);
+
+        terminal.clear_scrollback();
+        assert_eq!(terminal.spilled_lines(0), 0);
+        assert!(terminal.full_output(0).starts_with(b"line 091\n"));
+    }
+
+    #[test]
+    fn test_clear_scrollback_leaves_short_output() {
+        let mut terminal = terminal_with_output(3);
+        let before = terminal.tabs[0].output.clone();
+        terminal.clear_scrollback();
+        assert_eq!(terminal.tabs[0].output, before);
+    }
+
+    #[test]
+    fn test_line_start_follows_parsed_lines() {
+        let output = "one\r\ntwo\r\n\x1b[31mthree\x1b[0m\r\nfour";
+        assert_eq!(line_start(output, 0), 0);
+        assert_eq!(line_start(output, 1), 5);
+        assert_eq!(&output[line_start(output, 3)..], "four");
+        assert_eq!(line_start(output, 9), output.len());
+    }
+
+    #[test]
+    fn test_scroll_to_top_offset_from_length_and_viewport() {
+        // 50 lines and the empty line the cursor is on
+        for (rows, expected) in [(11, 41), (21, 31), (52, 0), (80, 0)] {
+            let mut terminal = terminal_with_output(50);
+            terminal.terminal_rows = rows;
+            terminal.scroll_to_top();
+            assert_eq!(terminal.scroll_offset(), expected, "{rows} rows");
+            assert_eq!(terminal.is_following_output(), expected == 0);
+            // The first line is at the top of the view
+            if expected > 0 {
+                assert_eq!(visible_text(&terminal)[0], "line 0");
+            }
+        }
+    }
+
+    #[test]
+    fn test_scroll_to_bottom_resumes_following() {
+        let mut terminal = terminal_with_output(50);
+        terminal.scroll_to_top();
+        assert_eq!(terminal.scroll_offset(), 41);
+
+        // Scrolled to the top, new output leaves the view where it is
+        terminal.process_shell_output_chunk(0, b"new 1\nnew 2\n");
+        assert_eq!(terminal.scroll_offset(), 43);
+        assert_eq!(visible_text(&terminal)[0], "line 0");
+
+        terminal.scroll_to_bottom();
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert!(terminal.is_following_output());
+        terminal.process_shell_output_chunk(0, b"new 3\n");
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert_eq!(visible_text(&terminal).last().unwrap(), "");
+    }
+
+    #[test]
+    fn test_scroll_to_top_pages_in_spilled_output() {
+        let mut terminal = spilling_terminal();
+        for i in 0..100 {
+            terminal.process_shell_output_chunk(0, format!("line {i:03}\n").as_bytes());
+        }
+        terminal.scroll_to_top();
+        assert_eq!(terminal.spilled_lines(0), 0);
+        assert_eq!(visible_text(&terminal)[0], "line 000");
+    }
+
+    #[test]
+    fn test_scroll_offsets_are_per_session() {
         let config = Config::default();
         let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs = vec![Tab::new(), Tab::new()];
 
-        terminal.search_query.clear();
-        terminal.execute_search();
-        assert!(terminal.search_results.is_empty());
+        terminal.active_session = 1;
+        terminal.set_scroll_offset(7);
+        assert_eq!(terminal.scroll_offset(), 7);
+
+        terminal.active_session = 0;
+        assert_eq!(terminal.scroll_offset(), 0);
     }
 
     #[test]
-    fn test_execute_search_with_matches() {
-        let config = Config::default();
+    fn test_background_tab_output_stays_in_its_tab() {
+        let mut config = Config::default();
+        config.terminal.bell_style = "visual".to_string();
         let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs = vec![Tab::new(), Tab::new()];
 
-        // Terminal starts with no sessions/buffers, so push one
-        terminal.output_buffers.push(b"hello world\nfoo bar\nhello again\n".to_vec());
-        terminal.search_query = "hello".to_string();
-        terminal.execute_search();
+        terminal.process_shell_output_chunk(
+            1,
+            b"\x1b]7;file://host/tmp\x07\x1b]133;A\x07$ \x1b[?2004h\x07",
+        );
+        let background = &terminal.tabs[1];
+        assert!(String::from_utf8_lossy(&background.output).contains("$ "));
+        assert_eq!(background.titles.dir(), Some("/tmp"));
+        assert!(background.prompt_marks.is_active());
+        assert!(background.child_modes.bracketed_paste());
+        assert!(terminal.tabs[0].output.is_empty());
+
+        // The bell and the state behind key bindings follow the active tab
+        assert!(!terminal.bell_flash.is_active(std::time::Instant::now()));
+        let keybindings = terminal.keybindings.borrow();
+        assert_eq!(keybindings.shell_integration().current_dir, None);
+    }
 
-        assert_eq!(terminal.search_results.len(), 2);
-        assert_eq!(terminal.search_results[0], 0); // First line
-        assert_eq!(terminal.search_results[1], 2); // Third line
+    #[test]
+    fn test_session_prompt_save_overwrite_and_pick() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut terminal = terminal_with_output(3);
+        terminal.session_manager = Some(SessionManager::with_dir(dir.path()).unwrap());
+
+        terminal.open_save_session_prompt();
+        for ch in "work".chars() {
+            terminal.session_prompt_input(ch);
+        }
+        terminal.session_prompt_submit();
+        assert!(terminal.session_prompt.is_none());
+
+        // Saving under the same name asks before replacing it
+        terminal.open_save_session_prompt();
+        for ch in "work".chars() {
+            terminal.session_prompt_input(ch);
+        }
+        terminal.session_prompt_submit();
+        assert!(matches!(
+            terminal.session_prompt,
+            Some(SessionPrompt::ConfirmOverwrite { .. })
+        ));
+        terminal.session_prompt_input('y');
+        assert!(terminal.session_prompt.is_none());
+
+        let sm = terminal.session_manager.as_ref().unwrap();
+        let sessions = sm.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].name, "work");
+
+        terminal.tabs[0].output.clear();
+        terminal.open_session_picker();
+        assert!(terminal
+            .session_prompt
+            .as_ref()
+            .unwrap()
+            .text()
+            .contains("work"));
+        terminal.session_prompt_submit();
+        assert!(terminal.session_prompt.is_none());
+        assert_eq!(terminal.tabs[0].output, b"line 0\nline 1\nline 2\n");
     }
 
     #[test]
-    fn test_execute_search_case_insensitive() {
-        let config = Config::default();
+    fn test_autosave_checkpoint_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = std::time::Instant::now();
+        let mut terminal = terminal_with_output(2);
+        terminal.session_manager = Some(SessionManager::with_dir(dir.path()).unwrap());
+        terminal.autosave_timer = AutosaveTimer::new(10, start);
+
+        assert!(terminal
+            .due_autosave(start + Duration::from_secs(5))
+            .is_none());
+        let checkpoint = terminal
+            .due_autosave(start + Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(checkpoint.id, AUTOSAVE_ID);
+        assert_eq!(checkpoint.tabs[0].output, "line 0\nline 1\n");
+        let sm = terminal.session_manager.clone().unwrap();
+        sm.save_session(&checkpoint).unwrap();
+
+        // The next run offers the checkpoint and restores it on 'y'
+        let mut next = terminal_with_output(0);
+        next.session_manager = Some(sm);
+        next.offer_autosave_restore();
+        assert!(matches!(
+            next.session_prompt,
+            Some(SessionPrompt::RestoreAutosave { .. })
+        ));
+        next.session_prompt_input('y');
+        assert!(next.session_prompt.is_none());
+        assert_eq!(next.tabs[0].output, b"line 0\nline 1\n");
+
+        next.discard_autosave();
+        next.offer_autosave_restore();
+        assert!(next.session_prompt.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_restored_tabs_start_in_their_own_directories() {
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        let paths: Vec<String> = dirs
+            .iter()
+            .map(|dir| dir.path().canonicalize().unwrap().display().to_string())
+            .collect();
+
+        // Each tab's shell reports a different directory over OSC 7
+        let mut terminal = terminal_with_tabs(2);
+        for (index, path) in paths.iter().enumerate() {
+            terminal.select_tab(index);
+            let report = format!("\x1b]7;file://localhost{path}\x07");
+            terminal.process_shell_output_chunk(index, report.as_bytes());
+        }
+        let saved = terminal.snapshot_session("work".to_string(), None);
+        let saved_dirs: Vec<_> = saved
+            .tabs
+            .iter()
+            .map(|tab| tab.working_dir.clone())
+            .collect();
+        assert_eq!(saved_dirs, [Some(paths[0].clone()), Some(paths[1].clone())]);
+
+        let mut next = terminal_with_tabs(1);
+        next.restore_session(&saved).unwrap();
+        assert_eq!(next.sessions.len(), 2);
+        for (index, path) in paths.iter().enumerate() {
+            let mut reader = OutputReader::spawn(next.sessions[index].clone(), 4096);
+            next.sessions[index].write_input(b"pwd\r").await.unwrap();
+            let mut output = String::new();
+            for _ in 0..250 {
+                if output.contains(path.as_str()) {
+                    break;
+                }
+                match tokio::time::timeout(Duration::from_millis(20), reader.recv()).await {
+                    Ok(Some(chunk)) => output.push_str(&String::from_utf8_lossy(&chunk)),
+                    Ok(None) => break,
+                    Err(_) => {}
+                }
+            }
+            assert!(output.contains(path.as_str()), "tab {index}: {output}");
+            assert_eq!(next.tab_dir(index).as_deref(), Some(path.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_command_palette_lists_actions_and_themes() {
+        let mut config = Config::default();
+        config.features.theme_manager = true;
         let mut terminal = Terminal::new(config).unwrap();
 
-        terminal.output_buffers.push(b"Hello World\nHELLO AGAIN\nhello small\n".to_vec());
-        terminal.search_query = "hello".to_string();
-        terminal.execute_search();
+        terminal.open_command_palette();
+        let lines = terminal.render_to_lines(80, 30);
+        let shown = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(shown("Command Palette (Esc to close)"));
+        assert!(shown("New tab  Ctrl+T"));
 
-        assert_eq!(terminal.search_results.len(), 3);
+        let palette = terminal.command_palette.as_mut().unwrap();
+        for ch in "nord".chars() {
+            palette.push(ch);
+        }
+        assert_eq!(
+            palette.selected().unwrap().target,
+            PaletteTarget::Theme("nord".to_string())
+        );
+        let lines = terminal.render_to_lines(80, 30);
+        assert!(lines.iter().any(|line| line.contains("> nord")));
+        assert!(lines.iter().any(|line| line.contains("Theme: nord")));
     }
 
     #[test]
-    fn test_execute_search_no_matches() {
+    fn test_ghost_text_follows_command_buffer() {
+        let mut config = Config::default();
+        config.features.autocomplete = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        if let Some(ref mut ac) = terminal.autocomplete {
+            ac.add_to_history("git status".to_string());
+        }
+        terminal.tabs.push(Tab {
+            command: b"git st".to_vec(),
+            ..Tab::new()
+        });
+
+        assert_eq!(terminal.ghost_suggestion().as_deref(), Some("atus"));
+        let lines = terminal.render_to_lines(40, 8);
+        assert_eq!(lines[0], "git status");
+
+        // Dismissed for this buffer only; typing brings it back
+        terminal.dismiss_ghost_suggestion();
+        assert_eq!(terminal.ghost_suggestion(), None);
+        assert_eq!(terminal.render_to_lines(40, 8)[0], "git st");
+        terminal.tabs[0].command.push(b'a');
+        assert_eq!(terminal.ghost_suggestion().as_deref(), Some("tus"));
+
+        // Cut to the room left on the row
+        assert_eq!(terminal.render_to_lines(9, 8)[0], "git stat");
+
+        // Never part of what would be sent on Enter
+        assert_eq!(terminal.tabs[0].command, b"git sta");
+    }
+
+    #[tokio::test]
+    async fn test_ghost_text_hidden_off_the_end_of_the_line() {
+        let mut config = Config::default();
+        config.features.autocomplete = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        if let Some(ref mut ac) = terminal.autocomplete {
+            ac.add_to_history("git status".to_string());
+        }
+        terminal.tabs.push(Tab {
+            command: b"git st".to_vec(),
+            ..Tab::new()
+        });
+
+        // Programs on the alternate screen get Right and End as typed
+        terminal.tabs[0].primary_screen = Some(modes::PrimaryScreen::default());
+        assert_eq!(terminal.ghost_suggestion(), None);
+        terminal.tabs[0].primary_screen = None;
+        assert_eq!(terminal.ghost_suggestion().as_deref(), Some("atus"));
+
+        // Once the cursor moves left, Right moves it back instead of completing
+        for code in [KeyCode::Left, KeyCode::Home] {
+            terminal
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .await
+                .unwrap();
+            assert_eq!(terminal.ghost_suggestion(), None);
+            terminal.ghost_dismissed = None;
+        }
+    }
+
+    #[test]
+    fn test_search_highlights_all_matches_on_line() {
         let config = Config::default();
         let mut terminal = Terminal::new(config).unwrap();
 
-        terminal.output_buffers.push(b"hello world\nfoo bar\n".to_vec());
-        terminal.search_query = "zzz".to_string();
+        terminal.tabs.push(Tab {
+            output: b"ab ab ab\n".to_vec(),
+            ..Tab::new()
+        });
+        terminal.toggle_search_mode();
+        terminal.search_query = "ab".to_string();
         terminal.execute_search();
 
-        assert!(terminal.search_results.is_empty());
+        let mut lines = vec![Line::from("ab ab ab")];
+        terminal.highlight_search_matches(&mut lines);
+        let highlighted = lines[0]
+            .spans
+            .iter()
+            .filter(|span| span.style.bg.is_some())
+            .count();
+        assert_eq!(highlighted, 3);
     }
 
     #[test]
@@ -3853,7 +8259,10 @@ mod tests {
         let config = Config::default();
         let mut terminal = Terminal::new(config).unwrap();
 
-        terminal.output_buffers.push(b"match1\nno\nmatch2\nno\nmatch3\n".to_vec());
+        terminal.tabs.push(Tab {
+            output: b"match1\nno\nmatch2\nno\nmatch3\n".to_vec(),
+            ..Tab::new()
+        });
         terminal.search_query = "match".to_string();
         terminal.execute_search();
         assert_eq!(terminal.search_results.len(), 3);
@@ -3923,9 +8332,9 @@ mod tests {
         let mut terminal = Terminal::new(config).unwrap();
 
         // active_session is 0 but output_buffers is empty
-        assert!(terminal.output_buffers.is_empty());
+        assert!(terminal.tabs.is_empty());
         // This should not panic due to the guard at the start of process_shell_output_chunk
-        terminal.process_shell_output_chunk(b"test output");
+        terminal.process_shell_output_chunk(0, b"test output");
     }
 
     #[test]
@@ -3934,15 +8343,255 @@ mod tests {
         let mut config = Config::default();
         config.terminal.hardware_acceleration = true;
         let mut terminal = Terminal::new(config).unwrap();
-        terminal.output_buffers.push(Vec::new());
+        terminal.tabs.push(Tab::new());
 
-        terminal.process_shell_output_chunk(b"hello world");
+        terminal.process_shell_output_chunk(0, b"hello world");
         assert_eq!(
-            String::from_utf8_lossy(&terminal.output_buffers[0]),
+            String::from_utf8_lossy(&terminal.tabs[0].output),
             "hello world"
         );
     }
 
+    #[test]
+    fn test_process_output_sanitizes_escapes() {
+        let chunk = b"\x1b]52;c;aGk=\x07\x1b[31mred\x1b[0m\x1bP+q544e\x1b\\";
+
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab::new());
+        terminal.process_shell_output_chunk(0, chunk);
+        assert_eq!(terminal.tabs[0].output, b"\x1b[31mred\x1b[0m");
+
+        // A sequence split across reads is removed whole
+        terminal.process_shell_output_chunk(0, b" \x1b]52;c;cm0g");
+        terminal.process_shell_output_chunk(0, b"LXJmIH4=\x07!");
+        assert_eq!(terminal.tabs[0].output, b"\x1b[31mred\x1b[0m !");
+
+        let mut config = Config::default();
+        config.terminal.sanitize_output = false;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+        terminal.process_shell_output_chunk(0, chunk);
+        assert_eq!(terminal.tabs[0].output, chunk);
+    }
+
+    #[test]
+    fn test_drain_batches_output_for_filters() {
+        let mut config = Config::default();
+        config.hooks.output_filters = vec!["output = (input:gsub('ERROR', 'E!'))".to_string()];
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+
+        let (output_tx, mut output_rx) = tokio::sync::mpsc::unbounded_channel();
+        // A match split across two reads is filtered once the reads are joined
+        output_tx.send(b"build ERR".to_vec()).unwrap();
+        output_tx.send(b"OR\n".to_vec()).unwrap();
+        terminal.process_output_batches(0, || output_rx.try_recv().ok());
+        assert_eq!(terminal.tabs[0].output, b"build E!\n");
+
+        // Batches larger than one read still respect the scrollback limit
+        let max_buffer = terminal.config.terminal.scrollback_lines * 256;
+        for _ in 0..(max_buffer / (64 * 1024) + 2) {
+            output_tx.send(vec![b'x'; 64 * 1024]).unwrap();
+        }
+        terminal.process_output_batches(0, || output_rx.try_recv().ok());
+        assert_eq!(terminal.tabs[0].output.len(), max_buffer);
+        assert!(output_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_osc133_marks_end_commands() {
+        let mut config = Config::default();
+        config.features.progress_bar = true;
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab {
+            output: b"earlier\r\n".to_vec(),
+            ..Tab::new()
+        });
+        let running = |terminal: &Terminal| {
+            terminal
+                .progress_bar
+                .as_ref()
+                .is_some_and(|pb| pb.visible && !pb.is_done())
+        };
+
+        // Before any mark the prompt heuristic decides
+        terminal.progress_bar.as_mut().unwrap().start_ref("make");
+        terminal.process_shell_output_chunk(0, b"user@host:~$ ");
+        assert!(!running(&terminal));
+
+        // With marks, prompt-like output no longer ends the command...
+        terminal.progress_bar.as_mut().unwrap().start_ref("make");
+        terminal.process_shell_output_chunk(0, b"\x1b]133;C;make\x07building\r\n> ");
+        assert!(running(&terminal));
+
+        // ...but the D mark does, recording the exit code
+        terminal.process_shell_output_chunk(0, b"\x1b]133;D;2\x07\x1b]133;A\x07$ ");
+        assert!(!running(&terminal));
+        let keybindings = terminal.keybindings.borrow();
+        let shell = keybindings.shell_integration();
+        assert_eq!(shell.last_command.as_deref(), Some("make"));
+        assert_eq!(shell.last_exit_code, Some(2));
+
+        // Marks are positioned in the session's buffer
+        let buffer = String::from_utf8_lossy(&terminal.tabs[0].output).to_string();
+        let blocks = terminal.tabs[0].prompt_marks.commands();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].output, buffer.find("\x1b]133;C"));
+        assert_eq!(blocks[0].end, buffer.find("\x1b]133;D"));
+        assert_eq!(blocks[0].exit_code, Some(2));
+        assert_eq!(blocks[1].prompt, buffer.find("\x1b]133;A"));
+    }
+
+    #[test]
+    fn test_osc7_tracks_working_directory() {
+        let mut terminal = Terminal::new(Config::default()).unwrap();
+        terminal.tabs.push(Tab::new());
+        let cwd = |terminal: &Terminal| {
+            terminal
+                .keybindings
+                .borrow()
+                .shell_integration()
+                .current_dir
+                .clone()
+        };
+
+        terminal.process_shell_output_chunk(0, b"\x1b]7;file://host/home/me/My%20Files\x07$ ");
+        assert_eq!(cwd(&terminal).as_deref(), Some("/home/me/My Files"));
+
+        // Other schemes leave the tracked directory alone
+        terminal.process_shell_output_chunk(0, b"\x1b]7;kitty-shell-cwd://host/tmp\x07$ ");
+        assert_eq!(cwd(&terminal).as_deref(), Some("/home/me/My Files"));
+    }
+
+    #[test]
+    fn test_reloaded_config_applies_live_settings() {
+        let mut config = Config::default();
+        config.features.autocomplete = false;
+        config.terminal.line_wrap = "wrap".to_string();
+        let mut terminal = Terminal::new(config.clone()).unwrap();
+        terminal.tabs.push(Tab {
+            styled_len: 42,
+            ..Tab::new()
+        });
+
+        let mut new = config;
+        new.features.autocomplete = true;
+        new.terminal.line_wrap = "truncate".to_string();
+        new.keybindings.custom.insert(
+            "list_sessions".to_string(),
+            "Ctrl+Alt+R".to_string(),
+        );
+        new.terminal.font_size = 20;
+        let diff = ConfigDiff::between(&terminal.config, &new);
+        terminal.apply_config_changes(&new, &diff);
+
+        assert!(terminal.autocomplete.is_some());
+        assert_eq!(terminal.line_wrap, wrap::LineWrap::Truncate);
+        assert_eq!(terminal.tabs[0].styled_len, 0);
+        assert!(matches!(
+            terminal.keybindings.borrow().get_action(
+                KeyCode::Char('r'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ),
+            Some(Action::ListSessions)
+        ));
+        // Font size needs a restart, so the running config keeps the old one
+        assert_ne!(terminal.config.terminal.font_size, 20);
+        assert_eq!(
+            terminal.notifications.latest(),
+            Some("Config reloaded; restart to apply terminal.font_size")
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_new_tab_opens_in_reported_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.shell.default_shell = "sh".to_string();
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+
+        let report = format!("\x1b]7;file://host{}\x07", dir.path().display());
+        terminal.process_shell_output_chunk(0, report.as_bytes());
+        terminal.create_new_tab().unwrap();
+
+        let pid = terminal.sessions[0].pid().unwrap();
+        let shell_cwd = std::fs::read_link(format!("/proc/{pid}/cwd")).unwrap();
+        assert_eq!(shell_cwd, dir.path().canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_output_hook_reads_buffer_and_notifies() {
+        let mut config = Config::default();
+        config.hooks.on_output = Some(
+            "local text = furnace.get_buffer() \
+             if text:find('Finished') then furnace.notify('built in ' .. tostring(furnace.get_cwd())) end"
+                .to_string(),
+        );
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+
+        terminal.process_shell_output_chunk(0, b"\x1b]7;/src/furnace\x07   Compiling furnace\n");
+        terminal.advance_frame();
+        assert_eq!(terminal.notifications.latest(), None);
+
+        terminal.process_shell_output_chunk(0, b"\x1b[32mFinished\x1b[0m dev profile\n");
+        // Shown between frames, not while the hook runs
+        assert_eq!(terminal.notifications.latest(), None);
+        terminal.advance_frame();
+        assert_eq!(
+            terminal.notifications.latest(),
+            Some("built in /src/furnace")
+        );
+    }
+
+    #[test]
+    fn test_every_hook_notification_is_shown_in_order() {
+        let mut config = Config::default();
+        config.hooks.on_output =
+            Some("furnace.notify('first') furnace.notify('second')".to_string());
+        let mut terminal = Terminal::new(config).unwrap();
+        terminal.tabs.push(Tab::new());
+
+        terminal.process_shell_output_chunk(0, b"done\n");
+        terminal.advance_frame();
+        // Newest on top
+        assert_eq!(
+            terminal.notifications.messages().collect::<Vec<_>>(),
+            ["second", "first"]
+        );
+    }
+
+    #[test]
+    fn test_detect_prompt_at_end_of_output() {
+        let detect = |output: &str| Terminal::detect_prompt(output, None);
+
+        assert!(detect("total 0\r\nuser@host:~$ "));
+        assert!(detect("\x1b[32m~/src\x1b[0m \x1b[1;35m❯\x1b[0m "));
+        assert!(detect("root@box:/# "));
+        assert!(detect("PS C:\\Users\\me> "));
+        assert!(detect(">>> "));
+        assert!(detect("➜  furnace git:(main) "));
+
+        // Prompt-like text earlier in the output doesn't count
+        assert!(!detect("build > step 1\nbuild > step 2\nstill building"));
+        assert!(!detect("cost: 5$ total\n# of files: 3\nwriting output\n"));
+        assert!(!detect("done\n"));
+        assert!(!detect(""));
+    }
+
+    #[test]
+    fn test_detect_prompt_with_regex() {
+        let regex = Regex::new(r"^\[\w+\] ").unwrap();
+        let detect = |output: &str| Terminal::detect_prompt(output, Some(&regex));
+
+        assert!(detect("building...\n\x1b[1m[dev]\x1b[0m "));
+        // The regex is authoritative: built-in endings no longer match
+        assert!(!detect("output\nuser@host:~$ "));
+        assert!(!detect("[dev] was here\nmore output"));
+    }
+
     #[test]
     fn test_osc133_prefix_lengths() {
         // Verify the OSC escape sequence prefix lengths are correct.
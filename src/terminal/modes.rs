@@ -0,0 +1,362 @@
+//! Terminal modes the program in a tab turns on and off
+//!
+//! Programs switch DEC private modes with `ESC [ ? Pm h` (set) and
+//! `ESC [ ? Pm l` (reset), where `Pm` is one or more mode numbers separated by
+//! `;`. Each tab keeps a [`ChildModes`] fed with the tab's output, so input
+//! can be sent the way the program asked for it.
+//!
+//! Tracked so far:
+//!
+//! - `2004` bracketed paste: pasted text is wrapped in `ESC[200~` and `ESC[201~`
+//! - `1049`, `1047` and `47` alternate screen: full-screen programs draw on a
+//!   screen of their own, without scrollback, and the shell's output comes
+//!   back as it was when they switch back. [`ChildModes::update`] says where in
+//!   the output each switch happens, so output on either side of it goes to
+//!   the right screen. The switching sequences themselves go to the alternate
+//!   screen, so the normal screen's output never holds them.
+//! - `1000`, `1002`, `1003` and `1006` mouse reporting, see [`super::mouse`]
+
+use std::ops::Range;
+
+use super::mouse::{self, MouseTracking};
+use super::osc133::MarkLog;
+
+/// Introducer of DEC private mode sequences
+const DEC_PRIVATE: &str = "\x1b[?";
+
+/// Longest unfinished sequence held back for the next read
+const MAX_PARTIAL: usize = 64;
+
+/// Bracketed paste mode
+pub const BRACKETED_PASTE: u16 = 2004;
+
+/// Alternate screen modes: with cursor save and clear, with clear, and plain
+pub const ALTERNATE_SCREEN: [u16; 3] = [1049, 1047, 47];
+
+/// One mode turned on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeChange {
+    pub mode: u16,
+    pub enabled: bool,
+}
+
+/// A switch between the normal and the alternate screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenSwitch {
+    /// Byte position in the output where the other screen's output starts:
+    /// at the switching sequence going to the alternate screen, just past it
+    /// coming back
+    pub at: usize,
+    /// Whether output from `at` on goes to the alternate screen
+    pub alternate: bool,
+}
+
+/// Complete mode changes in `output`, in order
+///
+/// Unfinished sequences at the end are skipped.
+#[must_use]
+pub fn mode_changes(output: &str) -> Vec<ModeChange> {
+    scan(output)
+        .0
+        .into_iter()
+        .map(|(_, change)| change)
+        .collect()
+}
+
+/// Mode changes in `text` with where the sequence of each is, and where an
+/// unfinished sequence at its end starts
+fn scan(text: &str) -> (Vec<(Range<usize>, ModeChange)>, Option<usize>) {
+    let mut changes = Vec::new();
+    for (start, _) in text.match_indices(DEC_PRIVATE) {
+        let body = &text[start + DEC_PRIVATE.len()..];
+        let Some(end) = body.find(|c: char| !(c.is_ascii_digit() || c == ';')) else {
+            // Only parameters up to the end: the rest is still to come
+            return (changes, Some(start));
+        };
+        let enabled = match body.as_bytes()[end] {
+            b'h' => true,
+            b'l' => false,
+            _ => continue,
+        };
+        let sequence = start..start + DEC_PRIVATE.len() + end + 1;
+        changes.extend(
+            body[..end]
+                .split(';')
+                .filter_map(|param| param.parse().ok())
+                .map(|mode| (sequence.clone(), ModeChange { mode, enabled })),
+        );
+    }
+
+    // A read can also end inside the introducer itself
+    let partial = (1..DEC_PRIVATE.len())
+        .rev()
+        .find(|&n| text.ends_with(&DEC_PRIVATE[..n]))
+        .map(|n| text.len() - n);
+    (changes, partial)
+}
+
+/// Modes the program in one tab has asked for
+#[derive(Debug, Clone, Default)]
+pub struct ChildModes {
+    bracketed_paste: bool,
+    alternate_screen: bool,
+    mouse_tracking: MouseTracking,
+    sgr_mouse: bool,
+    /// Start of a sequence cut off by the end of the last read
+    partial: String,
+}
+
+impl ChildModes {
+    /// Modes of a freshly started program: all off
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether pastes should be wrapped in `ESC[200~` and `ESC[201~`
+    #[must_use]
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Whether the program is drawing on the alternate screen
+    #[must_use]
+    pub fn alternate_screen(&self) -> bool {
+        self.alternate_screen
+    }
+
+    /// Which mouse events the program wants instead of local selection
+    #[must_use]
+    pub fn mouse_tracking(&self) -> MouseTracking {
+        self.mouse_tracking
+    }
+
+    /// Whether mouse reports use the SGR encoding
+    #[must_use]
+    pub fn sgr_mouse(&self) -> bool {
+        self.sgr_mouse
+    }
+
+    /// Apply the mode changes in the next piece of output
+    ///
+    /// Returns where the output switches screens, in order. A sequence split
+    /// across reads is finished with the next call, and a switch to the
+    /// alternate screen placed at the start of that output.
+    pub fn update(&mut self, output: &str) -> Vec<ScreenSwitch> {
+        let joined;
+        let held = self.partial.len();
+        let text = if self.partial.is_empty() {
+            output
+        } else {
+            joined = std::mem::take(&mut self.partial) + output;
+            joined.as_str()
+        };
+
+        let (changes, partial) = scan(text);
+        let mut switches = Vec::new();
+        for (sequence, change) in changes {
+            if self.apply(change) {
+                let at = if self.alternate_screen {
+                    sequence.start
+                } else {
+                    sequence.end
+                };
+                switches.push(ScreenSwitch {
+                    at: at.saturating_sub(held),
+                    alternate: self.alternate_screen,
+                });
+            }
+        }
+        if let Some(start) = partial {
+            if text.len() - start <= MAX_PARTIAL {
+                self.partial = text[start..].to_string();
+            }
+        }
+        switches
+    }
+
+    /// Apply one change, returning whether it switched screens
+    fn apply(&mut self, change: ModeChange) -> bool {
+        if change.mode == BRACKETED_PASTE {
+            self.bracketed_paste = change.enabled;
+        } else if ALTERNATE_SCREEN.contains(&change.mode) {
+            let switched = self.alternate_screen != change.enabled;
+            self.alternate_screen = change.enabled;
+            return switched;
+        } else if let Some(tracking) = MouseTracking::from_mode(change.mode) {
+            // Turning off any tracking mode ends mouse reporting
+            self.mouse_tracking = if change.enabled {
+                tracking
+            } else {
+                MouseTracking::Off
+            };
+        } else if change.mode == mouse::SGR_MODE {
+            self.sgr_mouse = change.enabled;
+        }
+        false
+    }
+}
+
+/// A tab's normal screen, put aside while a program uses the alternate screen
+#[derive(Debug, Default)]
+pub struct PrimaryScreen {
+    pub output: Vec<u8>,
+    pub scroll_offset: usize,
+    pub marks: MarkLog,
+}
+
+/// Where the last `rows` lines of `output` start
+///
+/// The alternate screen has no scrollback, so only these lines are kept.
+#[must_use]
+pub fn screen_start(output: &[u8], rows: usize) -> usize {
+    if rows == 0 {
+        return output.len();
+    }
+    output
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|&(_, &b)| b == b'\n')
+        .nth(rows - 1)
+        .map_or(0, |(i, _)| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_changes_parses_set_and_reset() {
+        assert_eq!(
+            mode_changes("\x1b[?2004hprompt$ \x1b[?2004l"),
+            vec![
+                ModeChange {
+                    mode: BRACKETED_PASTE,
+                    enabled: true
+                },
+                ModeChange {
+                    mode: BRACKETED_PASTE,
+                    enabled: false
+                },
+            ]
+        );
+        // Several modes in one sequence
+        assert_eq!(
+            mode_changes("\x1b[?1;2004h"),
+            vec![
+                ModeChange {
+                    mode: 1,
+                    enabled: true
+                },
+                ModeChange {
+                    mode: BRACKETED_PASTE,
+                    enabled: true
+                },
+            ]
+        );
+        // Not mode changes: plain CSI, other final bytes, unfinished
+        assert!(mode_changes("\x1b[2004h\x1b[?25$p\x1b[?2004").is_empty());
+        assert!(mode_changes("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_child_modes_follow_the_last_change() {
+        let mut modes = ChildModes::new();
+        assert!(!modes.bracketed_paste());
+        modes.update("\x1b[?2004h$ ");
+        assert!(modes.bracketed_paste());
+        modes.update("ls\r\n");
+        assert!(modes.bracketed_paste());
+        // Turned off and back on in one read
+        modes.update("\x1b[?2004lrunning\x1b[?2004h");
+        assert!(modes.bracketed_paste());
+        modes.update("\x1b[?2004l");
+        assert!(!modes.bracketed_paste());
+    }
+
+    #[test]
+    fn test_screen_switches_are_placed_in_the_output() {
+        let mut modes = ChildModes::new();
+        let output = "$ vim\r\n\x1b[?1049h\x1b[Hfile";
+        assert_eq!(
+            modes.update(output),
+            vec![ScreenSwitch {
+                at: output.find("\x1b[?").unwrap(),
+                alternate: true
+            }]
+        );
+        assert!(modes.alternate_screen());
+
+        // Setting it again isn't a switch; both ways in one read are
+        assert!(modes.update("\x1b[?1049h").is_empty());
+        let output = "\x1b[?1049l$ \x1b[?47h";
+        assert_eq!(
+            modes.update(output),
+            vec![
+                ScreenSwitch {
+                    at: 8,
+                    alternate: false
+                },
+                ScreenSwitch {
+                    at: output.rfind("\x1b").unwrap(),
+                    alternate: true
+                },
+            ]
+        );
+
+        // Finished in the next read: the switch comes before all of it
+        modes.update("tail\x1b[?10");
+        assert_eq!(
+            modes.update("47lmore"),
+            vec![ScreenSwitch {
+                at: 3,
+                alternate: false
+            }]
+        );
+        assert!(!modes.alternate_screen());
+    }
+
+    #[test]
+    fn test_mouse_modes() {
+        let mut modes = ChildModes::new();
+        assert_eq!(modes.mouse_tracking(), MouseTracking::Off);
+        modes.update("\x1b[?1000h\x1b[?1006h");
+        assert_eq!(modes.mouse_tracking(), MouseTracking::Click);
+        assert!(modes.sgr_mouse());
+        modes.update("\x1b[?1002;1006h");
+        assert_eq!(modes.mouse_tracking(), MouseTracking::Drag);
+        modes.update("\x1b[?1006l\x1b[?1002l");
+        assert_eq!(modes.mouse_tracking(), MouseTracking::Off);
+        assert!(!modes.sgr_mouse());
+    }
+
+    #[test]
+    fn test_screen_start_keeps_last_rows() {
+        let output = b"one\ntwo\nthree\nfour";
+        assert_eq!(screen_start(output, 1), 14);
+        assert_eq!(screen_start(output, 2), 8);
+        assert_eq!(screen_start(output, 4), 0);
+        assert_eq!(screen_start(output, 10), 0);
+        assert_eq!(screen_start(output, 0), output.len());
+    }
+
+    #[test]
+    fn test_sequence_split_across_reads() {
+        let sequence = "\x1b[?2004h";
+        for split in 0..=sequence.len() {
+            let mut modes = ChildModes::new();
+            modes.update(&format!("out{}", &sequence[..split]));
+            modes.update(&format!("{}put", &sequence[split..]));
+            assert!(modes.bracketed_paste(), "split at {split}");
+            assert!(modes.partial.is_empty());
+        }
+
+        // An introducer that turns out to be something else is dropped
+        let mut modes = ChildModes::new();
+        modes.update("\x1b[?20");
+        modes.update("04$p");
+        assert!(!modes.bracketed_paste());
+        assert!(modes.partial.is_empty());
+    }
+}
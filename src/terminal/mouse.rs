@@ -0,0 +1,203 @@
+//! Mouse reports for programs that ask for them
+//!
+//! Programs such as vim and htop turn on mouse reporting with DEC private
+//! modes and then expect clicks, drags and the wheel as input instead of the
+//! terminal selecting text:
+//!
+//! - `1000` reports presses, releases and the wheel
+//! - `1002` also reports movement while a button is held
+//! - `1003` reports all movement
+//! - `1006` sends reports as `ESC [ < b ; x ; y M` (`m` for a release)
+//!   instead of the default `ESC [ M b x y`, whose coordinates stop at 223
+//!
+//! Holding Shift keeps the terminal's own selection while reporting is on.
+
+use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+
+/// Which mouse events a program has asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseTracking {
+    /// None: the terminal handles the mouse itself
+    #[default]
+    Off,
+    /// Presses, releases and the wheel (mode 1000)
+    Click,
+    /// Also movement with a button held (mode 1002)
+    Drag,
+    /// Also movement without a button (mode 1003)
+    Motion,
+}
+
+impl MouseTracking {
+    /// The tracking a DEC private mode number turns on, if it is one
+    #[must_use]
+    pub fn from_mode(mode: u16) -> Option<Self> {
+        match mode {
+            1000 => Some(Self::Click),
+            1002 => Some(Self::Drag),
+            1003 => Some(Self::Motion),
+            _ => None,
+        }
+    }
+
+    /// Whether events of `kind` are reported
+    #[must_use]
+    pub fn reports(self, kind: MouseEventKind) -> bool {
+        match kind {
+            MouseEventKind::Down(_)
+            | MouseEventKind::Up(_)
+            | MouseEventKind::ScrollUp
+            | MouseEventKind::ScrollDown
+            | MouseEventKind::ScrollLeft
+            | MouseEventKind::ScrollRight => self != Self::Off,
+            MouseEventKind::Drag(_) => matches!(self, Self::Drag | Self::Motion),
+            MouseEventKind::Moved => self == Self::Motion,
+        }
+    }
+}
+
+/// SGR encoding of reports (mode 1006)
+pub const SGR_MODE: u16 = 1006;
+
+/// Largest coordinate the default encoding can carry
+const MAX_X10_COORD: u16 = 255 - 32;
+
+/// The report for a mouse event at a 1-based `column` and `row` of the screen
+///
+/// Returns `None` for events `tracking` doesn't report, and in the default
+/// encoding for positions past its largest coordinate.
+#[must_use]
+pub fn encode(
+    kind: MouseEventKind,
+    modifiers: KeyModifiers,
+    column: u16,
+    row: u16,
+    tracking: MouseTracking,
+    sgr: bool,
+) -> Option<Vec<u8>> {
+    if !tracking.reports(kind) {
+        return None;
+    }
+
+    let (code, release) = match kind {
+        MouseEventKind::Down(button) => (button_code(button), false),
+        MouseEventKind::Up(button) => (button_code(button), true),
+        MouseEventKind::Drag(button) => (button_code(button) + 32, false),
+        // No button held
+        MouseEventKind::Moved => (3 + 32, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+    };
+    let mut code = code;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+
+    if sgr {
+        let end = if release { 'm' } else { 'M' };
+        return Some(format!("\x1b[<{code};{column};{row}{end}").into_bytes());
+    }
+
+    // The default encoding can't say which button was released
+    if release {
+        code = (code & !0b11) | 3;
+    }
+    if column > MAX_X10_COORD || row > MAX_X10_COORD {
+        return None;
+    }
+    let byte = |value: u16| u8::try_from(value + 32).ok();
+    Some(vec![
+        0x1b,
+        b'[',
+        b'M',
+        byte(code)?,
+        byte(column)?,
+        byte(row)?,
+    ])
+}
+
+fn button_code(button: MouseButton) -> u16 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: MouseEventKind = MouseEventKind::Down(MouseButton::Left);
+
+    /// Report in the SGR encoding, with every event tracked
+    fn sgr(kind: MouseEventKind, modifiers: KeyModifiers, column: u16, row: u16) -> Vec<u8> {
+        encode(kind, modifiers, column, row, MouseTracking::Motion, true).unwrap()
+    }
+
+    /// Report in the default encoding
+    fn x10(kind: MouseEventKind, column: u16, row: u16) -> Option<Vec<u8>> {
+        encode(
+            kind,
+            KeyModifiers::NONE,
+            column,
+            row,
+            MouseTracking::Click,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_sgr_click_at_cell() {
+        assert_eq!(sgr(LEFT, KeyModifiers::NONE, 5, 3), b"\x1b[<0;5;3M");
+        let release = MouseEventKind::Up(MouseButton::Left);
+        assert_eq!(sgr(release, KeyModifiers::NONE, 5, 3), b"\x1b[<0;5;3m");
+        // Right button with Ctrl, far past the default encoding's limit
+        let right = MouseEventKind::Down(MouseButton::Right);
+        assert_eq!(
+            sgr(right, KeyModifiers::CONTROL, 300, 1),
+            b"\x1b[<18;300;1M"
+        );
+    }
+
+    #[test]
+    fn test_default_encoding_offsets_by_32() {
+        assert_eq!(x10(LEFT, 1, 1).unwrap(), b"\x1b[M !!");
+        // Releases don't say which button
+        let release = MouseEventKind::Up(MouseButton::Right);
+        assert_eq!(x10(release, 10, 2).unwrap(), b"\x1b[M#*\"");
+        assert_eq!(x10(LEFT, 224, 1), None);
+    }
+
+    #[test]
+    fn test_wheel_and_movement() {
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            sgr(MouseEventKind::ScrollDown, none, 2, 4),
+            b"\x1b[<65;2;4M"
+        );
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        assert_eq!(sgr(drag, none, 2, 4), b"\x1b[<32;2;4M");
+        assert_eq!(sgr(MouseEventKind::Moved, none, 2, 4), b"\x1b[<35;2;4M");
+    }
+
+    #[test]
+    fn test_tracking_decides_what_is_reported() {
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        assert!(!MouseTracking::Off.reports(LEFT));
+        assert!(MouseTracking::Click.reports(LEFT));
+        assert!(!MouseTracking::Click.reports(drag));
+        assert!(MouseTracking::Drag.reports(drag));
+        assert!(!MouseTracking::Drag.reports(MouseEventKind::Moved));
+        assert!(MouseTracking::Motion.reports(MouseEventKind::Moved));
+        assert_eq!(x10(drag, 1, 1), None);
+    }
+}
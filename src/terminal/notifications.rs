@@ -0,0 +1,132 @@
+//! Stacked notifications
+//!
+//! Messages such as "Copied to clipboard" or a hook's `furnace.notify` show
+//! above the output for `ui.notification_duration_secs`. Several can show at
+//! once: each is counted down in frames on its own and leaves when its time is
+//! up, and the newest is drawn on top.
+
+/// Most notifications kept at once; older ones make room for new ones
+pub const MAX_NOTIFICATIONS: usize = 3;
+
+/// A message and how many more frames it stays
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Notification {
+    message: String,
+    frames_left: u64,
+}
+
+/// Notifications on screen, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct NotificationQueue {
+    entries: Vec<Notification>,
+}
+
+impl NotificationQueue {
+    /// Create an empty queue
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show `message` for `frames` frames
+    ///
+    /// The same message as the newest one restarts its count instead of
+    /// stacking a copy. Past [`MAX_NOTIFICATIONS`] the oldest is dropped.
+    pub fn push(&mut self, message: String, frames: u64) {
+        let frames_left = frames.max(1);
+        if let Some(newest) = self.entries.last_mut() {
+            if newest.message == message {
+                newest.frames_left = frames_left;
+                return;
+            }
+        }
+        self.entries.push(Notification {
+            message,
+            frames_left,
+        });
+        if self.entries.len() > MAX_NOTIFICATIONS {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Count down one drawn frame, dropping notifications whose time is up
+    pub fn tick(&mut self) {
+        for entry in &mut self.entries {
+            entry.frames_left = entry.frames_left.saturating_sub(1);
+        }
+        self.entries.retain(|entry| entry.frames_left > 0);
+    }
+
+    /// Messages to draw, newest first
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|entry| entry.message.as_str())
+    }
+
+    /// The newest message, if any
+    #[must_use]
+    pub fn latest(&self) -> Option<&str> {
+        self.messages().next()
+    }
+
+    /// Number of notifications showing
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is showing
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_notification_expires_on_its_own() {
+        let mut queue = NotificationQueue::new();
+        queue.push("first".into(), 3);
+        queue.tick();
+        queue.push("second".into(), 3);
+        assert_eq!(queue.messages().collect::<Vec<_>>(), ["second", "first"]);
+
+        // "first" has one frame left, "second" two
+        queue.tick();
+        queue.tick();
+        assert_eq!(queue.messages().collect::<Vec<_>>(), ["second"]);
+        queue.tick();
+        assert!(queue.is_empty());
+        assert_eq!(queue.latest(), None);
+        // Ticking an empty queue is harmless
+        queue.tick();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_oldest_makes_room_and_repeats_restart() {
+        let mut queue = NotificationQueue::new();
+        for i in 0..=MAX_NOTIFICATIONS {
+            queue.push(format!("message {i}"), 10);
+        }
+        assert_eq!(queue.len(), MAX_NOTIFICATIONS);
+        assert_eq!(queue.messages().last(), Some("message 1"));
+
+        // Repeating the newest message restarts it rather than stacking
+        for _ in 0..9 {
+            queue.tick();
+        }
+        queue.push(format!("message {MAX_NOTIFICATIONS}"), 10);
+        assert_eq!(queue.len(), MAX_NOTIFICATIONS);
+        queue.tick();
+        assert_eq!(
+            queue.messages().collect::<Vec<_>>(),
+            [format!("message {MAX_NOTIFICATIONS}")]
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Semantic prompt marks (OSC 133)
+//!
+//! Shells set up for it bracket each prompt and command with marks, each
+//! ended by BEL or `ESC \`:
+//!
+//! - `ESC ] 133 ; A` where the prompt starts
+//! - `ESC ] 133 ; B` where the prompt ends and typing starts
+//! - `ESC ] 133 ; C` where the command's output starts, optionally followed
+//!   by `; command`
+//! - `ESC ] 133 ; D ; exit_code` where the command finished
+//!
+//! Once a session has sent marks they say exactly when a command finished, so
+//! the prompt heuristic in the terminal is no longer needed for it. A
+//! [`MarkLog`] keeps each mark's position in the session's output for
+//! navigating between commands.
+
+/// Introducer shared by all marks
+const OSC133: &str = "\x1b]133;";
+
+/// One OSC 133 mark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mark {
+    /// `A`: the prompt starts
+    PromptStart,
+    /// `B`: the prompt ends and the command line starts
+    PromptEnd,
+    /// `C`: the command runs; its output follows
+    CommandStart {
+        /// Command text, when the shell sends it
+        command: Option<String>,
+    },
+    /// `D`: the command finished
+    CommandEnd {
+        /// Exit code, when the shell sends one
+        exit_code: Option<i32>,
+    },
+}
+
+impl Mark {
+    /// Parse the text between `ESC ] 133 ;` and the terminator
+    fn parse(body: &str) -> Option<Self> {
+        let (kind, rest) = match body.split_once(';') {
+            Some((kind, rest)) => (kind, Some(rest)),
+            None => (body, None),
+        };
+        let text = rest.filter(|text| !text.is_empty());
+        match kind {
+            "A" => Some(Self::PromptStart),
+            "B" => Some(Self::PromptEnd),
+            "C" => Some(Self::CommandStart {
+                command: text.map(str::to_string),
+            }),
+            "D" => Some(Self::CommandEnd {
+                exit_code: text.and_then(|code| code.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the mark means the previous command has finished
+    ///
+    /// A new prompt implies it even if the shell sends no `D`.
+    #[must_use]
+    pub fn ends_command(&self) -> bool {
+        matches!(self, Self::PromptStart | Self::CommandEnd { .. })
+    }
+}
+
+/// Complete marks in `output`, with the byte offset each sequence starts at
+///
+/// Unterminated and unknown marks are skipped.
+#[must_use]
+pub fn parse_marks(output: &str) -> Vec<(usize, Mark)> {
+    output
+        .match_indices(OSC133)
+        .filter_map(|(start, _)| {
+            let body = &output[start + OSC133.len()..];
+            let end = body.find(['\x07', '\x1b'])?;
+            Mark::parse(&body[..end]).map(|mark| (start, mark))
+        })
+        .collect()
+}
+
+/// One prompt and the command run from it, as byte offsets into the output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// Where the prompt starts, if its `A` mark was seen
+    pub prompt: Option<usize>,
+    /// Where the command line starts
+    pub input: Option<usize>,
+    /// Where the command's output starts
+    pub output: Option<usize>,
+    /// Where the command finished
+    pub end: Option<usize>,
+    /// Command text sent with the `C` mark
+    pub command: Option<String>,
+    /// Exit code sent with the `D` mark
+    pub exit_code: Option<i32>,
+}
+
+/// Where commands' output starts in a session's buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputStarts {
+    /// Offsets of `C` marks, on the first line of output
+    Marked(Vec<usize>),
+    /// Buffer lengths when commands were submitted, still on the prompt line
+    Submitted(Vec<usize>),
+}
+
+/// Marks seen in one session, positioned in its output buffer
+#[derive(Debug, Clone, Default)]
+pub struct MarkLog {
+    /// Marks in output order, with offsets into the buffer
+    marks: Vec<(usize, Mark)>,
+    /// Whether the session has sent any mark, even if trimmed since
+    active: bool,
+    /// Buffer lengths when the user submitted commands, for shells without marks
+    submitted: Vec<usize>,
+}
+
+impl MarkLog {
+    /// Add marks parsed from output that was appended to the buffer at `base`
+    pub fn record(&mut self, base: usize, marks: Vec<(usize, Mark)>) {
+        self.active |= !marks.is_empty();
+        self.marks.extend(
+            marks
+                .into_iter()
+                .map(|(offset, mark)| (base + offset, mark)),
+        );
+    }
+
+    /// Shift offsets after `removed` bytes were dropped from the buffer's start
+    ///
+    /// Marks inside the dropped bytes are forgotten.
+    pub fn trim_front(&mut self, removed: usize) {
+        self.marks.retain(|(offset, _)| *offset >= removed);
+        for (offset, _) in &mut self.marks {
+            *offset -= removed;
+        }
+        self.submitted.retain(|offset| *offset >= removed);
+        for offset in &mut self.submitted {
+            *offset -= removed;
+        }
+    }
+
+    /// Shift offsets after `added` bytes were put back at the buffer's start
+    pub fn shift_back(&mut self, added: usize) {
+        for (offset, _) in &mut self.marks {
+            *offset += added;
+        }
+        for offset in &mut self.submitted {
+            *offset += added;
+        }
+    }
+
+    /// Note that a command was submitted when the buffer held `offset` bytes
+    pub fn record_submitted(&mut self, offset: usize) {
+        self.submitted.push(offset);
+    }
+
+    /// Where each command's output starts, oldest first
+    ///
+    /// `C` marks are used once the shell has sent any; until then the
+    /// positions at which commands were submitted stand in for them.
+    #[must_use]
+    pub fn output_starts(&self) -> OutputStarts {
+        let marked: Vec<usize> = self
+            .marks
+            .iter()
+            .filter(|(_, mark)| matches!(mark, Mark::CommandStart { .. }))
+            .map(|(offset, _)| *offset)
+            .collect();
+        if marked.is_empty() {
+            OutputStarts::Submitted(self.submitted.clone())
+        } else {
+            OutputStarts::Marked(marked)
+        }
+    }
+
+    /// Whether the session uses semantic prompts
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Marks in output order, with offsets into the buffer
+    #[must_use]
+    pub fn marks(&self) -> &[(usize, Mark)] {
+        &self.marks
+    }
+
+    /// The prompts and commands the marks delimit, oldest first
+    ///
+    /// A block starts at a prompt, or at any mark following a finished command
+    /// if the shell sent no prompt mark.
+    #[must_use]
+    pub fn commands(&self) -> Vec<CommandBlock> {
+        let mut blocks: Vec<CommandBlock> = Vec::new();
+        for (offset, mark) in &self.marks {
+            let offset = Some(*offset);
+            let open = blocks.last().is_some_and(|block| block.end.is_none());
+            if matches!(mark, Mark::PromptStart) || !open {
+                blocks.push(CommandBlock::default());
+            }
+            let Some(block) = blocks.last_mut() else {
+                continue;
+            };
+            match mark {
+                Mark::PromptStart => block.prompt = offset,
+                Mark::PromptEnd => block.input = offset,
+                Mark::CommandStart { command } => {
+                    block.output = offset;
+                    block.command.clone_from(command);
+                }
+                Mark::CommandEnd { exit_code } => {
+                    block.end = offset;
+                    block.exit_code = *exit_code;
+                }
+            }
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_marks() {
+        let output = concat!(
+            "\x1b]133;A\x07$ \x1b]133;B\x07",
+            "\x1b]133;C;ls -la\x1b\\",
+            "total 0\r\n",
+            "\x1b]133;D;2\x07",
+            "\x1b]133;D\x07",
+            "\x1b]133;Z\x07",
+            "\x1b]133;A",
+        );
+        let marks: Vec<Mark> = parse_marks(output).into_iter().map(|(_, m)| m).collect();
+        assert_eq!(
+            marks,
+            vec![
+                Mark::PromptStart,
+                Mark::PromptEnd,
+                Mark::CommandStart {
+                    command: Some("ls -la".to_string())
+                },
+                Mark::CommandEnd { exit_code: Some(2) },
+                Mark::CommandEnd { exit_code: None },
+            ]
+        );
+        assert_eq!(parse_marks(output)[1].0, output.find("\x1b]133;B").unwrap());
+    }
+
+    #[test]
+    fn test_command_boundaries() {
+        let first = "\x1b]133;A\x07$ \x1b]133;B\x07\x1b]133;C;make\x07building\r\n";
+        let second = "\x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x07";
+        let mut log = MarkLog::default();
+        log.record(0, parse_marks(first));
+        log.record(first.len(), parse_marks(second));
+        assert!(log.is_active());
+
+        let at = |needle: &str| {
+            let all = format!("{first}{second}");
+            Some(all.match_indices(needle).next().unwrap().0)
+        };
+        let blocks = log.commands();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[0],
+            CommandBlock {
+                prompt: Some(0),
+                input: at("\x1b]133;B"),
+                output: at("\x1b]133;C"),
+                end: at("\x1b]133;D"),
+                command: Some("make".to_string()),
+                exit_code: Some(1),
+            }
+        );
+        // The next prompt is waiting for a command
+        assert_eq!(
+            blocks[1].prompt,
+            Some(first.len() + "\x1b]133;D;1\x07".len())
+        );
+        assert_eq!(blocks[1].output, None);
+        assert_eq!(blocks[1].end, None);
+    }
+
+    #[test]
+    fn test_commands_without_prompt_marks() {
+        let output = "\x1b]133;C\x07a\x1b]133;D;0\x07\x1b]133;C\x07b\x1b]133;D;3\x07";
+        let mut log = MarkLog::default();
+        log.record(0, parse_marks(output));
+
+        let blocks = log.commands();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].prompt, None);
+        assert_eq!(blocks[0].exit_code, Some(0));
+        assert_eq!(blocks[1].exit_code, Some(3));
+    }
+
+    #[test]
+    fn test_trim_front_shifts_offsets() {
+        let mut log = MarkLog::default();
+        log.record(0, parse_marks("\x1b]133;A\x07$ \x1b]133;B\x07"));
+        log.trim_front(3);
+        assert_eq!(log.marks(), &[(7, Mark::PromptEnd)]);
+
+        // Semantic prompts stay on once every mark has been trimmed
+        log.trim_front(100);
+        assert!(log.marks().is_empty());
+        assert!(log.is_active());
+    }
+
+    #[test]
+    fn test_shift_back_undoes_trim_front() {
+        let mut log = MarkLog::default();
+        log.record(0, parse_marks("ab\x1b]133;A\x07$ \x1b]133;B\x07"));
+        let marks = log.marks().to_vec();
+        log.trim_front(2);
+        log.shift_back(2);
+        assert_eq!(log.marks(), marks.as_slice());
+    }
+
+    #[test]
+    fn test_output_starts_prefers_marks() {
+        let mut log = MarkLog::default();
+        log.record_submitted(5);
+        log.record_submitted(40);
+        assert_eq!(log.output_starts(), OutputStarts::Submitted(vec![5, 40]));
+
+        log.record(50, parse_marks("\x1b]133;A\x07$ \x1b]133;C\x07"));
+        assert_eq!(log.output_starts(), OutputStarts::Marked(vec![60]));
+
+        log.trim_front(20);
+        assert_eq!(log.output_starts(), OutputStarts::Marked(vec![40]));
+    }
+
+    #[test]
+    fn test_ends_command() {
+        assert!(Mark::PromptStart.ends_command());
+        assert!(Mark::CommandEnd { exit_code: None }.ends_command());
+        assert!(!Mark::PromptEnd.ends_command());
+        assert!(!Mark::CommandStart { command: None }.ends_command());
+    }
+}
@@ -0,0 +1,112 @@
+//! Clipboard writes (OSC 52)
+//!
+//! Programs such as tmux and vim set the clipboard by printing
+//! `ESC ] 52 ; Pc ; Pd` ended by BEL or `ESC \`, where `Pc` names the
+//! selections to set and `Pd` is the text in base64. Every write goes to the
+//! system clipboard whatever `Pc` says. Queries (a `Pd` of `?`) are never
+//! answered, as that would let any program read the clipboard, and payloads
+//! that aren't valid base64 or UTF-8 are ignored.
+//!
+//! Writes are only honored with `terminal.allow_osc52` enabled.
+
+/// Introducer of an OSC 52 request
+const OSC52: &str = "\x1b]52;";
+
+/// The text of the last valid clipboard write in `output`
+#[must_use]
+pub fn last_clipboard_text(output: &str) -> Option<String> {
+    output
+        .match_indices(OSC52)
+        .filter_map(|(start, _)| {
+            let body = &output[start + OSC52.len()..];
+            let end = body.find(['\x07', '\x1b'])?;
+            let (_, data) = body[..end].split_once(';')?;
+            String::from_utf8(base64_decode(data)?).ok()
+        })
+        .last()
+}
+
+/// Decode standard base64, with or without padding
+///
+/// Returns `None` for characters outside the alphabet, padding anywhere but
+/// the end, or a final group too short to hold a byte.
+#[must_use]
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let data = text.trim_end_matches('=');
+    if text.len() - data.len() > 2 || data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut count = 0;
+    for byte in data.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | u32::from(value);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            decoded.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_decode() {
+        assert_eq!(base64_decode("aGVsbG8=").as_deref(), Some(&b"hello"[..]));
+        assert_eq!(base64_decode("aGVsbG8").as_deref(), Some(&b"hello"[..]));
+        assert_eq!(base64_decode("aGk=").as_deref(), Some(&b"hi"[..]));
+        assert_eq!(base64_decode("").as_deref(), Some(&b""[..]));
+        assert_eq!(
+            base64_decode("+/+/").as_deref(),
+            Some(&[0xfb, 0xff, 0xbf][..])
+        );
+
+        assert_eq!(base64_decode("aGk*"), None);
+        assert_eq!(base64_decode("aG=k"), None);
+        assert_eq!(base64_decode("aGk==="), None);
+        assert_eq!(base64_decode("aGVsb"), None);
+    }
+
+    #[test]
+    fn test_last_clipboard_text() {
+        assert_eq!(
+            last_clipboard_text("\x1b]52;c;Y2FyZ28gdGVzdA==\x07").as_deref(),
+            Some("cargo test")
+        );
+        // String terminator, and an empty selection parameter
+        assert_eq!(
+            last_clipboard_text("\x1b]52;;Y2Fmw6k=\x1b\\").as_deref(),
+            Some("café")
+        );
+        // The latest valid write wins
+        assert_eq!(
+            last_clipboard_text(concat!(
+                "\x1b]52;c;Zmlyc3Q=\x07",
+                "\x1b]52;c;c2Vjb25k\x07",
+                "\x1b]52;c;not base64!\x07",
+            ))
+            .as_deref(),
+            Some("second")
+        );
+
+        // Queries, bad payloads and unterminated requests are ignored
+        assert_eq!(last_clipboard_text("\x1b]52;c;?\x07"), None);
+        assert_eq!(last_clipboard_text("\x1b]52;c;/w==\x07"), None);
+        assert_eq!(last_clipboard_text("\x1b]52;c;aGk="), None);
+        assert_eq!(last_clipboard_text("\x1b]52;aGk=\x07"), None);
+    }
+}
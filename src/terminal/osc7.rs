@@ -0,0 +1,154 @@
+//! Working directory reports (OSC 7)
+//!
+//! Shells set up for it print `ESC ] 7 ; file://host/path` ended by BEL or
+//! `ESC \` whenever the directory changes. The path is percent-encoded, so
+//! `/home/me/My%20Files` stands for `/home/me/My Files`. The host is not
+//! checked. Reports with another scheme, such as `kitty-shell-cwd://`, are
+//! ignored; a bare absolute path is accepted as is.
+
+/// Introducer of an OSC 7 report
+const OSC7: &str = "\x1b]7;";
+
+/// The directory from the last well-formed OSC 7 report in `output`
+#[must_use]
+pub fn last_directory(output: &str) -> Option<String> {
+    output
+        .match_indices(OSC7)
+        .filter_map(|(start, _)| {
+            let body = &output[start + OSC7.len()..];
+            let end = body.find(['\x07', '\x1b'])?;
+            directory_from_uri(&body[..end])
+        })
+        .last()
+}
+
+/// The local path a `file://` URI or bare absolute path refers to
+///
+/// Returns `None` for other schemes, an empty path or a path that doesn't
+/// decode to UTF-8.
+#[must_use]
+pub fn directory_from_uri(uri: &str) -> Option<String> {
+    let encoded = if uri.starts_with('/') {
+        uri
+    } else {
+        let (scheme, rest) = uri.split_once("://")?;
+        if !scheme.eq_ignore_ascii_case("file") {
+            return None;
+        }
+        // Skip the host; the path starts at the next slash
+        &rest[rest.find('/')?..]
+    };
+
+    let path = percent_decode(encoded)?;
+    // `file:///C:/Users` names `C:/Users` on Windows
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    (!path.is_empty()).then_some(path)
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+///
+/// Returns `None` if the decoded bytes aren't UTF-8.
+#[must_use]
+pub fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("/tmp/plain").as_deref(), Some("/tmp/plain"));
+        assert_eq!(
+            percent_decode("/home/me/My%20Files").as_deref(),
+            Some("/home/me/My Files")
+        );
+        // Multi-byte UTF-8 and lower-case hex
+        assert_eq!(percent_decode("/caf%c3%a9").as_deref(), Some("/café"));
+        // Malformed escapes are kept literally
+        assert_eq!(percent_decode("/100%").as_deref(), Some("/100%"));
+        assert_eq!(percent_decode("/a%zzb%4").as_deref(), Some("/a%zzb%4"));
+        // Bytes that aren't UTF-8
+        assert_eq!(percent_decode("/%ff"), None);
+    }
+
+    #[test]
+    fn test_directory_from_uri() {
+        assert_eq!(
+            directory_from_uri("file://host/home/user").as_deref(),
+            Some("/home/user")
+        );
+        assert_eq!(
+            directory_from_uri("file:///home/user").as_deref(),
+            Some("/home/user")
+        );
+        assert_eq!(
+            directory_from_uri("FILE://host/tmp/a%20b").as_deref(),
+            Some("/tmp/a b")
+        );
+        assert_eq!(
+            directory_from_uri("file:///C:/Users/me").as_deref(),
+            Some("C:/Users/me")
+        );
+        assert_eq!(
+            directory_from_uri("/src/furnace").as_deref(),
+            Some("/src/furnace")
+        );
+
+        assert_eq!(directory_from_uri("kitty-shell-cwd://host/tmp"), None);
+        assert_eq!(directory_from_uri("https://example.com/tmp"), None);
+        assert_eq!(directory_from_uri("file://host"), None);
+        assert_eq!(directory_from_uri("relative/path"), None);
+        assert_eq!(directory_from_uri(""), None);
+    }
+
+    #[test]
+    fn test_last_directory() {
+        assert_eq!(
+            last_directory("\x1b]7;file://host/tmp\x07$ ").as_deref(),
+            Some("/tmp")
+        );
+        // String terminator instead of BEL
+        assert_eq!(
+            last_directory("\x1b]7;file://host/var/log\x1b\\$ ").as_deref(),
+            Some("/var/log")
+        );
+        // The latest report wins; ignored ones don't hide earlier reports
+        assert_eq!(
+            last_directory(concat!(
+                "\x1b]7;file://host/a\x07ls\r\n",
+                "\x1b]7;file://host/b\x07",
+                "\x1b]7;kitty-shell-cwd://host/c\x07",
+            ))
+            .as_deref(),
+            Some("/b")
+        );
+        // Unterminated reports are ignored
+        assert_eq!(last_directory("\x1b]7;file://host/tmp"), None);
+        assert_eq!(last_directory("no report"), None);
+    }
+}
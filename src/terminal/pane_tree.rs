@@ -0,0 +1,363 @@
+//! Split pane layout
+//!
+//! The content area is divided by a binary tree. Each split cuts its area in
+//! two, top/bottom or left/right, and each leaf is a pane showing one session.
+//! A lone leaf is the unsplit terminal. Panes are numbered in leaf order, first
+//! child before second, which is also the order focus cycles through them.
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Share of a split the smaller side keeps at least
+pub const MIN_SPLIT_RATIO: f32 = 0.1;
+pub const MAX_SPLIT_RATIO: f32 = 0.9;
+
+/// How a split cuts its area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Top/bottom split
+    Horizontal,
+    /// Left/right split
+    Vertical,
+}
+
+/// Nested splits with a session at each leaf
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaneTree {
+    /// A pane showing the session with this index
+    Leaf(usize),
+    /// An area cut in two
+    Split {
+        direction: SplitDirection,
+        /// Share of the area the first child takes
+        ratio: f32,
+        first: Box<PaneTree>,
+        second: Box<PaneTree>,
+    },
+}
+
+impl Default for PaneTree {
+    fn default() -> Self {
+        Self::Leaf(0)
+    }
+}
+
+impl PaneTree {
+    /// Whether the area is cut into more than one pane
+    #[must_use]
+    pub fn is_split(&self) -> bool {
+        matches!(self, Self::Split { .. })
+    }
+
+    /// Number of panes
+    #[must_use]
+    pub fn leaf_count(&self) -> usize {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Split { first, second, .. } => first.leaf_count() + second.leaf_count(),
+        }
+    }
+
+    /// Session shown in each pane, in leaf order
+    #[must_use]
+    pub fn leaves(&self) -> Vec<usize> {
+        let mut sessions = Vec::with_capacity(self.leaf_count());
+        self.collect_leaves(&mut sessions);
+        sessions
+    }
+
+    fn collect_leaves(&self, sessions: &mut Vec<usize>) {
+        match self {
+            Self::Leaf(session) => sessions.push(*session),
+            Self::Split { first, second, .. } => {
+                first.collect_leaves(sessions);
+                second.collect_leaves(sessions);
+            }
+        }
+    }
+
+    /// Show `sessions` in the panes, in leaf order
+    ///
+    /// Panes past the end of `sessions` keep what they show.
+    pub fn set_leaves(&mut self, sessions: &[usize]) {
+        self.assign_leaves(&mut sessions.iter().copied());
+    }
+
+    fn assign_leaves(&mut self, sessions: &mut impl Iterator<Item = usize>) {
+        match self {
+            Self::Leaf(session) => {
+                if let Some(next) = sessions.next() {
+                    *session = next;
+                }
+            }
+            Self::Split { first, second, .. } => {
+                first.assign_leaves(sessions);
+                second.assign_leaves(sessions);
+            }
+        }
+    }
+
+    /// Screen area of each pane when the tree fills `area`, in leaf order
+    #[must_use]
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let mut rects = Vec::with_capacity(self.leaf_count());
+        self.collect_rects(area, &mut rects);
+        rects
+    }
+
+    fn collect_rects(&self, area: Rect, rects: &mut Vec<Rect>) {
+        match self {
+            Self::Leaf(_) => rects.push(area),
+            Self::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => {
+                let [first_area, second_area] = split_area(area, *direction, *ratio);
+                first.collect_rects(first_area, rects);
+                second.collect_rects(second_area, rects);
+            }
+        }
+    }
+
+    /// Cut pane `leaf` in two, keeping its session first and showing `session` second
+    ///
+    /// Returns `false` if there is no such pane.
+    pub fn split_leaf(&mut self, leaf: usize, direction: SplitDirection, session: usize) -> bool {
+        let Some(node) = self.leaf_mut(leaf) else {
+            return false;
+        };
+        let existing = std::mem::take(node);
+        *node = Self::Split {
+            direction,
+            ratio: 0.5,
+            first: Box::new(existing),
+            second: Box::new(Self::Leaf(session)),
+        };
+        true
+    }
+
+    fn leaf_mut(&mut self, leaf: usize) -> Option<&mut Self> {
+        match self {
+            Self::Leaf(_) => (leaf == 0).then_some(self),
+            Self::Split { first, second, .. } => {
+                let first_count = first.leaf_count();
+                if leaf < first_count {
+                    first.leaf_mut(leaf)
+                } else {
+                    second.leaf_mut(leaf - first_count)
+                }
+            }
+        }
+    }
+
+    /// Close pane `leaf`, giving its area to its sibling
+    ///
+    /// Returns `false` for the last pane or one that doesn't exist.
+    pub fn remove_leaf(&mut self, leaf: usize) -> bool {
+        let Self::Split { first, second, .. } = self else {
+            return false;
+        };
+        let first_count = first.leaf_count();
+        let (target, sibling, leaf) = if leaf < first_count {
+            (first, second, leaf)
+        } else {
+            (second, first, leaf - first_count)
+        };
+        if target.is_split() {
+            return target.remove_leaf(leaf);
+        }
+        if leaf != 0 {
+            return false;
+        }
+        let sibling = std::mem::take(&mut **sibling);
+        *self = sibling;
+        true
+    }
+
+    /// Forget a closed session: its pane closes and later sessions move down one index
+    ///
+    /// Returns the pane that was closed, if the session was shown in one and
+    /// it wasn't the last pane.
+    pub fn remove_session(&mut self, session: usize) -> Option<usize> {
+        let leaf = self.leaves().iter().position(|&shown| shown == session);
+        let removed = leaf.filter(|&leaf| self.remove_leaf(leaf));
+        let sessions: Vec<usize> = self
+            .leaves()
+            .into_iter()
+            .map(|shown| if shown > session { shown - 1 } else { shown })
+            .collect();
+        self.set_leaves(&sessions);
+        removed
+    }
+
+    /// Grow pane `leaf` by `delta` of the split it was cut from, or shrink it if negative
+    ///
+    /// Returns `false` for the only pane or one that doesn't exist.
+    pub fn resize_leaf(&mut self, leaf: usize, delta: f32) -> bool {
+        let Self::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        else {
+            return false;
+        };
+        let first_count = first.leaf_count();
+        // The ratio is the first child's share, so growing the second lowers it
+        let (target, leaf, sign) = if leaf < first_count {
+            (first, leaf, 1.0)
+        } else {
+            (second, leaf - first_count, -1.0)
+        };
+        if target.is_split() {
+            return target.resize_leaf(leaf, delta);
+        }
+        if leaf != 0 {
+            return false;
+        }
+        *ratio = adjust_split_ratio(*ratio, sign * delta);
+        true
+    }
+}
+
+/// Cut `area` in two with the first part taking `ratio` of it
+#[must_use]
+pub fn split_area(area: Rect, direction: SplitDirection, ratio: f32) -> [Rect; 2] {
+    let parts = match direction {
+        SplitDirection::Horizontal => {
+            // Top/bottom split
+            let split_height = (area.height as f32 * ratio) as u16;
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(split_height), Constraint::Min(0)])
+                .split(area)
+        }
+        SplitDirection::Vertical => {
+            // Left/right split
+            let split_width = (area.width as f32 * ratio) as u16;
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(split_width), Constraint::Min(0)])
+                .split(area)
+        }
+    };
+    [parts[0], parts[1]]
+}
+
+/// Change a split ratio by `delta`, kept within the allowed range
+///
+/// The result is rounded to whole percents so repeated steps don't drift.
+#[must_use]
+pub fn adjust_split_ratio(ratio: f32, delta: f32) -> f32 {
+    ((ratio + delta) * 100.0)
+        .round()
+        .clamp(MIN_SPLIT_RATIO * 100.0, MAX_SPLIT_RATIO * 100.0)
+        / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Session 0 on the left; 1 above 2 on the right
+    fn three_panes() -> PaneTree {
+        let mut tree = PaneTree::Leaf(0);
+        assert!(tree.split_leaf(0, SplitDirection::Vertical, 1));
+        assert!(tree.split_leaf(1, SplitDirection::Horizontal, 2));
+        tree
+    }
+
+    #[test]
+    fn test_single_pane_fills_area() {
+        let area = Rect::new(0, 0, 80, 24);
+        let tree = PaneTree::default();
+        assert!(!tree.is_split());
+        assert_eq!(tree.leaves(), vec![0]);
+        assert_eq!(tree.layout(area), vec![area]);
+    }
+
+    #[test]
+    fn test_three_pane_layout() {
+        let tree = three_panes();
+        assert_eq!(tree.leaves(), vec![0, 1, 2]);
+        assert_eq!(
+            tree.layout(Rect::new(0, 1, 80, 24)),
+            vec![
+                Rect::new(0, 1, 40, 24),
+                Rect::new(40, 1, 40, 12),
+                Rect::new(40, 13, 40, 12),
+            ]
+        );
+
+        // Ratios apply within each split's own area
+        let mut tree = three_panes();
+        assert!(tree.resize_leaf(0, 0.25));
+        assert!(tree.resize_leaf(2, 0.25));
+        assert_eq!(
+            tree.layout(Rect::new(0, 0, 80, 20)),
+            vec![
+                Rect::new(0, 0, 60, 20),
+                Rect::new(60, 0, 20, 5),
+                Rect::new(60, 5, 20, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_panes() {
+        let mut tree = three_panes();
+        tree.resize_leaf(0, 0.1);
+
+        // The sibling takes the closed pane's area
+        assert!(tree.remove_leaf(1));
+        assert_eq!(tree.leaves(), vec![0, 2]);
+        assert_eq!(
+            tree.layout(Rect::new(0, 0, 10, 10)),
+            vec![Rect::new(0, 0, 6, 10), Rect::new(6, 0, 4, 10)]
+        );
+
+        assert!(!tree.remove_leaf(2));
+        assert!(tree.remove_leaf(0));
+        assert_eq!(tree, PaneTree::Leaf(2));
+        assert!(!tree.remove_leaf(0));
+    }
+
+    #[test]
+    fn test_remove_session_shifts_indices() {
+        let mut tree = three_panes();
+        assert_eq!(tree.remove_session(0), Some(0));
+        assert_eq!(tree.leaves(), vec![0, 1]);
+
+        // Sessions not on screen only shift the others
+        let mut tree = three_panes();
+        tree.set_leaves(&[0, 2, 4]);
+        assert_eq!(tree.remove_session(3), None);
+        assert_eq!(tree.leaves(), vec![0, 2, 3]);
+
+        // The last pane stays, showing the tab that takes the closed one's place
+        let mut tree = PaneTree::Leaf(1);
+        assert_eq!(tree.remove_session(1), None);
+        assert_eq!(tree.leaves(), vec![1]);
+    }
+
+    #[test]
+    fn test_adjust_split_ratio() {
+        assert!((adjust_split_ratio(0.5, 0.05) - 0.55).abs() < f32::EPSILON);
+        assert!((adjust_split_ratio(0.5, -0.05) - 0.45).abs() < f32::EPSILON);
+        // Clamped at both ends
+        assert!((adjust_split_ratio(0.88, 0.05) - 0.9).abs() < f32::EPSILON);
+        assert!((adjust_split_ratio(0.1, -0.05) - 0.1).abs() < f32::EPSILON);
+
+        // Many steps land back on the starting ratio
+        let mut ratio = 0.5;
+        for _ in 0..7 {
+            ratio = adjust_split_ratio(ratio, 0.05);
+        }
+        for _ in 0..7 {
+            ratio = adjust_split_ratio(ratio, -0.05);
+        }
+        assert!((ratio - 0.5).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,170 @@
+//! Confirmation for multi-line pastes
+//!
+//! A pasted newline acts like pressing Enter, so pasting several lines can run
+//! commands before they have been read. With `terminal.confirm_multiline_paste`
+//! enabled such pastes are held as a [`PendingPaste`] and only written to the
+//! shell once the user confirms them.
+//!
+//! Programs that turn on bracketed paste mode get pastes between
+//! [`PASTE_START`] and [`PASTE_END`], see [`bracket`].
+
+/// Number of pasted lines shown in the confirmation preview
+pub const PREVIEW_LINES: usize = 8;
+
+/// Sent before pasted text in bracketed paste mode
+pub const PASTE_START: &str = "\x1b[200~";
+
+/// Sent after pasted text in bracketed paste mode
+pub const PASTE_END: &str = "\x1b[201~";
+
+/// Text to send for a paste, wrapped in bracket markers when `bracketed`
+///
+/// End markers inside the text are removed, so pasted text can't end the
+/// paste early and have the rest taken as typing. Removal repeats until none
+/// are left, since taking one out can join the pieces of another.
+#[must_use]
+pub fn bracket(mut text: String, bracketed: bool) -> String {
+    if !bracketed {
+        return text;
+    }
+    while text.contains(PASTE_END) {
+        text = text.replace(PASTE_END, "");
+    }
+    format!("{PASTE_START}{text}{PASTE_END}")
+}
+
+/// Check whether a paste must be confirmed before it reaches the shell
+#[must_use]
+pub fn needs_confirmation(text: &str, confirm_multiline: bool) -> bool {
+    confirm_multiline && text.contains(['\n', '\r'])
+}
+
+/// A paste waiting for the user to accept or decline it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingPaste {
+    text: String,
+}
+
+impl PendingPaste {
+    /// Hold `text` until the user decides
+    #[must_use]
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+
+    /// Take the pasted text to write it to the shell
+    #[must_use]
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Number of lines in the paste
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.text.lines().count()
+    }
+
+    /// Up to `max_lines` lines for display, with control characters made visible
+    ///
+    /// When lines are left out, a final `… N more lines` entry says how many.
+    #[must_use]
+    pub fn preview(&self, max_lines: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .text
+            .lines()
+            .take(max_lines)
+            .map(|line| {
+                line.chars()
+                    .map(|ch| match ch {
+                        '\t' => ' ',
+                        ch if ch.is_control() => '�',
+                        ch => ch,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let hidden = self.line_count().saturating_sub(max_lines);
+        if hidden > 0 {
+            lines.push(format!("… {hidden} more line{}", plural(hidden)));
+        }
+        lines
+    }
+
+    /// Prompt text for the status bar mode indicator
+    #[must_use]
+    pub fn text(&self) -> String {
+        let count = self.line_count();
+        format!(
+            " PASTE {count} LINE{}? (y/n) ",
+            plural(count).to_uppercase()
+        )
+    }
+
+    /// Key hints for the status bar
+    #[must_use]
+    pub fn hints(&self) -> &'static str {
+        " y/Enter: Paste │ n/Esc: Discard "
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_confirmation() {
+        assert!(needs_confirmation("ls\nrm -rf build\n", true));
+        assert!(needs_confirmation("echo hi\n", true));
+        assert!(needs_confirmation("one\r\ntwo", true));
+        assert!(needs_confirmation("one\rtwo", true));
+        assert!(!needs_confirmation("git status", true));
+        assert!(!needs_confirmation("", true));
+        // Disabled in config: everything goes straight through
+        assert!(!needs_confirmation("ls\nrm -rf build\n", false));
+    }
+
+    #[test]
+    fn test_bracket_wraps_only_when_asked() {
+        assert_eq!(bracket("ls\n".into(), false), "ls\n");
+        assert_eq!(bracket("ls\n".into(), true), "\x1b[200~ls\n\x1b[201~");
+        assert_eq!(bracket(String::new(), true), "\x1b[200~\x1b[201~");
+        // A smuggled end marker can't break out of the paste
+        assert_eq!(
+            bracket("a\x1b[201~rm -rf ~\n".into(), true),
+            "\x1b[200~arm -rf ~\n\x1b[201~"
+        );
+        // Nor can one that only forms once an inner marker is removed
+        assert_eq!(
+            bracket("\x1b[20\x1b[201~1~".into(), true),
+            "\x1b[200~\x1b[201~"
+        );
+        assert_eq!(
+            bracket("a\x1b[2\x1b[20\x1b[201~1~01~b".into(), true),
+            "\x1b[200~ab\x1b[201~"
+        );
+    }
+
+    #[test]
+    fn test_preview_truncates_and_shows_controls() {
+        let paste = PendingPaste::new("a\tb\nc\x1b[2Jd\n3\n4\n5\n".to_string());
+
+        assert_eq!(paste.line_count(), 5);
+        assert_eq!(paste.preview(2), vec!["a b", "c�[2Jd", "… 3 more lines"]);
+        assert_eq!(paste.preview(PREVIEW_LINES).len(), 5);
+        assert_eq!(paste.text(), " PASTE 5 LINES? (y/n) ");
+        assert_eq!(
+            PendingPaste::new("x\n".into()).text(),
+            " PASTE 1 LINE? (y/n) "
+        );
+        assert_eq!(paste.into_text(), "a\tb\nc\x1b[2Jd\n3\n4\n5\n");
+    }
+}
@@ -0,0 +1,119 @@
+//! Background readers of each tab's shell output
+//!
+//! Reads from a PTY block until the shell prints something, so each shell is
+//! read on a task of its own for as long as its tab is open. Every reader
+//! sends into its own channel, and the event loop empties all of them each
+//! pass, background tabs included, so a shell is only held up while the
+//! event loop is busy. Readers are found by the session's id rather than the
+//! tab's index, so moving or closing tabs never sends one shell's output to
+//! another's tab.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::shell::ShellSession;
+
+/// Reads a reader keeps waiting before it stops reading
+pub const PENDING_READS: usize = 64;
+
+/// A task reading one shell's output
+///
+/// The task is stopped when the reader is dropped.
+pub struct OutputReader {
+    output: mpsc::Receiver<Vec<u8>>,
+    task: JoinHandle<()>,
+}
+
+impl OutputReader {
+    /// Start reading `session`'s output `buffer_size` bytes at a time
+    #[must_use]
+    pub fn spawn(session: ShellSession, buffer_size: usize) -> Self {
+        let (output_tx, output) = mpsc::channel(PENDING_READS);
+        let task = tokio::spawn(async move {
+            let mut read_buf = vec![0u8; buffer_size];
+            loop {
+                match session.read_output(&mut read_buf).await {
+                    Ok(n) if n > 0 => {
+                        // Only whole characters are sent; a split one waits for the next read
+                        let text = session.decode_output(&read_buf[..n]);
+                        if !text.is_empty() && output_tx.send(text.into_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                    Err(e) => {
+                        warn!("Failed to read from shell: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        Self { output, task }
+    }
+
+    /// The next waiting read, if any
+    pub fn try_recv(&mut self) -> Option<Vec<u8>> {
+        self.output.try_recv().ok()
+    }
+
+    /// Wait for the next read; `None` once the reader has stopped
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        self.output.recv().await
+    }
+}
+
+impl Drop for OutputReader {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A reader for every open tab's shell
+pub struct OutputReaders {
+    readers: HashMap<u64, OutputReader>,
+    buffer_size: usize,
+}
+
+impl OutputReaders {
+    /// No readers yet; each reads `buffer_size` bytes at a time
+    #[must_use]
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            readers: HashMap::new(),
+            buffer_size,
+        }
+    }
+
+    /// Start readers for new shells and stop those of shells no longer open
+    pub fn sync(&mut self, sessions: &[ShellSession]) {
+        self.readers
+            .retain(|id, _| sessions.iter().any(|session| session.id() == *id));
+        let buffer_size = self.buffer_size;
+        for session in sessions {
+            self.readers
+                .entry(session.id())
+                .or_insert_with(|| OutputReader::spawn(session.clone(), buffer_size));
+        }
+    }
+
+    /// The reader of the shell with id `session`
+    pub fn get_mut(&mut self, session: u64) -> Option<&mut OutputReader> {
+        self.readers.get_mut(&session)
+    }
+
+    /// Number of shells being read
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.readers.len()
+    }
+
+    /// Whether no shell is being read
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.readers.is_empty()
+    }
+}
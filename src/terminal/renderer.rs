@@ -0,0 +1,459 @@
+//! Rendering backends for terminal output
+//!
+//! [`Renderer`] is the drawing interface shared by the ratatui path
+//! ([`CpuRenderer`]) and the wgpu path ([`GpuFrameRenderer`], behind the `gpu`
+//! feature). Both draw the same ratatui UI; the GPU path draws it into a
+//! [`CellBackend`] and uploads the cells. The terminal picks one at startup
+//! with [`select_renderer`] and falls back to the CPU path if the window or
+//! GPU renderer then fails to initialize.
+
+use anyhow::{Context, Result};
+use ratatui::backend::{Backend, WindowSize};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::{Rect, Size};
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use std::io;
+use unicode_width::UnicodeWidthStr;
+
+use crate::gpu::{CellStyle, GpuCell};
+
+/// Whether this build includes the wgpu renderer
+pub const GPU_FEATURE: bool = cfg!(feature = "gpu");
+
+/// Rendering backend in use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererKind {
+    /// Ratatui drawing to the controlling TTY
+    Cpu,
+    /// Wgpu drawing to a window
+    Gpu,
+}
+
+/// Draws the terminal UI and the cursor
+pub trait Renderer {
+    /// Which backend this is
+    fn kind(&self) -> RendererKind;
+
+    /// Draw one frame of the UI with `ui`
+    ///
+    /// # Errors
+    /// Returns an error if the frame could not be drawn or presented
+    fn draw(&mut self, ui: &mut dyn FnMut(&mut Frame)) -> Result<()>;
+
+    /// Draw `lines` from the top-left corner and place the cursor at `(column, row)`
+    ///
+    /// # Errors
+    /// Returns an error if the frame could not be drawn or presented
+    fn draw_lines(&mut self, lines: &[Line<'_>], cursor: (u16, u16)) -> Result<()> {
+        self.draw(&mut |f| {
+            let area = f.size();
+            f.render_widget(Paragraph::new(lines.to_vec()), area);
+            f.set_cursor(
+                cursor.0.min(area.width.saturating_sub(1)),
+                cursor.1.min(area.height.saturating_sub(1)),
+            );
+        })
+    }
+}
+
+/// Choose the rendering backend
+///
+/// The GPU path needs hardware acceleration enabled and the `gpu` feature
+/// compiled in. Only then is `gpu_available` called to probe for an adapter.
+#[must_use]
+pub fn select_renderer(
+    hardware_acceleration: bool,
+    gpu_feature: bool,
+    gpu_available: impl FnOnce() -> bool,
+) -> RendererKind {
+    if hardware_acceleration && gpu_feature && gpu_available() {
+        RendererKind::Gpu
+    } else {
+        RendererKind::Cpu
+    }
+}
+
+/// Ratatui renderer for any ratatui backend (the TTY in production)
+pub struct CpuRenderer<B: Backend> {
+    terminal: ratatui::Terminal<B>,
+}
+
+impl<B: Backend> CpuRenderer<B> {
+    /// Wrap a ratatui backend
+    ///
+    /// # Errors
+    /// Returns an error if the backend's size cannot be queried
+    pub fn new(backend: B) -> Result<Self> {
+        let terminal =
+            ratatui::Terminal::new(backend).context("Failed to create terminal backend")?;
+        Ok(Self { terminal })
+    }
+
+    /// The wrapped ratatui terminal
+    pub fn terminal_mut(&mut self) -> &mut ratatui::Terminal<B> {
+        &mut self.terminal
+    }
+}
+
+impl<B: Backend> Renderer for CpuRenderer<B> {
+    fn kind(&self) -> RendererKind {
+        RendererKind::Cpu
+    }
+
+    fn draw(&mut self, ui: &mut dyn FnMut(&mut Frame)) -> Result<()> {
+        self.terminal
+            .draw(|f| ui(f))
+            .context("Failed to draw frame")?;
+        Ok(())
+    }
+}
+
+/// Ratatui backend that keeps the drawn cells in memory
+///
+/// The GPU renderer draws frames into one and uploads its cells.
+#[derive(Debug)]
+pub struct CellBackend {
+    buffer: Buffer,
+    cursor: (u16, u16),
+    cursor_visible: bool,
+}
+
+impl CellBackend {
+    /// Empty grid of `width` x `height` cells
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            buffer: Buffer::empty(Rect::new(0, 0, width, height)),
+            cursor: (0, 0),
+            cursor_visible: false,
+        }
+    }
+
+    /// Change the grid size; the next draw redraws everything
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.buffer.resize(Rect::new(0, 0, width, height));
+    }
+
+    /// The drawn cells
+    #[must_use]
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Where the cursor is, if the last frame showed it
+    #[must_use]
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor_visible.then_some(self.cursor)
+    }
+}
+
+impl Backend for CellBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let area = self.buffer.area;
+        for (x, y, cell) in content {
+            if x < area.width && y < area.height {
+                *self.buffer.get_mut(x, y) = cell.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.cursor = (x, y);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.buffer.reset();
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        Ok(self.buffer.area)
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: Size {
+                width: self.buffer.area.width,
+                height: self.buffer.area.height,
+            },
+            pixels: Size::default(),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Wgpu renderer for the same ratatui UI the CPU path draws
+///
+/// Frames are drawn into a [`CellBackend`] and its cells uploaded, so
+/// overlays, the tab bar and split panes look the same in a window as in the
+/// TTY.
+#[cfg(feature = "gpu")]
+pub struct GpuFrameRenderer {
+    gpu: crate::gpu::GpuRenderer,
+    terminal: ratatui::Terminal<CellBackend>,
+}
+
+#[cfg(feature = "gpu")]
+impl GpuFrameRenderer {
+    /// Draw through `gpu` on a grid of `cols` x `rows` cells
+    ///
+    /// # Errors
+    /// Returns an error if the ratatui terminal cannot be created
+    pub fn new(gpu: crate::gpu::GpuRenderer, cols: u16, rows: u16) -> Result<Self> {
+        let terminal = ratatui::Terminal::new(CellBackend::new(cols, rows))
+            .context("Failed to create terminal backend")?;
+        Ok(Self { gpu, terminal })
+    }
+
+    /// Resize the window surface to `width` x `height` pixels and the grid to `cols` x `rows` cells
+    pub fn resize(&mut self, width: u32, height: u32, cols: u16, rows: u16) {
+        self.gpu.resize(width, height);
+        self.terminal.backend_mut().resize(cols, rows);
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl Renderer for GpuFrameRenderer {
+    fn kind(&self) -> RendererKind {
+        RendererKind::Gpu
+    }
+
+    fn draw(&mut self, ui: &mut dyn FnMut(&mut Frame)) -> Result<()> {
+        self.terminal
+            .draw(|f| ui(f))
+            .context("Failed to draw frame")?;
+
+        let backend = self.terminal.backend();
+        let area = backend.buffer().area;
+        let cells = buffer_to_gpu_cells(
+            backend.buffer(),
+            backend.cursor(),
+            rgba(super::COLOR_REDDISH_GRAY),
+            rgba(super::COLOR_PURE_BLACK),
+        );
+        self.gpu
+            .update_cells(&cells, u32::from(area.width), u32::from(area.height));
+        self.gpu.render().context("GPU render error")
+    }
+}
+
+/// Convert an `(r, g, b)` color constant to normalized RGBA
+#[must_use]
+pub fn rgba((r, g, b): (u8, u8, u8)) -> [f32; 4] {
+    [
+        f32::from(r) / 255.0,
+        f32::from(g) / 255.0,
+        f32::from(b) / 255.0,
+        1.0,
+    ]
+}
+
+/// Map a span color to RGBA, using `default` for unset, reset and indexed colors
+#[must_use]
+pub fn color_to_rgba(color: Option<Color>, default: [f32; 4]) -> [f32; 4] {
+    let rgb = match color {
+        Some(Color::Rgb(r, g, b)) => (r, g, b),
+        Some(Color::Black) => (0, 0, 0),
+        Some(Color::Red) => (205, 49, 49),
+        Some(Color::Green) => (13, 188, 121),
+        Some(Color::Yellow) => (229, 229, 16),
+        Some(Color::Blue) => (36, 114, 200),
+        Some(Color::Magenta) => (188, 63, 188),
+        Some(Color::Cyan) => (17, 168, 205),
+        Some(Color::Gray) => (229, 229, 229),
+        Some(Color::DarkGray) => (102, 102, 102),
+        Some(Color::LightRed) => (241, 76, 76),
+        Some(Color::LightGreen) => (35, 209, 139),
+        Some(Color::LightYellow) => (245, 245, 67),
+        Some(Color::LightBlue) => (59, 142, 234),
+        Some(Color::LightMagenta) => (214, 112, 214),
+        Some(Color::LightCyan) => (41, 184, 219),
+        Some(Color::White) => (255, 255, 255),
+        Some(Color::Reset | Color::Indexed(_)) | None => return default,
+    };
+    rgba(rgb)
+}
+
+/// Convert drawn ratatui cells into a row-major GPU cell grid
+///
+/// Reset colors become `default_fg` and `default_bg`. The cell after a wide
+/// character is a space in its colors, and the cursor cell is drawn reversed.
+#[must_use]
+pub fn buffer_to_gpu_cells(
+    buffer: &Buffer,
+    cursor: Option<(u16, u16)>,
+    default_fg: [f32; 4],
+    default_bg: [f32; 4],
+) -> Vec<GpuCell> {
+    let mut cells: Vec<GpuCell> = buffer
+        .content
+        .iter()
+        .map(|cell| GpuCell {
+            char_code: cell.symbol().chars().next().unwrap_or(' ') as u32,
+            fg_color: color_to_rgba(Some(cell.fg), default_fg),
+            bg_color: color_to_rgba(Some(cell.bg), default_bg),
+            style: cell_style(cell.modifier),
+        })
+        .collect();
+
+    let width = usize::from(buffer.area.width);
+    for (idx, cell) in buffer.content.iter().enumerate() {
+        let spacer = idx + 1;
+        if cell.symbol().width() == 2 && spacer % width != 0 && spacer < cells.len() {
+            cells[spacer].fg_color = cells[idx].fg_color;
+            cells[spacer].bg_color = cells[idx].bg_color;
+        }
+    }
+
+    if let Some((x, y)) = cursor {
+        if x < buffer.area.width && y < buffer.area.height {
+            cells[buffer.index_of(x, y)]
+                .style
+                .insert(CellStyle::REVERSE);
+        }
+    }
+    cells
+}
+
+/// GPU style flags for ratatui text modifiers
+fn cell_style(modifier: Modifier) -> CellStyle {
+    let flags = [
+        (Modifier::BOLD, CellStyle::BOLD),
+        (Modifier::ITALIC, CellStyle::ITALIC),
+        (Modifier::UNDERLINED, CellStyle::UNDERLINE),
+        (Modifier::CROSSED_OUT, CellStyle::STRIKETHROUGH),
+        (
+            Modifier::SLOW_BLINK | Modifier::RAPID_BLINK,
+            CellStyle::BLINK,
+        ),
+        (Modifier::REVERSED, CellStyle::REVERSE),
+        (Modifier::DIM, CellStyle::DIM),
+        (Modifier::HIDDEN, CellStyle::HIDDEN),
+    ];
+    flags
+        .into_iter()
+        .filter(|(text, _)| modifier.intersects(*text))
+        .fold(CellStyle::empty(), |style, (_, flag)| style | flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::style::Style;
+    use ratatui::text::Span;
+
+    #[test]
+    fn test_select_renderer_without_gpu_feature_is_cpu() {
+        let kind = select_renderer(true, false, || panic!("no probe without the gpu feature"));
+        assert_eq!(kind, RendererKind::Cpu);
+    }
+
+    #[test]
+    fn test_select_renderer() {
+        assert_eq!(select_renderer(true, true, || true), RendererKind::Gpu);
+        // No adapter found
+        assert_eq!(select_renderer(true, true, || false), RendererKind::Cpu);
+        // Hardware acceleration turned off
+        assert_eq!(
+            select_renderer(false, true, || panic!("probe skipped")),
+            RendererKind::Cpu
+        );
+    }
+
+    #[test]
+    fn test_cpu_renderer_draws_lines_and_cursor() {
+        let mut renderer = CpuRenderer::new(TestBackend::new(10, 3)).unwrap();
+        assert_eq!(renderer.kind(), RendererKind::Cpu);
+
+        renderer
+            .draw_lines(&[Line::from("$ ls"), Line::from("a  b")], (4, 0))
+            .unwrap();
+
+        let terminal = renderer.terminal_mut();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..4).map(|x| buffer.get(x, 0).symbol()).collect();
+        assert_eq!(row, "$ ls");
+        assert_eq!(buffer.get(0, 1).symbol(), "a");
+        assert_eq!(terminal.get_cursor().unwrap(), (4, 0));
+    }
+
+    #[test]
+    fn test_cell_backend_keeps_frame_and_cursor() {
+        let mut terminal = ratatui::Terminal::new(CellBackend::new(6, 2)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(Paragraph::new("hi"), f.size()))
+            .unwrap();
+        assert_eq!(terminal.backend().buffer().get(1, 0).symbol(), "i");
+        assert_eq!(terminal.backend().cursor(), None);
+
+        terminal.draw(|f| f.set_cursor(2, 1)).unwrap();
+        assert_eq!(terminal.backend().cursor(), Some((2, 1)));
+
+        terminal.backend_mut().resize(8, 3);
+        terminal.draw(|_| {}).unwrap();
+        assert_eq!(terminal.backend().buffer().area, Rect::new(0, 0, 8, 3));
+    }
+
+    #[test]
+    fn test_buffer_to_gpu_cells_colors_and_wide_chars() {
+        let fg = rgba((1, 2, 3));
+        let bg = rgba((0, 0, 0));
+        let red = Style::default()
+            .fg(Color::Rgb(255, 0, 0))
+            .bg(Color::Blue)
+            .add_modifier(Modifier::BOLD);
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_line(
+            0,
+            0,
+            &Line::from(vec![
+                Span::styled("a", red),
+                Span::styled("日", red),
+                Span::raw("b"),
+            ]),
+            4,
+        );
+        buffer.set_string(0, 1, "xyz", Style::default());
+
+        let cells = buffer_to_gpu_cells(&buffer, Some((3, 1)), fg, bg);
+
+        assert_eq!(cells[0].char_code, 'a' as u32);
+        assert_eq!(cells[0].fg_color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(cells[0].bg_color, rgba((36, 114, 200)));
+        assert_eq!(cells[0].style, CellStyle::BOLD);
+        assert_eq!(cells[1].char_code, '日' as u32);
+        assert_eq!(cells[2].char_code, ' ' as u32);
+        assert_eq!(cells[2].bg_color, rgba((36, 114, 200)));
+        assert_eq!(cells[3].char_code, 'b' as u32);
+        assert_eq!(cells[3].fg_color, fg);
+        assert_eq!(cells[4].char_code, 'x' as u32);
+        assert_eq!(cells[6].char_code, 'z' as u32);
+        assert_eq!(cells[7].style, CellStyle::REVERSE);
+    }
+}
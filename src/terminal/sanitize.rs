@@ -0,0 +1,397 @@
+//! Filtering of risky escape sequences in shell output
+//!
+//! Programs can print escape sequences that do more than draw text: some ask
+//! the terminal to report state back as if it were typed, others carry
+//! payloads (clipboard writes, file transfers) Furnace never acts on. With
+//! `terminal.sanitize_output` enabled those sequences are removed before
+//! output reaches the buffer, hooks or shell integration.
+//!
+//! Kept:
+//! - OSC 0/1/2 (window title), OSC 7 (working directory), OSC 8 (hyperlinks)
+//!   and OSC 133 (command tracking), as listed in [`ALLOWED_OSC`]
+//! - Every CSI sequence (SGR colors, cursor movement, erasing, modes) except
+//!   the title reports `CSI 20 t` and `CSI 21 t`
+//! - Other two-byte escapes such as `ESC 7` / `ESC 8`
+//! - Sixel images (DCS `q`), which only draw
+//!
+//! Removed:
+//! - OSC sequences with any other number (e.g. OSC 52 clipboard access, which
+//!   callers can let through with [`sanitize_output_allowing`])
+//! - Other DCS strings, and SOS, PM and APC strings
+//!
+//! Both 7-bit (`ESC ]`) and 8-bit C1 (`U+009D`) introducers are recognized.
+//! A [`Sanitizer`] holds a sequence cut off by the end of a read back until
+//! the next one, so a removed sequence split across reads is removed whole.
+//! The one-shot functions drop a removed sequence left unterminated up to the
+//! end of the text.
+
+use std::borrow::Cow;
+
+/// OSC numbers passed through to the buffer
+pub const ALLOWED_OSC: &[&str] = &["0", "1", "2", "7", "8", "133"];
+
+/// Longest unfinished sequence held back for the next read
+///
+/// A longer one is passed on if kept, or dropped through its end if removed.
+const MAX_HELD: usize = 4096;
+
+/// Characters that can start an escape sequence
+const SEQUENCE_STARTS: [char; 7] = [
+    '\x1b', '\u{90}', '\u{98}', '\u{9b}', '\u{9d}', '\u{9e}', '\u{9f}',
+];
+
+/// Kind of escape sequence, from its introducer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sequence {
+    /// Operating System Command
+    Osc,
+    /// Device Control String
+    Dcs,
+    /// Start Of String, Privacy Message or Application Program Command
+    ControlString,
+    /// Control Sequence Introducer
+    Csi,
+    /// Any other escape
+    Other,
+}
+
+/// Remove risky escape sequences from `text`
+///
+/// Borrows the input unchanged when there is nothing to remove.
+#[must_use]
+pub fn sanitize_output(text: &str) -> Cow<'_, str> {
+    sanitize_output_allowing(text, &[])
+}
+
+/// Remove risky escape sequences from `text`, also keeping the OSC numbers in `extra_osc`
+#[must_use]
+pub fn sanitize_output_allowing<'a>(text: &'a str, extra_osc: &[&str]) -> Cow<'a, str> {
+    let (clean, unfinished) = sanitize_text(text, extra_osc);
+    match unfinished {
+        Some(start) if scan_sequence(&text[start..], extra_osc).keep => match clean {
+            Cow::Borrowed(_) => Cow::Borrowed(text),
+            Cow::Owned(clean) => Cow::Owned(clean + &text[start..]),
+        },
+        _ => clean,
+    }
+}
+
+/// Sanitizes the output of one tab's shell, read by read
+#[derive(Debug, Clone, Default)]
+pub struct Sanitizer {
+    /// Start of a sequence cut off by the end of the last read
+    partial: String,
+    /// Inside a removed string too long to hold back, dropped through its end
+    discarding: bool,
+}
+
+impl Sanitizer {
+    /// Nothing held back yet
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove risky escape sequences from the next read, also keeping the OSC
+    /// numbers in `extra_osc`
+    ///
+    /// A sequence the read ends in the middle of is held back and finished
+    /// with the next call.
+    pub fn sanitize<'a>(&mut self, mut output: &'a str, extra_osc: &[&str]) -> Cow<'a, str> {
+        if self.discarding {
+            let Some((end, term_len)) = string_end(output) else {
+                return Cow::Borrowed("");
+            };
+            self.discarding = false;
+            output = &output[end + term_len..];
+        }
+        let output = if self.partial.is_empty() {
+            Cow::Borrowed(output)
+        } else {
+            Cow::Owned(std::mem::take(&mut self.partial) + output)
+        };
+
+        match output {
+            Cow::Borrowed(text) => match sanitize_text(text, extra_osc) {
+                (clean, None) => clean,
+                (clean, Some(start)) => {
+                    Cow::Owned(self.hold(clean.into_owned(), &text[start..], extra_osc))
+                }
+            },
+            Cow::Owned(text) => {
+                let (clean, unfinished) = sanitize_text(&text, extra_osc);
+                let clean = clean.into_owned();
+                Cow::Owned(match unfinished {
+                    Some(start) => self.hold(clean, &text[start..], extra_osc),
+                    None => clean,
+                })
+            }
+        }
+    }
+
+    /// Hold back the unfinished sequence `tail` for the next read, or settle
+    /// it now if it is too long; returns `clean` with anything passed on
+    fn hold(&mut self, mut clean: String, tail: &str, extra_osc: &[&str]) -> String {
+        if tail.len() <= MAX_HELD {
+            self.partial = tail.to_string();
+        } else if scan_sequence(tail, extra_osc).keep {
+            clean.push_str(tail);
+        } else {
+            self.discarding = true;
+        }
+        clean
+    }
+}
+
+/// Remove risky escape sequences from `text`, stopping at an unfinished one
+///
+/// Returns the cleaned text before the unfinished sequence and where that
+/// sequence starts, if there is one.
+fn sanitize_text<'a>(text: &'a str, extra_osc: &[&str]) -> (Cow<'a, str>, Option<usize>) {
+    let Some(first) = text.find(SEQUENCE_STARTS) else {
+        return (Cow::Borrowed(text), None);
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut removed = false;
+    let mut offset = 0;
+    let mut next = Some(first);
+
+    while let Some(pos) = next {
+        let start = offset + pos;
+        out.push_str(&text[offset..start]);
+        let seq = &text[start..];
+        let scan = scan_sequence(seq, extra_osc);
+        if !scan.complete {
+            let clean = if removed {
+                Cow::Owned(out)
+            } else {
+                Cow::Borrowed(&text[..start])
+            };
+            return (clean, Some(start));
+        }
+        if scan.keep {
+            out.push_str(&seq[..scan.len]);
+        } else {
+            removed = true;
+        }
+        offset = start + scan.len;
+        next = text[offset..].find(SEQUENCE_STARTS);
+    }
+    out.push_str(&text[offset..]);
+
+    if removed {
+        (Cow::Owned(out), None)
+    } else {
+        (Cow::Borrowed(text), None)
+    }
+}
+
+/// Where the string body at the start of `body` ends, and the length of its terminator
+///
+/// Strings end at BEL, ST (`ESC \\` or U+009C), or are cut short by another
+/// ESC, which is not part of the string. `None` if the body runs to the end
+/// of `body`, including an ESC at the very end that may start an ST.
+fn string_end(body: &str) -> Option<(usize, usize)> {
+    let end = body.find(['\x07', '\x1b', '\u{9c}'])?;
+    let rest = &body[end..];
+    if rest.starts_with("\x1b\\") {
+        Some((end, 2))
+    } else if rest == "\x1b" {
+        None
+    } else if rest.starts_with('\x1b') {
+        Some((end, 0))
+    } else {
+        Some((end, rest.chars().next().map_or(0, char::len_utf8)))
+    }
+}
+
+/// One escape sequence found by [`scan_sequence`]
+struct Scan {
+    /// Length in bytes
+    len: usize,
+    /// Whether to pass it on
+    keep: bool,
+    /// Whether it ended before the text did
+    complete: bool,
+}
+
+/// The sequence at the start of `seq`, and whether to keep it
+fn scan_sequence(seq: &str, extra_osc: &[&str]) -> Scan {
+    let (kind, intro_len) = introducer(seq);
+    let body = &seq[intro_len..];
+
+    match kind {
+        Sequence::Osc | Sequence::Dcs | Sequence::ControlString => {
+            let end = string_end(body);
+            let (body_len, term_len) = end.unwrap_or((body.len(), 0));
+            let body = &body[..body_len];
+            let keep = match kind {
+                Sequence::Osc => {
+                    let number = body.split(';').next().unwrap_or_default();
+                    ALLOWED_OSC.contains(&number) || extra_osc.contains(&number)
+                }
+                // Sixel: numeric parameters, then `q`
+                Sequence::Dcs => body
+                    .trim_start_matches(|ch: char| ch.is_ascii_digit() || ch == ';')
+                    .starts_with('q'),
+                _ => false,
+            };
+            Scan {
+                len: intro_len + body_len + term_len,
+                keep,
+                complete: end.is_some(),
+            }
+        }
+        Sequence::Csi => {
+            // Parameters and intermediates run up to a final byte in `@`..=`~`
+            match body.find(|ch: char| ('@'..='~').contains(&ch)) {
+                Some(end) => Scan {
+                    len: intro_len + end + 1,
+                    keep: !(body[end..].starts_with('t') && matches!(&body[..end], "20" | "21")),
+                    complete: true,
+                },
+                None => Scan {
+                    len: seq.len(),
+                    keep: true,
+                    complete: false,
+                },
+            }
+        }
+        Sequence::Other => Scan {
+            len: intro_len,
+            keep: true,
+            // A lone ESC could still start any sequence
+            complete: seq != "\x1b",
+        },
+    }
+}
+
+/// Classify the introducer at the start of `seq`, returning its length in bytes
+fn introducer(seq: &str) -> (Sequence, usize) {
+    let mut chars = seq.chars();
+    match chars.next() {
+        Some('\x1b') => match chars.next() {
+            Some(']') => (Sequence::Osc, 2),
+            Some('[') => (Sequence::Csi, 2),
+            Some('P') => (Sequence::Dcs, 2),
+            Some('X' | '^' | '_') => (Sequence::ControlString, 2),
+            _ => (Sequence::Other, 1),
+        },
+        Some(ch @ '\u{9d}') => (Sequence::Osc, ch.len_utf8()),
+        Some(ch @ '\u{9b}') => (Sequence::Csi, ch.len_utf8()),
+        Some(ch @ '\u{90}') => (Sequence::Dcs, ch.len_utf8()),
+        Some(ch @ ('\u{98}' | '\u{9e}' | '\u{9f}')) => (Sequence::ControlString, ch.len_utf8()),
+        Some(ch) => (Sequence::Other, ch.len_utf8()),
+        None => (Sequence::Other, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_and_sgr_text_is_borrowed() {
+        let text = "\x1b[1;31merror\x1b[0m: \x1b[38;2;255;0;0mred\x1b[0m\n\x1b[2J\x1b[H\x1b7\x1b8";
+        assert!(matches!(sanitize_output(text), Cow::Borrowed(t) if t == text));
+        assert!(matches!(sanitize_output("hello"), Cow::Borrowed("hello")));
+    }
+
+    #[test]
+    fn test_supported_osc_sequences_survive() {
+        let text = concat!(
+            "\x1b]0;build\x07",
+            "\x1b]2;title\x1b\\",
+            "\x1b]7;file://host/tmp\x07",
+            "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\",
+            "\x1b]133;C;ls\x07",
+            "\x1b]133;D;0\x07",
+        );
+        assert_eq!(sanitize_output(text), text);
+    }
+
+    #[test]
+    fn test_unsupported_osc_removed() {
+        // OSC 52 would write to the clipboard, OSC 4 recolors the palette
+        let text =
+            "a\x1b]52;c;cm0gLXJmIH4=\x07b\x1b]4;1;rgb:ff/00/00\x1b\\c\x1b]1337;File=x:AAAA\x07d";
+        assert_eq!(sanitize_output(text), "abcd");
+    }
+
+    #[test]
+    fn test_extra_osc_allowed() {
+        let text = "a\x1b]52;c;aGk=\x07b\x1b]4;1;rgb:ff/00/00\x1b\\c";
+        assert_eq!(
+            sanitize_output_allowing(text, &["52"]),
+            "a\x1b]52;c;aGk=\x07bc"
+        );
+    }
+
+    #[test]
+    fn test_control_strings_removed() {
+        let text =
+            "\x1b[32mok\x1b[0m\x1bP$q\"p\x1b\\ \x1b_payload\x1b\\\x1b^pm\x07\x1bXsos\x1b\\done";
+        assert_eq!(sanitize_output(text), "\x1b[32mok\x1b[0m done");
+    }
+
+    #[test]
+    fn test_sixel_images_survive() {
+        let sixel = "\x1bPq#1;2;100;0;0!16~-!16~\x1b\\";
+        let text = format!("a{sixel}b\x1bP0;1;0q~\x1b\\\u{90}q~\u{9c}");
+        assert_eq!(sanitize_output(&text), text);
+        // Requests that only look similar are still removed
+        assert_eq!(sanitize_output("\x1bP$q\"q\x1b\\x\x1bP1x\x1b\\"), "x");
+    }
+
+    #[test]
+    fn test_title_reports_removed() {
+        let text = "\x1b]2;rm -rf ~\x07\x1b[21t\x1b[20t\x1b[8;24;80t";
+        assert_eq!(sanitize_output(text), "\x1b]2;rm -rf ~\x07\x1b[8;24;80t");
+    }
+
+    #[test]
+    fn test_c1_introducers() {
+        let text = "x\u{9d}52;c;aGk=\u{9c}y\u{90}1$r\u{9c}z\u{9b}31mred";
+        assert_eq!(sanitize_output(text), "xyz\u{9b}31mred");
+    }
+
+    #[test]
+    fn test_unterminated_sequences() {
+        // Removed sequences are dropped to the end of the chunk, kept ones pass through
+        assert_eq!(sanitize_output("ok\x1b]52;c;aGk="), "ok");
+        assert_eq!(sanitize_output("ok\x1b]0;partial"), "ok\x1b]0;partial");
+        assert_eq!(sanitize_output("ok\x1b[3"), "ok\x1b[3");
+        assert_eq!(sanitize_output("ok\x1b"), "ok\x1b");
+        // An ESC inside a string aborts it; what follows is parsed normally
+        assert_eq!(sanitize_output("\x1b]52;c;aGk=\x1b[1mbold"), "\x1b[1mbold");
+    }
+
+    #[test]
+    fn test_sequences_split_across_reads() {
+        let mut sanitizer = Sanitizer::new();
+        let mut read = |output: &str| sanitizer.sanitize(output, &[]).into_owned();
+
+        // A removed sequence is held back, then dropped whole
+        assert_eq!(read("a\x1b]52;c;cm0g"), "a");
+        assert_eq!(read("LXJmIH4=\x07b"), "b");
+        // Split inside the introducer and the terminator
+        assert_eq!(read("c\x1b"), "c");
+        assert_eq!(read("]52;c;aGk=\x1b"), "");
+        assert_eq!(read("\\d"), "d");
+        // Kept sequences are passed on once finished
+        assert_eq!(read("\x1b]0;ti"), "");
+        assert_eq!(read("tle\x07\x1b[3"), "\x1b]0;title\x07");
+        assert_eq!(read("1mred"), "\x1b[31mred");
+
+        // Too long to hold: a removed string is dropped through its end
+        let payload = "A".repeat(MAX_HELD);
+        assert_eq!(read(&format!("e\x1b]52;c;{payload}")), "e");
+        assert_eq!(read(&payload), "");
+        assert_eq!(read("A\x07f"), "f");
+        // and a kept one is passed on as it comes
+        let sixel = format!("\x1bPq{}", "~".repeat(MAX_HELD));
+        assert_eq!(read(&sixel), sixel);
+        assert_eq!(read("~\x1b\\g"), "~\x1b\\g");
+    }
+}
@@ -0,0 +1,359 @@
+//! Scrollback search for terminal output
+//!
+//! Matching runs on the plain text of parsed lines (ANSI escapes removed),
+//! so match columns line up with what the renderer draws. Both literal and
+//! regex queries are compiled to a [`SearchPattern`]; literal queries are
+//! escaped first.
+
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use regex::{Regex, RegexBuilder};
+
+/// A single match within a line, as a half-open range of character columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Line index in the parsed output
+    pub line: usize,
+    /// First matched column (inclusive)
+    pub start: usize,
+    /// Last matched column (exclusive)
+    pub end: usize,
+}
+
+/// Per-session search options, toggled from the search overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Treat the query as a regex instead of literal text
+    pub regex: bool,
+    /// Match letter case exactly (searches ignore case by default)
+    pub case_sensitive: bool,
+    /// Only match where the query is not part of a larger word
+    pub whole_word: bool,
+}
+
+impl SearchOptions {
+    /// Short indicator of the active options for the search prompt
+    #[must_use]
+    pub fn indicator(self) -> String {
+        let flags: Vec<&str> = [
+            (self.regex, ".*"),
+            (self.case_sensitive, "Aa"),
+            (self.whole_word, "W"),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, flag)| *flag)
+        .collect();
+
+        if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(" "))
+        }
+    }
+}
+
+/// A compiled search query
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    regex: Regex,
+    whole_word: bool,
+}
+
+impl SearchPattern {
+    /// Compile a query with the given options
+    ///
+    /// # Errors
+    /// Returns an error if `options.regex` is set and the query is not a valid regex
+    pub fn new(query: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        let source = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(!options.case_sensitive)
+            .build()?;
+        Ok(Self {
+            regex,
+            whole_word: options.whole_word,
+        })
+    }
+
+    /// Check whether `text` contains at least one match
+    #[must_use]
+    pub fn is_match(&self, text: &str) -> bool {
+        !self.match_columns(text).is_empty()
+    }
+
+    /// Find all non-empty matches in `text` as character column ranges
+    #[must_use]
+    pub fn match_columns(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .filter(|m| !self.whole_word || is_word_bounded(text, m.start(), m.end()))
+            .map(|m| {
+                let start = text[..m.start()].chars().count();
+                (start, start + m.as_str().chars().count())
+            })
+            .collect()
+    }
+}
+
+/// Check that the byte range `start..end` is not adjacent to word characters
+fn is_word_bounded(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = text[..start].chars().next_back().is_some_and(is_word);
+    let after = text[end..].chars().next().is_some_and(is_word);
+    !before && !after
+}
+
+/// Get the plain text of a styled line
+#[must_use]
+pub fn line_text(line: &Line<'_>) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// Find every match of `pattern` across `lines`
+#[must_use]
+pub fn find_matches<'a, I>(lines: I, pattern: &SearchPattern) -> Vec<SearchMatch>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .flat_map(|(line, text)| {
+            pattern
+                .match_columns(text)
+                .into_iter()
+                .map(move |(start, end)| SearchMatch { line, start, end })
+        })
+        .collect()
+}
+
+/// Collapse matches to the sorted, de-duplicated list of lines containing them
+#[must_use]
+pub fn matched_lines(matches: &[SearchMatch]) -> Vec<usize> {
+    let mut lines: Vec<usize> = matches.iter().map(|m| m.line).collect();
+    lines.dedup();
+    lines
+}
+
+/// Re-style the given column ranges of a line, splitting spans as needed
+///
+/// `ranges` must be sorted and non-overlapping, as returned by
+/// [`SearchPattern::match_columns`].
+#[must_use]
+pub fn highlight_line(
+    line: &Line<'static>,
+    ranges: &[(usize, usize)],
+    highlight: Style,
+) -> Line<'static> {
+    if ranges.is_empty() {
+        return line.clone();
+    }
+
+    let mut spans = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut col = 0;
+
+    for span in &line.spans {
+        let mut current = String::new();
+        let mut current_hit = false;
+
+        for ch in span.content.chars() {
+            let hit = ranges.iter().any(|&(start, end)| col >= start && col < end);
+            if hit != current_hit && !current.is_empty() {
+                let style = if current_hit {
+                    span.style.patch(highlight)
+                } else {
+                    span.style
+                };
+                spans.push(Span::styled(std::mem::take(&mut current), style));
+            }
+            current_hit = hit;
+            current.push(ch);
+            col += 1;
+        }
+
+        if !current.is_empty() {
+            let style = if current_hit {
+                span.style.patch(highlight)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(current, style));
+        }
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn literal() -> SearchOptions {
+        SearchOptions::default()
+    }
+
+    fn regex() -> SearchOptions {
+        SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_literal_pattern_escapes_metacharacters() {
+        let pattern = SearchPattern::new("a.b", literal()).unwrap();
+        assert!(pattern.is_match("xa.by"));
+        assert!(!pattern.is_match("axb"));
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let pattern = SearchPattern::new(r"err(or)?\s+\d+", regex()).unwrap();
+        assert!(pattern.is_match("ERROR 42"));
+        assert!(pattern.is_match("err 7"));
+        assert!(!pattern.is_match("error x"));
+    }
+
+    #[test]
+    fn test_invalid_regex_is_error() {
+        assert!(SearchPattern::new("(unclosed", regex()).is_err());
+        assert!(SearchPattern::new("(unclosed", literal()).is_ok());
+    }
+
+    #[test]
+    fn test_case_sensitive_option() {
+        let insensitive = SearchPattern::new("Error", literal()).unwrap();
+        assert!(insensitive.is_match("ERROR"));
+        assert!(insensitive.is_match("error"));
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        let sensitive = SearchPattern::new("Error", options).unwrap();
+        assert!(sensitive.is_match("Error: boom"));
+        assert!(!sensitive.is_match("ERROR"));
+        assert!(!sensitive.is_match("error"));
+    }
+
+    #[test]
+    fn test_whole_word_option() {
+        let options = SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        let pattern = SearchPattern::new("cat", options).unwrap();
+        assert_eq!(
+            pattern.match_columns("cat concat cat_x (cat)"),
+            vec![(0, 3), (18, 21)]
+        );
+        assert!(!pattern.is_match("category"));
+
+        // Queries that start or end with punctuation still match at word edges
+        let pattern = SearchPattern::new("--all", options).unwrap();
+        assert!(pattern.is_match("git log --all"));
+        assert!(!pattern.is_match("git log --allow"));
+    }
+
+    #[test]
+    fn test_whole_word_with_regex() {
+        let options = SearchOptions {
+            regex: true,
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        let pattern = SearchPattern::new(r"\d+", options).unwrap();
+        assert_eq!(pattern.match_columns("a1 22 b3"), vec![(3, 5)]);
+    }
+
+    #[test]
+    fn test_options_indicator() {
+        assert_eq!(SearchOptions::default().indicator(), "");
+        let all = SearchOptions {
+            regex: true,
+            case_sensitive: true,
+            whole_word: true,
+        };
+        assert_eq!(all.indicator(), " [.* Aa W]");
+    }
+
+    #[test]
+    fn test_find_matches_multiple_per_line() {
+        let pattern = SearchPattern::new("o", literal()).unwrap();
+        let matches = find_matches(["foo", "bar", "boo"], &pattern);
+
+        assert_eq!(
+            matches,
+            vec![
+                SearchMatch {
+                    line: 0,
+                    start: 1,
+                    end: 2
+                },
+                SearchMatch {
+                    line: 0,
+                    start: 2,
+                    end: 3
+                },
+                SearchMatch {
+                    line: 2,
+                    start: 1,
+                    end: 2
+                },
+                SearchMatch {
+                    line: 2,
+                    start: 2,
+                    end: 3
+                },
+            ]
+        );
+        assert_eq!(matched_lines(&matches), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_match_columns_are_character_based() {
+        let pattern = SearchPattern::new("テスト", literal()).unwrap();
+        assert_eq!(pattern.match_columns("日本語テスト!"), vec![(3, 6)]);
+    }
+
+    #[test]
+    fn test_empty_regex_matches_are_skipped() {
+        let pattern = SearchPattern::new("x*", regex()).unwrap();
+        assert_eq!(pattern.match_columns("axxb"), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn test_highlight_line_splits_spans() {
+        let base = Style::default().fg(Color::Red);
+        let highlight = Style::default().bg(Color::Yellow);
+        let line = Line::from(vec![Span::styled("hello ", base), Span::raw("world")]);
+
+        let highlighted = highlight_line(&line, &[(4, 8)], highlight);
+        let parts: Vec<(&str, Style)> = highlighted
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style))
+            .collect();
+
+        assert_eq!(
+            parts,
+            vec![
+                ("hell", base),
+                ("o ", base.patch(highlight)),
+                ("wo", highlight),
+                ("rld", Style::default()),
+            ]
+        );
+        assert_eq!(line_text(&highlighted), "hello world");
+    }
+}
@@ -0,0 +1,131 @@
+//! Status bar prompt for saving and loading named sessions
+//!
+//! Holds the state of the save-as input, the overwrite confirmation and the
+//! saved session picker. The terminal drives it from key events and performs
+//! the actual save/load through the session manager.
+
+use chrono::{DateTime, Local};
+
+use crate::session::SessionSummary;
+
+/// Active session prompt, shown in place of the status bar mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionPrompt {
+    /// Typing the name to save the current session under
+    SaveName(String),
+    /// A session with this name exists; waiting for y/n to overwrite it
+    ConfirmOverwrite {
+        /// Name being saved
+        name: String,
+        /// Id of the existing session, reused so its file is replaced
+        id: String,
+    },
+    /// Choosing a saved session to load, most recent first
+    Pick {
+        /// Saved sessions to choose from
+        sessions: Vec<SessionSummary>,
+        /// Index of the highlighted session
+        selected: usize,
+    },
+    /// An autosave from a previous run was found; waiting for y/n to restore it
+    RestoreAutosave {
+        /// When the autosave was written
+        saved_at: DateTime<Local>,
+    },
+}
+
+impl SessionPrompt {
+    /// Prompt text for the status bar mode indicator
+    #[must_use]
+    pub fn text(&self) -> String {
+        match self {
+            Self::SaveName(name) => format!(" SAVE AS: {name} "),
+            Self::ConfirmOverwrite { name, .. } => format!(" OVERWRITE \"{name}\"? (y/n) "),
+            Self::Pick { sessions, selected } => match sessions.get(*selected) {
+                Some(session) => format!(
+                    " LOAD {}/{}: {} ({}) ",
+                    selected + 1,
+                    sessions.len(),
+                    session.name,
+                    session.created_at.format("%Y-%m-%d %H:%M")
+                ),
+                None => " LOAD: no saved sessions ".to_string(),
+            },
+            Self::RestoreAutosave { saved_at } => format!(
+                " RESTORE AUTOSAVE FROM {}? (y/n) ",
+                saved_at.format("%Y-%m-%d %H:%M")
+            ),
+        }
+    }
+
+    /// Key hints for the status bar
+    #[must_use]
+    pub fn hints(&self) -> &'static str {
+        match self {
+            Self::SaveName(_) => " Enter: Save │ Esc: Cancel ",
+            Self::ConfirmOverwrite { .. } => " y: Overwrite │ n: Rename │ Esc: Cancel ",
+            Self::Pick { .. } => " ↑/↓: Select │ Enter: Load │ Esc: Cancel ",
+            Self::RestoreAutosave { .. } => " y: Restore │ n: Discard │ Esc: Later ",
+        }
+    }
+
+    /// Move the picker selection by `delta`, wrapping at either end
+    pub fn move_selection(&mut self, delta: isize) {
+        if let Self::Pick { sessions, selected } = self {
+            if !sessions.is_empty() {
+                let len = sessions.len() as isize;
+                *selected = (*selected as isize + delta).rem_euclid(len) as usize;
+            }
+        }
+    }
+
+    /// The session highlighted in the picker, if any
+    #[must_use]
+    pub fn selected_session(&self) -> Option<&SessionSummary> {
+        match self {
+            Self::Pick { sessions, selected } => sessions.get(*selected),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, name: &str) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            name: name.to_string(),
+            created_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_picker_selection_wraps() {
+        let mut prompt = SessionPrompt::Pick {
+            sessions: vec![summary("a", "build"), summary("b", "deploy")],
+            selected: 0,
+        };
+
+        prompt.move_selection(-1);
+        assert_eq!(prompt.selected_session().unwrap().id, "b");
+        prompt.move_selection(1);
+        assert_eq!(prompt.selected_session().unwrap().id, "a");
+        assert!(prompt.text().starts_with(" LOAD 1/2: build ("));
+    }
+
+    #[test]
+    fn test_prompt_text() {
+        assert_eq!(
+            SessionPrompt::SaveName("work".into()).text(),
+            " SAVE AS: work "
+        );
+        let confirm = SessionPrompt::ConfirmOverwrite {
+            name: "work".into(),
+            id: "a".into(),
+        };
+        assert_eq!(confirm.text(), " OVERWRITE \"work\"? (y/n) ");
+        assert!(confirm.selected_session().is_none());
+    }
+}
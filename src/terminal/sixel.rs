@@ -0,0 +1,320 @@
+//! Sixel images
+//!
+//! Programs such as `img2sixel`, `chafa` and `timg` show images inline by
+//! printing a DCS string `ESC P P1 ; P2 ; P3 q data ESC \`. The data paints
+//! bands six pixels tall, one column at a time:
+//!
+//! - `?` to `~` draw one column of a band, each of the six low bits of the
+//!   byte minus `?` setting a pixel, top bit lowest
+//! - `! n c` repeats column `c` `n` times
+//! - `# n` picks color register `n`, and `# n ; 2 ; r ; g ; b` (percentages)
+//!   or `# n ; 1 ; h ; l ; s` first sets it
+//! - `$` goes back to the start of the band, `-` to the start of the next one
+//! - `" a ; b ; w ; h` declares the image size
+//!
+//! Pixels left unpainted stay transparent. The decoded image is shown by
+//! [`AnsiParser`](super::ansi_parser::AnsiParser) as a block of cells from
+//! the cursor, each cell drawn as `▀` with the color of its top half as the
+//! foreground and of its bottom half as the background.
+
+use crate::colors::TrueColor;
+
+/// Largest image drawn, in pixels each way; anything past it is cropped
+pub const MAX_IMAGE_SIZE: usize = 2048;
+
+/// Width of the pixels a cell covers, as for background images
+pub const CELL_WIDTH_PX: usize = super::background::CELL_WIDTH_PX as usize;
+
+/// Height of the pixels a cell covers
+pub const CELL_HEIGHT_PX: usize = super::background::CELL_HEIGHT_PX as usize;
+
+/// Most bytes of sixel data kept for one image
+pub const MAX_PAYLOAD_BYTES: usize = 16 * 1024 * 1024;
+
+/// The VT340's default color registers, as RGB percentages
+const DEFAULT_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (20, 20, 80),
+    (80, 13, 13),
+    (20, 80, 20),
+    (80, 20, 80),
+    (20, 80, 80),
+    (80, 80, 20),
+    (53, 53, 53),
+    (26, 26, 26),
+    (33, 33, 60),
+    (60, 26, 26),
+    (33, 60, 33),
+    (60, 33, 60),
+    (33, 60, 60),
+    (60, 60, 33),
+    (80, 80, 80),
+];
+
+/// Number of color registers
+const REGISTERS: usize = 256;
+
+/// A decoded sixel image
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SixelImage {
+    pub width: usize,
+    pub height: usize,
+    /// Painted rows, each only as long as its last painted pixel
+    rows: Vec<Vec<Option<TrueColor>>>,
+}
+
+impl SixelImage {
+    /// The color painted at `(x, y)`, if any
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<TrueColor> {
+        self.rows.get(y)?.get(x).copied().flatten()
+    }
+
+    /// Average color of the `width` by `height` pixels at `(x, y)`
+    ///
+    /// `None` if fewer than half of them were painted, so the edges of a
+    /// shape aren't smeared over the cells around it.
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn average(&self, x: usize, y: usize, width: usize, height: usize) -> Option<TrueColor> {
+        let mut sum = [0u64; 3];
+        let mut painted = 0u64;
+        for py in y..y + height {
+            for px in x..x + width {
+                if let Some(color) = self.pixel(px, py) {
+                    sum[0] += u64::from(color.r);
+                    sum[1] += u64::from(color.g);
+                    sum[2] += u64::from(color.b);
+                    painted += 1;
+                }
+            }
+        }
+        if painted == 0 || painted * 2 < (width * height) as u64 {
+            return None;
+        }
+        Some(TrueColor::new(
+            (sum[0] / painted) as u8,
+            (sum[1] / painted) as u8,
+            (sum[2] / painted) as u8,
+        ))
+    }
+
+    /// Paint `(x, y)`, growing the image to hold it
+    fn paint(&mut self, x: usize, y: usize, color: TrueColor) {
+        if x >= MAX_IMAGE_SIZE || y >= MAX_IMAGE_SIZE {
+            return;
+        }
+        if y >= self.rows.len() {
+            self.rows.resize_with(y + 1, Vec::new);
+        }
+        let row = &mut self.rows[y];
+        if x >= row.len() {
+            row.resize(x + 1, None);
+        }
+        row[x] = Some(color);
+        self.grow(x + 1, y + 1);
+    }
+
+    /// Make the image at least `width` by `height`
+    fn grow(&mut self, width: usize, height: usize) {
+        self.width = self.width.max(width.min(MAX_IMAGE_SIZE));
+        self.height = self.height.max(height.min(MAX_IMAGE_SIZE));
+    }
+}
+
+/// Decode the data of a sixel string, everything between `q` and the terminator
+///
+/// Unknown bytes are skipped, so a damaged image still shows what it can.
+#[must_use]
+pub fn decode(data: &[u8]) -> SixelImage {
+    let mut image = SixelImage::default();
+    let mut palette: Vec<TrueColor> = (0..REGISTERS)
+        .map(|index| {
+            DEFAULT_PALETTE
+                .get(index)
+                .map_or(TrueColor::new(0, 0, 0), |&(r, g, b)| percent_rgb(r, g, b))
+        })
+        .collect();
+    let mut color = palette[0];
+    let (mut x, mut band) = (0usize, 0usize);
+
+    let mut bytes = data.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'?'..=b'~' => {
+                draw_column(&mut image, x, band, byte, 1, color);
+                x += 1;
+            }
+            b'!' => {
+                let count = numbers(&mut bytes).first().copied().unwrap_or(1).max(1);
+                if let Some(byte @ b'?'..=b'~') = bytes.next() {
+                    let count = count.min(MAX_IMAGE_SIZE);
+                    draw_column(&mut image, x, band, byte, count, color);
+                    x += count;
+                }
+            }
+            b'#' => {
+                let params = numbers(&mut bytes);
+                let Some(&index) = params.first() else {
+                    continue;
+                };
+                let index = index % REGISTERS;
+                if let [_, space, a, b, c] = params[..] {
+                    if let Some(defined) = define_color(space, a, b, c) {
+                        palette[index] = defined;
+                    }
+                }
+                color = palette[index];
+            }
+            b'"' => {
+                if let [_, _, width, height, ..] = numbers(&mut bytes)[..] {
+                    image.grow(width, height);
+                }
+            }
+            b'$' => x = 0,
+            b'-' => {
+                x = 0;
+                band += 1;
+            }
+            _ => {}
+        }
+    }
+    image
+}
+
+/// Paint the six pixels of `byte` in `count` columns from `x` of `band`
+fn draw_column(
+    image: &mut SixelImage,
+    x: usize,
+    band: usize,
+    byte: u8,
+    count: usize,
+    color: TrueColor,
+) {
+    let bits = byte - b'?';
+    for bit in 0..6 {
+        if bits & (1 << bit) != 0 {
+            for column in x..(x + count).min(MAX_IMAGE_SIZE) {
+                image.paint(column, band * 6 + bit, color);
+            }
+        }
+    }
+}
+
+/// Read `;` separated numbers from the front of `bytes`
+fn numbers(bytes: &mut std::iter::Peekable<impl Iterator<Item = u8>>) -> Vec<usize> {
+    let mut values = Vec::new();
+    let mut value = 0usize;
+    while let Some(&byte) = bytes.peek() {
+        match byte {
+            b'0'..=b'9' => {
+                value = value
+                    .saturating_mul(10)
+                    .saturating_add(usize::from(byte - b'0'));
+            }
+            b';' => values.push(std::mem::take(&mut value)),
+            _ => break,
+        }
+        bytes.next();
+    }
+    values.push(value);
+    values
+}
+
+/// A color given in `space` 1 (HLS: hue in degrees, lightness and saturation
+/// in percent) or 2 (RGB in percent)
+#[allow(clippy::cast_precision_loss)]
+fn define_color(space: usize, a: usize, b: usize, c: usize) -> Option<TrueColor> {
+    match space {
+        // Hue 0 is blue on the VT340, where HSL has red
+        1 => Some(TrueColor::from_hsl(
+            a as f32 - 120.0,
+            c.min(100) as f32 / 100.0,
+            b.min(100) as f32 / 100.0,
+        )),
+        2 => Some(percent_rgb(
+            u8::try_from(a.min(100)).unwrap_or(100),
+            u8::try_from(b.min(100)).unwrap_or(100),
+            u8::try_from(c.min(100)).unwrap_or(100),
+        )),
+        _ => None,
+    }
+}
+
+/// A color from RGB percentages
+fn percent_rgb(r: u8, g: u8, b: u8) -> TrueColor {
+    #[allow(clippy::cast_possible_truncation)]
+    let scale = |percent: u8| ((u16::from(percent.min(100)) * 255 + 50) / 100) as u8;
+    TrueColor::new(scale(r), scale(g), scale(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: TrueColor = TrueColor::new(255, 0, 0);
+    const BLUE: TrueColor = TrueColor::new(0, 0, 255);
+
+    #[test]
+    fn test_columns_paint_six_pixel_bands() {
+        // `~` sets all six bits, `@` only the top one
+        let image = decode(b"#1;2;100;0;0~@");
+        assert_eq!((image.width, image.height), (2, 6));
+        assert_eq!(image.pixel(0, 0), Some(RED));
+        assert_eq!(image.pixel(0, 5), Some(RED));
+        assert_eq!(image.pixel(1, 0), Some(RED));
+        assert_eq!(image.pixel(1, 1), None);
+    }
+
+    #[test]
+    fn test_repeat_carriage_return_and_next_band() {
+        let image = decode(b"#1;2;100;0;0!3~$#2;2;0;0;100!3@-!2~");
+        assert_eq!((image.width, image.height), (3, 12));
+        // The blue row is drawn over the red band after `$`
+        assert_eq!(image.pixel(0, 0), Some(BLUE));
+        assert_eq!(image.pixel(2, 0), Some(BLUE));
+        assert_eq!(image.pixel(2, 1), Some(RED));
+        // `-` moves down to the next band, back at the left edge
+        assert_eq!(image.pixel(1, 6), Some(BLUE));
+        assert_eq!(image.pixel(2, 6), None);
+    }
+
+    #[test]
+    fn test_color_registers() {
+        // HLS hue 120 is red on the VT340
+        let image = decode(b"#5;1;120;50;100~#3~#5~");
+        assert_eq!(image.pixel(0, 0), Some(RED));
+        assert_eq!(image.pixel(1, 0), Some(percent_rgb(20, 80, 20)));
+        assert_eq!(image.pixel(2, 0), Some(RED));
+        // Registers wrap, and an unknown color space keeps the register as it was
+        let image = decode(b"#259;2;0;0;100~#3;9;1;2;3~");
+        assert_eq!(image.pixel(0, 0), Some(BLUE));
+        assert_eq!(image.pixel(1, 0), Some(BLUE));
+    }
+
+    #[test]
+    fn test_raster_attributes_set_the_size() {
+        let image = decode(b"\"1;1;20;14#1;2;100;0;0~");
+        assert_eq!((image.width, image.height), (20, 14));
+        assert_eq!(image.pixel(0, 0), Some(RED));
+        assert_eq!(image.pixel(19, 13), None);
+    }
+
+    #[test]
+    fn test_oversized_images_are_cropped() {
+        let image = decode(format!("!{}~", MAX_IMAGE_SIZE * 2).as_bytes());
+        assert_eq!((image.width, image.height), (MAX_IMAGE_SIZE, 6));
+        let image = decode(b"\"1;1;99999;99999");
+        assert_eq!(image.width, MAX_IMAGE_SIZE);
+    }
+
+    #[test]
+    fn test_average_skips_mostly_empty_cells() {
+        let image = decode(b"#1;2;100;0;0~~#2;2;0;0;100~~");
+        assert_eq!(image.average(0, 0, 2, 6), Some(RED));
+        assert_eq!(image.average(0, 0, 4, 6), Some(TrueColor::new(127, 0, 127)));
+        // A quarter painted is left transparent
+        assert_eq!(image.average(3, 0, 4, 6), None);
+        assert_eq!(image.average(10, 10, 2, 2), None);
+    }
+}
@@ -0,0 +1,331 @@
+//! Scrollback kept on disk past the in-memory limit
+//!
+//! Output beyond `scrollback_lines` is normally dropped from the front of a
+//! tab's buffer. With `terminal.scrollback_to_disk`, a [`ScrollbackSpill`]
+//! appends it to a temporary file instead, always cut at a line boundary, and
+//! hands it back a page at a time when the view is scrolled past the top of
+//! what is in memory. The file works as a stack: the most recently spilled
+//! bytes are the ones right before the buffer, so they are read back first.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tracing::warn;
+
+/// Least output read back into memory at a time when scrolling up
+pub const PAGE_BYTES: usize = 64 * 1024;
+
+/// Largest spill file; older output is dropped as before once it is full
+pub const MAX_SPILL_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Numbers spill files so tabs of one process never share one
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// One run of bytes spilled together
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    len: usize,
+    lines: usize,
+}
+
+/// A tab's output that no longer fits in memory
+#[derive(Debug, Default)]
+pub struct ScrollbackSpill {
+    /// Temporary file, created on the first spill
+    file: Option<(File, PathBuf)>,
+    /// Spilled runs, oldest first
+    chunks: Vec<Chunk>,
+    /// Bytes in the file
+    len: u64,
+    /// Lines in the file
+    lines: usize,
+    /// Bytes read back into memory, kept there while the view is scrolled up
+    paged_in: usize,
+}
+
+impl ScrollbackSpill {
+    /// Create an empty spill; no file exists until something is spilled
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether nothing is on disk
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Number of lines on disk, which come before the first line in memory
+    #[must_use]
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Bytes read back into memory since the view last followed the output
+    #[must_use]
+    pub fn paged_in(&self) -> usize {
+        self.paged_in
+    }
+
+    /// Stop keeping paged-in output in memory, once the view follows the output again
+    pub fn settle(&mut self) {
+        self.paged_in = 0;
+    }
+
+    /// Append bytes dropped from the front of the buffer
+    ///
+    /// Returns false, leaving the file as it was, when the file is full or
+    /// can't be written; the bytes are then lost as without spilling.
+    pub fn spill(&mut self, bytes: &[u8]) -> bool {
+        if bytes.is_empty() {
+            return true;
+        }
+        if self.len + bytes.len() as u64 > MAX_SPILL_BYTES {
+            return false;
+        }
+        match self.write_at_end(bytes) {
+            Ok(()) => {
+                let lines = newline_count(bytes);
+                self.chunks.push(Chunk {
+                    len: bytes.len(),
+                    lines,
+                });
+                self.len += bytes.len() as u64;
+                self.lines += lines;
+                self.paged_in = self.paged_in.saturating_sub(bytes.len());
+                true
+            }
+            Err(e) => {
+                warn!("Failed to write scrollback to disk: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Take the newest spilled bytes back, at least [`PAGE_BYTES`] of them
+    /// unless less is left
+    ///
+    /// The bytes go back in front of the buffer and count as paged in.
+    pub fn restore(&mut self) -> Option<Vec<u8>> {
+        let mut take = 0;
+        let mut count = 0;
+        for chunk in self.chunks.iter().rev() {
+            if take >= PAGE_BYTES {
+                break;
+            }
+            take += chunk.len;
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+
+        let start = self.len - take as u64;
+        match self.read_range(start, take) {
+            Ok(bytes) => {
+                for chunk in self.chunks.drain(self.chunks.len() - count..) {
+                    self.lines -= chunk.lines;
+                }
+                self.len = start;
+                if let Some((file, _)) = &self.file {
+                    if let Err(e) = file.set_len(start) {
+                        warn!("Failed to shrink scrollback file: {}", e);
+                    }
+                }
+                self.paged_in += bytes.len();
+                Some(bytes)
+            }
+            Err(e) => {
+                warn!("Failed to read scrollback from disk: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Everything on disk, oldest first
+    pub fn read_all(&self) -> io::Result<Vec<u8>> {
+        let len = usize::try_from(self.len).unwrap_or(usize::MAX);
+        self.read_range(0, len)
+    }
+
+    /// Forget everything on disk and delete the file
+    pub fn clear(&mut self) {
+        self.remove_file();
+        self.chunks.clear();
+        self.len = 0;
+        self.lines = 0;
+        self.paged_in = 0;
+    }
+
+    fn write_at_end(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let (file, _) = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(create_file()?),
+        };
+        file.seek(SeekFrom::Start(self.len))?;
+        file.write_all(bytes)
+    }
+
+    fn read_range(&self, start: u64, len: usize) -> io::Result<Vec<u8>> {
+        let Some((file, _)) = &self.file else {
+            return Ok(Vec::new());
+        };
+        // Reads go through `&File`, so the whole output can be read from `&self`
+        let mut file: &File = file;
+        let mut bytes = vec![0; len];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn remove_file(&mut self) {
+        if let Some((file, path)) = self.file.take() {
+            drop(file);
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to remove scrollback file {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+impl Drop for ScrollbackSpill {
+    fn drop(&mut self) {
+        self.remove_file();
+    }
+}
+
+/// Where to cut `buffer` to drop at least `excess` bytes from its front
+///
+/// The cut goes just past the first newline at or after `excess`, so whole
+/// lines are spilled and read back. A buffer with no newline there is cut at
+/// the next character boundary instead.
+#[must_use]
+pub fn spill_point(buffer: &[u8], excess: usize) -> usize {
+    if excess == 0 {
+        return 0;
+    }
+    if excess >= buffer.len() {
+        return buffer.len();
+    }
+    match buffer[excess - 1..].iter().position(|&b| b == b'\n') {
+        Some(i) => excess + i,
+        None => {
+            let mut cut = excess;
+            // Skip UTF-8 continuation bytes (10xxxxxx)
+            while cut < buffer.len() && buffer[cut] & 0xC0 == 0x80 {
+                cut += 1;
+            }
+            cut
+        }
+    }
+}
+
+/// Open a new temporary file only the user can read
+fn create_file() -> io::Result<(File, PathBuf)> {
+    let path = std::env::temp_dir().join(format!(
+        "furnace-scrollback-{}-{}",
+        std::process::id(),
+        NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(&path)?;
+    Ok((file, path))
+}
+
+/// Number of newlines in `bytes`
+fn newline_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_point_cuts_after_a_newline() {
+        let buffer = b"one\ntwo\nthree\n";
+        // Already at a line start
+        assert_eq!(spill_point(buffer, 4), 4);
+        // Inside "two": the rest of the line goes too
+        assert_eq!(spill_point(buffer, 5), 8);
+        assert_eq!(spill_point(buffer, 8), 8);
+        assert_eq!(spill_point(buffer, 9), 14);
+        assert_eq!(spill_point(buffer, 0), 0);
+        assert_eq!(spill_point(buffer, 20), 14);
+    }
+
+    #[test]
+    fn test_spill_point_without_newline_keeps_characters_whole() {
+        // "é" is C3 A9
+        let buffer = "abé tail".as_bytes();
+        assert_eq!(spill_point(buffer, 3), 4);
+        assert_eq!(spill_point(buffer, 2), 2);
+    }
+
+    #[test]
+    fn test_restore_returns_newest_bytes_first() {
+        let mut spill = ScrollbackSpill::new();
+        assert!(spill.is_empty());
+        assert!(spill.restore().is_none());
+
+        assert!(spill.spill(b"first\n"));
+        assert!(spill.spill(b"second\nthird\n"));
+        assert_eq!(spill.lines(), 3);
+        assert_eq!(spill.read_all().unwrap(), b"first\nsecond\nthird\n");
+
+        // Small chunks are read back together, newest run nearest the buffer
+        let page = spill.restore().unwrap();
+        assert_eq!(page, b"first\nsecond\nthird\n");
+        assert!(spill.is_empty());
+        assert_eq!(spill.lines(), 0);
+        assert_eq!(spill.paged_in(), page.len());
+    }
+
+    #[test]
+    fn test_restore_stops_after_a_page() {
+        let line = format!("{}\n", "x".repeat(1023));
+        let run = line.repeat(PAGE_BYTES / 1024 / 2);
+        let mut spill = ScrollbackSpill::new();
+        for _ in 0..5 {
+            assert!(spill.spill(run.as_bytes()));
+        }
+        let lines = spill.lines();
+
+        // Two half-page runs make a page
+        let page = spill.restore().unwrap();
+        assert_eq!(page.len(), PAGE_BYTES);
+        assert_eq!(spill.lines(), lines - PAGE_BYTES / 1024);
+        assert_eq!(spill.read_all().unwrap().len(), run.len() * 3);
+
+        // Spilling again goes after what is left, not after what was read back
+        assert!(spill.spill(b"again\n"));
+        let all = spill.read_all().unwrap();
+        assert!(all.ends_with(b"x\nagain\n"));
+        assert_eq!(all.len(), run.len() * 3 + 6);
+    }
+
+    #[test]
+    fn test_clear_and_drop_remove_the_file() {
+        let mut spill = ScrollbackSpill::new();
+        assert!(spill.spill(b"secret\n"));
+        let path = spill.file.as_ref().unwrap().1.clone();
+        assert!(path.exists());
+        spill.clear();
+        assert!(!path.exists());
+        assert!(spill.is_empty());
+
+        assert!(spill.spill(b"again\n"));
+        let path = spill.file.as_ref().unwrap().1.clone();
+        drop(spill);
+        assert!(!path.exists());
+    }
+}
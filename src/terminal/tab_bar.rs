@@ -0,0 +1,156 @@
+//! Tab bar layout and mouse targets
+//!
+//! Each tab is drawn as ` label × `, with a `│` between tabs. The layout is
+//! worked out here once and used both to draw the bar and to find what a click
+//! lands on: the label switches to the tab, the `×` closes it, and dragging a
+//! tab over another moves it there.
+
+use unicode_width::UnicodeWidthStr;
+
+/// Drawn at the end of each tab; clicking it closes the tab
+pub const CLOSE_BUTTON: &str = "×";
+
+/// Drawn between tabs
+pub const DIVIDER: &str = "│";
+
+/// Columns one tab takes in the bar, counted from the bar's left edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TabSpan {
+    pub start: u16,
+    /// One past the last column
+    pub end: u16,
+}
+
+impl TabSpan {
+    /// Column of the tab's close button, just before the trailing space
+    #[must_use]
+    pub fn close_column(self) -> u16 {
+        self.end.saturating_sub(2)
+    }
+}
+
+/// What a click in the tab bar lands on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabTarget {
+    /// The tab at this index
+    Tab(usize),
+    /// The close button of the tab at this index
+    Close(usize),
+}
+
+/// Text drawn for a tab labelled `label`
+#[must_use]
+pub fn tab_text(label: &str) -> String {
+    format!(" {label} {CLOSE_BUTTON} ")
+}
+
+/// Where each of `texts` is drawn, left to right with a divider between them
+#[must_use]
+pub fn layout(texts: &[String]) -> Vec<TabSpan> {
+    let divider = DIVIDER.width();
+    let mut start = 0usize;
+    texts
+        .iter()
+        .map(|text| {
+            let end = start + text.width();
+            let span = TabSpan {
+                start: u16::try_from(start).unwrap_or(u16::MAX),
+                end: u16::try_from(end).unwrap_or(u16::MAX),
+            };
+            start = end + divider;
+            span
+        })
+        .collect()
+}
+
+/// The tab or close button at `column`, counted from the bar's left edge
+///
+/// Dividers and the space after the last tab are not targets.
+#[must_use]
+pub fn target_at(spans: &[TabSpan], column: u16) -> Option<TabTarget> {
+    let index = spans
+        .iter()
+        .position(|span| (span.start..span.end).contains(&column))?;
+    if column == spans[index].close_column() {
+        Some(TabTarget::Close(index))
+    } else {
+        Some(TabTarget::Tab(index))
+    }
+}
+
+/// Where the item at `index` ends up after the one at `from` moves to `to`
+///
+/// The items in between shift over by one to make room.
+#[must_use]
+pub fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Move the item at `from` to `to`, shifting the ones in between
+///
+/// Does nothing if either index is past the end.
+pub fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) {
+    if from < items.len() && to < items.len() {
+        let item = items.remove(from);
+        items.insert(to, item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_and_targets() {
+        let texts = vec![tab_text("Tab 1"), tab_text("src")];
+        assert_eq!(texts[0], " Tab 1 × ");
+        let spans = layout(&texts);
+        // " Tab 1 × │ src × "
+        assert_eq!(
+            spans,
+            vec![TabSpan { start: 0, end: 9 }, TabSpan { start: 10, end: 17 }]
+        );
+
+        assert_eq!(target_at(&spans, 0), Some(TabTarget::Tab(0)));
+        assert_eq!(target_at(&spans, 3), Some(TabTarget::Tab(0)));
+        assert_eq!(target_at(&spans, 7), Some(TabTarget::Close(0)));
+        assert_eq!(target_at(&spans, 8), Some(TabTarget::Tab(0)));
+        assert_eq!(target_at(&spans, 9), None);
+        assert_eq!(target_at(&spans, 11), Some(TabTarget::Tab(1)));
+        assert_eq!(target_at(&spans, 15), Some(TabTarget::Close(1)));
+        assert_eq!(target_at(&spans, 17), None);
+        assert_eq!(target_at(&[], 0), None);
+    }
+
+    #[test]
+    fn test_wide_labels_take_two_columns_a_character() {
+        let spans = layout(&[tab_text("日本"), tab_text("x")]);
+        assert_eq!(spans[0], TabSpan { start: 0, end: 8 });
+        assert_eq!(spans[1].start, 9);
+        assert_eq!(target_at(&spans, 6), Some(TabTarget::Close(0)));
+    }
+
+    #[test]
+    fn test_move_item_matches_moved_index() {
+        for (from, to) in [(0, 3), (3, 0), (1, 2), (2, 1), (2, 2)] {
+            let mut items: Vec<usize> = (0..5).collect();
+            move_item(&mut items, from, to);
+            for index in 0..5 {
+                assert_eq!(items[moved_index(index, from, to)], index, "{from}->{to}");
+            }
+        }
+
+        let mut items = vec!['a', 'b'];
+        move_item(&mut items, 0, 2);
+        move_item(&mut items, 2, 0);
+        assert_eq!(items, vec!['a', 'b']);
+    }
+}
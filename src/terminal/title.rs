@@ -0,0 +1,385 @@
+//! Window and icon titles (OSC 0, 1 and 2)
+//!
+//! Programs name the terminal with `ESC ] Ps ; text` ended by BEL or `ESC \`:
+//!
+//! - `0` sets both the icon name and the window title
+//! - `1` sets only the icon name, the short label of a minimized window
+//! - `2` sets only the window title
+//!
+//! Each tab keeps its own [`Titles`]. The tab bar prefers the icon name, being
+//! the shorter of the two, and the window title goes to the real window. An
+//! empty text clears the name, so the default label comes back.
+//!
+//! `terminal.tab_title_source` can label tabs with the shell's directory or
+//! the program in the foreground instead, see [`TabTitleSource`].
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, System};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How long a looked-up process name is reused before asking the system again
+const PROCESS_NAME_TTL: Duration = Duration::from_secs(1);
+
+/// Introducer shared by OSC sequences
+const OSC: &str = "\x1b]";
+
+/// Which names an OSC title sequence sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleKind {
+    /// OSC 0: icon name and window title
+    Both,
+    /// OSC 1: icon name
+    Icon,
+    /// OSC 2: window title
+    Window,
+}
+
+/// One title sequence: what it sets and the text, empty to clear
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleUpdate {
+    pub kind: TitleKind,
+    pub text: String,
+}
+
+/// Complete OSC 0, 1 and 2 sequences in `output`, in order
+///
+/// Unterminated sequences are skipped.
+#[must_use]
+pub fn title_updates(output: &str) -> Vec<TitleUpdate> {
+    output
+        .match_indices(OSC)
+        .filter_map(|(start, _)| {
+            let body = &output[start + OSC.len()..];
+            let (code, rest) = body.split_once(';')?;
+            let kind = match code {
+                "0" => TitleKind::Both,
+                "1" => TitleKind::Icon,
+                "2" => TitleKind::Window,
+                _ => return None,
+            };
+            let end = rest.find(['\x07', '\x1b'])?;
+            Some(TitleUpdate {
+                kind,
+                text: rest[..end].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// What a tab is labelled with in the tab bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TabTitleSource {
+    /// The icon name or window title set with OSC 0, 1 or 2
+    #[default]
+    Title,
+    /// The last part of the shell's working directory, as reported by OSC 7
+    Cwd,
+    /// The name of the program running in the foreground
+    Process,
+}
+
+impl TabTitleSource {
+    /// Parse the `terminal.tab_title_source` config value, defaulting to titles
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "cwd" => Self::Cwd,
+            "process" => Self::Process,
+            _ => Self::Title,
+        }
+    }
+}
+
+/// The names a tab's programs have set, and where its shell is
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Titles {
+    icon: Option<String>,
+    window: Option<String>,
+    dir: Option<String>,
+}
+
+impl Titles {
+    /// Apply a title sequence; empty text clears the names it sets
+    pub fn apply(&mut self, update: &TitleUpdate) {
+        let text = (!update.text.is_empty()).then(|| update.text.clone());
+        match update.kind {
+            TitleKind::Both => {
+                self.icon.clone_from(&text);
+                self.window = text;
+            }
+            TitleKind::Icon => self.icon = text,
+            TitleKind::Window => self.window = text,
+        }
+    }
+
+    /// Record the shell's working directory
+    pub fn set_dir(&mut self, dir: impl Into<String>) {
+        self.dir = Some(dir.into());
+    }
+
+    /// The shell's working directory, if it has reported one
+    #[must_use]
+    pub fn dir(&self) -> Option<&str> {
+        self.dir.as_deref()
+    }
+
+    /// Label for the tab bar, or `None` for the default `Tab N`
+    ///
+    /// `process` is the name of the tab's foreground program, if known. Labels
+    /// wider than `max_width` cells are shortened.
+    #[must_use]
+    pub fn tab_label(
+        &self,
+        source: TabTitleSource,
+        process: Option<&str>,
+        max_width: usize,
+    ) -> Option<String> {
+        let label = match source {
+            TabTitleSource::Title => self.icon.as_deref().or(self.window.as_deref())?,
+            TabTitleSource::Cwd => dir_name(self.dir.as_deref()?),
+            TabTitleSource::Process => process?,
+        };
+        (!label.is_empty()).then(|| truncate(label, max_width))
+    }
+
+    /// Title for the window, or `None` for the default
+    #[must_use]
+    pub fn window_title(&self) -> Option<&str> {
+        self.window.as_deref()
+    }
+}
+
+/// Last part of `dir`, or all of it for a root such as `/`
+///
+/// Both separators are accepted since OSC 7 can report a Windows path.
+fn dir_name(dir: &str) -> &str {
+    let trimmed = dir.trim_end_matches(['/', '\\']);
+    match trimmed.rsplit(['/', '\\']).next() {
+        Some(name) if !name.is_empty() => name,
+        _ => dir,
+    }
+}
+
+/// Names of running processes, looked up at most once a second per process
+///
+/// The tab bar is drawn many times a second, far more often than the program
+/// in a tab changes.
+#[derive(Default)]
+pub struct ProcessNames {
+    system: System,
+    names: HashMap<u32, (Instant, Option<String>)>,
+}
+
+impl ProcessNames {
+    /// Name of process `pid` at `now`, or `None` if it isn't running
+    pub fn name(&mut self, pid: u32, now: Instant) -> Option<String> {
+        if let Some((looked_up, name)) = self.names.get(&pid) {
+            if now.duration_since(*looked_up) < PROCESS_NAME_TTL {
+                return name.clone();
+            }
+        }
+
+        let id = Pid::from_u32(pid);
+        let name = self
+            .system
+            .refresh_process_specifics(id, ProcessRefreshKind::new())
+            .then(|| self.system.process(id))
+            .flatten()
+            .map(|process| process.name().to_string());
+        self.names
+            .retain(|_, (looked_up, _)| now.duration_since(*looked_up) < PROCESS_NAME_TTL);
+        self.names.insert(pid, (now, name.clone()));
+        name
+    }
+}
+
+/// `text` cut to `width` cells, ending in `…` when shortened
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut used = 0;
+    let mut cut: String = text
+        .chars()
+        .take_while(|ch| {
+            used += ch.width().unwrap_or(0);
+            used < width
+        })
+        .collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Width the tests shorten labels to
+    const WIDTH: usize = 24;
+
+    fn title_label(titles: &Titles) -> Option<String> {
+        titles.tab_label(TabTitleSource::Title, None, WIDTH)
+    }
+
+    fn update(kind: TitleKind, text: &str) -> TitleUpdate {
+        TitleUpdate {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_title_updates_from_each_variant() {
+        assert_eq!(
+            title_updates("\x1b]0;vim notes.md\x07"),
+            vec![update(TitleKind::Both, "vim notes.md")]
+        );
+        assert_eq!(
+            title_updates("\x1b]1;vim\x1b\\"),
+            vec![update(TitleKind::Icon, "vim")]
+        );
+        assert_eq!(
+            title_updates("\x1b]2;me@host: ~/src\x07"),
+            vec![update(TitleKind::Window, "me@host: ~/src")]
+        );
+        // Several in one chunk, around other output
+        assert_eq!(
+            title_updates("$ \x1b]1;a\x07text\x1b]2;b; c\x07\x1b]0;\x07"),
+            vec![
+                update(TitleKind::Icon, "a"),
+                update(TitleKind::Window, "b; c"),
+                update(TitleKind::Both, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_and_unterminated_sequences_are_ignored() {
+        assert!(title_updates("\x1b]7;file:///tmp\x07\x1b]133;A\x07").is_empty());
+        assert!(title_updates("\x1b]10;?\x07\x1b]22;x\x07").is_empty());
+        assert!(title_updates("\x1b]2;no end").is_empty());
+        assert!(title_updates("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_icon_and_window_titles_are_separate() {
+        let mut titles = Titles::default();
+        assert_eq!(title_label(&titles), None);
+        assert_eq!(titles.window_title(), None);
+
+        titles.apply(&update(TitleKind::Window, "me@host: ~/src"));
+        assert_eq!(title_label(&titles).as_deref(), Some("me@host: ~/src"));
+        assert_eq!(titles.window_title(), Some("me@host: ~/src"));
+
+        // The icon name labels the tab without touching the window title
+        titles.apply(&update(TitleKind::Icon, "src"));
+        assert_eq!(title_label(&titles).as_deref(), Some("src"));
+        assert_eq!(titles.window_title(), Some("me@host: ~/src"));
+
+        titles.apply(&update(TitleKind::Both, "htop"));
+        assert_eq!(title_label(&titles).as_deref(), Some("htop"));
+        assert_eq!(titles.window_title(), Some("htop"));
+    }
+
+    #[test]
+    fn test_empty_title_reverts_to_default() {
+        let mut titles = Titles::default();
+        titles.apply(&update(TitleKind::Both, "htop"));
+        titles.apply(&update(TitleKind::Icon, ""));
+        // The window title still labels the tab
+        assert_eq!(title_label(&titles).as_deref(), Some("htop"));
+
+        titles.apply(&update(TitleKind::Both, ""));
+        assert_eq!(titles, Titles::default());
+    }
+
+    #[test]
+    fn test_long_tab_labels_are_shortened() {
+        let mut titles = Titles::default();
+        let exact = "x".repeat(WIDTH);
+        titles.apply(&update(TitleKind::Icon, &exact));
+        assert_eq!(title_label(&titles), Some(exact));
+
+        titles.apply(&update(TitleKind::Icon, &"y".repeat(40)));
+        let label = title_label(&titles).unwrap();
+        assert_eq!(label.chars().count(), WIDTH);
+        assert!(label.ends_with('…'));
+
+        // Wide characters count as two cells
+        titles.apply(&update(TitleKind::Icon, &"日".repeat(20)));
+        assert_eq!(
+            title_label(&titles).unwrap(),
+            format!("{}…", "日".repeat(11))
+        );
+
+        // The width is the caller's choice, whatever the label comes from
+        titles.set_dir("/home/me/a-rather-long-project-name");
+        assert_eq!(
+            titles.tab_label(TabTitleSource::Cwd, None, 8).as_deref(),
+            Some("a-rathe…")
+        );
+        assert_eq!(
+            titles
+                .tab_label(TabTitleSource::Process, Some("python3"), 4)
+                .as_deref(),
+            Some("pyt…")
+        );
+    }
+
+    #[test]
+    fn test_tab_title_source_from_name() {
+        assert_eq!(TabTitleSource::from_name("cwd"), TabTitleSource::Cwd);
+        assert_eq!(
+            TabTitleSource::from_name("process"),
+            TabTitleSource::Process
+        );
+        assert_eq!(TabTitleSource::from_name("title"), TabTitleSource::Title);
+        assert_eq!(TabTitleSource::from_name("host"), TabTitleSource::Title);
+    }
+
+    #[test]
+    fn test_cwd_label_is_last_directory() {
+        let cwd_label = |dir: &str| {
+            let mut titles = Titles::default();
+            titles.set_dir(dir);
+            titles.tab_label(TabTitleSource::Cwd, None, WIDTH)
+        };
+        assert_eq!(cwd_label("/home/me/src").as_deref(), Some("src"));
+        assert_eq!(cwd_label("/home/me/src/").as_deref(), Some("src"));
+        assert_eq!(cwd_label("C:\\Users\\me").as_deref(), Some("me"));
+        assert_eq!(cwd_label("/").as_deref(), Some("/"));
+
+        // Titles don't label the tab when the directory is asked for
+        let mut titles = Titles::default();
+        titles.apply(&update(TitleKind::Both, "htop"));
+        assert_eq!(titles.tab_label(TabTitleSource::Cwd, None, WIDTH), None);
+    }
+
+    #[test]
+    fn test_process_label_falls_back_to_default() {
+        let mut titles = Titles::default();
+        titles.set_dir("/tmp");
+        assert_eq!(
+            titles
+                .tab_label(TabTitleSource::Process, Some("vim"), WIDTH)
+                .as_deref(),
+            Some("vim")
+        );
+        assert_eq!(titles.tab_label(TabTitleSource::Process, None, WIDTH), None);
+        assert_eq!(
+            titles.tab_label(TabTitleSource::Process, Some(""), WIDTH),
+            None
+        );
+    }
+
+    #[test]
+    fn test_process_names_of_running_and_missing_processes() {
+        let now = Instant::now();
+        let mut names = ProcessNames::default();
+        let own = names.name(std::process::id(), now);
+        assert!(own.is_some_and(|name| !name.is_empty()));
+        // A pid above any system's limit is never running
+        assert_eq!(names.name(u32::MAX - 1, now), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Winit input for the GPU window
+//!
+//! The window gets key and mouse events from winit, while the keybinding
+//! manager and the terminal's handlers take crossterm events. These turn one
+//! into the other, so a key or click does the same thing in a window as in
+//! the TTY.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use winit::event::MouseScrollDelta;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use super::MOUSE_SCROLL_LINES;
+
+/// Crossterm modifiers for winit's modifier state
+///
+/// On macOS, Cmd counts as Ctrl.
+#[must_use]
+pub fn modifiers(state: ModifiersState) -> KeyModifiers {
+    let mut modifiers = KeyModifiers::NONE;
+    if state.control_key() || (cfg!(target_os = "macos") && state.super_key()) {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    if state.shift_key() {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if state.alt_key() {
+        modifiers |= KeyModifiers::ALT;
+    }
+    modifiers
+}
+
+/// Crossterm key event for a pressed winit logical key
+///
+/// Returns `None` for keys the terminal doesn't handle. Shift+Tab stays
+/// `Tab` with Shift held, which is how key bindings name it.
+#[must_use]
+pub fn key_event(key: &Key, state: ModifiersState) -> Option<KeyEvent> {
+    let code = match key {
+        Key::Character(text) => KeyCode::Char(text.chars().next()?),
+        Key::Named(NamedKey::Space) => KeyCode::Char(' '),
+        Key::Named(NamedKey::Enter) => KeyCode::Enter,
+        Key::Named(NamedKey::Tab) => KeyCode::Tab,
+        Key::Named(NamedKey::Backspace) => KeyCode::Backspace,
+        Key::Named(NamedKey::Escape) => KeyCode::Esc,
+        Key::Named(NamedKey::ArrowUp) => KeyCode::Up,
+        Key::Named(NamedKey::ArrowDown) => KeyCode::Down,
+        Key::Named(NamedKey::ArrowLeft) => KeyCode::Left,
+        Key::Named(NamedKey::ArrowRight) => KeyCode::Right,
+        Key::Named(NamedKey::Home) => KeyCode::Home,
+        Key::Named(NamedKey::End) => KeyCode::End,
+        Key::Named(NamedKey::PageUp) => KeyCode::PageUp,
+        Key::Named(NamedKey::PageDown) => KeyCode::PageDown,
+        Key::Named(NamedKey::Insert) => KeyCode::Insert,
+        Key::Named(NamedKey::Delete) => KeyCode::Delete,
+        _ => return None,
+    };
+    Some(KeyEvent::new(code, modifiers(state)))
+}
+
+/// Crossterm button for a winit mouse button; extra buttons are ignored
+#[must_use]
+pub fn mouse_button(button: winit::event::MouseButton) -> Option<MouseButton> {
+    match button {
+        winit::event::MouseButton::Left => Some(MouseButton::Left),
+        winit::event::MouseButton::Right => Some(MouseButton::Right),
+        winit::event::MouseButton::Middle => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// Where the pointer is on the cell grid and which button is held
+///
+/// Winit reports pixel positions and button changes separately; crossterm
+/// reports cells, and moves with a button held as drags.
+#[derive(Debug, Default)]
+pub struct Pointer {
+    cell: (u16, u16),
+    held: Option<MouseButton>,
+}
+
+impl Pointer {
+    /// Mouse event for the pointer moving to pixel `(x, y)` on cells
+    /// `cell_size` pixels wide and high
+    ///
+    /// Returns `None` while it stays within the same cell.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn moved(&mut self, (x, y): (f64, f64), cell_size: (f32, f32)) -> Option<MouseEventKind> {
+        // Float-to-int casts saturate, so positions off the window clamp to the grid edge
+        let cell = (
+            (x / f64::from(cell_size.0)).floor() as u16,
+            (y / f64::from(cell_size.1)).floor() as u16,
+        );
+        if cell == self.cell {
+            return None;
+        }
+        self.cell = cell;
+        Some(match self.held {
+            Some(button) => MouseEventKind::Drag(button),
+            None => MouseEventKind::Moved,
+        })
+    }
+
+    /// Mouse event for `button` being pressed or released
+    pub fn button(&mut self, button: MouseButton, pressed: bool) -> MouseEventKind {
+        if pressed {
+            self.held = Some(button);
+            MouseEventKind::Down(button)
+        } else {
+            self.held = None;
+            MouseEventKind::Up(button)
+        }
+    }
+
+    /// Crossterm event of `kind` at the pointer's cell
+    #[must_use]
+    pub fn event(&self, kind: MouseEventKind, state: ModifiersState) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: self.cell.0,
+            row: self.cell.1,
+            modifiers: modifiers(state),
+        }
+    }
+}
+
+/// Turns wheel movement into scroll events of [`MOUSE_SCROLL_LINES`] lines each
+///
+/// Trackpads report a few pixels at a time, so what doesn't make up a whole
+/// event carries over to the next movement.
+#[derive(Debug, Default)]
+pub struct Wheel {
+    remainder: f32,
+}
+
+impl Wheel {
+    /// Scroll events for a wheel movement on cells `cell_height` pixels high;
+    /// positive scrolls up, back through history
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn scroll(&mut self, delta: MouseScrollDelta, cell_height: f32) -> i32 {
+        let events = match delta {
+            // One event per notch
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(pos) => {
+                pos.y as f32 / cell_height / MOUSE_SCROLL_LINES as f32
+            }
+        } + self.remainder;
+        let whole = events.trunc();
+        self.remainder = events - whole;
+        whole as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+
+    #[test]
+    fn test_key_event_keeps_modifiers() {
+        let ctrl_shift = ModifiersState::CONTROL | ModifiersState::SHIFT;
+        assert_eq!(
+            key_event(&Key::Character("C".into()), ctrl_shift),
+            Some(KeyEvent::new(
+                KeyCode::Char('C'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            key_event(&Key::Named(NamedKey::Tab), ModifiersState::SHIFT),
+            Some(KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            key_event(&Key::Named(NamedKey::Space), ModifiersState::empty()),
+            Some(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            key_event(&Key::Named(NamedKey::CapsLock), ModifiersState::empty()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pointer_drags_while_a_button_is_held() {
+        let mut pointer = Pointer::default();
+        let cell = (10.0, 20.0);
+
+        assert_eq!(
+            pointer.moved((35.0, 45.0), cell),
+            Some(MouseEventKind::Moved)
+        );
+        assert_eq!(pointer.moved((38.0, 58.0), cell), None);
+        assert_eq!(
+            pointer.button(MouseButton::Left, true),
+            MouseEventKind::Down(MouseButton::Left)
+        );
+        assert_eq!(
+            pointer.moved((55.0, 45.0), cell),
+            Some(MouseEventKind::Drag(MouseButton::Left))
+        );
+        pointer.button(MouseButton::Left, false);
+
+        let event = pointer.event(MouseEventKind::Moved, ModifiersState::CONTROL);
+        assert_eq!((event.column, event.row), (5, 2));
+        assert_eq!(event.modifiers, KeyModifiers::CONTROL);
+    }
+
+    #[test]
+    fn test_wheel_carries_trackpad_pixels_over() {
+        let mut wheel = Wheel::default();
+        assert_eq!(
+            wheel.scroll(MouseScrollDelta::LineDelta(0.0, -2.0), 20.0),
+            -2
+        );
+
+        // Three lines of 20 pixels make one scroll event
+        let pixels = |y| MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, y));
+        assert_eq!(wheel.scroll(pixels(40.0), 20.0), 0);
+        assert_eq!(wheel.scroll(pixels(40.0), 20.0), 1);
+    }
+}
@@ -0,0 +1,300 @@
+//! Line wrapping for rendered terminal output
+//!
+//! Turns logical output lines into the visual rows drawn in the content area,
+//! either wrapping long lines with a continuation indent or truncating them
+//! with an ellipsis. Widths are measured in terminal cells, so wide (CJK,
+//! emoji) characters count as two columns.
+
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Indent applied to continuation rows of a wrapped line
+pub const CONTINUATION_INDENT: usize = 2;
+
+/// Marker appended to truncated lines
+pub const TRUNCATION_MARKER: &str = "…";
+
+/// How lines wider than the content area are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineWrap {
+    /// Continue on the next row, indented by [`CONTINUATION_INDENT`]
+    #[default]
+    Wrap,
+    /// Cut at the right edge and mark with [`TRUNCATION_MARKER`]
+    Truncate,
+}
+
+impl LineWrap {
+    /// Parse the `terminal.line_wrap` config value, defaulting to wrapping
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "truncate" => Self::Truncate,
+            _ => Self::Wrap,
+        }
+    }
+}
+
+/// Display width of a line in terminal cells
+#[must_use]
+pub fn line_width(line: &Line<'_>) -> usize {
+    line.spans.iter().map(|span| span.content.width()).sum()
+}
+
+/// Number of visual rows a line occupies at the given width
+#[must_use]
+pub fn row_count(line: &Line<'static>, width: usize, mode: LineWrap) -> usize {
+    if mode == LineWrap::Truncate || width == 0 || line_width(line) <= width {
+        return 1;
+    }
+    let mut rows = Vec::new();
+    wrap_line(line, width, &mut rows);
+    rows.len()
+}
+
+/// Lay out logical lines as visual rows for a content area `width` cells wide
+#[must_use]
+pub fn layout_lines(lines: &[Line<'static>], width: usize, mode: LineWrap) -> Vec<Line<'static>> {
+    let mut rows = Vec::with_capacity(lines.len());
+    for line in lines {
+        if width == 0 || line_width(line) <= width {
+            rows.push(line.clone());
+            continue;
+        }
+        match mode {
+            LineWrap::Wrap => wrap_line(line, width, &mut rows),
+            LineWrap::Truncate => rows.push(truncate_line(line, width)),
+        }
+    }
+    rows
+}
+
+/// Map a visual row and cell column back to a logical line and cell column
+///
+/// `row` counts from the first row produced by [`layout_lines`] for the same
+/// arguments. Returns `None` when the row is past the end of the layout.
+#[must_use]
+pub fn logical_position(
+    lines: &[Line<'static>],
+    width: usize,
+    mode: LineWrap,
+    row: usize,
+    col: usize,
+) -> Option<(usize, usize)> {
+    let mut remaining = row;
+    for (index, line) in lines.iter().enumerate() {
+        if mode == LineWrap::Truncate || width == 0 || line_width(line) <= width {
+            if remaining == 0 {
+                return Some((index, col));
+            }
+            remaining -= 1;
+            continue;
+        }
+
+        let mut rows = Vec::new();
+        wrap_line(line, width, &mut rows);
+        if remaining < rows.len() {
+            if remaining == 0 {
+                return Some((index, col));
+            }
+            // Continuation rows start with the indent, which maps to no column
+            let indent = width - continuation_width(width);
+            let before: usize = line_width(&rows[0])
+                + rows[1..remaining]
+                    .iter()
+                    .map(|r| line_width(r) - indent)
+                    .sum::<usize>();
+            return Some((index, before + col.saturating_sub(indent)));
+        }
+        remaining -= rows.len();
+    }
+    None
+}
+
+/// Usable width of a continuation row
+fn continuation_width(width: usize) -> usize {
+    if width > CONTINUATION_INDENT {
+        width - CONTINUATION_INDENT
+    } else {
+        width
+    }
+}
+
+/// Split a line into rows of at most `width` cells
+fn wrap_line(line: &Line<'static>, width: usize, rows: &mut Vec<Line<'static>>) {
+    let indent = width - continuation_width(width);
+    let mut row_spans: Vec<Span<'static>> = Vec::new();
+    let mut row_width = 0;
+
+    for span in &line.spans {
+        let mut text = String::new();
+        for ch in span.content.chars() {
+            let ch_width = ch.width().unwrap_or(0);
+            if row_width + ch_width > width && row_width > 0 {
+                if !text.is_empty() {
+                    row_spans.push(Span::styled(std::mem::take(&mut text), span.style));
+                }
+                rows.push(Line::from(std::mem::take(&mut row_spans)));
+                if indent > 0 {
+                    row_spans.push(Span::raw(" ".repeat(indent)));
+                }
+                row_width = indent;
+            }
+            text.push(ch);
+            row_width += ch_width;
+        }
+        if !text.is_empty() {
+            row_spans.push(Span::styled(text, span.style));
+        }
+    }
+
+    rows.push(Line::from(row_spans));
+}
+
+/// Cut a line to `width` cells, ending with the truncation marker
+fn truncate_line(line: &Line<'static>, width: usize) -> Line<'static> {
+    let budget = width.saturating_sub(TRUNCATION_MARKER.width());
+    let mut spans = Vec::new();
+    let mut used = 0;
+    let mut marker_style = Style::default();
+
+    'spans: for span in &line.spans {
+        marker_style = span.style;
+        let mut text = String::new();
+        for ch in span.content.chars() {
+            let ch_width = ch.width().unwrap_or(0);
+            if used + ch_width > budget {
+                if !text.is_empty() {
+                    spans.push(Span::styled(text, span.style));
+                }
+                break 'spans;
+            }
+            text.push(ch);
+            used += ch_width;
+        }
+        if !text.is_empty() {
+            spans.push(Span::styled(text, span.style));
+        }
+    }
+
+    spans.push(Span::styled(TRUNCATION_MARKER, marker_style));
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_short_lines_are_unchanged() {
+        let lines = vec![Line::from("hello")];
+        for mode in [LineWrap::Wrap, LineWrap::Truncate] {
+            let rows = layout_lines(&lines, 10, mode);
+            assert_eq!(rows.len(), 1);
+            assert_eq!(text(&rows[0]), "hello");
+            assert_eq!(row_count(&lines[0], 10, mode), 1);
+        }
+    }
+
+    #[test]
+    fn test_wrap_with_continuation_indent() {
+        let lines = vec![Line::from("abcdefghijklmnop")];
+        let rows = layout_lines(&lines, 8, LineWrap::Wrap);
+
+        let texts: Vec<String> = rows.iter().map(text).collect();
+        assert_eq!(texts, vec!["abcdefgh", "  ijklmn", "  op"]);
+        assert!(rows.iter().all(|row| line_width(row) <= 8));
+        assert_eq!(row_count(&lines[0], 8, LineWrap::Wrap), rows.len());
+    }
+
+    #[test]
+    fn test_wrap_preserves_span_styles() {
+        let red = Style::default().fg(Color::Red);
+        let line = Line::from(vec![Span::styled("abcd", red), Span::raw("efgh")]);
+        let rows = layout_lines(&[line], 6, LineWrap::Wrap);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].spans[0].style, red);
+        assert_eq!(text(&rows[0]), "abcdef");
+        assert_eq!(text(&rows[1]), "  gh");
+        assert_eq!(rows[1].spans.last().unwrap().style, Style::default());
+    }
+
+    #[test]
+    fn test_wrap_wide_characters() {
+        let line = Line::from("日本語テスト");
+        let rows = layout_lines(&[line.clone()], 5, LineWrap::Wrap);
+
+        // 12 cells: 4 on the first row (a third glyph would overflow), then 2 per row
+        assert_eq!(text(&rows[0]), "日本");
+        assert!(rows.iter().all(|row| line_width(row) <= 5));
+        assert_eq!(
+            rows.iter().map(text).collect::<String>().replace(' ', ""),
+            "日本語テスト"
+        );
+    }
+
+    #[test]
+    fn test_truncate_adds_marker() {
+        let line = Line::from("abcdefghijklmnop");
+        let rows = layout_lines(&[line.clone()], 8, LineWrap::Truncate);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(text(&rows[0]), "abcdefg…");
+        assert_eq!(line_width(&rows[0]), 8);
+        assert_eq!(row_count(&line, 8, LineWrap::Truncate), 1);
+    }
+
+    #[test]
+    fn test_row_count_matches_layout() {
+        let line = Line::from("x".repeat(50));
+        // First row holds 10 cells, each continuation row 8 more after the indent
+        assert_eq!(row_count(&line, 10, LineWrap::Wrap), 6);
+        for width in [1, 2, 3, 7, 10, 49, 50, 51] {
+            let rows = layout_lines(&[line.clone()], width, LineWrap::Wrap);
+            assert_eq!(
+                row_count(&line, width, LineWrap::Wrap),
+                rows.len(),
+                "width {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_logical_position() {
+        let lines = vec![Line::from("short"), Line::from("abcdefghijklmnop")];
+
+        assert_eq!(
+            logical_position(&lines, 8, LineWrap::Wrap, 0, 3),
+            Some((0, 3))
+        );
+        assert_eq!(
+            logical_position(&lines, 8, LineWrap::Wrap, 1, 7),
+            Some((1, 7))
+        );
+        // Second row of the wrapped line is "  ijklmn"
+        assert_eq!(
+            logical_position(&lines, 8, LineWrap::Wrap, 2, 2),
+            Some((1, 8))
+        );
+        assert_eq!(
+            logical_position(&lines, 8, LineWrap::Wrap, 3, 3),
+            Some((1, 15))
+        );
+        assert_eq!(logical_position(&lines, 8, LineWrap::Wrap, 4, 0), None);
+        assert_eq!(logical_position(&lines, 8, LineWrap::Truncate, 2, 0), None);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(LineWrap::from_name("wrap"), LineWrap::Wrap);
+        assert_eq!(LineWrap::from_name("truncate"), LineWrap::Truncate);
+        assert_eq!(LineWrap::from_name("bogus"), LineWrap::Wrap);
+    }
+}
@@ -90,6 +90,18 @@ static COMMON_COMMANDS: &[&str] = &[
     "cargo check",
 ];
 
+/// Most suggestions offered at once
+const MAX_SUGGESTIONS: usize = 15;
+
+/// Fuzzy score for each matched character
+const FUZZY_MATCH: i64 = 16;
+/// Fuzzy bonus for a character matched right after the previous one
+const FUZZY_CONSECUTIVE: i64 = 15;
+/// Fuzzy bonus for a character matched at the start of a word
+const FUZZY_WORD_START: i64 = 10;
+/// Fuzzy penalty for each character skipped before a match
+const FUZZY_GAP: i64 = 1;
+
 /// Bug #28: Use Arc<str> for shared strings to avoid cloning
 type SharedString = Arc<str>;
 
@@ -195,7 +207,7 @@ impl Autocomplete {
             let shared: SharedString = Arc::from(cmd);
             if seen.insert(shared.clone()) {
                 self.current_suggestions.push(shared);
-                if self.current_suggestions.len() >= 15 {
+                if self.current_suggestions.len() >= MAX_SUGGESTIONS {
                     break;
                 }
             }
@@ -205,7 +217,7 @@ impl Autocomplete {
         let path_suggestions = Self::get_path_suggestions(prefix);
         for path_str in &path_suggestions {
             let shared: SharedString = Arc::from(path_str.as_str());
-            if seen.insert(shared.clone()) && self.current_suggestions.len() < 15 {
+            if seen.insert(shared.clone()) && self.current_suggestions.len() < MAX_SUGGESTIONS {
                 self.current_suggestions.push(shared);
             }
         }
@@ -217,6 +229,81 @@ impl Autocomplete {
             .collect()
     }
 
+    /// Suggest completions for `input`, resolving paths against `cwd`
+    ///
+    /// History entries starting with `input` come first, most recent first.
+    /// If the last word of `input` looks like a path, the matching entries of
+    /// its directory follow, sorted by name. A `cwd` that can't be read adds
+    /// no entries. At most 15 suggestions are returned.
+    #[must_use]
+    pub fn suggest(&self, input: &str, cwd: &Path) -> Vec<String> {
+        let mut suggestions: Vec<String> = self
+            .history
+            .iter()
+            .filter(|cmd| cmd.starts_with(input))
+            .take(MAX_SUGGESTIONS)
+            .map(std::string::ToString::to_string)
+            .collect();
+
+        if Self::completes_path(input) {
+            let token = last_word(input);
+            let command = &input[..input.len() - token.len()];
+            for entry in directory_entries(token, cwd) {
+                if suggestions.len() >= MAX_SUGGESTIONS {
+                    break;
+                }
+                let suggestion = format!("{command}{entry}");
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+
+        suggestions
+    }
+
+    /// Rest of the most recent history entry extending `input`, fish-style
+    ///
+    /// This is shown as ghost text after the cursor. `None` for empty input
+    /// or when no history entry is longer than `input` and starts with it.
+    #[must_use]
+    pub fn ghost_text(&self, input: &str) -> Option<&str> {
+        if input.is_empty() {
+            return None;
+        }
+        self.history
+            .iter()
+            .find_map(|cmd| cmd.strip_prefix(input).filter(|rest| !rest.is_empty()))
+    }
+
+    /// History entries fuzzily matching `input`, best first
+    ///
+    /// `gc` matches `git commit`: the characters of `input` must appear in
+    /// order, and matches that are consecutive or start a word score higher
+    /// (see [`fuzzy_match`]). Equal scores keep the most recent entry first.
+    /// Empty input returns the most recent history with a score of 0.
+    #[must_use]
+    pub fn fuzzy_suggest(&self, input: &str) -> Vec<(String, i64)> {
+        let mut matches: Vec<(String, i64)> = self
+            .history
+            .iter()
+            .filter_map(|cmd| {
+                let (score, _) = fuzzy_match(input, cmd)?;
+                Some((cmd.to_string(), score))
+            })
+            .collect();
+        // Stable, so ties stay in history (most recent first) order
+        matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        matches.truncate(MAX_SUGGESTIONS);
+        matches
+    }
+
+    /// Whether [`suggest`](Self::suggest) would complete the last word of `input` as a path
+    #[must_use]
+    pub fn completes_path(input: &str) -> bool {
+        looks_like_path(last_word(input))
+    }
+
     /// Get file path suggestions based on the current input prefix
     /// Supports: "cd dir", "cat file", "vim path", bare paths starting with / or ./ or ~/
     fn get_path_suggestions(prefix: &str) -> Vec<String> {
@@ -372,6 +459,142 @@ impl Autocomplete {
     }
 }
 
+/// Fuzzily match `pattern` against `candidate`, ignoring case
+///
+/// Returns the score of the best alignment and the char indices of
+/// `candidate` it matched, or `None` if the characters of `pattern` don't all
+/// appear in order. Each match scores a base amount, plus a bonus if it
+/// directly follows the previous match or starts a word, minus a small
+/// penalty per character skipped. An empty pattern matches with score 0.
+#[must_use]
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = candidate.chars().collect();
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    if pattern.len() > text.len() {
+        return None;
+    }
+
+    let matches = |p: char, t: char| t.to_lowercase().eq(std::iter::once(p));
+    let word_start = |j: usize| {
+        j == 0 || matches!(text[j - 1], ' ' | '-' | '_' | '/' | '\\' | '.' | ':')
+    };
+    let n = text.len();
+
+    // best[i][j]: best score with pattern[i] matched at text[j]; from[i][j]
+    // is where pattern[i - 1] was matched in that alignment
+    let mut best = vec![vec![None::<i64>; n]; pattern.len()];
+    let mut from = vec![vec![0usize; n]; pattern.len()];
+    for (j, &t) in text.iter().enumerate() {
+        if matches(pattern[0], t) {
+            let bonus = if word_start(j) { FUZZY_WORD_START } else { 0 };
+            best[0][j] = Some(FUZZY_MATCH + bonus - FUZZY_GAP * j as i64);
+        }
+    }
+    for i in 1..pattern.len() {
+        // Best of best[i - 1][k] + gap adjustment over k < j - 1, kept as
+        // score + k so the gap to j is one subtraction
+        let mut far: Option<(i64, usize)> = None;
+        for j in i..n {
+            if j >= 2 {
+                if let Some(score) = best[i - 1][j - 2] {
+                    let shifted = score + (j - 2) as i64 * FUZZY_GAP;
+                    if far.is_none_or(|(s, _)| shifted > s) {
+                        far = Some((shifted, j - 2));
+                    }
+                }
+            }
+            if !matches(pattern[i], text[j]) {
+                continue;
+            }
+            let bonus = FUZZY_MATCH + if word_start(j) { FUZZY_WORD_START } else { 0 };
+            let next_to = best[i - 1][j - 1].map(|s| (s + FUZZY_CONSECUTIVE, j - 1));
+            let gapped = far.map(|(s, k)| (s - (j - 1) as i64 * FUZZY_GAP, k));
+            let chosen = match (next_to, gapped) {
+                (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+                (a, b) => a.or(b),
+            };
+            if let Some((score, k)) = chosen {
+                best[i][j] = Some(score + bonus);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut j, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| Some((j, (*score)?)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..pattern.len()).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some((score, positions))
+}
+
+/// The word being typed at the end of `input`; empty after a space
+fn last_word(input: &str) -> &str {
+    input.split(char::is_whitespace).next_back().unwrap_or("")
+}
+
+/// Whether `token` should be completed as a file path
+fn looks_like_path(token: &str) -> bool {
+    token.starts_with('.')
+        || token.starts_with('~')
+        || token.contains('/')
+        || token.contains('\\')
+        || (token.len() >= 2
+            && token.as_bytes()[0].is_ascii_alphabetic()
+            && token.as_bytes()[1] == b':')
+}
+
+/// Completions for the path `token`, keeping the directory part as typed
+///
+/// Relative directories are read from `cwd`. Directories get a trailing
+/// separator; hidden entries are skipped unless the name being completed
+/// starts with `.`.
+fn directory_entries(token: &str, cwd: &Path) -> Vec<String> {
+    let (typed_dir, name_prefix) = match token.rfind(['/', '\\']) {
+        Some(pos) => token.split_at(pos + 1),
+        None => ("", token),
+    };
+
+    let dir = match typed_dir.strip_prefix('~') {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches(['/', '\\'])),
+            None => return Vec::new(),
+        },
+        None => cwd.join(typed_dir),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(name_prefix)
+                || (name.starts_with('.') && !name_prefix.starts_with('.'))
+            {
+                return None;
+            }
+            Some(if entry.path().is_dir() {
+                format!("{typed_dir}{name}{}", std::path::MAIN_SEPARATOR)
+            } else {
+                format!("{typed_dir}{name}")
+            })
+        })
+        .collect();
+    names.sort();
+    names
+}
+
 impl Default for Autocomplete {
     fn default() -> Self {
         Self::new()
@@ -585,4 +808,150 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_looks_like_path() {
+        for token in ["./src", "../", ".", "~/code", "/etc", "src/main.rs", "C:\\Users"] {
+            assert!(looks_like_path(token), "{token}");
+        }
+        for token in ["", "git", "status", "-la"] {
+            assert!(!looks_like_path(token), "{token}");
+        }
+
+        // Only the word being typed counts
+        assert!(Autocomplete::completes_path("cd ../fur"));
+        assert!(!Autocomplete::completes_path("cat ./notes.txt "));
+        assert!(!Autocomplete::completes_path("git status"));
+    }
+
+    #[test]
+    fn test_suggest_ranks_history_before_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("setup.sh"), "").unwrap();
+        std::fs::write(dir.path().join(".secret"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("cat ./setup.sh".to_string());
+        autocomplete.add_to_history("cat ./scripts/run.sh".to_string());
+        autocomplete.add_to_history("ls".to_string());
+
+        let suggestions = autocomplete.suggest("cat ./s", dir.path());
+        assert_eq!(
+            suggestions,
+            vec![
+                "cat ./scripts/run.sh".to_string(),
+                "cat ./setup.sh".to_string(),
+                format!("cat ./src{}", std::path::MAIN_SEPARATOR),
+            ]
+        );
+
+        // Hidden entries only when asked for
+        assert_eq!(
+            autocomplete.suggest("cat ./.s", dir.path()),
+            vec!["cat ./.secret".to_string()]
+        );
+        // A word that isn't a path only gets history
+        assert!(autocomplete.suggest("cat s", dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_with_missing_cwd_uses_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("gone");
+
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("vim ./main.rs".to_string());
+
+        assert_eq!(
+            autocomplete.suggest("vim ./", &missing),
+            vec!["vim ./main.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_suggest_caps_results() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..30 {
+            std::fs::write(dir.path().join(format!("file{i:02}")), "").unwrap();
+        }
+
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("rm ./file00".to_string());
+
+        let suggestions = autocomplete.suggest("rm ./f", dir.path());
+        assert_eq!(suggestions.len(), MAX_SUGGESTIONS);
+        assert_eq!(suggestions[0], "rm ./file00");
+        // The history hit isn't repeated as a directory entry
+        assert_eq!(suggestions[1], "rm ./file01");
+    }
+
+    #[test]
+    fn test_fuzzy_match_scores() {
+        let (commit, positions) = fuzzy_match("gc", "git commit").unwrap();
+        let (config, _) = fuzzy_match("gc", "grep config").unwrap();
+        assert!(commit > config, "{commit} <= {config}");
+        assert_eq!(positions, vec![0, 4]);
+
+        // Consecutive and word-start matches beat scattered ones
+        let (together, positions) = fuzzy_match("com", "git commit").unwrap();
+        assert_eq!(positions, vec![4, 5, 6]);
+        let (scattered, _) = fuzzy_match("com", "cargo fmt").unwrap();
+        assert!(together > scattered);
+
+        assert_eq!(fuzzy_match("GC", "git commit").unwrap().1, vec![0, 4]);
+        assert!(fuzzy_match("cg", "git commit").is_none());
+        assert!(fuzzy_match("gitx", "git").is_none());
+        assert_eq!(fuzzy_match("", "ls"), Some((0, Vec::new())));
+    }
+
+    #[test]
+    fn test_fuzzy_suggest_ranking() {
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("git commit".to_string());
+        autocomplete.add_to_history("grep config".to_string());
+        autocomplete.add_to_history("ls -la".to_string());
+
+        let suggestions = autocomplete.fuzzy_suggest("gc");
+        let commands: Vec<&str> = suggestions.iter().map(|(cmd, _)| cmd.as_str()).collect();
+        assert_eq!(commands, vec!["git commit", "grep config"]);
+        assert!(suggestions[0].1 > suggestions[1].1);
+    }
+
+    #[test]
+    fn test_fuzzy_suggest_ties_and_empty_input() {
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("git add".to_string());
+        autocomplete.add_to_history("git log".to_string());
+
+        // Same score, so the more recent command comes first
+        let suggestions = autocomplete.fuzzy_suggest("git");
+        assert_eq!(suggestions[0].1, suggestions[1].1);
+        assert_eq!(suggestions[0].0, "git log");
+
+        let recent = autocomplete.fuzzy_suggest("");
+        assert_eq!(
+            recent,
+            vec![("git log".to_string(), 0), ("git add".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_ghost_text_from_history() {
+        let mut autocomplete = Autocomplete::new();
+        autocomplete.add_to_history("git status".to_string());
+        autocomplete.add_to_history("git stash pop".to_string());
+        autocomplete.add_to_history("échos".to_string());
+
+        // The most recent matching command wins
+        assert_eq!(autocomplete.ghost_text("git st"), Some("ash pop"));
+        assert_eq!(autocomplete.ghost_text("git stat"), Some("us"));
+        assert_eq!(autocomplete.ghost_text("éch"), Some("os"));
+
+        // Nothing to add: empty input, a complete command, or no match
+        assert_eq!(autocomplete.ghost_text(""), None);
+        assert_eq!(autocomplete.ghost_text("git status"), None);
+        assert_eq!(autocomplete.ghost_text("cargo"), None);
+    }
 }
@@ -1,6 +1,19 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use sysinfo::{Disks, System};
+use sysinfo::{Disks, Pid, System};
+
+/// Sparkline bars from lowest to highest
+const SPARK_BARS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+
+/// Samples kept for the sparklines
+pub const HISTORY_LEN: usize = 30;
+
+/// Shown in place of process stats once the process has exited
+pub const EXITED_PROCESS: &str = "\u{2014}";
 
 /// System resource monitor for displaying resource usage (optimized with caching)
 pub struct ResourceMonitor {
@@ -9,6 +22,20 @@ pub struct ResourceMonitor {
     update_interval: Duration,
     // Cached stats to avoid recomputing when not needed
     cached_stats: Option<ResourceStats>,
+    // Process table used by `process_stats` and when it was taken
+    process_tree: Option<(Instant, ProcessTree)>,
+    // Recent CPU and memory usage in percent, oldest first
+    cpu_history: VecDeque<f32>,
+    memory_history: VecDeque<f32>,
+}
+
+/// Value a sparkline can be drawn for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Average CPU usage across cores
+    Cpu,
+    /// Share of memory in use
+    Memory,
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +51,109 @@ pub struct ResourceStats {
     pub disk_usage: Vec<DiskInfo>,
 }
 
+/// CPU and memory used by a process together with its descendants
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessStats {
+    /// CPU usage in percent of one core (can exceed 100 on several cores)
+    pub cpu_usage: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+}
+
+impl fmt::Display for ProcessStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}% {}",
+            self.cpu_usage,
+            ResourceMonitor::format_bytes(self.memory)
+        )
+    }
+}
+
+impl ProcessStats {
+    /// Display text for `stats`, or [`EXITED_PROCESS`] if there are none
+    #[must_use]
+    pub fn label(stats: Option<Self>) -> String {
+        stats.map_or_else(|| EXITED_PROCESS.to_string(), |stats| stats.to_string())
+    }
+}
+
+/// One running process as reported by a [`ProcessSource`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    /// CPU usage in percent of one core
+    pub cpu_usage: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+}
+
+/// Where per-process figures come from
+pub trait ProcessSource {
+    /// Every running process
+    fn processes(&self) -> Vec<ProcessSample>;
+}
+
+impl ProcessSource for System {
+    fn processes(&self) -> Vec<ProcessSample> {
+        System::processes(self)
+            .iter()
+            .map(|(pid, process)| ProcessSample {
+                pid: pid.as_u32(),
+                parent: process.parent().map(Pid::as_u32),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+            })
+            .collect()
+    }
+}
+
+/// Snapshot of the process table indexed by parent, shared by every tab
+#[derive(Debug, Default)]
+pub struct ProcessTree {
+    processes: HashMap<u32, ProcessSample>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTree {
+    /// Index the processes currently reported by `source`
+    #[must_use]
+    pub fn new(source: &impl ProcessSource) -> Self {
+        let mut tree = Self::default();
+        for process in source.processes() {
+            if let Some(parent) = process.parent {
+                tree.children.entry(parent).or_default().push(process.pid);
+            }
+            tree.processes.insert(process.pid, process);
+        }
+        tree
+    }
+
+    /// Stats for `pid` and all its descendants, or `None` if `pid` isn't running
+    #[must_use]
+    pub fn stats(&self, pid: u32) -> Option<ProcessStats> {
+        self.processes.get(&pid)?;
+        let mut stats = ProcessStats {
+            cpu_usage: 0.0,
+            memory: 0,
+        };
+        let mut pending = vec![pid];
+        while let Some(pid) = pending.pop() {
+            let Some(process) = self.processes.get(&pid) else {
+                continue;
+            };
+            stats.cpu_usage += process.cpu_usage;
+            stats.memory = stats.memory.saturating_add(process.memory);
+            if let Some(kids) = self.children.get(&pid) {
+                pending.extend(kids);
+            }
+        }
+        Some(stats)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DiskInfo {
     pub name: String,
@@ -44,7 +174,69 @@ impl ResourceMonitor {
             last_update: Instant::now(),
             update_interval: Duration::from_millis(500), // Update every 500ms
             cached_stats: None,
+            process_tree: None,
+            cpu_history: VecDeque::with_capacity(HISTORY_LEN),
+            memory_history: VecDeque::with_capacity(HISTORY_LEN),
+        }
+    }
+
+    /// Record `stats` in the sparkline history, dropping the oldest sample when full
+    ///
+    /// [`Self::get_stats`] calls this each time it refreshes, so samples are
+    /// one update interval apart however often the terminal redraws.
+    pub fn push_sample(&mut self, stats: &ResourceStats) {
+        for (history, value) in [
+            (&mut self.cpu_history, stats.cpu_usage),
+            (&mut self.memory_history, stats.memory_percent),
+        ] {
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(value);
+        }
+    }
+
+    /// Recent samples of `metric` as one bar per sample, oldest first
+    ///
+    /// Bars are scaled to 0-100%, so equal heights mean equal usage.
+    #[must_use]
+    pub fn sparkline(&self, metric: Metric) -> String {
+        let history = match metric {
+            Metric::Cpu => &self.cpu_history,
+            Metric::Memory => &self.memory_history,
+        };
+        history
+            .iter()
+            .map(|value| {
+                let top = (SPARK_BARS.len() - 1) as f32;
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let level = (value.clamp(0.0, 100.0) / 100.0 * top).round() as usize;
+                SPARK_BARS[level]
+            })
+            .collect()
+    }
+
+    /// CPU and memory of process `pid` and its children
+    ///
+    /// Returns `None` once the process has exited. The process table is
+    /// refreshed and indexed at most once per update interval and shared by
+    /// every call in between, so calling this for every tab in a frame is
+    /// cheap. CPU usage reads 0 until the second refresh.
+    pub fn process_stats(&mut self, pid: u32) -> Option<ProcessStats> {
+        let stale = self
+            .process_tree
+            .as_ref()
+            .is_none_or(|(at, _)| at.elapsed() >= self.update_interval);
+        if stale {
+            let Ok(mut system) = self.system.lock() else {
+                return None;
+            };
+            system.refresh_processes();
+            let tree = ProcessTree::new(&*system);
+            drop(system);
+            self.process_tree = Some((Instant::now(), tree));
         }
+        self.process_tree.as_ref()?.1.stats(pid)
     }
 
     /// Get current resource statistics (with caching)
@@ -105,6 +297,7 @@ impl ResourceMonitor {
         // Current implementation provides basic disk info available cross-platform
         let (network_rx, network_tx) = Self::get_network_stats();
         let disk_usage = Self::get_disk_info(&system);
+        drop(system);
 
         let stats = ResourceStats {
             cpu_usage,
@@ -118,7 +311,8 @@ impl ResourceMonitor {
             disk_usage,
         };
 
-        // Cache the stats
+        // Cache the stats and record them for the sparklines
+        self.push_sample(&stats);
         self.cached_stats = Some(stats.clone());
         stats
     }
@@ -324,4 +518,128 @@ mod tests {
         assert_eq!(cloned.name, "disk0");
         assert_eq!(cloned.percent, 20.0);
     }
+
+    /// Fixed process table standing in for the live system
+    struct MockProcesses(Vec<ProcessSample>);
+
+    impl ProcessSource for MockProcesses {
+        fn processes(&self) -> Vec<ProcessSample> {
+            self.0.clone()
+        }
+    }
+
+    fn sample(pid: u32, parent: Option<u32>, cpu_usage: f32, memory: u64) -> ProcessSample {
+        ProcessSample {
+            pid,
+            parent,
+            cpu_usage,
+            memory,
+        }
+    }
+
+    #[test]
+    fn test_process_tree_sums_descendants() {
+        let source = MockProcesses(vec![
+            sample(1, None, 1.0, 1024),
+            sample(100, Some(1), 2.5, 4 * 1024 * 1024),
+            sample(101, Some(100), 10.0, 1024 * 1024),
+            sample(102, Some(101), 0.5, 1024 * 1024),
+            sample(200, Some(1), 50.0, 1024),
+        ]);
+
+        let stats = ProcessTree::new(&source).stats(100).unwrap();
+        assert_eq!(stats.cpu_usage, 13.0);
+        assert_eq!(stats.memory, 6 * 1024 * 1024);
+        assert_eq!(stats.to_string(), "13.0% 6.00 MB");
+
+        // A leaf only counts itself
+        assert_eq!(ProcessTree::new(&source).stats(200).unwrap().memory, 1024);
+    }
+
+    #[test]
+    fn test_exited_process_has_no_stats() {
+        let source = MockProcesses(vec![sample(1, None, 0.0, 0)]);
+
+        let stats = ProcessTree::new(&source).stats(4242);
+        assert_eq!(stats, None);
+        assert_eq!(ProcessStats::label(stats), "\u{2014}");
+        assert_eq!(
+            ProcessStats::label(Some(ProcessStats {
+                cpu_usage: 0.0,
+                memory: 512,
+            })),
+            "0.0% 512.00 B"
+        );
+    }
+
+    #[test]
+    fn test_process_stats_for_own_process() {
+        let mut monitor = ResourceMonitor::new();
+        let stats = monitor.process_stats(std::process::id()).unwrap();
+        assert!(stats.memory > 0);
+
+        // Later calls within the interval reuse the same snapshot
+        let taken = monitor.process_tree.as_ref().unwrap().0;
+        assert!(monitor.process_stats(std::process::id()).is_some());
+        assert_eq!(monitor.process_tree.as_ref().unwrap().0, taken);
+    }
+
+    fn stats_with(cpu_usage: f32, memory_percent: f32) -> ResourceStats {
+        ResourceStats {
+            cpu_usage,
+            cpu_count: 1,
+            memory_used: 0,
+            memory_total: 1,
+            memory_percent,
+            process_count: 0,
+            network_rx: 0,
+            network_tx: 0,
+            disk_usage: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sparkline_maps_samples_to_bars() {
+        let mut monitor = ResourceMonitor::new();
+        for value in [0.0, 14.3, 28.6, 42.9, 57.1, 71.4, 85.7, 100.0] {
+            monitor.push_sample(&stats_with(value, 100.0 - value));
+        }
+
+        assert_eq!(
+            monitor.sparkline(Metric::Cpu),
+            "\u{2581}\u{2582}\u{2583}\u{2584}\u{2585}\u{2586}\u{2587}\u{2588}"
+        );
+        assert_eq!(
+            monitor.sparkline(Metric::Memory),
+            "\u{2588}\u{2587}\u{2586}\u{2585}\u{2584}\u{2583}\u{2582}\u{2581}"
+        );
+
+        // Out-of-range values are clamped
+        monitor.push_sample(&stats_with(250.0, -5.0));
+        assert!(monitor.sparkline(Metric::Cpu).ends_with('\u{2588}'));
+        assert!(monitor.sparkline(Metric::Memory).ends_with('\u{2581}'));
+    }
+
+    #[test]
+    fn test_sparkline_keeps_recent_history() {
+        let mut monitor = ResourceMonitor::new();
+        assert_eq!(monitor.sparkline(Metric::Cpu), "");
+
+        for i in 0..HISTORY_LEN + 5 {
+            let value = if i < 5 { 100.0 } else { 0.0 };
+            monitor.push_sample(&stats_with(value, value));
+        }
+        let line = monitor.sparkline(Metric::Cpu);
+        assert_eq!(line.chars().count(), HISTORY_LEN);
+        // The full-CPU samples were the oldest and have been dropped
+        assert!(line.chars().all(|bar| bar == '\u{2581}'));
+    }
+
+    #[test]
+    fn test_cached_stats_add_no_samples() {
+        let mut monitor = ResourceMonitor::new();
+        let _ = monitor.get_stats();
+        let _ = monitor.get_stats();
+        assert_eq!(monitor.sparkline(Metric::Cpu).chars().count(), 1);
+    }
 }
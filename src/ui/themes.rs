@@ -1,11 +1,14 @@
+use crate::colors::{TrueColor, TrueColorPalette};
 use anyhow::{Context, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
 
 /// Advanced theme system supporting multiple color schemes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Theme {
     pub name: String,
     pub colors: ColorPalette,
@@ -13,7 +16,7 @@ pub struct Theme {
     pub syntax: SyntaxColors,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColorPalette {
     // ANSI colors
     pub black: String,
@@ -36,7 +39,7 @@ pub struct ColorPalette {
     pub bright_white: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UiColors {
     pub foreground: String,
     pub background: String,
@@ -49,7 +52,7 @@ pub struct UiColors {
     pub command_palette: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SyntaxColors {
     pub keyword: String,
     pub string: String,
@@ -60,6 +63,66 @@ pub struct SyntaxColors {
     pub warning: String,
 }
 
+impl Theme {
+    /// Contrast ratio of the text on the background, if it falls short of WCAG AA
+    ///
+    /// `None` when the text is readable or either color can't be parsed.
+    #[must_use]
+    pub fn low_contrast(&self) -> Option<f32> {
+        let fg = TrueColor::from_css(&self.ui.foreground).ok()?;
+        let bg = TrueColor::from_css(&self.ui.background).ok()?;
+        (!fg.meets_wcag_aa(&bg, false)).then(|| fg.contrast_ratio(&bg))
+    }
+}
+
+impl From<&TrueColorPalette> for ColorPalette {
+    fn from(palette: &TrueColorPalette) -> Self {
+        Self {
+            black: palette.black.to_hex(),
+            red: palette.red.to_hex(),
+            green: palette.green.to_hex(),
+            yellow: palette.yellow.to_hex(),
+            blue: palette.blue.to_hex(),
+            magenta: palette.magenta.to_hex(),
+            cyan: palette.cyan.to_hex(),
+            white: palette.white.to_hex(),
+            bright_black: palette.bright_black.to_hex(),
+            bright_red: palette.bright_red.to_hex(),
+            bright_green: palette.bright_green.to_hex(),
+            bright_yellow: palette.bright_yellow.to_hex(),
+            bright_blue: palette.bright_blue.to_hex(),
+            bright_magenta: palette.bright_magenta.to_hex(),
+            bright_cyan: palette.bright_cyan.to_hex(),
+            bright_white: palette.bright_white.to_hex(),
+        }
+    }
+}
+
+impl ColorPalette {
+    /// The same colors in the form the terminal's ANSI palette is built from
+    #[must_use]
+    pub fn to_ansi_colors(&self) -> crate::config::AnsiColors {
+        crate::config::AnsiColors {
+            black: self.black.clone(),
+            red: self.red.clone(),
+            green: self.green.clone(),
+            yellow: self.yellow.clone(),
+            blue: self.blue.clone(),
+            magenta: self.magenta.clone(),
+            cyan: self.cyan.clone(),
+            white: self.white.clone(),
+            bright_black: self.bright_black.clone(),
+            bright_red: self.bright_red.clone(),
+            bright_green: self.bright_green.clone(),
+            bright_yellow: self.bright_yellow.clone(),
+            bright_blue: self.bright_blue.clone(),
+            bright_magenta: self.bright_magenta.clone(),
+            bright_cyan: self.bright_cyan.clone(),
+            bright_white: self.bright_white.clone(),
+        }
+    }
+}
+
 /// Built-in themes
 pub struct Themes;
 
@@ -110,29 +173,12 @@ impl Themes {
         }
     }
 
-    /// Light theme
+    /// Light theme, with ANSI colors that stay readable on white
     #[must_use]
     pub fn light() -> Theme {
         Theme {
             name: "Light".to_string(),
-            colors: ColorPalette {
-                black: "#000000".to_string(),
-                red: "#D70000".to_string(),
-                green: "#008700".to_string(),
-                yellow: "#AF8700".to_string(),
-                blue: "#0087FF".to_string(),
-                magenta: "#AF00DB".to_string(),
-                cyan: "#00AFAF".to_string(),
-                white: "#5F5F5F".to_string(),
-                bright_black: "#767676".to_string(),
-                bright_red: "#D75F00".to_string(),
-                bright_green: "#00AF00".to_string(),
-                bright_yellow: "#FFAF00".to_string(),
-                bright_blue: "#5FAFFF".to_string(),
-                bright_magenta: "#D787D7".to_string(),
-                bright_cyan: "#00DFFF".to_string(),
-                bright_white: "#FFFFFF".to_string(),
-            },
+            colors: ColorPalette::from(&TrueColorPalette::default_light()),
             ui: UiColors {
                 foreground: "#000000".to_string(),
                 background: "#FFFFFF".to_string(),
@@ -202,6 +248,35 @@ impl Themes {
         }
     }
 
+    /// High-contrast theme: bright colors on pure black
+    #[must_use]
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "High Contrast".to_string(),
+            colors: ColorPalette::from(&TrueColorPalette::default_high_contrast()),
+            ui: UiColors {
+                foreground: "#FFFFFF".to_string(),
+                background: "#000000".to_string(),
+                cursor: "#FFFF00".to_string(),
+                selection: "#0050A0".to_string(),
+                border: "#FFFFFF".to_string(),
+                tab_active: "#FFFF00".to_string(),
+                tab_inactive: "#3A3A3A".to_string(),
+                status_bar: "#000000".to_string(),
+                command_palette: "#000000".to_string(),
+            },
+            syntax: SyntaxColors {
+                keyword: "#6CB6FF".to_string(),
+                string: "#90FF90".to_string(),
+                comment: "#A8A8A8".to_string(),
+                function: "#FFFF00".to_string(),
+                variable: "#FFFFFF".to_string(),
+                error: "#FF6B6B".to_string(),
+                warning: "#FFFF90".to_string(),
+            },
+        }
+    }
+
     /// Get all built-in themes
     #[must_use]
     pub fn all() -> HashMap<String, Theme> {
@@ -209,6 +284,8 @@ impl Themes {
         themes.insert("dark".to_string(), Self::dark());
         themes.insert("light".to_string(), Self::light());
         themes.insert("nord".to_string(), Self::nord());
+        let high_contrast = Self::high_contrast();
+        themes.insert(high_contrast.name.to_lowercase(), high_contrast);
         themes
     }
 }
@@ -219,6 +296,15 @@ impl Default for Theme {
     }
 }
 
+/// Outcome of [`ThemeManager::reload`]
+#[derive(Debug, Default)]
+pub struct ThemeReload {
+    /// Whether the active theme was replaced by a changed file
+    pub current_changed: bool,
+    /// Theme files that failed to load and why; their previous themes are kept
+    pub errors: Vec<(PathBuf, String)>,
+}
+
 /// Theme manager for dynamic theme loading and switching at runtime
 #[derive(Debug)]
 pub struct ThemeManager {
@@ -228,6 +314,14 @@ pub struct ThemeManager {
     available_themes: HashMap<String, Theme>,
     /// Path to custom themes directory
     themes_dir: Option<PathBuf>,
+    /// File watcher on `themes_dir` and the events it reports, once `watch` is called
+    watcher: Option<(RecommendedWatcher, Receiver<notify::Result<Event>>)>,
+}
+
+/// Whether `path` names a theme file
+fn is_theme_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
 }
 
 impl ThemeManager {
@@ -241,6 +335,7 @@ impl ThemeManager {
             current_theme,
             available_themes,
             themes_dir: None,
+            watcher: None,
         }
     }
 
@@ -265,38 +360,88 @@ impl ThemeManager {
 
     /// Load custom themes from the themes directory
     fn load_custom_themes(&mut self) -> Result<()> {
-        let Some(ref themes_dir) = self.themes_dir else {
-            return Ok(());
+        for (path, e) in self.reload()?.errors {
+            // Log warning but continue loading other themes
+            eprintln!("Warning: Failed to load theme from {}: {e}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Re-read every theme file in the themes directory
+    ///
+    /// Each theme that parses replaces its previous version, including the
+    /// active theme. A file that fails to parse keeps its last good theme and
+    /// is listed in [`ThemeReload::errors`].
+    ///
+    /// # Errors
+    /// Returns an error if the themes directory cannot be read
+    pub fn reload(&mut self) -> Result<ThemeReload> {
+        let mut report = ThemeReload::default();
+        let Some(themes_dir) = self.themes_dir.clone() else {
+            return Ok(report);
         };
 
         if !themes_dir.exists() {
-            return Ok(());
+            return Ok(report);
         }
 
-        for entry in fs::read_dir(themes_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Only process .yaml or .yml files
-            if let Some(ext) = path.extension() {
-                if ext == "yaml" || ext == "yml" {
-                    match Self::load_theme_from_file(&path) {
-                        Ok(theme) => {
-                            let name = theme.name.to_lowercase();
-                            self.available_themes.insert(name, theme);
-                        }
-                        Err(e) => {
-                            // Log warning but continue loading other themes
-                            eprintln!("Warning: Failed to load theme from {}: {e}", path.display());
-                        }
+        for entry in fs::read_dir(&themes_dir)? {
+            let path = entry?.path();
+            if !is_theme_file(&path) {
+                continue;
+            }
+            match Self::load_theme_from_file(&path) {
+                Ok(theme) => {
+                    let name = theme.name.to_lowercase();
+                    if name == self.current_theme.name.to_lowercase() && theme != self.current_theme
+                    {
+                        self.current_theme = theme.clone();
+                        report.current_changed = true;
                     }
+                    self.available_themes.insert(name, theme);
                 }
+                Err(e) => report.errors.push((path, format!("{e:#}"))),
             }
         }
 
+        Ok(report)
+    }
+
+    /// Watch the themes directory so [`Self::poll_changes`] notices edited files
+    ///
+    /// # Errors
+    /// Returns an error if no themes directory is configured or it cannot be watched
+    pub fn watch(&mut self) -> Result<()> {
+        let themes_dir = self
+            .themes_dir
+            .as_ref()
+            .context("Themes directory not configured")?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).context("Failed to create file watcher")?;
+        watcher
+            .watch(themes_dir, RecursiveMode::NonRecursive)
+            .context("Failed to watch themes directory")?;
+        self.watcher = Some((watcher, rx));
         Ok(())
     }
 
+    /// Reload the themes if a theme file changed since the last call
+    ///
+    /// Returns `None` when nothing changed or the directory isn't being watched.
+    pub fn poll_changes(&mut self) -> Option<Result<ThemeReload>> {
+        let (_, events) = self.watcher.as_ref()?;
+        let mut changed = false;
+        // Drain everything so a burst of writes triggers one reload
+        while let Ok(event) = events.try_recv() {
+            changed |= event.is_ok_and(|event| {
+                !event.kind.is_access() && event.paths.iter().any(|path| is_theme_file(path))
+            });
+        }
+        changed.then(|| self.reload())
+    }
+
     /// Load a theme from a YAML file
     fn load_theme_from_file<P: AsRef<Path>>(path: P) -> Result<Theme> {
         let contents = fs::read_to_string(path.as_ref()).context("Failed to read theme file")?;
@@ -379,6 +524,43 @@ impl ThemeManager {
         self.available_themes.insert(name, theme);
     }
 
+    /// Write the active theme to `path` in the format the themes directory is loaded from
+    ///
+    /// Every color is written as `#RRGGBB`, whichever CSS form it was given
+    /// in, so the file loads back as the same theme.
+    ///
+    /// # Errors
+    /// Returns an error if a color is invalid or the file cannot be written
+    #[allow(dead_code)] // Public API for future use
+    pub fn export_current<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut theme =
+            serde_yaml::to_value(&self.current_theme).context("Failed to serialize theme")?;
+        for section in ["colors", "ui", "syntax"] {
+            let Some(colors) = theme
+                .get_mut(section)
+                .and_then(serde_yaml::Value::as_mapping_mut)
+            else {
+                continue;
+            };
+            for (key, color) in colors.iter_mut() {
+                let Some(css) = color.as_str() else {
+                    continue;
+                };
+                let hex = TrueColor::from_css(css)
+                    .with_context(|| {
+                        let key = key.as_str().unwrap_or_default();
+                        format!("Invalid color {section}.{key} in theme")
+                    })?
+                    .to_hex();
+                *color = serde_yaml::Value::String(hex);
+            }
+        }
+
+        let contents = serde_yaml::to_string(&theme).context("Failed to serialize theme")?;
+        fs::write(path.as_ref(), contents).context("Failed to write theme file")?;
+        Ok(())
+    }
+
     /// Save a theme to the custom themes directory
     ///
     /// # Errors
@@ -487,6 +669,40 @@ mod tests {
         assert!(!theme.ui.background.is_empty());
     }
 
+    #[test]
+    fn test_high_contrast_theme() {
+        let theme = Themes::high_contrast();
+        assert_eq!(theme.name, "High Contrast");
+        assert_eq!(
+            theme.colors,
+            ColorPalette::from(&TrueColorPalette::default_high_contrast())
+        );
+
+        let mut manager = ThemeManager::new();
+        assert!(manager.switch_theme("high contrast"));
+        manager.prev_theme();
+        assert_eq!(manager.current().name, "Dark");
+        manager.next_theme();
+        manager.next_theme();
+        assert_eq!(manager.current().name, "Light");
+    }
+
+    #[test]
+    fn test_builtin_foreground_meets_wcag_aa() {
+        for theme in Themes::all().values() {
+            let fg = TrueColor::from_css(&theme.ui.foreground).unwrap();
+            let bg = TrueColor::from_css(&theme.ui.background).unwrap();
+            assert!(fg.meets_wcag_aa(&bg, false), "{}", theme.name);
+            assert_eq!(theme.low_contrast(), None, "{}", theme.name);
+        }
+
+        let mut gray = Themes::dark();
+        gray.ui.foreground = "#777777".to_string();
+        gray.ui.background = "#999999".to_string();
+        let ratio = gray.low_contrast().unwrap();
+        assert!((1.0..4.5).contains(&ratio), "{ratio}");
+    }
+
     #[test]
     fn test_nord_theme() {
         let theme = Themes::nord();
@@ -591,4 +807,126 @@ mod tests {
             assert!(path.to_string_lossy().contains("themes"));
         }
     }
+
+    fn write_theme(dir: &Path, file: &str, theme: &Theme) {
+        fs::write(dir.join(file), serde_yaml::to_string(theme).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_reload_picks_up_changed_theme_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut theme = Themes::nord();
+        theme.name = "Custom".to_string();
+        write_theme(dir.path(), "custom.yaml", &theme);
+
+        let mut manager = ThemeManager::with_themes_dir(dir.path()).unwrap();
+        assert!(manager.switch_theme("custom"));
+
+        theme.ui.background = "#101010".to_string();
+        write_theme(dir.path(), "custom.yaml", &theme);
+        let report = manager.reload().unwrap();
+        assert!(report.current_changed);
+        assert!(report.errors.is_empty());
+        assert_eq!(manager.current().ui.background, "#101010");
+
+        // Reloading unchanged files leaves the active theme alone
+        assert!(!manager.reload().unwrap().current_changed);
+    }
+
+    #[test]
+    fn test_reload_keeps_last_good_theme_on_parse_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut theme = Themes::light();
+        theme.name = "Custom".to_string();
+        write_theme(dir.path(), "custom.yml", &theme);
+
+        let mut manager = ThemeManager::with_themes_dir(dir.path()).unwrap();
+        assert!(manager.switch_theme("custom"));
+
+        fs::write(dir.path().join("custom.yml"), "name: Custom\ncolors: [").unwrap();
+        let report = manager.reload().unwrap();
+        assert!(!report.current_changed);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, dir.path().join("custom.yml"));
+        assert_eq!(manager.current(), &theme);
+        assert!(manager.switch_theme("light"));
+        assert!(manager.switch_theme("custom"));
+        assert_eq!(manager.current(), &theme);
+    }
+
+    #[test]
+    fn test_reload_ignores_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+        let mut manager = ThemeManager::with_themes_dir(dir.path()).unwrap();
+        let report = manager.reload().unwrap();
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            manager.available_theme_names(),
+            vec!["dark", "high contrast", "light", "nord"]
+        );
+    }
+
+    #[test]
+    fn test_poll_changes_needs_watch() {
+        let mut manager = ThemeManager::new();
+        assert!(manager.poll_changes().is_none());
+        assert!(manager.watch().is_err());
+    }
+
+    #[test]
+    fn test_export_current_round_trips() {
+        let themes_dir = tempfile::tempdir().unwrap();
+        let mut theme = Themes::nord();
+        theme.name = "Custom".to_string();
+        write_theme(themes_dir.path(), "custom.yaml", &theme);
+        let mut manager = ThemeManager::with_themes_dir(themes_dir.path()).unwrap();
+        assert!(manager.switch_theme("custom"));
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let export = export_dir.path().join("snapshot.yaml");
+        manager.export_current(&export).unwrap();
+
+        let exported = ThemeManager::load_theme_from_file(&export).unwrap();
+        assert_eq!(&exported, manager.current());
+
+        // The export loads through the themes directory like any other theme
+        let mut reloaded = ThemeManager::with_themes_dir(export_dir.path()).unwrap();
+        assert!(reloaded.switch_theme("custom"));
+        assert_eq!(reloaded.current(), &theme);
+    }
+
+    #[test]
+    fn test_export_current_writes_hex_colors() {
+        let mut theme = Themes::dark();
+        theme.name = "Css".to_string();
+        theme.colors.red = "rgb(255, 0, 0)".to_string();
+        theme.ui.background = "hsl(0, 0%, 100%)".to_string();
+        theme.ui.cursor = "#abcdef".to_string();
+        let mut manager = ThemeManager::new();
+        manager.add_theme(theme);
+        assert!(manager.switch_theme("css"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let export = dir.path().join("css.yaml");
+        manager.export_current(&export).unwrap();
+
+        let exported = ThemeManager::load_theme_from_file(&export).unwrap();
+        assert_eq!(exported.colors.red, "#FF0000");
+        assert_eq!(exported.ui.background, "#FFFFFF");
+        assert_eq!(exported.ui.cursor, "#ABCDEF");
+        assert_eq!(exported.colors.green, Themes::dark().colors.green);
+
+        manager.add_theme(Theme {
+            name: "Css".to_string(),
+            ui: UiColors {
+                border: "not a color".to_string(),
+                ..Themes::dark().ui
+            },
+            ..Themes::dark()
+        });
+        assert!(manager.switch_theme("css"));
+        let err = manager.export_current(&export).unwrap_err();
+        assert!(err.to_string().contains("ui.border"));
+    }
 }
@@ -391,11 +391,13 @@ fn test_session_manager_operations() {
                     output: "tab1 output".to_string(),
                     working_dir: Some("/tmp".to_string()),
                     active: true,
+                    scroll_offset: 0,
                 },
                 TabState {
                     output: "tab2 output".to_string(),
                     working_dir: None,
                     active: false,
+                    scroll_offset: 0,
                 },
             ],
         };
@@ -419,6 +421,7 @@ fn test_session_with_many_tabs() {
             output: format!("Output {}", i),
             working_dir: Some(format!("/dir{}", i)),
             active: i == 0,
+            scroll_offset: 0,
         });
     }
     
@@ -0,0 +1,66 @@
+//! Allocation counts for parsing a large buffer, owned lines vs borrowed lines
+//!
+//! Lives in its own test binary so the counting allocator sees nothing else.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use furnace::colors::TrueColorPalette;
+use furnace::terminal::ansi_parser::AnsiParser;
+
+/// System allocator that counts allocations
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Number of allocations made while running `f`
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (ALLOCATIONS.load(Ordering::Relaxed) - before, result)
+}
+
+/// About 1MB of colored shell output, several styled spans per line
+fn colored_output() -> String {
+    let line = "\x1b[1;32muser@host\x1b[0m:\x1b[34m~/src\x1b[0m$ ls -la \x1b[33mtarget\x1b[0m\r\n";
+    line.repeat(1024 * 1024 / line.len())
+}
+
+#[test]
+fn test_borrowed_parse_allocates_less_on_1mb_buffer() {
+    let text = colored_output();
+    assert!(text.len() >= 1000 * 1024);
+    let palette = TrueColorPalette::default_dark();
+
+    let (owned_allocs, (owned, _)) =
+        count_allocations(|| AnsiParser::parse_with_hyperlinks(&text, &palette));
+    let (borrowed_allocs, (borrowed, _)) =
+        count_allocations(|| AnsiParser::parse_borrowed(&text, &palette));
+
+    // Same output either way
+    assert_eq!(owned, borrowed);
+    // Borrowed spans skip a String per span, leaving roughly one allocation per line
+    assert!(
+        borrowed_allocs * 2 < owned_allocs,
+        "borrowed parse made {borrowed_allocs} allocations, owned made {owned_allocs}"
+    );
+}
@@ -130,6 +130,7 @@ fn test_session_structures() {
         output: "output".into(),
         working_dir: Some("/tmp".into()),
         active: true,
+        scroll_offset: 0,
     };
     assert!(tab.active);
     
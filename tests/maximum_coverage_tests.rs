@@ -41,6 +41,26 @@ fn test_config_keybindings_structure() {
         paste: "Ctrl+V".to_string(),
         search: "Ctrl+F".to_string(),
         clear: "Ctrl+L".to_string(),
+        save_session: "Ctrl+S".to_string(),
+        load_session: "Ctrl+Shift+L".to_string(),
+        next_theme: "Ctrl+]".to_string(),
+        prev_theme: "Ctrl+[".to_string(),
+        toggle_resource_monitor: "Ctrl+R".to_string(),
+        toggle_autocomplete: "Alt+Tab".to_string(),
+        show_keybindings: "Ctrl+Shift+K".to_string(),
+        command_palette: "Ctrl+Shift+P".to_string(),
+        interrupt: "Ctrl+Shift+X".to_string(),
+        restart_shell: "Ctrl+Shift+R".to_string(),
+        prev_command: "Ctrl+Shift+Up".to_string(),
+        next_command: "Ctrl+Shift+Down".to_string(),
+        copy_rich: "Ctrl+Alt+C".to_string(),
+        grow_pane: "Ctrl+Shift+Right".to_string(),
+        shrink_pane: "Ctrl+Shift+Left".to_string(),
+        scroll_to_top: "Shift+Home".to_string(),
+        scroll_to_bottom: "Shift+End".to_string(),
+        toggle_stats_overlay: "Ctrl+Shift+S".to_string(),
+        custom: std::collections::HashMap::new(),
+        chord_timeout_ms: 1000,
     };
     
     assert_eq!(kb.new_tab, "Ctrl+T");
@@ -58,9 +78,11 @@ fn test_config_hooks_structure() {
         on_output: Some("output.lua".to_string()),
         on_bell: Some("bell.lua".to_string()),
         on_title_change: Some("title.lua".to_string()),
+        on_resize: Some("resize.lua".to_string()),
         custom_keybindings: HashMap::new(),
         output_filters: vec!["filter1.lua".to_string(), "filter2.lua".to_string()],
         custom_widgets: vec!["widget1.lua".to_string()],
+        timeout_ms: 1000,
     };
     
     assert_eq!(hooks.on_startup, Some("startup_script.lua".to_string()));
@@ -0,0 +1,161 @@
+//! Tests for loading native plugins, using the example plugins
+//!
+//! `cargo test` builds examples, so the plugin libraries sit next to the test binary.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use furnace::plugins::PluginManager;
+
+/// Path of the library built from `examples/plugins/<name>.rs`
+fn example_library(name: &str) -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    // target/<profile>/deps/<test binary> -> target/<profile>/examples
+    let examples = exe.parent().unwrap().parent().unwrap().join("examples");
+    examples.join(format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        name,
+        std::env::consts::DLL_SUFFIX
+    ))
+}
+
+fn example_plugin() -> PathBuf {
+    example_library("hello_world")
+}
+
+/// Read one of the counters an example plugin exports
+fn counter(library: &libloading::Library, symbol: &[u8]) -> usize {
+    unsafe {
+        let counter: libloading::Symbol<*const AtomicUsize> = library.get(symbol).unwrap();
+        (**counter).load(Ordering::SeqCst)
+    }
+}
+
+/// Instances the example plugin has destroyed
+fn destroyed_count(library: &libloading::Library) -> usize {
+    counter(library, b"HELLO_WORLD_DESTROYED\0")
+}
+
+#[test]
+fn test_plugin_handles_commands() {
+    let mut manager = PluginManager::new();
+    let name = manager.load_plugin(example_plugin()).unwrap();
+    assert_eq!(name, "hello_world");
+    assert_eq!(manager.plugin_names(), vec!["hello_world"]);
+
+    assert_eq!(
+        manager.handle_command("hello").as_deref(),
+        Some("Hello, world!")
+    );
+    assert_eq!(
+        manager.handle_command("hello furnace").as_deref(),
+        Some("Hello, furnace!")
+    );
+    // Commands no plugin knows are left for the shell
+    assert_eq!(manager.handle_command("ls -la"), None);
+    assert_eq!(manager.handle_command("hel\0lo"), None);
+
+    // The plugin's name doubles as its command namespace
+    assert_eq!(
+        manager.handle_command("hello_world:hello furnace").as_deref(),
+        Some("Hello, furnace!")
+    );
+}
+
+#[test]
+fn test_unloading_destroys_plugin() {
+    // Keeps the library mapped so its counter can be read after the manager lets go
+    let library = unsafe { libloading::Library::new(example_plugin()) }.unwrap();
+    let before = destroyed_count(&library);
+
+    let mut manager = PluginManager::new();
+    manager.load_plugin(example_plugin()).unwrap();
+    assert!(manager.unload_plugin("hello_world"));
+    assert!(!manager.unload_plugin("hello_world"));
+    assert!(manager.is_empty());
+    assert_eq!(manager.handle_command("hello"), None);
+
+    // Other tests may destroy instances concurrently, so only a lower bound holds
+    assert!(destroyed_count(&library) > before);
+}
+
+#[test]
+fn test_load_directory_skips_broken_plugins() {
+    let dir = tempfile::tempdir().unwrap();
+    let suffix = std::env::consts::DLL_SUFFIX;
+    std::fs::copy(example_plugin(), dir.path().join(format!("good{}", suffix))).unwrap();
+    std::fs::write(
+        dir.path().join(format!("broken{}", suffix)),
+        b"not a library",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+    let mut manager = PluginManager::new();
+    let failures = manager.load_directory(dir.path()).unwrap();
+
+    assert_eq!(failures.len(), 1, "{:?}", failures);
+    assert!(failures[0].0.ends_with(format!("broken{}", suffix)));
+    // `_plugin_name` wins over the file name
+    assert_eq!(manager.plugin_names(), vec!["hello_world"]);
+    assert_eq!(
+        manager.handle_command("hello").as_deref(),
+        Some("Hello, world!")
+    );
+
+    assert!(manager.load_directory(dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_duplicate_plugin_name_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let suffix = std::env::consts::DLL_SUFFIX;
+    std::fs::copy(example_plugin(), dir.path().join(format!("first{}", suffix))).unwrap();
+
+    let mut manager = PluginManager::new();
+    manager.load_plugin(example_plugin()).unwrap();
+    let err = manager
+        .load_plugin(dir.path().join(format!("first{}", suffix)))
+        .unwrap_err();
+    assert!(err.to_string().contains("already loaded"), "{}", err);
+    assert_eq!(manager.plugin_names(), vec!["hello_world"]);
+    assert!(manager.command_conflicts().is_empty());
+}
+
+#[test]
+fn test_incompatible_abi_is_refused() {
+    let path = example_library("future_abi");
+    let library = unsafe { libloading::Library::new(&path) }.unwrap();
+
+    let mut manager = PluginManager::new();
+    let err = manager.load_plugin(&path).unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("Refusing to load plugin"), "{}", message);
+    assert!(message.contains("plugin ABI 2.0"), "{}", message);
+
+    // Rejected before the plugin could run any code
+    assert_eq!(counter(&library, b"FUTURE_ABI_CREATED\0"), 0);
+    assert!(manager.is_empty());
+}
+
+#[test]
+fn test_panicking_handler_still_destroyed_on_unload() {
+    let library = unsafe { libloading::Library::new(example_plugin()) }.unwrap();
+    let before = destroyed_count(&library);
+
+    let mut manager = PluginManager::new();
+    manager.load_plugin(example_plugin()).unwrap();
+
+    // The plugin catches the panic at its boundary and reports "not handled"
+    assert_eq!(manager.handle_command("hello --panic"), None);
+    // `_plugin_init` ran once: the greeting it sets is there, and a second
+    // init would have failed the load
+    assert_eq!(
+        manager.handle_command("hello").as_deref(),
+        Some("Hello, world!")
+    );
+
+    drop(manager);
+    assert!(destroyed_count(&library) > before);
+}
@@ -133,6 +133,7 @@ fn test_shell_integration_structure() {
         command_tracking: true,
         current_dir: Some("/home/user".to_string()),
         last_command: Some("ls -la".to_string()),
+        last_exit_code: Some(0),
     };
     
     assert!(si.osc_sequences);